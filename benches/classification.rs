@@ -0,0 +1,176 @@
+//! Criterion benchmarks for classification and clustering hot paths
+//!
+//! Run with `cargo bench`. Each run is compared with the previous one (or with
+//! `cargo bench --bench classification -- --baseline <name>` after saving one with
+//! `--save-baseline <name>`), and the run fails if any benchmark's mean time
+//! regressed by more than `REGRESSION_THRESHOLD`, so CI can catch performance
+//! regressions.
+
+// Shared helpers are only partially used by this benchmark binary, and their
+// tests are not run by it
+#[allow(dead_code, unused_imports)]
+#[path = "../tests/performance/mod.rs"]
+mod performance;
+
+use criterion::{black_box, criterion_group, BatchSize, Criterion};
+use gmail_automation::classifier::EmailClassifier;
+use gmail_automation::client::{GmailFixture, MockGmailClient};
+use gmail_automation::filter_manager::FilterManager;
+use gmail_automation::interactive::create_clusters;
+use gmail_automation::models::{FilterRule, LabelName, DEFAULT_FILTER_PRIORITY};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use performance::{generate_mixed_workload, generate_newsletter_emails, generate_random_emails};
+
+/// Largest accepted slowdown of a benchmark's mean time against the baseline
+const REGRESSION_THRESHOLD: f64 = 0.10;
+
+const BENCHMARKS: [&str; 4] = [
+    "classify_10k_messages",
+    "generate_filters_50k",
+    "create_clusters_100k",
+    "deduplicate_1k_filters",
+];
+
+fn filter_manager() -> FilterManager {
+    FilterManager::new(Box::new(MockGmailClient::new(GmailFixture::default())))
+}
+
+fn classify_10k_messages(c: &mut Criterion) {
+    let messages = generate_random_emails(10_000);
+    let classifier = EmailClassifier::new("auto".to_string());
+
+    c.bench_function("classify_10k_messages", |b| {
+        b.iter(|| {
+            for message in &messages {
+                black_box(classifier.classify(message).unwrap());
+            }
+        })
+    });
+}
+
+fn generate_filters_50k(c: &mut Criterion) {
+    let messages = generate_mixed_workload(50_000);
+    let manager = filter_manager();
+
+    c.bench_function("generate_filters_50k", |b| {
+        b.iter(|| black_box(manager.generate_filters(&messages, 5)))
+    });
+}
+
+fn create_clusters_100k(c: &mut Criterion) {
+    let messages = generate_newsletter_emails(100_000);
+    let classifier = EmailClassifier::new("auto".to_string());
+    let classifications: Vec<_> = messages
+        .iter()
+        .map(|m| (m.clone(), classifier.classify(m).unwrap()))
+        .collect();
+
+    c.bench_function("create_clusters_100k", |b| {
+        b.iter(|| black_box(create_clusters(&messages, &classifications, 5, &[])))
+    });
+}
+
+/// 1,000 filters over 200 domains, where most domains get the same criteria several
+/// times (sometimes with a different label) and some get subject-narrowed variants
+fn overlapping_filters() -> Vec<FilterRule> {
+    (0..1_000)
+        .map(|i| {
+            let domain = format!("domain{}.example", i % 200);
+            let subject_keywords = match i % 5 {
+                0 => vec![format!("digest {}", i % 3)],
+                1 => vec!["receipt".to_string(), "invoice".to_string()],
+                _ => vec![],
+            };
+            FilterRule {
+                id: None,
+                name: format!("{} #{}", domain, i),
+                from_pattern: Some(format!("*@{}", domain)),
+                is_specific_sender: false,
+                excluded_senders: vec![],
+                subject_keywords,
+                target_label: LabelName::new(format!("AutoManaged/Label{}", i % 7)),
+                target_label_id: None,
+                should_archive: i % 2 == 0,
+                estimated_matches: i,
+                priority: DEFAULT_FILTER_PRIORITY,
+                extra_terms: vec![],
+                additional_labels: vec![],
+                additional_label_ids: vec![],
+            }
+        })
+        .collect()
+}
+
+fn deduplicate_1k_filters(c: &mut Criterion) {
+    let filters = overlapping_filters();
+    let manager = filter_manager();
+
+    c.bench_function("deduplicate_1k_filters", |b| {
+        b.iter_batched(
+            || filters.clone(),
+            |filters| black_box(manager.deduplicate_filters(filters)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// Benchmarks whose mean time changed by more than `REGRESSION_THRESHOLD` in this run
+///
+/// Criterion writes `change/estimates.json` when it compares a benchmark with a
+/// baseline; files older than `started` are left over from earlier runs.
+fn regressions(started: SystemTime) -> Vec<(&'static str, f64)> {
+    let criterion_dir = std::env::var_os("CRITERION_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            std::env::var_os("CARGO_TARGET_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("target"))
+                .join("criterion")
+        });
+
+    BENCHMARKS
+        .iter()
+        .filter_map(|&name| {
+            let path = criterion_dir.join(name).join("change/estimates.json");
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            if modified < started {
+                return None;
+            }
+            let json = std::fs::read_to_string(&path).ok()?;
+            let estimates: serde_json::Value = serde_json::from_str(&json).ok()?;
+            let change = estimates["mean"]["point_estimate"].as_f64()?;
+            (change > REGRESSION_THRESHOLD).then_some((name, change))
+        })
+        .collect()
+}
+
+criterion_group! {
+    name = benches;
+    // Several benchmarks take tens of milliseconds per iteration
+    config = Criterion::default().sample_size(10);
+    targets = classify_10k_messages,
+        generate_filters_50k,
+        create_clusters_100k,
+        deduplicate_1k_filters
+}
+
+fn main() {
+    let started = SystemTime::now();
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+
+    let regressions = regressions(started);
+    if !regressions.is_empty() {
+        for (name, change) in &regressions {
+            eprintln!(
+                "{} regressed by {:.1}% (threshold {:.0}%)",
+                name,
+                change * 100.0,
+                REGRESSION_THRESHOLD * 100.0
+            );
+        }
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,181 @@
+//! Detection of sudden bursts of mail from a sender domain
+//!
+//! A domain that normally sends a few emails a week and suddenly sends dozens
+//! is often a spam campaign or a breach alert, worth a look before filtering it.
+
+use crate::models::MessageMetadata;
+use chrono::{Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A week counts as a spike above this multiple of the baseline
+const SPIKE_MULTIPLIER: f32 = 3.0;
+
+/// Fewer messages than this in a week are never a spike
+const MIN_SPIKE_COUNT: usize = 10;
+
+/// Weeks of mail needed before there is a baseline to compare against
+const MIN_WEEKS: usize = 3;
+
+/// A week in which a domain sent far more mail than usual
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VolumeSpike {
+    pub sender_domain: String,
+    /// Median messages per week, over all other weeks of the scan
+    pub baseline_per_week: f32,
+    /// Messages received in the spike week
+    pub spike_count: usize,
+    /// Busiest day of the spike week
+    pub spike_date: NaiveDate,
+}
+
+/// Finds sender domains with unusual message volume
+pub struct AnomalyDetector;
+
+impl AnomalyDetector {
+    /// Find weeks in which a domain sent more than 3× its median weekly volume
+    ///
+    /// Weeks run Monday to Sunday and span the whole scan, so weeks without mail
+    /// from a domain count towards its baseline. Largest spikes come first.
+    pub fn detect_spikes<'a, I>(messages: I) -> Vec<VolumeSpike>
+    where
+        I: IntoIterator<Item = &'a MessageMetadata>,
+    {
+        // domain -> week start -> day -> messages
+        let mut volumes: BTreeMap<&str, BTreeMap<NaiveDate, BTreeMap<NaiveDate, usize>>> =
+            BTreeMap::new();
+        let mut scan_weeks: Option<(NaiveDate, NaiveDate)> = None;
+        for message in messages {
+            let day = message.date_received.date_naive();
+            let week = day.week(Weekday::Mon).first_day();
+            *volumes
+                .entry(message.sender_domain.as_str())
+                .or_default()
+                .entry(week)
+                .or_default()
+                .entry(day)
+                .or_default() += 1;
+            scan_weeks = Some(match scan_weeks {
+                Some((first, last)) => (first.min(week), last.max(week)),
+                None => (week, week),
+            });
+        }
+
+        let Some((first_week, last_week)) = scan_weeks else {
+            return Vec::new();
+        };
+        let weeks: Vec<NaiveDate> = std::iter::successors(Some(first_week), |week| {
+            Some(*week + Duration::weeks(1)).filter(|next| *next <= last_week)
+        })
+        .collect();
+        if weeks.len() < MIN_WEEKS {
+            return Vec::new();
+        }
+
+        let mut spikes = Vec::new();
+        for (domain, by_week) in &volumes {
+            let counts: Vec<usize> = weeks
+                .iter()
+                .map(|week| by_week.get(week).map_or(0, |days| days.values().sum()))
+                .collect();
+
+            for (index, &count) in counts.iter().enumerate() {
+                if count < MIN_SPIKE_COUNT {
+                    continue;
+                }
+                let mut others: Vec<usize> = counts
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, _)| *other != index)
+                    .map(|(_, count)| *count)
+                    .collect();
+                let baseline = median(&mut others);
+                // A sender that is usually silent still needs a real burst
+                if count as f32 <= SPIKE_MULTIPLIER * baseline.max(1.0) {
+                    continue;
+                }
+
+                let days = &by_week[&weeks[index]];
+                let spike_date = days
+                    .iter()
+                    .max_by_key(|(day, count)| (**count, std::cmp::Reverse(**day)))
+                    .map(|(day, _)| *day)
+                    .unwrap_or(weeks[index]);
+                spikes.push(VolumeSpike {
+                    sender_domain: domain.to_string(),
+                    baseline_per_week: baseline,
+                    spike_count: count,
+                    spike_date,
+                });
+            }
+        }
+
+        spikes.sort_by(|a, b| {
+            b.spike_count
+                .cmp(&a.spike_count)
+                .then_with(|| a.sender_domain.cmp(&b.sender_domain))
+        });
+        spikes
+    }
+}
+
+fn median(values: &mut [usize]) -> f32 {
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    match values.len() {
+        0 => 0.0,
+        len if len % 2 == 0 => (values[mid - 1] + values[mid]) as f32 / 2.0,
+        _ => values[mid] as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MessageMetadataBuilder;
+    use chrono::{TimeZone, Utc};
+
+    fn messages_on(domain: &str, date: (i32, u32, u32), count: usize) -> Vec<MessageMetadata> {
+        let received = Utc
+            .with_ymd_and_hms(date.0, date.1, date.2, 12, 0, 0)
+            .unwrap();
+        (0..count)
+            .map(|i| {
+                MessageMetadataBuilder::new(&format!("{}-{:?}-{}", domain, date, i))
+                    .sender(&format!("news@{}", domain))
+                    .date(received)
+                    .build()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_spikes() {
+        let mut messages = Vec::new();
+        // Three a week from the shop for four weeks (weeks starting Mondays in March 2025)
+        for day in [3, 10, 17, 24] {
+            messages.extend(messages_on("shop.example", (2025, 3, day), 3));
+        }
+        // ... then a burst in the fifth week, most of it on one day
+        messages.extend(messages_on("shop.example", (2025, 3, 31), 8));
+        messages.extend(messages_on("shop.example", (2025, 4, 2), 42));
+        // A steady, busier sender is not a spike
+        for day in [3, 10, 17, 24, 31] {
+            messages.extend(messages_on("busy.example", (2025, 3, day), 20));
+        }
+
+        let spikes = AnomalyDetector::detect_spikes(&messages);
+        assert_eq!(
+            spikes,
+            vec![VolumeSpike {
+                sender_domain: "shop.example".to_string(),
+                baseline_per_week: 3.0,
+                spike_count: 50,
+                spike_date: NaiveDate::from_ymd_opt(2025, 4, 2).unwrap(),
+            }]
+        );
+
+        // Too little history for a baseline
+        assert!(AnomalyDetector::detect_spikes(&messages[..3]).is_empty());
+    }
+}
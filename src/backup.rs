@@ -0,0 +1,321 @@
+//! Timestamped backups of Gmail filters, auto-managed labels and the state file
+//!
+//! Filters are written in the same Atom XML format Gmail uses for
+//! Settings → Filters → Export, so a backup can be re-imported through the
+//! Gmail UI if a run needs to be undone by hand.
+
+use crate::client::{ExistingFilterInfo, GmailClient, LabelColor};
+use crate::config::Config;
+use crate::error::{GmailError, Result};
+use crate::state::ProcessingState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Summary of a completed backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub created_at: DateTime<Utc>,
+    pub filter_count: usize,
+    pub label_count: usize,
+    /// Run ID of the backed-up state file, if one existed
+    pub state_run_id: Option<String>,
+}
+
+/// An auto-managed label as written to `labels-<timestamp>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelBackup {
+    pub id: String,
+    pub name: String,
+    pub color: Option<LabelColor>,
+}
+
+/// Back up all filters, auto-managed labels and the state file into `output_dir`
+pub async fn create_backup(
+    client: &dyn GmailClient,
+    config: &Config,
+    state_file: &Path,
+    output_dir: &Path,
+) -> Result<BackupManifest> {
+    let created_at = Utc::now();
+    let timestamp = created_at.format("%Y%m%d-%H%M%S");
+    tokio::fs::create_dir_all(output_dir).await?;
+
+    let labels = client.list_labels().await?;
+    let filters = client.list_filters().await?;
+
+    // Filters reference labels by ID, but the XML format uses label names
+    let label_names: HashMap<String, String> = labels
+        .iter()
+        .map(|l| (l.id.clone(), l.name.clone()))
+        .collect();
+    let filters_xml = filters_to_xml(&filters, &label_names, created_at);
+    tokio::fs::write(
+        output_dir.join(format!("filters-{}.xml", timestamp)),
+        filters_xml,
+    )
+    .await?;
+
+    let prefix = config.labels.prefix.to_lowercase();
+    let managed_labels: Vec<LabelBackup> = labels
+        .into_iter()
+        .filter(|l| l.name.to_lowercase().starts_with(&prefix))
+        .map(|l| LabelBackup {
+            id: l.id,
+            name: l.name,
+            color: l.color,
+        })
+        .collect();
+    let labels_json = serde_json::to_string_pretty(&managed_labels)
+        .map_err(|e| GmailError::Unknown(format!("Failed to serialize labels: {}", e)))?;
+    tokio::fs::write(
+        output_dir.join(format!("labels-{}.json", timestamp)),
+        labels_json,
+    )
+    .await?;
+
+    // Copy the state file byte-for-byte so compression/encryption is preserved
+    let state_run_id = if state_file.exists() {
+        let key = config.state.encryption_key()?;
+        let run_id = ProcessingState::load_with_key(state_file, key.as_ref())
+            .await
+            .map(|s| s.run_id)
+            .ok();
+        tokio::fs::copy(
+            state_file,
+            output_dir.join(format!("state-{}.json", timestamp)),
+        )
+        .await?;
+        run_id
+    } else {
+        None
+    };
+
+    let manifest = BackupManifest {
+        created_at,
+        filter_count: filters.len(),
+        label_count: managed_labels.len(),
+        state_run_id,
+    };
+    tracing::info!(
+        "Backed up {} filters and {} labels to {:?}",
+        manifest.filter_count,
+        manifest.label_count,
+        output_dir
+    );
+    Ok(manifest)
+}
+
+/// Render filters in Gmail's filter export (Atom XML) format
+pub fn filters_to_xml(
+    filters: &[ExistingFilterInfo],
+    label_names: &HashMap<String, String>,
+    updated: DateTime<Utc>,
+) -> String {
+    let updated = updated.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let mut xml = String::new();
+    xml.push_str("<?xml version='1.0' encoding='UTF-8'?>\n");
+    xml.push_str("<feed xmlns='http://www.w3.org/2005/Atom' xmlns:apps='http://schemas.google.com/apps/2006'>\n");
+    xml.push_str("\t<title>Mail Filters</title>\n");
+    xml.push_str(&format!("\t<updated>{}</updated>\n", updated));
+
+    for filter in filters {
+        xml.push_str("\t<entry>\n");
+        xml.push_str("\t\t<category term='filter'></category>\n");
+        xml.push_str("\t\t<title>Mail Filter</title>\n");
+        xml.push_str(&format!(
+            "\t\t<id>tag:mail.google.com,2008:filter:{}</id>\n",
+            xml_escape(&filter.id)
+        ));
+        xml.push_str(&format!("\t\t<updated>{}</updated>\n", updated));
+        xml.push_str("\t\t<content></content>\n");
+
+        for (name, value) in filter_properties(filter, label_names) {
+            xml.push_str(&format!(
+                "\t\t<apps:property name='{}' value='{}'/>\n",
+                name,
+                xml_escape(&value)
+            ));
+        }
+        xml.push_str("\t</entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// Map a filter's criteria and actions to Gmail export property names
+fn filter_properties(
+    filter: &ExistingFilterInfo,
+    label_names: &HashMap<String, String>,
+) -> Vec<(&'static str, String)> {
+    let mut props = Vec::new();
+    if let Some(from) = &filter.from {
+        props.push(("from", from.clone()));
+    }
+    if let Some(to) = &filter.to {
+        props.push(("to", to.clone()));
+    }
+    if let Some(subject) = &filter.subject {
+        props.push(("subject", subject.clone()));
+    }
+    if let Some(query) = &filter.query {
+        props.push(("hasTheWord", query.clone()));
+    }
+
+    for label_id in &filter.add_label_ids {
+        match label_id.as_str() {
+            "STARRED" => props.push(("shouldStar", "true".to_string())),
+            "IMPORTANT" => props.push(("shouldAlwaysMarkAsImportant", "true".to_string())),
+            "TRASH" => props.push(("shouldTrash", "true".to_string())),
+            id => {
+                let name = label_names
+                    .get(id)
+                    .cloned()
+                    .unwrap_or_else(|| id.to_string());
+                props.push(("label", name));
+            }
+        }
+    }
+    for label_id in &filter.remove_label_ids {
+        match label_id.as_str() {
+            "INBOX" => props.push(("shouldArchive", "true".to_string())),
+            "UNREAD" => props.push(("shouldMarkAsRead", "true".to_string())),
+            "SPAM" => props.push(("shouldNeverSpam", "true".to_string())),
+            "IMPORTANT" => props.push(("shouldNeverMarkAsImportant", "true".to_string())),
+            _ => {}
+        }
+    }
+    props
+}
+
+/// Escape a value for use inside a single-quoted XML attribute
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&apos;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::LabelInfo;
+    use async_trait::async_trait;
+    use tempfile::TempDir;
+
+    mockall::mock! {
+        pub TestGmailClient {}
+
+        #[async_trait]
+        impl crate::client::GmailClient for TestGmailClient {
+            async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
+            async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+            async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+            async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
+            async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
+            async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+            async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+            async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
+            async fn delete_label(&self, label_id: &str) -> Result<()>;
+            async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
+            async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
+            async fn delete_filter(&self, filter_id: &str) -> Result<()>;
+            async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
+            async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+            async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+            async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+            async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+            async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
+            async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
+            async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
+            async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
+        }
+    }
+
+    fn test_filter() -> ExistingFilterInfo {
+        ExistingFilterInfo {
+            id: "filter1".to_string(),
+            query: Some("from:(*@news.com) subject:(\"Deals & Offers\")".to_string()),
+            from: None,
+            to: None,
+            subject: None,
+            add_label_ids: vec!["Label_1".to_string()],
+            remove_label_ids: vec!["INBOX".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_filters_to_xml() {
+        let label_names = HashMap::from([("Label_1".to_string(), "AutoManaged/News".to_string())]);
+        let xml = filters_to_xml(&[test_filter()], &label_names, Utc::now());
+
+        assert!(xml.starts_with("<?xml version='1.0' encoding='UTF-8'?>"));
+        assert!(xml.contains("<id>tag:mail.google.com,2008:filter:filter1</id>"));
+        assert!(xml.contains(
+            "<apps:property name='hasTheWord' value='from:(*@news.com) subject:(&quot;Deals &amp; Offers&quot;)'/>"
+        ));
+        assert!(xml.contains("<apps:property name='label' value='AutoManaged/News'/>"));
+        assert!(xml.contains("<apps:property name='shouldArchive' value='true'/>"));
+        assert!(xml.trim_end().ends_with("</feed>"));
+    }
+
+    #[tokio::test]
+    async fn test_create_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("backups");
+        let state_file = temp_dir.path().join("state.json");
+
+        let state = ProcessingState::new();
+        state.save(&state_file).await.unwrap();
+
+        let mut client = MockTestGmailClient::new();
+        client.expect_list_labels().returning(|| {
+            Ok(vec![
+                LabelInfo {
+                    id: "Label_1".to_string(),
+                    name: "AutoManaged/News".to_string(),
+                    color: Some(LabelColor {
+                        text_color: Some("#ffffff".to_string()),
+                        background_color: Some("#000000".to_string()),
+                    }),
+                },
+                LabelInfo {
+                    id: "Label_2".to_string(),
+                    name: "Personal".to_string(),
+                    color: None,
+                },
+            ])
+        });
+        client
+            .expect_list_filters()
+            .returning(|| Ok(vec![test_filter()]));
+
+        let manifest = create_backup(&client, &Config::default(), &state_file, &output_dir)
+            .await
+            .unwrap();
+
+        assert_eq!(manifest.filter_count, 1);
+        assert_eq!(manifest.label_count, 1);
+        assert_eq!(manifest.state_run_id, Some(state.run_id.clone()));
+
+        let mut files: Vec<String> = std::fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files.len(), 3);
+        assert!(files[0].starts_with("filters-") && files[0].ends_with(".xml"));
+        assert!(files[1].starts_with("labels-") && files[1].ends_with(".json"));
+        assert!(files[2].starts_with("state-") && files[2].ends_with(".json"));
+
+        let labels: Vec<LabelBackup> =
+            serde_json::from_str(&std::fs::read_to_string(output_dir.join(&files[1])).unwrap())
+                .unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].name, "AutoManaged/News");
+        assert!(labels[0].color.is_some());
+    }
+}
@@ -7,7 +7,8 @@
 //!
 //! - **Closed**: Normal operation, requests pass through
 //! - **Open**: Threshold exceeded, requests are rejected immediately
-//! - **HalfOpen**: Testing recovery, allows one request through to test if service recovered
+//! - **HalfOpen**: Testing recovery, allows up to `half_open_max_calls` requests through
+//!   at a time and closes after `success_threshold_to_close` of them succeed
 //!
 //! # Usage
 //!
@@ -20,7 +21,9 @@
 //! let config = CircuitBreakerConfig {
 //!     enabled: true,
 //!     failure_threshold: 5,
-//!     reset_timeout_secs: 60,
+//!     success_threshold_to_close: 1,
+//!     open_duration_secs: 60,
+//!     half_open_max_calls: 1,
 //! };
 //!
 //! let breaker = CircuitBreaker::new(config);
@@ -40,6 +43,9 @@
 //! # }
 //! ```
 
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
@@ -49,7 +55,7 @@ use crate::config::CircuitBreakerConfig;
 use crate::error::{GmailError, Result};
 
 /// Circuit breaker state
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CircuitState {
     /// Normal operation - requests pass through
     Closed,
@@ -68,6 +74,8 @@ struct CircuitBreakerState {
     failure_count: u32,
     /// Number of consecutive successes (used in half-open state)
     success_count: u32,
+    /// Test requests currently in flight (used in half-open state)
+    half_open_calls: u32,
     /// Time when circuit was opened
     opened_at: Option<Instant>,
     /// Configuration
@@ -80,6 +88,7 @@ impl CircuitBreakerState {
             state: CircuitState::Closed,
             failure_count: 0,
             success_count: 0,
+            half_open_calls: 0,
             opened_at: None,
             config,
         }
@@ -96,7 +105,7 @@ impl CircuitBreakerState {
                 // Check if reset timeout has elapsed
                 if let Some(opened_at) = self.opened_at {
                     let elapsed = opened_at.elapsed();
-                    let timeout = Duration::from_secs(self.config.reset_timeout_secs);
+                    let timeout = Duration::from_secs(self.config.open_duration_secs);
 
                     if elapsed >= timeout {
                         // Transition to half-open to test recovery
@@ -106,15 +115,11 @@ impl CircuitBreakerState {
                         );
                         self.state = CircuitState::HalfOpen;
                         self.success_count = 0;
+                        self.half_open_calls = 1;
                         Ok(())
                     } else {
-                        let remaining = timeout - elapsed;
-                        Err(GmailError::CircuitBreakerOpen {
-                            message: format!(
-                                "Circuit breaker is open after {} consecutive failures",
-                                self.failure_count
-                            ),
-                            retry_after_secs: remaining.as_secs(),
+                        Err(GmailError::CircuitOpen {
+                            retry_after: self.retry_after().unwrap_or_else(Utc::now),
                         })
                     }
                 } else {
@@ -127,12 +132,29 @@ impl CircuitBreakerState {
                 }
             }
             CircuitState::HalfOpen => {
-                // Allow request through to test recovery
+                // Allow a limited number of test requests through at a time
+                if self.half_open_calls >= self.config.half_open_max_calls {
+                    return Err(GmailError::CircuitOpen {
+                        retry_after: Utc::now(),
+                    });
+                }
+                self.half_open_calls += 1;
                 Ok(())
             }
         }
     }
 
+    /// Wall-clock time at which an open circuit starts letting test requests through
+    fn retry_after(&self) -> Option<DateTime<Utc>> {
+        if self.state != CircuitState::Open {
+            return None;
+        }
+        let opened_at = self.opened_at?;
+        let timeout = Duration::from_secs(self.config.open_duration_secs);
+        let remaining = timeout.saturating_sub(opened_at.elapsed());
+        Some(Utc::now() + chrono::Duration::from_std(remaining).unwrap_or_default())
+    }
+
     fn record_success(&mut self) {
         if !self.config.enabled {
             return;
@@ -147,12 +169,20 @@ impl CircuitBreakerState {
                 }
             }
             CircuitState::HalfOpen => {
-                // Success in half-open state - close the circuit
-                debug!("Circuit breaker: request succeeded in half-open state, closing circuit");
-                self.state = CircuitState::Closed;
-                self.failure_count = 0;
-                self.success_count = 0;
-                self.opened_at = None;
+                // Close the circuit once enough test requests succeeded
+                self.half_open_calls = self.half_open_calls.saturating_sub(1);
+                self.success_count += 1;
+                if self.success_count >= self.config.success_threshold_to_close {
+                    debug!(
+                        "Circuit breaker: {} requests succeeded in half-open state, closing circuit",
+                        self.success_count
+                    );
+                    self.state = CircuitState::Closed;
+                    self.failure_count = 0;
+                    self.success_count = 0;
+                    self.half_open_calls = 0;
+                    self.opened_at = None;
+                }
             }
             CircuitState::Open => {
                 // Should not happen as we check state before allowing request
@@ -187,7 +217,7 @@ impl CircuitBreakerState {
                 if self.failure_count >= self.config.failure_threshold {
                     warn!(
                         "Circuit breaker: threshold reached ({} failures), opening circuit for {} seconds",
-                        self.failure_count, self.config.reset_timeout_secs
+                        self.failure_count, self.config.open_duration_secs
                     );
                     self.state = CircuitState::Open;
                     self.opened_at = Some(Instant::now());
@@ -199,6 +229,7 @@ impl CircuitBreakerState {
                 self.state = CircuitState::Open;
                 self.opened_at = Some(Instant::now());
                 self.success_count = 0;
+                self.half_open_calls = 0;
             }
             CircuitState::Open => {
                 // Already open, just log
@@ -300,6 +331,7 @@ impl CircuitBreaker {
         state.state = CircuitState::Closed;
         state.failure_count = 0;
         state.success_count = 0;
+        state.half_open_calls = 0;
         state.opened_at = None;
         debug!("Circuit breaker manually reset to closed state");
     }
@@ -312,6 +344,7 @@ impl CircuitBreaker {
             failure_count: state.failure_count,
             success_count: state.success_count,
             opened_at: state.opened_at,
+            retry_after: state.retry_after(),
         }
     }
 }
@@ -323,6 +356,40 @@ pub struct CircuitBreakerStats {
     pub failure_count: u32,
     pub success_count: u32,
     pub opened_at: Option<Instant>,
+    /// When an open circuit starts letting test requests through
+    pub retry_after: Option<DateTime<Utc>>,
+}
+
+impl CircuitBreakerStats {
+    /// Snapshot of the circuit state that can be saved and shown later
+    pub fn status(&self) -> CircuitStatus {
+        CircuitStatus {
+            state: self.state,
+            retry_after: self.retry_after,
+        }
+    }
+}
+
+/// Circuit state as shown to the user, e.g. "Open (retries after 14:32:00)"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    pub retry_after: Option<DateTime<Utc>>,
+}
+
+impl fmt::Display for CircuitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.state, self.retry_after) {
+            (CircuitState::Closed, _) => f.write_str("Closed"),
+            (CircuitState::Open, Some(retry_after)) => write!(
+                f,
+                "Open (retries after {})",
+                retry_after.with_timezone(&chrono::Local).format("%H:%M:%S")
+            ),
+            (CircuitState::Open, None) => f.write_str("Open"),
+            (CircuitState::HalfOpen, _) => f.write_str("Half-Open"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -334,7 +401,8 @@ mod tests {
         let config = CircuitBreakerConfig {
             enabled: true,
             failure_threshold: 3,
-            reset_timeout_secs: 1,
+            open_duration_secs: 1,
+            ..Default::default()
         };
         let breaker = CircuitBreaker::new(config);
 
@@ -348,7 +416,8 @@ mod tests {
         let config = CircuitBreakerConfig {
             enabled: true,
             failure_threshold: 3,
-            reset_timeout_secs: 1,
+            open_duration_secs: 1,
+            ..Default::default()
         };
         let breaker = CircuitBreaker::new(config);
 
@@ -370,7 +439,7 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(
             result.unwrap_err(),
-            GmailError::CircuitBreakerOpen { .. }
+            GmailError::CircuitOpen { .. }
         ));
     }
 
@@ -379,7 +448,8 @@ mod tests {
         let config = CircuitBreakerConfig {
             enabled: true,
             failure_threshold: 3,
-            reset_timeout_secs: 1,
+            open_duration_secs: 1,
+            ..Default::default()
         };
         let breaker = CircuitBreaker::new(config);
 
@@ -410,7 +480,8 @@ mod tests {
         let config = CircuitBreakerConfig {
             enabled: true,
             failure_threshold: 2,
-            reset_timeout_secs: 1,
+            open_duration_secs: 1,
+            ..Default::default()
         };
         let breaker = CircuitBreaker::new(config);
 
@@ -439,7 +510,8 @@ mod tests {
         let config = CircuitBreakerConfig {
             enabled: true,
             failure_threshold: 2,
-            reset_timeout_secs: 1,
+            open_duration_secs: 1,
+            ..Default::default()
         };
         let breaker = CircuitBreaker::new(config);
 
@@ -469,7 +541,8 @@ mod tests {
         let config = CircuitBreakerConfig {
             enabled: true,
             failure_threshold: 2,
-            reset_timeout_secs: 1,
+            open_duration_secs: 1,
+            ..Default::default()
         };
         let breaker = CircuitBreaker::new(config);
 
@@ -504,7 +577,8 @@ mod tests {
         let config = CircuitBreakerConfig {
             enabled: false,
             failure_threshold: 2,
-            reset_timeout_secs: 1,
+            open_duration_secs: 1,
+            ..Default::default()
         };
         let breaker = CircuitBreaker::new(config);
 
@@ -527,7 +601,8 @@ mod tests {
         let config = CircuitBreakerConfig {
             enabled: true,
             failure_threshold: 3,
-            reset_timeout_secs: 1,
+            open_duration_secs: 1,
+            ..Default::default()
         };
         let breaker = CircuitBreaker::new(config);
 
@@ -568,7 +643,8 @@ mod tests {
         let config = CircuitBreakerConfig {
             enabled: true,
             failure_threshold: 2,
-            reset_timeout_secs: 60,
+            open_duration_secs: 60,
+            ..Default::default()
         };
         let breaker = CircuitBreaker::new(config);
 
@@ -594,6 +670,43 @@ mod tests {
         assert_eq!(stats.failure_count, 0);
     }
 
+    #[tokio::test]
+    async fn test_circuit_breaker_half_open_limits() {
+        let config = CircuitBreakerConfig {
+            enabled: true,
+            failure_threshold: 1,
+            success_threshold_to_close: 2,
+            open_duration_secs: 1,
+            half_open_max_calls: 1,
+        };
+        let breaker = CircuitBreaker::new(config);
+
+        breaker
+            .record_failure(&GmailError::NetworkError("connection reset".to_string()))
+            .await;
+        match breaker.check_request().await {
+            Err(GmailError::CircuitOpen { retry_after }) => assert!(retry_after > Utc::now()),
+            other => panic!("expected CircuitOpen, got {:?}", other),
+        }
+        let status = breaker.stats().await.status();
+        assert!(status.to_string().starts_with("Open (retries after "));
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        // Only one test request may be in flight
+        assert!(breaker.check_request().await.is_ok());
+        assert!(breaker.check_request().await.is_err());
+        assert_eq!(breaker.stats().await.status().to_string(), "Half-Open");
+
+        // The first success frees the slot but doesn't close the circuit yet
+        breaker.record_success().await;
+        assert_eq!(breaker.state().await, CircuitState::HalfOpen);
+        assert!(breaker.check_request().await.is_ok());
+        breaker.record_success().await;
+        assert_eq!(breaker.state().await, CircuitState::Closed);
+        assert_eq!(breaker.stats().await.status().to_string(), "Closed");
+    }
+
     #[test]
     fn test_should_count_as_failure() {
         // Transient errors should count
@@ -632,7 +745,8 @@ mod tests {
         let config = CircuitBreakerConfig {
             enabled: true,
             failure_threshold: 3,
-            reset_timeout_secs: 1,
+            open_duration_secs: 1,
+            ..Default::default()
         };
         let breaker = CircuitBreaker::new(config);
 
@@ -1,10 +1,18 @@
 //! Email classification engine with rule-based pattern matching
 
-use crate::error::Result;
-use crate::models::{Classification, EmailCategory, MessageMetadata};
+use crate::config::{ClassificationMode, ClassificationRule, SizeBoost};
+use crate::error::{GmailError, Result};
+use crate::label_manager::LabelManager;
+use crate::models::{Classification, ClassificationReason, EmailCategory, MessageMetadata};
+use aho_corasick::AhoCorasick;
+use chrono::Timelike;
+use chrono_tz::Tz;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
 
 /// Automated email patterns (lines 1388-1397)
 static AUTOMATED_PATTERNS: Lazy<HashMap<&'static str, Vec<&'static str>>> = Lazy::new(|| {
@@ -41,54 +49,235 @@ static COMMERCIAL_DOMAINS: Lazy<Vec<&'static str>> = Lazy::new(|| {
     ]
 });
 
+/// Built-in subject keywords per category, in the order categories take precedence
+/// when a subject matches keywords of several categories
+pub static DEFAULT_SUBJECT_KEYWORDS: &[(EmailCategory, &[&str])] = &[
+    (
+        EmailCategory::Receipt,
+        &[
+            "receipt",
+            "invoice",
+            "order",
+            "purchase",
+            "payment",
+            "transaction",
+            "confirmation",
+            "bill",
+        ],
+    ),
+    (
+        EmailCategory::Shipping,
+        &[
+            "ship",
+            "deliver",
+            "tracking",
+            "dispatch",
+            "out for delivery",
+            "package",
+            "parcel",
+            "fedex",
+            "ups",
+            "usps",
+            "dhl",
+        ],
+    ),
+    (
+        EmailCategory::Financial,
+        &[
+            "statement",
+            "balance",
+            "credit card",
+            "bank",
+            "account",
+            "payment due",
+            "funds",
+            "wire",
+            "transfer",
+        ],
+    ),
+    (
+        EmailCategory::Newsletter,
+        &[
+            "newsletter",
+            "digest",
+            "weekly",
+            "monthly",
+            "roundup",
+            "bulletin",
+            "update",
+        ],
+    ),
+    (
+        EmailCategory::Marketing,
+        &[
+            "sale",
+            "discount",
+            "offer",
+            "deal",
+            "promo",
+            "coupon",
+            "limited time",
+            "exclusive",
+            "save",
+            "% off",
+        ],
+    ),
+    (
+        EmailCategory::Notification,
+        &[
+            "notification",
+            "alert",
+            "reminder",
+            "verify",
+            "confirm",
+            "action required",
+            "security",
+        ],
+    ),
+];
+
 /// Subject pattern regexes using once_cell (lines 1415-1451)
 static SUBJECT_PATTERNS: Lazy<SubjectPatterns> = Lazy::new(|| SubjectPatterns {
-    receipt: Regex::new(
-        r"(?i)(receipt|invoice|order|purchase|payment|transaction|confirmation|bill)",
-    )
-    .unwrap(),
-
-    shipping: Regex::new(
-        r"(?i)(ship|deliver|tracking|dispatch|out for delivery|package|parcel|fedex|ups|usps|dhl)",
+    automated: Regex::new(
+        r"(?i)(automated|automatic|do not reply|this is an automated|system generated)",
     )
     .unwrap(),
 
-    newsletter: Regex::new(r"(?i)(newsletter|digest|weekly|monthly|roundup|bulletin|update)")
+    unsubscribe: Regex::new(r"(?i)(unsubscribe|opt.?out|manage.?preferences|update.?subscription)")
         .unwrap(),
 
-    marketing: Regex::new(
-        r"(?i)(sale|discount|offer|deal|promo|coupon|limited time|exclusive|save|% off)",
-    )
-    .unwrap(),
-
-    notification: Regex::new(
-        r"(?i)(notification|alert|reminder|verify|confirm|action required|security)",
-    )
-    .unwrap(),
-
-    financial: Regex::new(
-        r"(?i)(statement|balance|credit card|bank|account|payment due|funds|wire|transfer)",
-    )
-    .unwrap(),
+    auto_reply: Regex::new(r"(?i)^\s*(auto|automatic reply|out of office|vacation)\s*:").unwrap(),
 
-    automated: Regex::new(
-        r"(?i)(automated|automatic|do not reply|this is an automated|system generated)",
+    calendar: Regex::new(
+        r"(?i)^\s*(invitation|updated invitation|accepted|declined|tentative)\s*:",
     )
     .unwrap(),
-
-    unsubscribe: Regex::new(r"(?i)(unsubscribe|opt.?out|manage.?preferences|update.?subscription)")
-        .unwrap(),
 });
 
 struct SubjectPatterns {
-    receipt: Regex,
-    shipping: Regex,
-    newsletter: Regex,
-    marketing: Regex,
-    notification: Regex,
-    financial: Regex,
     automated: Regex,
     unsubscribe: Regex,
+    /// Subject prefixes used by out-of-office responders
+    auto_reply: Regex,
+    /// Subject prefixes used by calendar invitations and responses
+    calendar: Regex,
+}
+
+/// Keyword matcher built from the default keyword lists
+static DEFAULT_KEYWORD_MATCHER: Lazy<KeywordMatcher> = Lazy::new(|| {
+    KeywordMatcher::new(&HashMap::new()).expect("built-in subject keywords are valid")
+});
+
+/// Case-insensitive matcher finding every category keyword in a subject with a
+/// single Aho-Corasick pass
+#[derive(Debug, Clone)]
+struct KeywordMatcher {
+    automaton: AhoCorasick,
+    /// Index into `DEFAULT_SUBJECT_KEYWORDS` for each pattern ID
+    pattern_categories: Vec<usize>,
+}
+
+/// Built-in subject keywords plus extra keywords keyed by category name, lowercased,
+/// in the order of `DEFAULT_SUBJECT_KEYWORDS`
+fn subject_keywords(
+    extra: &HashMap<String, Vec<String>>,
+) -> Result<Vec<(&'static EmailCategory, Vec<String>)>> {
+    for name in extra.keys() {
+        if !DEFAULT_SUBJECT_KEYWORDS
+            .iter()
+            .any(|(category, _)| format!("{:?}", category).eq_ignore_ascii_case(name))
+        {
+            return Err(GmailError::ConfigError(format!(
+                "Unknown category '{}' in subject keywords",
+                name
+            )));
+        }
+    }
+
+    Ok(DEFAULT_SUBJECT_KEYWORDS
+        .iter()
+        .map(|(category, keywords)| {
+            let name = format!("{:?}", category);
+            let configured = extra
+                .iter()
+                .filter(|(key, _)| key.eq_ignore_ascii_case(&name))
+                .flat_map(|(_, words)| words.iter().map(String::as_str));
+            let keywords = keywords
+                .iter()
+                .copied()
+                .chain(configured)
+                .map(str::to_lowercase)
+                .collect();
+            (category, keywords)
+        })
+        .collect())
+}
+
+impl KeywordMatcher {
+    /// Build from the default keywords plus extra keywords keyed by category name
+    fn new(extra: &HashMap<String, Vec<String>>) -> Result<Self> {
+        let mut patterns: Vec<String> = Vec::new();
+        let mut pattern_categories = Vec::new();
+        for (index, (_, keywords)) in subject_keywords(extra)?.into_iter().enumerate() {
+            for keyword in keywords {
+                patterns.push(keyword);
+                pattern_categories.push(index);
+            }
+        }
+
+        let automaton = AhoCorasick::builder()
+            .ascii_case_insensitive(true)
+            .build(&patterns)
+            .map_err(|e| {
+                GmailError::ConfigError(format!("Failed to build keyword matcher: {}", e))
+            })?;
+
+        Ok(Self {
+            automaton,
+            pattern_categories,
+        })
+    }
+
+    fn find(&self, subject: &str) -> SubjectMatches {
+        let mut matched = 0u8;
+        for m in self.automaton.find_overlapping_iter(subject) {
+            matched |= 1 << self.pattern_categories[m.pattern().as_usize()];
+        }
+        SubjectMatches(matched)
+    }
+
+    /// First keyword of `category` in `subject`, as written in the subject
+    fn keyword_for(&self, subject: &str, category: &EmailCategory) -> Option<String> {
+        let index = DEFAULT_SUBJECT_KEYWORDS
+            .iter()
+            .position(|(c, _)| c == category)?;
+        self.automaton
+            .find_overlapping_iter(subject)
+            .find(|m| self.pattern_categories[m.pattern().as_usize()] == index)
+            .map(|m| subject[m.range()].to_string())
+    }
+}
+
+/// Set of categories whose keywords appear in a subject
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SubjectMatches(u8);
+
+impl SubjectMatches {
+    fn contains(&self, category: &EmailCategory) -> bool {
+        DEFAULT_SUBJECT_KEYWORDS
+            .iter()
+            .position(|(c, _)| c == category)
+            .is_some_and(|index| self.0 & (1 << index) != 0)
+    }
+
+    /// Matched categories in precedence order
+    fn categories(&self) -> impl Iterator<Item = &'static EmailCategory> + '_ {
+        DEFAULT_SUBJECT_KEYWORDS
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.0 & (1 << index) != 0)
+            .map(|(_, (category, _))| category)
+    }
 }
 
 /// Service information for known services (lines 1469-1498)
@@ -185,26 +374,477 @@ static KNOWN_SERVICES: Lazy<HashMap<&'static str, ServiceInfo>> = Lazy::new(|| {
     map
 });
 
+/// Label template used when none is configured
+pub const DEFAULT_LABEL_TEMPLATE: &str = "{prefix}/{category}/{domain}";
+
+/// Default label for calendar invites
+pub const DEFAULT_CALENDAR_LABEL: &str = "{prefix}/calendar";
+
+/// Priority of the built-in rule-based heuristics among [`ClassifierPlugin`]s
+pub const HEURISTIC_PRIORITY: u32 = 100;
+
+/// Priority of the built-in [`MlPlugin`], consulted before the heuristics
+pub const ML_PRIORITY: u32 = 50;
+
+/// Classification logic added to an [`EmailClassifier`], built in or from outside this crate
+///
+/// Plugins are consulted in ascending priority order and the first `Some` wins.
+/// The built-in heuristics run at [`HEURISTIC_PRIORITY`] and classify every
+/// message, so plugins with a higher priority only run when the heuristics are
+/// disabled. A plugin may leave `suggested_label` empty to get the label the
+/// classifier's label template gives the category.
+pub trait ClassifierPlugin: Send + Sync {
+    /// Classify `msg`, or return `None` to leave it to the next plugin
+    fn classify(&self, msg: &MessageMetadata) -> Option<Classification>;
+
+    fn priority(&self) -> u32;
+
+    fn name(&self) -> &str;
+}
+
 pub struct EmailClassifier {
+    /// Plugins added with `with_plugin`, sorted by priority and then the order added
+    plugins: Vec<Box<dyn ClassifierPlugin>>,
+    /// Built-in rule-based heuristics, also used to generate labels
+    heuristics: HeuristicPlugin,
+    /// Whether the heuristics are consulted; see `without_heuristics`
+    use_heuristics: bool,
+    /// Minimum confidence per category; less confident results become `Other`
+    min_confidence: HashMap<EmailCategory, f32>,
+    /// Labels configured for sender domains, replacing the suggested label
+    rules: Vec<ClassificationRule>,
+    /// Sublabel appended to the suggested label per attachment MIME type
+    attachment_sublabels: HashMap<String, String>,
+}
+
+/// The built-in rule-based heuristics, at [`HEURISTIC_PRIORITY`]
+///
+/// Recognizes known services, sender patterns and subject keywords, and
+/// classifies every message.
+pub struct HeuristicPlugin {
     /// Label prefix for generated labels (e.g., "auto" -> "auto/receipts/amazon")
     label_prefix: String,
+    /// Template for generated labels, see `LabelManager::resolve_label_name`
+    label_template: String,
+    /// Subject keyword matcher used for category detection
+    keywords: KeywordMatcher,
+    /// Label for calendar invites; `{prefix}` expands to the label prefix
+    calendar_label: String,
+    /// Confidence adjustments for large messages, applied additively
+    size_boosts: Vec<SizeBoost>,
+    /// Local time zone for time-of-day heuristics; `None` disables them
+    time_zone: Option<Tz>,
 }
 
 impl EmailClassifier {
     pub fn new(label_prefix: String) -> Self {
-        Self { label_prefix }
+        Self {
+            plugins: Vec::new(),
+            heuristics: HeuristicPlugin {
+                label_prefix,
+                label_template: DEFAULT_LABEL_TEMPLATE.to_string(),
+                calendar_label: DEFAULT_CALENDAR_LABEL.to_string(),
+                size_boosts: Vec::new(),
+                time_zone: None,
+                keywords: DEFAULT_KEYWORD_MATCHER.clone(),
+            },
+            use_heuristics: true,
+            min_confidence: HashMap::new(),
+            rules: Vec::new(),
+            attachment_sublabels: HashMap::new(),
+        }
     }
 
-    /// Classify an email using rule-based logic
+    /// Use a custom label template (e.g. "{prefix}/{year}/{domain}")
+    pub fn with_label_template(mut self, template: String) -> Self {
+        self.heuristics.label_template = template;
+        self
+    }
+
+    /// Route calendar invites to a custom label (e.g. "{prefix}/meetings")
+    pub fn with_calendar_label(mut self, label: String) -> Self {
+        self.heuristics.calendar_label = label;
+        self
+    }
+
+    /// Adjust confidence by message size (e.g. large `Financial` mail is likely a statement)
+    pub fn with_size_boosts(mut self, size_boosts: Vec<SizeBoost>) -> Self {
+        self.heuristics.size_boosts = size_boosts;
+        self
+    }
+
+    /// Adjust confidence by the hour messages arrive in `time_zone`
+    pub fn with_time_heuristics(mut self, time_zone: Tz) -> Self {
+        self.heuristics.time_zone = Some(time_zone);
+        self
+    }
+
+    /// Add subject keywords to the built-in lists, keyed by category name
+    /// (e.g. `"Newsletter" = ["changelog"]`)
+    pub fn with_subject_keywords(mut self, extra: &HashMap<String, Vec<String>>) -> Result<Self> {
+        self.heuristics.keywords = KeywordMatcher::new(extra)?;
+        Ok(self)
+    }
+
+    /// Reclassify results below a category's minimum confidence as `Other`
+    pub fn with_min_confidence(mut self, thresholds: HashMap<EmailCategory, f32>) -> Self {
+        self.min_confidence = thresholds;
+        self
+    }
+
+    /// Consult `plugin` in priority order with the other plugins and the
+    /// heuristics; plugins with equal priority run in the order added, before
+    /// the heuristics
+    pub fn with_plugin(mut self, plugin: Box<dyn ClassifierPlugin>) -> Self {
+        let index = self
+            .plugins
+            .partition_point(|added| added.priority() <= plugin.priority());
+        self.plugins.insert(index, plugin);
+        self
+    }
+
+    /// Classify with the plugins only; messages none of them recognizes become `Other`
+    pub fn without_heuristics(mut self) -> Self {
+        self.use_heuristics = false;
+        self
+    }
+
+    /// Set up the built-in plugins for `mode`; the subject word model learns
+    /// `subject_keywords` on top of the built-in keywords
+    pub fn with_mode(
+        self,
+        mode: ClassificationMode,
+        subject_keywords: &HashMap<String, Vec<String>>,
+    ) -> Result<Self> {
+        Ok(match mode {
+            ClassificationMode::Rules => self,
+            ClassificationMode::Ml => self
+                .with_plugin(Box::new(MlPlugin::new(subject_keywords)?))
+                .without_heuristics(),
+            ClassificationMode::Hybrid => {
+                self.with_plugin(Box::new(MlPlugin::new(subject_keywords)?))
+            }
+        })
+    }
+
+    /// Label messages from a rule's domain with the rule's label; the first match wins
+    pub fn with_rules(mut self, rules: Vec<ClassificationRule>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// File messages with attachments under a sublabel per MIME type
+    /// (e.g. `"application/pdf" = "documents"` gives `auto/receipts/amazon.com/documents`)
+    pub fn with_attachment_sublabels(mut self, sublabels: HashMap<String, String>) -> Self {
+        self.attachment_sublabels = sublabels
+            .into_iter()
+            .map(|(mime_type, sublabel)| (mime_type.to_lowercase(), sublabel))
+            .collect();
+        self
+    }
+
+    /// Categories whose subject keywords appear in `subject`, in precedence order
+    pub fn subject_categories(&self, subject: &str) -> Vec<EmailCategory> {
+        self.heuristics
+            .keywords
+            .find(subject)
+            .categories()
+            .cloned()
+            .collect()
+    }
+
+    /// Classify an email with the first plugin that recognizes it, then apply
+    /// the minimum confidence and any configured label rule
     pub fn classify(&self, message: &MessageMetadata) -> Result<Classification> {
+        // Plugins are kept sorted, so the heuristics slot in after those of equal priority
+        let split = self
+            .plugins
+            .partition_point(|plugin| plugin.priority() <= self.heuristics.priority());
+        let (before, after) = self.plugins.split_at(split);
+        let heuristics = self
+            .use_heuristics
+            .then_some(&self.heuristics as &dyn ClassifierPlugin);
+        let mut plugins = before
+            .iter()
+            .map(|plugin| plugin.as_ref())
+            .chain(heuristics)
+            .chain(after.iter().map(|plugin| plugin.as_ref()));
+
+        let classification = plugins.find_map(|plugin| {
+            let classification = plugin.classify(message)?;
+            tracing::debug!(
+                "Message {} classified by plugin '{}'",
+                message.id,
+                plugin.name()
+            );
+            Some(classification)
+        });
+        let mut classification = classification.unwrap_or_else(|| Classification {
+            message_id: message.id.clone(),
+            category: EmailCategory::Other,
+            confidence: 0.0,
+            suggested_label: String::new(),
+            should_archive: false,
+            reasoning: Some("Not recognized by any classifier plugin".to_string()),
+            reasons: Vec::new(),
+        });
+        if classification.suggested_label.is_empty() {
+            classification.suggested_label = self
+                .heuristics
+                .generate_label(message, &classification.category);
+        }
+
+        // Too uncertain for this category: don't risk an unwanted filter
+        let below_threshold = self
+            .min_confidence
+            .get(&classification.category)
+            .is_some_and(|threshold| classification.confidence < *threshold);
+        if below_threshold {
+            classification.category = EmailCategory::Other;
+            classification.confidence = 1.0;
+            classification.suggested_label = self
+                .heuristics
+                .generate_label(message, &EmailCategory::Other);
+            classification.should_archive = false;
+            let note = "Below minimum confidence for detected category";
+            classification.reasoning = Some(match classification.reasoning {
+                Some(reasoning) => format!("{}. {}", reasoning, note),
+                None => note.to_string(),
+            });
+        }
+
+        let domain = message.sender_domain.to_lowercase();
+        let rule = self.rules.iter().find(|rule| {
+            let rule_domain = rule.domain.to_lowercase();
+            domain == rule_domain || domain.ends_with(&format!(".{}", rule_domain))
+        });
+        if let Some(rule) = rule {
+            classification.suggested_label = rule.label.clone();
+            let note = format!("Label set by classification rule for {}", rule.domain);
+            classification.reasoning = Some(match classification.reasoning {
+                Some(reasoning) => format!("{}. {}", reasoning, note),
+                None => note,
+            });
+            classification.reasons.insert(
+                0,
+                ClassificationReason::MatchesCustomRule {
+                    rule_domain: rule.domain.clone(),
+                },
+            );
+        }
+
+        // The first attachment with a configured type picks the sublabel
+        let sublabel = message.attachment_types.iter().find_map(|mime_type| {
+            let mime_type = mime_type.to_lowercase();
+            let sublabel = self.attachment_sublabels.get(&mime_type)?;
+            Some((mime_type, sublabel))
+        });
+        if let Some((mime_type, sublabel)) = sublabel {
+            classification.suggested_label =
+                format!("{}/{}", classification.suggested_label, sublabel);
+            classification
+                .reasons
+                .push(ClassificationReason::AttachmentType {
+                    mime_type,
+                    sublabel: sublabel.clone(),
+                });
+        }
+        Ok(classification)
+    }
+
+    /// Check if the message is a calendar invitation or a response to one
+    pub fn is_calendar_invite(message: &MessageMetadata) -> bool {
+        message
+            .attachment_types
+            .iter()
+            .any(|mime_type| mime_type == "text/calendar")
+            || SUBJECT_PATTERNS.calendar.is_match(&message.subject)
+    }
+
+    /// Check if the message is an out-of-office or other automatic reply
+    ///
+    /// Uses `Auto-Submitted: auto-replied`, typical subject prefixes, or a reply
+    /// (`In-Reply-To`) that was sent automatically.
+    pub fn is_auto_reply(message: &MessageMetadata) -> bool {
+        let auto_submitted = message.auto_submitted.as_deref();
+        if auto_submitted.is_some_and(|value| value.starts_with("auto-replied")) {
+            return true;
+        }
+
+        if SUBJECT_PATTERNS.auto_reply.is_match(&message.subject) {
+            return true;
+        }
+
+        message.is_reply && auto_submitted.is_some_and(|value| value != "no")
+    }
+
+    /// Check if sender appears to be automated
+    pub fn is_automated_sender(&self, message: &MessageMetadata) -> bool {
+        self.heuristics.is_automated_sender(message)
+    }
+
+    /// Detect category from subject and sender
+    pub fn detect_category(&self, message: &MessageMetadata) -> EmailCategory {
+        self.heuristics.detect_category(message)
+    }
+
+    /// Cluster messages by domain for bulk classification
+    pub fn cluster_by_domain(&self, messages: &[MessageMetadata]) -> HashMap<String, Vec<String>> {
+        let mut clusters: HashMap<String, Vec<String>> = HashMap::new();
+
+        for message in messages {
+            let main_domain = extract_main_domain(&message.sender_domain);
+            clusters
+                .entry(main_domain)
+                .or_default()
+                .push(message.id.clone());
+        }
+
+        clusters
+    }
+
+    /// Get domain statistics for analysis
+    pub fn analyze_domain_patterns(&self, messages: &[MessageMetadata]) -> Vec<DomainStats> {
+        let clusters = self.cluster_by_domain(messages);
+
+        let mut stats: Vec<DomainStats> = clusters
+            .into_iter()
+            .map(|(domain, message_ids)| {
+                let sample_messages: Vec<&MessageMetadata> = messages
+                    .iter()
+                    .filter(|m| extract_main_domain(&m.sender_domain) == domain)
+                    .take(10)
+                    .collect();
+
+                let category = if let Some(msg) = sample_messages.first() {
+                    self.detect_category(msg)
+                } else {
+                    EmailCategory::Other
+                };
+
+                let mut category_breakdown = BTreeMap::new();
+                let mut senders: HashMap<&str, usize> = HashMap::new();
+                for msg in messages
+                    .iter()
+                    .filter(|m| extract_main_domain(&m.sender_domain) == domain)
+                {
+                    *category_breakdown
+                        .entry(format!("{:?}", self.detect_category(msg)))
+                        .or_default() += 1;
+                    *senders.entry(msg.sender_email.as_str()).or_default() += 1;
+                }
+                let mut top_senders: Vec<(String, usize)> = senders
+                    .into_iter()
+                    .map(|(sender, count)| (sender.to_string(), count))
+                    .collect();
+                top_senders.sort_by(|(a_sender, a), (b_sender, b)| {
+                    b.cmp(a).then_with(|| a_sender.cmp(b_sender))
+                });
+                top_senders.truncate(DOMAIN_STATS_TOP_SENDERS);
+
+                let automated_count = sample_messages
+                    .iter()
+                    .filter(|m| self.is_automated_sender(m))
+                    .count();
+
+                DomainStats {
+                    domain: domain.clone(),
+                    count: message_ids.len(),
+                    suggested_category: category,
+                    automation_ratio: automated_count as f32 / sample_messages.len() as f32,
+                    category_breakdown,
+                    top_senders,
+                }
+            })
+            .collect();
+
+        // Sort by count descending
+        stats.sort_by_key(|s| std::cmp::Reverse(s.count));
+
+        stats
+    }
+}
+
+impl HeuristicPlugin {
+    /// Classify an email using rule-based logic
+    fn classify_with_heuristics(&self, message: &MessageMetadata) -> Classification {
+        let mut reasons = Vec::new();
+        if let Some(list_id) = &message.list_id {
+            reasons.push(ClassificationReason::ListIdPresent(list_id.clone()));
+        }
+        if message.has_unsubscribe {
+            reasons.push(ClassificationReason::HasUnsubscribeHeader);
+        }
+
+        // Mailing lists are bulk mail even when they omit List-Unsubscribe
+        let message = if message.list_id.is_some() && !message.has_unsubscribe {
+            Cow::Owned(MessageMetadata {
+                has_unsubscribe: true,
+                ..message.clone()
+            })
+        } else {
+            Cow::Borrowed(message)
+        };
+        let message = message.as_ref();
+
+        // Bounces, invites and out-of-office replies look automated but aren't bulk mail
+        let detected = if message.is_bounce {
+            Some((EmailCategory::Bounce, 0.99, "delivery failure notice"))
+        } else if EmailClassifier::is_calendar_invite(message) {
+            Some((EmailCategory::CalendarInvite, 0.95, "calendar invite"))
+        } else if EmailClassifier::is_auto_reply(message) {
+            Some((EmailCategory::AutoReply, 0.95, "automatic reply"))
+        } else {
+            None
+        };
+        if let Some((category, confidence, kind)) = detected {
+            return Classification {
+                message_id: message.id.clone(),
+                suggested_label: self.generate_label(message, &category),
+                reasoning: Some(format!(
+                    "Categorized as {:?}. Detected as {}",
+                    category, kind
+                )),
+                category,
+                confidence,
+                should_archive: false,
+                reasons,
+            };
+        }
+
+        // Match subject keywords once for all scoring steps
+        let matches = self.keywords.find(&message.subject);
+
         // Determine if automated
         let is_automated = self.is_automated_sender(message);
 
         // Detect category
-        let category = self.detect_category(message);
+        let category = self.detect_category_with(message, matches);
+        if matches.contains(&category) {
+            if let Some(keyword) = self.keywords.keyword_for(&message.subject, &category) {
+                reasons.push(ClassificationReason::MatchesKeyword(keyword));
+            }
+        }
+        // Bulk mail headers already count as automated
+        if is_automated && !message.has_unsubscribe {
+            reasons.push(ClassificationReason::IsAutomatedSender);
+        }
+
+        // Calculate confidence based on multiple factors
+        let mut confidence = self.calculate_confidence(message, is_automated, matches);
+        let authenticated = Self::adjust_for_authentication(message, &category, confidence);
+        if authenticated > confidence {
+            reasons.push(ClassificationReason::SpfDkimPass);
+        }
+        confidence = authenticated;
+        if message.list_id.is_some() && category == EmailCategory::Newsletter {
+            confidence = (confidence + 0.1).min(1.0);
+        }
+        confidence = self.adjust_for_size(message, &category, confidence);
+        confidence = self.adjust_for_time(message, &category, confidence);
 
         // Calculate priority score (lines 1504-1566)
-        let priority_score = self.calculate_priority_score(message, &category);
+        let priority_score = self.calculate_priority_score(message, &category, matches);
 
         // Generate suggested label
         let suggested_label = self.generate_label(message, &category);
@@ -212,20 +852,57 @@ impl EmailClassifier {
         // Determine if should archive
         let should_archive = self.should_auto_archive(message, &category, priority_score);
 
-        // Calculate confidence based on multiple factors
-        let confidence = self.calculate_confidence(message, &category, is_automated);
-
         // Generate reasoning
-        let reasoning = self.generate_reasoning(message, &category, is_automated, priority_score);
+        let reasoning =
+            self.generate_reasoning(message, &category, is_automated, priority_score, matches);
 
-        Ok(Classification {
+        Classification {
             message_id: message.id.clone(),
             category,
             confidence,
             suggested_label,
             should_archive,
             reasoning: Some(reasoning),
-        })
+            reasons,
+        }
+    }
+
+    /// Apply every size boost matching the message's size and category
+    fn adjust_for_size(
+        &self,
+        message: &MessageMetadata,
+        category: &EmailCategory,
+        confidence: f32,
+    ) -> f32 {
+        let boost: f32 = self
+            .size_boosts
+            .iter()
+            .filter(|b| b.category == *category && message.size_bytes >= b.min_bytes)
+            .map(|b| b.boost)
+            .sum();
+        (confidence + boost).clamp(0.0, 1.0)
+    }
+
+    /// Apply time-of-day boosts in the configured time zone
+    ///
+    /// Mail arriving late at night is usually written by a person, while newsletters
+    /// are scheduled for the top of the hour.
+    fn adjust_for_time(
+        &self,
+        message: &MessageMetadata,
+        category: &EmailCategory,
+        confidence: f32,
+    ) -> f32 {
+        let Some(time_zone) = self.time_zone else {
+            return confidence;
+        };
+        let local = message.date_received.with_timezone(&time_zone);
+        let boost = match category {
+            EmailCategory::Personal if local.hour() >= 22 || local.hour() < 6 => 0.15,
+            EmailCategory::Newsletter if local.minute() == 0 => 0.1,
+            _ => 0.0,
+        };
+        (confidence + boost).clamp(0.0, 1.0)
     }
 
     /// Check if sender appears to be automated
@@ -263,8 +940,14 @@ impl EmailClassifier {
 
     /// Detect category from subject and sender
     pub fn detect_category(&self, message: &MessageMetadata) -> EmailCategory {
-        let subject_lower = message.subject.to_lowercase();
+        self.detect_category_with(message, self.keywords.find(&message.subject))
+    }
 
+    fn detect_category_with(
+        &self,
+        message: &MessageMetadata,
+        matches: SubjectMatches,
+    ) -> EmailCategory {
         // Check known services first
         if let Some(service_info) = KNOWN_SERVICES.get(message.sender_domain.as_str()) {
             return service_info.category.clone();
@@ -277,37 +960,19 @@ impl EmailClassifier {
             || sender_email_lower.starts_with("finance@")
             || sender_email_lower.starts_with("invoices@")
             || sender_email_lower.starts_with("accounts@"))
-            && (SUBJECT_PATTERNS.financial.is_match(&subject_lower)
-                || subject_lower.contains("invoice")
-                || subject_lower.contains("statement")
-                || subject_lower.contains("bill"))
+            && (matches.contains(&EmailCategory::Financial) || {
+                let subject_lower = message.subject.to_lowercase();
+                subject_lower.contains("invoice")
+                    || subject_lower.contains("statement")
+                    || subject_lower.contains("bill")
+            })
         {
             return EmailCategory::Financial;
         }
 
-        // Pattern matching on subject
-        if SUBJECT_PATTERNS.receipt.is_match(&subject_lower) {
-            return EmailCategory::Receipt;
-        }
-
-        if SUBJECT_PATTERNS.shipping.is_match(&subject_lower) {
-            return EmailCategory::Shipping;
-        }
-
-        if SUBJECT_PATTERNS.financial.is_match(&subject_lower) {
-            return EmailCategory::Financial;
-        }
-
-        if SUBJECT_PATTERNS.newsletter.is_match(&subject_lower) {
-            return EmailCategory::Newsletter;
-        }
-
-        if SUBJECT_PATTERNS.marketing.is_match(&subject_lower) {
-            return EmailCategory::Marketing;
-        }
-
-        if SUBJECT_PATTERNS.notification.is_match(&subject_lower) {
-            return EmailCategory::Notification;
+        // Keyword matching on subject, highest-precedence category wins
+        if let Some(category) = matches.categories().next() {
+            return category.clone();
         }
 
         // Check sender patterns
@@ -333,7 +998,12 @@ impl EmailClassifier {
     }
 
     /// Calculate priority score (lines 1504-1566)
-    fn calculate_priority_score(&self, message: &MessageMetadata, category: &EmailCategory) -> i32 {
+    fn calculate_priority_score(
+        &self,
+        message: &MessageMetadata,
+        category: &EmailCategory,
+        matches: SubjectMatches,
+    ) -> i32 {
         let mut score = 50; // Base score
 
         // Category-based scoring
@@ -345,6 +1015,8 @@ impl EmailClassifier {
             EmailCategory::Notification => score += 10,
             EmailCategory::Newsletter => score -= 10,
             EmailCategory::Marketing => score -= 20,
+            EmailCategory::CalendarInvite => score += 20,
+            EmailCategory::AutoReply | EmailCategory::Bounce => score -= 30,
             EmailCategory::Other => score += 0,
         }
 
@@ -388,7 +1060,7 @@ impl EmailClassifier {
         }
 
         // Marketing indicators reduce priority
-        if SUBJECT_PATTERNS.marketing.is_match(&subject_lower) {
+        if matches.contains(&EmailCategory::Marketing) {
             score -= 20;
         }
 
@@ -402,36 +1074,17 @@ impl EmailClassifier {
 
     /// Generate label suggestion based on domain clustering
     fn generate_label(&self, message: &MessageMetadata, category: &EmailCategory) -> String {
+        if *category == EmailCategory::CalendarInvite {
+            return self.calendar_label.replace("{prefix}", &self.label_prefix);
+        }
+
         // Check for known services first
         if let Some(service_info) = KNOWN_SERVICES.get(message.sender_domain.as_str()) {
             return format!("{}/{}", self.label_prefix, service_info.name);
         }
 
-        // Domain clustering logic
-        let domain = &message.sender_domain;
-
-        // Extract main domain (remove subdomains)
-        let main_domain = extract_main_domain(domain);
-
-        // Generate label based on category and domain
-        let category_prefix = match category {
-            EmailCategory::Newsletter => "newsletters",
-            EmailCategory::Receipt => "receipts",
-            EmailCategory::Notification => "notifications",
-            EmailCategory::Marketing => "marketing",
-            EmailCategory::Shipping => "shipping",
-            EmailCategory::Financial => "financial",
-            EmailCategory::Personal => "personal",
-            EmailCategory::Other => "other",
-        };
-
-        // Create hierarchical label
-        if main_domain.is_empty() {
-            format!("{}/{}", self.label_prefix, category_prefix)
-        } else {
-            let domain_label = sanitize_label_name(&main_domain);
-            format!("{}/{}/{}", self.label_prefix, category_prefix, domain_label)
-        }
+        let template = self.label_template.replace("{prefix}", &self.label_prefix);
+        LabelManager::resolve_label_name(message, category, &template)
     }
 
     /// Determine if message should be auto-archived
@@ -446,8 +1099,11 @@ impl EmailClassifier {
             return false;
         }
 
-        // Never archive personal emails
-        if matches!(category, EmailCategory::Personal) {
+        // Never archive personal emails or time-sensitive invites
+        if matches!(
+            category,
+            EmailCategory::Personal | EmailCategory::CalendarInvite
+        ) {
             return false;
         }
 
@@ -477,8 +1133,8 @@ impl EmailClassifier {
     fn calculate_confidence(
         &self,
         message: &MessageMetadata,
-        _category: &EmailCategory,
         is_automated: bool,
+        matches: SubjectMatches,
     ) -> f32 {
         let mut confidence: f32 = 0.5;
 
@@ -488,19 +1144,11 @@ impl EmailClassifier {
         }
 
         // Strong subject pattern match
-        let subject_lower = message.subject.to_lowercase();
-        let pattern_matches = [
-            SUBJECT_PATTERNS.receipt.is_match(&subject_lower),
-            SUBJECT_PATTERNS.shipping.is_match(&subject_lower),
-            SUBJECT_PATTERNS.financial.is_match(&subject_lower),
-            SUBJECT_PATTERNS.newsletter.is_match(&subject_lower),
-            SUBJECT_PATTERNS.marketing.is_match(&subject_lower),
-        ]
-        .iter()
-        .filter(|&&x| x)
-        .count();
+        let strong_match = matches
+            .categories()
+            .any(|category| *category != EmailCategory::Notification);
 
-        if pattern_matches > 0 {
+        if strong_match {
             confidence += 0.2;
         }
 
@@ -518,6 +1166,26 @@ impl EmailClassifier {
         confidence.clamp(0.0, 1.0)
     }
 
+    /// Adjust confidence using SPF/DKIM results, when both were reported
+    ///
+    /// Authenticated bulk mail is more likely to be a genuine newsletter or
+    /// campaign; mail failing both checks shouldn't be trusted as automated.
+    fn adjust_for_authentication(
+        message: &MessageMetadata,
+        category: &EmailCategory,
+        confidence: f32,
+    ) -> f32 {
+        let both_pass = message.spf_pass == Some(true) && message.dkim_pass == Some(true);
+        let both_fail = message.spf_pass == Some(false) && message.dkim_pass == Some(false);
+        let adjusted = match category {
+            EmailCategory::Newsletter | EmailCategory::Marketing if both_pass => confidence + 0.15,
+            EmailCategory::Personal | EmailCategory::Other => confidence,
+            _ if both_fail => confidence - 0.1,
+            _ => confidence,
+        };
+        adjusted.clamp(0.0, 1.0)
+    }
+
     /// Generate reasoning for classification
     fn generate_reasoning(
         &self,
@@ -525,6 +1193,7 @@ impl EmailClassifier {
         category: &EmailCategory,
         is_automated: bool,
         priority: i32,
+        matches: SubjectMatches,
     ) -> String {
         let mut reasons = Vec::new();
 
@@ -542,14 +1211,13 @@ impl EmailClassifier {
         }
 
         // Subject patterns
-        let subject_lower = message.subject.to_lowercase();
-        if SUBJECT_PATTERNS.receipt.is_match(&subject_lower) {
+        if matches.contains(&EmailCategory::Receipt) {
             reasons.push("Subject matches receipt pattern".to_string());
         }
-        if SUBJECT_PATTERNS.marketing.is_match(&subject_lower) {
+        if matches.contains(&EmailCategory::Marketing) {
             reasons.push("Subject matches marketing pattern".to_string());
         }
-        if SUBJECT_PATTERNS.financial.is_match(&subject_lower) {
+        if matches.contains(&EmailCategory::Financial) {
             reasons.push("Subject matches financial pattern".to_string());
         }
 
@@ -558,59 +1226,136 @@ impl EmailClassifier {
 
         reasons.join(". ")
     }
+}
 
-    /// Cluster messages by domain for bulk classification
-    pub fn cluster_by_domain(&self, messages: &[MessageMetadata]) -> HashMap<String, Vec<String>> {
-        let mut clusters: HashMap<String, Vec<String>> = HashMap::new();
+impl ClassifierPlugin for HeuristicPlugin {
+    fn classify(&self, msg: &MessageMetadata) -> Option<Classification> {
+        Some(self.classify_with_heuristics(msg))
+    }
 
-        for message in messages {
-            let main_domain = extract_main_domain(&message.sender_domain);
-            clusters
-                .entry(main_domain)
-                .or_default()
-                .push(message.id.clone());
-        }
+    fn priority(&self) -> u32 {
+        HEURISTIC_PRIORITY
+    }
 
-        clusters
+    fn name(&self) -> &str {
+        "heuristics"
     }
+}
 
-    /// Get domain statistics for analysis
-    pub fn analyze_domain_patterns(&self, messages: &[MessageMetadata]) -> Vec<DomainStats> {
-        let clusters = self.cluster_by_domain(messages);
+/// Word counts of one category in an [`MlPlugin`]
+struct CategoryWords {
+    category: EmailCategory,
+    counts: HashMap<String, usize>,
+    total: usize,
+}
 
-        let mut stats: Vec<DomainStats> = clusters
-            .into_iter()
-            .map(|(domain, message_ids)| {
-                let sample_messages: Vec<&MessageMetadata> = messages
-                    .iter()
-                    .filter(|m| extract_main_domain(&m.sender_domain) == domain)
-                    .take(10)
-                    .collect();
+/// Naive Bayes model over subject words, at [`ML_PRIORITY`]
+///
+/// Unlike the heuristics, which take the first matching category in precedence
+/// order, the model weighs every known word of the subject, so a subject with
+/// words of several categories goes to the one with the most evidence. Subjects
+/// without a known word are left to the next plugin.
+pub struct MlPlugin {
+    /// Per category, in the precedence order that breaks ties
+    categories: Vec<CategoryWords>,
+    vocabulary: HashSet<String>,
+}
 
-                let category = if let Some(msg) = sample_messages.first() {
-                    self.detect_category(msg)
-                } else {
-                    EmailCategory::Other
-                };
+impl MlPlugin {
+    /// Train on the built-in subject keywords plus extra keywords keyed by category name
+    pub fn new(extra: &HashMap<String, Vec<String>>) -> Result<Self> {
+        let mut plugin = Self {
+            categories: Vec::new(),
+            vocabulary: HashSet::new(),
+        };
+        for (category, keywords) in subject_keywords(extra)? {
+            for keyword in keywords {
+                plugin.train(&keyword, category.clone());
+            }
+        }
+        Ok(plugin)
+    }
 
-                let automated_count = sample_messages
-                    .iter()
-                    .filter(|m| self.is_automated_sender(m))
-                    .count();
+    /// Learn that the words of `text` belong to `category`
+    pub fn train(&mut self, text: &str, category: EmailCategory) {
+        let index = match self.categories.iter().position(|c| c.category == category) {
+            Some(index) => index,
+            None => {
+                self.categories.push(CategoryWords {
+                    category,
+                    counts: HashMap::new(),
+                    total: 0,
+                });
+                self.categories.len() - 1
+            }
+        };
+        for word in Self::words(text) {
+            let words = &mut self.categories[index];
+            *words.counts.entry(word.clone()).or_default() += 1;
+            words.total += 1;
+            self.vocabulary.insert(word);
+        }
+    }
 
-                DomainStats {
-                    domain: domain.clone(),
-                    count: message_ids.len(),
-                    suggested_category: category,
-                    automation_ratio: automated_count as f32 / sample_messages.len() as f32,
-                }
+    fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+    }
+}
+
+impl ClassifierPlugin for MlPlugin {
+    fn classify(&self, msg: &MessageMetadata) -> Option<Classification> {
+        let words: Vec<String> = Self::words(&msg.subject)
+            .filter(|word| self.vocabulary.contains(word))
+            .collect();
+        if words.is_empty() {
+            return None;
+        }
+
+        // Log-likelihood of the words per category, with add-one smoothing and
+        // equal priors
+        let vocabulary = self.vocabulary.len() as f64;
+        let scores: Vec<(&EmailCategory, f64)> = self
+            .categories
+            .iter()
+            .map(|c| {
+                let denominator = c.total as f64 + vocabulary;
+                let score = words
+                    .iter()
+                    .map(|word| {
+                        let count = c.counts.get(word).copied().unwrap_or(0);
+                        ((count + 1) as f64 / denominator).ln()
+                    })
+                    .sum();
+                (&c.category, score)
             })
             .collect();
+        // `max_by` keeps the last of equal scores, so ties go to the earlier category
+        let (category, best) = *scores.iter().rev().max_by(|a, b| a.1.total_cmp(&b.1))?;
+        // Posterior probability of the winning category
+        let evidence: f64 = scores.iter().map(|(_, score)| (score - best).exp()).sum();
+
+        Some(Classification {
+            message_id: msg.id.clone(),
+            category: category.clone(),
+            confidence: (1.0 / evidence) as f32,
+            suggested_label: String::new(),
+            should_archive: false,
+            reasoning: Some(format!(
+                "Categorized as {:?} by the subject word model",
+                category
+            )),
+            reasons: Vec::new(),
+        })
+    }
 
-        // Sort by count descending
-        stats.sort_by(|a, b| b.count.cmp(&a.count));
+    fn priority(&self) -> u32 {
+        ML_PRIORITY
+    }
 
-        stats
+    fn name(&self) -> &str {
+        "ml"
     }
 }
 
@@ -620,13 +1365,33 @@ impl Default for EmailClassifier {
     }
 }
 
+/// Sender addresses listed in each [`DomainStats::top_senders`]
+pub const DOMAIN_STATS_TOP_SENDERS: usize = 5;
+
 /// Domain statistics for analysis
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DomainStats {
     pub domain: String,
     pub count: usize,
     pub suggested_category: EmailCategory,
     pub automation_ratio: f32,
+    /// Messages per detected category
+    pub category_breakdown: BTreeMap<String, usize>,
+    /// Most frequent sender addresses and their message counts
+    pub top_senders: Vec<(String, usize)>,
+}
+
+impl DomainStats {
+    /// Write `stats` as pretty-printed JSON; holds no subjects or message content
+    pub async fn export_json(stats: &[DomainStats], path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let json = serde_json::to_string_pretty(stats)
+            .map_err(|e| GmailError::Unknown(format!("Failed to serialize domain stats: {}", e)))?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
 }
 
 /// Known compound TLD suffixes (second-level domains that are part of the TLD)
@@ -675,7 +1440,7 @@ static COMPOUND_TLDS: &[&str] = &[
 ];
 
 /// Extract main domain from full domain (remove subdomains, handle compound TLDs)
-fn extract_main_domain(domain: &str) -> String {
+pub(crate) fn extract_main_domain(domain: &str) -> String {
     let parts: Vec<&str> = domain.split('.').collect();
 
     if parts.len() < 2 {
@@ -706,7 +1471,7 @@ fn extract_main_domain(domain: &str) -> String {
 }
 
 /// Sanitize domain name for use in label
-fn sanitize_label_name(domain: &str) -> String {
+pub(crate) fn sanitize_label_name(domain: &str) -> String {
     domain
         .replace('.', "-")
         .replace('@', "-at-")
@@ -714,10 +1479,154 @@ fn sanitize_label_name(domain: &str) -> String {
         .to_lowercase()
 }
 
+/// One message classified by both modes of an A/B test
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgreementResult {
+    pub message_id: String,
+    pub control_category: EmailCategory,
+    pub experiment_category: EmailCategory,
+    #[serde(rename = "match")]
+    pub matches: bool,
+}
+
+impl AgreementResult {
+    pub fn new(message_id: String, control: EmailCategory, experiment: EmailCategory) -> Self {
+        Self {
+            message_id,
+            matches: control == experiment,
+            control_category: control,
+            experiment_category: experiment,
+        }
+    }
+
+    /// Whether message `id` is in the A/B test sample
+    ///
+    /// Hashing the ID keeps the sample the same across runs, so repeated scans
+    /// compare the same messages.
+    pub fn is_sampled(id: &str, fraction: f32) -> bool {
+        // FNV-1a, stable across builds unlike `DefaultHasher`
+        let hash = id.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        ((hash % 10_000) as f32) < fraction * 10_000.0
+    }
+
+    /// Append results to a JSONL log, creating the file if needed
+    pub async fn append_all(results: &[AgreementResult], path: &Path) -> Result<()> {
+        crate::jsonl::append(results, path, "A/B test result").await
+    }
+
+    /// Load every result from a JSONL log, oldest first
+    pub async fn load_all(path: &Path) -> Result<Vec<AgreementResult>> {
+        crate::jsonl::load(path, "A/B test").await
+    }
+}
+
+/// A reviewed message whose accepted label differs from the suggested one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeedbackEntry {
+    pub message_id: String,
+    pub sender_domain: String,
+    pub suggested_category: EmailCategory,
+    pub accepted_label: String,
+    pub run_id: String,
+}
+
+impl FeedbackEntry {
+    /// Append entries to a JSONL log, creating the file if needed
+    pub async fn append_all(entries: &[FeedbackEntry], path: &Path) -> Result<()> {
+        crate::jsonl::append(entries, path, "feedback").await
+    }
+
+    /// Load every entry from a JSONL log, oldest first
+    ///
+    /// A missing log means no suggestion was ever corrected.
+    pub async fn load_all(path: &Path) -> Result<Vec<FeedbackEntry>> {
+        crate::jsonl::load(path, "feedback").await
+    }
+
+    /// Rules for domains with at least `min_feedback` entries, more than half of
+    /// which were corrected to the same label, sorted by domain
+    pub fn suggest_rules(
+        entries: &[FeedbackEntry],
+        min_feedback: usize,
+    ) -> Vec<ClassificationRule> {
+        let mut by_domain: HashMap<String, HashMap<&str, usize>> = HashMap::new();
+        for entry in entries {
+            *by_domain
+                .entry(entry.sender_domain.to_lowercase())
+                .or_default()
+                .entry(entry.accepted_label.as_str())
+                .or_default() += 1;
+        }
+
+        let mut rules: Vec<ClassificationRule> = by_domain
+            .into_iter()
+            .filter_map(|(domain, labels)| {
+                let total: usize = labels.values().sum();
+                let (label, count) = labels
+                    .into_iter()
+                    .max_by(|(a_label, a), (b_label, b)| a.cmp(b).then(b_label.cmp(a_label)))?;
+                (total >= min_feedback && count * 2 > total).then(|| ClassificationRule {
+                    domain,
+                    label: label.to_string(),
+                })
+            })
+            .collect();
+        rules.sort_by(|a, b| a.domain.cmp(&b.domain));
+        rules
+    }
+}
+
+/// Agreement between the two modes of an A/B test log
+#[derive(Debug, Clone, Default)]
+pub struct AbTestReport {
+    pub total: usize,
+    pub agreements: usize,
+    /// Messages the modes classified differently
+    pub divergences: Vec<AgreementResult>,
+    /// (control, experiment) category pairs and how often they occurred, most frequent first
+    pub confusion: Vec<((EmailCategory, EmailCategory), usize)>,
+}
+
+impl AbTestReport {
+    pub fn from_results(results: &[AgreementResult]) -> Self {
+        let mut confusion: HashMap<(EmailCategory, EmailCategory), usize> = HashMap::new();
+        for result in results {
+            *confusion
+                .entry((
+                    result.control_category.clone(),
+                    result.experiment_category.clone(),
+                ))
+                .or_default() += 1;
+        }
+        let mut confusion: Vec<_> = confusion.into_iter().collect();
+        confusion.sort_by(|(a_pair, a), (b_pair, b)| {
+            b.cmp(a)
+                .then_with(|| format!("{:?}", a_pair).cmp(&format!("{:?}", b_pair)))
+        });
+
+        Self {
+            total: results.len(),
+            agreements: results.iter().filter(|r| r.matches).count(),
+            divergences: results.iter().filter(|r| !r.matches).cloned().collect(),
+            confusion,
+        }
+    }
+
+    /// Fraction of compared messages both modes put in the same category
+    pub fn agreement_rate(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.agreements as f64 / self.total as f64
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
 
     fn create_test_message(sender_email: &str, subject: &str) -> MessageMetadata {
         let sender_domain = sender_email.split('@').nth(1).unwrap_or("example.com");
@@ -734,7 +1643,362 @@ mod tests {
             labels: vec![],
             has_unsubscribe: false,
             is_automated: false,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ab_test_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("ab-test.jsonl");
+        let results = vec![
+            AgreementResult::new(
+                "m1".into(),
+                EmailCategory::Newsletter,
+                EmailCategory::Newsletter,
+            ),
+            AgreementResult::new(
+                "m2".into(),
+                EmailCategory::Newsletter,
+                EmailCategory::Marketing,
+            ),
+            AgreementResult::new("m3".into(), EmailCategory::Receipt, EmailCategory::Receipt),
+            AgreementResult::new(
+                "m4".into(),
+                EmailCategory::Newsletter,
+                EmailCategory::Newsletter,
+            ),
+        ];
+        AgreementResult::append_all(&results[..2], &log)
+            .await
+            .unwrap();
+        AgreementResult::append_all(&results[2..], &log)
+            .await
+            .unwrap();
+        assert!(std::fs::read_to_string(&log)
+            .unwrap()
+            .contains("\"match\":false"));
+
+        let loaded = AgreementResult::load_all(&log).await.unwrap();
+        assert_eq!(loaded, results);
+        let report = AbTestReport::from_results(&loaded);
+        assert_eq!(report.total, 4);
+        assert!((report.agreement_rate() - 0.75).abs() < f64::EPSILON);
+        assert_eq!(report.divergences.len(), 1);
+        assert_eq!(report.divergences[0].message_id, "m2");
+        assert_eq!(
+            report.confusion[0],
+            ((EmailCategory::Newsletter, EmailCategory::Newsletter), 2)
+        );
+
+        // The sample depends only on the message ID
+        let sampled = (0..1000)
+            .filter(|i| AgreementResult::is_sampled(&format!("msg{}", i), 0.2))
+            .count();
+        assert!((150..250).contains(&sampled));
+        assert!(!AgreementResult::is_sampled("m1", 0.0));
+        assert!(AgreementResult::is_sampled("m1", 1.0));
+    }
+
+    #[tokio::test]
+    async fn test_feedback_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("feedback.jsonl");
+        let entry = |id: &str, domain: &str, label: &str| FeedbackEntry {
+            message_id: id.to_string(),
+            sender_domain: domain.to_string(),
+            suggested_category: EmailCategory::Marketing,
+            accepted_label: label.to_string(),
+            run_id: "run-1".to_string(),
+        };
+        let entries = vec![
+            entry("m1", "shop.example", "auto/receipts/shop"),
+            entry("m2", "shop.example", "auto/receipts/shop"),
+            entry("m3", "Shop.example", "auto/receipts/shop"),
+            entry("m4", "shop.example", "auto/shop"),
+            // Corrected, but never to the same label twice
+            entry("m5", "mixed.example", "auto/a"),
+            entry("m6", "mixed.example", "auto/b"),
+            entry("m7", "mixed.example", "auto/c"),
+            entry("m8", "rare.example", "auto/rare"),
+        ];
+        assert!(FeedbackEntry::load_all(&log).await.unwrap().is_empty());
+        FeedbackEntry::append_all(&entries, &log).await.unwrap();
+        let loaded = FeedbackEntry::load_all(&log).await.unwrap();
+        assert_eq!(loaded, entries);
+
+        let rules = FeedbackEntry::suggest_rules(&loaded, 3);
+        assert_eq!(
+            rules,
+            vec![ClassificationRule {
+                domain: "shop.example".to_string(),
+                label: "auto/receipts/shop".to_string(),
+            }]
+        );
+        assert_eq!(FeedbackEntry::suggest_rules(&loaded, 1).len(), 2);
+
+        // Rules replace the suggested label of the domain and its subdomains
+        let classifier = EmailClassifier::new("auto".to_string()).with_rules(rules);
+        let msg = create_test_message("orders@mail.shop.example", "Your order");
+        assert_eq!(
+            classifier.classify(&msg).unwrap().suggested_label,
+            "auto/receipts/shop"
+        );
+        let msg = create_test_message("orders@notshop.example", "Your order");
+        assert_ne!(
+            classifier.classify(&msg).unwrap().suggested_label,
+            "auto/receipts/shop"
+        );
+    }
+
+    #[test]
+    fn test_attachment_sublabels() {
+        let sublabels = HashMap::from([
+            ("application/pdf".to_string(), "documents".to_string()),
+            ("image/PNG".to_string(), "images".to_string()),
+        ]);
+        let classifier = EmailClassifier::new("auto".to_string());
+        let mut msg = create_test_message("orders@shop.example", "Your order receipt");
+        msg.attachment_types = vec!["multipart/mixed".to_string(), "application/pdf".to_string()];
+        let label = classifier.classify(&msg).unwrap().suggested_label;
+
+        let classifier = classifier.with_attachment_sublabels(sublabels);
+        assert_eq!(
+            classifier.classify(&msg).unwrap().suggested_label,
+            format!("{}/documents", label)
+        );
+
+        msg.attachment_types = vec!["image/png".to_string()];
+        assert_eq!(
+            classifier.classify(&msg).unwrap().suggested_label,
+            format!("{}/images", label)
+        );
+
+        // MIME types are matched case-insensitively on both sides
+        msg.attachment_types = vec!["Application/PDF".to_string()];
+        let classification = classifier.classify(&msg).unwrap();
+        assert_eq!(
+            classification.suggested_label,
+            format!("{}/documents", label)
+        );
+        assert_eq!(classification.base_label(), label);
+        assert_eq!(
+            classification.attachment_sublabel(),
+            Some(("application/pdf", "documents"))
+        );
+
+        // Unconfigured types keep the plain label
+        msg.attachment_types = vec!["text/plain".to_string()];
+        assert_eq!(classifier.classify(&msg).unwrap().suggested_label, label);
+    }
+
+    #[test]
+    fn test_classification_reasons() {
+        let mut msg = create_test_message("news@lists.example", "The Newsletter: issue 12");
+        msg.list_id = Some("<weekly.lists.example>".to_string());
+        msg.spf_pass = Some(true);
+        msg.dkim_pass = Some(true);
+        msg.attachment_types = vec!["application/pdf".to_string()];
+
+        let classifier = EmailClassifier::new("auto".to_string());
+        let classification = classifier.classify(&msg).unwrap();
+        assert_eq!(classification.category, EmailCategory::Newsletter);
+        assert_eq!(
+            classification.reasons,
+            vec![
+                ClassificationReason::ListIdPresent("<weekly.lists.example>".to_string()),
+                ClassificationReason::MatchesKeyword("Newsletter".to_string()),
+                ClassificationReason::SpfDkimPass,
+            ]
+        );
+        assert_eq!(
+            classification.top_reason().unwrap().to_string(),
+            "Has List-ID header"
+        );
+
+        let classifier = classifier
+            .with_rules(vec![ClassificationRule {
+                domain: "example".to_string(),
+                label: "Lists".to_string(),
+            }])
+            .with_attachment_sublabels(HashMap::from([(
+                "application/pdf".to_string(),
+                "documents".to_string(),
+            )]));
+        let reasons = classifier.classify(&msg).unwrap().reasons;
+        assert_eq!(
+            reasons.first(),
+            Some(&ClassificationReason::MatchesCustomRule {
+                rule_domain: "example".to_string()
+            })
+        );
+        assert_eq!(
+            reasons.last(),
+            Some(&ClassificationReason::AttachmentType {
+                mime_type: "application/pdf".to_string(),
+                sublabel: "documents".to_string(),
+            })
+        );
+    }
+
+    struct DomainPlugin {
+        domain: &'static str,
+        priority: u32,
+    }
+
+    impl ClassifierPlugin for DomainPlugin {
+        fn classify(&self, msg: &MessageMetadata) -> Option<Classification> {
+            (msg.sender_domain == self.domain).then(|| Classification {
+                message_id: msg.id.clone(),
+                category: EmailCategory::Receipt,
+                confidence: 0.9,
+                suggested_label: "auto/receipts".to_string(),
+                should_archive: false,
+                reasoning: Some(format!("priority {}", self.priority)),
+                reasons: Vec::new(),
+            })
         }
+
+        fn priority(&self) -> u32 {
+            self.priority
+        }
+
+        fn name(&self) -> &str {
+            self.domain
+        }
+    }
+
+    #[test]
+    fn test_classifier_plugins() {
+        let classifier = EmailClassifier::new("auto".to_string())
+            .with_plugin(Box::new(DomainPlugin {
+                domain: "shop.example",
+                priority: 60,
+            }))
+            .with_plugin(Box::new(DomainPlugin {
+                domain: "shop.example",
+                priority: 50,
+            }))
+            .with_plugin(Box::new(DomainPlugin {
+                domain: "github.com",
+                priority: HEURISTIC_PRIORITY,
+            }))
+            .with_plugin(Box::new(DomainPlugin {
+                domain: "gitlab.com",
+                priority: HEURISTIC_PRIORITY + 1,
+            }));
+
+        // The lowest priority plugin that recognizes the message wins
+        let msg = create_test_message("john@shop.example", "Hello");
+        let result = classifier.classify(&msg).unwrap();
+        assert_eq!(result.category, EmailCategory::Receipt);
+        assert_eq!(result.reasoning.as_deref(), Some("priority 50"));
+
+        // A plugin with the heuristics' priority runs before them
+        let msg = create_test_message("notifications@github.com", "Build failed");
+        assert_eq!(
+            classifier.classify(&msg).unwrap().reasoning.as_deref(),
+            Some("priority 100")
+        );
+
+        // Unrecognized messages and plugins after the heuristics fall through
+        let msg = create_test_message("john@example.com", "Hello");
+        assert_eq!(
+            classifier.classify(&msg).unwrap().category,
+            EmailClassifier::default().classify(&msg).unwrap().category
+        );
+        let msg = create_test_message("noreply@gitlab.com", "Pipeline failed");
+        assert_ne!(
+            classifier.classify(&msg).unwrap().reasoning.as_deref(),
+            Some("priority 101")
+        );
+
+        // Without the heuristics, later plugins run and the rest is `Other`
+        let classifier = classifier.without_heuristics();
+        assert_eq!(
+            classifier.classify(&msg).unwrap().reasoning.as_deref(),
+            Some("priority 101")
+        );
+        let msg = create_test_message("john@example.com", "Hello");
+        let result = classifier.classify(&msg).unwrap();
+        assert_eq!(result.category, EmailCategory::Other);
+        assert_eq!(result.suggested_label, "auto/other/example-com");
+    }
+
+    #[test]
+    fn test_classification_modes_disagree() {
+        let extra = HashMap::new();
+        let classifier_for = |mode| {
+            EmailClassifier::new("auto".to_string())
+                .with_mode(mode, &extra)
+                .unwrap()
+        };
+        let rules = classifier_for(ClassificationMode::Rules);
+        let ml = classifier_for(ClassificationMode::Ml);
+        let hybrid = classifier_for(ClassificationMode::Hybrid);
+
+        // The heuristics trust the known service, the model the subject words
+        let msg = create_test_message("notification@facebook.com", "Your weekly digest");
+        assert_eq!(
+            rules.classify(&msg).unwrap().category,
+            EmailCategory::Notification
+        );
+        assert_eq!(
+            ml.classify(&msg).unwrap().category,
+            EmailCategory::Newsletter
+        );
+        assert_eq!(
+            hybrid.classify(&msg).unwrap().category,
+            EmailCategory::Newsletter
+        );
+
+        // Only the hybrid mode falls back to the heuristics
+        let msg = create_test_message("friend@example.com", "Lunch tomorrow?");
+        assert_eq!(
+            rules.classify(&msg).unwrap().category,
+            EmailCategory::Personal
+        );
+        assert_eq!(ml.classify(&msg).unwrap().category, EmailCategory::Other);
+        assert_eq!(
+            hybrid.classify(&msg).unwrap().category,
+            EmailCategory::Personal
+        );
+    }
+
+    #[test]
+    fn test_ml_plugin_weighs_subject_words() {
+        let plugin = MlPlugin::new(&HashMap::new()).unwrap();
+        assert_eq!(plugin.priority(), ML_PRIORITY);
+
+        // "order" is a receipt keyword, but the newsletter words outweigh it
+        let msg = create_test_message("news@shop.example", "Weekly digest: new order options");
+        let result = plugin.classify(&msg).unwrap();
+        assert_eq!(result.category, EmailCategory::Newsletter);
+        // More likely than any of the other five categories
+        assert!(result.confidence > 1.0 / 6.0 && result.confidence < 1.0);
+        assert!(result.suggested_label.is_empty());
+
+        // No known word: left to the next plugin
+        let msg = create_test_message("john@example.com", "Lunch tomorrow?");
+        assert!(plugin.classify(&msg).is_none());
+
+        // Extra keywords are learned too
+        let extra = HashMap::from([("Newsletter".to_string(), vec!["changelog".to_string()])]);
+        let plugin = MlPlugin::new(&extra).unwrap();
+        let msg = create_test_message("team@example.com", "Changelog");
+        assert_eq!(
+            plugin.classify(&msg).unwrap().category,
+            EmailCategory::Newsletter
+        );
+        assert!(MlPlugin::new(&HashMap::from([("Bogus".to_string(), vec![])])).is_err());
     }
 
     #[test]
@@ -773,15 +2037,44 @@ mod tests {
 
     #[test]
     fn test_priority_score() {
-        let classifier = EmailClassifier::new("auto".to_string());
+        let classifier = EmailClassifier::new("auto".to_string()).heuristics;
+
+        let score_for = |msg: &MessageMetadata, category: EmailCategory| {
+            let matches = classifier.keywords.find(&msg.subject);
+            classifier.calculate_priority_score(msg, &category, matches)
+        };
 
         let financial = create_test_message("billing@bank.com", "Important: Payment Due");
-        let score = classifier.calculate_priority_score(&financial, &EmailCategory::Financial);
-        assert!(score > 70);
+        assert!(score_for(&financial, EmailCategory::Financial) > 70);
 
         let marketing = create_test_message("marketing@store.com", "Check out our deals");
-        let score = classifier.calculate_priority_score(&marketing, &EmailCategory::Marketing);
-        assert!(score < 50);
+        assert!(score_for(&marketing, EmailCategory::Marketing) < 50);
+    }
+
+    #[test]
+    fn test_subject_keyword_matching() {
+        let classifier = EmailClassifier::new("auto".to_string());
+
+        assert_eq!(
+            classifier.subject_categories("WEEKLY Digest: 20% OFF everything"),
+            vec![EmailCategory::Newsletter, EmailCategory::Marketing]
+        );
+        // Overlapping keywords are all found ("payment" and "payment due")
+        assert_eq!(
+            classifier.subject_categories("Payment due soon"),
+            vec![EmailCategory::Receipt, EmailCategory::Financial]
+        );
+        assert!(classifier.subject_categories("Lunch?").is_empty());
+
+        let extra = HashMap::from([("newsletter".to_string(), vec!["Changelog".to_string()])]);
+        let classifier = classifier.with_subject_keywords(&extra).unwrap();
+        let msg = create_test_message("team@tool.dev", "March changelog");
+        assert_eq!(classifier.detect_category(&msg), EmailCategory::Newsletter);
+
+        let unknown = HashMap::from([("Spam".to_string(), vec!["x".to_string()])]);
+        assert!(EmailClassifier::default()
+            .with_subject_keywords(&unknown)
+            .is_err());
     }
 
     #[test]
@@ -818,6 +2111,241 @@ mod tests {
         assert!(classification.reasoning.is_some());
     }
 
+    #[test]
+    fn test_min_confidence() {
+        // Subject keyword match only: 0.5 base + 0.2
+        let msg = create_test_message("team@shop.example", "Big sale this weekend");
+        let classifier = EmailClassifier::new("auto".to_string());
+        let classification = classifier.classify(&msg).unwrap();
+        assert_eq!(classification.category, EmailCategory::Marketing);
+        assert!((classification.confidence - 0.7).abs() < 0.001);
+
+        let classifier =
+            classifier.with_min_confidence(HashMap::from([(EmailCategory::Marketing, 0.8)]));
+        let classification = classifier.classify(&msg).unwrap();
+        assert_eq!(classification.category, EmailCategory::Other);
+        assert_eq!(classification.confidence, 1.0);
+
+        // Other categories are unaffected
+        let receipt = create_test_message("orders@shop.example", "Your receipt");
+        let classification = classifier.classify(&receipt).unwrap();
+        assert_eq!(classification.category, EmailCategory::Receipt);
+
+        // Plugin results are held to the same thresholds
+        let classifier = classifier.with_plugin(Box::new(DomainPlugin {
+            domain: "shop.example",
+            priority: 10,
+        }));
+        let classifier =
+            classifier.with_min_confidence(HashMap::from([(EmailCategory::Receipt, 0.95)]));
+        let classification = classifier.classify(&receipt).unwrap();
+        assert_eq!(classification.category, EmailCategory::Other);
+        assert_eq!(classification.suggested_label, "auto/other/shop-example");
+        assert!(classification
+            .reasoning
+            .unwrap()
+            .ends_with("Below minimum confidence for detected category"));
+    }
+
+    #[test]
+    fn test_authentication_results_adjust_confidence() {
+        let classifier = EmailClassifier::new("auto".to_string());
+        // Subject keyword match only: 0.5 base + 0.2
+        let mut msg = create_test_message("team@shop.example", "Big sale this weekend");
+
+        msg.spf_pass = Some(true);
+        msg.dkim_pass = Some(true);
+        let classification = classifier.classify(&msg).unwrap();
+        assert_eq!(classification.category, EmailCategory::Marketing);
+        assert!((classification.confidence - 0.85).abs() < 0.001);
+
+        msg.spf_pass = Some(false);
+        msg.dkim_pass = Some(false);
+        let classification = classifier.classify(&msg).unwrap();
+        assert!((classification.confidence - 0.6).abs() < 0.001);
+
+        // A single result isn't enough to adjust anything
+        msg.dkim_pass = None;
+        let classification = classifier.classify(&msg).unwrap();
+        assert!((classification.confidence - 0.7).abs() < 0.001);
+
+        // Both passing doesn't boost non-bulk categories
+        let mut receipt = create_test_message("orders@shop.example", "Your receipt");
+        let baseline = classifier.classify(&receipt).unwrap().confidence;
+        receipt.spf_pass = Some(true);
+        receipt.dkim_pass = Some(true);
+        let classification = classifier.classify(&receipt).unwrap();
+        assert_eq!(classification.confidence, baseline);
+    }
+
+    #[test]
+    fn test_list_id_marks_mailing_list() {
+        let classifier = EmailClassifier::new("auto".to_string());
+        let mut msg = create_test_message("alice@example.org", "Weekly newsletter");
+        let baseline = classifier.classify(&msg).unwrap();
+        assert_eq!(baseline.category, EmailCategory::Newsletter);
+
+        // Keyword match only: 0.5 base + 0.2
+        assert!((baseline.confidence - 0.7).abs() < 0.001);
+
+        // Treated as having an unsubscribe header, which also makes the sender
+        // automated (+0.1 +0.15), plus the list boost (+0.1)
+        msg.list_id = Some("dev.example.org".to_string());
+        let classification = classifier.classify(&msg).unwrap();
+        assert_eq!(classification.category, EmailCategory::Newsletter);
+        assert_eq!(classification.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_auto_reply_detection() {
+        let classifier = EmailClassifier::new("auto".to_string());
+
+        let subjects = [
+            "Automatic reply: Weekly newsletter",
+            "Out of Office: Re: Your receipt",
+            "auto: sale this weekend",
+            "Vacation: back on Monday",
+        ];
+        for subject in subjects {
+            let msg = create_test_message("colleague@example.com", subject);
+            let classification = classifier.classify(&msg).unwrap();
+            assert_eq!(
+                classification.category,
+                EmailCategory::AutoReply,
+                "{}",
+                subject
+            );
+            assert_eq!(classification.confidence, 0.95);
+        }
+
+        let mut msg = create_test_message("news@example.com", "Weekly newsletter");
+        msg.auto_submitted = Some("auto-replied".to_string());
+        assert!(EmailClassifier::is_auto_reply(&msg));
+
+        // A reply alone isn't automatic, and neither is Auto-Submitted: no
+        msg.auto_submitted = Some("no".to_string());
+        msg.is_reply = true;
+        assert!(!EmailClassifier::is_auto_reply(&msg));
+        msg.auto_submitted = Some("auto-generated".to_string());
+        assert!(EmailClassifier::is_auto_reply(&msg));
+
+        let msg = create_test_message("news@example.com", "Automation tips: weekly newsletter");
+        assert!(!EmailClassifier::is_auto_reply(&msg));
+    }
+
+    #[test]
+    fn test_size_boosts() {
+        let classifier = EmailClassifier::new("auto".to_string());
+        let boosted = EmailClassifier::new("auto".to_string()).with_size_boosts(vec![SizeBoost {
+            min_bytes: 500 * 1024,
+            category: EmailCategory::Financial,
+            boost: 0.2,
+        }]);
+
+        let mut msg = create_test_message("alerts@bank.com", "Your monthly statement is ready");
+        let base = classifier.classify(&msg).unwrap();
+        assert_eq!(base.category, EmailCategory::Financial);
+
+        // Below the size threshold nothing changes
+        msg.size_bytes = 20 * 1024;
+        assert_eq!(boosted.classify(&msg).unwrap().confidence, base.confidence);
+
+        msg.size_bytes = 800 * 1024;
+        let classification = boosted.classify(&msg).unwrap();
+        assert_eq!(classification.confidence, (base.confidence + 0.2).min(1.0));
+
+        // Boosts only apply to their own category
+        let mut receipt = create_test_message("orders@shop.com", "Your order receipt #12345");
+        receipt.size_bytes = 800 * 1024;
+        assert_eq!(
+            boosted.classify(&receipt).unwrap().confidence,
+            classifier.classify(&receipt).unwrap().confidence
+        );
+    }
+
+    #[test]
+    fn test_time_heuristics() {
+        let classifier = EmailClassifier::new("auto".to_string());
+        let local =
+            EmailClassifier::new("auto".to_string()).with_time_heuristics(Tz::America__New_York);
+        let at = |hour: u32, minute: u32| {
+            // New York is UTC-5 in January
+            Utc.with_ymd_and_hms(2025, 1, 15, hour, minute, 0).unwrap()
+        };
+
+        // 23:30 local: a person writing late at night
+        let mut personal = create_test_message("friend@example.com", "Dinner plans?");
+        personal.date_received = at(4, 30);
+        let base = classifier.classify(&personal).unwrap();
+        assert_eq!(base.category, EmailCategory::Personal);
+        let boosted = local.classify(&personal).unwrap();
+        assert_eq!(boosted.confidence, (base.confidence + 0.15).min(1.0));
+
+        // 14:30 local: no boost
+        personal.date_received = at(19, 30);
+        assert_eq!(
+            local.classify(&personal).unwrap().confidence,
+            base.confidence
+        );
+
+        // 07:00 local: a scheduled newsletter
+        let mut newsletter = create_test_message("news@example.com", "Weekly newsletter");
+        newsletter.date_received = at(12, 0);
+        let base = classifier.classify(&newsletter).unwrap();
+        assert_eq!(base.category, EmailCategory::Newsletter);
+        let boosted = local.classify(&newsletter).unwrap();
+        assert_eq!(boosted.confidence, (base.confidence + 0.1).min(1.0));
+    }
+
+    #[test]
+    fn test_calendar_invite_classification() {
+        let classifier = EmailClassifier::new("auto".to_string());
+
+        for subject in [
+            "Invitation: Sprint planning @ Mon 10am",
+            "Updated invitation: Sprint planning",
+            "Accepted: Sprint planning",
+            "declined: Sprint planning",
+            "Tentative: Sprint planning",
+        ] {
+            let msg = create_test_message("calendar-notification@google.com", subject);
+            let classification = classifier.classify(&msg).unwrap();
+            assert_eq!(
+                classification.category,
+                EmailCategory::CalendarInvite,
+                "{}",
+                subject
+            );
+            assert_eq!(classification.suggested_label, "auto/calendar");
+            assert!(!classification.should_archive);
+        }
+
+        // An .ics part is enough, whatever the subject
+        let mut msg = create_test_message("team@example.com", "Weekly newsletter");
+        msg.attachment_types = vec!["multipart/mixed".to_string(), "text/calendar".to_string()];
+        let classifier = classifier.with_calendar_label("{prefix}/meetings".to_string());
+        let classification = classifier.classify(&msg).unwrap();
+        assert_eq!(classification.category, EmailCategory::CalendarInvite);
+        assert_eq!(classification.suggested_label, "auto/meetings");
+
+        let msg = create_test_message("team@example.com", "Invitations are open: join our beta");
+        assert!(!EmailClassifier::is_calendar_invite(&msg));
+    }
+
+    #[test]
+    fn test_bounce_classification() {
+        let classifier = EmailClassifier::new("auto".to_string());
+        let mut msg = create_test_message("mailer-daemon@example.com", "Weekly newsletter");
+        msg.is_bounce = true;
+        // Bounces often also carry Auto-Submitted: auto-replied
+        msg.auto_submitted = Some("auto-replied".to_string());
+
+        let classification = classifier.classify(&msg).unwrap();
+        assert_eq!(classification.category, EmailCategory::Bounce);
+        assert_eq!(classification.confidence, 0.99);
+        assert!(!classification.should_archive);
+    }
+
     #[test]
     fn test_domain_clustering() {
         let classifier = EmailClassifier::new("auto".to_string());
@@ -832,4 +2360,38 @@ mod tests {
         assert_eq!(clusters.get("example.com").unwrap().len(), 2);
         assert_eq!(clusters.get("test.org").unwrap().len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_domain_stats_export() {
+        let classifier = EmailClassifier::new("auto".to_string());
+        let messages = vec![
+            create_test_message("orders@shop.com", "Your order receipt"),
+            create_test_message("orders@shop.com", "Your order receipt"),
+            create_test_message("deals@shop.com", "50% off everything"),
+            create_test_message("admin@test.org", "Hello"),
+        ];
+
+        let stats = classifier.analyze_domain_patterns(&messages);
+        assert_eq!(stats[0].domain, "shop.com");
+        assert_eq!(stats[0].count, 3);
+        assert_eq!(stats[0].category_breakdown.values().sum::<usize>(), 3);
+        assert_eq!(stats[0].category_breakdown.get("Receipt"), Some(&2));
+        assert_eq!(
+            stats[0].top_senders,
+            vec![
+                ("orders@shop.com".to_string(), 2),
+                ("deals@shop.com".to_string(), 1)
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("domain-stats.json");
+        DomainStats::export_json(&stats, &path).await.unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[0]["domain"], "shop.com");
+        assert_eq!(json[0]["top_senders"][0][0], "orders@shop.com");
+        assert!(!json.to_string().contains("receipt"));
+    }
 }
@@ -0,0 +1,200 @@
+//! Removal of old run reports, state snapshots and decision files
+//!
+//! Every run leaves a `report-<run_id>.md` and a `state-<run_id>.json` snapshot
+//! next to the state file. These are never removed by the pipeline itself, so
+//! `cleanup` (or `execution.auto_cleanup_runs`) keeps only the most recent ones.
+
+use crate::error::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Kind of file kept per run in the state directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CleanupKind {
+    /// `report-*.md`, `report-*.html` and `report-*.json`
+    Report,
+    /// `state-*.json` run snapshots (never the state file itself, see [`find_stale_files`])
+    State,
+    /// `decisions*.json`
+    Decisions,
+}
+
+impl CleanupKind {
+    /// Kind of the file called `file_name`, if cleanup manages it
+    pub fn of(file_name: &str) -> Option<Self> {
+        if file_name.starts_with("report-")
+            && file_name != "report-schema.json"
+            && [".md", ".html", ".json"]
+                .iter()
+                .any(|ext| file_name.ends_with(ext))
+        {
+            Some(Self::Report)
+        } else if file_name.starts_with("state-") && file_name.ends_with(".json") {
+            Some(Self::State)
+        } else if file_name.starts_with("decisions") && file_name.ends_with(".json") {
+            Some(Self::Decisions)
+        } else {
+            None
+        }
+    }
+}
+
+/// Directory holding the state file and the files kept per run
+pub fn state_dir(state_file: &Path) -> &Path {
+    state_file
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+/// Files next to `state_file` beyond the `keep_last` most recently modified of their kind
+///
+/// Reports are counted per extension, so Markdown and HTML copies of the same
+/// run are kept together. `state_file` itself is never included, even when its
+/// name looks like a run snapshot (e.g. `--state-file state-work.json`). The
+/// result is sorted by path.
+pub fn find_stale_files(state_file: &Path, keep_last: usize) -> Result<Vec<PathBuf>> {
+    let dir = state_dir(state_file);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = entry.file_name();
+        if Some(name.as_os_str()) == state_file.file_name() {
+            continue;
+        }
+        if CleanupKind::of(&name.to_string_lossy()).is_some() {
+            files.push((entry.path(), entry.metadata()?.modified()?));
+        }
+    }
+    Ok(select_stale(files, keep_last))
+}
+
+/// All but the `keep_last` newest files of each kind (and extension)
+fn select_stale(files: Vec<(PathBuf, SystemTime)>, keep_last: usize) -> Vec<PathBuf> {
+    let mut groups: BTreeMap<(CleanupKind, String), Vec<(PathBuf, SystemTime)>> = BTreeMap::new();
+    for (path, modified) in files {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let Some(kind) = CleanupKind::of(&name) else {
+            continue;
+        };
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        groups
+            .entry((kind, extension))
+            .or_default()
+            .push((path, modified));
+    }
+
+    let mut stale = Vec::new();
+    for mut group in groups.into_values() {
+        // Newest first; equal times fall back to the name for a stable order
+        group.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.0.cmp(&a.0)));
+        stale.extend(group.into_iter().skip(keep_last).map(|(path, _)| path));
+    }
+    stale.sort();
+    stale
+}
+
+/// Delete `files`, returning how many were removed
+pub fn remove_files(files: &[PathBuf]) -> Result<usize> {
+    for path in files {
+        std::fs::remove_file(path)?;
+    }
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cleanup_kind() {
+        assert_eq!(CleanupKind::of("report-abc.md"), Some(CleanupKind::Report));
+        assert_eq!(
+            CleanupKind::of("report-abc.html"),
+            Some(CleanupKind::Report)
+        );
+        assert_eq!(CleanupKind::of("state-abc.json"), Some(CleanupKind::State));
+        assert_eq!(
+            CleanupKind::of("decisions.json"),
+            Some(CleanupKind::Decisions)
+        );
+        assert_eq!(CleanupKind::of("report-schema.json"), None);
+        assert_eq!(CleanupKind::of("state.json"), None);
+        assert_eq!(CleanupKind::of("token.json"), None);
+    }
+
+    #[test]
+    fn test_select_stale() {
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let files = vec![
+            (PathBuf::from("report-a.md"), at(1)),
+            (PathBuf::from("report-b.md"), at(3)),
+            (PathBuf::from("report-c.md"), at(2)),
+            (PathBuf::from("report-b.html"), at(3)),
+            (PathBuf::from("state-a.json"), at(1)),
+            (PathBuf::from("state-b.json"), at(2)),
+            (PathBuf::from("state.json"), at(0)),
+        ];
+
+        assert_eq!(
+            select_stale(files.clone(), 1),
+            vec![
+                PathBuf::from("report-a.md"),
+                PathBuf::from("report-c.md"),
+                PathBuf::from("state-a.json"),
+            ]
+        );
+        assert_eq!(
+            select_stale(files.clone(), 2),
+            vec![PathBuf::from("report-a.md")]
+        );
+        assert!(select_stale(files, 3).is_empty());
+    }
+
+    #[test]
+    fn test_find_stale_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "state.json",
+            "report-schema.json",
+            "report-a.md",
+            "config.toml",
+        ] {
+            std::fs::write(dir.path().join(name), "{}").unwrap();
+        }
+
+        let state_file = dir.path().join("state.json");
+        let stale = find_stale_files(&state_file, 0).unwrap();
+        assert_eq!(stale, vec![dir.path().join("report-a.md")]);
+        assert_eq!(remove_files(&stale).unwrap(), 1);
+        assert!(find_stale_files(&state_file, 0).unwrap().is_empty());
+        assert!(state_file.exists());
+    }
+
+    #[test]
+    fn test_find_stale_files_keeps_snapshot_named_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["state-work.json", "state-run1.json"] {
+            std::fs::write(dir.path().join(name), "{}").unwrap();
+        }
+
+        let state_file = dir.path().join("state-work.json");
+        let stale = find_stale_files(&state_file, 0).unwrap();
+        assert_eq!(stale, vec![dir.path().join("state-run1.json")]);
+    }
+}
@@ -1,9 +1,9 @@
 //! Command-line interface
 
-use crate::client::{GmailClient, ProductionGmailClient};
-use clap::{Parser, Subcommand};
+use crate::client::{GmailClient, MockGmailClient, ProductionGmailClient};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 #[derive(Parser, Debug)]
 #[command(name = "gmail-filters")]
@@ -34,6 +34,15 @@ pub struct Cli {
     #[arg(short, long)]
     pub verbose: bool,
 
+    /// Disable mouse support in the review session (for terminals that send
+    /// spurious mouse events)
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    /// Disable colors in the review session
+    #[arg(long)]
+    pub no_color: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -77,6 +86,45 @@ pub enum Commands {
         /// Use this to re-apply decisions from a previous dry-run or to rerun decisions
         #[arg(long, value_name = "FILE")]
         apply_decisions: Option<PathBuf>,
+
+        /// Tag this run for later filtering with `history --tag` (repeatable)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+
+        /// Back up filters, labels and state to .gmail-automation/backups before making changes
+        #[arg(long)]
+        auto_backup: bool,
+
+        /// Continue the last review session from .gmail-automation/review-progress.json
+        #[arg(long)]
+        resume_review: bool,
+
+        /// Only scan mail from these sender domains, each queried in parallel
+        /// (comma-separated, e.g. --domains a.com,b.com)
+        #[arg(long, value_delimiter = ',', value_name = "DOMAINS")]
+        domains: Vec<String>,
+
+        /// Write per-domain statistics (counts, categories, top senders, no message
+        /// content) to this JSON file after scanning
+        #[arg(long, value_name = "PATH")]
+        export_domain_stats: Option<PathBuf>,
+
+        /// Run against a JSON fixture of messages, labels and filters instead of Gmail
+        /// (no authentication; changes are only made in memory)
+        #[arg(long, value_name = "FIXTURE_PATH")]
+        test_mode: Option<PathBuf>,
+    },
+
+    /// Run the pipeline every interval without review, picking up changes to the
+    /// config file between runs
+    Watch {
+        /// Minutes between the start of one run and the next
+        #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u64).range(1..))]
+        interval_mins: u64,
+
+        /// Dry run mode (don't make any changes)
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Rollback changes from a previous run
@@ -103,6 +151,206 @@ pub enum Commands {
         /// Show detailed information
         #[arg(long)]
         detailed: bool,
+
+        /// Compare the current state against a previous run (by run ID)
+        #[arg(long, value_name = "RUN_ID")]
+        diff_with: Option<String>,
+
+        /// Also show inbox, filter and auto-managed label counts from Gmail (cached for 60s)
+        #[arg(long)]
+        live: bool,
+    },
+
+    /// Show aggregate statistics across all previous runs
+    History {
+        /// Maximum number of runs to display
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+
+        /// Only show runs with this tag
+        #[arg(long, value_parser = parse_tag)]
+        tag: Option<String>,
+    },
+
+    /// Back up existing filters, auto-managed labels and the state file
+    Backup {
+        /// Directory to write the timestamped backup files to
+        #[arg(short, long, default_value = ".gmail-automation/backups")]
+        output_dir: PathBuf,
+    },
+
+    /// Show how many messages each created filter matches over time
+    Stats {
+        /// Query Gmail for current match counts before displaying
+        #[arg(long)]
+        refresh: bool,
+
+        /// Also analyse labels, show consolidation opportunities and a timeline
+        /// of scanned mail per day
+        #[arg(long)]
+        detailed: bool,
+
+        /// Days shown in the `--detailed` timeline
+        #[arg(long, default_value_t = DEFAULT_TIMELINE_DAYS)]
+        days: usize,
+    },
+
+    /// Show how created filters changed and what they matched over time
+    FilterHistory {
+        /// Filter to show the full timeline of; lists every filter by trend when omitted
+        filter_id: Option<String>,
+
+        /// Only consider changes and match counts from this date on (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+    },
+
+    /// Move all messages from one or more labels into a single label
+    MergeLabels {
+        /// Label names to merge from (repeat for several labels)
+        #[arg(long, required = true)]
+        source: Vec<String>,
+
+        /// Label name to merge into (created if it doesn't exist)
+        #[arg(long)]
+        target: String,
+
+        /// Only report how many messages would be moved
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Delete the source labels once their messages have been moved
+        #[arg(long)]
+        delete_sources: bool,
+    },
+
+    /// Convert filters from another mail client into a decisions file for `run --apply-decisions`
+    Import {
+        /// Mail client the filters come from
+        #[arg(long, value_enum)]
+        source: ImportSource,
+
+        /// Filter file to import (Thunderbird: `msgFilterRules.dat` in the account folder)
+        path: PathBuf,
+
+        /// Path to write the decisions file to
+        #[arg(
+            short,
+            long,
+            default_value = ".gmail-automation/imported-decisions.json"
+        )]
+        output: PathBuf,
+    },
+
+    /// Export classification results or the full state for external analysis
+    Export {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+
+        /// Path to write the export to
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Only scan the mailbox and save the message metadata, changing nothing
+    ///
+    /// The saved scan can be classified offline with `classify`.
+    Scan {
+        /// Days of mail to scan (defaults to scan.period_days)
+        #[arg(long)]
+        period_days: Option<u32>,
+
+        /// File to save the scanned messages to
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = ScanFormat::Json)]
+        format: ScanFormat,
+    },
+
+    /// Classify the messages of a saved JSON scan without calling the Gmail API
+    Classify {
+        /// Scan file written by `scan --format json`
+        input: PathBuf,
+
+        /// Print every classification, with the reasons behind it, as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Compare the reports of two previous runs (by run ID)
+    Compare {
+        /// Earlier run
+        run_a: String,
+
+        /// Later run
+        run_b: String,
+    },
+
+    /// Delete old reports, state snapshots and decision files, keeping the most recent
+    Cleanup {
+        /// Number of files of each kind to keep
+        #[arg(long, default_value_t = 10)]
+        keep_last: usize,
+
+        /// List the files that would be deleted without deleting them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Delete without a confirmation prompt
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Combine the state files of two partial runs (e.g. over different scan periods)
+    MergeStates {
+        /// First state file
+        state_a: PathBuf,
+
+        /// Second state file
+        state_b: PathBuf,
+
+        /// Path to write the merged state to
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Suggest classification rules for domains whose labels were corrected in review
+    GenerateRules {
+        /// Corrections needed from a domain before a rule is suggested
+        #[arg(long, default_value_t = 5)]
+        min_feedback: usize,
+    },
+
+    /// Compare the two classification modes of an A/B test log
+    AbTestReport {
+        /// Log written by runs with `classification.ab_test` configured
+        #[arg(long, default_value = ".gmail-automation/ab-test.jsonl")]
+        log: PathBuf,
+    },
+
+    /// Unsubscribe from senders using the List-Unsubscribe links found by the last scan
+    Unsubscribe {
+        /// Sender domain to unsubscribe from, including subdomains (repeatable)
+        #[arg(long, required = true)]
+        domain: Vec<String>,
+
+        /// Only show which links would be used
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Manage saved cluster exclusions
+    #[command(alias = "exclusion-list")]
+    Exclusions {
+        #[command(subcommand)]
+        action: ExclusionAction,
     },
 
     /// Generate example configuration file
@@ -129,11 +377,178 @@ pub enum Commands {
         /// Force deletion without confirmation prompt
         #[arg(long)]
         force: bool,
+
+        /// Count the messages that would lose each deleted label (one search per label)
+        #[arg(long)]
+        count_messages: bool,
+    },
+}
+
+/// Actions for the exclusions command
+#[derive(Subcommand, Debug)]
+pub enum ExclusionAction {
+    /// List saved exclusions
+    List {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+
+        /// Include exclusions that have expired but not been pruned yet
+        #[arg(long)]
+        show_expired: bool,
+    },
+
+    /// Write all exclusions to a JSON file
+    Export {
+        /// Path to write the exclusions to
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Load exclusions from a JSON file written by `exclusions export`
+    Import {
+        /// Path to read the exclusions from
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Merge with existing exclusions instead of replacing them
+        #[arg(long)]
+        merge: bool,
     },
+
+    /// Exclude a cluster key (e.g. "*@example.com") or a regex of cluster keys
+    Add {
+        /// Cluster key to exclude, or a regex with --pattern
+        key: String,
+
+        /// Reason for the exclusion
+        #[arg(short, long)]
+        reason: Option<String>,
+
+        /// Expire the exclusion after this many days (default: never)
+        #[arg(long, visible_alias = "days")]
+        expires_days: Option<u32>,
+
+        /// Treat the key as a regex matched against cluster keys
+        #[arg(long)]
+        pattern: bool,
+    },
+
+    /// Remove an exclusion by key
+    Remove {
+        /// Cluster key or pattern to remove
+        key: String,
+    },
+
+    /// Remove all exclusions
+    Clear,
+
+    /// Remove exclusions that have expired
+    Prune,
+}
+
+/// Output format for list-style commands
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable table
+    Table,
+    /// Pretty-printed JSON
+    Json,
+}
+
+/// Output format for the scan command
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScanFormat {
+    /// Scan header and messages as pretty-printed JSON (readable by `classify`)
+    Json,
+    /// One row per message (for spreadsheets)
+    Csv,
+}
+
+/// Mail client the import command reads filters from
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportSource {
+    /// Thunderbird message filters (`msgFilterRules.dat`)
+    Thunderbird,
+}
+
+/// Output format for the export command
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One row per classified message (for spreadsheets / pandas)
+    Csv,
+    /// The full processing state as pretty-printed JSON
+    Json,
 }
 
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use std::time::Duration;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Longest tag accepted by `--tag`
+const MAX_TAG_LEN: usize = 64;
+
+/// Validate a `--tag` value: non-empty and at most 64 characters
+fn parse_tag(tag: &str) -> std::result::Result<String, String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("tag must not be empty".to_string());
+    }
+    if tag.chars().count() > MAX_TAG_LEN {
+        return Err(format!("tag must be at most {} characters", MAX_TAG_LEN));
+    }
+    Ok(tag.to_string())
+}
+
+/// Inline stylesheet of the HTML report
+const REPORT_CSS: &str = "<style>
+body { font-family: -apple-system, 'Segoe UI', Roboto, sans-serif; margin: 2em auto; max-width: 960px; color: #202124; padding: 0 1em; }
+h1 { font-size: 1.6em; }
+h2 { font-size: 1.2em; margin-top: 2em; border-bottom: 1px solid #dadce0; padding-bottom: 0.3em; }
+.muted { color: #5f6368; }
+.warning { background: #fef7e0; border-left: 4px solid #f9ab00; padding: 0.6em 1em; }
+.summary { display: grid; grid-template-columns: repeat(auto-fit, minmax(150px, 1fr)); gap: 0.8em; }
+.metric { background: #f1f3f4; border-radius: 8px; padding: 0.8em 1em; }
+.metric .value { font-size: 1.5em; font-weight: 600; }
+.metric .name { color: #5f6368; font-size: 0.85em; }
+.chart text { font-size: 13px; fill: #202124; }
+.chart rect { fill: #1a73e8; }
+table { border-collapse: collapse; width: 100%; margin: 0.5em 0; }
+th, td { text-align: left; padding: 0.35em 0.6em; border-bottom: 1px solid #e8eaed; }
+td.num { text-align: right; }
+code { font-size: 0.9em; }
+details { margin: 0.4em 0; }
+summary { cursor: pointer; font-weight: 500; }
+</style>
+";
+
+/// Escape text for use in HTML element content and attribute values
+fn html_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Percent-encode text for use in a URL
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
 
 /// Truncate a string to max_len characters, adding "..." if truncated
 fn truncate_string(s: &str, max_len: usize) -> String {
@@ -150,7 +565,7 @@ fn truncate_string(s: &str, max_len: usize) -> String {
 }
 
 /// Format a number with commas as thousands separator
-fn format_number(n: u64) -> String {
+pub fn format_number(n: u64) -> String {
     let s = n.to_string();
     let chars: Vec<char> = s.chars().collect();
     let mut result = String::new();
@@ -165,6 +580,80 @@ fn format_number(n: u64) -> String {
     result
 }
 
+/// Most messages counted per label by `unmanage --count-messages`
+pub const UNMANAGE_COUNT_LIMIT: usize = 10_000;
+
+/// Days shown by the email volume timeline of `stats --detailed`
+pub const DEFAULT_TIMELINE_DAYS: usize = 30;
+
+/// Render messages per day as bars, e.g. `2024-01-15 | ████████░░░░ 142`
+///
+/// Shows the `days` days up to the newest message; bars are scaled so the busiest
+/// day fills `bar_width`, and days without mail are shown empty.
+pub fn render_email_timeline(
+    messages: &[MessageMetadata],
+    days: usize,
+    bar_width: usize,
+) -> String {
+    let Some(last_day) = messages.iter().map(|m| m.date_received.date_naive()).max() else {
+        return String::new();
+    };
+    let first_day = last_day - chrono::Duration::days(days.saturating_sub(1) as i64);
+
+    let mut per_day: std::collections::BTreeMap<chrono::NaiveDate, usize> = first_day
+        .iter_days()
+        .take_while(|day| *day <= last_day)
+        .map(|day| (day, 0))
+        .collect();
+    for message in messages {
+        if let Some(count) = per_day.get_mut(&message.date_received.date_naive()) {
+            *count += 1;
+        }
+    }
+
+    let peak = per_day.values().copied().max().unwrap_or(0).max(1);
+    let count_width = peak.to_string().len();
+    per_day
+        .iter()
+        .map(|(day, &count)| {
+            // Round up so every day with mail shows at least one block
+            let filled = (count * bar_width + peak - 1) / peak;
+            format!(
+                "{} | {}{} {:>width$}",
+                day.format("%Y-%m-%d"),
+                "█".repeat(filled),
+                "░".repeat(bar_width - filled),
+                count,
+                width = count_width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Built-in spinner template; {elapsed} is human-readable (e.g., "1s", "234ms")
+pub const DEFAULT_SPINNER_TEMPLATE: &str = "{spinner:.green} [{elapsed:>6}] {msg}";
+
+/// Built-in progress bar template
+pub const DEFAULT_PROGRESS_BAR_TEMPLATE: &str =
+    "[{elapsed:>6}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}";
+
+/// Style from a configured template, falling back to `default` if it doesn't parse
+///
+/// `Config::validate` rejects bad templates, so the fallback only covers configs
+/// built in code.
+fn progress_style(template: Option<&str>, default: &str) -> ProgressStyle {
+    template
+        .and_then(|template| match ProgressStyle::with_template(template) {
+            Ok(style) => Some(style),
+            Err(e) => {
+                warn!("Invalid progress template '{}': {}", template, e);
+                None
+            }
+        })
+        .unwrap_or_else(|| ProgressStyle::with_template(default).unwrap())
+}
+
 /// Progress reporter using indicatif
 pub struct ProgressReporter {
     multi: MultiProgress,
@@ -179,16 +668,26 @@ impl ProgressReporter {
 
     /// Create a ProgressReporter with a shared MultiProgress (for tracing-indicatif integration)
     pub fn with_multi_progress(multi: MultiProgress) -> Self {
-        // Use {elapsed} for human-readable format (e.g., "1s", "234ms")
-        let spinner_style = ProgressStyle::default_spinner()
-            .template("{spinner:.green} [{elapsed:>6}] {msg}")
-            .unwrap()
-            .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ");
+        Self::with_ui_config(multi, &UiConfig::default())
+    }
 
-        let bar_style = ProgressStyle::default_bar()
-            .template("[{elapsed:>6}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
-            .unwrap()
-            .progress_chars("##-");
+    /// Create a ProgressReporter styled by the `[ui]` settings
+    pub fn with_ui_config(multi: MultiProgress, ui: &UiConfig) -> Self {
+        let tick_chars = if ui.use_unicode_bars {
+            "⠁⠂⠄⡀⢀⠠⠐⠈ "
+        } else {
+            "|/-\\ "
+        };
+
+        let spinner_style =
+            progress_style(ui.spinner_template.as_deref(), DEFAULT_SPINNER_TEMPLATE)
+                .tick_chars(tick_chars);
+
+        let bar_style = progress_style(
+            ui.progress_bar_template.as_deref(),
+            DEFAULT_PROGRESS_BAR_TEMPLATE,
+        )
+        .progress_chars("##-");
 
         Self {
             multi,
@@ -223,6 +722,12 @@ impl ProgressReporter {
         pb.finish_and_clear();
         println!("  ✓ {}", msg);
     }
+
+    /// Print a warning line in yellow
+    pub fn warn(&self, msg: &str) {
+        use crossterm::style::Stylize;
+        println!("  {} {}", "!".yellow(), msg.yellow());
+    }
 }
 
 impl Default for ProgressReporter {
@@ -233,21 +738,25 @@ impl Default for ProgressReporter {
 
 /// Report data structure
 /// Planned filter to be created (for dry run reporting)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PlannedFilter {
     pub name: String,
     pub from_pattern: Option<String>,
     pub subject_keywords: Vec<String>,
     pub target_label: String,
+    /// Secondary labels applied together with `target_label`
+    pub additional_labels: Vec<String>,
     pub should_archive: bool,
     pub estimated_matches: usize,
+    /// Creation order (lower = created first)
+    pub priority: u32,
     pub gmail_query: String,
     /// Actual emails that match this filter query (from live API query)
     pub actual_matches: usize,
 }
 
 /// Planned changes for dry run mode
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct PlannedChanges {
     /// Labels that would be newly created
     pub new_labels: Vec<String>,
@@ -258,6 +767,7 @@ pub struct PlannedChanges {
     pub messages_to_archive: usize,
 }
 
+#[derive(Serialize)]
 pub struct Report {
     pub run_id: String,
     pub started_at: chrono::DateTime<chrono::Utc>,
@@ -283,19 +793,250 @@ pub struct Report {
     pub messages_cleaned: usize,
     /// Number of hierarchy labels created to repair missing parents
     pub hierarchy_labels_created: usize,
+    /// Number of messages whose labels could not be applied, even after retrying
+    pub failed_count: usize,
+    /// IDs of the messages counted in `failed_count`
+    pub failed_message_ids: Vec<String>,
+    /// Number of retries made for messages whose fetch failed
+    pub retry_attempts: usize,
+    /// Number of messages that could not be fetched, even after retrying
+    pub permanently_failed: usize,
+    /// Tracked filters with no recent matches: (query, label_name)
+    pub stale_filters: Vec<(String, String)>,
     pub classification_breakdown: Vec<(String, usize, f32)>,
-    pub top_senders: Vec<(String, usize, String)>,
+    /// Number of bounced-mail notices among the classified messages
+    pub bounces_detected: usize,
+    /// Weeks in which a sender domain sent far more mail than usual
+    pub anomalies: Vec<VolumeSpike>,
+    pub top_senders: Vec<TopSender>,
     /// Examples per category: category -> [(sender_email, subject)]
     pub category_examples: HashMap<String, Vec<(String, String)>>,
     /// Whether this was a dry run
     pub dry_run: bool,
     /// Planned changes (only populated in dry run mode)
     pub planned_changes: Option<PlannedChanges>,
+    /// Gmail API calls and quota units per method, e.g. "messages.get"
+    pub api_usage: BTreeMap<String, QuotaBreakdown>,
+    /// Names of every label the run applied, created or already existing
+    pub label_names: Vec<String>,
+    /// Seconds spent in each pipeline phase, in pipeline order
+    pub phase_timings: Vec<(String, u64)>,
+}
+
+/// Sender domains listed in a report's top senders
+pub const TOP_SENDERS: usize = 10;
+
+/// A sender domain among the busiest of a run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopSender {
+    pub domain: String,
+    pub count: usize,
+    /// Category of the first message seen from the domain
+    pub category: String,
+}
+
+impl TopSender {
+    /// The `TOP_SENDERS` domains with the most messages, given each message's
+    /// sender domain and category
+    pub fn top<'a>(messages: impl IntoIterator<Item = (&'a str, &'a EmailCategory)>) -> Vec<Self> {
+        let mut senders: Vec<TopSender> = Vec::new();
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        for (domain, category) in messages {
+            match index.get(domain) {
+                Some(&i) => senders[i].count += 1,
+                None => {
+                    index.insert(domain, senders.len());
+                    senders.push(TopSender {
+                        domain: domain.to_string(),
+                        count: 1,
+                        category: format!("{:?}", category),
+                    });
+                }
+            }
+        }
+        senders.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.domain.cmp(&b.domain)));
+        senders.truncate(TOP_SENDERS);
+        senders
+    }
+}
+
+/// Differences between two runs, from `Report::compare`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunComparison {
+    pub run_a_id: String,
+    pub run_b_id: String,
+    /// Labels of run B that run A didn't have
+    pub new_labels: Vec<String>,
+    /// Labels of run A that run B doesn't have
+    pub removed_labels: Vec<String>,
+    pub new_filter_count_delta: i64,
+    pub emails_scanned_delta: i64,
+    pub duration_delta_secs: i64,
+    /// Top sender domains of run B that weren't among run A's, with their counts
+    pub top_new_senders: Vec<(String, usize)>,
+}
+
+impl RunComparison {
+    pub fn to_markdown(&self) -> String {
+        fn signed(delta: i64) -> String {
+            format!("{:+}", delta)
+        }
+
+        let mut md = format!(
+            "# Run Comparison\n\n`{}` → `{}`\n\n",
+            self.run_a_id, self.run_b_id
+        );
+        md.push_str("| Metric | Change |\n|--------|--------|\n");
+        md.push_str(&format!(
+            "| Emails scanned | {} |\n",
+            signed(self.emails_scanned_delta)
+        ));
+        md.push_str(&format!(
+            "| Filters created | {} |\n",
+            signed(self.new_filter_count_delta)
+        ));
+        md.push_str(&format!(
+            "| Duration (seconds) | {} |\n\n",
+            signed(self.duration_delta_secs)
+        ));
+
+        for (title, labels) in [
+            ("New Labels", &self.new_labels),
+            ("Removed Labels", &self.removed_labels),
+        ] {
+            md.push_str(&format!("## {}\n\n", title));
+            if labels.is_empty() {
+                md.push_str("_None_\n\n");
+                continue;
+            }
+            for label in labels {
+                md.push_str(&format!("- `{}`\n", label));
+            }
+            md.push('\n');
+        }
+
+        md.push_str("## New Top Senders\n\n");
+        if self.top_new_senders.is_empty() {
+            md.push_str("_None_\n");
+        }
+        for (domain, count) in &self.top_new_senders {
+            md.push_str(&format!("- **{}** ({} emails)\n", domain, count));
+        }
+        md
+    }
 }
 
+/// JSON schema of [`Report::to_json`], saved next to JSON reports
+pub const REPORT_SCHEMA: &str = include_str!("report-schema.json");
+
 impl Report {
+    /// Rebuild the report of a finished run from its saved state
+    ///
+    /// Only what the state records is filled in: counts, labels, classifications
+    /// and timings. Cleanup details, planned changes and API usage are left empty.
+    pub fn from_state(state: &ProcessingState) -> Self {
+        let mut category_counts: HashMap<String, usize> = HashMap::new();
+        let mut category_examples: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        for classification in &state.classifications {
+            let category = format!("{:?}", classification.category);
+            *category_counts.entry(category.clone()).or_default() += 1;
+            let examples = category_examples.entry(category).or_default();
+            if examples.len() < 10 {
+                examples.push((
+                    classification.sender_email.clone(),
+                    classification.subject.clone(),
+                ));
+            }
+        }
+        let total = state.classifications.len() as f32;
+        let mut classification_breakdown: Vec<_> = category_counts
+            .into_iter()
+            .map(|(category, count)| (category, count, count as f32 / total * 100.0))
+            .collect();
+        classification_breakdown.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Self {
+            run_id: state.run_id.clone(),
+            started_at: state.started_at,
+            completed_at: state.updated_at,
+            duration_seconds: state.duration_secs(),
+            emails_scanned: state.messages_scanned,
+            emails_classified: state.messages_classified,
+            labels_created: state.labels_created.len(),
+            filters_created: state.filters_created.len(),
+            messages_modified: state.messages_modified,
+            messages_archived: 0,
+            orphaned_filters_found: 0,
+            filters_deleted: 0,
+            filters_to_delete: Vec::new(),
+            orphaned_labels_deleted: 0,
+            orphaned_labels_to_delete: Vec::new(),
+            messages_cleaned: 0,
+            hierarchy_labels_created: 0,
+            failed_count: state.failed_message_ids.len(),
+            failed_message_ids: state.failed_message_ids.clone(),
+            retry_attempts: 0,
+            permanently_failed: state.permanently_failed_ids.len(),
+            stale_filters: Vec::new(),
+            bounces_detected: state
+                .classifications
+                .iter()
+                .filter(|c| c.category == EmailCategory::Bounce)
+                .count(),
+            classification_breakdown,
+            anomalies: Vec::new(),
+            top_senders: TopSender::top(
+                state
+                    .classifications
+                    .iter()
+                    .map(|c| (c.sender_domain.as_str(), &c.category)),
+            ),
+            category_examples,
+            dry_run: false,
+            planned_changes: None,
+            api_usage: BTreeMap::new(),
+            label_names: state.labels_applied.clone(),
+            phase_timings: state.phase_breakdown(),
+        }
+    }
+
+    /// Compare this run (A) against a later run (B)
+    pub fn compare(&self, other: &Report) -> RunComparison {
+        let labels_a: HashSet<&String> = self.label_names.iter().collect();
+        let labels_b: HashSet<&String> = other.label_names.iter().collect();
+        let mut new_labels: Vec<String> = labels_b
+            .difference(&labels_a)
+            .map(|l| l.to_string())
+            .collect();
+        new_labels.sort();
+        let mut removed_labels: Vec<String> = labels_a
+            .difference(&labels_b)
+            .map(|l| l.to_string())
+            .collect();
+        removed_labels.sort();
+
+        let senders_a: HashSet<&str> = self.top_senders.iter().map(|s| s.domain.as_str()).collect();
+        let top_new_senders = other
+            .top_senders
+            .iter()
+            .filter(|s| !senders_a.contains(s.domain.as_str()))
+            .map(|s| (s.domain.clone(), s.count))
+            .collect();
+
+        RunComparison {
+            run_a_id: self.run_id.clone(),
+            run_b_id: other.run_id.clone(),
+            new_labels,
+            removed_labels,
+            new_filter_count_delta: other.filters_created as i64 - self.filters_created as i64,
+            emails_scanned_delta: other.emails_scanned as i64 - self.emails_scanned as i64,
+            duration_delta_secs: other.duration_seconds - self.duration_seconds,
+            top_new_senders,
+        }
+    }
+
     /// Generate Markdown report
-    pub fn to_markdown(&self) -> String {
+    pub fn to_markdown(&self, previous_run: Option<&StateDiff>) -> String {
         let mut md = String::new();
 
         if self.dry_run {
@@ -309,6 +1050,10 @@ impl Report {
             self.completed_at.format("%Y-%m-%d %H:%M:%S")
         ));
 
+        if let Some(diff) = previous_run {
+            md.push_str(&diff.to_markdown());
+        }
+
         md.push_str("## Summary\n\n");
         md.push_str(&format!("- **Run ID:** {}\n", self.run_id));
         md.push_str(&format!("- **Emails scanned:** {}\n", self.emails_scanned));
@@ -317,11 +1062,30 @@ impl Report {
             self.duration_seconds / 60,
             self.duration_seconds % 60
         ));
+        if self.retry_attempts > 0 {
+            md.push_str(&format!("- **Fetch retries:** {}\n", self.retry_attempts));
+        }
+        if self.permanently_failed > 0 {
+            md.push_str(&format!(
+                "- **Messages that could not be fetched:** {}\n",
+                self.permanently_failed
+            ));
+        }
         if self.dry_run {
             md.push_str("- **Mode:** Dry Run (preview only)\n");
         }
         md.push('\n');
 
+        if !self.phase_timings.is_empty() {
+            md.push_str("## Phase Breakdown\n\n");
+            md.push_str("| Phase | Duration |\n");
+            md.push_str("|-------|----------|\n");
+            for (phase, secs) in &self.phase_timings {
+                md.push_str(&format!("| {} | {}s |\n", phase, secs));
+            }
+            md.push('\n');
+        }
+
         // If dry run, show planned changes prominently
         if let Some(ref planned) = self.planned_changes {
             md.push_str("## Planned Changes\n\n");
@@ -359,8 +1123,12 @@ impl Report {
             if planned.filters.is_empty() {
                 md.push_str("_No filters would be created._\n\n");
             } else {
-                md.push_str("| Filter Name | Gmail Query | Archive | Emails Matched |\n");
-                md.push_str("|-------------|-------------|---------|----------------|\n");
+                md.push_str(
+                    "| Priority | Filter Name | Gmail Query | Additional Labels | Archive | Emails Matched |\n",
+                );
+                md.push_str(
+                    "|----------|-------------|-------------|-------------------|---------|----------------|\n",
+                );
                 let mut total_to_archive = 0;
                 for filter in &planned.filters {
                     let archive_str = if filter.should_archive { "Yes" } else { "No" };
@@ -369,9 +1137,19 @@ impl Report {
                     }
                     // Escape pipes in query
                     let escaped_query = filter.gmail_query.replace('|', "\\|");
+                    let additional_labels = if filter.additional_labels.is_empty() {
+                        "-".to_string()
+                    } else {
+                        filter.additional_labels.join(", ")
+                    };
                     md.push_str(&format!(
-                        "| {} | `{}` | {} | {} |\n",
-                        filter.name, escaped_query, archive_str, filter.actual_matches
+                        "| {} | {} | `{}` | {} | {} | {} |\n",
+                        filter.priority,
+                        filter.name,
+                        escaped_query,
+                        additional_labels,
+                        archive_str,
+                        filter.actual_matches
                     ));
                 }
                 md.push_str(&format!(
@@ -456,6 +1234,29 @@ impl Report {
         }
 
         md.push_str("## Classification Results\n\n");
+        if self.bounces_detected > 0 {
+            md.push_str(&format!(
+                "**Bounces detected:** {} (delivery failure notices, not used for filters unless \
+                 `classification.create_bounce_filters` is set)\n\n",
+                self.bounces_detected
+            ));
+        }
+        if !self.anomalies.is_empty() {
+            md.push_str("### Volume Anomalies\n\n");
+            md.push_str("Senders whose weekly volume jumped above 3× their usual rate:\n\n");
+            md.push_str("| Domain | Usual per Week | Emails in Spike Week | Busiest Day |\n");
+            md.push_str("|--------|----------------|----------------------|-------------|\n");
+            for spike in &self.anomalies {
+                md.push_str(&format!(
+                    "| {} | {:.1} | {} | {} |\n",
+                    spike.sender_domain,
+                    spike.baseline_per_week,
+                    spike.spike_count,
+                    spike.spike_date
+                ));
+            }
+            md.push('\n');
+        }
         for (category, count, percentage) in &self.classification_breakdown {
             md.push_str(&format!(
                 "### {} — {} emails ({:.1}%)\n\n",
@@ -503,9 +1304,16 @@ impl Report {
                 self.messages_archived
             ));
             md.push_str(&format!(
-                "- **Messages kept in inbox:** {}\n\n",
+                "- **Messages kept in inbox:** {}\n",
                 self.emails_scanned - self.messages_archived
             ));
+            if self.failed_count > 0 {
+                md.push_str(&format!(
+                    "- **Messages with label application errors:** {}\n",
+                    self.failed_count
+                ));
+            }
+            md.push('\n');
 
             // Cleanup section (only show if any cleanup happened)
             if self.orphaned_filters_found > 0 || self.filters_deleted > 0 || self.orphaned_labels_deleted > 0 {
@@ -527,16 +1335,25 @@ impl Report {
                 }
                 md.push('\n');
             }
+
+            if !self.stale_filters.is_empty() {
+                md.push_str("## Stale Filters\n\n");
+                md.push_str("These filters matched no messages in the latest check and may be safe to remove:\n\n");
+                for (query, label) in &self.stale_filters {
+                    md.push_str(&format!("- `{}` → {}\n", query, label));
+                }
+                md.push('\n');
+            }
         }
 
         md.push_str("## Top Senders\n\n");
-        for (i, (sender, count, label)) in self.top_senders.iter().enumerate() {
+        for (i, sender) in self.top_senders.iter().enumerate() {
             md.push_str(&format!(
                 "{}. **{}** ({} emails) → {}\n",
                 i + 1,
-                sender,
-                count,
-                label
+                sender.domain,
+                sender.count,
+                sender.category
             ));
         }
         md.push('\n');
@@ -551,45 +1368,267 @@ impl Report {
         md
     }
 
+    /// Generate a self-contained HTML report (inline CSS and SVG, no external resources)
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let title = if self.dry_run {
+            "Email Management Report (DRY RUN)"
+        } else {
+            "Email Management Report"
+        };
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n", title));
+        html.push_str(REPORT_CSS);
+        html.push_str("</head>\n<body>\n");
+        html.push_str(&format!("<h1>{}</h1>\n", title));
+        if self.dry_run {
+            html.push_str(
+                "<p class=\"warning\">Dry run: no changes were made. This report shows what would happen.</p>\n",
+            );
+        }
+        html.push_str(&format!(
+            "<p class=\"muted\">Run {} &middot; generated {}</p>\n",
+            html_escape(&self.run_id),
+            self.completed_at.format("%Y-%m-%d %H:%M:%S")
+        ));
+
+        // Key metrics
+        let mut metrics = vec![
+            ("Emails scanned", format_number(self.emails_scanned as u64)),
+            (
+                "Emails classified",
+                format_number(self.emails_classified as u64),
+            ),
+            (
+                "Processing time",
+                format!(
+                    "{}m {}s",
+                    self.duration_seconds / 60,
+                    self.duration_seconds % 60
+                ),
+            ),
+        ];
+        match &self.planned_changes {
+            Some(planned) => {
+                metrics.push(("Labels to create", planned.new_labels.len().to_string()));
+                metrics.push(("Filters to create", planned.filters.len().to_string()));
+                metrics.push((
+                    "Messages to archive",
+                    format_number(planned.messages_to_archive as u64),
+                ));
+            }
+            None => {
+                metrics.push(("Labels created", self.labels_created.to_string()));
+                metrics.push(("Filters created", self.filters_created.to_string()));
+                metrics.push((
+                    "Messages archived",
+                    format_number(self.messages_archived as u64),
+                ));
+            }
+        }
+        html.push_str("<div class=\"summary\">\n");
+        for (name, value) in metrics {
+            html.push_str(&format!(
+                "<div class=\"metric\"><div class=\"value\">{}</div><div class=\"name\">{}</div></div>\n",
+                value, name
+            ));
+        }
+        html.push_str("</div>\n");
+
+        // Category bar chart, bars scaled by each category's share of messages
+        if !self.classification_breakdown.is_empty() {
+            const ROW_HEIGHT: usize = 26;
+            const LABEL_WIDTH: usize = 180;
+            const BAR_WIDTH: f32 = 400.0;
+            let height = self.classification_breakdown.len() * ROW_HEIGHT;
+            html.push_str("<h2>Categories</h2>\n");
+            html.push_str(&format!(
+                "<svg class=\"chart\" width=\"{}\" height=\"{}\" role=\"img\">\n",
+                LABEL_WIDTH + BAR_WIDTH as usize + 120,
+                height
+            ));
+            for (i, (category, count, percentage)) in
+                self.classification_breakdown.iter().enumerate()
+            {
+                let y = i * ROW_HEIGHT;
+                let width = (percentage / 100.0 * BAR_WIDTH).clamp(0.0, BAR_WIDTH);
+                html.push_str(&format!(
+                    "<text x=\"0\" y=\"{}\">{}</text>\n<rect x=\"{}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" rx=\"3\"/>\n<text x=\"{:.1}\" y=\"{}\">{} ({:.1}%)</text>\n",
+                    y + 17,
+                    html_escape(category),
+                    LABEL_WIDTH,
+                    y + 4,
+                    width,
+                    ROW_HEIGHT - 8,
+                    LABEL_WIDTH as f32 + width + 6.0,
+                    y + 17,
+                    count,
+                    percentage
+                ));
+            }
+            html.push_str("</svg>\n");
+        }
+
+        // Filters that a real run would create
+        if let Some(planned) = self.planned_changes.as_ref().filter(|_| self.dry_run) {
+            html.push_str("<h2>Filters to Create</h2>\n");
+            if planned.filters.is_empty() {
+                html.push_str("<p class=\"muted\">No filters would be created.</p>\n");
+            } else {
+                html.push_str("<table>\n<tr><th>Priority</th><th>Filter</th><th>Gmail Query</th><th>Archive</th><th>Emails Matched</th></tr>\n");
+                for filter in &planned.filters {
+                    html.push_str(&format!(
+                        "<tr><td>{}</td><td>{}</td><td><code>{}</code></td><td>{}</td><td class=\"num\">{}</td></tr>\n",
+                        filter.priority,
+                        html_escape(&filter.name),
+                        html_escape(&filter.gmail_query),
+                        if filter.should_archive { "Yes" } else { "No" },
+                        filter.actual_matches
+                    ));
+                }
+                html.push_str("</table>\n");
+            }
+        }
+
+        if !self.top_senders.is_empty() {
+            html.push_str("<h2>Top Senders</h2>\n");
+            html.push_str("<table>\n<tr><th>Sender</th><th>Emails</th><th>Category</th></tr>\n");
+            for sender in &self.top_senders {
+                html.push_str(&format!(
+                    "<tr><td><a href=\"https://mail.google.com/mail/u/0/#search/{}\">{}</a></td><td class=\"num\">{}</td><td>{}</td></tr>\n",
+                    percent_encode(&format!("from:{}", sender.domain)),
+                    html_escape(&sender.domain),
+                    sender.count,
+                    html_escape(&sender.category)
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        if !self.phase_timings.is_empty() {
+            html.push_str("<h2>Phase Breakdown</h2>\n");
+            html.push_str("<table>\n<tr><th>Phase</th><th>Duration</th></tr>\n");
+            for (phase, secs) in &self.phase_timings {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td class=\"num\">{}s</td></tr>\n",
+                    html_escape(phase),
+                    secs
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        // Examples stay collapsed so large reports remain readable
+        let with_examples: Vec<_> = self
+            .classification_breakdown
+            .iter()
+            .filter_map(|(category, count, _)| {
+                self.category_examples
+                    .get(category)
+                    .filter(|examples| !examples.is_empty())
+                    .map(|examples| (category, count, examples))
+            })
+            .collect();
+        if !with_examples.is_empty() {
+            html.push_str("<h2>Classification Examples</h2>\n");
+            for (category, count, examples) in with_examples {
+                html.push_str(&format!(
+                    "<details>\n<summary>{} ({} emails)</summary>\n<table>\n<tr><th>Sender</th><th>Subject</th></tr>\n",
+                    html_escape(category),
+                    count
+                ));
+                for (sender, subject) in examples.iter().take(10) {
+                    html.push_str(&format!(
+                        "<tr><td>{}</td><td>{}</td></tr>\n",
+                        html_escape(sender),
+                        html_escape(subject)
+                    ));
+                }
+                html.push_str("</table>\n</details>\n");
+            }
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Report data as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| GmailError::Unknown(format!("Failed to serialize report: {}", e)))
+    }
+
+    /// Save the JSON report to `path`, with `report-schema.json` next to it
+    pub async fn save_json(&self, path: &Path) -> Result<()> {
+        tokio::fs::write(path, self.to_json()?).await?;
+        tokio::fs::write(path.with_file_name("report-schema.json"), REPORT_SCHEMA).await?;
+        Ok(())
+    }
+
     /// Save report to file
-    pub async fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
-        let markdown = self.to_markdown();
+    pub async fn save(
+        &self,
+        path: &std::path::Path,
+        previous_run: Option<&StateDiff>,
+    ) -> std::io::Result<()> {
+        let markdown = self.to_markdown(previous_run);
         tokio::fs::write(path, markdown).await?;
         Ok(())
     }
 }
 
+use crate::anomaly::{AnomalyDetector, VolumeSpike};
 use crate::auth;
-use crate::classifier::EmailClassifier;
-use crate::client::ExistingFilterInfo;
-use crate::config::Config;
-use crate::error::{GmailError, Result};
+use crate::backup;
+use crate::classifier::{AgreementResult, DomainStats, EmailClassifier, FeedbackEntry};
+use crate::client::{BatchModifyResult, ExistingFilterInfo, MAX_PAGE_SIZE};
+use crate::config::{ClassificationMode, Config, ReportFormat, UiConfig};
+use crate::error::{Context, GmailError, Result};
 use crate::exclusions::ExclusionManager;
 use crate::filter_manager::FilterManager;
+use crate::filter_stats::FilterStatsStore;
 use crate::interactive::{
-    create_clusters, ClusterDecision, ClusterSource, DecisionAction, EmailCluster, ReviewSession,
+    auto_merge_domain_clusters, create_clusters, resolve_merges, ClusterDecision, ClusterSource,
+    DecisionAction, EmailCluster, ReviewSession, Theme,
+};
+use crate::label_manager::{LabelManager, LabelProgressCallback};
+use crate::models::{
+    Classification, EmailCategory, FilterRule, LabelId, MessageMetadata, DEFAULT_FILTER_PRIORITY,
+};
+use crate::rate_limiter::QuotaBreakdown;
+use crate::scanner::{ListedPages, ScanCheckpoint, ScanOutput};
+use crate::state::{
+    FailedFetch, ProcessingPhase, ProcessingState, SerializedClassification, StateDiff, StateGuard,
+    StateManager, MAX_FETCH_RETRIES,
 };
-use crate::label_manager::LabelManager;
-use crate::models::{Classification, FilterRule, MessageMetadata};
-use crate::state::{ProcessingPhase, ProcessingState};
 use chrono::Utc;
-use std::collections::HashMap;
+use futures::{StreamExt, TryStreamExt};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{self, Write};
 use std::path::Path;
 
 /// Get a unique key for a cluster (mirrors ReviewSession::cluster_key)
 fn cluster_key(cluster: &EmailCluster) -> String {
-    let base = if cluster.is_specific_sender {
-        cluster.sender_email.clone()
-    } else {
-        format!("*@{}", cluster.sender_domain)
-    };
+    cluster.key()
+}
 
-    // Include subject pattern in key to differentiate subject-based clusters
-    if let Some(subject) = &cluster.subject_pattern {
-        format!("{}|subject:{}", base, subject)
+/// Key tying a filter generated from a review decision back to that decision
+///
+/// Built from the filter's criteria, so the decision side uses the criteria its
+/// filter is generated with (`ClusterDecision::filter_from_pattern` and
+/// `ClusterDecision::filter_extra_terms`).
+fn decision_filter_key(
+    from_pattern: Option<&str>,
+    subject_keywords: &[String],
+    extra_terms: &[String],
+) -> String {
+    let base = from_pattern.unwrap_or_default();
+    if !extra_terms.is_empty() {
+        format!("{}|{}", base, extra_terms.join(" "))
+    } else if !subject_keywords.is_empty() {
+        format!("{}|subject:{}", base, subject_keywords.join(" "))
     } else {
-        base
+        base.to_string()
     }
 }
 
@@ -619,7 +1658,7 @@ fn find_orphaned_auto_managed_filters<'a>(
 /// Find auto-managed filters whose pattern matches the exclusion list
 fn find_excluded_pattern_filters<'a>(
     existing_filters: &'a [ExistingFilterInfo],
-    exclusion_manager: &ExclusionManager,
+    exclusion_manager: &mut ExclusionManager,
     label_prefix: &str,
     label_id_to_name: &HashMap<String, String>,
 ) -> Vec<&'a ExistingFilterInfo> {
@@ -654,16 +1693,30 @@ fn create_synthetic_clusters(
             let query = filter.query.as_ref()?;
             let query_lower = query.to_lowercase();
 
+            // Mailing list filters match `list:<id>`, with or without a sender
+            let list_id = query_lower
+                .split_whitespace()
+                .find_map(|term| term.strip_prefix("list:"))
+                .map(str::to_string);
+
             // Extract from pattern
-            let from_start = query_lower.find("from:(")?;
-            let from_content_start = from_start + 6;
-            let from_end = query_lower[from_content_start..].find(')')? + from_content_start;
-            let from_pattern = query[from_content_start..from_end].trim().to_string();
+            let from_pattern = match query_lower.find("from:(") {
+                Some(from_start) => {
+                    let from_content_start = from_start + 6;
+                    let from_end =
+                        query_lower[from_content_start..].find(')')? + from_content_start;
+                    query[from_content_start..from_end].trim().to_string()
+                }
+                None => format!(
+                    "*@{}",
+                    crate::classifier::extract_main_domain(list_id.as_deref()?)
+                ),
+            };
 
             // Parse from pattern to get domain and email
-            let (sender_domain, sender_email, is_specific_sender) = if from_pattern.starts_with("*@") {
+            let (sender_domain, sender_email, is_specific_sender) = if let Some(rest) = from_pattern.strip_prefix("*@") {
                 // Domain pattern: *@domain.com
-                let domain = from_pattern[2..].split_whitespace().next().unwrap_or(&from_pattern[2..]);
+                let domain = rest.split_whitespace().next().unwrap_or(rest);
                 (domain.to_string(), String::new(), false)
             } else {
                 // Specific sender: email@domain.com
@@ -701,17 +1754,22 @@ fn create_synthetic_clusters(
                 excluded_senders: Vec::new(),
                 subject_pattern,
                 message_ids: Vec::new(), // No messages - synthetic cluster
+                thread_ids: HashSet::new(),
                 suggested_category: EmailCategory::Other,
                 suggested_label: label_name.clone(),
                 confidence: 1.0,
+                top_reason: None,
                 sample_subjects: Vec::new(),
                 should_archive,
                 existing_filter_id: Some(filter.id.clone()),
                 existing_filter_label_id: filter.add_label_ids.first().cloned(),
                 existing_filter_label: Some(label_name),
                 existing_filter_archive: Some(should_archive),
+                existing_filter_query: Some(filter.display_query()),
                 source: source.clone(),
                 default_action: Some(DecisionAction::Delete),
+                preview_count: None,
+                list_id,
             })
         })
         .collect()
@@ -719,6 +1777,181 @@ fn create_synthetic_clusters(
 
 use std::sync::Arc;
 
+/// A label change that failed for some messages, re-applied in the final retry pass
+struct FailedModification {
+    message_ids: Vec<String>,
+    add_label_ids: Vec<LabelId>,
+    remove_label_ids: Vec<LabelId>,
+}
+
+/// Records messages a batch label change couldn't modify so they can be retried
+fn record_failed_messages(
+    state: &mut ProcessingState,
+    failed_modifications: &mut Vec<FailedModification>,
+    result: &BatchModifyResult,
+    add_label_ids: &[LabelId],
+    remove_label_ids: &[LabelId],
+) {
+    let Some(first) = result.failed.first() else {
+        return;
+    };
+    warn!(
+        "Failed to modify labels on {} messages: {}",
+        result.failed.len(),
+        first.error
+    );
+    let message_ids: Vec<String> = result.failed_ids().map(String::from).collect();
+    state.failed_message_ids.extend(message_ids.iter().cloned());
+    failed_modifications.push(FailedModification {
+        message_ids,
+        add_label_ids: add_label_ids.to_vec(),
+        remove_label_ids: remove_label_ids.to_vec(),
+    });
+}
+
+/// Records the messages of a batch label change that failed as a whole
+///
+/// Permanent errors (see [`GmailError::is_permanent`]) are recorded in
+/// `failed_message_ids` without being retried.
+fn record_failed_batch(
+    state: &mut ProcessingState,
+    failed_modifications: &mut Vec<FailedModification>,
+    message_ids: &[String],
+    error: &GmailError,
+    add_label_ids: &[LabelId],
+    remove_label_ids: &[LabelId],
+) {
+    warn!(
+        "Failed to modify labels on {} messages: {}",
+        message_ids.len(),
+        error.chain_message()
+    );
+    state.failed_message_ids.extend(message_ids.iter().cloned());
+    if !error.is_permanent() {
+        failed_modifications.push(FailedModification {
+            message_ids: message_ids.to_vec(),
+            add_label_ids: add_label_ids.to_vec(),
+            remove_label_ids: remove_label_ids.to_vec(),
+        });
+    }
+}
+
+/// Re-applies failed label changes once, removing recovered messages from
+/// `failed_message_ids`. Returns the number of messages that were labelled.
+async fn retry_failed_modifications(
+    client: &dyn GmailClient,
+    state: &mut ProcessingState,
+    failed_modifications: Vec<FailedModification>,
+) -> usize {
+    let mut labeled = 0;
+    for modification in failed_modifications {
+        info!(
+            "Retrying label changes on {} messages",
+            modification.message_ids.len()
+        );
+        let result = match client
+            .batch_modify_labels(
+                &modification.message_ids,
+                &modification.add_label_ids,
+                &modification.remove_label_ids,
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("Retry failed: {}", e);
+                continue;
+            }
+        };
+
+        let still_failed: HashSet<&str> = result.failed_ids().collect();
+        let recovered: HashSet<&str> = modification
+            .message_ids
+            .iter()
+            .map(String::as_str)
+            .filter(|id| !still_failed.contains(id))
+            .collect();
+        state
+            .failed_message_ids
+            .retain(|id| !recovered.contains(id.as_str()));
+        if !modification.add_label_ids.is_empty() {
+            labeled += result.succeeded;
+        }
+    }
+    labeled
+}
+
+/// Retries the fetches in `state.retry_queue`, waiting until each one is due
+///
+/// Entries that fail again are re-queued with a doubled delay until they run
+/// out of retries and move to `permanently_failed_ids`. Returns the fetched
+/// messages and the number of retries made.
+async fn process_retry_queue(
+    client: &dyn GmailClient,
+    state: &mut ProcessingState,
+    thread_mode: bool,
+) -> (Vec<MessageMetadata>, usize) {
+    let mut recovered = Vec::new();
+    let mut attempts = 0;
+
+    while let Some(index) = state
+        .retry_queue
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, entry)| entry.next_retry_at)
+        .map(|(index, _)| index)
+    {
+        let mut entry = state.retry_queue.swap_remove(index);
+        let wait = (entry.next_retry_at - Utc::now())
+            .to_std()
+            .unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        attempts += 1;
+        match crate::scanner::fetch_messages_for(client, &entry.message_id, thread_mode).await {
+            Ok(messages) => recovered.extend(messages),
+            Err(e) => {
+                entry.record_failure(&e);
+                if entry.exhausted() {
+                    warn!(
+                        "Giving up on {} after {} retries: {}",
+                        entry.message_id, entry.attempts, e
+                    );
+                    state.permanently_failed_ids.push(entry.message_id);
+                } else {
+                    debug!(
+                        "Retry {} of {} failed: {}",
+                        entry.attempts, entry.message_id, e
+                    );
+                    state.retry_queue.push(entry);
+                }
+            }
+        }
+    }
+
+    (recovered, attempts)
+}
+
+/// Build the classifier used for `mode` from the classification settings
+fn classifier_for_mode(config: &Config, mode: ClassificationMode) -> Result<EmailClassifier> {
+    let mut classifier = EmailClassifier::new(config.labels.prefix.clone())
+        .with_label_template(config.labels.label_template.clone())
+        .with_calendar_label(config.labels.calendar_label.clone())
+        .with_size_boosts(config.classification.active_size_boosts())
+        .with_subject_keywords(&config.classification.subject_keywords)?
+        .with_min_confidence(config.classification.min_confidence_thresholds())
+        .with_rules(config.classification.rules.clone())
+        .with_mode(mode, &config.classification.subject_keywords)?;
+    if config.classification.use_time_heuristics {
+        classifier = classifier.with_time_heuristics(config.execution.time_zone()?);
+    }
+    if config.labels.use_attachment_sublabels {
+        classifier =
+            classifier.with_attachment_sublabels(config.labels.attachment_sublabels.clone());
+    }
+    Ok(classifier)
+}
+
 /// Load review decisions from a JSON file
 async fn load_decisions(path: &Path) -> Result<Vec<ClusterDecision>> {
     if !path.exists() {
@@ -731,6 +1964,108 @@ async fn load_decisions(path: &Path) -> Result<Vec<ClusterDecision>> {
     Ok(decisions)
 }
 
+/// Classify the messages of a saved scan with the configured rules, offline
+pub fn classify_scan(
+    config: &Config,
+    scan: &ScanOutput,
+) -> Result<Vec<(MessageMetadata, Classification)>> {
+    let classifier = classifier_for_mode(config, config.classification.mode)?;
+    // The scan may predate the current `scan.exclude_senders`
+    scan.messages
+        .iter()
+        .filter(|msg| {
+            !crate::scanner::is_sender_excluded(&msg.sender_email, &config.scan.exclude_senders)
+        })
+        .map(|msg| Ok((msg.clone(), classifier.classify(msg)?)))
+        .collect()
+}
+
+/// Scan the mailbox without classifying or changing anything
+///
+/// Lists and fetches the messages of the configured period (or `period_days`)
+/// so they can be saved and classified offline with the `classify` command.
+pub async fn run_scan(
+    cli: &Cli,
+    period_days: Option<u32>,
+    multi_progress: MultiProgress,
+) -> Result<ScanOutput> {
+    let mut config = Config::load(&cli.config).await?;
+    if let Some(days) = period_days {
+        config.scan.period_days = days;
+        config.validate()?;
+    }
+    let reporter = ProgressReporter::with_ui_config(multi_progress, &config.ui);
+
+    let auth_spinner = reporter.add_spinner("Authenticating with Gmail API...");
+    let hub = auth::initialize_gmail_hub(&cli.credentials, &cli.token_cache).await?;
+    reporter.finish_spinner(&auth_spinner, "Gmail API authenticated successfully");
+
+    let client = ProductionGmailClient::with_full_config(
+        hub,
+        config.scan.max_concurrent_requests,
+        250.0, // quota units per second
+        500.0, // quota burst capacity
+        config.circuit_breaker.clone(),
+    )
+    .with_retry_config(config.client.retry.clone())
+    .with_auth_headers(config.scan.parse_auth_headers)
+    .with_unsubscribe_links(config.scan.extract_unsubscribe_links);
+
+    let query = config.scan.search_query();
+    tracing::info!("Scanning emails with query: {}", query);
+    // Thread mode fetches whole conversations with one request per thread
+    let thread_mode = config.scan.thread_mode;
+    let unit = if thread_mode { "threads" } else { "messages" };
+    let scan_spinner = reporter.add_spinner(&format!("Listing {}...", unit));
+    let ids = if thread_mode {
+        client.list_thread_ids(&query).await?
+    } else {
+        let ids = client.message_id_stream(&query, MAX_PAGE_SIZE)?;
+        match config.scan.max_messages {
+            Some(max) => ids.take(max).try_collect::<Vec<_>>().await?,
+            None => ids.try_collect::<Vec<_>>().await?,
+        }
+    };
+    reporter.finish_spinner(&scan_spinner, &format!("Found {} {}", ids.len(), unit));
+
+    let fetch_bar = reporter.add_progress_bar(ids.len() as u64, "Fetching messages...");
+    let max_concurrent = config.scan.max_concurrent_requests;
+    let max_messages = config.scan.max_messages.unwrap_or(usize::MAX);
+    let (tx, mut rx) = tokio::sync::mpsc::channel(max_concurrent * 2);
+    let (fetch_result, messages) = tokio::join!(
+        crate::scanner::send_messages(&client, ids, thread_mode, max_concurrent, tx),
+        async {
+            let mut messages = Vec::new();
+            while let Some(msg) = rx.recv().await {
+                // Threads hold an unknown number of messages, so the limit and the
+                // bar length are only known as they arrive
+                if messages.len() >= max_messages {
+                    rx.close();
+                    break;
+                }
+                messages.push(msg);
+                if fetch_bar
+                    .length()
+                    .is_some_and(|len| fetch_bar.position() >= len)
+                {
+                    fetch_bar.inc_length(1);
+                }
+                fetch_bar.inc(1);
+            }
+            messages
+        }
+    );
+    fetch_bar.finish_and_clear();
+
+    let failed = fetch_result?;
+    if !failed.is_empty() {
+        warn!("{} messages could not be fetched", failed.len());
+    }
+    let messages = crate::scanner::apply_sender_exclusions(messages, &config.scan.exclude_senders);
+
+    Ok(ScanOutput::new(query, messages))
+}
+
 /// Main orchestration function that runs the complete email management pipeline
 ///
 /// This function coordinates all modules to:
@@ -750,6 +2085,14 @@ async fn load_decisions(path: &Path) -> Result<Vec<ClusterDecision>> {
 /// * `review` - If true, enter interactive cluster review mode
 /// * `resume` - If true, resume from previous state
 /// * `ignore_exclusions` - If true, ignore saved exclusions and show all clusters
+/// * `tags` - Tags recorded on the run state for filtering run history
+/// * `auto_backup` - If true, back up filters, labels and state before making changes
+/// * `resume_review` - If true, restore decisions saved by an earlier review session
+/// * `domains` - If not empty, scan only these sender domains, one query per domain
+/// * `test_mode` - Fixture to run against instead of Gmail; state, reports and
+///   caches then go to [`test_mode_state_dir`] instead of next to `cli.state_file`
+/// * `config` - Config to run with instead of loading `cli.config`, e.g. one kept
+///   by `watch` after a reload failed
 ///
 /// # Returns
 /// * `Ok(Report)` - Execution report with statistics
@@ -764,6 +2107,13 @@ pub async fn run_pipeline(
     resume: bool,
     ignore_exclusions: bool,
     apply_decisions_file: Option<PathBuf>,
+    tags: Vec<String>,
+    auto_backup: bool,
+    resume_review: bool,
+    domains: Vec<String>,
+    export_domain_stats: Option<PathBuf>,
+    test_mode: Option<PathBuf>,
+    config: Option<Config>,
     multi_progress: MultiProgress,
 ) -> Result<Report> {
     let mut reporter = ProgressReporter::with_multi_progress(multi_progress);
@@ -771,48 +2121,215 @@ pub async fn run_pipeline(
 
     // Step 1: Load configuration
     let config_spinner = reporter.add_spinner("Loading configuration...");
-    let mut config = Config::load(&cli.config).await?;
+    let mut config = match config {
+        Some(config) => config,
+        None => Config::load(&cli.config).await?,
+    };
     if dry_run {
         config.execution.dry_run = true;
     }
+    // Later progress bars follow the [ui] settings
+    reporter = ProgressReporter::with_ui_config(reporter.multi_progress(), &config.ui);
     reporter.finish_spinner(
         &config_spinner,
         &format!("Configuration loaded from {:?}", cli.config),
     );
 
-    // Step 2: Initialize Gmail API
-    let auth_spinner = reporter.add_spinner("Authenticating with Gmail API...");
-    let hub = auth::initialize_gmail_hub(&cli.credentials, &cli.token_cache).await?;
-    reporter.finish_spinner(&auth_spinner, "Gmail API authenticated successfully");
+    // Step 2: Initialize Gmail API (test mode reads a fixture instead)
+    let hub = if test_mode.is_none() {
+        let auth_spinner = reporter.add_spinner("Authenticating with Gmail API...");
+        let hub = auth::initialize_gmail_hub(&cli.credentials, &cli.token_cache).await?;
+        reporter.finish_spinner(&auth_spinner, "Gmail API authenticated successfully");
+        Some(hub)
+    } else {
+        None
+    };
 
-    // Step 3: Create client with rate limiting and circuit breaker
-    let client = Arc::new(ProductionGmailClient::with_full_config(
-        hub,
-        config.scan.max_concurrent_requests,
-        250.0, // quota units per second
-        500.0, // quota burst capacity
-        config.circuit_breaker.clone(),
-    ));
+    // Step 3: Load or create processing state
+    let state_key = config.state.encryption_key()?;
+    // Everything the run writes goes next to the state file, so a fixture run
+    // never touches the real state, reports, filter stats or caches
+    let state_file = match &test_mode {
+        Some(_) => test_mode_state_dir().join(
+            cli.state_file
+                .file_name()
+                .unwrap_or_else(|| "state.json".as_ref()),
+        ),
+        None => cli.state_file.clone(),
+    };
+    let state_file = state_file.as_path();
+
+    // Keep the last completed run around to report what changed since then
+    let previous_state = if resume {
+        None
+    } else {
+        ProcessingState::load_with_key(state_file, state_key.as_ref())
+            .await
+            .ok()
+            .filter(|s| s.completed)
+    };
 
-    // Step 4: Load or create processing state
     let mut state = if resume {
-        ProcessingState::load(&cli.state_file).await?
+        ProcessingState::load_with_key(state_file, state_key.as_ref()).await?
     } else {
         ProcessingState::new()
-    };
+    }
+    .with_compression(config.state.compress)
+    .with_format(config.state.format)
+    .with_encryption_key(state_key);
+    if !tags.is_empty() {
+        state.set_tags(tags);
+    }
+
+    tracing::info!("Starting pipeline run: {}", state.run_id);
+
+    // The manager owns the state; checkpoints publish it to the background writer
+    let state_manager = StateManager::spawn(
+        state,
+        state_file.to_path_buf(),
+        Duration::from_secs(config.state.checkpoint_interval_secs),
+    );
+    // On Ctrl-C, write the state at the next checkpoint so `--resume` can continue
+    let shutdown_manager = state_manager.clone();
+    let shutdown_watcher = tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("Saving state at the next checkpoint (press Ctrl-C again to quit now)...");
+            tokio::select! {
+                result = shutdown_manager.flush() => {
+                    if let Err(e) = result {
+                        warn!("Failed to save processing state: {}", e);
+                    }
+                }
+                _ = tokio::signal::ctrl_c() => {}
+            }
+            std::process::exit(130);
+        }
+    });
+
+    let result = run_pipeline_steps(
+        cli,
+        dry_run,
+        labels_only,
+        interactive,
+        review,
+        resume,
+        ignore_exclusions,
+        apply_decisions_file,
+        auto_backup,
+        resume_review,
+        domains,
+        export_domain_stats,
+        test_mode,
+        state_file,
+        reporter,
+        started_at,
+        config,
+        hub,
+        previous_state,
+        state_key,
+        state_manager.lock().await,
+    )
+    .await;
+
+    // Save how far the run got, whether it finished or failed
+    shutdown_watcher.abort();
+    match state_manager.flush().await {
+        Ok(()) => result,
+        Err(e) if result.is_ok() => Err(e),
+        Err(e) => {
+            warn!("Failed to save processing state: {}", e);
+            result
+        }
+    }
+}
+
+/// Directory `run --test-mode` keeps its state, reports and caches in
+pub fn test_mode_state_dir() -> PathBuf {
+    std::env::temp_dir().join("gmail-automation-test-mode")
+}
 
+/// Run the pipeline from Step 4 on, holding the processing state owned by a
+/// [`StateManager`]
+#[allow(clippy::too_many_arguments)]
+async fn run_pipeline_steps(
+    cli: &Cli,
+    dry_run: bool,
+    labels_only: bool,
+    interactive: bool,
+    review: bool,
+    resume: bool,
+    ignore_exclusions: bool,
+    apply_decisions_file: Option<PathBuf>,
+    auto_backup: bool,
+    resume_review: bool,
+    domains: Vec<String>,
+    export_domain_stats: Option<PathBuf>,
+    test_mode: Option<PathBuf>,
+    state_file: &Path,
+    mut reporter: ProgressReporter,
+    started_at: chrono::DateTime<Utc>,
+    config: Config,
+    hub: Option<auth::GmailHub>,
+    previous_state: Option<ProcessingState>,
+    state_key: Option<[u8; 32]>,
+    mut state: StateGuard,
+) -> Result<Report> {
     let run_id = state.run_id.clone();
-    tracing::info!("Starting pipeline run: {}", run_id);
+    let run_tags = state.tags.clone();
+
+    // Step 4: Create client with rate limiting and circuit breaker
+    let (client, production_client): (Arc<dyn GmailClient>, _) = match (hub, test_mode.as_deref()) {
+        (Some(hub), _) => {
+            let production = Arc::new(
+                ProductionGmailClient::with_full_config(
+                    hub,
+                    config.scan.max_concurrent_requests,
+                    250.0, // quota units per second
+                    500.0, // quota burst capacity
+                    config.circuit_breaker.clone(),
+                )
+                .with_retry_config(config.client.retry.clone())
+                .with_auth_headers(config.scan.parse_auth_headers)
+                .with_unsubscribe_links(config.scan.extract_unsubscribe_links)
+                .with_filter_change_log(
+                    state_file.with_file_name("filter-changes.jsonl"),
+                    run_id.clone(),
+                ),
+            );
+            (production.clone(), Some(production))
+        }
+        (None, Some(fixture)) => {
+            let mock = MockGmailClient::from_fixture(fixture)?;
+            info!("Test mode: using Gmail fixture {:?}", fixture);
+            (Arc::new(mock), None)
+        }
+        (None, None) => unreachable!("Gmail is only skipped in test mode"),
+    };
+
+    if auto_backup && !config.execution.dry_run {
+        let backup_spinner = reporter.add_spinner("Backing up filters, labels and state...");
+        let backup_dir = state_file.with_file_name("backups");
+        let manifest =
+            backup::create_backup(client.as_ref(), &config, state_file, &backup_dir).await?;
+        reporter.finish_spinner(
+            &backup_spinner,
+            &format!(
+                "Backed up {} filters and {} labels to {:?}",
+                manifest.filter_count, manifest.label_count, backup_dir
+            ),
+        );
+    }
 
     if state.can_resume() {
         // Declare variables early for proper scoping across resume paths
         let mut review_decisions: Vec<ClusterDecision> = Vec::new();
         let mut review_mode_completed = false; // Track if review was completed (not aborted with Q)
         let mut classifications: Vec<(MessageMetadata, Classification)> = Vec::new();
+        let mut anomalies: Vec<VolumeSpike> = Vec::new();
         let mut existing_filters: Vec<ExistingFilterInfo> = Vec::new();
         let mut category_counts: HashMap<String, usize> = HashMap::new();
         let mut domain_counts: HashMap<String, Vec<MessageMetadata>> = HashMap::new();
-        let mut label_name_to_id: HashMap<String, String> = HashMap::new();
+        let mut label_name_to_id: HashMap<String, LabelId> = HashMap::new();
         let mut planned_labels: Vec<String> = Vec::new();
         let mut existing_label_names: Vec<String> = Vec::new();
         let mut labels_created = 0;
@@ -826,6 +2343,8 @@ pub async fn run_pipeline(
         let mut orphaned_labels_to_delete_names: Vec<String> = Vec::new();
         let mut messages_cleaned = 0;
         let mut hierarchy_labels_created = 0;
+        let mut failed_modifications: Vec<FailedModification> = Vec::new();
+        let mut fetch_retry_attempts = 0;
 
         // Handle resume from CreatingFilters or CreatingLabels phase
         if resume
@@ -835,7 +2354,7 @@ pub async fn run_pipeline(
             )
         {
             // Load saved decisions
-            let decisions_file = cli.state_file.with_file_name("decisions.json");
+            let decisions_file = state_file.with_file_name("decisions.json");
             review_decisions = load_decisions(&decisions_file).await?;
 
             if review_decisions.is_empty() {
@@ -863,9 +2382,9 @@ pub async fn run_pipeline(
             let existing_label_count = label_manager.load_existing_labels().await?;
 
             // Build label name -> ID mapping from the label cache
-            // Cache keys are already lowercase for case-insensitive lookup
-            for (name, id) in label_manager.get_label_cache() {
-                label_name_to_id.insert(name.clone(), id.clone());
+            // Names are already lowercase for case-insensitive lookup
+            for (name, id) in label_manager.labels() {
+                label_name_to_id.insert(name.into_inner(), id);
             }
 
             reporter.finish_spinner(
@@ -936,8 +2455,8 @@ pub async fn run_pipeline(
             let existing_label_count = label_manager.load_existing_labels().await?;
 
             // Build label name -> ID mapping from the label cache
-            for (name, id) in label_manager.get_label_cache() {
-                label_name_to_id.insert(name.clone(), id.clone());
+            for (name, id) in label_manager.labels() {
+                label_name_to_id.insert(name.into_inner(), id);
             }
 
             reporter.finish_spinner(
@@ -946,10 +2465,14 @@ pub async fn run_pipeline(
             );
 
             // Initialize fresh state for this run
-            state = ProcessingState::new();
+            *state = ProcessingState::new()
+                .with_compression(config.state.compress)
+                .with_format(config.state.format)
+                .with_encryption_key(state_key);
             state.run_id = run_id.clone();
+            state.set_tags(run_tags.clone());
             state.phase = ProcessingPhase::CreatingLabels;
-            state.save(&cli.state_file).await?;
+            state.publish().await?;
 
             let create_count = review_decisions
                 .iter()
@@ -976,52 +2499,316 @@ pub async fn run_pipeline(
         // Step 5 & 6: Scan and classify emails (skip if resuming from later phases or applying decisions)
         if !applying_decisions
             && (!resume
+                || state.scan_truncated
                 || matches!(
                     state.phase,
                     ProcessingPhase::Scanning | ProcessingPhase::Classifying
                 ))
         {
+            // Pick up the messages classified before an interrupted scan
+            let resume_page_token = state
+                .scan_checkpoint
+                .as_ref()
+                .filter(|_| resume)
+                .and_then(|checkpoint| checkpoint.page_token.clone());
+            let resumed: Vec<(MessageMetadata, Classification)> =
+                if resume && state.scan_checkpoint.is_some() {
+                    state
+                        .classifications
+                        .iter()
+                        .map(SerializedClassification::restore)
+                        .collect()
+                } else {
+                    state.classifications.clear();
+                    state.scan_checkpoint = Some(ScanCheckpoint::new());
+                    Vec::new()
+                };
+            let already_classified: HashSet<String> =
+                resumed.iter().map(|(msg, _)| msg.id.clone()).collect();
+            // A resumed scan starts at the saved page, so fetches queued for retry
+            // by the interrupted run are retried below rather than listed again
+            if !resume {
+                state.retry_queue.clear();
+                state.permanently_failed_ids.clear();
+            }
+            state.scan_truncated = false;
+            state.completed = false;
+            if !resumed.is_empty() {
+                info!(
+                    "Resuming scan with {} messages already classified",
+                    resumed.len()
+                );
+            }
+
             state.phase = ProcessingPhase::Scanning;
-            state.save(&cli.state_file).await?;
+            state.publish().await?;
+            // Messages are classified as they arrive; that time counts as "Classify"
+            let scan_started = Instant::now();
+            let mut classify_time = Duration::ZERO;
 
             let scan_spinner = reporter.add_spinner("Scanning emails from inbox...");
 
-            // Build query for time period
-            let period = chrono::Duration::days(config.scan.period_days as i64);
-            let after_date = (Utc::now() - period).format("%Y/%m/%d").to_string();
-            let query = format!("after:{}", after_date);
+            // Build query for the time period and any label restrictions
+            let query = config.scan.search_query();
 
             tracing::info!("Scanning emails with query: {}", query);
 
-            // List message IDs
-            let message_ids = client.list_message_ids(&query).await?;
-            let total_messages = message_ids.len();
+            // List message IDs, or thread IDs when fetching whole conversations
+            let by_domain = !domains.is_empty();
+            let thread_mode = config.scan.thread_mode && !by_domain;
+            let streaming = config.scan.streaming_fetch && !thread_mode && !by_domain;
+            let mut listed_pages = None;
+            let (ids, unit) = if by_domain {
+                // Each domain lists and fetches its own messages, see below
+                (Vec::new(), "messages")
+            } else if thread_mode {
+                (client.list_thread_ids(&query).await?, "threads")
+            } else if streaming {
+                // IDs are listed while messages are fetched, see below
+                (Vec::new(), "messages")
+            } else {
+                // IDs arrive page by page but are still collected up front, which gives
+                // the progress bar a total; `scan.streaming_fetch` skips this step.
+                // A resumed scan lists again from the first page it hadn't finished
+                let (ids, pages) = crate::scanner::list_message_pages(
+                    client.as_ref(),
+                    &query,
+                    resume_page_token,
+                    &already_classified,
+                    config.scan.max_messages,
+                )
+                .await?;
+                if pages.truncated() {
+                    state.scan_truncated = true;
+                }
+                listed_pages = Some(pages);
+                (ids, "messages")
+            };
+            let total_items = ids.len();
+            let checkpoint_interval = config.scan.checkpoint_interval_messages;
 
-            reporter.finish_spinner(
-                &scan_spinner,
-                &format!("Found {} messages to process", total_messages),
-            );
+            if by_domain {
+                reporter.finish_spinner(
+                    &scan_spinner,
+                    &format!("Scanning {} domains in parallel", domains.len()),
+                );
+            } else if streaming {
+                reporter.finish_spinner(&scan_spinner, "Streaming messages as they are listed");
+            } else {
+                reporter.finish_spinner(
+                    &scan_spinner,
+                    &format!("Found {} {} to process", total_items, unit),
+                );
+            }
 
-            // Fetch message metadata and load existing filters/labels concurrently
+            // Fetch and classify messages while loading existing filters/labels concurrently
             // These are independent API calls that can run in parallel
-            let fetch_bar = reporter.add_progress_bar(
-                total_messages as u64,
-                "Fetching emails, filters, and labels...",
-            );
-            let fetch_bar_clone = fetch_bar.clone();
+            let fetch_bar =
+                reporter.add_progress_bar(total_items as u64, "Fetching and classifying emails...");
+            let ab_test = config.classification.ab_test.as_ref();
+            let classifier = classifier_for_mode(
+                &config,
+                ab_test.map_or(config.classification.mode, |t| t.control_mode),
+            )?;
+            // A sample of messages is also classified by the experiment mode and logged
+            let experiment = match ab_test {
+                Some(ab_test) => {
+                    info!(
+                        "A/B testing {} against {} on {:.0}% of messages",
+                        ab_test.control_mode,
+                        ab_test.experiment_mode,
+                        ab_test.experiment_fraction * 100.0
+                    );
+                    Some((
+                        ab_test,
+                        classifier_for_mode(&config, ab_test.experiment_mode)?,
+                    ))
+                }
+                None => None,
+            };
+            let mut ab_results = Vec::new();
 
-            let progress_callback: crate::client::ProgressCallback = Arc::new(move || {
-                fetch_bar_clone.inc(1);
-            });
+            // Messages fetched by earlier runs are reused; threads and streamed IDs
+            // aren't known up front, so only the plain scan checks the cache
+            if config.scan.metadata_cache_enabled && state_key.is_some() {
+                warn!("Metadata cache disabled: it can't be encrypted like the state file");
+            }
+            let mut metadata_cache = if config.scan.metadata_cache_enabled && state_key.is_none() {
+                let path = state_file.with_file_name("metadata-cache.json");
+                let max_size = config.scan.metadata_cache_max_entries;
+                let cache = crate::scanner::MetadataCache::load(&path, max_size)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to load metadata cache, starting empty: {}", e);
+                        crate::scanner::MetadataCache::new(path, max_size)
+                    });
+                Some(cache)
+            } else {
+                None
+            };
+            let (cached_messages, ids) = match metadata_cache.as_mut() {
+                Some(cache) if !thread_mode && !streaming && !by_domain => {
+                    let (cached, missing) = cache.split(ids);
+                    info!(
+                        "{} messages found in the metadata cache, fetching {}",
+                        cached.len(),
+                        missing.len()
+                    );
+                    (cached, missing)
+                }
+                _ => (Vec::new(), ids),
+            };
+
+            // The bounded channel pauses fetching whenever classification falls behind
+            let (tx, mut rx) = tokio::sync::mpsc::channel(config.scan.max_concurrent_requests * 2);
 
-            // Run message fetching and filter/label loading concurrently
+            // Run message fetching, classification and filter/label loading concurrently
             let client_clone = client.clone();
             let client_clone2 = client.clone();
             let label_prefix = config.labels.prefix.clone();
+            // Set when the domains used up `scan.max_messages` before all were scanned
+            let mut domains_truncated = false;
 
-            let (messages_result, filters_result, labels_result) = tokio::join!(
-                // Fetch all message metadata (already internally concurrent)
-                client.fetch_messages_with_progress(message_ids, progress_callback),
+            let (fetch_result, classify_result, filters_result, labels_result) = tokio::join!(
+                // Fetch message metadata (already internally concurrent)
+                async {
+                    if by_domain {
+                        let client: Arc<dyn GmailClient> = client.clone();
+                        let (scanned, truncated) =
+                            crate::scanner::fetch_by_domains(&domains, &config.scan, &client)
+                                .await?;
+                        domains_truncated = truncated;
+                        // Overlapping domains (e.g. a.com and mail.a.com) find the same mail
+                        let (messages, _) =
+                            crate::scanner::deduplicate(scanned.into_values().flatten().collect());
+                        for message in messages {
+                            if tx.send(message).await.is_err() {
+                                break;
+                            }
+                        }
+                        return Ok(Vec::new());
+                    }
+                    for message in cached_messages {
+                        if tx.send(message).await.is_err() {
+                            return Ok(Vec::new());
+                        }
+                    }
+                    if !streaming {
+                        return crate::scanner::send_messages(
+                            client.as_ref(),
+                            ids,
+                            thread_mode,
+                            config.scan.max_concurrent_requests,
+                            tx,
+                        )
+                        .await;
+                    }
+                    // Classification starts with the first page instead of after the last
+                    let (messages, lister) = client.clone().stream_messages_bounded(
+                        &query,
+                        MAX_PAGE_SIZE,
+                        config.scan.max_concurrent_requests,
+                    );
+                    let skipped = crate::scanner::forward_streamed_messages(messages, tx).await;
+                    lister.abort();
+                    let skipped = skipped?;
+                    if skipped > 0 {
+                        warn!(
+                            "{} messages could not be fetched; run with --resume to retry them",
+                            skipped
+                        );
+                    }
+                    Ok(Vec::new())
+                },
+                // Classify messages as they are delivered, checkpointing progress
+                async {
+                    let mut classified = resumed;
+                    classified.reserve(total_items);
+                    let mut since_checkpoint = 0;
+                    let mut fetched = 0;
+                    // A message matching several queries must only be classified once
+                    let mut seen = HashSet::new();
+                    let mut duplicates = 0;
+                    let mut excluded_senders = 0;
+                    while let Some(msg) = rx.recv().await {
+                        if let Some(pages) = listed_pages.as_mut() {
+                            pages.complete(&msg.id);
+                        }
+                        // Threads are fetched whole, so skip their already classified messages
+                        if already_classified.contains(&msg.id) {
+                            continue;
+                        }
+                        if !seen.insert(msg.id.clone()) {
+                            duplicates += 1;
+                            continue;
+                        }
+                        if crate::scanner::is_sender_excluded(
+                            &msg.sender_email,
+                            &config.scan.exclude_senders,
+                        ) {
+                            excluded_senders += 1;
+                            continue;
+                        }
+                        // Threads and streamed messages aren't counted before they arrive
+                        if config.scan.max_messages.is_some_and(|max| fetched >= max) {
+                            state.scan_truncated = true;
+                            rx.close();
+                            break;
+                        }
+                        fetched += 1;
+                        if let Some(cache) = metadata_cache.as_mut() {
+                            cache.insert(msg.clone());
+                        }
+                        let classify_started = Instant::now();
+                        let classification = classifier.classify(&msg)?;
+                        classify_time += classify_started.elapsed();
+                        if let Some((ab_test, experiment)) = &experiment {
+                            if AgreementResult::is_sampled(&msg.id, ab_test.experiment_fraction) {
+                                ab_results.push(AgreementResult::new(
+                                    msg.id.clone(),
+                                    classification.category.clone(),
+                                    experiment.classify(&msg)?.category,
+                                ));
+                            }
+                        }
+                        state
+                            .classifications
+                            .push(SerializedClassification::new(&msg, &classification));
+                        state.record_unsubscribe_links(&msg);
+                        if let Some(checkpoint) = state.scan_checkpoint.as_mut() {
+                            let page_token =
+                                listed_pages.as_mut().and_then(ListedPages::resume_token);
+                            checkpoint.update(page_token, Some(msg.id.clone()));
+                        }
+                        classified.push((msg, classification));
+
+                        since_checkpoint += 1;
+                        if since_checkpoint >= checkpoint_interval {
+                            state.messages_scanned = classified.len();
+                            state.record_checkpoint();
+                            state.publish().await?;
+                            since_checkpoint = 0;
+                        }
+                        // Threads hold an unknown number of messages, so grow the bar as needed
+                        if fetch_bar
+                            .length()
+                            .is_some_and(|len| fetch_bar.position() >= len)
+                        {
+                            fetch_bar.inc_length(1);
+                        }
+                        fetch_bar.inc(1);
+                    }
+                    if duplicates > 0 {
+                        tracing::debug!("Skipped {} duplicate messages", duplicates);
+                    }
+                    if excluded_senders > 0 {
+                        info!(
+                            "Excluded {} messages from senders in scan.exclude_senders",
+                            excluded_senders
+                        );
+                    }
+                    Ok::<_, GmailError>(classified)
+                },
                 // Load existing filters for cluster matching
                 async {
                     let filters = client_clone.list_filters().await.unwrap_or_else(|e| {
@@ -1041,59 +2828,166 @@ pub async fn run_pipeline(
                 }
             );
 
-            let messages = messages_result?;
+            // A classification error drops the receiver and stops fetching, so report it first
+            classifications = classify_result?;
+            if let Some(cache) = &metadata_cache {
+                if let Err(e) = cache.save().await {
+                    warn!("Failed to save metadata cache: {}", e);
+                }
+            }
+            if let Some((ab_test, _)) = &experiment {
+                if let Err(e) = AgreementResult::append_all(&ab_results, &ab_test.log_path).await {
+                    warn!("Failed to write A/B test log: {}", e);
+                } else {
+                    info!(
+                        "Logged {} A/B test comparisons to {:?}",
+                        ab_results.len(),
+                        ab_test.log_path
+                    );
+                }
+            }
+            let failed_fetches = fetch_result?;
+            if domains_truncated {
+                state.scan_truncated = true;
+            }
             existing_filters = filters_result?;
             let preloaded_label_manager = labels_result?;
 
+            // Retry fetches that failed with transient errors, backing off between attempts,
+            // along with any left queued by an interrupted run
+            let classified_ids: HashSet<String> = classifications
+                .iter()
+                .map(|(msg, _)| msg.id.clone())
+                .collect();
+            state
+                .retry_queue
+                .retain(|entry| !classified_ids.contains(&entry.message_id));
+            if !failed_fetches.is_empty() || !state.retry_queue.is_empty() {
+                for (id, e) in &failed_fetches {
+                    // Entries queued before keep their attempts and backoff
+                    if !state
+                        .retry_queue
+                        .iter()
+                        .any(|entry| &entry.message_id == id)
+                    {
+                        state.retry_queue.push(FailedFetch::new(id.clone(), e));
+                    }
+                }
+                info!(
+                    "Queueing {} failed fetches for retry",
+                    state.retry_queue.len()
+                );
+                state.record_checkpoint();
+                state.publish().await?;
+
+                let (recovered, attempts) =
+                    process_retry_queue(client.as_ref(), &mut state, thread_mode).await;
+                fetch_retry_attempts = attempts;
+                let recovered = crate::scanner::apply_sender_exclusions(
+                    recovered,
+                    &config.scan.exclude_senders,
+                );
+                for msg in recovered {
+                    if already_classified.contains(&msg.id) {
+                        continue;
+                    }
+                    let classify_started = Instant::now();
+                    let classification = classifier.classify(&msg)?;
+                    classify_time += classify_started.elapsed();
+                    state
+                        .classifications
+                        .push(SerializedClassification::new(&msg, &classification));
+                    state.record_unsubscribe_links(&msg);
+                    classifications.push((msg, classification));
+                    fetch_bar.inc(1);
+                }
+                if !state.permanently_failed_ids.is_empty() {
+                    warn!(
+                        "{} {} could not be fetched after {} retries",
+                        state.permanently_failed_ids.len(),
+                        unit,
+                        MAX_FETCH_RETRIES
+                    );
+                }
+            }
+
             fetch_bar.finish_with_message(format!(
-                "Fetched {} emails, {} filters, {} labels",
-                messages.len(),
+                "Fetched and classified {} emails, loaded {} filters, {} labels",
+                classifications.len(),
                 existing_filters.len(),
-                preloaded_label_manager.get_label_cache().len()
+                preloaded_label_manager.label_count()
             ));
 
-            state.messages_scanned = messages.len();
-            state.checkpoint(&cli.state_file).await?;
-
-            // Step 6: Classify emails
+            state.record_phase_timing("Scan", scan_started.elapsed().saturating_sub(classify_time));
+            state.record_phase_timing("Classify", classify_time);
+            state.messages_scanned = classifications.len();
             state.phase = ProcessingPhase::Classifying;
-            state.save(&cli.state_file).await?;
-
-            let classify_bar =
-                reporter.add_progress_bar(messages.len() as u64, "Classifying emails...");
-            let classifier = EmailClassifier::new(config.labels.prefix.clone());
-
-            for msg in &messages {
-                let classification = classifier.classify(msg)?;
-                classifications.push((msg.clone(), classification));
-                classify_bar.inc(1);
+            state.messages_classified = classifications.len();
+            if state.scan_truncated {
+                // Keep the checkpoint so `--resume` continues the scan
+                reporter.warn(&format!(
+                    "Scan truncated at {} messages; increase max_messages or use incremental mode",
+                    format_number(classifications.len() as u64)
+                ));
+            } else {
+                state.scan_checkpoint = None;
+            }
+            state.record_checkpoint();
+            state.publish().await?;
+
+            anomalies = AnomalyDetector::detect_spikes(classifications.iter().map(|(m, _)| m));
+            for spike in &anomalies {
+                warn!(
+                    "{} sent {} emails in one week, peaking on {} (usually {:.1} per week)",
+                    spike.sender_domain,
+                    spike.spike_count,
+                    spike.spike_date,
+                    spike.baseline_per_week
+                );
             }
 
-            classify_bar
-                .finish_with_message(format!("Classified {} emails", classifications.len()));
-
-            state.messages_classified = classifications.len();
-            state.checkpoint(&cli.state_file).await?;
+            if let Some(path) = &export_domain_stats {
+                let messages: Vec<MessageMetadata> =
+                    classifications.iter().map(|(m, _)| m.clone()).collect();
+                let stats = classifier.analyze_domain_patterns(&messages);
+                DomainStats::export_json(&stats, path).await?;
+                info!(
+                    "Exported statistics for {} domains to {:?}",
+                    stats.len(),
+                    path
+                );
+            }
 
             // Step 7: Interactive review (if enabled)
             if review {
+                let review_started = Instant::now();
+                // Messages are carried alongside their classifications
                 let mut clusters = create_clusters(
-                    &messages,
+                    &[],
                     &classifications,
                     config.classification.minimum_emails_for_label,
+                    &config.classification.hidden_cluster_categories(),
+                );
+                auto_merge_domain_clusters(
+                    &mut clusters,
+                    config.classification.auto_merge_threshold,
                 );
 
                 // Filter out excluded clusters (unless --ignore-exclusions is set)
-                let exclusions_path = cli.state_file.with_file_name("exclusions.json");
-                let exclusion_manager = if !ignore_exclusions {
+                let exclusions_path = state_file.with_file_name("exclusions.json");
+                let mut exclusion_manager = if !ignore_exclusions {
                     ExclusionManager::load(&exclusions_path)
                         .await
                         .unwrap_or_else(|_| ExclusionManager::new())
                 } else {
                     ExclusionManager::new()
                 };
+                // Static patterns from the config file apply even with --ignore-exclusions
+                for pattern in &config.exclusions.patterns {
+                    exclusion_manager.add_pattern(pattern, None)?;
+                }
 
-                let excluded_count = if !ignore_exclusions && !exclusion_manager.is_empty() {
+                let excluded_count = if !exclusion_manager.is_empty() {
                     let before_count = clusters.len();
                     clusters.retain(|c| {
                         let key = cluster_key(c);
@@ -1116,6 +3010,13 @@ pub async fn run_pipeline(
                 // Note: We can only check the from pattern now; label matching happens later
                 // after labels are created and we have label IDs
                 for cluster in &mut clusters {
+                    // A whole mailing list is matched by its list term, not a sender
+                    let list_term = cluster
+                        .list_id
+                        .as_ref()
+                        .map(|id| format!("list:{}", id.to_lowercase()));
+                    let whole_list = list_term.is_some() && !cluster.is_specific_sender;
+
                     // Build a temporary from pattern to match
                     let from_pattern = if cluster.is_specific_sender {
                         cluster.sender_email.clone()
@@ -1144,10 +3045,21 @@ pub async fn run_pipeline(
                             .trim()
                             .to_string();
 
-                        let from_matches = existing_clean == new_normalized
-                            || existing_query.contains(&format!("from:({})", new_normalized));
+                        let from_matches = if whole_list {
+                            !existing_query
+                                .split_whitespace()
+                                .any(|term| term.starts_with("from:("))
+                        } else {
+                            existing_clean == new_normalized
+                                || existing_query.contains(&format!("from:({})", new_normalized))
+                        };
+                        // List filters and sender filters never stand in for each other
+                        let list_matches = match &list_term {
+                            Some(term) => existing_query.split_whitespace().any(|t| t == term),
+                            None => !existing_query.contains("list:"),
+                        };
 
-                        if !from_matches {
+                        if !from_matches || !list_matches {
                             continue;
                         }
 
@@ -1179,6 +3091,7 @@ pub async fn run_pipeline(
                             // Store the existing archive setting (check if INBOX is removed)
                             cluster.existing_filter_archive =
                                 Some(existing.remove_label_ids.iter().any(|l| l == "INBOX"));
+                            cluster.existing_filter_query = Some(existing.display_query());
 
                             break; // Found a match, stop looking
                         }
@@ -1188,9 +3101,8 @@ pub async fn run_pipeline(
                 // Detect orphaned and excluded-pattern auto-managed filters
                 // These will be shown in review with DELETE as default action
                 let label_id_to_name_for_detection: HashMap<String, String> = preloaded_label_manager
-                    .get_label_cache()
-                    .iter()
-                    .map(|(name, id)| (id.clone(), name.clone()))
+                    .labels()
+                    .map(|(name, id)| (id.into_inner(), name.into_inner()))
                     .collect();
 
                 let orphaned_filters = find_orphaned_auto_managed_filters(
@@ -1202,7 +3114,7 @@ pub async fn run_pipeline(
 
                 let excluded_pattern_filters = find_excluded_pattern_filters(
                     &existing_filters,
-                    &exclusion_manager,
+                    &mut exclusion_manager,
                     &config.labels.prefix,
                     &label_id_to_name_for_detection,
                 );
@@ -1233,11 +3145,30 @@ pub async fn run_pipeline(
                 }
 
                 if !clusters.is_empty() {
+                    // Point out sender domains run by the same company (costs a DNS query per domain)
+                    let domain_groups = if config.scan.analyze_mx_groups {
+                        let mut domains: Vec<String> =
+                            clusters.iter().map(|c| c.sender_domain.clone()).collect();
+                        domains.sort();
+                        domains.dedup();
+                        match crate::network::build_domain_groups(&domains).await {
+                            Ok(groups) => {
+                                info!("Found {} groups of related sender domains", groups.len());
+                                groups
+                            }
+                            Err(e) => {
+                                warn!("Skipping MX grouping of sender domains: {}", e);
+                                Vec::new()
+                            }
+                        }
+                    } else {
+                        Vec::new()
+                    };
+
                     // Use preloaded labels from concurrent fetch (build label ID -> name mapping for review UI)
                     let label_id_to_name: HashMap<String, String> = preloaded_label_manager
-                        .get_label_cache()
-                        .iter()
-                        .map(|(name, id)| (id.clone(), name.clone()))
+                        .labels()
+                        .map(|(name, id)| (id.into_inner(), name.into_inner()))
                         .collect();
 
                     // Save the MultiProgress before dropping reporter (for reuse after interactive mode)
@@ -1252,11 +3183,27 @@ pub async fn run_pipeline(
                         config.classification.minimum_emails_for_label
                     );
 
-                    let mut session = ReviewSession::with_label_map(clusters, label_id_to_name);
-                    let decisions = session.run()?;
+                    let progress_path = state_file.with_file_name("review-progress.json");
+                    let mut session = ReviewSession::with_label_map(clusters, label_id_to_name)
+                        .with_mouse(!cli.no_mouse)
+                        .with_theme(Theme::new(&config.ui, !cli.no_color))
+                        .with_autosave(progress_path.clone())
+                        .with_preview_client(client.clone())
+                        .with_min_confidence(config.classification.min_confidence_thresholds())
+                        .with_domain_groups(domain_groups)
+                        .with_anomalies(&anomalies);
+                    if resume_review {
+                        if progress_path.exists() {
+                            session = session.with_progress(&progress_path)?;
+                        } else {
+                            warn!("No saved review progress at {:?}, starting fresh", progress_path);
+                        }
+                    }
+                    // Hand this worker thread over so [P] previews keep running during review
+                    let decisions = tokio::task::block_in_place(|| session.run())?;
 
                     // Create new reporter after interactive mode (reuse same MultiProgress for tracing coordination)
-                    reporter = ProgressReporter::with_multi_progress(multi);
+                    reporter = ProgressReporter::with_ui_config(multi, &config.ui);
 
                     // If user pressed Q or Ctrl-C (empty decisions), abort the operation
                     if decisions.is_empty() {
@@ -1269,6 +3216,48 @@ pub async fn run_pipeline(
                     // Mark review as completed (user pressed W with decisions)
                     review_mode_completed = true;
 
+                    // Merged clusters are folded into the decisions for their target clusters
+                    let merge_count = decisions
+                        .iter()
+                        .filter(|d| matches!(d.action, DecisionAction::Merge { .. }))
+                        .count();
+                    let (decisions, dropped_merges) = resolve_merges(decisions);
+
+                    // Accepted labels that differ from the suggestion feed `generate-rules`
+                    let feedback: Vec<FeedbackEntry> = decisions
+                        .iter()
+                        .filter(|d| {
+                            matches!(d.action, DecisionAction::Accept | DecisionAction::Custom(_))
+                                && !d.label.is_empty()
+                        })
+                        .flat_map(|decision| {
+                            classifications
+                                .iter()
+                                .filter(|(msg, class)| {
+                                    class.suggested_label != decision.label.as_str()
+                                        && decision.message_ids.contains(&msg.id)
+                                })
+                                .map(|(msg, class)| FeedbackEntry {
+                                    message_id: msg.id.clone(),
+                                    sender_domain: msg.sender_domain.clone(),
+                                    suggested_category: class.category.clone(),
+                                    accepted_label: decision.label.to_string(),
+                                    run_id: state.run_id.clone(),
+                                })
+                        })
+                        .collect();
+                    if !feedback.is_empty() {
+                        let feedback_path = state_file.with_file_name("feedback.jsonl");
+                        match FeedbackEntry::append_all(&feedback, &feedback_path).await {
+                            Ok(()) => info!(
+                                "Recorded {} corrected suggestions in {:?}",
+                                feedback.len(),
+                                feedback_path
+                            ),
+                            Err(e) => warn!("Failed to record review feedback: {}", e),
+                        }
+                    }
+
                     // Apply user decisions to classifications
                     for decision in &decisions {
                         if matches!(decision.action, DecisionAction::Skip) {
@@ -1278,7 +3267,7 @@ pub async fn run_pipeline(
                         // Update classifications for messages in this cluster
                         for (msg, class) in &mut classifications {
                             if decision.message_ids.contains(&msg.id) {
-                                class.suggested_label = decision.label.clone();
+                                class.suggested_label = decision.label.to_string();
                                 class.should_archive = decision.should_archive;
                             }
                         }
@@ -1294,16 +3283,24 @@ pub async fn run_pipeline(
                             DecisionAction::Skip => "Skip",
                             DecisionAction::Delete => "Delete",
                             DecisionAction::Exclude => "Exclude",
+                            DecisionAction::Merge { .. } => "Merge",
                         };
 
-                        let sender_pattern = if decision.is_specific_sender {
-                            format!("from:({})", decision.sender_email)
-                        } else if decision.excluded_senders.is_empty() {
-                            format!("from:(*@{})", decision.sender_domain)
+                        let criteria = decision
+                            .filter_from_pattern()
+                            .map(|pattern| format!("from:({})", pattern))
+                            .into_iter()
+                            .chain(decision.filter_extra_terms())
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        let sender_pattern = if decision.is_specific_sender
+                            || decision.excluded_senders.is_empty()
+                        {
+                            criteria
                         } else {
                             format!(
-                                "from:(*@{}) excluding {} senders",
-                                decision.sender_domain,
+                                "{} excluding {} senders",
+                                criteria,
                                 decision.excluded_senders.len()
                             )
                         };
@@ -1325,6 +3322,19 @@ pub async fn run_pipeline(
                             action_str, sender_pattern, label_info, archive_info
                         );
                     }
+                    let merged_count = merge_count - dropped_merges.len();
+                    if merged_count > 0 {
+                        println!(
+                            "  [Merge] {} clusters merged into other clusters",
+                            merged_count
+                        );
+                    }
+                    for dropped in &dropped_merges {
+                        println!(
+                            "  [Merge dropped] {} into {}: {}",
+                            dropped.cluster, dropped.into_domain, dropped.reason
+                        );
+                    }
 
                     // Store decisions for filter generation
                     // Include Accept/Custom for creation, Reject/Delete/Exclude for deletion
@@ -1343,7 +3353,7 @@ pub async fn run_pipeline(
                         .collect();
 
                     // Save decisions for resume capability
-                    let decisions_file = cli.state_file.with_file_name("decisions.json");
+                    let decisions_file = state_file.with_file_name("decisions.json");
                     let decisions_json =
                         serde_json::to_string_pretty(&review_decisions).map_err(|e| {
                             GmailError::Unknown(format!("Failed to serialize decisions: {}", e))
@@ -1393,6 +3403,7 @@ pub async fn run_pipeline(
                 } else {
                     println!("\nNo clusters meet minimum size threshold for review.");
                 }
+                state.record_phase_timing("Review", review_started.elapsed());
             }
 
             // Step 8: Analyze classifications
@@ -1411,10 +3422,112 @@ pub async fn run_pipeline(
             reporter.finish_spinner(&analysis_spinner, "Email pattern analysis complete");
         }
 
+        // Filter rules are decided before labels are created, so that labels only a
+        // filter applies (attachment sublabels) are created with the rest
+        let classification_by_id: HashMap<&str, &Classification> = classifications
+            .iter()
+            .map(|(msg, classification)| (msg.id.as_str(), classification))
+            .collect();
+        let mut filter_manager = FilterManager::new(Box::new(client.clone()));
+
+        // Generate filters: from review decisions if review was completed, otherwise from classifications
+        // Note: review_mode_completed means user pressed W (finish), not Q (quit)
+        // If all items were skipped, review_decisions is empty but we still don't fall back
+        let mut filters: Vec<FilterRule> = if labels_only {
+            Vec::new()
+        } else if review_mode_completed {
+            // Convert user decisions directly to filter rules
+            // Filter out Reject/Delete decisions without existing filters (they don't need new filters)
+            // Keep Accept and Custom decisions for filter creation
+            // Keep Reject/Delete with existing_filter_id for filter deletion (handled separately)
+            review_decisions
+                .iter()
+                .filter(|d| matches!(d.action, DecisionAction::Accept | DecisionAction::Custom(_)))
+                .flat_map(|d| {
+                    // Filters entered as a raw Gmail query ([Q] in review) are used as-is
+                    if let Some(custom) = &d.custom_filter {
+                        let mut rule = custom.clone();
+                        rule.should_archive = d.should_archive;
+                        rule.estimated_matches = d.message_ids.len();
+                        return vec![rule];
+                    }
+
+                    let from_pattern = d.filter_from_pattern();
+
+                    // Build filter name including subject pattern if present
+                    let filter_name = if let Some(subject) = &d.subject_pattern {
+                        format!("{} + \"{}\" → {}", d.sender_email, subject, d.label)
+                    } else if d.is_specific_sender {
+                        format!("{} → {}", d.sender_email, d.label)
+                    } else if let Some(list_id) = &d.list_id {
+                        format!("list {} → {}", list_id, d.label)
+                    } else {
+                        format!("{} → {}", d.sender_domain, d.label)
+                    };
+                    let filter_name = match (&d.list_id, d.is_specific_sender) {
+                        (Some(list_id), true) => format!("{} (list {})", filter_name, list_id),
+                        _ => filter_name,
+                    };
+
+                    // If there's a subject pattern, use it as a subject keyword
+                    let subject_keywords = if let Some(subject) = &d.subject_pattern {
+                        vec![subject.clone()]
+                    } else {
+                        vec![]
+                    };
+
+                    let rule = FilterRule {
+                        id: None,
+                        name: filter_name,
+                        from_pattern,
+                        is_specific_sender: d.is_specific_sender,
+                        excluded_senders: d.excluded_senders.clone(),
+                        subject_keywords,
+                        target_label: d.label.clone(),
+                        target_label_id: None,
+                        should_archive: d.should_archive,
+                        estimated_matches: d.message_ids.len(),
+                        priority: DEFAULT_FILTER_PRIORITY,
+                        extra_terms: d.filter_extra_terms(),
+                        additional_labels: d.additional_labels.clone(),
+                        additional_label_ids: vec![],
+                    };
+                    let mut rules = FilterManager::attachment_filters(
+                        &rule,
+                        d.message_ids
+                            .iter()
+                            .filter_map(|id| classification_by_id.get(id.as_str()).copied()),
+                    );
+                    rules.insert(0, rule);
+                    rules
+                })
+                .collect()
+        } else if !review {
+            // No review mode requested, generate from classifications
+            let idf_index = FilterManager::build_idf_index(classifications.iter().map(|(m, _)| m));
+            filter_manager.generate_filters_from_classifications(
+                &classifications,
+                config.classification.minimum_emails_for_label,
+                &idf_index,
+            )
+        } else {
+            // Review mode requested but no clusters met threshold, create empty filter list
+            Vec::new()
+        };
+        // Filters are reported by name, so refuse duplicates before any label or filter exists
+        let duplicates = FilterManager::check_name_uniqueness(&filters);
+        if !duplicates.is_empty() {
+            return Err(GmailError::ConfigError(format!(
+                "Duplicate filter names: {}",
+                duplicates.join(", ")
+            )));
+        }
+
         // Step 8: Create labels (skip if resuming from CreatingFilters phase)
         if !resume || !matches!(state.phase, ProcessingPhase::CreatingFilters) {
             state.phase = ProcessingPhase::CreatingLabels;
-            state.save(&cli.state_file).await?;
+            state.publish().await?;
+            let labels_started = Instant::now();
 
             if interactive {
                 println!("\nReady to create labels. Categories found:");
@@ -1466,8 +3579,9 @@ pub async fn run_pipeline(
                 // (review_decisions may be empty if all items were skipped - that's intentional)
                 for decision in &review_decisions {
                     if !decision.label.is_empty() {
-                        unique_labels.insert(decision.label.clone());
+                        unique_labels.insert(decision.label.to_string());
                     }
+                    unique_labels.extend(decision.additional_labels.iter().map(|l| l.to_string()));
                 }
             } else if !review {
                 // No review mode: collect from classifications for domains above threshold
@@ -1487,6 +3601,15 @@ pub async fn run_pipeline(
                 }
             }
 
+            // Attachment filters apply `<label>/<sublabel>`, which no message may
+            // have been classified into
+            unique_labels.extend(
+                filters
+                    .iter()
+                    .filter(|filter| !filter.extra_terms.is_empty())
+                    .map(|filter| filter.target_label.to_string()),
+            );
+
             // Determine which labels already exist vs need to be created
             let unique_labels_vec: Vec<String> = unique_labels.iter().cloned().collect();
             let _existing_labels = label_manager.find_existing_labels(&unique_labels_vec);
@@ -1499,10 +3622,33 @@ pub async fn run_pipeline(
             labels_created = 0; // Reset for this run
             let mut labels_skipped = 0;
 
+            // Category of each suggested label, used to pick its Gmail UI visibility
+            let label_categories: HashMap<String, crate::models::EmailCategory> = classifications
+                .iter()
+                .map(|(_, c)| (c.suggested_label.to_lowercase(), c.category.clone()))
+                .collect();
+
+            // Create all missing parent labels up front, shallowest first, so that
+            // child labels never race to create the same parent
+            if !dry_run {
+                let new_labels: Vec<String> = unique_labels
+                    .iter()
+                    .filter_map(|label| label_manager.sanitize_label_name(label).ok())
+                    .filter(|sanitized| label_manager.get_label_id(sanitized).is_none())
+                    .collect();
+                let parents_created = label_manager.create_missing_parents(&new_labels).await?;
+                if parents_created > 0 {
+                    info!("Created {} parent labels", parents_created);
+                }
+            }
+
             for label in &unique_labels {
                 // The label from suggested_label already has full path like "auto/other/domain"
                 // We need to create it directly without adding another prefix
                 let sanitized = label_manager.sanitize_label_name(label).unwrap_or_default();
+                if !state.labels_applied.contains(&sanitized) {
+                    state.labels_applied.push(sanitized.clone());
+                }
 
                 // Check if label already exists in cache (case-insensitive)
                 if let Some(existing_id) = label_manager.get_label_id(&sanitized) {
@@ -1515,8 +3661,18 @@ pub async fn run_pipeline(
 
                 if !dry_run {
                     // Create the label directly (it already has the full path)
-                    let label_id = label_manager.create_label_direct(&sanitized).await?;
-                    state.labels_created.push(label_id.clone());
+                    let visibility = label_categories
+                        .get(&label.to_lowercase())
+                        .map(|category| config.labels.visibility_for(category))
+                        .unwrap_or_default();
+                    let label_id = label_manager
+                        .create_label_direct(&sanitized, &visibility)
+                        .await
+                        .context(format!("Creating label '{}'", sanitized))?;
+                    state.labels_created.push(label_id.to_string());
+                    state
+                        .label_ids_by_name
+                        .insert(sanitized.to_string(), label_id.to_string());
                     // Store with lowercase key for case-insensitive lookup later
                     label_name_to_id.insert(label.to_lowercase(), label_id);
                     labels_created += 1;
@@ -1536,17 +3692,32 @@ pub async fn run_pipeline(
                 &label_spinner,
                 &format!("{} {} labels{}", label_action, labels_created, skip_msg),
             );
-            state.checkpoint(&cli.state_file).await?;
+            state.record_phase_timing("Labels", labels_started.elapsed());
+            state.record_checkpoint();
+            state.publish().await?;
         }
 
+        // Track created filters so their match counts can be measured over time
+        let filter_stats_path = state_file.with_file_name("filter-stats.json");
+        let mut filter_stats = FilterStatsStore::load(&filter_stats_path)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to load filter stats, starting fresh: {}", e);
+                FilterStatsStore::new()
+            });
+
+        // Labelling existing messages as filters are created counts as "Apply"
+        let mut apply_time = Duration::ZERO;
+
         // Step 9: Create filters (unless labels_only)
-        let (filters_created, planned_filters, total_labeled_count): (
+        let (filters_created, planned_filters, mut total_labeled_count): (
             usize,
             Vec<PlannedFilter>,
             usize,
         ) = if !labels_only {
             state.phase = ProcessingPhase::CreatingFilters;
-            state.save(&cli.state_file).await?;
+            state.publish().await?;
+            let filters_started = Instant::now();
 
             if interactive {
                 println!("\nReady to create {} filter rules", domain_counts.len());
@@ -1557,68 +3728,6 @@ pub async fn run_pipeline(
                 }
             }
 
-            let mut filter_manager = FilterManager::new(Box::new(client.clone()));
-
-            // Generate filters: from review decisions if review was completed, otherwise from classifications
-            // Note: review_mode_completed means user pressed W (finish), not Q (quit)
-            // If all items were skipped, review_decisions is empty but we still don't fall back
-            let filters: Vec<FilterRule> = if review_mode_completed {
-                // Convert user decisions directly to filter rules
-                // Filter out Reject/Delete decisions without existing filters (they don't need new filters)
-                // Keep Accept and Custom decisions for filter creation
-                // Keep Reject/Delete with existing_filter_id for filter deletion (handled separately)
-                review_decisions
-                    .iter()
-                    .filter(|d| {
-                        matches!(d.action, DecisionAction::Accept | DecisionAction::Custom(_))
-                    })
-                    .map(|d| {
-                        let from_pattern = if d.is_specific_sender {
-                            Some(d.sender_email.clone())
-                        } else {
-                            Some(format!("*@{}", d.sender_domain))
-                        };
-
-                        // Build filter name including subject pattern if present
-                        let filter_name = if let Some(subject) = &d.subject_pattern {
-                            format!("{} + \"{}\" → {}", d.sender_email, subject, d.label)
-                        } else if d.is_specific_sender {
-                            format!("{} → {}", d.sender_email, d.label)
-                        } else {
-                            format!("{} → {}", d.sender_domain, d.label)
-                        };
-
-                        // If there's a subject pattern, use it as a subject keyword
-                        let subject_keywords = if let Some(subject) = &d.subject_pattern {
-                            vec![subject.clone()]
-                        } else {
-                            vec![]
-                        };
-
-                        FilterRule {
-                            id: None,
-                            name: filter_name,
-                            from_pattern,
-                            is_specific_sender: d.is_specific_sender,
-                            excluded_senders: d.excluded_senders.clone(),
-                            subject_keywords,
-                            target_label_id: d.label.clone(),
-                            should_archive: d.should_archive,
-                            estimated_matches: d.message_ids.len(),
-                        }
-                    })
-                    .collect()
-            } else if !review {
-                // No review mode requested, generate from classifications
-                filter_manager.generate_filters_from_classifications(
-                    &classifications,
-                    config.classification.minimum_emails_for_label,
-                )
-            } else {
-                // Review mode requested but no clusters met threshold, create empty filter list
-                Vec::new()
-            };
-
             // Process Delete/Reject/Exclude decisions separately (they're not in the filters Vec)
             // These are orphaned filters or filters the user explicitly wants to delete
             let delete_decisions: Vec<_> = review_decisions
@@ -1640,7 +3749,7 @@ pub async fn run_pipeline(
                 // Build label ID -> name map for cleanup messages (inverse of label_name_to_id)
                 let label_id_to_name_for_deletion: HashMap<String, String> = label_name_to_id
                     .iter()
-                    .map(|(name, id)| (id.clone(), name.clone()))
+                    .map(|(name, id)| (id.to_string(), name.clone()))
                     .collect();
 
                 for decision in &delete_decisions {
@@ -1663,6 +3772,10 @@ pub async fn run_pipeline(
                     } else {
                         format!("*@{}", decision.sender_domain)
                     };
+                    let pattern = match &decision.list_id {
+                        Some(list_id) => format!("{} (list: {})", pattern, list_id),
+                        None => pattern,
+                    };
 
                     // Look up label name from existing filter
                     let label_name = existing_filters
@@ -1702,12 +3815,23 @@ pub async fn run_pipeline(
                                         msg_ids.len()
                                     );
                                     messages_cleaned += msg_ids.len();
-                                    let labels_to_remove = vec![label_id.clone()];
-                                    let empty: Vec<String> = vec![];
-                                    for chunk in msg_ids.chunks(1000) {
-                                        let _ = client
-                                            .batch_modify_labels(chunk, &empty, &labels_to_remove)
-                                            .await;
+                                    let labels_to_remove = vec![LabelId::new(label_id.as_str())];
+                                    let empty: Vec<LabelId> = vec![];
+                                    match client
+                                        .batch_modify_labels(&msg_ids, &empty, &labels_to_remove)
+                                        .await
+                                    {
+                                        Ok(result) => record_failed_messages(
+                                            &mut state,
+                                            &mut failed_modifications,
+                                            &result,
+                                            &empty,
+                                            &labels_to_remove,
+                                        ),
+                                        Err(e) => warn!(
+                                            "Failed to remove label '{}': {}",
+                                            label_name, e
+                                        ),
                                     }
                                 }
                             }
@@ -1747,25 +3871,28 @@ pub async fn run_pipeline(
             // Key must include subject_pattern to avoid collisions between subject-based clusters
             let mut decision_map: HashMap<String, &ClusterDecision> = HashMap::new();
             for decision in &review_decisions {
-                let base = if decision.is_specific_sender {
-                    decision.sender_email.clone()
-                } else {
-                    format!("*@{}", decision.sender_domain)
-                };
-                let key = if let Some(subject) = &decision.subject_pattern {
-                    format!("{}|subject:{}", base, subject)
-                } else {
-                    base
-                };
+                let key = decision_filter_key(
+                    decision.filter_from_pattern().as_deref(),
+                    &decision.subject_pattern.iter().cloned().collect::<Vec<_>>(),
+                    &decision.filter_extra_terms(),
+                );
                 decision_map.insert(key, decision);
             }
 
             // Build label ID -> name map for deletion cleanup (inverse of label_name_to_id)
             let label_id_to_name_for_deletion: HashMap<String, String> = label_name_to_id
                 .iter()
-                .map(|(name, id)| (id.clone(), name.clone()))
+                .map(|(name, id)| (id.to_string(), name.clone()))
                 .collect();
 
+            FilterManager::sort_for_creation(&mut filters, config.scan.filter_sort_order);
+            if !dry_run {
+                // Labels were created above, so every filter's label now has an ID
+                for filter in &mut filters {
+                    filter.resolve_labels(&label_name_to_id)?;
+                }
+            }
+
             for filter in &filters {
                 filter_bar
                     .set_message(format!("Processing: {}", truncate_string(&filter.name, 40)));
@@ -1773,41 +3900,23 @@ pub async fn run_pipeline(
                 let gmail_query = filter_manager.build_gmail_query(filter);
 
                 if !dry_run {
-                    // Look up the actual label ID from the label name (case-insensitive)
-                    let label_id = label_name_to_id
-                        .get(&filter.target_label_id.to_lowercase())
-                        .ok_or_else(|| {
-                            GmailError::LabelError(format!(
-                                "Label ID not found for label: {}",
-                                filter.target_label_id
-                            ))
-                        })?;
-
-                    // Create a modified filter with the actual label ID
-                    let mut filter_with_id = filter.clone();
-                    filter_with_id.target_label_id = label_id.clone();
+                    let add_label_ids = filter.label_ids()?;
+                    let label_id = &add_label_ids[0];
 
                     // Check if this filter came from a decision with an existing filter
                     // Key must match the format used when building decision_map
-                    let decision_key = {
-                        let base = if filter.is_specific_sender {
-                            filter.from_pattern.clone().unwrap_or_default()
-                        } else {
-                            format!(
-                                "*@{}",
-                                filter
-                                    .from_pattern
-                                    .as_deref()
-                                    .unwrap_or("")
-                                    .trim_start_matches("*@")
-                            )
-                        };
-                        if !filter.subject_keywords.is_empty() {
-                            format!("{}|subject:{}", base, filter.subject_keywords.join(" "))
-                        } else {
-                            base
+                    // Attachment filters and raw queries never replace an existing filter
+                    let from_pattern = match &filter.from_pattern {
+                        Some(pattern) if !filter.is_specific_sender => {
+                            Some(format!("*@{}", pattern.trim_start_matches("*@")))
                         }
+                        pattern => pattern.clone(),
                     };
+                    let decision_key = decision_filter_key(
+                        from_pattern.as_deref(),
+                        &filter.subject_keywords,
+                        &filter.extra_terms,
+                    );
 
                     let decision = decision_map.get(&decision_key);
                     let existing_filter_id = decision.and_then(|d| d.existing_filter_id.as_ref());
@@ -1844,10 +3953,20 @@ pub async fn run_pipeline(
                                             if !msg_ids.is_empty() {
                                                 info!("Removing label '{}' from {} messages", label_name, msg_ids.len());
                                                 messages_cleaned += msg_ids.len();
-                                                let labels_to_remove = vec![label_id.clone()];
-                                                let empty: Vec<String> = vec![];
-                                                for chunk in msg_ids.chunks(1000) {
-                                                    let _ = client.batch_modify_labels(chunk, &empty, &labels_to_remove).await;
+                                                let labels_to_remove = vec![LabelId::new(label_id.as_str())];
+                                                let empty: Vec<LabelId> = vec![];
+                                                match client.batch_modify_labels(&msg_ids, &empty, &labels_to_remove).await {
+                                                    Ok(result) => record_failed_messages(
+                                                        &mut state,
+                                                        &mut failed_modifications,
+                                                        &result,
+                                                        &empty,
+                                                        &labels_to_remove,
+                                                    ),
+                                                    Err(e) => warn!(
+                                                        "Failed to remove label '{}': {}",
+                                                        label_name, e
+                                                    ),
                                                 }
                                             }
                                         }
@@ -1858,6 +3977,7 @@ pub async fn run_pipeline(
                             match client.delete_filter(existing_id).await {
                                 Ok(_) => {
                                     info!("Successfully deleted filter");
+                                    filter_stats.untrack(existing_id);
                                     // Note: filters_deleted was already counted in the delete_decisions loop
                                 }
                                 Err(e) => warn!("Failed to delete filter: {}", e),
@@ -1869,8 +3989,14 @@ pub async fn run_pipeline(
                                 "Updating existing filter '{}' (ID: {}) with new settings",
                                 filter.name, existing_id
                             );
-                            match client.update_filter(existing_id, &filter_with_id).await {
+                            match client.update_filter(existing_id, filter).await {
                                 Ok(new_id) => {
+                                    filter_stats.replace(
+                                        existing_id,
+                                        &new_id,
+                                        &gmail_query,
+                                        label_id.as_str(),
+                                    );
                                     state.filters_created.push(new_id);
                                     filters_created += 1;
                                 }
@@ -1889,7 +4015,7 @@ pub async fn run_pipeline(
                         // This handles the resume case where filter may have been created before crash
                         let already_exists = existing_filters
                             .iter()
-                            .any(|ef| ef.matches_filter_rule(&filter_with_id));
+                            .any(|ef| ef.matches_filter_rule(filter));
 
                         if already_exists {
                             info!(
@@ -1899,13 +4025,18 @@ pub async fn run_pipeline(
                             filters_skipped += 1;
                         } else {
                             // Create new filter
-                            let filter_id = filter_manager.create_filter(&filter_with_id).await?;
+                            let filter_id = filter_manager
+                                .create_filter(filter)
+                                .await
+                                .context(format!("Creating filter '{}'", filter.name))?;
+                            filter_stats.track(&filter_id, &gmail_query, label_id.as_str());
                             state.filters_created.push(filter_id);
                             filters_created += 1;
                         }
                     }
 
                     // ALWAYS apply labels retroactively, even if filter already exists
+                    let apply_started = Instant::now();
                     let matching_ids = client.list_message_ids(&gmail_query).await?;
                     if !matching_ids.is_empty() {
                         let count = matching_ids.len();
@@ -1915,35 +4046,90 @@ pub async fn run_pipeline(
                                 "Labeling and archiving {} emails for filter '{}'",
                                 count, filter.name
                             );
+                            let remove_label_ids = vec![LabelId::new("INBOX")];
                             match client
                                 .batch_modify_labels(
                                     &matching_ids,
-                                    std::slice::from_ref(label_id),
-                                    &["INBOX".to_string()],
+                                    &add_label_ids,
+                                    &remove_label_ids,
                                 )
                                 .await
-                            {
-                                Ok(modified) => {
-                                    total_labeled += modified;
-                                    total_archived += modified;
+                                .context(format!(
+                                    "Labeling and archiving {} emails for filter '{}'",
+                                    count, filter.name
+                                )) {
+                                Ok(result) => {
+                                    total_labeled += result.succeeded;
+                                    total_archived += result.succeeded;
+                                    record_failed_messages(
+                                        &mut state,
+                                        &mut failed_modifications,
+                                        &result,
+                                        &add_label_ids,
+                                        &remove_label_ids,
+                                    );
+                                }
+                                Err(e) => record_failed_batch(
+                                    &mut state,
+                                    &mut failed_modifications,
+                                    &matching_ids,
+                                    &e,
+                                    &add_label_ids,
+                                    &remove_label_ids,
+                                ),
+                            }
+                        } else if add_label_ids.len() > 1 {
+                            // Add primary and secondary labels in one batch call
+                            info!("Labeling {} emails for filter '{}'", count, filter.name);
+                            match client
+                                .batch_modify_labels(&matching_ids, &add_label_ids, &[])
+                                .await
+                                .context(format!(
+                                    "Labeling {} emails for filter '{}'",
+                                    count, filter.name
+                                )) {
+                                Ok(result) => {
+                                    total_labeled += result.succeeded;
+                                    record_failed_messages(
+                                        &mut state,
+                                        &mut failed_modifications,
+                                        &result,
+                                        &add_label_ids,
+                                        &[],
+                                    );
                                 }
-                                Err(e) => warn!(
-                                    "Failed to label/archive emails for filter '{}': {}",
-                                    filter.name, e
+                                Err(e) => record_failed_batch(
+                                    &mut state,
+                                    &mut failed_modifications,
+                                    &matching_ids,
+                                    &e,
+                                    &add_label_ids,
+                                    &[],
                                 ),
                             }
                         } else {
                             // Just add label
                             info!("Labeling {} emails for filter '{}'", count, filter.name);
-                            match client.batch_add_label(&matching_ids, label_id).await {
+                            match client
+                                .batch_add_label(&matching_ids, label_id)
+                                .await
+                                .context(format!(
+                                    "Labeling {} emails for filter '{}'",
+                                    count, filter.name
+                                )) {
                                 Ok(modified) => total_labeled += modified,
-                                Err(e) => warn!(
-                                    "Failed to label emails for filter '{}': {}",
-                                    filter.name, e
+                                Err(e) => record_failed_batch(
+                                    &mut state,
+                                    &mut failed_modifications,
+                                    &matching_ids,
+                                    &e,
+                                    &add_label_ids,
+                                    &[],
                                 ),
                             }
                         }
                     }
+                    apply_time += apply_started.elapsed();
                 } else {
                     // Dry run: query the API to get actual match count (read-only)
                     let matching_ids = client.list_message_ids(&gmail_query).await?;
@@ -1953,9 +4139,15 @@ pub async fn run_pipeline(
                         name: filter.name.clone(),
                         from_pattern: filter.from_pattern.clone(),
                         subject_keywords: filter.subject_keywords.clone(),
-                        target_label: filter.target_label_id.clone(),
+                        target_label: filter.target_label.to_string(),
+                        additional_labels: filter
+                            .additional_labels
+                            .iter()
+                            .map(|label| label.to_string())
+                            .collect(),
                         should_archive: filter.should_archive,
                         estimated_matches: filter.estimated_matches,
+                        priority: filter.priority,
                         gmail_query,
                         actual_matches,
                     });
@@ -1994,7 +4186,8 @@ pub async fn run_pipeline(
                 "{} {} filters{}{}",
                 filter_action, filters_created, skip_msg, retroactive_msg
             ));
-            state.checkpoint(&cli.state_file).await?;
+            state.record_checkpoint();
+            state.publish().await?;
 
             // Ensure label hierarchy is complete (repair missing parent labels)
             // This must run before orphaned label detection to avoid treating
@@ -2048,7 +4241,27 @@ pub async fn run_pipeline(
                     if !dry_run {
                         // Remove labels from messages before deleting them
                         for (label_id, label_name) in &orphaned_labels {
-                            match label_manager.remove_label_from_all_messages(label_id).await {
+                            let remove_spinner = reporter.add_spinner(&format!(
+                                "Removing label '{}' from messages...",
+                                label_name
+                            ));
+                            let on_progress: LabelProgressCallback = {
+                                let pb = remove_spinner.clone();
+                                let label_name = label_name.clone();
+                                Arc::new(move |count| {
+                                    pb.inc(count as u64);
+                                    pb.set_message(format!(
+                                        "Removing label '{}' from messages ({} done)...",
+                                        label_name,
+                                        pb.position()
+                                    ));
+                                })
+                            };
+                            let removed = label_manager
+                                .remove_label_from_all_messages(label_id, Some(on_progress))
+                                .await;
+                            remove_spinner.finish_and_clear();
+                            match removed {
                                 Ok(count) => {
                                     if count > 0 {
                                         info!("Removed label '{}' from {} messages", label_name, count);
@@ -2070,6 +4283,10 @@ pub async fn run_pipeline(
                 }
             }
 
+            state.record_phase_timing(
+                "Filters",
+                filters_started.elapsed().saturating_sub(apply_time),
+            );
             (filters_created, planned_filters, total_labeled)
         } else {
             (0, Vec::new(), 0)
@@ -2078,39 +4295,71 @@ pub async fn run_pipeline(
         // Step 10: Labels already applied during filter creation (using Gmail query search)
         // This catches ALL matching emails, not just recent ones
         state.phase = ProcessingPhase::ApplyingLabels;
+        let retry_started = Instant::now();
+
+        // Give messages whose label changes failed one more try
+        if !failed_modifications.is_empty() {
+            total_labeled_count +=
+                retry_failed_modifications(client.as_ref(), &mut state, failed_modifications).await;
+            if !state.failed_message_ids.is_empty() {
+                warn!(
+                    "{} messages still have label application errors",
+                    state.failed_message_ids.len()
+                );
+            }
+        }
+        if !labels_only && !dry_run {
+            state.record_phase_timing("Apply", apply_time + retry_started.elapsed());
+        }
         state.messages_modified = total_labeled_count;
         state.phase = ProcessingPhase::Complete;
         state.completed = true;
-        state.save(&cli.state_file).await?;
+        state.updated_at = Utc::now();
+        // Only the production client has a circuit breaker
+        let circuit = match &production_client {
+            Some(production) => Some(production.circuit_breaker_stats().await.status()),
+            None => None,
+        };
+        state.circuit = circuit.clone();
+        state.flush().await?;
+        state
+            .save(&ProcessingState::run_state_path(state_file, &run_id))
+            .await?;
+
+        // Measure how many messages each tracked filter matches now
+        let stale_filters: Vec<(String, String)> = if dry_run {
+            Vec::new()
+        } else {
+            filter_stats.refresh(client.as_ref()).await;
+            filter_stats.save(&filter_stats_path).await?;
+            filter_stats
+                .stale(config.stats.stale_threshold_days)
+                .into_iter()
+                .map(|f| (f.query.clone(), f.label.clone()))
+                .collect()
+        };
 
         // Step 11: Generate report
         let completed_at = Utc::now();
         let duration_seconds = (completed_at - started_at).num_seconds();
 
+        let bounces_detected = category_counts
+            .get(&format!("{:?}", EmailCategory::Bounce))
+            .copied()
+            .unwrap_or(0);
         let mut classification_breakdown = Vec::new();
         let total = classifications.len() as f32;
         for (category, count) in category_counts {
             let percentage = (count as f32 / total) * 100.0;
             classification_breakdown.push((category, count, percentage));
         }
-        classification_breakdown.sort_by(|a, b| b.1.cmp(&a.1));
-
-        let mut top_senders = Vec::new();
-        let mut domain_list: Vec<_> = domain_counts.into_iter().collect();
-        domain_list.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
-        for (domain, msgs) in domain_list.iter().take(10) {
-            if let Some(msg) = msgs.first() {
-                let label = format!(
-                    "{:?}",
-                    classifications
-                        .iter()
-                        .find(|(m, _)| m.id == msg.id)
-                        .map(|(_, c)| &c.category)
-                        .unwrap_or(&crate::models::EmailCategory::Other)
-                );
-                top_senders.push((domain.clone(), msgs.len(), label));
-            }
-        }
+        classification_breakdown.sort_by_key(|c| std::cmp::Reverse(c.1));
+
+        let top_senders = TopSender::top(
+            classifications
+                .iter()
+                .map(|(m, c)| (m.sender_domain.as_str(), &c.category)),
+        );
 
         // Build category examples (up to 10 per category)
         let mut category_examples: HashMap<String, Vec<(String, String)>> = HashMap::new();
@@ -2152,6 +4401,9 @@ pub async fn run_pipeline(
             None
         };
 
+        // Get quota usage statistics
+        let quota_stats = client.quota_stats().await;
+
         let report = Report {
             run_id: run_id.clone(),
             started_at,
@@ -2178,26 +4430,67 @@ pub async fn run_pipeline(
             orphaned_labels_to_delete: orphaned_labels_to_delete_names,
             messages_cleaned,
             hierarchy_labels_created,
+            failed_count: state.failed_message_ids.len(),
+            failed_message_ids: state.failed_message_ids.clone(),
+            retry_attempts: fetch_retry_attempts,
+            permanently_failed: state.permanently_failed_ids.len(),
+            stale_filters,
             classification_breakdown,
+            bounces_detected,
+            anomalies,
             top_senders,
             category_examples,
             dry_run,
             planned_changes,
+            api_usage: quota_stats
+                .by_operation
+                .iter()
+                .map(|(operation, usage)| (operation.clone(), *usage))
+                .collect(),
+            label_names: state.labels_applied.clone(),
+            phase_timings: state.phase_breakdown(),
         };
 
-        // Save report
-        let report_path = cli
-            .state_file
-            .with_file_name(format!("report-{}.md", run_id));
-        report
-            .save(&report_path)
-            .await
-            .map_err(|e| GmailError::Unknown(format!("Failed to save report: {}", e)))?;
+        // Save report; JSON replaces the Markdown report, HTML is saved next to it
+        let report_format = config.execution.report_format;
+        let report_path = state_file.with_file_name(format!(
+            "report-{}.{}",
+            run_id,
+            if report_format == ReportFormat::Json {
+                "json"
+            } else {
+                "md"
+            }
+        ));
+        let mut html_report_path = None;
+        if report_format == ReportFormat::Json {
+            report.save_json(&report_path).await?;
+        } else {
+            let previous_run_diff = previous_state.as_ref().map(|prev| state.diff(prev));
+            report
+                .save(&report_path, previous_run_diff.as_ref())
+                .await
+                .map_err(|e| GmailError::Unknown(format!("Failed to save report: {}", e)))?;
+            if report_format == ReportFormat::Html {
+                let path = report_path.with_extension("html");
+                tokio::fs::write(&path, report.to_html()).await?;
+                tracing::info!("Report saved to {:?}", path);
+                html_report_path = Some(path);
+            }
+        }
 
         tracing::info!("Report saved to {:?}", report_path);
 
-        // Get quota usage statistics
-        let quota_stats = client.quota_stats().await;
+        if let Some(keep_last) = config.execution.auto_cleanup_runs.filter(|_| !dry_run) {
+            let dir = crate::cleanup::state_dir(state_file);
+            match crate::cleanup::find_stale_files(state_file, keep_last)
+                .and_then(|files| crate::cleanup::remove_files(&files))
+            {
+                Ok(0) => {}
+                Ok(removed) => info!("Removed {} old run files from {:?}", removed, dir),
+                Err(e) => warn!("Failed to clean up old run files: {}", e),
+            }
+        }
 
         if dry_run {
             println!("\nDry run completed! No changes were made.");
@@ -2209,6 +4502,9 @@ pub async fn run_pipeline(
             println!("\nPipeline completed successfully!");
             println!("Report saved to: {:?}", report_path);
         }
+        if let Some(path) = &html_report_path {
+            println!("Report also saved to: {:?}", path);
+        }
 
         // Display API usage statistics
         println!("\nAPI Usage:");
@@ -2224,6 +4520,23 @@ pub async fn run_pipeline(
             let avg = quota_stats.total_consumed as f64 / quota_stats.total_operations as f64;
             println!("  Average quota per operation: {:.1} units", avg);
         }
+        if let Some(circuit) = &circuit {
+            println!("  Circuit: {}", circuit);
+        }
+        if !quota_stats.by_operation.is_empty() {
+            println!(
+                "\n  {:<22} {:>10} {:>12}",
+                "Operation", "Calls", "Quota units"
+            );
+            for (operation, usage) in quota_stats.breakdown_by_cost() {
+                println!(
+                    "  {:<22} {:>10} {:>12}",
+                    operation,
+                    format_number(usage.count),
+                    format_number(usage.quota_consumed)
+                );
+            }
+        }
 
         Ok(report)
     } else {
@@ -2250,3 +4563,174 @@ fn confirm_action(prompt: &str) -> Result<bool> {
 
     Ok(input.trim().to_lowercase() == "y")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::SerializedClassification;
+
+    fn classification(id: &str, domain: &str, category: EmailCategory) -> SerializedClassification {
+        SerializedClassification {
+            message_id: id.to_string(),
+            thread_id: format!("t-{}", id),
+            sender_email: format!("news@{}", domain),
+            sender_domain: domain.to_string(),
+            subject: format!("Subject {}", id),
+            date_received: Utc::now(),
+            category,
+            suggested_label: "AutoManaged/Newsletters".to_string(),
+            should_archive: true,
+            confidence: 0.9,
+            sender_name: String::new(),
+            recipients: Vec::new(),
+            labels: Vec::new(),
+            has_unsubscribe: false,
+            is_automated: false,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 0,
+            reasoning: None,
+            reasons: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_report_from_state() {
+        let mut state = ProcessingState::new();
+        state.messages_scanned = 4;
+        state.messages_classified = 3;
+        state.labels_created = vec!["Label_1".to_string()];
+        state
+            .label_ids_by_name
+            .insert("AutoManaged/Shop".to_string(), "Label_1".to_string());
+        state.labels_applied = vec![
+            "AutoManaged/Shop".to_string(),
+            "AutoManaged/Newsletters".to_string(),
+        ];
+        state.filters_created = vec!["f1".to_string(), "f2".to_string()];
+        state.failed_message_ids = vec!["m4".to_string()];
+        state.classifications = vec![
+            classification("m1", "news.com", EmailCategory::Newsletter),
+            classification("m2", "news.com", EmailCategory::Newsletter),
+            classification("m3", "mailer.com", EmailCategory::Bounce),
+        ];
+
+        let report = Report::from_state(&state);
+
+        assert_eq!(report.run_id, state.run_id);
+        assert_eq!(report.emails_scanned, 4);
+        assert_eq!(report.emails_classified, 3);
+        assert_eq!(report.labels_created, 1);
+        assert_eq!(report.filters_created, 2);
+        assert_eq!(report.failed_count, 1);
+        assert_eq!(report.failed_message_ids, vec!["m4".to_string()]);
+        assert_eq!(report.bounces_detected, 1);
+        // Labels that already existed are reported next to the created one
+        assert_eq!(report.label_names, state.labels_applied);
+        assert_eq!(report.classification_breakdown[0].0, "Newsletter");
+        assert_eq!(report.classification_breakdown[0].1, 2);
+        assert_eq!(report.top_senders[0].domain, "news.com");
+        assert!(!report.dry_run);
+    }
+
+    #[test]
+    fn test_report_compare() {
+        let mut state_a = ProcessingState::new();
+        state_a.messages_scanned = 10;
+        state_a.filters_created = vec!["f1".to_string()];
+        state_a.labels_applied = vec![
+            "AutoManaged/Old".to_string(),
+            "AutoManaged/Shop".to_string(),
+        ];
+        state_a.classifications = vec![classification("m1", "shop.com", EmailCategory::Receipt)];
+
+        let mut state_b = ProcessingState::new();
+        state_b.messages_scanned = 25;
+        state_b.filters_created = vec!["f1".to_string(), "f2".to_string(), "f3".to_string()];
+        state_b.labels_applied = vec![
+            "AutoManaged/Shop".to_string(),
+            "AutoManaged/New".to_string(),
+        ];
+        state_b.classifications = vec![
+            classification("m2", "shop.com", EmailCategory::Receipt),
+            classification("m3", "news.com", EmailCategory::Newsletter),
+        ];
+
+        let report_a = Report::from_state(&state_a);
+        let report_b = Report::from_state(&state_b);
+        let comparison = report_a.compare(&report_b);
+
+        assert_eq!(comparison.run_a_id, state_a.run_id);
+        assert_eq!(comparison.run_b_id, state_b.run_id);
+        assert_eq!(comparison.new_labels, vec!["AutoManaged/New".to_string()]);
+        assert_eq!(
+            comparison.removed_labels,
+            vec!["AutoManaged/Old".to_string()]
+        );
+        assert_eq!(comparison.new_filter_count_delta, 2);
+        assert_eq!(comparison.emails_scanned_delta, 15);
+        assert_eq!(
+            comparison.top_new_senders,
+            vec![("news.com".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_report_markdown_volume_anomalies() {
+        let mut report = Report::from_state(&ProcessingState::new());
+        report.anomalies = vec![VolumeSpike {
+            sender_domain: "shop.com".to_string(),
+            baseline_per_week: 2.0,
+            spike_count: 14,
+            spike_date: chrono::NaiveDate::from_ymd_opt(2024, 3, 5).unwrap(),
+        }];
+
+        let markdown = report.to_markdown(None);
+
+        assert!(
+            markdown.contains("| Domain | Usual per Week | Emails in Spike Week | Busiest Day |")
+        );
+        assert!(markdown.contains("| shop.com | 2.0 | 14 | 2024-03-05 |"));
+    }
+
+    #[test]
+    fn test_report_to_html() {
+        let mut state = ProcessingState::new();
+        state.messages_scanned = 3;
+        state.messages_classified = 3;
+        let mut risky = classification("m3", "shop.com", EmailCategory::Receipt);
+        risky.subject = "Your <b>order</b> & \"receipt\"".to_string();
+        state.classifications = vec![
+            classification("m1", "news.com", EmailCategory::Newsletter),
+            classification("m2", "news.com", EmailCategory::Newsletter),
+            risky,
+        ];
+
+        let mut report = Report::from_state(&state);
+        let html = report.to_html();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.ends_with("</html>\n"));
+        assert!(html.contains("<title>Email Management Report</title>"));
+        assert!(!html.contains("DRY RUN"));
+        // One bar per category, scaled by its share of messages
+        assert_eq!(html.matches("<rect ").count(), 2);
+        assert!(html.contains("width=\"266.7\""));
+        assert!(html.contains("#search/from%3Anews.com\">news.com</a>"));
+        // Subjects are escaped rather than rendered as markup
+        assert!(html.contains("Your &lt;b&gt;order&lt;/b&gt; &amp; &quot;receipt&quot;"));
+        assert!(!html.contains("<b>order</b>"));
+        assert!(html.contains("<summary>Newsletter (2 emails)</summary>"));
+
+        report.dry_run = true;
+        let html = report.to_html();
+        assert!(html.contains("<title>Email Management Report (DRY RUN)</title>"));
+        assert!(html.contains("Dry run: no changes were made."));
+    }
+}
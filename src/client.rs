@@ -2,7 +2,7 @@
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use futures::stream::{self, StreamExt, TryStreamExt};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
 use google_gmail1::{
     api::{
         BatchModifyMessagesRequest, Filter, FilterAction, FilterCriteria, Label, Message,
@@ -10,40 +10,86 @@ use google_gmail1::{
     },
     hyper_rustls, hyper_util, Gmail,
 };
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Semaphore;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinHandle;
 use tracing::{debug, warn};
 
 use crate::circuit_breaker::CircuitBreaker;
-use crate::config::CircuitBreakerConfig;
+use crate::config::{CircuitBreakerConfig, LabelVisibility, RetryConfig};
 use crate::error::{GmailError, Result};
-use crate::models::{FilterRule, MessageMetadata};
+use crate::filter_manager::{FilterChange, FilterManager};
+use crate::models::{
+    describe_gmail_query, describe_sender, EmailThread, FilterRule, LabelId, MessageMetadata,
+};
 use crate::rate_limiter::{QuotaCost, QuotaRateLimiter};
 
 /// Progress callback type for batch operations
 pub type ProgressCallback = Arc<dyn Fn() + Send + Sync>;
 
+/// Stream of message IDs, yielded page by page as Gmail returns them
+pub type MessageIdStream<'a> = BoxStream<'a, Result<String>>;
+
+/// Largest page size accepted by Gmail's messages.list
+pub const MAX_PAGE_SIZE: usize = 500;
+
 /// Label info returned from Gmail API
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LabelInfo {
     pub id: String,
     pub name: String,
+    /// Label colour, if one has been set in Gmail
+    pub color: Option<LabelColor>,
+}
+
+/// Label colour as hex strings (e.g. "#ffffff")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelColor {
+    pub text_color: Option<String>,
+    pub background_color: Option<String>,
 }
 
 /// Existing Gmail filter info for comparison
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExistingFilterInfo {
     pub id: String,
     pub query: Option<String>,
     pub from: Option<String>,
     pub to: Option<String>,
     pub subject: Option<String>,
+    #[serde(default)]
     pub add_label_ids: Vec<String>,
+    #[serde(default)]
     pub remove_label_ids: Vec<String>,
 }
 
 impl ExistingFilterInfo {
+    /// The filter's criteria as one line per criterion, e.g.
+    /// "From: All @linkedin.com\nExcept: messaging-digest-noreply@linkedin.com\nSubject: Job Alerts"
+    pub fn display_query(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(from) = &self.from {
+            lines.push(format!("From: {}", describe_sender(from)));
+        }
+        if let Some(to) = &self.to {
+            lines.push(format!("To: {}", to));
+        }
+        if let Some(subject) = &self.subject {
+            lines.push(format!("Subject: {}", subject));
+        }
+        if let Some(query) = self.query.as_deref().filter(|q| !q.trim().is_empty()) {
+            lines.push(describe_gmail_query(query));
+        }
+        if lines.is_empty() {
+            "(no criteria)".to_string()
+        } else {
+            lines.join("\n")
+        }
+    }
+
     /// Check if this existing filter matches the new filter rule
     /// Returns true if they are functionally equivalent
     pub fn matches_filter_rule(&self, new_filter: &FilterRule) -> bool {
@@ -98,7 +144,10 @@ impl ExistingFilterInfo {
         }
 
         // Compare add_label_ids
-        let label_matches = self.add_label_ids.contains(&new_filter.target_label_id);
+        let label_matches = self
+            .add_label_ids
+            .iter()
+            .any(|id| new_filter.target_label_id.as_ref().is_some_and(|t| t == id));
 
         // Compare archive behavior (remove INBOX)
         let archive_matches = if new_filter.should_archive {
@@ -154,20 +203,168 @@ impl ExistingFilterInfo {
     }
 }
 
+/// Outcome of a batch label modification
+///
+/// Batches that still fail after retries don't abort the whole operation;
+/// their messages are reported in `failed` so callers can retry them later.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchModifyResult {
+    /// Number of messages modified
+    pub succeeded: usize,
+    /// Messages whose labels could not be modified
+    pub failed: Vec<FailedMessage>,
+}
+
+impl BatchModifyResult {
+    /// IDs of the messages that could not be modified
+    pub fn failed_ids(&self) -> impl Iterator<Item = &str> {
+        self.failed.iter().map(|f| f.message_id.as_str())
+    }
+
+    /// Turns any failed messages into an error, for callers that can't
+    /// continue with some messages left unmodified
+    pub fn into_result(self) -> Result<usize> {
+        match self.failed.first() {
+            None => Ok(self.succeeded),
+            Some(first) => Err(GmailError::LabelError(format!(
+                "Failed to modify labels on {} messages: {}",
+                self.failed.len(),
+                first.error
+            ))),
+        }
+    }
+}
+
+/// A message whose labels could not be modified
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedMessage {
+    pub message_id: String,
+    pub error: String,
+}
+
 /// Trait defining Gmail client operations for easier testing
 #[async_trait]
 pub trait GmailClient: Send + Sync {
     /// List all message IDs matching a query
     async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
 
+    /// List at most `limit` message IDs matching a query, and whether more
+    /// messages match than were returned
+    async fn list_message_ids_limited(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<(Vec<String>, bool)> {
+        let mut ids = self.list_message_ids(query).await?;
+        let truncated = ids.len() > limit;
+        ids.truncate(limit);
+        Ok((ids, truncated))
+    }
+
+    /// Fetch one page of message IDs matching a query, starting at `page_token`,
+    /// and return the IDs with the token of the next page
+    ///
+    /// Clients that can't page return every match as a single page and ignore
+    /// the token.
+    async fn list_message_page(
+        &self,
+        query: &str,
+        _page_size: usize,
+        _page_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        Ok((self.list_message_ids(query).await?, None))
+    }
+
+    /// Stream message IDs matching a query, requesting `page_size` IDs per page
+    ///
+    /// Unlike `list_message_ids`, each page is yielded before the next one is
+    /// requested, so callers can start processing without holding every ID
+    /// of a very large mailbox in memory.
+    fn message_id_stream<'a>(
+        &'a self,
+        query: &'a str,
+        page_size: usize,
+    ) -> Result<MessageIdStream<'a>> {
+        validate_page_size(page_size)?;
+        Ok(stream::once(self.list_message_ids(query))
+            .map_ok(|ids| stream::iter(ids.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed())
+    }
+
+    /// Page through messages matching `query` in a background task, sending their
+    /// metadata as it arrives
+    ///
+    /// The channel holds at most `buffer` messages and at most `buffer` fetches run
+    /// at once, so fetching pauses while the receiver falls behind, and the receiver
+    /// can start on the first page before the last one is listed. Failed fetches are
+    /// sent as errors; the task stops once the receiver is dropped.
+    fn stream_messages_bounded(
+        self: Arc<Self>,
+        query: &str,
+        page_size: usize,
+        buffer: usize,
+    ) -> (mpsc::Receiver<Result<MessageMetadata>>, JoinHandle<()>)
+    where
+        Self: 'static,
+    {
+        let buffer = buffer.max(1);
+        let (tx, rx) = mpsc::channel(buffer);
+        let query = query.to_string();
+        let handle = tokio::spawn(async move {
+            let ids = match self.message_id_stream(&query, page_size) {
+                Ok(ids) => ids,
+                Err(e) => {
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+            let client = &self;
+            let mut fetched = std::pin::pin!(ids
+                .map(|id| async move {
+                    match id {
+                        Ok(id) => client.get_message(&id).await,
+                        Err(e) => Err(e),
+                    }
+                })
+                .buffer_unordered(buffer));
+            while let Some(result) = fetched.next().await {
+                if tx.send(result).await.is_err() {
+                    return;
+                }
+            }
+        });
+        (rx, handle)
+    }
+
     /// Get detailed message metadata
     async fn get_message(&self, id: &str) -> Result<MessageMetadata>;
 
+    /// List all thread IDs matching a query
+    async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+
+    /// Get a thread with metadata for all of its messages
+    async fn get_thread(&self, thread_id: &str) -> Result<EmailThread>;
+
     /// List all labels in the account
     async fn list_labels(&self) -> Result<Vec<LabelInfo>>;
 
     /// Create a new label
-    async fn create_label(&self, name: &str) -> Result<String>;
+    async fn create_label(&self, name: &str) -> Result<LabelId>;
+
+    /// Create a new label with the given message list / label list visibility
+    async fn create_label_with_visibility(
+        &self,
+        name: &str,
+        visibility: &LabelVisibility,
+    ) -> Result<LabelId>;
+
+    /// Change the message list / label list visibility of an existing label
+    async fn update_label_visibility(
+        &self,
+        label_id: &str,
+        visibility: &LabelVisibility,
+    ) -> Result<()>;
 
     /// Delete a label by ID
     async fn delete_label(&self, label_id: &str) -> Result<()>;
@@ -185,28 +382,30 @@ pub trait GmailClient: Send + Sync {
     async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
 
     /// Apply a label to a message
-    async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
+    async fn apply_label(&self, message_id: &str, label_id: &LabelId) -> Result<()>;
 
     /// Remove a label from a message (used for archiving - removing INBOX)
-    async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
+    async fn remove_label(&self, message_id: &str, label_id: &LabelId) -> Result<()>;
 
     /// Remove a label from multiple messages in batch (up to 1000 per call)
     /// Returns the number of messages successfully modified
-    async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
+    async fn batch_remove_label(&self, message_ids: &[String], label_id: &LabelId)
+        -> Result<usize>;
 
     /// Add a label to multiple messages in batch (up to 1000 per call)
     /// Returns the number of messages successfully modified
-    async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
+    async fn batch_add_label(&self, message_ids: &[String], label_id: &LabelId) -> Result<usize>;
 
     /// Batch modify labels on multiple messages (up to 1000 per call)
     /// Can add and remove labels in a single API call
-    /// Returns the number of messages successfully modified
+    /// Batches that fail after retries are reported in the result instead of
+    /// aborting the remaining batches
     async fn batch_modify_labels(
         &self,
         message_ids: &[String],
-        add_label_ids: &[String],
-        remove_label_ids: &[String],
-    ) -> Result<usize>;
+        add_label_ids: &[LabelId],
+        remove_label_ids: &[LabelId],
+    ) -> Result<BatchModifyResult>;
 
     /// Fetch multiple messages concurrently
     async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<MessageMetadata>>;
@@ -267,7 +466,7 @@ where
 /// - Semaphore-based concurrency limiting
 /// - Quota-aware rate limiting (token bucket algorithm)
 /// - Circuit breaker pattern for fault tolerance
-/// - Exponential backoff retry logic
+/// - Exponential backoff retry logic (configurable per error kind)
 /// - Concurrent message fetching with buffered streams
 ///
 /// The client enforces Gmail API quotas:
@@ -284,6 +483,14 @@ pub struct ProductionGmailClient {
     circuit_breaker: CircuitBreaker,
     /// Maximum number of concurrent requests (used for buffer_unordered)
     max_concurrent: usize,
+    /// Which errors are retried, how often and how long to wait
+    retry: RetryConfig,
+    /// Request the `Authentication-Results` header with message metadata
+    auth_headers: bool,
+    /// Keep the links from `List-Unsubscribe` on parsed messages
+    unsubscribe_links: bool,
+    /// JSONL file filter updates are recorded in, and the run making them
+    filter_change_log: Option<(PathBuf, String)>,
 }
 
 impl ProductionGmailClient {
@@ -307,6 +514,10 @@ impl ProductionGmailClient {
             quota_limiter: QuotaRateLimiter::new(),
             circuit_breaker: CircuitBreaker::new(CircuitBreakerConfig::default()),
             max_concurrent,
+            retry: RetryConfig::default(),
+            auth_headers: true,
+            unsubscribe_links: true,
+            filter_change_log: None,
         }
     }
 
@@ -334,6 +545,10 @@ impl ProductionGmailClient {
             ),
             circuit_breaker: CircuitBreaker::new(CircuitBreakerConfig::default()),
             max_concurrent,
+            retry: RetryConfig::default(),
+            auth_headers: true,
+            unsubscribe_links: true,
+            filter_change_log: None,
         }
     }
 
@@ -363,6 +578,56 @@ impl ProductionGmailClient {
             ),
             circuit_breaker: CircuitBreaker::new(circuit_breaker_config),
             max_concurrent,
+            retry: RetryConfig::default(),
+            auth_headers: true,
+            unsubscribe_links: true,
+            filter_change_log: None,
+        }
+    }
+
+    /// Use a custom retry policy instead of the default
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Whether to fetch `Authentication-Results` for SPF/DKIM checks (default: true)
+    pub fn with_auth_headers(mut self, enabled: bool) -> Self {
+        self.auth_headers = enabled;
+        self
+    }
+
+    /// Whether to keep `List-Unsubscribe` links on fetched messages (default: true)
+    pub fn with_unsubscribe_links(mut self, enabled: bool) -> Self {
+        self.unsubscribe_links = enabled;
+        self
+    }
+
+    /// Record every filter update made by `run_id` in the JSONL file at `path`
+    pub fn with_filter_change_log(mut self, path: PathBuf, run_id: String) -> Self {
+        self.filter_change_log = Some((path, run_id));
+        self
+    }
+
+    /// Metadata headers requested for each message
+    fn metadata_headers(&self) -> &'static [&'static str] {
+        // Authentication-Results must stay last so it can be left off
+        const HEADERS: &[&str] = &[
+            "From",
+            "Subject",
+            "Date",
+            "List-Unsubscribe",
+            "List-ID",
+            "In-Reply-To",
+            "Auto-Submitted",
+            "Content-Type",
+            "Return-Path",
+            "Authentication-Results",
+        ];
+        if self.auth_headers {
+            HEADERS
+        } else {
+            &HEADERS[..HEADERS.len() - 1]
         }
     }
 
@@ -385,31 +650,30 @@ impl ProductionGmailClient {
         let _concurrency_permit = self.concurrency_limiter.acquire().await.map_err(|e| {
             GmailError::Unknown(format!("Failed to acquire concurrency permit: {}", e))
         })?;
-        let _quota_permit = self.quota_limiter.acquire(QuotaCost::Read).await;
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("messages.get", QuotaCost::Read)
+            .await;
 
         let mut attempts = 0;
-        let max_attempts = 4; // Initial + 3 retries
+        let max_attempts = self.retry.max_attempts;
         let mut delay = std::time::Duration::from_millis(100);
 
         loop {
             attempts += 1;
 
-            let result = self
-                .hub
-                .users()
-                .messages_get("me", id)
-                .format("metadata")
-                .add_metadata_headers("From")
-                .add_metadata_headers("Subject")
-                .add_metadata_headers("Date")
-                .add_metadata_headers("List-Unsubscribe")
+            let mut call = self.hub.users().messages_get("me", id).format("metadata");
+            for header in self.metadata_headers() {
+                call = call.add_metadata_headers(header);
+            }
+            let result = call
                 .add_scope("https://www.googleapis.com/auth/gmail.modify")
                 .doit()
                 .await;
 
             match result {
                 Ok((_, msg)) => {
-                    match parse_message_metadata(msg) {
+                    match parse_message_metadata(msg, self.unsubscribe_links) {
                         Ok(metadata) => {
                             // Record success in circuit breaker
                             self.circuit_breaker.record_success().await;
@@ -433,21 +697,14 @@ impl ProductionGmailClient {
                     // Record failure in circuit breaker
                     self.circuit_breaker.record_failure(&gmail_error).await;
 
-                    if gmail_error.is_transient() && attempts < max_attempts {
-                        // For rate limit errors, respect the server's Retry-After header
-                        let retry_delay = match &gmail_error {
-                            GmailError::RateLimitExceeded { retry_after } => {
-                                Duration::from_secs(*retry_after)
-                            }
-                            _ => delay,
-                        };
-
+                    if self.retry.should_retry(&gmail_error) && attempts < max_attempts {
+                        let retry_delay = self.retry_delay(&gmail_error, attempts);
+                        debug!(
+                            attempt = attempts,
+                            delay_ms = retry_delay.as_millis() as u64,
+                            "Retrying operation"
+                        );
                         tokio::time::sleep(retry_delay).await;
-
-                        // Only use exponential backoff for non-rate-limit errors
-                        if !matches!(gmail_error, GmailError::RateLimitExceeded { .. }) {
-                            delay *= 2;
-                        }
                         continue;
                     }
                     return Err(gmail_error);
@@ -456,23 +713,19 @@ impl ProductionGmailClient {
         }
     }
 
-    /// Check if an error is retryable
-    fn should_retry(error: &GmailError) -> bool {
-        matches!(
-            error,
-            GmailError::ServerError { .. }
-                | GmailError::RateLimitExceeded { .. }
-                | GmailError::NetworkError(_)
-        )
+    /// Delay before retrying after `attempt` failed with `error`
+    ///
+    /// Rate limit errors respect the server's Retry-After header; everything else
+    /// backs off exponentially.
+    fn retry_delay(&self, error: &GmailError, attempt: u32) -> Duration {
+        match error {
+            GmailError::RateLimitExceeded { retry_after } => Duration::from_secs(*retry_after),
+            _ => self.retry.delay(attempt),
+        }
     }
 
-    /// Execute an async operation with exponential backoff retry
-    async fn with_retry<T, F, Fut>(
-        &self,
-        operation_name: &str,
-        max_retries: u32,
-        mut operation: F,
-    ) -> Result<T>
+    /// Execute an async operation, retrying errors selected by the retry config
+    async fn with_retry<T, F, Fut>(&self, operation_name: &str, mut operation: F) -> Result<T>
     where
         F: FnMut() -> Fut,
         Fut: std::future::Future<Output = Result<T>>,
@@ -480,7 +733,6 @@ impl ProductionGmailClient {
         // Check circuit breaker before starting
         self.circuit_breaker.check_request().await?;
 
-        let mut delay = Duration::from_secs(1);
         let mut attempts = 0;
 
         loop {
@@ -491,32 +743,21 @@ impl ProductionGmailClient {
                     self.circuit_breaker.record_success().await;
                     return Ok(result);
                 }
-                Err(e) if Self::should_retry(&e) && attempts <= max_retries => {
+                Err(e) if self.retry.should_retry(&e) && attempts < self.retry.max_attempts => {
                     // Record failure in circuit breaker
                     self.circuit_breaker.record_failure(&e).await;
 
-                    // For rate limit errors, respect the server's Retry-After header
-                    let retry_delay = match &e {
-                        GmailError::RateLimitExceeded { retry_after } => {
-                            Duration::from_secs(*retry_after)
-                        }
-                        _ => delay,
-                    };
-
+                    let retry_delay = self.retry_delay(&e, attempts);
                     warn!(
                         "{} failed (attempt {}/{}): {}. Retrying in {:?}...",
-                        operation_name,
-                        attempts,
-                        max_retries + 1,
-                        e,
-                        retry_delay
+                        operation_name, attempts, self.retry.max_attempts, e, retry_delay
+                    );
+                    debug!(
+                        attempt = attempts,
+                        delay_ms = retry_delay.as_millis() as u64,
+                        "Retrying operation"
                     );
                     tokio::time::sleep(retry_delay).await;
-
-                    // Only use exponential backoff for non-rate-limit errors
-                    if !matches!(e, GmailError::RateLimitExceeded { .. }) {
-                        delay = std::cmp::min(delay * 2, Duration::from_secs(30));
-                    }
                 }
                 Err(e) => {
                     // Record final failure in circuit breaker
@@ -529,7 +770,18 @@ impl ProductionGmailClient {
 }
 
 /// Parse Gmail API Message into our MessageMetadata structure
-fn parse_message_metadata(msg: Message) -> Result<MessageMetadata> {
+/// Check that a page size is accepted by Gmail's messages.list
+fn validate_page_size(page_size: usize) -> Result<()> {
+    if page_size == 0 || page_size > MAX_PAGE_SIZE {
+        return Err(GmailError::BadRequest(format!(
+            "Page size must be between 1 and {}, got {}",
+            MAX_PAGE_SIZE, page_size
+        )));
+    }
+    Ok(())
+}
+
+fn parse_message_metadata(msg: Message, unsubscribe_links: bool) -> Result<MessageMetadata> {
     let id = msg
         .id
         .ok_or_else(|| GmailError::InvalidMessageFormat("Missing message ID".to_string()))?;
@@ -540,6 +792,13 @@ fn parse_message_metadata(msg: Message) -> Result<MessageMetadata> {
 
     let labels = msg.label_ids.unwrap_or_default();
 
+    let attachment_types = msg
+        .payload
+        .as_ref()
+        .map(crate::scanner::collect_mime_types)
+        .unwrap_or_default();
+    let size_bytes = msg.size_estimate.unwrap_or(0).max(0) as u64;
+
     // Parse headers
     let headers = msg
         .payload
@@ -553,6 +812,11 @@ fn parse_message_metadata(msg: Message) -> Result<MessageMetadata> {
     let mut recipients = Vec::new();
     let mut date_str = String::new();
     let mut has_unsubscribe = false;
+    let mut auth_results = None;
+    let mut is_reply = false;
+    let mut auto_submitted = None;
+    let mut content_type = None;
+    let mut return_path = None;
 
     for header in headers {
         if let (Some(name), Some(value)) = (&header.name, &header.value) {
@@ -578,6 +842,22 @@ fn parse_message_metadata(msg: Message) -> Result<MessageMetadata> {
                 "list-unsubscribe" => {
                     has_unsubscribe = true;
                 }
+                "in-reply-to" => {
+                    is_reply = true;
+                }
+                "auto-submitted" => {
+                    auto_submitted = Some(value.trim().to_lowercase());
+                }
+                "content-type" => {
+                    content_type = Some(value.as_str());
+                }
+                "return-path" => {
+                    return_path = Some(value.as_str());
+                }
+                // Gmail lists its own verdict first; later ones come from relays
+                "authentication-results" if auth_results.is_none() => {
+                    auth_results = Some(crate::scanner::parse_authentication_results(value));
+                }
                 _ => {}
             }
         }
@@ -589,6 +869,15 @@ fn parse_message_metadata(msg: Message) -> Result<MessageMetadata> {
     // Parse date
     let date_received = parse_date(&date_str).unwrap_or_else(|_| Utc::now());
 
+    let (spf_pass, dkim_pass) = auth_results.unwrap_or_default();
+    let is_bounce = crate::scanner::is_bounce(content_type, return_path, &subject);
+    let list_id = crate::scanner::extract_list_id(headers);
+    let unsubscribe_links = if unsubscribe_links {
+        crate::scanner::extract_unsubscribe_links(headers)
+    } else {
+        Vec::new()
+    };
+
     // Check if automated
     let is_automated = check_if_automated(&sender_email, &sender_name, has_unsubscribe);
 
@@ -604,6 +893,15 @@ fn parse_message_metadata(msg: Message) -> Result<MessageMetadata> {
         labels,
         has_unsubscribe,
         is_automated,
+        spf_pass,
+        dkim_pass,
+        list_id,
+        unsubscribe_links,
+        is_reply,
+        auto_submitted,
+        is_bounce,
+        attachment_types,
+        size_bytes,
     })
 }
 
@@ -656,17 +954,121 @@ fn check_if_automated(sender_email: &str, sender_name: &str, has_unsubscribe: bo
 #[async_trait]
 impl GmailClient for ProductionGmailClient {
     async fn list_message_ids(&self, query: &str) -> Result<Vec<String>> {
+        let (ids, _) = self.list_message_ids_limited(query, usize::MAX).await?;
+        Ok(ids)
+    }
+
+    async fn list_message_ids_limited(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<(Vec<String>, bool)> {
         let mut all_ids = Vec::new();
         let mut page_token: Option<String> = None;
 
         loop {
-            // Each page request costs 5 quota units
-            let _quota_permit = self.quota_limiter.acquire(QuotaCost::Read).await;
+            let (ids, next_page_token) = self
+                .list_message_page(query, 100, page_token.take())
+                .await?;
+            all_ids.extend(ids);
+
+            // Stop paging once enough IDs were found
+            page_token = next_page_token;
+            if page_token.is_none() || all_ids.len() >= limit {
+                break;
+            }
+        }
+
+        // More pages, or more IDs on the last page, than the limit allows
+        let truncated = page_token.is_some() || all_ids.len() > limit;
+        all_ids.truncate(limit);
+        Ok((all_ids, truncated))
+    }
+
+    async fn list_message_page(
+        &self,
+        query: &str,
+        page_size: usize,
+        page_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        // Each page request costs 5 quota units
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("messages.list", QuotaCost::Read)
+            .await;
+
+        let mut call = self
+            .hub
+            .users()
+            .messages_list("me")
+            .q(query)
+            .max_results(page_size as u32);
+
+        if let Some(token) = page_token.as_deref() {
+            call = call.page_token(token);
+        }
+
+        let (_, response) = call
+            .add_scope("https://www.googleapis.com/auth/gmail.modify")
+            .doit()
+            .await?;
+
+        let ids = response
+            .messages
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|msg_ref| msg_ref.id)
+            .collect();
+        Ok((ids, response.next_page_token))
+    }
+
+    fn message_id_stream<'a>(
+        &'a self,
+        query: &'a str,
+        page_size: usize,
+    ) -> Result<MessageIdStream<'a>> {
+        validate_page_size(page_size)?;
+
+        // State is the token of the next page to fetch; `None` once the last
+        // page has been fetched
+        let pages = stream::try_unfold(
+            Some(None),
+            move |page_token: Option<Option<String>>| async move {
+                let page: Result<_> = match page_token {
+                    None => Ok(None),
+                    Some(page_token) => self
+                        .list_message_page(query, page_size, page_token)
+                        .await
+                        .map(|(ids, next_page_token)| Some((ids, next_page_token.map(Some)))),
+                };
+                page
+            },
+        );
+
+        Ok(pages
+            .map_ok(|ids| stream::iter(ids.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed())
+    }
+
+    async fn get_message(&self, id: &str) -> Result<MessageMetadata> {
+        self.fetch_single_with_retry(id).await
+    }
+
+    async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>> {
+        let mut all_ids = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let _quota_permit = self
+                .quota_limiter
+                .acquire_for("threads.list", QuotaCost::Read)
+                .await;
 
             let mut call = self
                 .hub
                 .users()
-                .messages_list("me")
+                .threads_list("me")
                 .q(query)
                 .max_results(100);
 
@@ -679,12 +1081,8 @@ impl GmailClient for ProductionGmailClient {
                 .doit()
                 .await?;
 
-            if let Some(messages) = response.messages {
-                for msg_ref in messages {
-                    if let Some(id) = msg_ref.id {
-                        all_ids.push(id);
-                    }
-                }
+            if let Some(threads) = response.threads {
+                all_ids.extend(threads.into_iter().filter_map(|t| t.id));
             }
 
             page_token = response.next_page_token;
@@ -696,15 +1094,49 @@ impl GmailClient for ProductionGmailClient {
         Ok(all_ids)
     }
 
-    async fn get_message(&self, id: &str) -> Result<MessageMetadata> {
-        self.fetch_single_with_retry(id).await
+    async fn get_thread(&self, thread_id: &str) -> Result<EmailThread> {
+        // threads.get costs 10 quota units
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("threads.get", QuotaCost::Custom(10))
+            .await;
+
+        let thread = self
+            .with_retry("get_thread", || async {
+                let mut call = self
+                    .hub
+                    .users()
+                    .threads_get("me", thread_id)
+                    .format("metadata");
+                for header in self.metadata_headers() {
+                    call = call.add_metadata_headers(header);
+                }
+                let (_, thread) = call
+                    .add_scope("https://www.googleapis.com/auth/gmail.modify")
+                    .doit()
+                    .await?;
+                Ok(thread)
+            })
+            .await?;
+
+        let messages = thread
+            .messages
+            .unwrap_or_default()
+            .into_iter()
+            .map(|msg| parse_message_metadata(msg, self.unsubscribe_links))
+            .collect::<Result<Vec<_>>>()?;
+
+        EmailThread::from_messages(thread_id.to_string(), messages)
     }
 
     async fn list_labels(&self) -> Result<Vec<LabelInfo>> {
         // Acquire quota before retry loop (quota is consumed per attempt)
-        let _quota_permit = self.quota_limiter.acquire(QuotaCost::Read).await;
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("labels.list", QuotaCost::Read)
+            .await;
 
-        self.with_retry("list_labels", 3, || async {
+        self.with_retry("list_labels", || async {
             // Wrap API call in timeout to prevent indefinite hangs
             let timeout_duration = Duration::from_secs(30);
             let api_call = async {
@@ -739,7 +1171,14 @@ impl GmailClient for ProductionGmailClient {
                 .unwrap_or_default()
                 .into_iter()
                 .filter_map(|label| match (label.id, label.name) {
-                    (Some(id), Some(name)) => Some(LabelInfo { id, name }),
+                    (Some(id), Some(name)) => Some(LabelInfo {
+                        id,
+                        name,
+                        color: label.color.map(|c| LabelColor {
+                            text_color: c.text_color,
+                            background_color: c.background_color,
+                        }),
+                    }),
                     _ => None,
                 })
                 .collect();
@@ -750,16 +1189,28 @@ impl GmailClient for ProductionGmailClient {
         .await
     }
 
-    async fn create_label(&self, name: &str) -> Result<String> {
+    async fn create_label(&self, name: &str) -> Result<LabelId> {
+        self.create_label_with_visibility(name, &LabelVisibility::default())
+            .await
+    }
+
+    async fn create_label_with_visibility(
+        &self,
+        name: &str,
+        visibility: &LabelVisibility,
+    ) -> Result<LabelId> {
         let name = name.to_string();
         // Write operation costs 50 quota units
-        let _quota_permit = self.quota_limiter.acquire(QuotaCost::Write).await;
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("labels.create", QuotaCost::Write)
+            .await;
 
-        self.with_retry("create_label", 3, || async {
+        self.with_retry("create_label", || async {
             let label = Label {
                 name: Some(name.clone()),
-                message_list_visibility: Some("show".to_string()),
-                label_list_visibility: Some("labelShow".to_string()),
+                message_list_visibility: Some(visibility.message_list_api_value().to_string()),
+                label_list_visibility: Some(visibility.label_list_api_value().to_string()),
                 ..Default::default()
             };
 
@@ -773,14 +1224,48 @@ impl GmailClient for ProductionGmailClient {
 
             created_label
                 .id
+                .map(LabelId::new)
                 .ok_or_else(|| GmailError::LabelError("Created label has no ID".to_string()))
         })
         .await
     }
 
+    async fn update_label_visibility(
+        &self,
+        label_id: &str,
+        visibility: &LabelVisibility,
+    ) -> Result<()> {
+        // Write operation costs 50 quota units
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("labels.patch", QuotaCost::Write)
+            .await;
+
+        self.with_retry("update_label_visibility", || async {
+            // Patch only touches the fields that are set
+            let label = Label {
+                message_list_visibility: Some(visibility.message_list_api_value().to_string()),
+                label_list_visibility: Some(visibility.label_list_api_value().to_string()),
+                ..Default::default()
+            };
+
+            self.hub
+                .users()
+                .labels_patch(label, "me", label_id)
+                .add_scope("https://www.googleapis.com/auth/gmail.labels")
+                .doit()
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
     async fn delete_label(&self, label_id: &str) -> Result<()> {
         // Write operation costs 50 quota units
-        let _quota_permit = self.quota_limiter.acquire(QuotaCost::Write).await;
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("labels.delete", QuotaCost::Write)
+            .await;
 
         self.hub
             .users()
@@ -794,10 +1279,15 @@ impl GmailClient for ProductionGmailClient {
 
     async fn create_filter(&self, filter: &FilterRule) -> Result<String> {
         let filter = filter.clone();
+        let add_label_ids: Vec<String> =
+            filter.label_ids()?.into_iter().map(String::from).collect();
         // Write operation costs 50 quota units
-        let _quota_permit = self.quota_limiter.acquire(QuotaCost::Write).await;
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("filters.create", QuotaCost::Write)
+            .await;
 
-        self.with_retry("create_filter", 3, || async {
+        self.with_retry("create_filter", || async {
             // Build the full Gmail query including from pattern, exclusions, and subject keywords
             let full_query =
                 crate::filter_manager::FilterManager::build_gmail_query_static(&filter);
@@ -812,7 +1302,7 @@ impl GmailClient for ProductionGmailClient {
 
             // Build the filter action
             let mut action = FilterAction {
-                add_label_ids: Some(vec![filter.target_label_id.clone()]),
+                add_label_ids: Some(add_label_ids.clone()),
                 ..Default::default()
             };
 
@@ -844,9 +1334,12 @@ impl GmailClient for ProductionGmailClient {
 
     async fn list_filters(&self) -> Result<Vec<ExistingFilterInfo>> {
         // Read operation costs 5 quota units
-        let _quota_permit = self.quota_limiter.acquire(QuotaCost::Read).await;
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("filters.list", QuotaCost::Read)
+            .await;
 
-        self.with_retry("list_filters", 3, || async {
+        self.with_retry("list_filters", || async {
             // Wrap API call in timeout to prevent indefinite hangs
             let timeout_duration = Duration::from_secs(30);
             let api_call = async {
@@ -906,9 +1399,12 @@ impl GmailClient for ProductionGmailClient {
     async fn delete_filter(&self, filter_id: &str) -> Result<()> {
         let filter_id = filter_id.to_string();
         // Write operation costs 50 quota units
-        let _quota_permit = self.quota_limiter.acquire(QuotaCost::Write).await;
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("filters.delete", QuotaCost::Write)
+            .await;
 
-        self.with_retry("delete_filter", 3, || async {
+        self.with_retry("delete_filter", || async {
             self.hub
                 .users()
                 .settings_filters_delete("me", &filter_id)
@@ -924,19 +1420,59 @@ impl GmailClient for ProductionGmailClient {
     async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String> {
         let filter_id = filter_id.to_string();
         let filter = filter.clone();
-        self.with_retry("update_filter", 3, || async {
-            // Delete the old filter
-            self.delete_filter(&filter_id).await?;
+        // Read the old criteria first, it's gone once the filter is deleted
+        let old_filter = match &self.filter_change_log {
+            Some(_) => self
+                .list_filters()
+                .await
+                .ok()
+                .and_then(|filters| filters.into_iter().find(|f| f.id == filter_id)),
+            None => None,
+        };
 
-            // Create a new one with updated settings
-            self.create_filter(&filter).await
-        })
-        .await
+        let new_filter_id = self
+            .with_retry("update_filter", || async {
+                // Delete the old filter
+                self.delete_filter(&filter_id).await?;
+
+                // Create a new one with updated settings
+                self.create_filter(&filter).await
+            })
+            .await?;
+
+        if let Some((path, run_id)) = &self.filter_change_log {
+            let change = FilterChange {
+                filter_id,
+                new_filter_id: new_filter_id.clone(),
+                changed_at: Utc::now(),
+                old_query: old_filter.as_ref().and_then(|f| {
+                    f.query
+                        .clone()
+                        .or_else(|| f.from.as_ref().map(|from| format!("from:({})", from)))
+                }),
+                new_query: FilterManager::build_gmail_query_static(&filter),
+                old_label_id: old_filter.and_then(|f| f.add_label_ids.into_iter().next()),
+                new_label_id: filter
+                    .target_label_id
+                    .as_ref()
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+                changed_by_run_id: Some(run_id.clone()),
+            };
+            if let Err(e) = change.append(path).await {
+                warn!("Failed to record filter change in {:?}: {}", path, e);
+            }
+        }
+
+        Ok(new_filter_id)
     }
 
-    async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()> {
+    async fn apply_label(&self, message_id: &str, label_id: &LabelId) -> Result<()> {
         // Write operation costs 50 quota units
-        let _quota_permit = self.quota_limiter.acquire(QuotaCost::Write).await;
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("messages.modify", QuotaCost::Write)
+            .await;
 
         let modify_request = ModifyMessageRequest {
             add_label_ids: Some(vec![label_id.to_string()]),
@@ -953,9 +1489,12 @@ impl GmailClient for ProductionGmailClient {
         Ok(())
     }
 
-    async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()> {
+    async fn remove_label(&self, message_id: &str, label_id: &LabelId) -> Result<()> {
         // Write operation costs 50 quota units
-        let _quota_permit = self.quota_limiter.acquire(QuotaCost::Write).await;
+        let _quota_permit = self
+            .quota_limiter
+            .acquire_for("messages.modify", QuotaCost::Write)
+            .await;
 
         let modify_request = ModifyMessageRequest {
             add_label_ids: None,
@@ -972,7 +1511,11 @@ impl GmailClient for ProductionGmailClient {
         Ok(())
     }
 
-    async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize> {
+    async fn batch_remove_label(
+        &self,
+        message_ids: &[String],
+        label_id: &LabelId,
+    ) -> Result<usize> {
         if message_ids.is_empty() {
             return Ok(0);
         }
@@ -984,12 +1527,15 @@ impl GmailClient for ProductionGmailClient {
 
         for chunk in message_ids.chunks(BATCH_SIZE) {
             // Batch operation costs 50 quota units per batch
-            let _quota_permit = self.quota_limiter.acquire(QuotaCost::Batch).await;
+            let _quota_permit = self
+                .quota_limiter
+                .acquire_for("messages.batchModify", QuotaCost::Batch)
+                .await;
 
             let chunk_vec = chunk.to_vec();
             let label_id_clone = label_id.clone();
 
-            self.with_retry("batch_remove_label", 3, || async {
+            self.with_retry("batch_remove_label", || async {
                 let request = BatchModifyMessagesRequest {
                     ids: Some(chunk_vec.clone()),
                     add_label_ids: None,
@@ -1013,7 +1559,7 @@ impl GmailClient for ProductionGmailClient {
         Ok(total_modified)
     }
 
-    async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize> {
+    async fn batch_add_label(&self, message_ids: &[String], label_id: &LabelId) -> Result<usize> {
         if message_ids.is_empty() {
             return Ok(0);
         }
@@ -1024,12 +1570,15 @@ impl GmailClient for ProductionGmailClient {
 
         for chunk in message_ids.chunks(BATCH_SIZE) {
             // Batch operation costs 50 quota units per batch
-            let _quota_permit = self.quota_limiter.acquire(QuotaCost::Batch).await;
+            let _quota_permit = self
+                .quota_limiter
+                .acquire_for("messages.batchModify", QuotaCost::Batch)
+                .await;
 
             let chunk_vec = chunk.to_vec();
             let label_id_clone = label_id.clone();
 
-            self.with_retry("batch_add_label", 3, || async {
+            self.with_retry("batch_add_label", || async {
                 let request = BatchModifyMessagesRequest {
                     ids: Some(chunk_vec.clone()),
                     add_label_ids: Some(vec![label_id_clone.clone()]),
@@ -1056,58 +1605,72 @@ impl GmailClient for ProductionGmailClient {
     async fn batch_modify_labels(
         &self,
         message_ids: &[String],
-        add_label_ids: &[String],
-        remove_label_ids: &[String],
-    ) -> Result<usize> {
+        add_label_ids: &[LabelId],
+        remove_label_ids: &[LabelId],
+    ) -> Result<BatchModifyResult> {
+        let mut result = BatchModifyResult::default();
         if message_ids.is_empty() {
-            return Ok(0);
+            return Ok(result);
         }
 
         const BATCH_SIZE: usize = 1000;
-        let mut total_modified = 0;
 
         let add_labels = if add_label_ids.is_empty() {
             None
         } else {
-            Some(add_label_ids.to_vec())
+            Some(add_label_ids.iter().map(|id| id.to_string()).collect())
         };
 
         let remove_labels = if remove_label_ids.is_empty() {
             None
         } else {
-            Some(remove_label_ids.to_vec())
+            Some(remove_label_ids.iter().map(|id| id.to_string()).collect())
         };
 
         for chunk in message_ids.chunks(BATCH_SIZE) {
             // Batch operation costs 50 quota units per batch
-            let _quota_permit = self.quota_limiter.acquire(QuotaCost::Batch).await;
+            let _quota_permit = self
+                .quota_limiter
+                .acquire_for("messages.batchModify", QuotaCost::Batch)
+                .await;
 
             let chunk_vec = chunk.to_vec();
             let add_labels_clone = add_labels.clone();
             let remove_labels_clone = remove_labels.clone();
 
-            self.with_retry("batch_modify_labels", 3, || async {
-                let request = BatchModifyMessagesRequest {
-                    ids: Some(chunk_vec.clone()),
-                    add_label_ids: add_labels_clone.clone(),
-                    remove_label_ids: remove_labels_clone.clone(),
-                };
+            let outcome = self
+                .with_retry("batch_modify_labels", || async {
+                    let request = BatchModifyMessagesRequest {
+                        ids: Some(chunk_vec.clone()),
+                        add_label_ids: add_labels_clone.clone(),
+                        remove_label_ids: remove_labels_clone.clone(),
+                    };
 
-                self.hub
-                    .users()
-                    .messages_batch_modify(request, "me")
-                    .add_scope("https://www.googleapis.com/auth/gmail.modify")
-                    .doit()
-                    .await?;
+                    self.hub
+                        .users()
+                        .messages_batch_modify(request, "me")
+                        .add_scope("https://www.googleapis.com/auth/gmail.modify")
+                        .doit()
+                        .await?;
 
-                Ok(())
-            })
-            .await?;
+                    Ok(())
+                })
+                .await;
 
-            total_modified += chunk.len();
+            match outcome {
+                Ok(()) => result.succeeded += chunk.len(),
+                Err(e) => {
+                    warn!("Batch of {} messages failed: {}", chunk.len(), e);
+                    let error = e.to_string();
+                    result.failed.extend(chunk.iter().map(|id| FailedMessage {
+                        message_id: id.clone(),
+                        error: error.clone(),
+                    }));
+                }
+            }
         }
 
-        Ok(total_modified)
+        Ok(result)
     }
 
     async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<MessageMetadata>> {
@@ -1158,25 +1721,80 @@ impl GmailClient for ProductionGmailClient {
     }
 }
 
-// Implement GmailClient for Arc<ProductionGmailClient> to allow shared ownership
+// Implement GmailClient for Arc<T> to allow shared ownership (including Arc<dyn GmailClient>)
 #[async_trait]
-impl GmailClient for Arc<ProductionGmailClient> {
+impl<T: GmailClient + ?Sized> GmailClient for Arc<T> {
     async fn list_message_ids(&self, query: &str) -> Result<Vec<String>> {
         self.as_ref().list_message_ids(query).await
     }
 
+    async fn list_message_ids_limited(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<(Vec<String>, bool)> {
+        self.as_ref().list_message_ids_limited(query, limit).await
+    }
+
+    async fn list_message_page(
+        &self,
+        query: &str,
+        page_size: usize,
+        page_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        self.as_ref()
+            .list_message_page(query, page_size, page_token)
+            .await
+    }
+
+    fn message_id_stream<'a>(
+        &'a self,
+        query: &'a str,
+        page_size: usize,
+    ) -> Result<MessageIdStream<'a>> {
+        self.as_ref().message_id_stream(query, page_size)
+    }
+
     async fn get_message(&self, id: &str) -> Result<MessageMetadata> {
         self.as_ref().get_message(id).await
     }
 
+    async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>> {
+        self.as_ref().list_thread_ids(query).await
+    }
+
+    async fn get_thread(&self, thread_id: &str) -> Result<EmailThread> {
+        self.as_ref().get_thread(thread_id).await
+    }
+
     async fn list_labels(&self) -> Result<Vec<LabelInfo>> {
         self.as_ref().list_labels().await
     }
 
-    async fn create_label(&self, name: &str) -> Result<String> {
+    async fn create_label(&self, name: &str) -> Result<LabelId> {
         self.as_ref().create_label(name).await
     }
 
+    async fn create_label_with_visibility(
+        &self,
+        name: &str,
+        visibility: &LabelVisibility,
+    ) -> Result<LabelId> {
+        self.as_ref()
+            .create_label_with_visibility(name, visibility)
+            .await
+    }
+
+    async fn update_label_visibility(
+        &self,
+        label_id: &str,
+        visibility: &LabelVisibility,
+    ) -> Result<()> {
+        self.as_ref()
+            .update_label_visibility(label_id, visibility)
+            .await
+    }
+
     async fn delete_label(&self, label_id: &str) -> Result<()> {
         self.as_ref().delete_label(label_id).await
     }
@@ -1197,30 +1815,34 @@ impl GmailClient for Arc<ProductionGmailClient> {
         self.as_ref().update_filter(filter_id, filter).await
     }
 
-    async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()> {
+    async fn apply_label(&self, message_id: &str, label_id: &LabelId) -> Result<()> {
         self.as_ref().apply_label(message_id, label_id).await
     }
 
-    async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()> {
+    async fn remove_label(&self, message_id: &str, label_id: &LabelId) -> Result<()> {
         self.as_ref().remove_label(message_id, label_id).await
     }
 
-    async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize> {
+    async fn batch_remove_label(
+        &self,
+        message_ids: &[String],
+        label_id: &LabelId,
+    ) -> Result<usize> {
         self.as_ref()
             .batch_remove_label(message_ids, label_id)
             .await
     }
 
-    async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize> {
+    async fn batch_add_label(&self, message_ids: &[String], label_id: &LabelId) -> Result<usize> {
         self.as_ref().batch_add_label(message_ids, label_id).await
     }
 
     async fn batch_modify_labels(
         &self,
         message_ids: &[String],
-        add_label_ids: &[String],
-        remove_label_ids: &[String],
-    ) -> Result<usize> {
+        add_label_ids: &[LabelId],
+        remove_label_ids: &[LabelId],
+    ) -> Result<BatchModifyResult> {
         self.as_ref()
             .batch_modify_labels(message_ids, add_label_ids, remove_label_ids)
             .await
@@ -1245,9 +1867,393 @@ impl GmailClient for Arc<ProductionGmailClient> {
     }
 }
 
+/// Account contents read by [`MockGmailClient::from_fixture`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GmailFixture {
+    #[serde(default)]
+    pub messages: Vec<MessageMetadata>,
+    #[serde(default)]
+    pub labels: Vec<LabelInfo>,
+    #[serde(default)]
+    pub filters: Vec<ExistingFilterInfo>,
+}
+
+/// Offline Gmail client backed by a JSON fixture, for `run --test-mode`
+///
+/// Queries are matched against the fixture messages with a simplified version of
+/// Gmail's search: `from:`, `subject:`, `in:`/`label:`, `-` negation, `OR` groups and
+/// plain words. Other operators, including dates (`after:`, `newer_than:`), match
+/// everything so fixtures don't go stale. Labels, filters and label changes are kept
+/// in memory; nothing is written back to the fixture.
+pub struct MockGmailClient {
+    data: std::sync::Mutex<GmailFixture>,
+    created_filters: std::sync::Mutex<Vec<FilterRule>>,
+    next_id: std::sync::atomic::AtomicUsize,
+}
+
+impl MockGmailClient {
+    pub fn new(fixture: GmailFixture) -> Self {
+        Self {
+            data: std::sync::Mutex::new(fixture),
+            created_filters: std::sync::Mutex::new(Vec::new()),
+            next_id: std::sync::atomic::AtomicUsize::new(1),
+        }
+    }
+
+    /// Load a fixture file of `{ "messages": [...], "labels": [...], "filters": [...] }`
+    pub fn from_fixture(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let fixture: GmailFixture = serde_json::from_str(&json).map_err(|e| {
+            GmailError::ConfigError(format!("Invalid Gmail fixture {:?}: {}", path, e))
+        })?;
+        Ok(Self::new(fixture))
+    }
+
+    /// Filters created through this client, in creation order
+    pub fn created_filters(&self) -> Vec<FilterRule> {
+        self.created_filters.lock().unwrap().clone()
+    }
+
+    fn next_id(&self, prefix: &str) -> String {
+        let n = self
+            .next_id
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        format!("{}_fixture_{}", prefix, n)
+    }
+
+    fn matching_messages(&self, query: &str) -> Vec<MessageMetadata> {
+        // `OR` binds tighter than the implicit AND between terms, as in Gmail
+        let mut groups: Vec<Vec<String>> = Vec::new();
+        let mut joined = false;
+        for term in fixture_query_terms(query) {
+            if term == "OR" {
+                joined = true;
+            } else if joined && !groups.is_empty() {
+                groups.last_mut().unwrap().push(term);
+                joined = false;
+            } else {
+                groups.push(vec![term]);
+            }
+        }
+
+        let data = self.data.lock().unwrap();
+        data.messages
+            .iter()
+            .filter(|m| {
+                groups.iter().all(|alternatives| {
+                    alternatives
+                        .iter()
+                        .any(|term| fixture_term_matches(m, &data.labels, term))
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn modify_labels(&self, message_ids: &[String], add: &[LabelId], remove: &[LabelId]) -> usize {
+        let mut data = self.data.lock().unwrap();
+        let mut modified = 0;
+        for message in data
+            .messages
+            .iter_mut()
+            .filter(|m| message_ids.contains(&m.id))
+        {
+            message.labels.retain(|l| !remove.iter().any(|r| r == l));
+            for label in add {
+                if !message.labels.iter().any(|l| label == l) {
+                    message.labels.push(label.to_string());
+                }
+            }
+            modified += 1;
+        }
+        modified
+    }
+}
+
+/// Split a query into top-level terms, keeping quoted and parenthesised parts together
+fn fixture_query_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() && !quoted && depth == 0 => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    terms
+}
+
+/// Whether a fixture message matches one query term
+fn fixture_term_matches(message: &MessageMetadata, labels: &[LabelInfo], term: &str) -> bool {
+    if let Some(negated) = term.strip_prefix('-') {
+        return !fixture_term_matches(message, labels, negated);
+    }
+    let (operator, value) = match term.split_once(':') {
+        Some((operator, value)) if !operator.starts_with('(') => {
+            (Some(operator.to_lowercase()), value)
+        }
+        _ => (None, term),
+    };
+
+    // `(a OR b)` matches when any alternative does
+    let value = value
+        .strip_prefix('(')
+        .and_then(|v| v.strip_suffix(')'))
+        .unwrap_or(value);
+    if value.contains(" OR ") {
+        return value.split(" OR ").any(|alternative| {
+            let alternative = alternative.trim();
+            match &operator {
+                Some(operator) => {
+                    fixture_term_matches(message, labels, &format!("{}:{}", operator, alternative))
+                }
+                None => fixture_term_matches(message, labels, alternative),
+            }
+        });
+    }
+    if operator.is_none() && fixture_query_terms(value).len() > 1 {
+        return fixture_query_terms(value)
+            .iter()
+            .all(|t| fixture_term_matches(message, labels, t));
+    }
+
+    let value = value.trim_matches('"').to_lowercase();
+    let value = value.trim_start_matches('*');
+    match operator.as_deref() {
+        Some("from") => message.sender_email.to_lowercase().contains(value),
+        Some("subject") => message.subject.to_lowercase().contains(value),
+        // By ID, or by name with `/` and spaces written as `-` like Gmail accepts
+        Some("in" | "label") => message.labels.iter().any(|id| {
+            id.eq_ignore_ascii_case(value)
+                || labels.iter().any(|l| {
+                    l.id == *id
+                        && (l.name.eq_ignore_ascii_case(value)
+                            || l.name.replace(['/', ' '], "-").eq_ignore_ascii_case(value))
+                })
+        }),
+        Some(_) => true,
+        None => {
+            message.subject.to_lowercase().contains(value)
+                || message.sender_email.to_lowercase().contains(value)
+                || message.sender_name.to_lowercase().contains(value)
+        }
+    }
+}
+
+#[async_trait]
+impl GmailClient for MockGmailClient {
+    async fn list_message_ids(&self, query: &str) -> Result<Vec<String>> {
+        Ok(self
+            .matching_messages(query)
+            .into_iter()
+            .map(|m| m.id)
+            .collect())
+    }
+
+    async fn get_message(&self, id: &str) -> Result<MessageMetadata> {
+        self.data
+            .lock()
+            .unwrap()
+            .messages
+            .iter()
+            .find(|m| m.id == id)
+            .cloned()
+            .ok_or_else(|| GmailError::MessageNotFound(id.to_string()))
+    }
+
+    async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>> {
+        let mut thread_ids: Vec<String> = Vec::new();
+        for message in self.matching_messages(query) {
+            if !thread_ids.contains(&message.thread_id) {
+                thread_ids.push(message.thread_id);
+            }
+        }
+        Ok(thread_ids)
+    }
+
+    async fn get_thread(&self, thread_id: &str) -> Result<EmailThread> {
+        let messages = self
+            .data
+            .lock()
+            .unwrap()
+            .messages
+            .iter()
+            .filter(|m| m.thread_id == thread_id)
+            .cloned()
+            .collect();
+        EmailThread::from_messages(thread_id.to_string(), messages)
+    }
+
+    async fn list_labels(&self) -> Result<Vec<LabelInfo>> {
+        Ok(self.data.lock().unwrap().labels.clone())
+    }
+
+    async fn create_label(&self, name: &str) -> Result<LabelId> {
+        let id = self.next_id("Label");
+        self.data.lock().unwrap().labels.push(LabelInfo {
+            id: id.clone(),
+            name: name.to_string(),
+            color: None,
+        });
+        Ok(LabelId::new(id))
+    }
+
+    async fn create_label_with_visibility(
+        &self,
+        name: &str,
+        _visibility: &LabelVisibility,
+    ) -> Result<LabelId> {
+        self.create_label(name).await
+    }
+
+    async fn update_label_visibility(
+        &self,
+        _label_id: &str,
+        _visibility: &LabelVisibility,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_label(&self, label_id: &str) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        data.labels.retain(|l| l.id != label_id);
+        for message in &mut data.messages {
+            message.labels.retain(|l| l != label_id);
+        }
+        Ok(())
+    }
+
+    async fn create_filter(&self, filter: &FilterRule) -> Result<String> {
+        let id = self.next_id("Filter");
+        let add_label_ids = filter.label_ids()?.into_iter().map(String::from).collect();
+        self.data.lock().unwrap().filters.push(ExistingFilterInfo {
+            id: id.clone(),
+            query: Some(FilterManager::build_gmail_query_static(filter)),
+            from: None,
+            to: None,
+            subject: None,
+            add_label_ids,
+            remove_label_ids: if filter.should_archive {
+                vec!["INBOX".to_string()]
+            } else {
+                Vec::new()
+            },
+        });
+        self.created_filters.lock().unwrap().push(filter.clone());
+        Ok(id)
+    }
+
+    async fn list_filters(&self) -> Result<Vec<ExistingFilterInfo>> {
+        Ok(self.data.lock().unwrap().filters.clone())
+    }
+
+    async fn delete_filter(&self, filter_id: &str) -> Result<()> {
+        self.data
+            .lock()
+            .unwrap()
+            .filters
+            .retain(|f| f.id != filter_id);
+        Ok(())
+    }
+
+    async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String> {
+        self.delete_filter(filter_id).await?;
+        self.create_filter(filter).await
+    }
+
+    async fn apply_label(&self, message_id: &str, label_id: &LabelId) -> Result<()> {
+        self.modify_labels(
+            &[message_id.to_string()],
+            std::slice::from_ref(label_id),
+            &[],
+        );
+        Ok(())
+    }
+
+    async fn remove_label(&self, message_id: &str, label_id: &LabelId) -> Result<()> {
+        self.modify_labels(
+            &[message_id.to_string()],
+            &[],
+            std::slice::from_ref(label_id),
+        );
+        Ok(())
+    }
+
+    async fn batch_remove_label(
+        &self,
+        message_ids: &[String],
+        label_id: &LabelId,
+    ) -> Result<usize> {
+        Ok(self.modify_labels(message_ids, &[], std::slice::from_ref(label_id)))
+    }
+
+    async fn batch_add_label(&self, message_ids: &[String], label_id: &LabelId) -> Result<usize> {
+        Ok(self.modify_labels(message_ids, std::slice::from_ref(label_id), &[]))
+    }
+
+    async fn batch_modify_labels(
+        &self,
+        message_ids: &[String],
+        add_label_ids: &[LabelId],
+        remove_label_ids: &[LabelId],
+    ) -> Result<BatchModifyResult> {
+        Ok(BatchModifyResult {
+            succeeded: self.modify_labels(message_ids, add_label_ids, remove_label_ids),
+            failed: Vec::new(),
+        })
+    }
+
+    async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<MessageMetadata>> {
+        let mut messages = Vec::with_capacity(message_ids.len());
+        for id in &message_ids {
+            messages.push(self.get_message(id).await?);
+        }
+        Ok(messages)
+    }
+
+    async fn fetch_messages_with_progress(
+        &self,
+        message_ids: Vec<String>,
+        on_progress: ProgressCallback,
+    ) -> Result<Vec<MessageMetadata>> {
+        let mut messages = Vec::with_capacity(message_ids.len());
+        for id in &message_ids {
+            messages.push(self.get_message(id).await?);
+            on_progress();
+        }
+        Ok(messages)
+    }
+
+    async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats {
+        crate::rate_limiter::QuotaStats {
+            available_units: 0,
+            max_units: 0,
+            refill_rate: 0,
+            total_consumed: 0,
+            total_operations: 0,
+            by_operation: Default::default(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::LabelName;
 
     #[test]
     fn test_should_retry_server_error() {
@@ -1255,31 +2261,57 @@ mod tests {
             status: 500,
             message: "Internal error".to_string(),
         };
-        assert!(ProductionGmailClient::should_retry(&error));
+        assert!(RetryConfig::default().should_retry(&error));
     }
 
     #[test]
     fn test_should_retry_rate_limit() {
         let error = GmailError::RateLimitExceeded { retry_after: 5 };
-        assert!(ProductionGmailClient::should_retry(&error));
+        assert!(RetryConfig::default().should_retry(&error));
     }
 
     #[test]
     fn test_should_retry_network_error() {
         let error = GmailError::NetworkError("connection reset".to_string());
-        assert!(ProductionGmailClient::should_retry(&error));
+        assert!(RetryConfig::default().should_retry(&error));
     }
 
     #[test]
     fn test_should_not_retry_auth_error() {
         let error = GmailError::AuthError("invalid token".to_string());
-        assert!(!ProductionGmailClient::should_retry(&error));
+        assert!(!RetryConfig::default().should_retry(&error));
     }
 
     #[test]
     fn test_should_not_retry_filter_error() {
         let error = GmailError::FilterError("invalid filter".to_string());
-        assert!(!ProductionGmailClient::should_retry(&error));
+        assert!(!RetryConfig::default().should_retry(&error));
+    }
+
+    #[test]
+    fn test_batch_modify_result() {
+        let complete = BatchModifyResult {
+            succeeded: 3,
+            failed: vec![],
+        };
+        assert_eq!(complete.into_result().unwrap(), 3);
+
+        let partial = BatchModifyResult {
+            succeeded: 1,
+            failed: vec![
+                FailedMessage {
+                    message_id: "m2".to_string(),
+                    error: "Server error 503".to_string(),
+                },
+                FailedMessage {
+                    message_id: "m3".to_string(),
+                    error: "Server error 503".to_string(),
+                },
+            ],
+        };
+        assert_eq!(partial.failed_ids().collect::<Vec<_>>(), ["m2", "m3"]);
+        let err = partial.into_result().unwrap_err();
+        assert!(err.to_string().contains("2 messages"));
     }
 
     #[test]
@@ -1352,4 +2384,66 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.unwrap().is_ok());
     }
+
+    #[tokio::test]
+    async fn test_mock_gmail_client_from_fixture() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/gmail-fixture.json");
+        let client = MockGmailClient::from_fixture(&path).unwrap();
+
+        let github = client
+            .list_message_ids("from:(*@github.com) -in:chats after:2020/01/01")
+            .await
+            .unwrap();
+        assert_eq!(github.len(), 5);
+        let friends = client
+            .list_message_ids("label:friends OR saturday")
+            .await
+            .unwrap();
+        assert_eq!(friends, ["msg016", "msg017"]);
+        let shipped = client
+            .list_message_ids("subject:\"has shipped\" (from:shop.example OR from:bank.example)")
+            .await
+            .unwrap();
+        assert_eq!(shipped.len(), 3);
+
+        let rule = FilterRule {
+            id: None,
+            name: "GitHub".to_string(),
+            from_pattern: Some("*@github.com".to_string()),
+            is_specific_sender: false,
+            excluded_senders: vec![],
+            subject_keywords: vec![],
+            target_label: LabelName::new("AutoManaged/GitHub"),
+            target_label_id: Some(LabelId::new("Label_2")),
+            should_archive: true,
+            estimated_matches: 0,
+            priority: crate::models::DEFAULT_FILTER_PRIORITY,
+            extra_terms: vec![],
+            additional_labels: vec![],
+            additional_label_ids: vec![],
+        };
+        let filter_id = client.create_filter(&rule).await.unwrap();
+        assert_eq!(client.created_filters().len(), 1);
+        assert!(client
+            .list_filters()
+            .await
+            .unwrap()
+            .iter()
+            .any(|f| f.id == filter_id && f.remove_label_ids == ["INBOX"]));
+
+        let archived = client
+            .batch_modify_labels(
+                &github,
+                &[LabelId::new("Label_2")],
+                &[LabelId::new("INBOX")],
+            )
+            .await
+            .unwrap();
+        assert_eq!(archived.succeeded, 5);
+        assert!(client
+            .list_message_ids("from:github.com in:inbox")
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
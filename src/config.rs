@@ -1,7 +1,10 @@
+use futures::Stream;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use crate::error::{GmailError, Result};
+use crate::models::EmailCategory;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -15,14 +18,70 @@ pub struct Config {
     pub execution: ExecutionConfig,
     #[serde(default)]
     pub circuit_breaker: CircuitBreakerConfig,
+    #[serde(default)]
+    pub client: ClientConfig,
+    #[serde(default)]
+    pub state: StateConfig,
+    #[serde(default)]
+    pub exclusions: ExclusionsConfig,
+    #[serde(default)]
+    pub stats: StatsConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanConfig {
     #[serde(default = "default_period_days")]
     pub period_days: u32,
+    /// Gmail API calls in flight at once; CPU parallelism is `execution.worker_threads`
     #[serde(default = "default_max_concurrent")]
     pub max_concurrent_requests: usize,
+    /// Order in which generated filters are created in Gmail
+    #[serde(default)]
+    pub filter_sort_order: FilterSortOrder,
+    /// Fetch whole threads instead of individual messages while scanning
+    #[serde(default)]
+    pub thread_mode: bool,
+    /// Save scan progress to the state file after this many messages
+    #[serde(default = "default_checkpoint_interval_messages")]
+    pub checkpoint_interval_messages: usize,
+    /// Read SPF/DKIM results from `Authentication-Results` to adjust confidence
+    #[serde(default = "default_parse_auth_headers")]
+    pub parse_auth_headers: bool,
+    /// Keep `List-Unsubscribe` links on scanned messages for the `unsubscribe` command
+    #[serde(default = "default_extract_unsubscribe_links")]
+    pub extract_unsubscribe_links: bool,
+    /// Look up MX records before review to point out domains of the same company
+    /// (one DNS query per sender domain)
+    #[serde(default)]
+    pub analyze_mx_groups: bool,
+    /// Fetch and classify messages while later pages of IDs are still being listed
+    #[serde(default)]
+    pub streaming_fetch: bool,
+    /// Stop a scan after fetching this many new messages (`None` = no limit);
+    /// `run --resume` continues a truncated scan
+    #[serde(default)]
+    pub max_messages: Option<usize>,
+    /// Only scan messages in at least one of these system labels (empty = all mail)
+    #[serde(default)]
+    pub include_labels: Vec<String>,
+    /// Skip messages in any of these system labels
+    #[serde(default)]
+    pub exclude_labels: Vec<String>,
+    /// Senders that are never scanned, classified or counted: exact addresses or
+    /// globs such as `*@domain.com`
+    #[serde(default)]
+    pub exclude_senders: Vec<String>,
+    /// Reuse message metadata fetched by earlier runs instead of fetching it again
+    ///
+    /// The cache is stored unencrypted, so it is not used while the state is
+    /// encrypted (see `StateConfig::encryption_key_env`).
+    #[serde(default)]
+    pub metadata_cache_enabled: bool,
+    /// Messages kept in the metadata cache; the least recently used are evicted
+    #[serde(default = "default_metadata_cache_max_entries")]
+    pub metadata_cache_max_entries: usize,
 }
 
 impl Default for ScanConfig {
@@ -30,29 +89,218 @@ impl Default for ScanConfig {
         Self {
             period_days: default_period_days(),
             max_concurrent_requests: default_max_concurrent(),
+            filter_sort_order: FilterSortOrder::default(),
+            thread_mode: false,
+            checkpoint_interval_messages: default_checkpoint_interval_messages(),
+            parse_auth_headers: default_parse_auth_headers(),
+            extract_unsubscribe_links: default_extract_unsubscribe_links(),
+            analyze_mx_groups: false,
+            streaming_fetch: false,
+            max_messages: None,
+            include_labels: Vec::new(),
+            exclude_labels: Vec::new(),
+            exclude_senders: Vec::new(),
+            metadata_cache_enabled: false,
+            metadata_cache_max_entries: default_metadata_cache_max_entries(),
         }
     }
 }
 
+/// Gmail system labels accepted by `scan.include_labels` and `scan.exclude_labels`;
+/// user labels would have to be looked up by ID first
+const SCAN_SYSTEM_LABELS: &[&str] = &["INBOX", "SENT", "SPAM", "TRASH", "UNREAD"];
+
+impl ScanConfig {
+    /// Gmail search query for the messages to scan: the scan period, narrowed down
+    /// by `include_labels` and `exclude_labels`
+    pub fn search_query(&self) -> String {
+        let period = chrono::Duration::days(self.period_days as i64);
+        let after_date = (chrono::Utc::now() - period).format("%Y/%m/%d");
+        let mut query = format!("after:{}", after_date);
+
+        if !self.include_labels.is_empty() {
+            let included: Vec<String> = self
+                .include_labels
+                .iter()
+                .map(|name| format!("in:{}", name.to_lowercase()))
+                .collect();
+            query.push_str(&format!(" ({})", included.join(" OR ")));
+        }
+        for name in &self.exclude_labels {
+            query.push_str(&format!(" -in:{}", name.to_lowercase()));
+        }
+        query
+    }
+}
+
+/// Order in which filters are created.
+///
+/// Gmail does not guarantee the order filters run in, but creation order decides
+/// which label wins for messages matched by overlapping filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterSortOrder {
+    /// Lowest `priority` first
+    #[default]
+    ByPriority,
+    /// Filters expected to match the most messages first
+    ByEstimatedMatches,
+}
+
+/// Classification engine named by `classification.mode`
+///
+/// `rules` uses the built-in heuristics, `ml` the subject word model (messages it
+/// doesn't recognize become `Other`), and `hybrid` the model with the heuristics
+/// for the messages it doesn't recognize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ClassificationMode {
+    #[default]
+    Rules,
+    Ml,
+    Hybrid,
+}
+
+impl std::fmt::Display for ClassificationMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ClassificationMode::Rules => "rules",
+            ClassificationMode::Ml => "ml",
+            ClassificationMode::Hybrid => "hybrid",
+        })
+    }
+}
+
+/// Classify a sample of messages with a second mode and log where the modes disagree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbTestConfig {
+    /// Mode whose classifications the run uses
+    #[serde(default)]
+    pub control_mode: ClassificationMode,
+    /// Mode compared against the control, only logged
+    pub experiment_mode: ClassificationMode,
+    /// Fraction of messages classified by both modes (0.0-1.0)
+    #[serde(default = "default_experiment_fraction")]
+    pub experiment_fraction: f32,
+    /// JSON Lines log of the compared classifications, read by `ab-test-report`
+    #[serde(default = "default_ab_test_log_path")]
+    pub log_path: PathBuf,
+}
+
+/// Label every message from `domain` (or its subdomains) as `label`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClassificationRule {
+    pub domain: String,
+    pub label: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClassificationConfig {
-    #[serde(default = "default_mode")]
-    pub mode: String,
+    #[serde(default)]
+    pub mode: ClassificationMode,
     #[serde(default = "default_llm_provider")]
     pub llm_provider: String,
     #[serde(default = "default_min_emails")]
     pub minimum_emails_for_label: usize,
     #[serde(default)]
     pub claude_agents: ClaudeAgentsConfig,
+    /// Extra subject keywords per category, added to the built-in lists
+    #[serde(default)]
+    pub subject_keywords: HashMap<String, Vec<String>>,
+    /// Sender clusters holding less than this fraction of their domain's messages
+    /// are merged into the domain cluster (0.0 disables merging)
+    #[serde(default = "default_auto_merge_threshold")]
+    pub auto_merge_threshold: f32,
+    /// Minimum confidence per category (e.g. "Newsletter" = 0.6); less confident
+    /// classifications fall back to `Other`
+    #[serde(default)]
+    pub min_confidence: HashMap<String, f32>,
+    /// Show clusters of automatic (out-of-office) replies in review
+    #[serde(default)]
+    pub show_autoreply_clusters: bool,
+    /// Show clusters of bounced-mail notices in review, so filters can be created for them
+    #[serde(default)]
+    pub create_bounce_filters: bool,
+    /// Adjust confidence by the local hour a message arrived (see `execution.timezone`)
+    #[serde(default)]
+    pub use_time_heuristics: bool,
+    /// Apply `size_boosts` using Gmail's message size estimate
+    #[serde(default = "default_use_size_heuristics")]
+    pub use_size_heuristics: bool,
+    /// Confidence adjustments for large messages of a given category
+    #[serde(default = "default_size_boosts")]
+    pub size_boosts: Vec<SizeBoost>,
+    /// Compare two classification modes on a sample of scanned messages
+    #[serde(default)]
+    pub ab_test: Option<AbTestConfig>,
+    /// Labels for specific sender domains, overriding the suggested label
+    /// (see the `generate-rules` command)
+    #[serde(default)]
+    pub rules: Vec<ClassificationRule>,
+}
+
+/// Confidence added to `category` classifications of messages of at least `min_bytes`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SizeBoost {
+    pub min_bytes: u64,
+    pub category: EmailCategory,
+    pub boost: f32,
 }
 
 impl Default for ClassificationConfig {
     fn default() -> Self {
         Self {
-            mode: default_mode(),
+            mode: ClassificationMode::default(),
             llm_provider: default_llm_provider(),
             minimum_emails_for_label: default_min_emails(),
             claude_agents: ClaudeAgentsConfig::default(),
+            subject_keywords: HashMap::new(),
+            auto_merge_threshold: default_auto_merge_threshold(),
+            min_confidence: HashMap::new(),
+            show_autoreply_clusters: false,
+            create_bounce_filters: false,
+            use_time_heuristics: false,
+            use_size_heuristics: default_use_size_heuristics(),
+            size_boosts: default_size_boosts(),
+            ab_test: None,
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl ClassificationConfig {
+    /// `min_confidence` keyed by category (case-insensitive names, unknown names skipped)
+    pub fn min_confidence_thresholds(&self) -> HashMap<EmailCategory, f32> {
+        EmailCategory::all_variants()
+            .iter()
+            .filter_map(|category| {
+                let name = format!("{:?}", category);
+                self.min_confidence
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(&name))
+                    .map(|(_, threshold)| (category.clone(), *threshold))
+            })
+            .collect()
+    }
+
+    /// Categories left out of review clusters
+    pub fn hidden_cluster_categories(&self) -> Vec<EmailCategory> {
+        let mut hidden = Vec::new();
+        if !self.show_autoreply_clusters {
+            hidden.push(EmailCategory::AutoReply);
+        }
+        if !self.create_bounce_filters {
+            hidden.push(EmailCategory::Bounce);
+        }
+        hidden
+    }
+
+    /// `size_boosts`, or none when size heuristics are disabled
+    pub fn active_size_boosts(&self) -> Vec<SizeBoost> {
+        if self.use_size_heuristics {
+            self.size_boosts.clone()
+        } else {
+            Vec::new()
         }
     }
 }
@@ -83,6 +331,22 @@ pub struct LabelConfig {
     pub prefix: String,
     #[serde(default = "default_auto_archive_categories")]
     pub auto_archive_categories: Vec<String>,
+    /// Template for generated label names; see `LabelManager::resolve_label_name`.
+    /// `{prefix}` expands to `prefix`.
+    #[serde(default = "default_label_template")]
+    pub label_template: String,
+    /// Gmail UI visibility for newly created labels, keyed by category name (e.g. "Newsletter")
+    #[serde(default = "default_category_visibility")]
+    pub category_visibility: HashMap<String, LabelVisibility>,
+    /// Label for calendar invites; `{prefix}` expands to `prefix`
+    #[serde(default = "default_calendar_label")]
+    pub calendar_label: String,
+    /// Append a sublabel to the suggested label of messages with attachments
+    #[serde(default)]
+    pub use_attachment_sublabels: bool,
+    /// Sublabel per attachment MIME type (e.g. "application/pdf" -> "documents")
+    #[serde(default = "default_attachment_sublabels")]
+    pub attachment_sublabels: HashMap<String, String>,
 }
 
 impl Default for LabelConfig {
@@ -90,24 +354,152 @@ impl Default for LabelConfig {
         Self {
             prefix: default_prefix(),
             auto_archive_categories: default_auto_archive_categories(),
+            label_template: default_label_template(),
+            category_visibility: default_category_visibility(),
+            calendar_label: default_calendar_label(),
+            use_attachment_sublabels: false,
+            attachment_sublabels: default_attachment_sublabels(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+impl LabelConfig {
+    /// Visibility for labels of the given category (case-insensitive lookup, `Show` if unset)
+    pub fn visibility_for(&self, category: &EmailCategory) -> LabelVisibility {
+        let name = format!("{:?}", category);
+        self.category_visibility
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(&name))
+            .map(|(_, vis)| *vis)
+            .unwrap_or_default()
+    }
+}
+
+/// Whether a label is shown in the message list and in the label sidebar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct LabelVisibility {
+    #[serde(default)]
+    pub message_list: VisibilityMode,
+    #[serde(default)]
+    pub label_list: VisibilityMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VisibilityMode {
+    #[default]
+    Show,
+    Hide,
+    /// Only show the label while it has unread messages (label list only)
+    ShowIfUnread,
+}
+
+impl LabelVisibility {
+    /// Value for the Gmail API `messageListVisibility` field.
+    ///
+    /// The message list has no "if unread" mode, so `ShowIfUnread` is treated as `Show`.
+    pub fn message_list_api_value(&self) -> &'static str {
+        match self.message_list {
+            VisibilityMode::Hide => "hide",
+            VisibilityMode::Show | VisibilityMode::ShowIfUnread => "show",
+        }
+    }
+
+    /// Value for the Gmail API `labelListVisibility` field
+    pub fn label_list_api_value(&self) -> &'static str {
+        match self.label_list {
+            VisibilityMode::Show => "labelShow",
+            VisibilityMode::Hide => "labelHide",
+            VisibilityMode::ShowIfUnread => "labelShowIfUnread",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionConfig {
     #[serde(default)]
     pub dry_run: bool,
+    /// Tokio worker threads, i.e. CPU parallelism for JSON parsing and
+    /// classification. Network I/O parallelism is `scan.max_concurrent_requests`.
+    /// Defaults to the number of CPU cores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_threads: Option<usize>,
+    /// IANA time zone of the mailbox owner (e.g. "America/New_York"), used by the
+    /// classifier's time-of-day heuristics
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Format the run report is saved in
+    #[serde(default)]
+    pub report_format: ReportFormat,
+    /// After each successful run, delete all but this many of the most recent
+    /// reports, state snapshots and decision files (`None` keeps everything)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_cleanup_runs: Option<usize>,
+}
+
+/// Format of the run report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    /// Self-contained HTML page with charts, saved as `report-<run>.html` next to
+    /// the Markdown report
+    Html,
+    /// Report data as JSON, saved as `report-<run>.json` instead of Markdown
+    Json,
 }
 
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            worker_threads: None,
+            timezone: default_timezone(),
+            report_format: ReportFormat::default(),
+            auto_cleanup_runs: None,
+        }
+    }
+}
+
+impl ExecutionConfig {
+    /// `timezone` parsed as an IANA time zone
+    pub fn time_zone(&self) -> Result<chrono_tz::Tz> {
+        self.timezone.parse().map_err(|_| {
+            GmailError::ConfigError(format!(
+                "execution.timezone '{}' is not a known time zone (e.g. \"America/New_York\")",
+                self.timezone
+            ))
+        })
+    }
+
+    /// Worker thread count to build the runtime with
+    pub fn effective_worker_threads(&self) -> usize {
+        self.worker_threads.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+    }
+}
+
+/// When the circuit breaker stops sending requests to the Gmail API, and how it recovers
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CircuitBreakerConfig {
     #[serde(default = "default_circuit_breaker_enabled")]
     pub enabled: bool,
+    /// Consecutive transient failures (429s, 5xx, network errors) that open the circuit
     #[serde(default = "default_failure_threshold")]
     pub failure_threshold: u32,
-    #[serde(default = "default_reset_timeout_secs")]
-    pub reset_timeout_secs: u64,
+    /// Successful test requests needed while half-open before the circuit closes again
+    #[serde(default = "default_success_threshold_to_close")]
+    pub success_threshold_to_close: u32,
+    /// Seconds the circuit stays open before test requests are allowed (half-open)
+    #[serde(default = "default_open_duration_secs", alias = "reset_timeout_secs")]
+    pub open_duration_secs: u64,
+    /// Test requests allowed in flight at once while half-open
+    #[serde(default = "default_half_open_max_calls")]
+    pub half_open_max_calls: u32,
 }
 
 impl Default for CircuitBreakerConfig {
@@ -115,27 +507,363 @@ impl Default for CircuitBreakerConfig {
         Self {
             enabled: default_circuit_breaker_enabled(),
             failure_threshold: default_failure_threshold(),
-            reset_timeout_secs: default_reset_timeout_secs(),
+            success_threshold_to_close: default_success_threshold_to_close(),
+            open_duration_secs: default_open_duration_secs(),
+            half_open_max_calls: default_half_open_max_calls(),
+        }
+    }
+}
+
+/// Gmail API client options
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ClientConfig {
+    #[serde(default)]
+    pub retry: RetryConfig,
+}
+
+/// When and how failed Gmail API calls are retried
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Total attempts per call, including the first one
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub base_delay_ms: u64,
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// Factor the delay grows by after each retry
+    #[serde(default = "default_retry_multiplier")]
+    pub multiplier: f32,
+    /// Randomize each delay between half and the full value
+    #[serde(default)]
+    pub jitter: bool,
+    /// Errors worth retrying; anything else fails immediately
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<ErrorKind>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_retry_max_attempts(),
+            base_delay_ms: default_retry_base_delay_ms(),
+            max_delay_ms: default_retry_max_delay_ms(),
+            multiplier: default_retry_multiplier(),
+            jitter: false,
+            retry_on: default_retry_on(),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Whether `error` is one of the `retry_on` kinds
+    pub fn should_retry(&self, error: &GmailError) -> bool {
+        ErrorKind::of(error).is_some_and(|kind| self.retry_on.contains(&kind))
+    }
+
+    /// Delay before retry number `retry` (1 for the first retry)
+    pub fn delay(&self, retry: u32) -> std::time::Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay_ms = (self.base_delay_ms as f64 * (self.multiplier as f64).powi(exponent))
+            .min(self.max_delay_ms as f64);
+        let delay_ms = if self.jitter {
+            rand::Rng::gen_range(&mut rand::thread_rng(), delay_ms / 2.0..=delay_ms)
+        } else {
+            delay_ms
+        };
+        std::time::Duration::from_millis(delay_ms as u64)
+    }
+}
+
+/// Kinds of transient errors that `RetryConfig::retry_on` can select
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// HTTP 429 / quota exhausted
+    RateLimit,
+    /// HTTP 5xx other than 503
+    #[serde(rename = "server_error_500")]
+    ServerError500,
+    /// HTTP 503 Service Unavailable
+    #[serde(rename = "server_error_503")]
+    ServerError503,
+    /// Request timed out
+    Timeout,
+    /// Connection and other network failures
+    Network,
+}
+
+impl ErrorKind {
+    /// The retryable kind of `error`, or `None` for permanent errors
+    pub fn of(error: &GmailError) -> Option<Self> {
+        match error {
+            GmailError::RateLimitExceeded { .. } | GmailError::RateLimitError(_) => {
+                Some(Self::RateLimit)
+            }
+            GmailError::ServerError { status: 503, .. } => Some(Self::ServerError503),
+            GmailError::ServerError { .. } => Some(Self::ServerError500),
+            GmailError::NetworkError(message) => {
+                let message = message.to_lowercase();
+                if message.contains("timed out") || message.contains("timeout") {
+                    Some(Self::Timeout)
+                } else {
+                    Some(Self::Network)
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Persistence options for the processing state file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateConfig {
+    /// Write state files zstd-compressed (loading auto-detects either format)
+    #[serde(default)]
+    pub compress: bool,
+    /// Serialization format for state files (loading auto-detects either format)
+    #[serde(default)]
+    pub format: StateFormat,
+    /// Name of an environment variable holding a hex-encoded 256-bit key.
+    /// When set, state files are encrypted at rest with AES-256-GCM.
+    #[serde(default)]
+    pub encryption_key_env: Option<String>,
+    /// Seconds between background saves of the state during a run
+    #[serde(default = "default_checkpoint_interval_secs")]
+    pub checkpoint_interval_secs: u64,
+}
+
+impl Default for StateConfig {
+    fn default() -> Self {
+        Self {
+            compress: false,
+            format: StateFormat::default(),
+            encryption_key_env: None,
+            checkpoint_interval_secs: default_checkpoint_interval_secs(),
+        }
+    }
+}
+
+/// Serialization format of the processing state file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StateFormat {
+    /// Human-readable JSON
+    #[default]
+    Json,
+    /// Compact binary MessagePack, much faster for large states
+    #[serde(alias = "msgpack")]
+    MessagePack,
+}
+
+impl StateConfig {
+    /// Resolve the state encryption key from the configured environment variable
+    pub fn encryption_key(&self) -> Result<Option<[u8; 32]>> {
+        let Some(var) = &self.encryption_key_env else {
+            return Ok(None);
+        };
+
+        let value = std::env::var(var).map_err(|_| {
+            GmailError::ConfigError(format!(
+                "state.encryption_key_env is set but environment variable {} is not defined",
+                var
+            ))
+        })?;
+
+        let mut key = [0u8; 32];
+        hex::decode_to_slice(value.trim(), &mut key).map_err(|_| {
+            GmailError::ConfigError(format!(
+                "Environment variable {} must contain a hex-encoded 256-bit key (64 hex characters)",
+                var
+            ))
+        })?;
+
+        Ok(Some(key))
+    }
+}
+
+/// Static exclusions defined in the config file
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ExclusionsConfig {
+    /// Regex patterns matched against cluster keys (e.g. `^\*@.*\.example\.com$`).
+    /// Unlike saved exclusions these still apply with `--ignore-exclusions`.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+/// Filter effectiveness tracking options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsConfig {
+    /// Filters older than this that matched no messages in the latest snapshot are flagged stale
+    #[serde(default = "default_stale_threshold_days")]
+    pub stale_threshold_days: u32,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            stale_threshold_days: default_stale_threshold_days(),
+        }
+    }
+}
+
+/// Review session and progress bar appearance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiConfig {
+    /// Banner shown for clusters that already have a Gmail filter
+    #[serde(default = "default_existing_filter_color")]
+    pub existing_filter_color: AnsiColor,
+    /// Current/proposed values that differ on existing filters
+    #[serde(default = "default_changed_field_color")]
+    pub changed_field_color: AnsiColor,
+    /// Badge for clusters already accepted
+    #[serde(default = "default_accepted_badge_color")]
+    pub accepted_badge_color: AnsiColor,
+    /// Badge for clusters already rejected
+    #[serde(default = "default_rejected_badge_color")]
+    pub rejected_badge_color: AnsiColor,
+    #[serde(default)]
+    pub box_style: BoxStyle,
+    /// indicatif template for progress bars (`None` keeps the built-in one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress_bar_template: Option<String>,
+    /// indicatif template for spinners (`None` keeps the built-in one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spinner_template: Option<String>,
+    /// Animate spinners with Unicode braille dots; plain ASCII `|/-\` when false
+    #[serde(default = "default_use_unicode_bars")]
+    pub use_unicode_bars: bool,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            existing_filter_color: default_existing_filter_color(),
+            changed_field_color: default_changed_field_color(),
+            accepted_badge_color: default_accepted_badge_color(),
+            rejected_badge_color: default_rejected_badge_color(),
+            box_style: BoxStyle::default(),
+            progress_bar_template: None,
+            spinner_template: None,
+            use_unicode_bars: default_use_unicode_bars(),
+        }
+    }
+}
+
+/// The 16 standard ANSI terminal colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnsiColor {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+}
+
+impl AnsiColor {
+    /// Escape sequence that sets this foreground color
+    pub fn escape_code(&self) -> &'static str {
+        match self {
+            AnsiColor::Black => "\x1b[30m",
+            AnsiColor::Red => "\x1b[31m",
+            AnsiColor::Green => "\x1b[32m",
+            AnsiColor::Yellow => "\x1b[33m",
+            AnsiColor::Blue => "\x1b[34m",
+            AnsiColor::Magenta => "\x1b[35m",
+            AnsiColor::Cyan => "\x1b[36m",
+            AnsiColor::White => "\x1b[37m",
+            AnsiColor::BrightBlack => "\x1b[90m",
+            AnsiColor::BrightRed => "\x1b[91m",
+            AnsiColor::BrightGreen => "\x1b[92m",
+            AnsiColor::BrightYellow => "\x1b[93m",
+            AnsiColor::BrightBlue => "\x1b[94m",
+            AnsiColor::BrightMagenta => "\x1b[95m",
+            AnsiColor::BrightCyan => "\x1b[96m",
+            AnsiColor::BrightWhite => "\x1b[97m",
         }
     }
 }
 
+/// Characters used to draw the review session boxes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BoxStyle {
+    /// Box-drawing characters (`┌─┐│`)
+    #[default]
+    Unicode,
+    /// Plain `+`, `-` and `|` for terminals without Unicode support
+    Ascii,
+}
+
 fn default_period_days() -> u32 {
     90
 }
 
+fn default_metadata_cache_max_entries() -> usize {
+    crate::scanner::DEFAULT_METADATA_CACHE_SIZE
+}
+
 fn default_max_concurrent() -> usize {
     40
 }
 
-fn default_mode() -> String {
-    "rules".to_string()
+fn default_checkpoint_interval_messages() -> usize {
+    1000
+}
+
+fn default_parse_auth_headers() -> bool {
+    true
+}
+
+fn default_extract_unsubscribe_links() -> bool {
+    true
+}
+
+fn default_experiment_fraction() -> f32 {
+    0.1
+}
+
+fn default_ab_test_log_path() -> PathBuf {
+    PathBuf::from(".gmail-automation/ab-test.jsonl")
 }
 
 fn default_min_emails() -> usize {
     5
 }
 
+fn default_auto_merge_threshold() -> f32 {
+    0.1
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+fn default_use_size_heuristics() -> bool {
+    true
+}
+
+fn default_size_boosts() -> Vec<SizeBoost> {
+    // Statements and invoices usually carry PDF attachments
+    vec![SizeBoost {
+        min_bytes: 500 * 1024,
+        category: EmailCategory::Financial,
+        boost: 0.2,
+    }]
+}
+
 fn default_prefix() -> String {
     "AutoManaged".to_string()
 }
@@ -148,6 +876,41 @@ fn default_auto_archive_categories() -> Vec<String> {
     ]
 }
 
+fn default_label_template() -> String {
+    crate::classifier::DEFAULT_LABEL_TEMPLATE.to_string()
+}
+
+fn default_calendar_label() -> String {
+    crate::classifier::DEFAULT_CALENDAR_LABEL.to_string()
+}
+
+fn default_attachment_sublabels() -> HashMap<String, String> {
+    [
+        ("application/pdf", "documents"),
+        ("image/png", "images"),
+        ("image/jpeg", "images"),
+    ]
+    .into_iter()
+    .map(|(mime_type, sublabel)| (mime_type.to_string(), sublabel.to_string()))
+    .collect()
+}
+
+/// Categories that are archived by default are hidden from the label sidebar to reduce clutter
+fn default_category_visibility() -> HashMap<String, LabelVisibility> {
+    ["Newsletter", "Notification", "Marketing"]
+        .into_iter()
+        .map(|category| {
+            (
+                category.to_string(),
+                LabelVisibility {
+                    message_list: VisibilityMode::Show,
+                    label_list: VisibilityMode::Hide,
+                },
+            )
+        })
+        .collect()
+}
+
 fn default_llm_provider() -> String {
     "openai".to_string()
 }
@@ -168,10 +931,72 @@ fn default_failure_threshold() -> u32 {
     5
 }
 
-fn default_reset_timeout_secs() -> u64 {
+fn default_success_threshold_to_close() -> u32 {
+    1
+}
+
+fn default_open_duration_secs() -> u64 {
     60
 }
 
+fn default_half_open_max_calls() -> u32 {
+    1
+}
+
+fn default_retry_max_attempts() -> u32 {
+    4
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    1000
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    30_000
+}
+
+fn default_retry_multiplier() -> f32 {
+    2.0
+}
+
+fn default_retry_on() -> Vec<ErrorKind> {
+    vec![
+        ErrorKind::RateLimit,
+        ErrorKind::ServerError500,
+        ErrorKind::ServerError503,
+        ErrorKind::Timeout,
+        ErrorKind::Network,
+    ]
+}
+
+fn default_checkpoint_interval_secs() -> u64 {
+    10
+}
+
+fn default_stale_threshold_days() -> u32 {
+    30
+}
+
+fn default_existing_filter_color() -> AnsiColor {
+    AnsiColor::Yellow
+}
+
+fn default_changed_field_color() -> AnsiColor {
+    AnsiColor::Red
+}
+
+fn default_accepted_badge_color() -> AnsiColor {
+    AnsiColor::Green
+}
+
+fn default_rejected_badge_color() -> AnsiColor {
+    AnsiColor::Red
+}
+
+fn default_use_unicode_bars() -> bool {
+    true
+}
+
 impl Config {
     pub async fn load(path: &Path) -> Result<Self> {
         // If file doesn't exist, return default config with warning
@@ -184,13 +1009,32 @@ impl Config {
             .await
             .map_err(|e| GmailError::ConfigError(format!("Failed to read config file: {}", e)))?;
 
-        let config: Self = toml::from_str(&content)
+        let config = Self::parse(&content)?;
+
+        tracing::info!("Loaded configuration from {:?}", path);
+        Ok(config)
+    }
+
+    /// Blocking variant of `load` for use before the async runtime exists
+    pub fn load_blocking(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| GmailError::ConfigError(format!("Failed to read config file: {}", e)))?;
+
+        Self::parse(&content)
+    }
+
+    /// Parse and validate TOML config content
+    fn parse(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)
             .map_err(|e| GmailError::ConfigError(format!("Failed to parse config file: {}", e)))?;
 
         // Validate the loaded config
         config.validate()?;
 
-        tracing::info!("Loaded configuration from {:?}", path);
         Ok(config)
     }
 
@@ -238,14 +1082,61 @@ impl Config {
                 "scan.max_concurrent_requests cannot exceed 50 (to stay under Gmail API rate limits of 250 units/sec)".to_string(),
             ));
         }
+        if self.scan.checkpoint_interval_messages == 0 {
+            return Err(GmailError::ConfigError(
+                "scan.checkpoint_interval_messages must be at least 1".to_string(),
+            ));
+        }
+        for name in self
+            .scan
+            .include_labels
+            .iter()
+            .chain(&self.scan.exclude_labels)
+        {
+            if !SCAN_SYSTEM_LABELS.contains(&name.to_uppercase().as_str()) {
+                return Err(GmailError::ConfigError(format!(
+                    "scan.include_labels/exclude_labels: '{}' is not a system label (expected one of {})",
+                    name,
+                    SCAN_SYSTEM_LABELS.join(", ")
+                )));
+            }
+        }
+        if self
+            .scan
+            .exclude_senders
+            .iter()
+            .any(|s| s.trim().is_empty())
+        {
+            return Err(GmailError::ConfigError(
+                "scan.exclude_senders entries must not be empty".to_string(),
+            ));
+        }
+        if self.scan.max_messages == Some(0) {
+            return Err(GmailError::ConfigError(
+                "scan.max_messages must be at least 1 (omit it to scan without a limit)"
+                    .to_string(),
+            ));
+        }
+        if self.scan.metadata_cache_max_entries == 0 {
+            return Err(GmailError::ConfigError(
+                "scan.metadata_cache_max_entries must be at least 1".to_string(),
+            ));
+        }
 
         // Validate classification config
-        match self.classification.mode.as_str() {
-            "rules" | "ml" | "hybrid" => {}
-            other => {
+        for rule in &self.classification.rules {
+            if rule.domain.trim().is_empty() || rule.label.trim().is_empty() {
+                return Err(GmailError::ConfigError(
+                    "classification.rules entries need a domain and a label".to_string(),
+                ));
+            }
+        }
+
+        if let Some(ab_test) = &self.classification.ab_test {
+            if !(0.0..=1.0).contains(&ab_test.experiment_fraction) {
                 return Err(GmailError::ConfigError(format!(
-                    "Invalid classification.mode: '{}'. Must be 'rules', 'ml', or 'hybrid'",
-                    other
+                    "classification.ab_test.experiment_fraction must be between 0.0 and 1.0, got {}",
+                    ab_test.experiment_fraction
                 )));
             }
         }
@@ -266,53 +1157,767 @@ impl Config {
             ));
         }
 
-        if self.classification.claude_agents.max_iterations == 0 {
-            return Err(GmailError::ConfigError(
-                "classification.claude_agents.max_iterations must be greater than 0".to_string(),
-            ));
-        }
+        if !(0.0..=1.0).contains(&self.classification.auto_merge_threshold) {
+            return Err(GmailError::ConfigError(
+                "classification.auto_merge_threshold must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+
+        for size_boost in &self.classification.size_boosts {
+            if !(-1.0..=1.0).contains(&size_boost.boost) {
+                return Err(GmailError::ConfigError(format!(
+                    "classification.size_boosts: boost for {:?} must be between -1.0 and 1.0",
+                    size_boost.category
+                )));
+            }
+        }
+
+        if self.classification.claude_agents.max_iterations == 0 {
+            return Err(GmailError::ConfigError(
+                "classification.claude_agents.max_iterations must be greater than 0".to_string(),
+            ));
+        }
+
+        // Validate labels config
+        if self.labels.prefix.is_empty() {
+            return Err(GmailError::ConfigError(
+                "labels.prefix cannot be empty".to_string(),
+            ));
+        }
+        if self.labels.prefix.contains('/') {
+            return Err(GmailError::ConfigError(
+                "labels.prefix cannot contain '/' character".to_string(),
+            ));
+        }
+
+        // Validate auto_archive_categories
+        for category in &self.labels.auto_archive_categories {
+            if category.is_empty() {
+                return Err(GmailError::ConfigError(
+                    "labels.auto_archive_categories cannot contain empty strings".to_string(),
+                ));
+            }
+        }
+
+        // Validate label_template: {domain} keeps labels unique per sender domain
+        if !self.labels.label_template.contains("{domain}") {
+            return Err(GmailError::ConfigError(
+                "labels.label_template must contain the {domain} placeholder".to_string(),
+            ));
+        }
+        let placeholder = regex::Regex::new(r"\{([^}]*)\}").expect("valid regex");
+        for cap in placeholder.captures_iter(&self.labels.label_template) {
+            if !["prefix", "category", "domain", "sender", "year"].contains(&&cap[1]) {
+                return Err(GmailError::ConfigError(format!(
+                    "labels.label_template has unknown placeholder '{{{}}}' (expected {{prefix}}, {{category}}, {{domain}}, {{sender}} or {{year}})",
+                    &cap[1]
+                )));
+            }
+        }
+
+        if self.labels.calendar_label.trim().is_empty() {
+            return Err(GmailError::ConfigError(
+                "labels.calendar_label cannot be empty".to_string(),
+            ));
+        }
+
+        for (mime_type, sublabel) in &self.labels.attachment_sublabels {
+            let sublabel = sublabel.trim();
+            if sublabel.is_empty() || sublabel.starts_with('/') || sublabel.ends_with('/') {
+                return Err(GmailError::ConfigError(format!(
+                    "labels.attachment_sublabels has an invalid sublabel '{}' for {}",
+                    sublabel, mime_type
+                )));
+            }
+        }
+
+        // Validate category_visibility keys
+        const CATEGORIES: &[&str] = &[
+            "Newsletter",
+            "Receipt",
+            "Notification",
+            "Marketing",
+            "Shipping",
+            "Financial",
+            "Personal",
+            "AutoReply",
+            "Bounce",
+            "CalendarInvite",
+            "Other",
+        ];
+        for category in self.labels.category_visibility.keys() {
+            if !CATEGORIES.iter().any(|c| c.eq_ignore_ascii_case(category)) {
+                return Err(GmailError::ConfigError(format!(
+                    "labels.category_visibility has unknown category '{}' (expected one of: {})",
+                    category,
+                    CATEGORIES.join(", ")
+                )));
+            }
+        }
+
+        // Validate subject keyword categories: only categories with built-in keywords
+        // are matched on subject keywords
+        let keyword_categories: Vec<String> = crate::classifier::DEFAULT_SUBJECT_KEYWORDS
+            .iter()
+            .map(|(category, _)| format!("{:?}", category))
+            .collect();
+        for (category, keywords) in &self.classification.subject_keywords {
+            if !keyword_categories
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(category))
+            {
+                return Err(GmailError::ConfigError(format!(
+                    "classification.subject_keywords has unknown category '{}' (expected one of: {})",
+                    category,
+                    keyword_categories.join(", ")
+                )));
+            }
+            if keywords.iter().any(|k| k.trim().is_empty()) {
+                return Err(GmailError::ConfigError(format!(
+                    "classification.subject_keywords.{} contains an empty keyword",
+                    category
+                )));
+            }
+        }
+
+        for (category, threshold) in &self.classification.min_confidence {
+            if !CATEGORIES.iter().any(|c| c.eq_ignore_ascii_case(category)) {
+                return Err(GmailError::ConfigError(format!(
+                    "classification.min_confidence has unknown category '{}' (expected one of: {})",
+                    category,
+                    CATEGORIES.join(", ")
+                )));
+            }
+            if !(0.0..=1.0).contains(threshold) {
+                return Err(GmailError::ConfigError(format!(
+                    "classification.min_confidence.{} must be between 0.0 and 1.0",
+                    category
+                )));
+            }
+        }
+
+        // Validate worker thread count
+        if self.execution.worker_threads == Some(0) {
+            return Err(GmailError::ConfigError(
+                "execution.worker_threads must be at least 1".to_string(),
+            ));
+        }
+        self.execution.time_zone()?;
+        if self.execution.auto_cleanup_runs == Some(0) {
+            return Err(GmailError::ConfigError(
+                "execution.auto_cleanup_runs must be at least 1".to_string(),
+            ));
+        }
+
+        // Validate circuit breaker config
+        if self.state.checkpoint_interval_secs == 0 {
+            return Err(GmailError::ConfigError(
+                "state.checkpoint_interval_secs must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.circuit_breaker.failure_threshold == 0 {
+            return Err(GmailError::ConfigError(
+                "circuit_breaker.failure_threshold must be greater than 0".to_string(),
+            ));
+        }
+        if self.circuit_breaker.success_threshold_to_close == 0 {
+            return Err(GmailError::ConfigError(
+                "circuit_breaker.success_threshold_to_close must be greater than 0".to_string(),
+            ));
+        }
+        if self.circuit_breaker.open_duration_secs == 0 {
+            return Err(GmailError::ConfigError(
+                "circuit_breaker.open_duration_secs must be greater than 0".to_string(),
+            ));
+        }
+        if self.circuit_breaker.half_open_max_calls == 0 {
+            return Err(GmailError::ConfigError(
+                "circuit_breaker.half_open_max_calls must be greater than 0".to_string(),
+            ));
+        }
+
+        // Validate retry config
+        let retry = &self.client.retry;
+        if retry.max_attempts == 0 {
+            return Err(GmailError::ConfigError(
+                "client.retry.max_attempts must be at least 1".to_string(),
+            ));
+        }
+        if retry.multiplier < 1.0 {
+            return Err(GmailError::ConfigError(
+                "client.retry.multiplier must be at least 1.0".to_string(),
+            ));
+        }
+        if retry.base_delay_ms > retry.max_delay_ms {
+            return Err(GmailError::ConfigError(
+                "client.retry.base_delay_ms must not exceed client.retry.max_delay_ms".to_string(),
+            ));
+        }
+
+        // Validate exclusion patterns
+        for pattern in &self.exclusions.patterns {
+            if let Err(e) = regex::Regex::new(pattern) {
+                return Err(GmailError::ConfigError(format!(
+                    "Invalid exclusion regex: '{}': {}",
+                    pattern, e
+                )));
+            }
+        }
+
+        // Validate progress templates
+        for (key, template) in [
+            ("ui.progress_bar_template", &self.ui.progress_bar_template),
+            ("ui.spinner_template", &self.ui.spinner_template),
+        ] {
+            if let Some(template) = template {
+                if let Err(e) = indicatif::ProgressStyle::with_template(template) {
+                    return Err(GmailError::ConfigError(format!(
+                        "Invalid {}: '{}': {}",
+                        key, template, e
+                    )));
+                }
+            }
+        }
+
+        tracing::debug!("Configuration validation passed");
+        Ok(())
+    }
+
+    /// Create an example configuration file
+    ///
+    /// Every setting is written with its default value and a comment explaining
+    /// it; optional settings that are unset by default are written commented out.
+    pub async fn create_example(path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                GmailError::ConfigError(format!("Failed to create config directory: {}", e))
+            })?;
+        }
+
+        let content = Self::default().to_example_toml()?;
+
+        tokio::fs::write(path, content)
+            .await
+            .map_err(|e| GmailError::ConfigError(format!("Failed to write config file: {}", e)))?;
+
+        tracing::info!("Saved example configuration to {:?}", path);
+        Ok(())
+    }
+
+    /// This config as TOML, with `EXAMPLE_COMMENTS` above each setting
+    fn to_example_toml(&self) -> Result<String> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| GmailError::ConfigError(format!("Failed to serialize config: {}", e)))?;
+        let mut document: toml_edit::DocumentMut = content
+            .parse()
+            .map_err(|e| GmailError::ConfigError(format!("Failed to serialize config: {}", e)))?;
+
+        annotate_example_table(document.as_table_mut(), "", String::new());
+
+        Ok(format!(
+            "{}\n{}",
+            comment_lines(EXAMPLE_HEADER),
+            document.to_string().trim_start()
+        ))
+    }
+}
+
+/// Delay after a change event before reloading, so the several events an editor
+/// produces for one save trigger a single reload
+const CONFIG_RELOAD_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Watch a config file and yield the reloaded config after every change
+///
+/// The parent directory is watched rather than the file itself, so editors that
+/// save by writing a new file and renaming it over the old one are picked up.
+/// A config that fails to parse or validate is yielded as an error, leaving it
+/// to the caller to keep using the previous one.
+pub fn watch_config(path: &Path) -> impl Stream<Item = Result<Config>> {
+    use notify::{RecursiveMode, Watcher};
+
+    let path = path.to_path_buf();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+    // Set up the watcher right away so changes made before the first poll are seen
+    let watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })
+    .and_then(|mut watcher| {
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    })
+    .map_err(|e| GmailError::ConfigError(format!("Failed to watch config file: {}", e)));
+
+    async_stream::stream! {
+        // Dropping the watcher stops the events
+        let _watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                yield Err(e);
+                return;
+            }
+        };
+
+        while let Some(event) = rx.recv().await {
+            let event: notify::Event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::warn!("Config file watch error: {}", e);
+                    continue;
+                }
+            };
+            let is_config_file = event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == path.file_name());
+            if !is_config_file || event.kind.is_access() || event.kind.is_remove() {
+                continue;
+            }
+
+            tokio::time::sleep(CONFIG_RELOAD_DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
 
-        // Validate labels config
-        if self.labels.prefix.is_empty() {
-            return Err(GmailError::ConfigError(
-                "labels.prefix cannot be empty".to_string(),
-            ));
-        }
-        if self.labels.prefix.contains('/') {
-            return Err(GmailError::ConfigError(
-                "labels.prefix cannot contain '/' character".to_string(),
-            ));
-        }
+            // Mid-rename, the file may briefly not exist
+            if !path.exists() {
+                continue;
+            }
 
-        // Validate auto_archive_categories
-        for category in &self.labels.auto_archive_categories {
-            if category.is_empty() {
-                return Err(GmailError::ConfigError(
-                    "labels.auto_archive_categories cannot contain empty strings".to_string(),
-                ));
+            tracing::info!("Config file {:?} changed, reloading", path);
+            let config = Config::load(&path).await;
+            if let Err(e) = &config {
+                tracing::warn!("Ignoring invalid config change: {}", e);
             }
+            yield config;
         }
+    }
+}
 
-        // Validate circuit breaker config
-        if self.circuit_breaker.failure_threshold == 0 {
-            return Err(GmailError::ConfigError(
-                "circuit_breaker.failure_threshold must be greater than 0".to_string(),
-            ));
-        }
-        if self.circuit_breaker.reset_timeout_secs == 0 {
-            return Err(GmailError::ConfigError(
-                "circuit_breaker.reset_timeout_secs must be greater than 0".to_string(),
-            ));
+/// Written at the top of the file from `Config::create_example`
+const EXAMPLE_HEADER: &str =
+    "Gmail automation configuration, generated by `gmail-automation init-config`
+Every setting shows its default value; settings left out of this file use
+their default too. Commented-out settings are optional and unset by default.";
+
+/// Comments written above each section and setting by `Config::create_example`,
+/// keyed by dotted path
+const EXAMPLE_COMMENTS: &[(&str, &str)] = &[
+    ("scan", "Which messages are scanned and how they are fetched"),
+    (
+        "scan.period_days",
+        "Number of days of email history to scan. Increase for larger inboxes. (1-365)",
+    ),
+    (
+        "scan.max_concurrent_requests",
+        "Gmail API requests in flight at once (1-50). Higher is faster but may hit rate limits.",
+    ),
+    (
+        "scan.filter_sort_order",
+        "Order filters are created in Gmail: \"by_priority\" or \"by_estimated_matches\"",
+    ),
+    (
+        "scan.thread_mode",
+        "Fetch whole conversations instead of individual messages",
+    ),
+    (
+        "scan.checkpoint_interval_messages",
+        "Save scan progress after this many messages, so `run --resume` can continue a crashed scan",
+    ),
+    (
+        "scan.parse_auth_headers",
+        "Adjust confidence using the SPF/DKIM results in Authentication-Results headers",
+    ),
+    (
+        "scan.extract_unsubscribe_links",
+        "Keep List-Unsubscribe links for the `unsubscribe` command",
+    ),
+    (
+        "scan.analyze_mx_groups",
+        "Point out sender domains sharing a mail server before review (one DNS query per domain)",
+    ),
+    (
+        "scan.streaming_fetch",
+        "Fetch and classify messages while later pages of message IDs are still being listed",
+    ),
+    (
+        "scan.include_labels",
+        "Only scan messages in at least one of these system labels (empty = all mail)
+Valid labels: INBOX, SENT, SPAM, TRASH, UNREAD",
+    ),
+    (
+        "scan.exclude_labels",
+        "Skip messages in any of these system labels",
+    ),
+    (
+        "scan.exclude_senders",
+        "Senders that are never scanned, classified or counted, e.g. your own address
+Exact addresses or globs such as \"*@domain.com\"",
+    ),
+    (
+        "scan.metadata_cache_enabled",
+        "Reuse message metadata fetched by earlier runs instead of fetching it again",
+    ),
+    (
+        "scan.metadata_cache_max_entries",
+        "Messages kept in the metadata cache; the least recently used are evicted",
+    ),
+    ("classification", "How messages are sorted into categories"),
+    (
+        "classification.mode",
+        "Classification engine: \"rules\", \"ml\" or \"hybrid\"",
+    ),
+    (
+        "classification.llm_provider",
+        "LLM used by the \"ml\" and \"hybrid\" modes: \"openai\", \"anthropic\" or \"anthropic-agents\"",
+    ),
+    (
+        "classification.minimum_emails_for_label",
+        "Minimum emails from a sender before it gets its own label (at least 1)",
+    ),
+    (
+        "classification.auto_merge_threshold",
+        "Merge sender clusters holding less than this fraction of their domain's messages
+into the domain cluster (0.0-1.0, 0.0 disables merging)",
+    ),
+    (
+        "classification.show_autoreply_clusters",
+        "Show clusters of automatic (out-of-office) replies in review",
+    ),
+    (
+        "classification.create_bounce_filters",
+        "Show clusters of bounced-mail notices in review, so filters can be created for them",
+    ),
+    (
+        "classification.use_time_heuristics",
+        "Adjust confidence by the local hour a message arrived (see execution.timezone)",
+    ),
+    (
+        "classification.use_size_heuristics",
+        "Apply size_boosts using Gmail's message size estimate",
+    ),
+    (
+        "classification.rules",
+        "Labels for specific sender domains, overriding the suggested label
+Example: rules = [{ domain = \"github.com\", label = \"Dev/GitHub\" }]",
+    ),
+    (
+        "classification.claude_agents",
+        "Claude agent analysis (requires the claude-agents feature)",
+    ),
+    (
+        "classification.claude_agents.enabled",
+        "Use Claude agents for classification",
+    ),
+    (
+        "classification.claude_agents.use_advanced_analysis",
+        "Use the agents' advanced analysis",
+    ),
+    (
+        "classification.claude_agents.max_iterations",
+        "Maximum agent iterations per classification (at least 1)",
+    ),
+    (
+        "classification.subject_keywords",
+        "Extra subject keywords per category, added to the built-in lists
+Example: Receipt = [\"invoice\", \"payment received\"]",
+    ),
+    (
+        "classification.min_confidence",
+        "Minimum confidence per category (0.0-1.0); less confident classifications fall back to Other
+Example: Newsletter = 0.6",
+    ),
+    (
+        "classification.size_boosts",
+        "Confidence added to classifications of large messages of a category (boost between -1.0 and 1.0)",
+    ),
+    ("labels", "Names and visibility of the labels that are created"),
+    (
+        "labels.prefix",
+        "Top-level label holding every generated label (must not contain \"/\")",
+    ),
+    (
+        "labels.auto_archive_categories",
+        "Categories whose filters also archive messages (skip the inbox)",
+    ),
+    (
+        "labels.label_template",
+        "Template for generated label names; placeholders: {prefix}, {category}, {domain}",
+    ),
+    (
+        "labels.calendar_label",
+        "Label for calendar invites; {prefix} expands to prefix",
+    ),
+    (
+        "labels.use_attachment_sublabels",
+        "Append a sublabel from attachment_sublabels to the label of messages with attachments",
+    ),
+    (
+        "labels.category_visibility",
+        "Gmail visibility of new labels per category
+message_list: \"show\" or \"hide\"; label_list: \"show\", \"hide\" or \"show_if_unread\"",
+    ),
+    (
+        "labels.attachment_sublabels",
+        "Sublabel per attachment MIME type, used with use_attachment_sublabels",
+    ),
+    ("execution", "How runs are carried out and reported"),
+    (
+        "execution.dry_run",
+        "Only report the changes a run would make, without changing anything in Gmail",
+    ),
+    (
+        "execution.timezone",
+        "IANA time zone of the mailbox owner (e.g. \"America/New_York\"), used by time heuristics",
+    ),
+    (
+        "execution.report_format",
+        "Format the run report is saved in: \"markdown\", \"html\" or \"json\"",
+    ),
+    (
+        "circuit_breaker",
+        "Stop sending requests to the Gmail API after repeated failures, and when to try again",
+    ),
+    (
+        "circuit_breaker.enabled",
+        "Use the circuit breaker",
+    ),
+    (
+        "circuit_breaker.failure_threshold",
+        "Consecutive transient failures (429s, 5xx, network errors) that open the circuit (at least 1)",
+    ),
+    (
+        "circuit_breaker.success_threshold_to_close",
+        "Successful test requests needed while half-open before the circuit closes again",
+    ),
+    (
+        "circuit_breaker.open_duration_secs",
+        "Seconds the circuit stays open before test requests are allowed",
+    ),
+    (
+        "circuit_breaker.half_open_max_calls",
+        "Test requests allowed in flight at once while half-open",
+    ),
+    ("client", "Gmail API client options"),
+    ("client.retry", "When and how failed Gmail API calls are retried"),
+    (
+        "client.retry.max_attempts",
+        "Total attempts per call, including the first one",
+    ),
+    (
+        "client.retry.base_delay_ms",
+        "Delay before the first retry, in milliseconds",
+    ),
+    (
+        "client.retry.max_delay_ms",
+        "Longest delay between retries, in milliseconds",
+    ),
+    (
+        "client.retry.multiplier",
+        "Factor the delay grows by after each retry",
+    ),
+    (
+        "client.retry.jitter",
+        "Randomize each delay between half and the full value",
+    ),
+    (
+        "client.retry.retry_on",
+        "Errors worth retrying; anything else fails immediately
+Valid kinds: rate_limit, server_error_500, server_error_503, timeout, network",
+    ),
+    ("state", "How the processing state file is stored"),
+    (
+        "state.compress",
+        "Write state files zstd-compressed (loading detects either)",
+    ),
+    (
+        "state.checkpoint_interval_secs",
+        "Seconds between background saves of the state during a run (at least 1)",
+    ),
+    (
+        "state.format",
+        "State file format: \"json\" or \"message_pack\" (loading detects either)",
+    ),
+    ("exclusions", "Clusters never turned into filters"),
+    (
+        "exclusions.patterns",
+        "Regex patterns matched against cluster keys (e.g. \"^\\\\*@.*\\\\.example\\\\.com$\")
+These still apply with --ignore-exclusions",
+    ),
+    ("stats", "Filter effectiveness tracking"),
+    (
+        "stats.stale_threshold_days",
+        "Filters older than this that matched nothing in the latest snapshot are flagged stale",
+    ),
+    ("ui", "Review session and progress bar appearance"),
+    (
+        "ui.existing_filter_color",
+        "Banner color for clusters that already have a Gmail filter
+Colors: black, red, green, yellow, blue, magenta, cyan, white, or bright_ versions of them",
+    ),
+    (
+        "ui.changed_field_color",
+        "Color of values that differ on existing filters",
+    ),
+    (
+        "ui.accepted_badge_color",
+        "Badge color for clusters already accepted",
+    ),
+    (
+        "ui.rejected_badge_color",
+        "Badge color for clusters already rejected",
+    ),
+    (
+        "ui.box_style",
+        "Review box characters: \"unicode\" or \"ascii\"",
+    ),
+    (
+        "ui.use_unicode_bars",
+        "Animate spinners with Unicode braille dots; plain ASCII `|/-\\` when false",
+    ),
+];
+
+/// Optional settings, unset by default, written commented out at the end of
+/// their section by `Config::create_example`
+const EXAMPLE_OPTIONAL_SETTINGS: &[(&str, &str)] = &[
+    (
+        "scan",
+        "Stop a scan after fetching this many new messages; `run --resume` continues it
+max_messages = 10000",
+    ),
+    (
+        "classification",
+        "Also classify a sample of messages with a second mode and log both categories
+(see the ab-test-report command)
+[classification.ab_test]
+control_mode = \"rules\"
+experiment_mode = \"hybrid\"
+experiment_fraction = 0.1
+log_path = \".gmail-automation/ab-test.jsonl\"",
+    ),
+    (
+        "execution",
+        "Tokio worker threads for CPU work (default: number of CPU cores)
+worker_threads = 4",
+    ),
+    (
+        "execution",
+        "After each run, delete all but this many of the most recent reports,
+state snapshots and decision files (at least 1)
+auto_cleanup_runs = 10",
+    ),
+    (
+        "state",
+        "Environment variable holding a hex-encoded 256-bit key; state files are
+then encrypted with AES-256-GCM
+encryption_key_env = \"GMAIL_AUTOMATION_STATE_KEY\"",
+    ),
+    (
+        "ui",
+        "Custom indicatif templates for progress bars and spinners
+progress_bar_template = \"[{elapsed:>6}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}\"
+spinner_template = \"{spinner:.green} [{elapsed:>6}] {msg}\"",
+    ),
+];
+
+/// `text` as TOML comment lines
+fn comment_lines(text: &str) -> String {
+    text.lines().map(|line| format!("# {}\n", line)).collect()
+}
+
+fn example_comment(path: &str) -> Option<&'static str> {
+    EXAMPLE_COMMENTS
+        .iter()
+        .find(|(key, _)| *key == path)
+        .map(|(_, comment)| *comment)
+}
+
+/// Write an `f32` setting as its shortest form (0.1 rather than 0.10000000149011612)
+fn shorten_float(value: &mut toml_edit::Value) {
+    if let Some(float) = value.as_float() {
+        if let Ok(short) = (float as f32).to_string().parse::<f64>() {
+            *value = short.into();
         }
+    }
+}
 
-        tracing::debug!("Configuration validation passed");
-        Ok(())
+/// Add `EXAMPLE_COMMENTS` and `EXAMPLE_OPTIONAL_SETTINGS` to `table` and its
+/// subtables. `inherited` is the comment of an implicit parent table, which has
+/// no header of its own to write it above.
+fn annotate_example_table(table: &mut toml_edit::Table, path: &str, inherited: String) {
+    let mut comment = inherited;
+    if let Some(text) = example_comment(path) {
+        comment.push_str(&comment_lines(text));
+    }
+    let mut pending = if table.is_implicit() {
+        comment
+    } else {
+        table.decor_mut().set_prefix(format!("\n{}", comment));
+        String::new()
+    };
+
+    let mut last_value = None;
+    let mut first_value = true;
+    for (mut key, item) in table.iter_mut() {
+        let key_path = if path.is_empty() {
+            key.get().to_string()
+        } else {
+            format!("{}.{}", path, key.get())
+        };
+        match item {
+            toml_edit::Item::Value(value) => {
+                shorten_float(value);
+                if let Some(text) = example_comment(&key_path) {
+                    let separator = if first_value { "" } else { "\n" };
+                    key.leaf_decor_mut().set_prefix(format!(
+                        "{}{}",
+                        separator,
+                        comment_lines(text)
+                    ));
+                }
+                first_value = false;
+                last_value = Some(key.get().to_string());
+            }
+            toml_edit::Item::Table(subtable) => {
+                annotate_example_table(subtable, &key_path, std::mem::take(&mut pending));
+            }
+            toml_edit::Item::ArrayOfTables(tables) => {
+                for value in tables
+                    .iter_mut()
+                    .flat_map(|table| table.iter_mut())
+                    .filter_map(|(_, item)| item.as_value_mut())
+                {
+                    shorten_float(value);
+                }
+                if let Some(first) = tables.iter_mut().next() {
+                    let mut comment = std::mem::take(&mut pending);
+                    if let Some(text) = example_comment(&key_path) {
+                        comment.push_str(&comment_lines(text));
+                    }
+                    first.decor_mut().set_prefix(format!("\n{}", comment));
+                }
+            }
+            toml_edit::Item::None => {}
+        }
     }
 
-    /// Create an example configuration file
-    pub async fn create_example(path: &Path) -> Result<()> {
-        let config = Self::default();
-        config.save(path).await
+    let optional: String = EXAMPLE_OPTIONAL_SETTINGS
+        .iter()
+        .filter(|(table_path, _)| *table_path == path)
+        .map(|(_, text)| format!("\n{}", comment_lines(text)))
+        .collect();
+    if let Some(value) = last_value
+        .and_then(|key| table.get_mut(&key))
+        .and_then(|item| item.as_value_mut())
+    {
+        if !optional.is_empty() {
+            value
+                .decor_mut()
+                .set_suffix(format!("\n{}", optional.trim_end_matches('\n')));
+        }
     }
 }
 
@@ -330,9 +1935,10 @@ mod tests {
         assert_eq!(config.scan.max_concurrent_requests, 40);
 
         // Verify classification defaults
-        assert_eq!(config.classification.mode, "rules");
+        assert_eq!(config.classification.mode, ClassificationMode::Rules);
         assert_eq!(config.classification.llm_provider, "openai");
         assert_eq!(config.classification.minimum_emails_for_label, 5);
+        assert_eq!(config.classification.auto_merge_threshold, 0.1);
         assert!(!config.classification.claude_agents.enabled);
         assert!(config.classification.claude_agents.use_advanced_analysis);
         assert_eq!(config.classification.claude_agents.max_iterations, 3);
@@ -355,6 +1961,9 @@ mod tests {
 
         // Verify execution defaults
         assert!(!config.execution.dry_run);
+
+        // Verify state defaults
+        assert!(!config.state.compress);
     }
 
     #[test]
@@ -397,6 +2006,78 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_config_validation_checkpoint_interval_zero() {
+        let mut config = Config::default();
+        assert_eq!(config.scan.checkpoint_interval_messages, 1000);
+        config.scan.checkpoint_interval_messages = 0;
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("checkpoint_interval_messages"));
+    }
+
+    #[test]
+    fn test_scan_search_query_labels() {
+        let mut config = Config::default();
+        assert!(!config.scan.search_query().contains("in:"));
+
+        config.scan.include_labels = vec!["INBOX".to_string(), "unread".to_string()];
+        config.scan.exclude_labels = vec!["TRASH".to_string()];
+        assert!(config.validate().is_ok());
+        let query = config.scan.search_query();
+        assert!(query.starts_with("after:"));
+        assert!(query.ends_with(" (in:inbox OR in:unread) -in:trash"));
+
+        config.scan.exclude_labels = vec!["Receipts".to_string()];
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("'Receipts' is not a system label"));
+    }
+
+    #[test]
+    fn test_config_validation_max_messages() {
+        let mut config = Config::default();
+        assert_eq!(config.scan.max_messages, None);
+        config.scan.max_messages = Some(10_000);
+        assert!(config.validate().is_ok());
+        config.scan.max_messages = Some(0);
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("max_messages"));
+    }
+
+    #[test]
+    fn test_circuit_breaker_config() {
+        // The old name for open_duration_secs is still accepted
+        let config: Config = toml::from_str(
+            r#"
+            [circuit_breaker]
+            reset_timeout_secs = 30
+            half_open_max_calls = 3
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.circuit_breaker.open_duration_secs, 30);
+        assert_eq!(config.circuit_breaker.half_open_max_calls, 3);
+        assert_eq!(config.circuit_breaker.success_threshold_to_close, 1);
+
+        let mut config = Config::default();
+        config.circuit_breaker.success_threshold_to_close = 0;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("success_threshold_to_close"));
+    }
+
     #[test]
     fn test_config_validation_max_concurrent_zero() {
         let mut config = Config::default();
@@ -429,29 +2110,77 @@ mod tests {
     }
 
     #[test]
-    fn test_config_validation_invalid_mode() {
-        let mut config = Config::default();
-        config.classification.mode = "invalid".to_string();
-        let result = config.validate();
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("Invalid classification.mode"));
+    fn test_config_invalid_mode() {
+        assert!(toml::from_str::<Config>("[classification]\nmode = \"invalid\"\n").is_err());
     }
 
     #[test]
-    fn test_config_validation_valid_modes() {
-        let mut config = Config::default();
+    fn test_config_valid_modes() {
+        for (name, mode) in [
+            ("rules", ClassificationMode::Rules),
+            ("ml", ClassificationMode::Ml),
+            ("hybrid", ClassificationMode::Hybrid),
+        ] {
+            let config: Config =
+                toml::from_str(&format!("[classification]\nmode = \"{}\"\n", name)).unwrap();
+            assert_eq!(config.classification.mode, mode);
+            assert!(config.validate().is_ok());
+        }
+    }
 
-        config.classification.mode = "rules".to_string();
-        assert!(config.validate().is_ok());
+    #[test]
+    fn test_report_format() {
+        assert_eq!(
+            Config::default().execution.report_format,
+            ReportFormat::Markdown
+        );
+        let config: Config = toml::from_str("[execution]\nreport_format = \"html\"\n").unwrap();
+        assert_eq!(config.execution.report_format, ReportFormat::Html);
+        assert!(toml::from_str::<Config>("[execution]\nreport_format = \"pdf\"\n").is_err());
+    }
 
-        config.classification.mode = "ml".to_string();
+    #[test]
+    fn test_config_ab_test() {
+        let mut config: Config = toml::from_str(
+            "[classification.ab_test]\nexperiment_mode = \"hybrid\"\nexperiment_fraction = 0.25\n",
+        )
+        .unwrap();
+        let ab_test = config.classification.ab_test.as_ref().unwrap();
+        assert_eq!(ab_test.control_mode, ClassificationMode::Rules);
+        assert_eq!(ab_test.experiment_mode, ClassificationMode::Hybrid);
+        assert_eq!(ab_test.log_path, default_ab_test_log_path());
         assert!(config.validate().is_ok());
 
-        config.classification.mode = "hybrid".to_string();
+        config
+            .classification
+            .ab_test
+            .as_mut()
+            .unwrap()
+            .experiment_fraction = 1.5;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("experiment_fraction"));
+    }
+
+    #[test]
+    fn test_config_classification_rules() {
+        let mut config: Config = toml::from_str(
+            "[[classification.rules]]\ndomain = \"shop.example\"\nlabel = \"auto/receipts/shop\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.classification.rules,
+            vec![ClassificationRule {
+                domain: "shop.example".to_string(),
+                label: "auto/receipts/shop".to_string(),
+            }]
+        );
         assert!(config.validate().is_ok());
+
+        config.classification.rules[0].label.clear();
+        assert!(config.validate().is_err());
     }
 
     #[test]
@@ -492,6 +2221,159 @@ mod tests {
             .contains("minimum_emails_for_label must be greater than 0"));
     }
 
+    #[test]
+    fn test_hidden_cluster_categories() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.classification.hidden_cluster_categories(),
+            vec![EmailCategory::AutoReply, EmailCategory::Bounce]
+        );
+
+        let toml = r#"
+            [classification]
+            create_bounce_filters = true
+        "#;
+        config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.classification.hidden_cluster_categories(),
+            vec![EmailCategory::AutoReply]
+        );
+    }
+
+    #[test]
+    fn test_size_boosts() {
+        let mut config = Config::default();
+        assert_eq!(config.classification.active_size_boosts().len(), 1);
+
+        let toml = r#"
+            [classification]
+            use_size_heuristics = true
+
+            [[classification.size_boosts]]
+            min_bytes = 1048576
+            category = "Receipt"
+            boost = 0.1
+        "#;
+        config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.classification.active_size_boosts(),
+            vec![SizeBoost {
+                min_bytes: 1048576,
+                category: EmailCategory::Receipt,
+                boost: 0.1,
+            }]
+        );
+        assert!(config.validate().is_ok());
+
+        config.classification.use_size_heuristics = false;
+        assert!(config.classification.active_size_boosts().is_empty());
+
+        config.classification.size_boosts[0].boost = 1.5;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_min_confidence() {
+        let toml = r#"
+            [classification.min_confidence]
+            newsletter = 0.6
+            Personal = 0.8
+        "#;
+        let mut config: Config = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_ok());
+
+        let thresholds = config.classification.min_confidence_thresholds();
+        assert_eq!(thresholds.len(), 2);
+        assert_eq!(thresholds[&EmailCategory::Newsletter], 0.6);
+        assert_eq!(thresholds[&EmailCategory::Personal], 0.8);
+
+        config
+            .classification
+            .min_confidence
+            .insert("Spam".to_string(), 0.5);
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("unknown category 'Spam'"));
+
+        config.classification.min_confidence.remove("Spam");
+        config
+            .classification
+            .min_confidence
+            .insert("Receipt".to_string(), 1.5);
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("min_confidence.Receipt must be between 0.0 and 1.0"));
+    }
+
+    #[test]
+    fn test_retry_config() {
+        let toml = r#"
+            [client.retry]
+            max_attempts = 3
+            base_delay_ms = 200
+            max_delay_ms = 1000
+            multiplier = 3.0
+            retry_on = ["rate_limit", "server_error_503"]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_ok());
+
+        let retry = &config.client.retry;
+        assert!(retry.should_retry(&GmailError::RateLimitExceeded { retry_after: 1 }));
+        assert!(retry.should_retry(&GmailError::ServerError {
+            status: 503,
+            message: "Service Unavailable".to_string(),
+        }));
+        assert!(!retry.should_retry(&GmailError::ServerError {
+            status: 500,
+            message: "Internal Server Error".to_string(),
+        }));
+        assert!(!retry.should_retry(&GmailError::NetworkError("operation timed out".to_string())));
+        assert!(!retry.should_retry(&GmailError::Forbidden("nope".to_string())));
+
+        assert_eq!(retry.delay(1).as_millis(), 200);
+        assert_eq!(retry.delay(2).as_millis(), 600);
+        assert_eq!(retry.delay(3).as_millis(), 1000);
+
+        assert_eq!(
+            ErrorKind::of(&GmailError::NetworkError("operation timed out".to_string())),
+            Some(ErrorKind::Timeout)
+        );
+        assert_eq!(
+            ErrorKind::of(&GmailError::NetworkError("connection reset".to_string())),
+            Some(ErrorKind::Network)
+        );
+
+        let jittered = RetryConfig {
+            jitter: true,
+            ..RetryConfig::default()
+        };
+        let delay = jittered.delay(1).as_millis();
+        assert!((500..=1000).contains(&delay));
+
+        let mut config = Config::default();
+        config.client.retry.max_attempts = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_validation_auto_merge_threshold() {
+        let mut config = Config::default();
+        config.classification.auto_merge_threshold = 0.0;
+        assert!(config.validate().is_ok());
+
+        config.classification.auto_merge_threshold = 1.5;
+        let result = config.validate();
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("auto_merge_threshold must be between 0.0 and 1.0"));
+    }
+
     #[test]
     fn test_config_validation_max_iterations_zero() {
         let mut config = Config::default();
@@ -540,6 +2422,24 @@ mod tests {
             .contains("auto_archive_categories cannot contain empty strings"));
     }
 
+    #[test]
+    fn test_config_validation_invalid_exclusion_pattern() {
+        let mut config = Config::default();
+        config
+            .exclusions
+            .patterns
+            .push(r"^\*@.*\.example\.com$".to_string());
+        assert!(config.validate().is_ok());
+
+        config.exclusions.patterns.push("news(letter".to_string());
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid exclusion regex"));
+    }
+
     #[tokio::test]
     async fn test_config_serialization_roundtrip() {
         let config = Config::default();
@@ -630,7 +2530,7 @@ dry_run = true
 
         // Check default values are still present
         assert_eq!(config.scan.max_concurrent_requests, 40); // default
-        assert_eq!(config.classification.mode, "rules"); // default
+        assert_eq!(config.classification.mode, ClassificationMode::Rules); // default
         assert_eq!(config.labels.prefix, "AutoManaged"); // default
     }
 
@@ -647,17 +2547,260 @@ dry_run = true
         // Verify it can be loaded
         let config = Config::load(path).await.unwrap();
         assert_eq!(config.scan.period_days, 90);
+        assert_eq!(config.classification.auto_merge_threshold, 0.1);
+
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains(
+            "# Number of days of email history to scan. Increase for larger inboxes. (1-365)\nperiod_days = 90"
+        ));
+        assert!(content.contains("auto_merge_threshold = 0.1\n"));
+        assert!(content.contains("# worker_threads = 4"));
+    }
+
+    #[test]
+    fn test_example_comments_cover_every_setting() {
+        // Children of these are map entries or array items, described by their parent
+        const DYNAMIC: &[&str] = &[
+            "classification.subject_keywords",
+            "classification.min_confidence",
+            "classification.size_boosts",
+            "labels.category_visibility",
+            "labels.attachment_sublabels",
+        ];
+
+        fn collect(table: &toml::Table, path: &str, paths: &mut Vec<String>) {
+            for (key, value) in table {
+                let key_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                paths.push(key_path.clone());
+                if let (toml::Value::Table(subtable), false) =
+                    (value, DYNAMIC.contains(&key_path.as_str()))
+                {
+                    collect(subtable, &key_path, paths);
+                }
+            }
+        }
+
+        let table = toml::Table::try_from(Config::default()).unwrap();
+        let mut paths = Vec::new();
+        collect(&table, "", &mut paths);
+
+        let missing: Vec<&String> = paths
+            .iter()
+            .filter(|path| example_comment(path).is_none())
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "settings without a comment: {:?}",
+            missing
+        );
+
+        for (path, _) in EXAMPLE_COMMENTS {
+            assert!(paths.iter().any(|p| p == path), "unknown setting {}", path);
+        }
     }
 
     #[test]
     fn test_default_functions() {
         assert_eq!(default_period_days(), 90);
         assert_eq!(default_max_concurrent(), 40);
-        assert_eq!(default_mode(), "rules");
+        assert_eq!(ClassificationMode::default(), ClassificationMode::Rules);
         assert_eq!(default_min_emails(), 5);
         assert_eq!(default_prefix(), "AutoManaged");
         assert_eq!(default_llm_provider(), "openai");
         assert!(default_advanced_analysis());
         assert_eq!(default_max_iterations(), 3);
     }
+
+    #[test]
+    fn test_state_encryption_key_from_env() {
+        let mut state = StateConfig::default();
+        assert!(state.encryption_key().unwrap().is_none());
+
+        std::env::set_var("GMAIL_AUTOMATION_TEST_STATE_KEY", "ab".repeat(32));
+        state.encryption_key_env = Some("GMAIL_AUTOMATION_TEST_STATE_KEY".to_string());
+        assert_eq!(state.encryption_key().unwrap(), Some([0xab; 32]));
+    }
+
+    #[test]
+    fn test_state_encryption_key_invalid() {
+        let mut state = StateConfig {
+            encryption_key_env: Some("GMAIL_AUTOMATION_TEST_MISSING_KEY".to_string()),
+            ..Default::default()
+        };
+        assert!(state.encryption_key().is_err());
+
+        std::env::set_var("GMAIL_AUTOMATION_TEST_SHORT_KEY", "abcd");
+        state.encryption_key_env = Some("GMAIL_AUTOMATION_TEST_SHORT_KEY".to_string());
+        let result = state.encryption_key();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("256-bit"));
+    }
+
+    #[test]
+    fn test_label_category_visibility() {
+        let config = Config::default();
+        let newsletter = config.labels.visibility_for(&EmailCategory::Newsletter);
+        assert_eq!(newsletter.label_list, VisibilityMode::Hide);
+        assert_eq!(newsletter.label_list_api_value(), "labelHide");
+        assert_eq!(newsletter.message_list_api_value(), "show");
+        assert_eq!(
+            config.labels.visibility_for(&EmailCategory::Receipt),
+            LabelVisibility::default()
+        );
+
+        let config: Config = toml::from_str(
+            r#"
+            [labels.category_visibility.receipt]
+            message_list = "hide"
+            label_list = "show_if_unread"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        let receipt = config.labels.visibility_for(&EmailCategory::Receipt);
+        assert_eq!(receipt.message_list_api_value(), "hide");
+        assert_eq!(receipt.label_list_api_value(), "labelShowIfUnread");
+
+        let mut config = Config::default();
+        config
+            .labels
+            .category_visibility
+            .insert("Bogus".to_string(), LabelVisibility::default());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_label_template_validation() {
+        let mut config = Config::default();
+        assert_eq!(config.labels.label_template, "{prefix}/{category}/{domain}");
+        assert!(config.validate().is_ok());
+
+        config.labels.label_template = "{prefix}/{year}/{domain}/{sender}".to_string();
+        assert!(config.validate().is_ok());
+
+        config.labels.label_template = "{prefix}/{category}".to_string();
+        assert!(config.validate().is_err());
+
+        config.labels.label_template = "{prefix}/{month}/{domain}".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_subject_keywords_validation() {
+        let mut config = Config::default();
+        config
+            .classification
+            .subject_keywords
+            .insert("newsletter".to_string(), vec!["changelog".to_string()]);
+        assert!(config.validate().is_ok());
+
+        config
+            .classification
+            .subject_keywords
+            .insert("Personal".to_string(), vec!["hi".to_string()]);
+        assert!(config.validate().is_err());
+
+        config.classification.subject_keywords.clear();
+        config
+            .classification
+            .subject_keywords
+            .insert("Marketing".to_string(), vec!["  ".to_string()]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_worker_threads() {
+        let mut config = Config::default();
+        assert!(config.execution.worker_threads.is_none());
+        assert!(config.execution.effective_worker_threads() >= 1);
+
+        config.execution.worker_threads = Some(3);
+        assert_eq!(config.execution.effective_worker_threads(), 3);
+        assert!(config.validate().is_ok());
+
+        config.execution.worker_threads = Some(0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_timezone() {
+        let mut config = Config::default();
+        assert_eq!(config.execution.time_zone().unwrap(), chrono_tz::UTC);
+
+        config.execution.timezone = "America/New_York".to_string();
+        assert_eq!(
+            config.execution.time_zone().unwrap(),
+            chrono_tz::America::New_York
+        );
+        assert!(config.validate().is_ok());
+
+        config.execution.timezone = "Mars/Olympus_Mons".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_ui_config_parsing() {
+        let config: Config = toml::from_str(
+            r#"
+[ui]
+changed_field_color = "bright_magenta"
+box_style = "ascii"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.ui.changed_field_color, AnsiColor::BrightMagenta);
+        assert_eq!(config.ui.changed_field_color.escape_code(), "\x1b[95m");
+        assert_eq!(config.ui.box_style, BoxStyle::Ascii);
+        // Unset fields keep their defaults
+        assert_eq!(config.ui.existing_filter_color, AnsiColor::Yellow);
+
+        assert!(toml::from_str::<Config>("[ui]\nbox_style = \"rounded\"").is_err());
+    }
+
+    #[test]
+    fn test_progress_templates() {
+        let mut config = Config::default();
+        assert!(config.ui.use_unicode_bars);
+        assert!(config.validate().is_ok());
+
+        config.ui.progress_bar_template = Some("{bar:20} {pos}/{len}".to_string());
+        config.ui.spinner_template = Some("{spinner} {msg}".to_string());
+        assert!(config.validate().is_ok());
+
+        config.ui.spinner_template = Some("{spinner:x} {msg}".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("ui.spinner_template"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_watch_config_reloads_on_change() {
+        use futures::StreamExt;
+        use std::time::Duration;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[scan]\nperiod_days = 30\n").unwrap();
+
+        let mut changes = Box::pin(watch_config(&path));
+
+        std::fs::write(&path, "[scan]\nperiod_days = 60\n").unwrap();
+        let config = tokio::time::timeout(Duration::from_secs(5), changes.next())
+            .await
+            .expect("no reload after a valid change")
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.scan.period_days, 60);
+
+        std::fs::write(&path, "[scan]\nperiod_days = 0\n").unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(5), changes.next())
+            .await
+            .expect("no reload after an invalid change")
+            .unwrap();
+        assert!(result.is_err());
+    }
 }
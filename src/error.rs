@@ -83,36 +83,84 @@ pub enum GmailError {
     QuotaExceeded(String),
 
     /// Circuit breaker is open (rejecting requests)
-    #[error("Circuit breaker open: {message}. Will retry after {retry_after_secs} seconds")]
-    CircuitBreakerOpen {
-        message: String,
-        retry_after_secs: u64,
+    #[error(
+        "Circuit breaker open, requests resume after {}",
+        retry_after.with_timezone(&chrono::Local).format("%H:%M:%S")
+    )]
+    CircuitOpen {
+        retry_after: chrono::DateTime<chrono::Utc>,
     },
 
     /// Generic catch-all error
     #[error("Unknown error: {0}")]
     Unknown(String),
+
+    /// Another error with a description of what was being done when it occurred
+    #[error("{message}")]
+    Context {
+        message: String,
+        #[source]
+        source: Box<GmailError>,
+    },
 }
 
 impl GmailError {
     /// Check if the error is transient and should be retried
     pub fn is_transient(&self) -> bool {
         matches!(
-            self,
+            self.root_cause(),
             GmailError::RateLimitExceeded { .. }
                 | GmailError::RateLimitError(_)
                 | GmailError::ServerError { .. }
                 | GmailError::NetworkError(_)
-                | GmailError::CircuitBreakerOpen { .. }
+                | GmailError::CircuitOpen { .. }
         )
     }
 
+    /// Wrap this error with a description of what was being done, e.g. "Creating filter 'x'"
+    pub fn with_context(self, ctx: impl Into<String>) -> Self {
+        GmailError::Context {
+            message: ctx.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// The innermost error, below any added context
+    pub fn root_cause(&self) -> &GmailError {
+        match self {
+            GmailError::Context { source, .. } => source.root_cause(),
+            other => other,
+        }
+    }
+
+    /// All messages from the outermost context down to the root cause, joined with ": "
+    pub fn chain_message(&self) -> String {
+        match self {
+            GmailError::Context { message, source } => {
+                format!("{}: {}", message, source.chain_message())
+            }
+            other => other.to_string(),
+        }
+    }
+
     /// Check if the error is permanent and should not be retried
     pub fn is_permanent(&self) -> bool {
         !self.is_transient()
     }
 }
 
+/// Add context to the error of a `Result`, like `anyhow::Context`
+pub trait Context<T> {
+    /// Wrap the error with `ctx` (see `GmailError::with_context`)
+    fn context(self, ctx: impl Into<String>) -> Result<T>;
+}
+
+impl<T> Context<T> for Result<T> {
+    fn context(self, ctx: impl Into<String>) -> Result<T> {
+        self.map_err(|e| e.with_context(ctx))
+    }
+}
+
 /// Parse the Retry-After header from an HTTP response
 ///
 /// The Retry-After header can be specified in two formats:
@@ -226,6 +274,32 @@ mod tests {
         assert!(forbidden.is_permanent());
     }
 
+    #[test]
+    fn test_error_context() {
+        let result: Result<()> = Err(GmailError::ServerError {
+            status: 503,
+            message: "Service unavailable".to_string(),
+        });
+        let error = result
+            .context("Creating filter 'GitHub'")
+            .context("Applying review decisions")
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Applying review decisions");
+        assert_eq!(
+            error.chain_message(),
+            "Applying review decisions: Creating filter 'GitHub': Server error (HTTP 503): Service unavailable"
+        );
+        assert!(matches!(
+            error.root_cause(),
+            GmailError::ServerError { status: 503, .. }
+        ));
+        assert!(error.is_transient());
+
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), "Creating filter 'GitHub'");
+    }
+
     #[test]
     fn test_error_display() {
         let error = GmailError::RateLimitExceeded { retry_after: 10 };
@@ -291,7 +365,7 @@ mod tests {
         let retry_after = parse_retry_after_header(&response);
         // Should be close to 60 seconds (allowing for some test execution time)
         assert!(
-            retry_after >= 59 && retry_after <= 61,
+            (59..=61).contains(&retry_after),
             "Expected ~60, got {}",
             retry_after
         );
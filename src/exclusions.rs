@@ -1,24 +1,41 @@
 //! Persistent exclusions for email clusters
 //!
-//! Allows users to permanently exclude certain clusters from review.
+//! Allows users to exclude certain clusters from review, either permanently
+//! or until an expiry date.
 //! Exclusions are saved to `.gmail-automation/exclusions.json` and
 //! persist across runs.
 
 use crate::error::{GmailError, Result};
 use chrono::{DateTime, Utc};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::Path;
 
 /// A persistent exclusion for a cluster
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Exclusion {
+pub struct ExclusionEntry {
     /// The cluster key (e.g., "*@domain.com" or "email@domain.com|subject:Pattern")
-    pub cluster_key: String,
-    /// When this exclusion was created
-    pub created_at: DateTime<Utc>,
+    #[serde(alias = "cluster_key")]
+    pub key: String,
     /// Optional reason for the exclusion
     pub reason: Option<String>,
+    /// When this exclusion was created
+    #[serde(alias = "created_at")]
+    pub added_at: DateTime<Utc>,
+    /// When this exclusion stops applying (`None` = never expires)
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether `key` is a regex matched against cluster keys rather than an exact key
+    #[serde(default)]
+    pub is_pattern: bool,
+}
+
+impl ExclusionEntry {
+    /// Check whether this entry has expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.map(|at| at < Utc::now()).unwrap_or(false)
+    }
 }
 
 /// Manager for persistent exclusions
@@ -26,8 +43,10 @@ pub struct Exclusion {
 pub struct ExclusionManager {
     /// Set of excluded cluster keys for fast lookup
     excluded_keys: HashSet<String>,
+    /// Compiled regex patterns, checked after the exact keys
+    patterns: Vec<Regex>,
     /// Full exclusion records (for saving)
-    exclusions: Vec<Exclusion>,
+    exclusions: Vec<ExclusionEntry>,
 }
 
 impl ExclusionManager {
@@ -35,6 +54,7 @@ impl ExclusionManager {
     pub fn new() -> Self {
         Self {
             excluded_keys: HashSet::new(),
+            patterns: Vec::new(),
             exclusions: Vec::new(),
         }
     }
@@ -46,16 +66,19 @@ impl ExclusionManager {
         }
 
         let json = tokio::fs::read_to_string(path).await?;
-        let exclusions: Vec<Exclusion> = serde_json::from_str(&json)
-            .map_err(|e| GmailError::Unknown(format!("Failed to parse exclusions file: {}", e)))?;
+        let mut manager = Self::from_json(&json)?;
+        manager.prune_expired();
+        Ok(manager)
+    }
 
-        let excluded_keys: HashSet<String> =
-            exclusions.iter().map(|e| e.cluster_key.clone()).collect();
+    /// Load exclusions without dropping the ones that have expired, e.g. to list or prune them
+    pub async fn load_with_expired(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
 
-        Ok(Self {
-            excluded_keys,
-            exclusions,
-        })
+        let json = tokio::fs::read_to_string(path).await?;
+        Self::from_json(&json)
     }
 
     /// Load exclusions synchronously (for use in non-async contexts)
@@ -66,14 +89,37 @@ impl ExclusionManager {
 
         let json = std::fs::read_to_string(path)
             .map_err(|e| GmailError::Unknown(format!("Failed to read exclusions file: {}", e)))?;
-        let exclusions: Vec<Exclusion> = serde_json::from_str(&json)
+        let mut manager = Self::from_json(&json)?;
+        manager.prune_expired();
+        Ok(manager)
+    }
+
+    /// Build a manager from serialized exclusions, keeping the ones that have expired
+    fn from_json(json: &str) -> Result<Self> {
+        let exclusions: Vec<ExclusionEntry> = serde_json::from_str(json)
             .map_err(|e| GmailError::Unknown(format!("Failed to parse exclusions file: {}", e)))?;
+        Self::build(exclusions)
+    }
 
-        let excluded_keys: HashSet<String> =
-            exclusions.iter().map(|e| e.cluster_key.clone()).collect();
+    /// Build a manager from exclusion entries, dropping entries that have already expired
+    fn from_entries(exclusions: Vec<ExclusionEntry>) -> Result<Self> {
+        let mut manager = Self::build(exclusions)?;
+        manager.prune_expired();
+        Ok(manager)
+    }
 
+    fn build(exclusions: Vec<ExclusionEntry>) -> Result<Self> {
         Ok(Self {
-            excluded_keys,
+            excluded_keys: exclusions
+                .iter()
+                .filter(|e| !e.is_pattern)
+                .map(|e| e.key.clone())
+                .collect(),
+            patterns: exclusions
+                .iter()
+                .filter(|e| e.is_pattern)
+                .map(|e| compile_pattern(&e.key))
+                .collect::<Result<_>>()?,
             exclusions,
         })
     }
@@ -106,23 +152,190 @@ impl ExclusionManager {
         Ok(())
     }
 
-    /// Add an exclusion
+    /// Add an exclusion that never expires
     pub fn add(&mut self, cluster_key: String, reason: Option<String>) {
+        self.add_with_expiry(cluster_key, reason, None);
+    }
+
+    /// Add an exclusion that stops applying at `expires_at`
+    pub fn add_with_expiry(
+        &mut self,
+        cluster_key: String,
+        reason: Option<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) {
         if self.excluded_keys.contains(&cluster_key) {
             return; // Already excluded
         }
 
         self.excluded_keys.insert(cluster_key.clone());
-        self.exclusions.push(Exclusion {
-            cluster_key,
-            created_at: Utc::now(),
+        self.exclusions.push(ExclusionEntry {
+            key: cluster_key,
+            reason,
+            added_at: Utc::now(),
+            expires_at,
+            is_pattern: false,
+        });
+    }
+
+    /// Add a regex exclusion matched against cluster keys
+    pub fn add_pattern(&mut self, regex_str: &str, reason: Option<String>) -> Result<()> {
+        self.add_pattern_with_expiry(regex_str, reason, None)
+    }
+
+    /// Add a regex exclusion that stops applying at `expires_at`
+    pub fn add_pattern_with_expiry(
+        &mut self,
+        regex_str: &str,
+        reason: Option<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let regex = compile_pattern(regex_str)?;
+        if self.patterns.iter().any(|re| re.as_str() == regex_str) {
+            return Ok(()); // Already excluded
+        }
+
+        self.patterns.push(regex);
+        self.exclusions.push(ExclusionEntry {
+            key: regex_str.to_string(),
             reason,
+            added_at: Utc::now(),
+            expires_at,
+            is_pattern: true,
         });
+        Ok(())
+    }
+
+    /// Check if a cluster key is excluded, removing any matching entry that has expired.
+    ///
+    /// Exact keys are checked first, then each regex pattern in turn.
+    pub fn is_excluded(&mut self, cluster_key: &str) -> bool {
+        if self.excluded_keys.contains(cluster_key) {
+            if !self.is_entry_expired(cluster_key, false) {
+                return true;
+            }
+            self.remove_entry(cluster_key, false);
+        }
+
+        let matching: Vec<String> = self
+            .patterns
+            .iter()
+            .filter(|re| re.is_match(cluster_key))
+            .map(|re| re.as_str().to_string())
+            .collect();
+        for pattern in matching {
+            if !self.is_entry_expired(&pattern, true) {
+                return true;
+            }
+            self.remove_entry(&pattern, true);
+        }
+        false
+    }
+
+    /// Remove the exclusion (exact key or pattern) with this key, returning whether one existed
+    pub fn remove(&mut self, key: &str) -> bool {
+        let before = self.exclusions.len();
+        self.exclusions.retain(|e| e.key != key);
+        self.excluded_keys.remove(key);
+        self.patterns.retain(|re| re.as_str() != key);
+        self.exclusions.len() != before
+    }
+
+    /// Remove all exclusions
+    pub fn clear(&mut self) {
+        self.excluded_keys.clear();
+        self.patterns.clear();
+        self.exclusions.clear();
     }
 
-    /// Check if a cluster key is excluded
-    pub fn is_excluded(&self, cluster_key: &str) -> bool {
-        self.excluded_keys.contains(cluster_key)
+    /// Write the full exclusion list to `path` as JSON, returning the number of entries written
+    pub fn export_json(&self, path: &Path) -> Result<usize> {
+        self.save_sync(path)?;
+        Ok(self.exclusions.len())
+    }
+
+    /// Import exclusions from a JSON file written by `export_json`.
+    ///
+    /// With `merge = false` the imported list replaces the current one. With `merge = true`
+    /// the lists are unioned; when both contain the same key, the longer-lasting expiry wins
+    /// (an exclusion that never expires beats any dated one). Returns the number of entries read.
+    pub fn import_json(&mut self, path: &Path, merge: bool) -> Result<usize> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| GmailError::Unknown(format!("Failed to read exclusions file: {}", e)))?;
+        let entries: Vec<ExclusionEntry> = serde_json::from_str(&json)
+            .map_err(|e| GmailError::Unknown(format!("Failed to parse exclusions file: {}", e)))?;
+
+        for entry in &entries {
+            if entry.key.trim().is_empty() {
+                return Err(GmailError::Unknown(
+                    "Exclusions file contains an entry with an empty key".to_string(),
+                ));
+            }
+            if entry.is_pattern {
+                compile_pattern(&entry.key)?;
+            }
+        }
+        let count = entries.len();
+
+        if !merge {
+            *self = Self::from_entries(entries)?;
+            return Ok(count);
+        }
+
+        for entry in entries {
+            let existing = self
+                .exclusions
+                .iter_mut()
+                .find(|e| e.key == entry.key && e.is_pattern == entry.is_pattern);
+            match existing {
+                Some(existing) => {
+                    existing.expires_at = match (existing.expires_at, entry.expires_at) {
+                        (Some(a), Some(b)) => Some(a.max(b)),
+                        _ => None,
+                    };
+                }
+                None => {
+                    if entry.is_pattern {
+                        self.patterns.push(compile_pattern(&entry.key)?);
+                    } else {
+                        self.excluded_keys.insert(entry.key.clone());
+                    }
+                    self.exclusions.push(entry);
+                }
+            }
+        }
+        self.prune_expired();
+        Ok(count)
+    }
+
+    /// Remove all expired exclusions, returning the number removed
+    pub fn prune_expired(&mut self) -> usize {
+        let expired: Vec<(String, bool)> = self
+            .exclusions
+            .iter()
+            .filter(|e| e.is_expired())
+            .map(|e| (e.key.clone(), e.is_pattern))
+            .collect();
+        for (key, is_pattern) in &expired {
+            self.remove_entry(key, *is_pattern);
+        }
+        expired.len()
+    }
+
+    fn is_entry_expired(&self, key: &str, is_pattern: bool) -> bool {
+        self.exclusions
+            .iter()
+            .any(|e| e.key == key && e.is_pattern == is_pattern && e.is_expired())
+    }
+
+    fn remove_entry(&mut self, key: &str, is_pattern: bool) {
+        self.exclusions
+            .retain(|e| !(e.key == key && e.is_pattern == is_pattern));
+        if is_pattern {
+            self.patterns.retain(|re| re.as_str() != key);
+        } else {
+            self.excluded_keys.remove(key);
+        }
     }
 
     /// Get the number of exclusions
@@ -136,11 +349,18 @@ impl ExclusionManager {
     }
 
     /// Get all exclusions
-    pub fn exclusions(&self) -> &[Exclusion] {
+    pub fn exclusions(&self) -> &[ExclusionEntry] {
         &self.exclusions
     }
 }
 
+/// Compile an exclusion regex, reporting failures as configuration errors
+fn compile_pattern(regex_str: &str) -> Result<Regex> {
+    Regex::new(regex_str).map_err(|e| {
+        GmailError::ConfigError(format!("Invalid exclusion regex: '{}': {}", regex_str, e))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -148,7 +368,7 @@ mod tests {
 
     #[test]
     fn test_exclusion_manager_new() {
-        let manager = ExclusionManager::new();
+        let mut manager = ExclusionManager::new();
         assert!(manager.is_empty());
         assert!(!manager.is_excluded("test@example.com"));
     }
@@ -183,7 +403,7 @@ mod tests {
 
         manager.save(&path).await.unwrap();
 
-        let loaded = ExclusionManager::load(&path).await.unwrap();
+        let mut loaded = ExclusionManager::load(&path).await.unwrap();
         assert_eq!(loaded.len(), 2);
         assert!(loaded.is_excluded("*@example.com"));
         assert!(loaded.is_excluded("specific@test.com"));
@@ -195,4 +415,230 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
     }
+
+    #[test]
+    fn test_exclusion_manager_expired_entry_removed() {
+        let mut manager = ExclusionManager::new();
+        manager.add_with_expiry(
+            "*@expired.com".to_string(),
+            None,
+            Some(Utc::now() - chrono::Duration::days(1)),
+        );
+        manager.add_with_expiry(
+            "*@active.com".to_string(),
+            None,
+            Some(Utc::now() + chrono::Duration::days(7)),
+        );
+
+        assert!(!manager.is_excluded("*@expired.com"));
+        assert!(manager.is_excluded("*@active.com"));
+        assert_eq!(manager.len(), 1);
+    }
+
+    #[test]
+    fn test_exclusion_manager_prune_expired() {
+        let mut manager = ExclusionManager::new();
+        let yesterday = Some(Utc::now() - chrono::Duration::days(1));
+        manager.add_with_expiry("*@a.com".to_string(), None, yesterday);
+        manager.add_with_expiry("*@b.com".to_string(), None, yesterday);
+        manager.add("*@forever.com".to_string(), None);
+
+        assert_eq!(manager.prune_expired(), 2);
+        assert_eq!(manager.len(), 1);
+        assert!(manager.is_excluded("*@forever.com"));
+    }
+
+    #[tokio::test]
+    async fn test_exclusion_manager_load_with_expired() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("exclusions.json");
+        let yesterday = Some(Utc::now() - chrono::Duration::days(1));
+
+        let mut manager = ExclusionManager::new();
+        manager.add_with_expiry("*@old.com".to_string(), None, yesterday);
+        manager
+            .add_pattern_with_expiry(
+                r"@.*\.example$",
+                None,
+                Some(Utc::now() + chrono::Duration::days(7)),
+            )
+            .unwrap();
+        manager.save(&path).await.unwrap();
+
+        assert_eq!(ExclusionManager::load(&path).await.unwrap().len(), 1);
+
+        let mut all = ExclusionManager::load_with_expired(&path).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(all.exclusions()[1].expires_at.is_some());
+        assert_eq!(all.prune_expired(), 1);
+        assert!(all.is_excluded("news@mail.example"));
+    }
+
+    #[test]
+    fn test_exclusion_manager_loads_legacy_format() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("exclusions.json");
+        std::fs::write(
+            &path,
+            r#"[{"cluster_key":"*@old.com","created_at":"2024-01-01T00:00:00Z","reason":null}]"#,
+        )
+        .unwrap();
+
+        let mut loaded = ExclusionManager::load_sync(&path).unwrap();
+        assert!(loaded.is_excluded("*@old.com"));
+        assert!(loaded.exclusions()[0].expires_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_exclusion_manager_load_with_expired_legacy_format() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("exclusions.json");
+        std::fs::write(
+            &path,
+            r#"[{"cluster_key":"*@old.com","created_at":"2024-01-01T00:00:00Z","reason":"noisy"}]"#,
+        )
+        .unwrap();
+
+        let mut loaded = ExclusionManager::load_with_expired(&path).await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.exclusions()[0].reason.as_deref(), Some("noisy"));
+        assert!(loaded.exclusions()[0].expires_at.is_none());
+        assert!(loaded.is_excluded("*@old.com"));
+    }
+
+    #[test]
+    fn test_exclusion_manager_pattern_wildcard_domain() {
+        let mut manager = ExclusionManager::new();
+        manager
+            .add_pattern(r"^\*@.*\.example\.com$", Some("All subdomains".to_string()))
+            .unwrap();
+
+        assert!(manager.is_excluded("*@mail.example.com"));
+        assert!(manager.is_excluded("*@news.example.com"));
+        assert!(!manager.is_excluded("*@example.org"));
+        assert!(!manager.is_excluded("user@mail.example.com"));
+    }
+
+    #[test]
+    fn test_exclusion_manager_pattern_unanchored() {
+        let mut manager = ExclusionManager::new();
+        manager.add_pattern("noreply", None).unwrap();
+
+        assert!(manager.is_excluded("noreply@github.com"));
+        assert!(manager.is_excluded("alerts-noreply@bank.com|subject:Statement"));
+        assert!(!manager.is_excluded("*@github.com"));
+    }
+
+    #[test]
+    fn test_exclusion_manager_invalid_pattern() {
+        let mut manager = ExclusionManager::new();
+        let result = manager.add_pattern("news(letter", None);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid exclusion regex"));
+        assert!(manager.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_exclusion_manager_pattern_save_load() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("exclusions.json");
+
+        let mut manager = ExclusionManager::new();
+        manager.add("*@example.com".to_string(), None);
+        manager.add_pattern(r"@promo\.", None).unwrap();
+        manager.save(&path).await.unwrap();
+
+        let mut loaded = ExclusionManager::load(&path).await.unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.is_excluded("*@example.com"));
+        assert!(loaded.is_excluded("deals@promo.shop.com"));
+    }
+
+    #[test]
+    fn test_exclusion_manager_remove_and_clear() {
+        let mut manager = ExclusionManager::new();
+        manager.add("*@example.com".to_string(), None);
+        manager.add_pattern("noreply", None).unwrap();
+
+        assert!(manager.remove("noreply"));
+        assert!(!manager.remove("noreply"));
+        assert!(!manager.is_excluded("noreply@example.org"));
+        assert_eq!(manager.len(), 1);
+
+        manager.clear();
+        assert!(manager.is_empty());
+        assert!(!manager.is_excluded("*@example.com"));
+    }
+
+    #[test]
+    fn test_exclusion_manager_import_replace() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("shared.json");
+
+        let mut shared = ExclusionManager::new();
+        shared.add("*@shared.com".to_string(), None);
+        shared.add_pattern(r"@promo\.", None).unwrap();
+        assert_eq!(shared.export_json(&path).unwrap(), 2);
+
+        let mut manager = ExclusionManager::new();
+        manager.add("*@local.com".to_string(), None);
+        assert_eq!(manager.import_json(&path, false).unwrap(), 2);
+
+        assert_eq!(manager.len(), 2);
+        assert!(!manager.is_excluded("*@local.com"));
+        assert!(manager.is_excluded("*@shared.com"));
+        assert!(manager.is_excluded("deals@promo.shop.com"));
+    }
+
+    #[test]
+    fn test_exclusion_manager_import_merge_keeps_longest_expiry() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("shared.json");
+        let in_7_days = Utc::now() + chrono::Duration::days(7);
+        let in_30_days = Utc::now() + chrono::Duration::days(30);
+
+        let mut shared = ExclusionManager::new();
+        shared.add_with_expiry("*@a.com".to_string(), None, Some(in_30_days));
+        shared.add_with_expiry("*@b.com".to_string(), None, Some(in_7_days));
+        shared.add("*@c.com".to_string(), None);
+        shared.export_json(&path).unwrap();
+
+        let mut manager = ExclusionManager::new();
+        manager.add_with_expiry("*@a.com".to_string(), None, Some(in_7_days));
+        manager.add("*@b.com".to_string(), None);
+        manager.add("*@local.com".to_string(), None);
+        manager.import_json(&path, true).unwrap();
+
+        assert_eq!(manager.len(), 4);
+        let expiry = |key: &str| {
+            manager
+                .exclusions()
+                .iter()
+                .find(|e| e.key == key)
+                .unwrap()
+                .expires_at
+        };
+        assert_eq!(expiry("*@a.com"), Some(in_30_days));
+        assert_eq!(expiry("*@b.com"), None);
+        assert_eq!(expiry("*@c.com"), None);
+    }
+
+    #[test]
+    fn test_exclusion_manager_import_rejects_invalid_pattern() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("shared.json");
+        std::fs::write(
+            &path,
+            r#"[{"key":"news(letter","reason":null,"added_at":"2024-01-01T00:00:00Z","is_pattern":true}]"#,
+        )
+        .unwrap();
+
+        let mut manager = ExclusionManager::new();
+        manager.add("*@local.com".to_string(), None);
+        assert!(manager.import_json(&path, false).is_err());
+        assert!(manager.is_excluded("*@local.com"));
+    }
 }
@@ -1,10 +1,19 @@
 //! Filter rule management with generation, deduplication, and retroactive application
 use crate::client::GmailClient;
+use crate::config::FilterSortOrder;
 use crate::error::{GmailError, Result};
-use crate::models::{Classification, EmailCategory, FilterRule, MessageMetadata};
-use std::collections::{HashMap, HashSet};
+use crate::models::{
+    Classification, EmailCategory, FilterRule, LabelName, MessageMetadata, DEFAULT_FILTER_PRIORITY,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
 use tracing::{debug, info, warn};
 
+/// Messages counted by `FilterManager::estimate_filter_matches` before it stops
+pub const ESTIMATE_LIMIT: usize = 500;
+
 /// Manages Gmail filters including generation, creation, and deduplication
 pub struct FilterManager {
     client: Box<dyn GmailClient>,
@@ -49,6 +58,7 @@ impl FilterManager {
     /// # Arguments
     /// * `classifications` - List of classified messages
     /// * `min_threshold` - Minimum message count to create dedicated filter
+    /// * `idf_index` - Subject word weights from [`FilterManager::build_idf_index`]
     ///
     /// # Returns
     /// * Vector of FilterRule objects ready for creation
@@ -56,6 +66,7 @@ impl FilterManager {
         &self,
         classifications: &[(MessageMetadata, Classification)],
         min_threshold: usize,
+        idf_index: &HashMap<String, f64>,
     ) -> Vec<FilterRule> {
         info!(
             "Generating filters from {} classifications (threshold: {})",
@@ -86,10 +97,9 @@ impl FilterManager {
             }
 
             // Analyze patterns in this domain
-            let pattern_analysis = self.analyze_domain_patterns(&domain, &messages);
+            let pattern_analysis = self.analyze_domain_patterns(&domain, &messages, idf_index);
 
-            // Determine category and label (use most common from user's choices)
-            let category = self.determine_dominant_category(&messages);
+            // Determine label (use most common from user's choices)
             let target_label = self.determine_dominant_label(&messages);
 
             // Skip domains where user rejected (empty label means no filter wanted)
@@ -106,12 +116,14 @@ impl FilterManager {
             if let Some(filter) = self.build_filter_rule(
                 domain,
                 pattern_analysis,
-                category,
                 target_label,
                 should_archive,
                 messages.len(),
             ) {
+                let attachment_filters =
+                    Self::attachment_filters(&filter, messages.iter().map(|(_, c)| c));
                 filters.push(filter);
+                filters.extend(attachment_filters);
             }
         }
 
@@ -122,6 +134,47 @@ impl FilterManager {
         deduplicated
     }
 
+    /// Filters that file the messages of `filter` with attachments under a sublabel
+    ///
+    /// `classifications` are those of the messages `filter` was built from. Each
+    /// attachment sublabel among them (see `labels.attachment_sublabels`) gets a
+    /// copy of `filter` narrowed to `has:attachment filename:<ext>` that applies
+    /// `<label>/<sublabel>`, so the sender's other messages keep the plain label.
+    pub fn attachment_filters<'a>(
+        filter: &FilterRule,
+        classifications: impl IntoIterator<Item = &'a Classification>,
+    ) -> Vec<FilterRule> {
+        let mut counts: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+        for classification in classifications {
+            let Some((mime_type, sublabel)) = classification.attachment_sublabel() else {
+                continue;
+            };
+            match attachment_extension(mime_type) {
+                Some(extension) => *counts.entry((extension, sublabel)).or_default() += 1,
+                None => debug!("No file extension known for {} attachments", mime_type),
+            }
+        }
+
+        counts
+            .into_iter()
+            .map(|((extension, sublabel), count)| {
+                let mut rule = FilterRule {
+                    id: None,
+                    target_label: LabelName::new(format!("{}/{}", filter.target_label, sublabel)),
+                    target_label_id: None,
+                    estimated_matches: count,
+                    additional_labels: vec![],
+                    additional_label_ids: vec![],
+                    ..filter.clone()
+                };
+                rule.extra_terms.push("has:attachment".to_string());
+                rule.extra_terms.push(format!("filename:{}", extension));
+                rule.name = rule.auto_name();
+                rule
+            })
+            .collect()
+    }
+
     /// Generates filters from raw message metadata (without classifications)
     ///
     /// Useful for simpler pattern-based filter generation
@@ -146,6 +199,7 @@ impl FilterManager {
                 .push(message);
         }
 
+        let idf_index = Self::build_idf_index(messages);
         let mut filters = Vec::new();
 
         // Generate filters for high-volume senders
@@ -158,7 +212,7 @@ impl FilterManager {
             let category = self.infer_category_from_messages(&msgs);
 
             // Analyze patterns
-            let pattern_analysis = self.analyze_domain_patterns_simple(&domain, &msgs);
+            let pattern_analysis = self.analyze_domain_patterns_simple(&domain, &msgs, &idf_index);
 
             // Determine should_archive based on category
             let should_archive = matches!(
@@ -170,7 +224,6 @@ impl FilterManager {
             if let Some(filter) = self.build_filter_rule(
                 domain,
                 pattern_analysis,
-                category,
                 String::new(),
                 should_archive,
                 msgs.len(),
@@ -182,6 +235,37 @@ impl FilterManager {
         self.deduplicate_filters(filters)
     }
 
+    /// Inverse document frequency of each subject keyword, treating each sender
+    /// domain's subjects as one document
+    ///
+    /// Words used by every domain (e.g. "your") weigh 0; words used by a single
+    /// domain weigh the most.
+    pub fn build_idf_index<'a, I>(all_messages: I) -> HashMap<String, f64>
+    where
+        I: IntoIterator<Item = &'a MessageMetadata>,
+    {
+        let mut domain_words: HashMap<&str, HashSet<String>> = HashMap::new();
+        for msg in all_messages {
+            domain_words
+                .entry(msg.sender_domain.as_str())
+                .or_default()
+                .extend(Self::extract_subject_keywords(&msg.subject));
+        }
+
+        let mut document_frequency: HashMap<String, usize> = HashMap::new();
+        for words in domain_words.values() {
+            for word in words {
+                *document_frequency.entry(word.clone()).or_default() += 1;
+            }
+        }
+
+        let domains = domain_words.len() as f64;
+        document_frequency
+            .into_iter()
+            .map(|(word, count)| (word, (domains / count as f64).ln()))
+            .collect()
+    }
+
     /// Creates a filter in Gmail
     ///
     /// Validates the filter before creation and tracks created filters
@@ -209,16 +293,29 @@ impl FilterManager {
     /// - Gmail query syntax is valid
     pub fn validate_filter(&self, filter: &FilterRule) -> Result<()> {
         // Must have some criteria
-        if filter.from_pattern.is_none() && filter.subject_keywords.is_empty() {
+        if filter.from_pattern.is_none()
+            && filter.subject_keywords.is_empty()
+            && filter.extra_terms.is_empty()
+        {
             return Err(GmailError::ConfigError(
-                "Filter must have from_pattern or subject_keywords".to_string(),
+                "Filter must have from_pattern, subject_keywords or extra_terms".to_string(),
             ));
         }
 
         // Must have target label
-        if filter.target_label_id.is_empty() {
+        if filter.target_label.is_empty() {
             return Err(GmailError::ConfigError(
-                "Filter must have target_label_id".to_string(),
+                "Filter must have target_label".to_string(),
+            ));
+        }
+
+        if filter
+            .additional_labels
+            .iter()
+            .any(|label| label.is_empty())
+        {
+            return Err(GmailError::ConfigError(
+                "Filter additional_labels must not contain empty labels".to_string(),
             ));
         }
 
@@ -242,6 +339,7 @@ impl FilterManager {
     /// - `from:(*@github.com)` - All emails from github.com domain
     /// - `from:(noreply@company.com) subject:(newsletter)` - Specific sender with subject
     /// - `subject:(receipt OR invoice OR order)` - Multiple subject keywords
+    /// - `subject:("order (shipped)")` - Keywords that aren't a single word are quoted
     pub fn build_gmail_query_static(filter: &FilterRule) -> String {
         let mut query_parts = Vec::new();
 
@@ -254,11 +352,14 @@ impl FilterManager {
                 // Domain-wide pattern: *@domain.com or @domain.com
                 let domain = from_pattern.trim_start_matches('*');
                 query_parts.push(format!("from:(*{})", domain));
+            }
+        }
 
-                // Add exclusions for specific senders that have their own filters
-                for excluded in &filter.excluded_senders {
-                    query_parts.push(format!("-from:({})", excluded));
-                }
+        // Add exclusions for specific senders that have their own filters
+        // (a mailing list filter has no from pattern but still excludes them)
+        if !filter.is_specific_sender {
+            for excluded in &filter.excluded_senders {
+                query_parts.push(format!("-from:({})", excluded));
             }
         }
 
@@ -268,18 +369,20 @@ impl FilterManager {
             // If there's only one keyword, use it directly (for exact subject matches)
             // If multiple, join with OR for broader matching
             let keywords = if filter.subject_keywords.len() == 1 {
-                filter.subject_keywords[0].clone()
+                subject_search_term(&filter.subject_keywords[0])
             } else {
                 filter
                     .subject_keywords
                     .iter()
-                    .map(|k| k.to_lowercase())
+                    .map(|k| subject_search_term(&k.to_lowercase()))
                     .collect::<Vec<_>>()
                     .join(" OR ")
             };
             query_parts.push(format!("subject:({})", keywords));
         }
 
+        query_parts.extend(filter.extra_terms.iter().cloned());
+
         query_parts.join(" ")
     }
 
@@ -290,6 +393,26 @@ impl FilterManager {
         Self::build_gmail_query_static(filter)
     }
 
+    /// Sorts filters into the order they should be created in Gmail
+    ///
+    /// Ties are broken by filter name so the creation order is deterministic
+    /// regardless of the order filters were generated in.
+    pub fn sort_for_creation(filters: &mut [FilterRule], order: FilterSortOrder) {
+        match order {
+            FilterSortOrder::ByPriority => filters.sort_by(|a, b| {
+                a.priority
+                    .cmp(&b.priority)
+                    .then_with(|| a.name.cmp(&b.name))
+            }),
+            FilterSortOrder::ByEstimatedMatches => filters.sort_by(|a, b| {
+                b.estimated_matches
+                    .cmp(&a.estimated_matches)
+                    .then_with(|| a.priority.cmp(&b.priority))
+                    .then_with(|| a.name.cmp(&b.name))
+            }),
+        }
+    }
+
     /// Deduplicates filters to prevent overlapping rules
     ///
     /// Logic:
@@ -309,7 +432,7 @@ impl FilterManager {
                 filter.subject_keywords.join(","),
                 filter.excluded_senders.join(","),
                 filter.should_archive,
-                filter.target_label_id
+                filter.target_label
             );
 
             // Check if we've seen this exact pattern
@@ -382,10 +505,11 @@ impl FilterManager {
             );
 
             if !dry_run && !message_ids.is_empty() {
+                let label_ids = filter.label_ids()?;
                 // Apply label to all matching messages in batch
                 match self
                     .client
-                    .batch_add_label(&message_ids, &filter.target_label_id)
+                    .batch_add_label(&message_ids, &label_ids[0])
                     .await
                 {
                     Ok(count) => {
@@ -450,6 +574,15 @@ impl FilterManager {
         filters: Vec<FilterRule>,
         dry_run: bool,
     ) -> Result<HashMap<String, std::result::Result<String, String>>> {
+        // Results are keyed by name, so a duplicate would hide another filter's result
+        let duplicates = Self::check_name_uniqueness(&filters);
+        if !duplicates.is_empty() {
+            return Err(GmailError::ConfigError(format!(
+                "Duplicate filter names: {}",
+                duplicates.join(", ")
+            )));
+        }
+
         let total = filters.len();
         let mut results: HashMap<String, std::result::Result<String, String>> = HashMap::new();
         let mut success_count = 0;
@@ -506,31 +639,47 @@ impl FilterManager {
         Ok(results)
     }
 
+    /// Names used by more than one filter, each listed once in sorted order
+    pub fn check_name_uniqueness(filters: &[FilterRule]) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let duplicates: BTreeSet<&str> = filters
+            .iter()
+            .map(|f| f.name.as_str())
+            .filter(|name| !seen.insert(*name))
+            .collect();
+        duplicates.into_iter().map(String::from).collect()
+    }
+
     /// Estimates the number of messages that would match a filter
     ///
     /// This performs a search query to count matching messages without applying any changes.
+    /// Counting stops after `ESTIMATE_LIMIT` messages, so large filters are reported as
+    /// "500+" instead of paging through every match.
     ///
     /// # Arguments
     /// * `filter` - The filter rule to estimate matches for
     ///
     /// # Returns
-    /// * Number of messages that would be affected by this filter
-    pub async fn estimate_filter_matches(&self, filter: &FilterRule) -> Result<usize> {
+    /// * Number of messages that would be affected by this filter, up to the limit
+    pub async fn estimate_filter_matches(&self, filter: &FilterRule) -> Result<MatchEstimate> {
         let query = self.build_gmail_query(filter);
 
         info!("Estimating matches for filter: {}", filter.name);
         debug!("Search query: {}", query);
 
-        let message_ids = self
+        let (message_ids, truncated) = self
             .client
-            .list_message_ids(&query)
+            .list_message_ids_limited(&query, ESTIMATE_LIMIT)
             .await
             .map_err(|e| GmailError::ApiError(format!("Failed to search messages: {}", e)))?;
 
-        let count = message_ids.len();
-        info!("Filter '{}' would match {} messages", filter.name, count);
+        let estimate = MatchEstimate {
+            count: message_ids.len(),
+            truncated,
+        };
+        info!("Filter '{}' would match {} messages", filter.name, estimate);
 
-        Ok(count)
+        Ok(estimate)
     }
 
     /// Prompts for user confirmation before applying filters
@@ -559,7 +708,7 @@ impl FilterManager {
 
             println!("\nFilter: {}", filter.name);
             println!("  Query: {}", self.build_gmail_query(filter));
-            println!("  Target Label ID: {}", filter.target_label_id);
+            println!("  Target Label: {}", filter.target_label);
             println!("  Archive: {}", filter.should_archive);
             println!("  Estimated Matches: {}", matches);
         }
@@ -582,16 +731,12 @@ impl FilterManager {
         &self,
         domain: &str,
         messages: &[&(MessageMetadata, Classification)],
+        idf_index: &HashMap<String, f64>,
     ) -> PatternAnalysis {
-        let mut subject_keywords = HashSet::new();
         let mut has_unsubscribe_count = 0;
         let mut is_automated_count = 0;
 
         for (msg, _) in messages {
-            // Extract common keywords from subjects
-            let words = self.extract_subject_keywords(&msg.subject);
-            subject_keywords.extend(words);
-
             if msg.has_unsubscribe {
                 has_unsubscribe_count += 1;
             }
@@ -605,8 +750,8 @@ impl FilterManager {
         let is_automated = (is_automated_count as f32 / messages.len() as f32) > 0.7;
         let has_unsubscribe = (has_unsubscribe_count as f32 / messages.len() as f32) > 0.7;
 
-        // Select most common keywords (limit to 3)
-        let top_keywords = subject_keywords.into_iter().take(3).collect::<Vec<_>>();
+        // Select the keywords most specific to this domain (limit to 3)
+        let top_keywords = top_keywords(messages.iter().map(|(msg, _)| msg), idf_index);
 
         PatternAnalysis {
             domain: domain.to_string(),
@@ -621,15 +766,12 @@ impl FilterManager {
         &self,
         domain: &str,
         messages: &[&MessageMetadata],
+        idf_index: &HashMap<String, f64>,
     ) -> PatternAnalysis {
-        let mut subject_keywords = HashSet::new();
         let mut has_unsubscribe_count = 0;
         let mut is_automated_count = 0;
 
         for msg in messages {
-            let words = self.extract_subject_keywords(&msg.subject);
-            subject_keywords.extend(words);
-
             if msg.has_unsubscribe {
                 has_unsubscribe_count += 1;
             }
@@ -642,7 +784,7 @@ impl FilterManager {
         let is_automated = (is_automated_count as f32 / messages.len() as f32) > 0.7;
         let has_unsubscribe = (has_unsubscribe_count as f32 / messages.len() as f32) > 0.7;
 
-        let top_keywords = subject_keywords.into_iter().take(3).collect::<Vec<_>>();
+        let top_keywords = top_keywords(messages.iter().copied(), idf_index);
 
         PatternAnalysis {
             domain: domain.to_string(),
@@ -653,7 +795,7 @@ impl FilterManager {
     }
 
     /// Extracts significant keywords from subject line
-    fn extract_subject_keywords(&self, subject: &str) -> Vec<String> {
+    fn extract_subject_keywords(subject: &str) -> Vec<String> {
         // Remove common prefixes
         let cleaned = subject
             .trim()
@@ -673,36 +815,16 @@ impl FilterManager {
             .collect()
     }
 
-    /// Determines the dominant category from classified messages
-    fn determine_dominant_category(
-        &self,
-        messages: &[&(MessageMetadata, Classification)],
-    ) -> EmailCategory {
-        let mut category_counts: HashMap<EmailCategory, usize> = HashMap::new();
-
-        for (_, classification) in messages {
-            *category_counts
-                .entry(classification.category.clone())
-                .or_insert(0) += 1;
-        }
-
-        // Return most common category
-        category_counts
-            .into_iter()
-            .max_by_key(|(_, count)| *count)
-            .map(|(cat, _)| cat)
-            .unwrap_or(EmailCategory::Other)
-    }
-
     /// Determines the dominant suggested_label from classified messages
     /// This reflects the user's choices from interactive review
     fn determine_dominant_label(&self, messages: &[&(MessageMetadata, Classification)]) -> String {
         let mut label_counts: HashMap<String, usize> = HashMap::new();
 
+        // Attachment sublabels get filters of their own (see `attachment_filters`)
         for (_, classification) in messages {
             if !classification.suggested_label.is_empty() {
                 *label_counts
-                    .entry(classification.suggested_label.clone())
+                    .entry(classification.base_label().to_string())
                     .or_insert(0) += 1;
             }
         }
@@ -748,7 +870,6 @@ impl FilterManager {
         &self,
         domain: String,
         analysis: PatternAnalysis,
-        category: EmailCategory,
         target_label: String,
         should_archive: bool,
         message_count: usize,
@@ -760,24 +881,25 @@ impl FilterManager {
             None
         };
 
-        // Build filter name using target_label if available, otherwise category
-        let filter_name = if !target_label.is_empty() {
-            format!("{} → {}", domain, target_label)
-        } else {
-            format!("{} - {:?}", domain, category)
-        };
-
-        Some(FilterRule {
+        let mut rule = FilterRule {
             id: None,
-            name: filter_name,
+            name: String::new(),
             from_pattern,
             is_specific_sender: false,
             excluded_senders: vec![],
             subject_keywords: analysis.subject_keywords,
-            target_label_id: target_label,
+            target_label: LabelName::new(target_label),
+            target_label_id: None,
             should_archive,
             estimated_matches: message_count,
-        })
+            priority: DEFAULT_FILTER_PRIORITY,
+            extra_terms: vec![],
+            additional_labels: vec![],
+            additional_label_ids: vec![],
+        };
+        // Named after its criteria and label, so different filters never share a name
+        rule.name = rule.auto_name();
+        Some(rule)
     }
 
     /// Checks if a filter is redundant given existing filters
@@ -789,8 +911,10 @@ impl FilterManager {
             {
                 // Check for exact match
                 if new_from == existing_from {
-                    // If subjects also match, it's redundant
-                    if filter.subject_keywords == existing_filter.subject_keywords {
+                    // If subjects and other terms also match, it's redundant
+                    if filter.subject_keywords == existing_filter.subject_keywords
+                        && filter.extra_terms == existing_filter.extra_terms
+                    {
                         return true;
                     }
                 }
@@ -810,6 +934,89 @@ impl FilterManager {
     }
 }
 
+/// File extension Gmail's `filename:` operator matches for an attachment MIME type
+fn attachment_extension(mime_type: &str) -> Option<&str> {
+    match mime_type {
+        "image/jpeg" => Some("jpg"),
+        "text/plain" => Some("txt"),
+        "application/msword" => Some("doc"),
+        "application/vnd.ms-excel" => Some("xls"),
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => Some("docx"),
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => Some("xlsx"),
+        _ => {
+            // e.g. application/pdf, image/png, application/zip
+            let (_, subtype) = mime_type.split_once('/')?;
+            let plain = !subtype.is_empty() && subtype.chars().all(|c| c.is_ascii_alphanumeric());
+            plain.then_some(subtype)
+        }
+    }
+}
+
+/// A subject keyword as a Gmail search term
+///
+/// Single words are used as they are; anything else becomes a quoted phrase so
+/// spaces, parentheses and operators like `OR` are matched literally. Gmail has
+/// no escape for `"` inside a phrase, so quotes in the keyword are dropped.
+fn subject_search_term(keyword: &str) -> String {
+    let keyword = keyword.replace('"', " ");
+    let keyword = keyword.split_whitespace().collect::<Vec<_>>().join(" ");
+    let is_word = !keyword.is_empty()
+        && keyword.chars().all(char::is_alphanumeric)
+        && !keyword.eq_ignore_ascii_case("or")
+        && !keyword.eq_ignore_ascii_case("and");
+    if is_word {
+        keyword
+    } else {
+        format!("\"{}\"", keyword)
+    }
+}
+
+/// The 3 subject keywords of a domain's messages with the highest TF × IDF
+///
+/// Ties are broken alphabetically so the same messages always give the same keywords.
+fn top_keywords<'a>(
+    messages: impl Iterator<Item = &'a MessageMetadata>,
+    idf_index: &HashMap<String, f64>,
+) -> Vec<String> {
+    let mut term_counts: HashMap<String, usize> = HashMap::new();
+    let mut total_terms = 0;
+    for msg in messages {
+        for word in FilterManager::extract_subject_keywords(&msg.subject) {
+            *term_counts.entry(word).or_default() += 1;
+            total_terms += 1;
+        }
+    }
+
+    let mut scored: Vec<(f64, String)> = term_counts
+        .into_iter()
+        .map(|(word, count)| {
+            let tf = count as f64 / total_terms as f64;
+            let idf = idf_index.get(&word).copied().unwrap_or(0.0);
+            (tf * idf, word)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    scored.into_iter().take(3).map(|(_, word)| word).collect()
+}
+
+/// Messages a filter would match, from `FilterManager::estimate_filter_matches`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchEstimate {
+    pub count: usize,
+    /// More messages match than were counted
+    pub truncated: bool,
+}
+
+impl std::fmt::Display for MatchEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.truncated {
+            write!(f, "{}+", self.count)
+        } else {
+            write!(f, "{}", self.count)
+        }
+    }
+}
+
 /// Pattern analysis results for a domain
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -820,6 +1027,363 @@ struct PatternAnalysis {
     has_unsubscribe: bool,
 }
 
+/// An update to an existing filter, one JSON object per line in
+/// `.gmail-automation/filter-changes.jsonl`
+///
+/// Gmail can't edit filters in place, so an update deletes `filter_id` and
+/// creates `new_filter_id` with the new criteria.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterChange {
+    pub filter_id: String,
+    pub new_filter_id: String,
+    pub changed_at: DateTime<Utc>,
+    /// Criteria of the replaced filter, if it could still be read from Gmail
+    pub old_query: Option<String>,
+    pub new_query: String,
+    pub old_label_id: Option<String>,
+    pub new_label_id: String,
+    pub changed_by_run_id: Option<String>,
+}
+
+impl FilterChange {
+    /// Whether this change replaced or created the filter with `filter_id`
+    pub fn involves(&self, filter_id: &str) -> bool {
+        self.filter_id == filter_id || self.new_filter_id == filter_id
+    }
+
+    /// Append this change to a JSONL log, creating the file if needed
+    pub async fn append(&self, path: &Path) -> Result<()> {
+        crate::jsonl::append(std::slice::from_ref(self), path, "filter change").await
+    }
+
+    /// Load every change from a JSONL log, oldest first
+    ///
+    /// A missing log means no filter was ever updated.
+    pub async fn load_all(path: &Path) -> Result<Vec<FilterChange>> {
+        crate::jsonl::load(path, "filter change").await
+    }
+}
+
+/// Read filters from a Thunderbird `msgFilterRules.dat` file
+///
+/// Tag actions (`Label`, `AddTag`) set the target label, and `Move to folder` also
+/// archives, labelling with the folder name when the filter sets no tag. The label
+/// name stands in for the label ID until the label is created. Filters that are
+/// disabled, set no label, or use conditions a Gmail filter can't express are
+/// skipped with a warning.
+pub fn import_thunderbird(path: &Path) -> Result<Vec<FilterRule>> {
+    let contents = std::fs::read_to_string(path)?;
+    parse_thunderbird_filters(&contents)
+}
+
+/// Parse the contents of a `msgFilterRules.dat` file, see [`import_thunderbird`]
+pub fn parse_thunderbird_filters(contents: &str) -> Result<Vec<FilterRule>> {
+    fn finish(filter: ThunderbirdFilter, rules: &mut Vec<FilterRule>) {
+        let name = filter.name.clone();
+        match filter.into_rule() {
+            Ok(rule) => rules.push(rule),
+            Err(reason) => warn!("Skipping Thunderbird filter '{}': {}", name, reason),
+        }
+    }
+
+    let mut rules = Vec::new();
+    let mut current: Option<ThunderbirdFilter> = None;
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((key, value)) = parse_thunderbird_line(line) else {
+            return Err(GmailError::ConfigError(format!(
+                "Invalid Thunderbird filter line {}: {}",
+                index + 1,
+                line
+            )));
+        };
+
+        match key {
+            "version" | "logging" => {}
+            "name" => {
+                if let Some(filter) = current.take() {
+                    finish(filter, &mut rules);
+                }
+                current = Some(ThunderbirdFilter::new(value));
+            }
+            _ => {
+                let Some(filter) = current.as_mut() else {
+                    return Err(GmailError::ConfigError(format!(
+                        "Thunderbird filter line {} comes before any filter name",
+                        index + 1
+                    )));
+                };
+                filter.set(key, value);
+            }
+        }
+    }
+    if let Some(filter) = current {
+        finish(filter, &mut rules);
+    }
+    Ok(rules)
+}
+
+/// Split a `key="value"` line, unescaping `\"` and `\\` in the value
+fn parse_thunderbird_line(line: &str) -> Option<(&str, String)> {
+    let (key, value) = line.split_once('=')?;
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value);
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            unescaped.extend(chars.next());
+        } else {
+            unescaped.push(c);
+        }
+    }
+    Some((key.trim(), unescaped))
+}
+
+/// A filter being read from `msgFilterRules.dat`
+struct ThunderbirdFilter {
+    name: String,
+    enabled: bool,
+    condition: Option<String>,
+    /// (action, actionValue) in file order
+    actions: Vec<(String, Option<String>)>,
+}
+
+/// One `(field,operator,value)` term of a Thunderbird condition
+struct ThunderbirdTerm {
+    field: String,
+    operator: String,
+    value: String,
+}
+
+impl ThunderbirdFilter {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            enabled: true,
+            condition: None,
+            actions: Vec::new(),
+        }
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        match key {
+            "enabled" => self.enabled = value != "no",
+            "condition" => self.condition = Some(value),
+            "action" => self.actions.push((value, None)),
+            "actionValue" => {
+                if let Some((_, action_value)) = self.actions.last_mut() {
+                    *action_value = Some(value);
+                }
+            }
+            // `type` (when the filter runs) and other keys don't apply to Gmail
+            _ => {}
+        }
+    }
+
+    fn into_rule(self) -> std::result::Result<FilterRule, String> {
+        if !self.enabled {
+            return Err("disabled".to_string());
+        }
+
+        let mut label = None;
+        let mut folder = None;
+        let mut should_archive = false;
+        for (action, value) in &self.actions {
+            match action.to_lowercase().as_str() {
+                "label" | "addtag" if label.is_none() => {
+                    label = value
+                        .as_deref()
+                        .map(|tag| tag.trim_start_matches('$').to_string())
+                        .filter(|tag| !tag.is_empty());
+                }
+                "move to folder" => {
+                    should_archive = true;
+                    folder = value.as_deref().and_then(thunderbird_folder_name);
+                }
+                "copy to folder" => {
+                    folder = value.as_deref().and_then(thunderbird_folder_name);
+                }
+                _ => {}
+            }
+        }
+        let label = label.or(folder).ok_or("no tag or folder action")?;
+
+        let condition = self.condition.as_deref().unwrap_or("ALL").trim();
+        let (any, terms) = parse_thunderbird_condition(condition)?;
+
+        let mut from_pattern = None;
+        let mut is_specific_sender = false;
+        let mut excluded_senders = Vec::new();
+        let mut subject_keywords = Vec::new();
+        for term in &terms {
+            match (term.field.as_str(), term.operator.as_str()) {
+                ("from", "contains" | "is" | "ends with") if from_pattern.is_none() => {
+                    let value = term.value.as_str();
+                    if let Some(domain) = value.strip_prefix('@') {
+                        from_pattern = Some(format!("*@{}", domain));
+                    } else if value.contains('@') {
+                        from_pattern = Some(value.to_string());
+                        is_specific_sender = true;
+                    } else if value.contains('.') {
+                        from_pattern = Some(format!("*@{}", value));
+                    } else {
+                        return Err(format!("sender \"{}\" is not an address or domain", value));
+                    }
+                }
+                ("from", "doesn't contain" | "isn't") if term.value.contains('@') => {
+                    excluded_senders.push(term.value.clone());
+                }
+                ("subject", "contains" | "is") => subject_keywords.push(term.value.clone()),
+                _ => {
+                    return Err(format!(
+                        "condition ({},{},{}) has no Gmail filter equivalent",
+                        term.field, term.operator, term.value
+                    ))
+                }
+            }
+        }
+
+        // Several subjects are matched as alternatives, a sender and subject together
+        if any && terms.len() > 1 && (from_pattern.is_some() || !excluded_senders.is_empty()) {
+            return Err("only subject conditions can be combined with OR".to_string());
+        }
+        if !any && subject_keywords.len() > 1 {
+            return Err("more than one subject condition combined with AND".to_string());
+        }
+        if from_pattern.is_none() && subject_keywords.is_empty() {
+            return Err("no sender or subject condition".to_string());
+        }
+        if !excluded_senders.is_empty() && (from_pattern.is_none() || is_specific_sender) {
+            return Err("excluded senders need a sender domain".to_string());
+        }
+
+        Ok(FilterRule {
+            id: None,
+            name: self.name,
+            from_pattern,
+            is_specific_sender,
+            excluded_senders,
+            subject_keywords,
+            target_label: LabelName::new(label),
+            target_label_id: None,
+            should_archive,
+            estimated_matches: 0,
+            priority: DEFAULT_FILTER_PRIORITY,
+            extra_terms: Vec::new(),
+            additional_labels: Vec::new(),
+            additional_label_ids: Vec::new(),
+        })
+    }
+}
+
+/// Parse `AND (from,contains,@a.com) AND (subject,contains,x)`, or the shorthand
+/// `contains @a.com`, into whether terms are ORed and the terms
+fn parse_thunderbird_condition(
+    condition: &str,
+) -> std::result::Result<(bool, Vec<ThunderbirdTerm>), String> {
+    if condition.eq_ignore_ascii_case("ALL") {
+        return Err("matches every message".to_string());
+    }
+    // Shorthand: an address or domain is a sender, anything else a subject
+    if let Some(value) = condition.strip_prefix("contains ") {
+        let value = value.trim().to_string();
+        let field = if value.contains('@') {
+            "from"
+        } else {
+            "subject"
+        };
+        return Ok((
+            false,
+            vec![ThunderbirdTerm {
+                field: field.to_string(),
+                operator: "contains".to_string(),
+                value,
+            }],
+        ));
+    }
+
+    let mut any = None;
+    let mut terms = Vec::new();
+    let mut rest = condition;
+    while !rest.is_empty() {
+        let (is_or, after) = if let Some(after) = rest.strip_prefix("AND") {
+            (false, after)
+        } else if let Some(after) = rest.strip_prefix("OR") {
+            (true, after)
+        } else {
+            return Err(format!("unrecognised condition \"{}\"", rest));
+        };
+        if any.is_some_and(|any| any != is_or) {
+            return Err("mixes AND and OR".to_string());
+        }
+        any = Some(is_or);
+
+        let inner = after
+            .trim_start()
+            .strip_prefix('(')
+            .ok_or_else(|| format!("unrecognised condition \"{}\"", rest))?;
+        // A term ends at the `)` followed by the next AND/OR or the end
+        let end = inner
+            .match_indices(')')
+            .map(|(i, _)| i)
+            .find(|&i| {
+                let next = inner[i + 1..].trim_start();
+                next.is_empty() || next.starts_with("AND") || next.starts_with("OR")
+            })
+            .ok_or_else(|| format!("unterminated condition \"{}\"", rest))?;
+
+        let mut parts = inner[..end].splitn(3, ',');
+        let (Some(field), Some(operator), Some(value)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!("unrecognised condition \"({})\"", &inner[..end]));
+        };
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        terms.push(ThunderbirdTerm {
+            field: field.trim().to_lowercase(),
+            operator: operator.trim().to_lowercase(),
+            value: value.to_string(),
+        });
+        rest = inner[end + 1..].trim_start();
+    }
+    Ok((any.unwrap_or(false), terms))
+}
+
+/// Name of the folder in a URI like `imap://me@host/INBOX/My%20News`
+fn thunderbird_folder_name(uri: &str) -> Option<String> {
+    let segment = uri.trim_end_matches('/').rsplit('/').next()?;
+
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut i = 0;
+    while i < segment.len() {
+        if segment.as_bytes()[i] == b'%' {
+            if let Some(byte) = segment
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                bytes.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        bytes.push(segment.as_bytes()[i]);
+        i += 1;
+    }
+    String::from_utf8(bytes)
+        .ok()
+        .filter(|name| !name.is_empty())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -838,6 +1402,15 @@ mod tests {
             labels: vec![],
             has_unsubscribe,
             is_automated: has_unsubscribe,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 0,
         }
     }
 
@@ -852,18 +1425,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -881,9 +1458,14 @@ mod tests {
             is_specific_sender: false,
             excluded_senders: vec![],
             subject_keywords: vec![],
-            target_label_id: "label-id".to_string(),
+            target_label: LabelName::new("label-id"),
+            target_label_id: None,
             should_archive: false,
             estimated_matches: 10,
+            priority: 100,
+            extra_terms: vec![],
+            additional_labels: vec![],
+            additional_label_ids: vec![],
         };
 
         let query = manager.build_gmail_query(&filter);
@@ -919,53 +1501,159 @@ mod tests {
         assert!(query.contains("-from:(messaging-digest-noreply@linkedin.com)"));
         assert!(query.contains("-from:(messages-noreply@linkedin.com)"));
         assert!(query.contains("-from:(jobs-listings@linkedin.com)"));
-    }
 
-    #[test]
-    fn test_extract_subject_keywords() {
-        use async_trait::async_trait;
+        // Keywords that aren't a single word are quoted, quotes inside them dropped
+        let filter_with_phrases = FilterRule {
+            from_pattern: None,
+            excluded_senders: vec![],
+            subject_keywords: vec![
+                "Order (shipped)".to_string(),
+                "say \"hi\"".to_string(),
+                "or".to_string(),
+            ],
+            ..filter_with_exclusions.clone()
+        };
+        let query = manager.build_gmail_query(&filter_with_phrases);
+        assert_eq!(
+            query,
+            "subject:(\"order (shipped)\" OR \"say hi\" OR \"or\")"
+        );
 
-        mockall::mock! {
-            pub TestGmailClient {}
+        let filter_with_phrase = FilterRule {
+            subject_keywords: vec!["Weekly digest".to_string()],
+            ..filter_with_phrases
+        };
+        let query = manager.build_gmail_query(&filter_with_phrase);
+        assert_eq!(query, "subject:(\"Weekly digest\")");
 
-            #[async_trait]
-            impl crate::client::GmailClient for TestGmailClient {
-                async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
-                async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
-                async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
-                async fn delete_label(&self, label_id: &str) -> Result<()>;
-                async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
-                async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
-                async fn delete_filter(&self, filter_id: &str) -> Result<()>;
-                async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
-                async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
-                async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
-                async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
-            }
-        }
+        // A mailing list filter matches the list, carving out senders with their own filter
+        let list_filter = FilterRule {
+            subject_keywords: vec![],
+            excluded_senders: vec!["alice@example.org".to_string()],
+            extra_terms: vec!["list:dev.rust-lang.org".to_string()],
+            ..filter_with_phrase
+        };
+        assert!(manager.validate_filter(&list_filter).is_ok());
+        let query = manager.build_gmail_query(&list_filter);
+        assert_eq!(query, "-from:(alice@example.org) list:dev.rust-lang.org");
+    }
 
-        let mock_client = MockTestGmailClient::new();
-        let manager = FilterManager::new(Box::new(mock_client));
+    #[test]
+    fn test_attachment_filters() {
+        let classification = |label: &str, attachment: Option<(&str, &str)>| Classification {
+            message_id: "m".to_string(),
+            category: EmailCategory::Receipt,
+            confidence: 0.9,
+            suggested_label: label.to_string(),
+            should_archive: false,
+            reasoning: None,
+            reasons: attachment
+                .map(
+                    |(mime_type, sublabel)| crate::models::ClassificationReason::AttachmentType {
+                        mime_type: mime_type.to_string(),
+                        sublabel: sublabel.to_string(),
+                    },
+                )
+                .into_iter()
+                .collect(),
+        };
+        let classifications = vec![
+            classification("auto/receipts/shop.com", None),
+            classification(
+                "auto/receipts/shop.com/documents",
+                Some(("application/pdf", "documents")),
+            ),
+            classification(
+                "auto/receipts/shop.com/documents",
+                Some(("application/pdf", "documents")),
+            ),
+            classification(
+                "auto/receipts/shop.com/images",
+                Some(("image/jpeg", "images")),
+            ),
+        ];
+        assert_eq!(classifications[1].base_label(), "auto/receipts/shop.com");
+
+        let filter = FilterRule {
+            id: None,
+            name: "*@shop.com".to_string(),
+            from_pattern: Some("*@shop.com".to_string()),
+            is_specific_sender: false,
+            excluded_senders: vec![],
+            subject_keywords: vec![],
+            target_label: LabelName::new("Shopping"),
+            target_label_id: None,
+            should_archive: true,
+            estimated_matches: 4,
+            priority: DEFAULT_FILTER_PRIORITY,
+            extra_terms: vec![],
+            additional_labels: vec![LabelName::new("Inbox/Shopping")],
+            additional_label_ids: vec![],
+        };
+        let filters = FilterManager::attachment_filters(&filter, &classifications);
+
+        // The sublabel goes under the filter's label, which review may have changed
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters[0].target_label, "Shopping/images");
+        assert_eq!(filters[0].estimated_matches, 1);
+        assert_eq!(
+            FilterManager::build_gmail_query_static(&filters[0]),
+            "from:(*@shop.com) has:attachment filename:jpg"
+        );
+        assert_eq!(filters[1].target_label, "Shopping/documents");
+        assert_eq!(filters[1].estimated_matches, 2);
+        assert_eq!(
+            FilterManager::build_gmail_query_static(&filters[1]),
+            "from:(*@shop.com) has:attachment filename:pdf"
+        );
+        assert!(filters[1].should_archive);
+        assert!(filters[1].additional_labels.is_empty());
+        assert_ne!(filters[0].name, filters[1].name);
+        assert_ne!(filters[1].name, filter.name);
+    }
 
-        let keywords = manager.extract_subject_keywords("Weekly Newsletter The Best Articles");
+    #[test]
+    fn test_extract_subject_keywords() {
+        let keywords =
+            FilterManager::extract_subject_keywords("Weekly Newsletter The Best Articles");
         assert!(keywords.contains(&"weekly".to_string()));
         assert!(keywords.contains(&"newsletter".to_string()));
         assert!(keywords.contains(&"best".to_string()));
         assert!(keywords.contains(&"articles".to_string()));
 
         // Should filter out short words and common words
-        let filtered = manager.extract_subject_keywords("The best of the week");
+        let filtered = FilterManager::extract_subject_keywords("The best of the week");
         assert!(!filtered.contains(&"the".to_string()));
         assert!(filtered.contains(&"best".to_string()));
         assert!(filtered.contains(&"week".to_string()));
     }
 
+    #[test]
+    fn test_tf_idf_keywords() {
+        let messages = vec![
+            create_test_message("bank.com", "Your statement is ready", false),
+            create_test_message("bank.com", "Your monthly statement", false),
+            create_test_message("bank.com", "Your account alert", false),
+            create_test_message("shop.com", "Your order shipped", false),
+            create_test_message("news.com", "Your weekly digest", false),
+        ];
+
+        let idf = FilterManager::build_idf_index(&messages);
+        // "your" is used by every domain, "statement" only by the bank
+        assert_eq!(idf["your"], 0.0);
+        assert!(idf["your"] < idf["statement"]);
+        assert!(idf["monthly"] > 0.0);
+
+        let bank: Vec<&MessageMetadata> = messages
+            .iter()
+            .filter(|m| m.sender_domain == "bank.com")
+            .collect();
+        assert_eq!(
+            top_keywords(bank.iter().copied(), &idf),
+            vec!["statement", "account", "alert"]
+        );
+    }
+
     #[test]
     fn test_infer_category_from_messages() {
         use async_trait::async_trait;
@@ -977,18 +1665,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1001,8 +1693,7 @@ mod tests {
         // Newsletter category
         let msg1 = create_test_message("test.com", "Weekly Newsletter Digest", true);
         let msg2 = create_test_message("test.com", "Monthly Newsletter", true);
-        let messages = vec![&msg1, &msg2];
-        let refs: Vec<&MessageMetadata> = messages.iter().map(|m| *m).collect();
+        let refs: Vec<&MessageMetadata> = vec![&msg1, &msg2];
 
         let category = manager.infer_category_from_messages(&refs);
         assert!(matches!(category, EmailCategory::Newsletter));
@@ -1010,8 +1701,7 @@ mod tests {
         // Receipt category
         let msg3 = create_test_message("store.com", "Your receipt for order #123", false);
         let msg4 = create_test_message("store.com", "Order confirmation", false);
-        let messages = vec![&msg3, &msg4];
-        let refs: Vec<&MessageMetadata> = messages.iter().map(|m| *m).collect();
+        let refs: Vec<&MessageMetadata> = vec![&msg3, &msg4];
 
         let category = manager.infer_category_from_messages(&refs);
         assert!(matches!(category, EmailCategory::Receipt));
@@ -1028,18 +1718,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1057,9 +1751,14 @@ mod tests {
             is_specific_sender: false,
             excluded_senders: vec![],
             subject_keywords: vec![],
-            target_label_id: "label-123".to_string(),
+            target_label: LabelName::new("label-123"),
+            target_label_id: None,
             should_archive: false,
             estimated_matches: 10,
+            priority: 100,
+            extra_terms: vec![],
+            additional_labels: vec![],
+            additional_label_ids: vec![],
         };
 
         assert!(manager.validate_filter(&valid_filter).is_ok());
@@ -1073,9 +1772,17 @@ mod tests {
 
         assert!(manager.validate_filter(&invalid_no_criteria).is_err());
 
+        // Invalid: empty secondary label
+        let invalid_additional_label = FilterRule {
+            additional_labels: vec![LabelName::new("AutoManaged/Other"), Default::default()],
+            ..valid_filter.clone()
+        };
+
+        assert!(manager.validate_filter(&invalid_additional_label).is_err());
+
         // Invalid: no target label
         let invalid_no_label = FilterRule {
-            target_label_id: String::new(),
+            target_label: Default::default(),
             ..valid_filter
         };
 
@@ -1093,18 +1800,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1122,9 +1833,14 @@ mod tests {
                 is_specific_sender: false,
                 excluded_senders: vec![],
                 subject_keywords: vec![],
-                target_label_id: "label-1".to_string(),
+                target_label: LabelName::new("label-1"),
+                target_label_id: None,
                 should_archive: false,
                 estimated_matches: 10,
+                priority: 100,
+                extra_terms: vec![],
+                additional_labels: vec![],
+                additional_label_ids: vec![],
             },
             FilterRule {
                 id: None,
@@ -1133,9 +1849,14 @@ mod tests {
                 is_specific_sender: false,
                 excluded_senders: vec![],
                 subject_keywords: vec![],
-                target_label_id: "label-1".to_string(),
+                target_label: LabelName::new("label-1"),
+                target_label_id: None,
                 should_archive: false,
                 estimated_matches: 10,
+                priority: 100,
+                extra_terms: vec![],
+                additional_labels: vec![],
+                additional_label_ids: vec![],
             },
             FilterRule {
                 id: None,
@@ -1144,9 +1865,14 @@ mod tests {
                 is_specific_sender: false,
                 excluded_senders: vec![],
                 subject_keywords: vec![],
-                target_label_id: "label-2".to_string(),
+                target_label: LabelName::new("label-2"),
+                target_label_id: None,
                 should_archive: false,
                 estimated_matches: 5,
+                priority: 100,
+                extra_terms: vec![],
+                additional_labels: vec![],
+                additional_label_ids: vec![],
             },
         ];
 
@@ -1166,18 +1892,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1196,9 +1926,14 @@ mod tests {
                 is_specific_sender: false,
                 excluded_senders: vec![],
                 subject_keywords: vec![],
-                target_label_id: "label-123".to_string(),
+                target_label: LabelName::new("label-123"),
+                target_label_id: None,
                 should_archive: true,
                 estimated_matches: 50,
+                priority: 100,
+                extra_terms: vec![],
+                additional_labels: vec![],
+                additional_label_ids: vec![],
             },
             FilterRule {
                 id: None,
@@ -1207,9 +1942,14 @@ mod tests {
                 is_specific_sender: false,
                 excluded_senders: vec![],
                 subject_keywords: vec!["receipt".to_string()],
-                target_label_id: "label-456".to_string(),
+                target_label: LabelName::new("label-456"),
+                target_label_id: None,
                 should_archive: false,
                 estimated_matches: 100,
+                priority: 100,
+                extra_terms: vec![],
+                additional_labels: vec![],
+                additional_label_ids: vec![],
             },
         ];
 
@@ -1235,18 +1975,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1265,9 +2009,14 @@ mod tests {
                 is_specific_sender: false,
                 excluded_senders: vec![],
                 subject_keywords: vec![],
-                target_label_id: "label-123".to_string(),
+                target_label: LabelName::new("label-123"),
+                target_label_id: None,
                 should_archive: false,
                 estimated_matches: 10,
+                priority: 100,
+                extra_terms: vec![],
+                additional_labels: vec![],
+                additional_label_ids: vec![],
             },
             // Invalid: no criteria
             FilterRule {
@@ -1277,12 +2026,26 @@ mod tests {
                 is_specific_sender: false,
                 excluded_senders: vec![],
                 subject_keywords: vec![],
-                target_label_id: "label-456".to_string(),
+                target_label: LabelName::new("label-456"),
+                target_label_id: None,
                 should_archive: false,
                 estimated_matches: 0,
+                priority: 100,
+                extra_terms: vec![],
+                additional_labels: vec![],
+                additional_label_ids: vec![],
             },
         ];
 
+        // Results are keyed by name, so duplicate names are rejected up front
+        let duplicated = vec![filters[0].clone(), filters[0].clone(), filters[1].clone()];
+        assert_eq!(
+            FilterManager::check_name_uniqueness(&duplicated),
+            vec!["Valid Filter".to_string()]
+        );
+        let err = manager.create_filters(duplicated, true).await.unwrap_err();
+        assert!(matches!(err, GmailError::ConfigError(ref msg) if msg.contains("Valid Filter")));
+
         let result = manager.create_filters(filters, true).await;
         assert!(result.is_ok());
 
@@ -1310,18 +2073,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1352,14 +2119,92 @@ mod tests {
             is_specific_sender: false,
             excluded_senders: vec![],
             subject_keywords: vec![],
-            target_label_id: "label-123".to_string(),
+            target_label: LabelName::new("label-123"),
+            target_label_id: None,
             should_archive: false,
             estimated_matches: 0, // Will be updated by estimate
+            priority: 100,
+            extra_terms: vec![],
+            additional_labels: vec![],
+            additional_label_ids: vec![],
         };
 
         let result = manager.estimate_filter_matches(&filter).await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), 3);
+        assert_eq!(
+            result.unwrap(),
+            MatchEstimate {
+                count: 3,
+                truncated: false
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_estimate_filter_matches_stops_at_limit() {
+        use async_trait::async_trait;
+
+        mockall::mock! {
+            pub TestGmailClient {}
+
+            #[async_trait]
+            impl crate::client::GmailClient for TestGmailClient {
+                async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn list_message_ids_limited(&self, query: &str, limit: usize) -> Result<(Vec<String>, bool)>;
+                async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
+                async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
+                async fn delete_label(&self, label_id: &str) -> Result<()>;
+                async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
+                async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
+                async fn delete_filter(&self, filter_id: &str) -> Result<()>;
+                async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
+                async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
+                async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
+                async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
+            }
+        }
+
+        let mut mock_client = MockTestGmailClient::new();
+        // The exact listing is never used for estimates
+        mock_client.expect_list_message_ids().never();
+        mock_client
+            .expect_list_message_ids_limited()
+            .withf(|query, limit| query == "from:(*@github.com)" && *limit == ESTIMATE_LIMIT)
+            .times(1)
+            .returning(|_, limit| Ok(((0..limit).map(|i| format!("msg{}", i)).collect(), true)));
+
+        let manager = FilterManager::new(Box::new(mock_client));
+        let filter = FilterRule {
+            id: None,
+            name: "GitHub Filter".to_string(),
+            from_pattern: Some("*@github.com".to_string()),
+            is_specific_sender: false,
+            excluded_senders: vec![],
+            subject_keywords: vec![],
+            target_label: LabelName::new("label-123"),
+            target_label_id: None,
+            should_archive: false,
+            estimated_matches: 0,
+            priority: 100,
+            extra_terms: vec![],
+            additional_labels: vec![],
+            additional_label_ids: vec![],
+        };
+
+        let estimate = manager.estimate_filter_matches(&filter).await.unwrap();
+        assert_eq!(estimate.count, ESTIMATE_LIMIT);
+        assert!(estimate.truncated);
+        assert_eq!(estimate.to_string(), "500+");
     }
 
     #[test]
@@ -1373,18 +2218,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1401,9 +2250,14 @@ mod tests {
             is_specific_sender: false,
             excluded_senders: vec![],
             subject_keywords: vec![],
-            target_label_id: "label-1".to_string(),
+            target_label: LabelName::new("label-1"),
+            target_label_id: None,
             should_archive: false,
             estimated_matches: 10,
+            priority: 100,
+            extra_terms: vec![],
+            additional_labels: vec![],
+            additional_label_ids: vec![],
         }];
 
         let mut estimates = HashMap::new();
@@ -1413,4 +2267,144 @@ mod tests {
         let confirmed = manager.confirm_filter_creation(&filters, &estimates);
         assert!(confirmed);
     }
+
+    #[test]
+    fn test_sort_for_creation() {
+        let rule = |name: &str, priority: u32, estimated_matches: usize| FilterRule {
+            id: None,
+            name: name.to_string(),
+            from_pattern: Some(format!("*@{}.com", name)),
+            is_specific_sender: false,
+            excluded_senders: vec![],
+            subject_keywords: vec![],
+            target_label: LabelName::new("AutoManaged/Test"),
+            target_label_id: None,
+            should_archive: false,
+            estimated_matches,
+            priority,
+            extra_terms: vec![],
+            additional_labels: vec![],
+            additional_label_ids: vec![],
+        };
+        let names = |filters: &[FilterRule]| -> Vec<String> {
+            filters.iter().map(|f| f.name.clone()).collect()
+        };
+
+        let mut filters = vec![
+            rule("charlie", 100, 5),
+            rule("alpha", 100, 50),
+            rule("bravo", 10, 1),
+        ];
+
+        FilterManager::sort_for_creation(&mut filters, FilterSortOrder::ByPriority);
+        assert_eq!(names(&filters), vec!["bravo", "alpha", "charlie"]);
+
+        FilterManager::sort_for_creation(&mut filters, FilterSortOrder::ByEstimatedMatches);
+        assert_eq!(names(&filters), vec!["alpha", "charlie", "bravo"]);
+    }
+
+    #[tokio::test]
+    async fn test_filter_change_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("filter-changes.jsonl");
+        assert!(FilterChange::load_all(&path).await.unwrap().is_empty());
+
+        let change = |old: &str, new: &str| FilterChange {
+            filter_id: old.to_string(),
+            new_filter_id: new.to_string(),
+            changed_at: Utc::now(),
+            old_query: Some("from:(*@a.com)".to_string()),
+            new_query: "from:(*@a.com) subject:(receipt)".to_string(),
+            old_label_id: Some("Label_1".to_string()),
+            new_label_id: "Label_2".to_string(),
+            changed_by_run_id: Some("run-1".to_string()),
+        };
+        change("f1", "f2").append(&path).await.unwrap();
+        change("f2", "f3").append(&path).await.unwrap();
+
+        let changes = FilterChange::load_all(&path).await.unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].filter_id, "f2");
+        assert!(changes[0].involves("f2") && changes[1].involves("f2"));
+        assert!(!changes[0].involves("f3"));
+    }
+
+    #[test]
+    fn test_parse_thunderbird_filters() {
+        let contents = r#"version="9"
+logging="no"
+name="GitHub"
+enabled="yes"
+type="17"
+action="AddTag"
+actionValue="$github"
+action="Move to folder"
+actionValue="imap://me%40example.com@imap.example.com/INBOX/Dev"
+condition="AND (from,contains,@github.com)"
+name="Invoices"
+enabled="yes"
+type="17"
+action="Move to folder"
+actionValue="mailbox://nobody@Local%20Folders/Bills%20%26%20Invoices"
+condition="OR (subject,contains,invoice) OR (subject,contains,\"receipt (copy)\")"
+name="Old rule"
+enabled="no"
+action="AddTag"
+actionValue="$old"
+condition="AND (from,contains,@old.com)"
+name="Everything"
+enabled="yes"
+action="AddTag"
+actionValue="$all"
+condition="ALL"
+name="Shorthand"
+enabled="yes"
+action="Label"
+actionValue="News"
+condition="contains @news.example.com"
+name="Boss"
+enabled="yes"
+action="Copy to folder"
+actionValue="imap://me@imap.example.com/Work"
+condition="AND (from,is,boss@corp.com)"
+name="Corp"
+enabled="yes"
+action="AddTag"
+actionValue="$corp"
+condition="AND (from,ends with,corp.com) AND (from,doesn't contain,boss@corp.com)"
+"#;
+
+        let rules = parse_thunderbird_filters(contents).unwrap();
+        let names: Vec<&str> = rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["GitHub", "Invoices", "Shorthand", "Boss", "Corp"]);
+
+        // The tag wins over the folder, and moving archives
+        assert_eq!(rules[0].from_pattern.as_deref(), Some("*@github.com"));
+        assert_eq!(rules[0].target_label.as_str(), "github");
+        assert!(rules[0].should_archive);
+
+        assert_eq!(rules[1].from_pattern, None);
+        assert_eq!(rules[1].subject_keywords, ["invoice", "receipt (copy)"]);
+        assert_eq!(rules[1].target_label.as_str(), "Bills & Invoices");
+        assert!(rules[1].should_archive);
+
+        assert_eq!(rules[2].from_pattern.as_deref(), Some("*@news.example.com"));
+        assert_eq!(rules[2].target_label.as_str(), "News");
+        assert!(!rules[2].should_archive);
+
+        assert_eq!(rules[3].from_pattern.as_deref(), Some("boss@corp.com"));
+        assert!(rules[3].is_specific_sender);
+        assert_eq!(rules[3].target_label.as_str(), "Work");
+        assert!(!rules[3].should_archive);
+
+        assert_eq!(rules[4].from_pattern.as_deref(), Some("*@corp.com"));
+        assert_eq!(rules[4].excluded_senders, ["boss@corp.com"]);
+    }
+
+    #[test]
+    fn test_parse_thunderbird_filters_rejects_malformed_lines() {
+        let err = parse_thunderbird_filters("name=\"A\"\nnot a setting\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+        assert!(parse_thunderbird_filters("action=\"AddTag\"\n").is_err());
+    }
 }
@@ -0,0 +1,395 @@
+//! Filter effectiveness tracking
+//!
+//! Records how many messages each created filter matches over time so filters
+//! that no longer capture anything can be flagged as stale. Stats are saved to
+//! `.gmail-automation/filter-stats.json` and persist across runs.
+
+use crate::client::GmailClient;
+use crate::error::{GmailError, Result};
+use crate::filter_manager::FilterChange;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::warn;
+
+/// Width of the longest match-count bar in a timeline
+const TIMELINE_BAR_WIDTH: usize = 30;
+
+/// Direction a filter's match count moved in over a period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchTrend {
+    Growing,
+    Stable,
+    Shrinking,
+    /// Fewer than two snapshots in the period
+    Unknown,
+}
+
+impl std::fmt::Display for MatchTrend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MatchTrend::Growing => "growing",
+            MatchTrend::Stable => "stable",
+            MatchTrend::Shrinking => "shrinking",
+            MatchTrend::Unknown => "unknown",
+        };
+        f.pad(name)
+    }
+}
+
+/// Match-count history for a single filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterStats {
+    pub filter_id: String,
+    /// Gmail search query equivalent to the filter criteria
+    pub query: String,
+    /// Name of the label the filter applies
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+    /// (time measured, number of matching messages), oldest first
+    pub snapshots: Vec<(DateTime<Utc>, usize)>,
+}
+
+impl FilterStats {
+    /// Match count from the most recent snapshot
+    pub fn latest_matches(&self) -> Option<usize> {
+        self.snapshots.last().map(|(_, count)| *count)
+    }
+
+    /// A filter is stale when it is older than `threshold_days` and matched nothing last time
+    pub fn is_stale(&self, threshold_days: u32) -> bool {
+        let age = Utc::now() - self.created_at;
+        self.latest_matches() == Some(0) && age > chrono::Duration::days(threshold_days as i64)
+    }
+
+    /// Snapshots taken at or after `since` (all of them when `None`)
+    pub fn snapshots_since(&self, since: Option<DateTime<Utc>>) -> &[(DateTime<Utc>, usize)] {
+        let start = since.map_or(0, |since| {
+            self.snapshots.partition_point(|(taken, _)| *taken < since)
+        });
+        &self.snapshots[start..]
+    }
+
+    /// Compare the first and last match counts since `since`
+    ///
+    /// Changes within 10% (or a single message) count as stable.
+    pub fn trend(&self, since: Option<DateTime<Utc>>) -> MatchTrend {
+        let snapshots = self.snapshots_since(since);
+        if snapshots.len() < 2 {
+            return MatchTrend::Unknown;
+        }
+
+        let first = snapshots[0].1 as f64;
+        let last = snapshots[snapshots.len() - 1].1 as f64;
+        let tolerance = (first * 0.1).max(1.0);
+        let change = last - first;
+        if change > tolerance {
+            MatchTrend::Growing
+        } else if change < -tolerance {
+            MatchTrend::Shrinking
+        } else {
+            MatchTrend::Stable
+        }
+    }
+}
+
+/// The ID a filter has now, following any updates recorded since `filter_id` was current
+pub fn current_filter_id<'a>(filter_id: &'a str, changes: &'a [FilterChange]) -> &'a str {
+    let mut current = filter_id;
+    // Each update replaces one ID with a fresh one, so the chain can't loop back
+    while let Some(change) = changes.iter().find(|c| c.filter_id == current) {
+        current = &change.new_filter_id;
+    }
+    current
+}
+
+/// Updates that led up to the filter with `filter_id`, oldest first
+pub fn changes_for<'a>(filter_id: &str, changes: &'a [FilterChange]) -> Vec<&'a FilterChange> {
+    let mut chain = Vec::new();
+    let mut current = filter_id;
+    while let Some(change) = changes.iter().rev().find(|c| c.new_filter_id == current) {
+        chain.push(change);
+        current = &change.filter_id;
+    }
+    chain.reverse();
+    chain
+}
+
+/// Render a filter's creation, updates and match counts as lines of an ASCII timeline
+///
+/// Only events at or after `since` are shown; match counts are drawn as bars
+/// scaled to the largest count shown.
+pub fn render_timeline(
+    stats: &FilterStats,
+    changes: &[&FilterChange],
+    since: Option<DateTime<Utc>>,
+) -> Vec<String> {
+    let shown = |time: &DateTime<Utc>| since.map_or(true, |since| *time >= since);
+    let mut events: Vec<(DateTime<Utc>, String)> = Vec::new();
+
+    if shown(&stats.created_at) {
+        events.push((stats.created_at, "*  created".to_string()));
+    }
+    for change in changes.iter().filter(|c| shown(&c.changed_at)) {
+        let mut line = format!(
+            "~  updated  {} -> {}",
+            change.old_query.as_deref().unwrap_or("?"),
+            change.new_query
+        );
+        if change.old_label_id.as_deref() != Some(change.new_label_id.as_str()) {
+            line.push_str(&format!(
+                "  (label {} -> {})",
+                change.old_label_id.as_deref().unwrap_or("?"),
+                change.new_label_id
+            ));
+        }
+        if let Some(run_id) = &change.changed_by_run_id {
+            line.push_str(&format!("  [run {}]", run_id));
+        }
+        events.push((change.changed_at, line));
+    }
+
+    let snapshots = stats.snapshots_since(since);
+    let max_count = snapshots
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for (taken, count) in snapshots {
+        let width = (count * TIMELINE_BAR_WIDTH + max_count - 1) / max_count;
+        events.push((
+            *taken,
+            format!(
+                "|  {:<bar$}  {}",
+                "#".repeat(width),
+                count,
+                bar = TIMELINE_BAR_WIDTH
+            ),
+        ));
+    }
+
+    // Stable sort keeps creation ahead of a snapshot taken in the same instant
+    events.sort_by_key(|(time, _)| *time);
+    events
+        .into_iter()
+        .map(|(time, line)| format!("{}  {}", time.format("%Y-%m-%d %H:%M"), line))
+        .collect()
+}
+
+/// Persistent store of `FilterStats` for all tracked filters
+#[derive(Debug, Default)]
+pub struct FilterStatsStore {
+    filters: Vec<FilterStats>,
+}
+
+impl FilterStatsStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load stats from a JSON file
+    pub async fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let json = tokio::fs::read_to_string(path).await?;
+        let filters = serde_json::from_str(&json).map_err(|e| {
+            GmailError::Unknown(format!("Failed to parse filter stats file: {}", e))
+        })?;
+        Ok(Self { filters })
+    }
+
+    /// Save stats to a JSON file
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let json = serde_json::to_string_pretty(&self.filters)
+            .map_err(|e| GmailError::Unknown(format!("Failed to serialize filter stats: {}", e)))?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
+
+    /// Start tracking a filter (no-op if it is already tracked)
+    pub fn track(&mut self, filter_id: &str, query: &str, label: &str) {
+        if self.filters.iter().any(|f| f.filter_id == filter_id) {
+            return;
+        }
+
+        self.filters.push(FilterStats {
+            filter_id: filter_id.to_string(),
+            query: query.to_string(),
+            label: label.to_string(),
+            created_at: Utc::now(),
+            snapshots: Vec::new(),
+        });
+    }
+
+    /// Move a filter's history to the ID Gmail gave it when it was updated
+    ///
+    /// Starts tracking `new_id` afresh if `old_id` wasn't tracked.
+    pub fn replace(&mut self, old_id: &str, new_id: &str, query: &str, label: &str) {
+        match self.filters.iter_mut().find(|f| f.filter_id == old_id) {
+            Some(stats) => {
+                stats.filter_id = new_id.to_string();
+                stats.query = query.to_string();
+                stats.label = label.to_string();
+            }
+            None => self.track(new_id, query, label),
+        }
+    }
+
+    /// Stop tracking a filter, e.g. after it was deleted
+    pub fn untrack(&mut self, filter_id: &str) {
+        self.filters.retain(|f| f.filter_id != filter_id);
+    }
+
+    /// Query the current match count of every tracked filter and append a snapshot.
+    ///
+    /// Filters whose query fails are skipped with a warning. Returns the number refreshed.
+    pub async fn refresh(&mut self, client: &dyn GmailClient) -> usize {
+        let now = Utc::now();
+        let mut refreshed = 0;
+        for stats in &mut self.filters {
+            match client.list_message_ids(&stats.query).await {
+                Ok(ids) => {
+                    stats.snapshots.push((now, ids.len()));
+                    refreshed += 1;
+                }
+                Err(e) => warn!(
+                    "Failed to refresh stats for filter {}: {}",
+                    stats.filter_id, e
+                ),
+            }
+        }
+        refreshed
+    }
+
+    /// Filters flagged as stale for the given age threshold
+    pub fn stale(&self, threshold_days: u32) -> Vec<&FilterStats> {
+        self.filters
+            .iter()
+            .filter(|f| f.is_stale(threshold_days))
+            .collect()
+    }
+
+    /// All tracked filters
+    pub fn filters(&self) -> &[FilterStats] {
+        &self.filters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_filter_stats_track_dedup() {
+        let mut store = FilterStatsStore::new();
+        store.track("f1", "from:(*@a.com)", "AutoManaged/A");
+        store.track("f1", "from:(*@a.com)", "AutoManaged/A");
+        assert_eq!(store.filters().len(), 1);
+
+        store.untrack("f1");
+        assert!(store.filters().is_empty());
+    }
+
+    #[test]
+    fn test_filter_stats_stale() {
+        let mut store = FilterStatsStore::new();
+        store.track("old-empty", "from:(*@a.com)", "A");
+        store.track("old-active", "from:(*@b.com)", "B");
+        store.track("new-empty", "from:(*@c.com)", "C");
+        store.track("never-measured", "from:(*@d.com)", "D");
+
+        let long_ago = Utc::now() - chrono::Duration::days(60);
+        for stats in &mut store.filters {
+            if stats.filter_id != "new-empty" {
+                stats.created_at = long_ago;
+            }
+        }
+        store.filters[0].snapshots.push((Utc::now(), 0));
+        store.filters[1].snapshots.push((Utc::now(), 12));
+        store.filters[2].snapshots.push((Utc::now(), 0));
+
+        let stale: Vec<&str> = store
+            .stale(30)
+            .iter()
+            .map(|f| f.filter_id.as_str())
+            .collect();
+        assert_eq!(stale, vec!["old-empty"]);
+    }
+
+    #[tokio::test]
+    async fn test_filter_stats_save_load() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("filter-stats.json");
+
+        let mut store = FilterStatsStore::new();
+        store.track("f1", "from:(*@a.com)", "AutoManaged/A");
+        store.filters[0].snapshots.push((Utc::now(), 5));
+        store.save(&path).await.unwrap();
+
+        let loaded = FilterStatsStore::load(&path).await.unwrap();
+        assert_eq!(loaded.filters().len(), 1);
+        assert_eq!(loaded.filters()[0].latest_matches(), Some(5));
+    }
+
+    #[test]
+    fn test_filter_history() {
+        let start = Utc::now() - chrono::Duration::days(30);
+        let day = |n: i64| start + chrono::Duration::days(n);
+
+        let mut store = FilterStatsStore::new();
+        store.track("f1", "from:(*@a.com)", "AutoManaged/A");
+        store.filters[0].created_at = start;
+        store.filters[0].snapshots = vec![(day(1), 10), (day(5), 20)];
+        store.replace(
+            "f1",
+            "f2",
+            "from:(*@a.com) subject:(receipt)",
+            "AutoManaged/A",
+        );
+        store.filters[0].snapshots.push((day(10), 40));
+
+        let stats = &store.filters()[0];
+        assert_eq!(stats.filter_id, "f2");
+        assert_eq!(stats.trend(None), MatchTrend::Growing);
+        assert_eq!(stats.trend(Some(day(6))), MatchTrend::Unknown);
+        assert_eq!(stats.snapshots_since(Some(day(5))).len(), 2);
+
+        let changes = vec![FilterChange {
+            filter_id: "f1".to_string(),
+            new_filter_id: "f2".to_string(),
+            changed_at: day(7),
+            old_query: Some("from:(*@a.com)".to_string()),
+            new_query: "from:(*@a.com) subject:(receipt)".to_string(),
+            old_label_id: Some("Label_1".to_string()),
+            new_label_id: "Label_1".to_string(),
+            changed_by_run_id: Some("run-2".to_string()),
+        }];
+        assert_eq!(current_filter_id("f1", &changes), "f2");
+        let chain = changes_for("f2", &changes);
+        assert_eq!(chain.len(), 1);
+
+        let timeline = render_timeline(stats, &chain, None);
+        assert_eq!(timeline.len(), 5);
+        assert!(timeline[0].ends_with("*  created"));
+        assert!(timeline[3].contains(
+            "~  updated  from:(*@a.com) -> from:(*@a.com) subject:(receipt)  [run run-2]"
+        ));
+        assert!(timeline[4].contains(&format!("|  {}  40", "#".repeat(TIMELINE_BAR_WIDTH))));
+        assert!(timeline[1].contains(&format!("|  {:<30}  10", "#".repeat(8))));
+
+        let mut flat = stats.clone();
+        flat.snapshots = vec![(day(1), 100), (day(2), 95)];
+        assert_eq!(flat.trend(None), MatchTrend::Stable);
+        flat.snapshots.push((day(3), 50));
+        assert_eq!(flat.trend(None), MatchTrend::Shrinking);
+    }
+}
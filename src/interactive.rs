@@ -3,19 +3,42 @@
 //! Provides a terminal-based interface for reviewing and adjusting
 //! email classifications with minimal keystrokes.
 
+use crate::anomaly::VolumeSpike;
+use crate::client::GmailClient;
+use crate::config::{BoxStyle, UiConfig};
 use crate::error::{GmailError, Result};
 use crate::exclusions::ExclusionManager;
-use crate::models::{Classification, EmailCategory, MessageMetadata};
+use crate::filter_manager::FilterManager;
+use crate::models::{
+    Classification, ClassificationReason, EmailCategory, FilterRule, LabelName, MessageMetadata,
+};
+use crate::network::DomainGroup;
+use chrono::{DateTime, Utc};
 use crossterm::{
     cursor,
-    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{self, ClearType},
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// How often review progress is written to disk while the session runs
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Most matches counted by a `[P]` preview; more are shown as "1000+"
+const PREVIEW_LIMIT: usize = 1000;
+
+/// How often a pending `[P]` preview is checked for its result
+const PREVIEW_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 /// A cluster of emails from the same sender (specific email or domain)
 #[derive(Debug, Clone)]
@@ -30,9 +53,13 @@ pub struct EmailCluster {
     /// Subject pattern for subject-based clusters (e.g., "QNAP NAS Notification")
     pub subject_pattern: Option<String>,
     pub message_ids: Vec<String>,
+    /// Threads the messages belong to (empty for synthetic clusters)
+    pub thread_ids: HashSet<String>,
     pub suggested_category: EmailCategory,
     pub suggested_label: String,
     pub confidence: f32,
+    /// Most common top classification reason among the messages
+    pub top_reason: Option<ClassificationReason>,
     pub sample_subjects: Vec<String>,
     pub should_archive: bool,
     /// Existing filter ID if a matching filter already exists
@@ -43,16 +70,78 @@ pub struct EmailCluster {
     pub existing_filter_label: Option<String>,
     /// Original archive setting from existing filter (for detecting changes)
     pub existing_filter_archive: Option<bool>,
+    /// Criteria of the existing filter, from `ExistingFilterInfo::display_query`
+    pub existing_filter_query: Option<String>,
     /// Source of this cluster (email scan or synthetic from orphaned filter)
     pub source: ClusterSource,
     /// Default action for this cluster (None for Accept, Some(Delete) for orphaned)
     pub default_action: Option<DecisionAction>,
+    /// Number of emails in Gmail matching the proposed filter, once previewed with `[P]`
+    pub preview_count: Option<usize>,
+    /// `List-ID` of the mailing list the messages were posted to. The cluster's
+    /// filter matches the list (`list:<id>`) rather than its domain, since posts
+    /// come from many sender domains
+    pub list_id: Option<String>,
 }
 
 impl EmailCluster {
+    /// Unique key of the cluster, e.g. `*@shop.com` or `orders@shop.com|subject:Shipped`
+    ///
+    /// The subject pattern and mailing list are part of the key, so a sender's
+    /// subject clusters and list posts don't collide with its own cluster.
+    pub fn key(&self) -> String {
+        let base = if self.is_specific_sender {
+            self.sender_email.clone()
+        } else {
+            format!("*@{}", self.sender_domain)
+        };
+        cluster_key_of(
+            base,
+            self.subject_pattern.as_deref(),
+            self.list_id.as_deref(),
+        )
+    }
+
     pub fn email_count(&self) -> usize {
         self.message_ids.len()
     }
+
+    pub fn thread_count(&self) -> usize {
+        self.thread_ids.len()
+    }
+
+    /// "42 emails (15 threads)", or just "42 emails" when threads aren't known
+    pub fn volume_summary(&self) -> String {
+        match self.thread_count() {
+            0 => format!("{} emails", self.email_count()),
+            1 => format!("{} emails (1 thread)", self.email_count()),
+            threads => format!("{} emails ({} threads)", self.email_count(), threads),
+        }
+    }
+
+    /// What the average number of messages per thread suggests about the cluster
+    pub fn thread_pattern(&self) -> Option<ThreadPattern> {
+        if self.thread_ids.is_empty() {
+            return None;
+        }
+        let per_thread = self.email_count() as f32 / self.thread_count() as f32;
+        if per_thread > 3.0 {
+            Some(ThreadPattern::MailingList)
+        } else if per_thread > 2.0 {
+            Some(ThreadPattern::Conversation)
+        } else {
+            None
+        }
+    }
+}
+
+/// Notable thread shape of a cluster, shown in review
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadPattern {
+    /// More than 3 messages per thread
+    MailingList,
+    /// 2–3 messages per thread: may be `Personal` mail rather than bulk
+    Conversation,
 }
 
 /// Decision made by user for a cluster
@@ -68,13 +157,118 @@ pub struct ClusterDecision {
     /// Subject pattern for subject-based clusters
     pub subject_pattern: Option<String>,
     pub message_ids: Vec<String>,
-    pub label: String,
+    pub label: LabelName,
     pub should_archive: bool,
     pub action: DecisionAction,
     /// Existing filter ID if this cluster had a matching filter
     pub existing_filter_id: Option<String>,
     /// Whether the existing filter needs to be updated (settings changed)
     pub needs_filter_update: bool,
+    /// Reason given when the cluster was excluded (kept for audit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclusion_reason: Option<String>,
+    /// Filter parsed from a Gmail query typed during review; replaces the cluster's criteria
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_filter: Option<FilterRule>,
+    /// Secondary labels applied together with `label` (e.g. its parent label)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_labels: Vec<LabelName>,
+    /// Sender patterns of clusters from other domains merged into this one, matched
+    /// by the filter alongside its own sender (see [`resolve_merges`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged_senders: Vec<String>,
+    /// `List-ID` of a mailing list cluster, matched by the filter with `list:<id>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list_id: Option<String>,
+}
+
+impl ClusterDecision {
+    /// Accept decision that creates `rule` as-is, for filters imported from elsewhere
+    pub fn from_filter_rule(rule: FilterRule) -> Self {
+        let sender = rule.from_pattern.clone().unwrap_or_default();
+        let sender_domain = sender
+            .rsplit_once('@')
+            .map(|(_, domain)| domain.to_string())
+            .unwrap_or_default();
+
+        Self {
+            sender_domain,
+            sender_email: sender,
+            is_specific_sender: rule.is_specific_sender,
+            excluded_senders: rule.excluded_senders.clone(),
+            subject_pattern: rule.subject_keywords.first().cloned(),
+            message_ids: vec![],
+            label: rule.target_label.clone(),
+            should_archive: rule.should_archive,
+            action: DecisionAction::Accept,
+            existing_filter_id: None,
+            needs_filter_update: false,
+            exclusion_reason: None,
+            custom_filter: Some(rule),
+            additional_labels: vec![],
+            merged_senders: vec![],
+            list_id: None,
+        }
+    }
+
+    /// Key of the cluster this decision was made for, as listed by `[M]` in review
+    pub fn cluster_key(&self) -> String {
+        let base = if self.is_specific_sender {
+            self.sender_email.clone()
+        } else {
+            format!("*@{}", self.sender_domain)
+        };
+        cluster_key_of(
+            base,
+            self.subject_pattern.as_deref(),
+            self.list_id.as_deref(),
+        )
+    }
+
+    /// Sender criteria of the decision's filter: its sender or `*@domain`, widened by
+    /// [`Self::merged_senders`], e.g. `*@shop.com OR *@shop-mail.com`
+    pub fn from_pattern(&self) -> String {
+        let own = if self.is_specific_sender {
+            self.sender_email.clone()
+        } else {
+            format!("*@{}", self.sender_domain)
+        };
+        std::iter::once(own)
+            .chain(self.merged_senders.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    }
+
+    /// `from_pattern` of the decision's filter; `None` for a whole mailing list,
+    /// which its `list:` term matches on its own
+    pub fn filter_from_pattern(&self) -> Option<String> {
+        if self.list_id.is_some() && !self.is_specific_sender {
+            None
+        } else {
+            Some(self.from_pattern())
+        }
+    }
+
+    /// `extra_terms` of the decision's filter: `list:<id>` for mailing list clusters
+    pub fn filter_extra_terms(&self) -> Vec<String> {
+        self.list_id
+            .iter()
+            .map(|id| format!("list:{}", id))
+            .collect()
+    }
+}
+
+/// Key of a cluster from its sender pattern, subject pattern and mailing list,
+/// e.g. `*@shop.com`, `orders@shop.com|subject:Shipped` or `*@rust-lang.org|list:dev.rust-lang.org`
+fn cluster_key_of(base: String, subject: Option<&str>, list_id: Option<&str>) -> String {
+    let mut key = base;
+    if let Some(subject) = subject {
+        key.push_str(&format!("|subject:{}", subject));
+    }
+    if let Some(list_id) = list_id {
+        key.push_str(&format!("|list:{}", list_id));
+    }
+    key
 }
 
 /// Type of decision action
@@ -87,6 +281,12 @@ pub enum DecisionAction {
     Delete,
     /// Permanently exclude this cluster from future reviews (saved to exclusions file)
     Exclude,
+    /// Fold this cluster into the cluster whose key is `into_domain` (e.g.
+    /// `*@shop.com` or `orders@shop.com`) instead of creating a filter of its own
+    /// (see [`resolve_merges`])
+    Merge {
+        into_domain: String,
+    },
 }
 
 /// Source of a cluster - whether from email scan or synthetic (orphaned filter)
@@ -101,6 +301,16 @@ pub enum ClusterSource {
     ExcludedPattern,
 }
 
+/// Review decisions saved to disk so a quit or crashed session can be resumed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewProgress {
+    pub decisions: HashMap<String, ClusterDecision>,
+    pub current_index: usize,
+    #[serde(default)]
+    pub deferred_indices: Vec<usize>,
+    pub saved_at: DateTime<Utc>,
+}
+
 /// Entry in the undo history
 #[derive(Debug, Clone)]
 struct HistoryEntry {
@@ -109,6 +319,20 @@ struct HistoryEntry {
     decision: Option<ClusterDecision>,
 }
 
+/// Match count query started by `[P]`; dropping it cancels the query
+struct PreviewTask {
+    /// Index of the cluster being previewed
+    index: usize,
+    task: JoinHandle<()>,
+    result: Arc<Mutex<Option<std::result::Result<usize, String>>>>,
+}
+
+impl Drop for PreviewTask {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
 /// Interactive review session
 pub struct ReviewSession {
     clusters: Vec<EmailCluster>,
@@ -125,6 +349,38 @@ pub struct ReviewSession {
     exclusion_manager: ExclusionManager,
     /// Path to save exclusions file
     exclusions_path: PathBuf,
+    /// One-off message shown above the next cluster (e.g. exclusion confirmation)
+    status_message: Option<String>,
+    /// Whether mouse capture is enabled while the session runs
+    mouse_enabled: bool,
+    /// Clickable `[Y]`/`[N]`/`[S]` buttons from the last `display_current`
+    buttons: Vec<ButtonRegion>,
+    /// Where progress is auto-saved while the session runs
+    progress_path: Option<PathBuf>,
+    /// Colors and box characters used for drawing
+    theme: Theme,
+    /// Only clusters of this category are shown while set
+    category_filter: Option<EmailCategory>,
+    /// Only clusters whose sender or subjects contain this text are shown while set
+    search_query: Option<String>,
+    /// Search text being typed after `/`, until Enter or Escape
+    search_input: Option<String>,
+    /// Whether the `[I]` statistics overlay replaces the sample subjects
+    show_stats: bool,
+    /// When the session started, for elapsed and remaining time estimates
+    session_start: Instant,
+    /// Client and runtime used for `[P]` match count previews
+    preview_client: Option<(Arc<dyn GmailClient>, tokio::runtime::Handle)>,
+    /// Whether a `[P]` match count query is running for the current cluster
+    cluster_preview_pending: bool,
+    /// The running `[P]` query, if any
+    preview_task: Option<PreviewTask>,
+    /// Configured minimum confidence per category, shown next to the proposed rule
+    min_confidence: HashMap<EmailCategory, f32>,
+    /// Sender domains that appear to belong to the same company
+    domain_groups: Vec<DomainGroup>,
+    /// Sender domains with an unusual burst of mail, marked with ⚠
+    spiking_domains: HashSet<String>,
 }
 
 impl ReviewSession {
@@ -187,7 +443,132 @@ impl ReviewSession {
             existing_filter_count,
             exclusion_manager,
             exclusions_path,
+            status_message: None,
+            mouse_enabled: true,
+            buttons: Vec::new(),
+            progress_path: None,
+            theme: Theme::default(),
+            category_filter: None,
+            search_query: None,
+            search_input: None,
+            show_stats: false,
+            session_start: Instant::now(),
+            preview_client: None,
+            cluster_preview_pending: false,
+            preview_task: None,
+            min_confidence: HashMap::new(),
+            domain_groups: Vec::new(),
+            spiking_domains: HashSet::new(),
+        }
+    }
+
+    /// Enable `[P]` match count previews using `client`
+    ///
+    /// Must be called from within a Tokio runtime; queries are spawned onto it.
+    pub fn with_preview_client(mut self, client: Arc<dyn GmailClient>) -> Self {
+        self.preview_client = Some((client, tokio::runtime::Handle::current()));
+        self
+    }
+
+    /// Show the classifier's minimum confidence for each cluster's category
+    pub fn with_min_confidence(mut self, thresholds: HashMap<EmailCategory, f32>) -> Self {
+        self.min_confidence = thresholds;
+        self
+    }
+
+    /// Point out clusters whose domain belongs to one of `groups`
+    pub fn with_domain_groups(mut self, groups: Vec<DomainGroup>) -> Self {
+        self.domain_groups = groups;
+        self
+    }
+
+    /// Mark clusters from domains with a volume spike
+    pub fn with_anomalies(mut self, spikes: &[VolumeSpike]) -> Self {
+        self.spiking_domains = spikes.iter().map(|s| s.sender_domain.clone()).collect();
+        self
+    }
+
+    /// Draw the session with `theme` instead of the default colors and box style
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Restore a session from progress saved by `save_progress`
+    pub fn load_progress(path: &Path, clusters: Vec<EmailCluster>) -> Result<Self> {
+        Self::new(clusters).with_progress(path)
+    }
+
+    /// Restore decisions and position saved by `save_progress`.
+    ///
+    /// Decisions for clusters that no longer exist are dropped.
+    pub fn with_progress(mut self, path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| GmailError::Unknown(format!("Failed to read review progress: {}", e)))?;
+        let progress: ReviewProgress = serde_json::from_str(&json)
+            .map_err(|e| GmailError::Unknown(format!("Failed to parse review progress: {}", e)))?;
+
+        let keys: HashSet<String> = self.clusters.iter().map(Self::cluster_key).collect();
+        self.decisions = progress
+            .decisions
+            .into_iter()
+            .filter(|(key, _)| keys.contains(key))
+            .collect();
+        self.deferred_indices = progress
+            .deferred_indices
+            .into_iter()
+            .filter(|&i| i < self.clusters.len())
+            .collect();
+        self.current_index = progress.current_index.min(self.clusters.len());
+        self.status_message = Some(format!(
+            "Resumed review saved at {} ({} decisions)",
+            progress.saved_at.format("%Y-%m-%d %H:%M"),
+            self.decisions.len()
+        ));
+
+        Ok(self)
+    }
+
+    /// Auto-save progress to `path` every 30 seconds and when quitting
+    pub fn with_autosave(mut self, path: PathBuf) -> Self {
+        self.progress_path = Some(path);
+        self
+    }
+
+    /// Write the current decisions and position to `path`
+    pub fn save_progress(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| GmailError::Unknown(format!("Failed to create directory: {}", e)))?;
         }
+
+        let progress = ReviewProgress {
+            decisions: self.decisions.clone(),
+            current_index: self.current_index,
+            deferred_indices: self.deferred_indices.clone(),
+            saved_at: Utc::now(),
+        };
+        let json = serde_json::to_string_pretty(&progress).map_err(|e| {
+            GmailError::Unknown(format!("Failed to serialize review progress: {}", e))
+        })?;
+        std::fs::write(path, json)
+            .map_err(|e| GmailError::Unknown(format!("Failed to write review progress: {}", e)))?;
+        Ok(())
+    }
+
+    /// Save progress to the auto-save file, if one is configured
+    fn autosave(&self) {
+        if let Some(path) = &self.progress_path {
+            if let Err(e) = self.save_progress(path) {
+                tracing::warn!("Failed to auto-save review progress: {}", e);
+            }
+        }
+    }
+
+    /// Enable or disable mouse scrolling and button clicks
+    pub fn with_mouse(mut self, enabled: bool) -> Self {
+        self.mouse_enabled = enabled;
+        self
     }
 
     /// Run the interactive review session
@@ -203,6 +584,9 @@ impl ReviewSession {
         let result = self.run_inner();
 
         // Always restore terminal
+        if self.mouse_enabled {
+            let _ = execute!(io::stdout(), DisableMouseCapture);
+        }
         let _ = terminal::disable_raw_mode();
         let _ = execute!(io::stdout(), cursor::Show);
 
@@ -212,35 +596,78 @@ impl ReviewSession {
     fn run_inner(&mut self) -> Result<Vec<ClusterDecision>> {
         let mut stdout = io::stdout();
 
+        if self.mouse_enabled {
+            execute!(stdout, EnableMouseCapture)
+                .map_err(|e| GmailError::Unknown(format!("Terminal error: {}", e)))?;
+        }
+
+        let mut last_save = Instant::now();
+        let mut redraw = true;
+
         loop {
-            // Clear screen and display current cluster
-            execute!(
-                stdout,
-                terminal::Clear(ClearType::All),
-                cursor::MoveTo(0, 0)
-            )
-            .map_err(|e| GmailError::Unknown(format!("Terminal error: {}", e)))?;
+            if redraw {
+                // Clear screen and display current cluster
+                execute!(
+                    stdout,
+                    terminal::Clear(ClearType::All),
+                    cursor::MoveTo(0, 0)
+                )
+                .map_err(|e| GmailError::Unknown(format!("Terminal error: {}", e)))?;
+
+                self.display_current(&mut stdout)?;
+                stdout
+                    .flush()
+                    .map_err(|e| GmailError::Unknown(e.to_string()))?;
+            }
+
+            // Periodically save progress so decisions survive a quit or crash
+            if last_save.elapsed() >= AUTOSAVE_INTERVAL {
+                self.autosave();
+                last_save = Instant::now();
+            }
 
-            self.display_current(&mut stdout)?;
-            stdout
-                .flush()
-                .map_err(|e| GmailError::Unknown(e.to_string()))?;
+            // Wait for input, waking up in time for the next auto-save or preview check
+            let mut timeout = AUTOSAVE_INTERVAL.saturating_sub(last_save.elapsed());
+            if self.cluster_preview_pending {
+                timeout = timeout.min(PREVIEW_POLL_INTERVAL);
+            }
+            let ready = event::poll(timeout)
+                .map_err(|e| GmailError::Unknown(format!("Input error: {}", e)))?;
+            redraw = ready;
+            if !ready {
+                redraw = self.poll_preview();
+                continue;
+            }
 
-            // Wait for key input
-            // Only handle Press events to avoid key bounce on Windows
-            // (Windows sends Press, Repeat, and Release events for a single key press)
-            if let Event::Key(key_event) = event::read()
+            // Wait for key or mouse input
+            let action = match event::read()
                 .map_err(|e| GmailError::Unknown(format!("Input error: {}", e)))?
             {
-                if key_event.kind != KeyEventKind::Press {
-                    continue;
+                // Only handle Press events to avoid key bounce on Windows
+                // (Windows sends Press, Repeat, and Release events for a single key press)
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    self.handle_key(key_event)?
+                }
+                Event::Mouse(mouse_event) => self.handle_mouse(mouse_event)?,
+                _ => SessionAction::Continue,
+            };
+
+            // Navigating away cancels a running preview
+            self.poll_preview();
+
+            match action {
+                SessionAction::Continue => continue,
+                SessionAction::Quit => {
+                    // Keep decisions so far for --resume-review
+                    self.autosave();
+                    break;
                 }
-                match self.handle_key(key_event)? {
-                    SessionAction::Continue => continue,
-                    SessionAction::Quit => break,
-                    SessionAction::Finish => {
-                        return Ok(self.decisions.values().cloned().collect());
+                SessionAction::Finish => {
+                    // Decisions are about to be applied, nothing left to resume
+                    if let Some(path) = &self.progress_path {
+                        let _ = std::fs::remove_file(path);
                     }
+                    return Ok(self.decisions.values().cloned().collect());
                 }
             }
         }
@@ -248,7 +675,7 @@ impl ReviewSession {
         Ok(Vec::new())
     }
 
-    fn display_current(&self, stdout: &mut io::Stdout) -> Result<()> {
+    fn display_current(&mut self, stdout: &mut io::Stdout) -> Result<()> {
         let total = self.clusters.len();
         let reviewed = self.decisions.len();
         let deferred = self.deferred_indices.len();
@@ -256,22 +683,33 @@ impl ReviewSession {
         // Dynamic box width based on terminal size (inner content width, not including borders)
         let w = get_display_width();
 
+        // Screen rows of successive lines, used to locate clickable buttons
+        let mut rows = 0u16..;
+        let mut buttons = Vec::new();
+
         // Helper macro for raw mode: \r\n needed (not just \n)
         macro_rules! out {
-            ($($arg:tt)*) => {
-                write!(stdout, "{}\r\n", format!($($arg)*))
+            ($($arg:tt)*) => {{
+                let text = format!($($arg)*);
+                let row = rows.next().unwrap_or(u16::MAX);
+                buttons.extend(find_buttons(&text, row));
+                write!(stdout, "{}\r\n", text)
                     .map_err(|e| GmailError::Unknown(e.to_string()))?
-            };
+            }};
         }
 
-        // Helper to create a padded line
+        let theme = &self.theme;
+        let b = &theme.chars;
+
+        // Helper to create a padded line (ANSI color codes don't count towards the width)
         let line = |content: &str| -> String {
-            let chars: Vec<char> = content.chars().collect();
-            let len = chars.len();
+            let len = visible_len(content);
             if len >= w {
-                format!("│ {} │", chars.iter().take(w).collect::<String>())
+                let plain: String = strip_ansi(content).chars().take(w).collect();
+                format!("{} {} {}", b.vertical, plain, b.vertical)
             } else {
-                format!("│ {}{} │", content, " ".repeat(w - len))
+                let padding = " ".repeat(w - len);
+                format!("{v} {content}{padding} {v}", v = b.vertical)
             }
         };
 
@@ -279,12 +717,19 @@ impl ReviewSession {
         let progress_width = (w / 2).min(60);
         let filled = (reviewed * progress_width) / total.max(1);
         let bar: String = (0..progress_width)
-            .map(|i| if i < filled { '█' } else { '░' })
+            .map(|i| {
+                if i < filled {
+                    b.bar_filled
+                } else {
+                    b.bar_empty
+                }
+            })
             .collect();
 
-        let top = format!("┌{}┐", "─".repeat(w + 2));
-        let mid = format!("├{}┤", "─".repeat(w + 2));
-        let bottom = format!("└{}┘", "─".repeat(w + 2));
+        let rule = b.horizontal.to_string();
+        let top = format!("{}{}{}", b.top_left, rule.repeat(w + 2), b.top_right);
+        let mid = format!("{}{}{}", b.mid_left, rule.repeat(w + 2), b.mid_right);
+        let bottom = format!("{}{}{}", b.bottom_left, rule.repeat(w + 2), b.bottom_right);
 
         out!("{}", top);
         let new_count = total - self.existing_filter_count;
@@ -297,6 +742,31 @@ impl ReviewSession {
             format!("Progress: [{}] {:>3}/{:<3} clusters", bar, reviewed, total)
         };
         out!("{}", line(&progress_text));
+        if let Some(category) = &self.category_filter {
+            let shown = self
+                .clusters
+                .iter()
+                .filter(|c| &c.suggested_category == category)
+                .count();
+            out!(
+                "{}",
+                line(&format!(
+                    "(showing: {:?}, {} of {} clusters)  [F] Change filter",
+                    category, shown, total
+                ))
+            );
+        }
+        if let Some(input) = &self.search_input {
+            out!(
+                "{}",
+                line(&format!("/ {}_  (Enter to search, Esc to clear)", input))
+            );
+        } else if let Some(query) = &self.search_query {
+            out!("{}", line(&format!("/ {}  (Esc to clear)", query)));
+        }
+        if let Some(message) = &self.status_message {
+            out!("{}", line(message));
+        }
         out!("{}", mid);
 
         // Show banner for orphaned/excluded clusters
@@ -304,20 +774,36 @@ impl ReviewSession {
             let cluster = &self.clusters[self.current_index];
             match cluster.source {
                 ClusterSource::OrphanedFilter => {
-                    let banner_top = format!("┌─ ORPHANED FILTER {}", "─".repeat(w.saturating_sub(19)));
-                    let banner_mid = "│ No matching emails found in scan period - DELETE recommended";
-                    let banner_bottom = format!("└{}", "─".repeat(w + 1));
+                    let banner_top = format!(
+                        "{}{} ORPHANED FILTER {}",
+                        b.top_left,
+                        b.horizontal,
+                        rule.repeat(w.saturating_sub(19))
+                    );
+                    let banner_mid = format!(
+                        "{} No matching emails found in scan period - DELETE recommended",
+                        b.vertical
+                    );
+                    let banner_bottom = format!("{}{}", b.bottom_left, rule.repeat(w + 1));
                     out!("{}", banner_top);
-                    out!("{}", line(banner_mid));
+                    out!("{}", line(&banner_mid));
                     out!("{}", banner_bottom);
                     out!("{}", mid);
                 }
                 ClusterSource::ExcludedPattern => {
-                    let banner_top = format!("┌─ EXCLUDED PATTERN {}", "─".repeat(w.saturating_sub(20)));
-                    let banner_mid = "│ Filter matches permanently excluded pattern - DELETE recommended";
-                    let banner_bottom = format!("└{}", "─".repeat(w + 1));
+                    let banner_top = format!(
+                        "{}{} EXCLUDED PATTERN {}",
+                        b.top_left,
+                        b.horizontal,
+                        rule.repeat(w.saturating_sub(20))
+                    );
+                    let banner_mid = format!(
+                        "{} Filter matches permanently excluded pattern - DELETE recommended",
+                        b.vertical
+                    );
+                    let banner_bottom = format!("{}{}", b.bottom_left, rule.repeat(w + 1));
                     out!("{}", banner_top);
-                    out!("{}", line(banner_mid));
+                    out!("{}", line(&banner_mid));
                     out!("{}", banner_bottom);
                     out!("{}", mid);
                 }
@@ -330,7 +816,16 @@ impl ReviewSession {
         if self.current_index >= self.clusters.len() {
             // All done - show summary
             out!("{}", line(""));
-            out!("{}", line("All clusters reviewed!"));
+            match &self.category_filter {
+                Some(category) => {
+                    out!(
+                        "{}",
+                        line(&format!("All {:?} clusters reviewed!", category))
+                    );
+                    out!("{}", line("Press [F] to review another category"));
+                }
+                None => out!("{}", line("All clusters reviewed!")),
+            }
             out!("{}", line(""));
             out!("{}", line("Summary:"));
             out!("{}", line(&format!("  Reviewed: {:>4}", reviewed)));
@@ -345,26 +840,7 @@ impl ReviewSession {
             let archive_status = if cluster.should_archive { "YES" } else { "NO" };
 
             // Build the filter query for display
-            let filter_query = if let Some(subject) = &cluster.subject_pattern {
-                // Subject-based cluster
-                if cluster.is_specific_sender {
-                    format!("from:({}) subject:({})", cluster.sender_email, subject)
-                } else {
-                    format!("from:(*@{}) subject:({})", cluster.sender_domain, subject)
-                }
-            } else if cluster.is_specific_sender {
-                format!("from:({})", cluster.sender_email)
-            } else if cluster.excluded_senders.is_empty() {
-                format!("from:(*@{})", cluster.sender_domain)
-            } else {
-                let exclusions = cluster
-                    .excluded_senders
-                    .iter()
-                    .map(|s| format!("-from:({})", s))
-                    .collect::<Vec<_>>()
-                    .join(" ");
-                format!("from:(*@{}) {}", cluster.sender_domain, exclusions)
-            };
+            let filter_query = cluster_query(cluster);
 
             // Show cluster name based on type
             let cluster_name = if let Some(subject) = &cluster.subject_pattern {
@@ -372,6 +848,8 @@ impl ReviewSession {
                 format!("{} + \"{}\"", cluster.sender_email, subject)
             } else if cluster.is_specific_sender {
                 format!("{} (specific sender)", cluster.sender_email)
+            } else if let Some(list_id) = &cluster.list_id {
+                format!("list:{}", list_id)
             } else if !cluster.excluded_senders.is_empty() {
                 format!(
                     "*@{} (excl. {} senders)",
@@ -381,23 +859,53 @@ impl ReviewSession {
             } else {
                 format!("*@{}", cluster.sender_domain)
             };
+            let cluster_name = if self.spiking_domains.contains(&cluster.sender_domain) {
+                format!("⚠ {}", cluster_name)
+            } else {
+                cluster_name
+            };
 
             // Truncation lengths scale with width
-            let name_max = w.saturating_sub(22); // "CLUSTER: " + " (XX emails)"
+            let name_max = w.saturating_sub(34); // "CLUSTER: " + " - XX emails (XX threads)"
             let query_max = w.saturating_sub(12); // "  Query:   "
             let label_max = w.saturating_sub(12); // "  Label:   "
             let subject_max = w.saturating_sub(6); // "  • "
 
+            // Badge for clusters revisited after a decision (e.g. via undo or --resume-review)
+            let badge = match self
+                .decisions
+                .get(&Self::cluster_key(cluster))
+                .map(|d| &d.action)
+            {
+                Some(DecisionAction::Accept | DecisionAction::Custom(_)) => {
+                    format!("  {}", theme.paint("ACCEPTED", theme.accepted_badge))
+                }
+                Some(DecisionAction::Reject) => {
+                    format!("  {}", theme.paint("REJECTED", theme.rejected_badge))
+                }
+                _ => String::new(),
+            };
+
             out!(
                 "{}",
                 line(&format!(
-                    "CLUSTER: {} ({} emails)",
+                    "CLUSTER: {} - {}{}",
                     truncate_str(&cluster_name, name_max),
-                    cluster.email_count()
+                    cluster.volume_summary(),
+                    badge
                 ))
             );
             out!("{}", mid);
-            out!("{}", line("Proposed filter rule:"));
+            match self.min_confidence.get(&cluster.suggested_category) {
+                Some(threshold) => out!(
+                    "{}",
+                    line(&format!(
+                        "Proposed filter rule:  (confidence threshold: {:.0}%)",
+                        threshold * 100.0
+                    ))
+                ),
+                None => out!("{}", line("Proposed filter rule:")),
+            }
             out!(
                 "{}",
                 line(&format!(
@@ -412,11 +920,58 @@ impl ReviewSession {
                     truncate_str(&cluster.suggested_label, label_max)
                 ))
             );
+            if let Some(reason) = &cluster.top_reason {
+                out!(
+                    "{}",
+                    line(&format!("           (classified because: {})", reason))
+                );
+            }
             out!("{}", line(&format!("  Archive: {}", archive_status)));
+            match cluster.thread_pattern() {
+                Some(ThreadPattern::MailingList) => out!(
+                    "{}",
+                    line(&format!(
+                        "  {} High volume per thread - likely mailing list",
+                        b.warning
+                    ))
+                ),
+                Some(ThreadPattern::Conversation) => out!(
+                    "{}",
+                    line(&format!(
+                        "  {} Multi-message threads - may be conversation",
+                        b.two_way
+                    ))
+                ),
+                None => {}
+            }
+            if let Some(hint) = self.domain_group_hint(cluster) {
+                out!(
+                    "{}",
+                    line(&format!("  Related: {}", truncate_str(&hint, label_max)))
+                );
+            }
+            if let Some(count) = cluster.preview_count {
+                let count = if count >= PREVIEW_LIMIT {
+                    format!("{}+", PREVIEW_LIMIT)
+                } else {
+                    count.to_string()
+                };
+                out!("{}", line(&format!("  Matches: {} emails in Gmail", count)));
+            }
             out!("{}", mid);
 
-            // Show sample subjects or "No recent emails" message
-            if cluster.sample_subjects.is_empty() {
+            // Show statistics overlay, sample subjects or "No recent emails" message
+            if self.show_stats {
+                for stats_line in self.stats_lines(self.session_start.elapsed()) {
+                    out!("{}", line(&stats_line));
+                }
+            } else if self.cluster_preview_pending {
+                out!("{}", line("Match count:"));
+                out!("{}", line("  Querying Gmail for match count..."));
+                for _ in 0..3 {
+                    out!("{}", line(""));
+                }
+            } else if cluster.sample_subjects.is_empty() {
                 out!("{}", line("Sample subjects:"));
                 out!("{}", line("  (No recent emails found)"));
                 // Pad remaining lines to maintain layout
@@ -427,7 +982,7 @@ impl ReviewSession {
                 out!("{}", line("Sample subjects:"));
                 for subject in cluster.sample_subjects.iter().take(4) {
                     let truncated = truncate_str(subject, subject_max);
-                    out!("{}", line(&format!("  • {}", truncated)));
+                    out!("{}", line(&format!("  {} {}", b.bullet, truncated)));
                 }
 
                 // Pad remaining lines if fewer than 4 subjects
@@ -449,16 +1004,16 @@ impl ReviewSession {
 
                 // Format with colors based on differences
                 let (cur_label, prop_label) =
-                    format_field_pair(current_label, &cluster.suggested_label, colors::RED);
-                let (cur_archive, prop_archive) =
-                    format_field_pair(current_archive, archive_status, colors::BLUE);
+                    theme.field_pair(current_label, &cluster.suggested_label);
+                let (cur_archive, prop_archive) = theme.field_pair(current_archive, archive_status);
 
-                out!(
-                    "{}",
-                    line("⚠ EXISTING FILTER - [S] keeps current, [Y] updates to proposed")
+                let banner = format!(
+                    "{} EXISTING FILTER - [S] keeps current, [Y] updates to proposed",
+                    b.warning
                 );
+                out!("{}", line(&theme.paint(&banner, theme.existing_filter)));
                 out!("{}", mid);
-                // Note: line() doesn't account for ANSI codes in length, so we format manually
+                // Label padding counts color codes, so the archive column stays aligned
                 let cur_line = format!(
                     "  Current:  Label: {:30}  Archive: {}",
                     cur_label, cur_archive
@@ -469,13 +1024,26 @@ impl ReviewSession {
                 );
                 out!("{}", line(&cur_line));
                 out!("{}", line(&prop_line));
+                if let Some(query) = &cluster.existing_filter_query {
+                    for (i, criterion) in query.lines().enumerate() {
+                        let heading = if i == 0 { "  Criteria:" } else { "" };
+                        out!(
+                            "{}",
+                            line(&format!(
+                                "{:<12}{}",
+                                heading,
+                                truncate_str(criterion, query_max)
+                            ))
+                        );
+                    }
+                }
                 out!("{}", mid);
 
                 // Check if default action is Delete (for orphaned/excluded)
                 if matches!(cluster.default_action, Some(DecisionAction::Delete)) {
                     out!(
                         "{}",
-                        line("[Enter/D] DELETE filter  [Y] Keep  [E] Exclude permanently")
+                        line("[Enter/D] DELETE filter  [Y] Keep  [E] Exclude")
                     );
                     out!("{}", line("[S] Skip for now  [?] Help"));
                 } else {
@@ -485,7 +1053,7 @@ impl ReviewSession {
                     );
                     out!(
                         "{}",
-                        line("[D] DELETE filter  [E] Exclude permanently  [?] Help")
+                        line("[D] DELETE filter  [E] Exclude  [?] Help")
                     );
                     out!(
                         "{}",
@@ -499,24 +1067,109 @@ impl ReviewSession {
                 );
                 out!(
                     "{}",
-                    line("[E] Exclude permanently  [A] Toggle archive  [L] Label")
+                    line("[E] Exclude  [A] Toggle archive  [L] Label  [P] Preview")
+                );
+                out!(
+                    "{}",
+                    line("[Shift+Q] Gmail query  [M] Merge  [F] Filter  [?] Help")
                 );
-                out!("{}", line("[?] Help"));
             }
         }
 
         out!("{}", bottom);
 
+        self.buttons = buttons;
+
         Ok(())
     }
 
+    /// Scroll to move between clusters; click a `[Y]`/`[N]`/`[S]` button to press it
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<SessionAction> {
+        // Clicks would otherwise be typed into the search text
+        if self.search_input.is_some() {
+            return Ok(SessionAction::Continue);
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown => {
+                if self.current_index < self.clusters.len() {
+                    self.status_message = None;
+                    self.advance();
+                }
+                Ok(SessionAction::Continue)
+            }
+            MouseEventKind::ScrollUp => {
+                self.status_message = None;
+                self.undo();
+                Ok(SessionAction::Continue)
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                match button_at(&self.buttons, mouse.column, mouse.row) {
+                    Some(key) => {
+                        self.handle_key(KeyEvent::new(KeyCode::Char(key), KeyModifiers::NONE))
+                    }
+                    None => Ok(SessionAction::Continue),
+                }
+            }
+            _ => Ok(SessionAction::Continue),
+        }
+    }
+
     fn handle_key(&mut self, key: KeyEvent) -> Result<SessionAction> {
         // Handle Ctrl+C to quit
         if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
             return Ok(SessionAction::Quit);
         }
+        self.status_message = None;
+
+        // While typing a search, keys edit the search text instead of acting on clusters
+        if let Some(input) = &mut self.search_input {
+            match key.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let query = input.trim().to_string();
+                    self.search_input = None;
+                    self.set_search((!query.is_empty()).then_some(query));
+                }
+                KeyCode::Esc => {
+                    self.search_input = None;
+                    self.set_search(None);
+                }
+                _ => {}
+            }
+            return Ok(SessionAction::Continue);
+        }
+
+        // Any key dismisses the statistics overlay
+        if self.show_stats {
+            self.show_stats = false;
+            return Ok(SessionAction::Continue);
+        }
 
         match key.code {
+            KeyCode::Char('p') | KeyCode::Char('P') => {
+                if self.current_index < self.clusters.len() {
+                    self.start_preview();
+                }
+                Ok(SessionAction::Continue)
+            }
+            KeyCode::Char('i') | KeyCode::Char('I') => {
+                self.show_stats = true;
+                Ok(SessionAction::Continue)
+            }
+            KeyCode::Char('/') => {
+                self.search_input = Some(self.search_query.clone().unwrap_or_default());
+                Ok(SessionAction::Continue)
+            }
+            KeyCode::Esc => {
+                if self.search_query.is_some() {
+                    self.set_search(None);
+                }
+                Ok(SessionAction::Continue)
+            }
             KeyCode::Char('y') | KeyCode::Enter => {
                 if self.current_index < self.clusters.len() {
                     let cluster = &self.clusters[self.current_index];
@@ -579,8 +1232,13 @@ impl ReviewSession {
             }
             KeyCode::Char('e') | KeyCode::Char('E') => {
                 // Exclude permanently - saves to exclusions file and treats as reject for this run
-                if self.current_index < self.clusters.len() {
-                    self.exclude_current()?;
+                if self.current_index < self.clusters.len() && self.exclude_current()? {
+                    self.advance();
+                }
+                Ok(SessionAction::Continue)
+            }
+            KeyCode::Char('m') | KeyCode::Char('M') => {
+                if self.current_index < self.clusters.len() && self.merge_current()? {
                     self.advance();
                 }
                 Ok(SessionAction::Continue)
@@ -589,10 +1247,20 @@ impl ReviewSession {
                 self.undo();
                 Ok(SessionAction::Continue)
             }
+            KeyCode::Char('f') | KeyCode::Char('F') => {
+                self.prompt_category_filter();
+                Ok(SessionAction::Continue)
+            }
             KeyCode::Char('?') => {
                 self.show_help()?;
                 Ok(SessionAction::Continue)
             }
+            KeyCode::Char('Q') => {
+                if self.current_index < self.clusters.len() {
+                    self.custom_query()?;
+                }
+                Ok(SessionAction::Continue)
+            }
             KeyCode::Char('q') => Ok(SessionAction::Quit),
             KeyCode::Char('w') | KeyCode::Char('W') => {
                 if self.current_index >= self.clusters.len() {
@@ -605,20 +1273,27 @@ impl ReviewSession {
         }
     }
 
+    /// "3 senders appear to be from the same company" note for a cluster in a domain group
+    fn domain_group_hint(&self, cluster: &EmailCluster) -> Option<String> {
+        let group = self
+            .domain_groups
+            .iter()
+            .find(|group| group.contains(&cluster.sender_domain))?;
+        let others: Vec<&str> = std::iter::once(&group.canonical_domain)
+            .chain(&group.related_domains)
+            .map(String::as_str)
+            .filter(|domain| *domain != cluster.sender_domain)
+            .collect();
+        Some(format!(
+            "{} senders appear to be from the same company (also {})",
+            group.domain_count(),
+            others.join(", ")
+        ))
+    }
+
     /// Get a unique key for a cluster (specific sender email or domain, plus subject pattern if any)
     fn cluster_key(cluster: &EmailCluster) -> String {
-        let base = if cluster.is_specific_sender {
-            cluster.sender_email.clone()
-        } else {
-            format!("*@{}", cluster.sender_domain)
-        };
-
-        // Include subject pattern in key to differentiate subject-based clusters
-        if let Some(subject) = &cluster.subject_pattern {
-            format!("{}|subject:{}", base, subject)
-        } else {
-            base
-        }
+        cluster.key()
     }
 
     fn accept_current(&mut self) {
@@ -639,11 +1314,16 @@ impl ReviewSession {
                 excluded_senders: cluster.excluded_senders.clone(),
                 subject_pattern: cluster.subject_pattern.clone(),
                 message_ids: cluster.message_ids.clone(),
-                label: cluster.suggested_label.clone(),
+                label: LabelName::new(cluster.suggested_label.clone()),
                 should_archive: cluster.should_archive,
                 action: DecisionAction::Accept,
                 existing_filter_id: cluster.existing_filter_id.clone(),
                 needs_filter_update: false, // Accepting as-is
+                exclusion_reason: None,
+                custom_filter: None,
+                additional_labels: vec![],
+                merged_senders: vec![],
+                list_id: cluster.list_id.clone(),
             };
 
             self.decisions.insert(key, decision);
@@ -668,11 +1348,16 @@ impl ReviewSession {
                 excluded_senders: cluster.excluded_senders.clone(),
                 subject_pattern: cluster.subject_pattern.clone(),
                 message_ids: cluster.message_ids.clone(),
-                label: String::new(), // No label
+                label: LabelName::default(), // No label
                 should_archive: false,
                 action: DecisionAction::Reject,
                 existing_filter_id: cluster.existing_filter_id.clone(),
                 needs_filter_update: cluster.existing_filter_id.is_some(), // Need to delete if exists
+                exclusion_reason: None,
+                custom_filter: None,
+                additional_labels: vec![],
+                merged_senders: vec![],
+                list_id: cluster.list_id.clone(),
             };
 
             self.decisions.insert(key, decision);
@@ -697,18 +1382,67 @@ impl ReviewSession {
                 excluded_senders: cluster.excluded_senders.clone(),
                 subject_pattern: cluster.subject_pattern.clone(),
                 message_ids: cluster.message_ids.clone(),
-                label: String::new(),
+                label: LabelName::default(),
                 should_archive: false,
                 action: DecisionAction::Delete,
                 existing_filter_id: cluster.existing_filter_id.clone(),
                 needs_filter_update: false, // Not updating, deleting
+                exclusion_reason: None,
+                custom_filter: None,
+                additional_labels: vec![],
+                merged_senders: vec![],
+                list_id: cluster.list_id.clone(),
             };
 
             self.decisions.insert(key, decision);
         }
     }
 
-    fn exclude_current(&mut self) -> Result<()> {
+    /// Prompt for how long to exclude the cluster and an optional reason.
+    ///
+    /// Returns `None` if the user cancelled the duration prompt.
+    fn prompt_exclusion() -> Option<(Option<DateTime<Utc>>, Option<String>)> {
+        const DURATIONS: [(&str, Option<i64>); 4] = [
+            ("Exclude forever", None),
+            ("For 7 days", Some(7)),
+            ("For 30 days", Some(30)),
+            ("For 90 days", Some(90)),
+        ];
+
+        // Temporarily disable raw mode for inquire
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Show);
+
+        let options: Vec<&str> = DURATIONS.iter().map(|(name, _)| *name).collect();
+        let selected = inquire::Select::new("Exclude for how long?", options).prompt();
+        let reason = selected.is_ok().then(|| {
+            inquire::Text::new("Exclusion reason (optional, Enter to skip):")
+                .prompt()
+                .ok()
+                .map(|r| r.trim().to_string())
+                .filter(|r| !r.is_empty())
+        });
+
+        let _ = terminal::enable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Hide);
+
+        let selected = selected.ok()?;
+        let expires_at = DURATIONS
+            .iter()
+            .find(|(name, _)| *name == selected)
+            .and_then(|(_, days)| days.map(|d| Utc::now() + chrono::Duration::days(d)));
+        Some((expires_at, reason.flatten()))
+    }
+
+    /// Exclude the current cluster, returning false if the user cancelled the prompt
+    fn exclude_current(&mut self) -> Result<bool> {
+        if self.current_index >= self.clusters.len() {
+            return Ok(false);
+        }
+        let Some((expires_at, reason)) = Self::prompt_exclusion() else {
+            return Ok(false);
+        };
+
         if let Some(cluster) = self.clusters.get(self.current_index) {
             let key = Self::cluster_key(cluster);
 
@@ -719,7 +1453,8 @@ impl ReviewSession {
             });
 
             // Add to persistent exclusions
-            self.exclusion_manager.add(key.clone(), None);
+            self.exclusion_manager
+                .add_with_expiry(key.clone(), reason.clone(), expires_at);
 
             // Save exclusions immediately
             self.exclusion_manager.save_sync(&self.exclusions_path)?;
@@ -732,25 +1467,101 @@ impl ReviewSession {
                 excluded_senders: cluster.excluded_senders.clone(),
                 subject_pattern: cluster.subject_pattern.clone(),
                 message_ids: cluster.message_ids.clone(),
-                label: String::new(),
+                label: LabelName::default(),
                 should_archive: false,
                 action: DecisionAction::Exclude,
                 existing_filter_id: cluster.existing_filter_id.clone(),
                 needs_filter_update: false,
+                exclusion_reason: reason.clone(),
+                custom_filter: None,
+                additional_labels: vec![],
+                merged_senders: vec![],
+                list_id: cluster.list_id.clone(),
             };
 
+            self.status_message = Some(match &reason {
+                Some(reason) => format!("Excluded: {} (reason: {})", key, reason),
+                None => format!("Excluded: {}", key),
+            });
             self.decisions.insert(key, decision);
         }
-        Ok(())
+        Ok(true)
     }
 
-    fn toggle_archive(&mut self) {
-        if let Some(cluster) = self.clusters.get_mut(self.current_index) {
-            cluster.should_archive = !cluster.should_archive;
+    /// Merge the current cluster into any other cluster, returning false if there
+    /// is none or the user cancelled the prompt
+    fn merge_current(&mut self) -> Result<bool> {
+        let Some(cluster) = self.clusters.get(self.current_index) else {
+            return Ok(false);
+        };
+        let key = Self::cluster_key(cluster);
 
-            // If there's an existing filter and we're changing archive setting, mark for update
-            if cluster.existing_filter_id.is_some() {
-                let key = Self::cluster_key(cluster);
+        let mut options: Vec<String> = self
+            .clusters
+            .iter()
+            .map(Self::cluster_key)
+            .filter(|k| *k != key)
+            .collect();
+        options.dedup();
+        if options.is_empty() {
+            self.status_message = Some("No other cluster to merge into".to_string());
+            return Ok(false);
+        }
+
+        // Temporarily disable raw mode for inquire
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Show);
+
+        let selected = inquire::Select::new(&format!("Merge {} into:", key), options)
+            .with_page_size(10)
+            .prompt();
+
+        let _ = terminal::enable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Hide);
+
+        let Ok(into_domain) = selected else {
+            return Ok(false);
+        };
+
+        self.history.push(HistoryEntry {
+            index: self.current_index,
+            cluster: cluster.clone(),
+            decision: self.decisions.get(&key).cloned(),
+        });
+
+        let decision = ClusterDecision {
+            sender_domain: cluster.sender_domain.clone(),
+            sender_email: cluster.sender_email.clone(),
+            is_specific_sender: cluster.is_specific_sender,
+            excluded_senders: cluster.excluded_senders.clone(),
+            subject_pattern: cluster.subject_pattern.clone(),
+            message_ids: cluster.message_ids.clone(),
+            label: LabelName::default(),
+            should_archive: false,
+            action: DecisionAction::Merge {
+                into_domain: into_domain.clone(),
+            },
+            existing_filter_id: cluster.existing_filter_id.clone(),
+            needs_filter_update: false,
+            exclusion_reason: None,
+            custom_filter: None,
+            additional_labels: vec![],
+            merged_senders: vec![],
+            list_id: cluster.list_id.clone(),
+        };
+
+        self.status_message = Some(format!("Merged {} into {}", key, into_domain));
+        self.decisions.insert(key, decision);
+        Ok(true)
+    }
+
+    fn toggle_archive(&mut self) {
+        if let Some(cluster) = self.clusters.get_mut(self.current_index) {
+            cluster.should_archive = !cluster.should_archive;
+
+            // If there's an existing filter and we're changing archive setting, mark for update
+            if cluster.existing_filter_id.is_some() {
+                let key = Self::cluster_key(cluster);
                 if let Some(decision) = self.decisions.get_mut(&key) {
                     // Check if archive setting changed from original
                     let archive_changed = cluster
@@ -803,6 +1614,7 @@ impl ReviewSession {
                 };
 
                 if !label.is_empty() {
+                    let additional_labels = self.prompt_additional_labels(&label);
                     if let Some(cluster) = self.clusters.get(self.current_index) {
                         let key = Self::cluster_key(cluster);
 
@@ -832,11 +1644,16 @@ impl ReviewSession {
                             excluded_senders: cluster.excluded_senders.clone(),
                             subject_pattern: cluster.subject_pattern.clone(),
                             message_ids: cluster.message_ids.clone(),
-                            label: label.clone(),
+                            label: LabelName::new(label.clone()),
                             should_archive: cluster.should_archive,
                             action: DecisionAction::Custom(label),
                             existing_filter_id: cluster.existing_filter_id.clone(),
                             needs_filter_update: needs_update,
+                            exclusion_reason: None,
+                            custom_filter: None,
+                            additional_labels,
+                            merged_senders: vec![],
+                            list_id: cluster.list_id.clone(),
                         };
 
                         self.decisions.insert(key, decision);
@@ -852,6 +1669,153 @@ impl ReviewSession {
         Ok(())
     }
 
+    /// Let the user pick secondary labels to apply together with `primary`
+    fn prompt_additional_labels(&self, primary: &str) -> Vec<LabelName> {
+        let options = additional_label_options(primary, &self.available_labels);
+        if options.is_empty() {
+            return Vec::new();
+        }
+
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Show);
+
+        let selected =
+            inquire::MultiSelect::new("Also apply (Space to select, Enter to confirm):", options)
+                .with_page_size(10)
+                .prompt_skippable();
+
+        let _ = terminal::enable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Hide);
+
+        selected
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+            .into_iter()
+            .map(LabelName::new)
+            .collect()
+    }
+
+    /// Replace the current cluster's criteria with a filter typed as a Gmail query
+    fn custom_query(&mut self) -> Result<()> {
+        let Some(cluster) = self.clusters.get(self.current_index).cloned() else {
+            return Ok(());
+        };
+
+        // Temporarily disable raw mode for inquire
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Show);
+
+        let result = Self::prompt_query_filter(&cluster, &self.clusters);
+
+        let _ = terminal::enable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Hide);
+
+        let mut rule = match result {
+            Ok(Some(rule)) => rule,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                self.status_message = Some(format!("Invalid query: {}", e));
+                return Ok(());
+            }
+        };
+        rule.should_archive = cluster.should_archive;
+
+        let key = Self::cluster_key(&cluster);
+        self.history.push(HistoryEntry {
+            index: self.current_index,
+            cluster: cluster.clone(),
+            decision: self.decisions.get(&key).cloned(),
+        });
+
+        let label = rule.target_label.to_string();
+        if !self.available_labels.contains(&label) {
+            self.available_labels.push(label.clone());
+        }
+
+        // A query filter is always created as a new filter; any existing filter is left alone
+        let decision = ClusterDecision {
+            sender_domain: cluster.sender_domain.clone(),
+            sender_email: cluster.sender_email.clone(),
+            is_specific_sender: cluster.is_specific_sender,
+            excluded_senders: cluster.excluded_senders.clone(),
+            subject_pattern: cluster.subject_pattern.clone(),
+            message_ids: cluster.message_ids.clone(),
+            label: LabelName::new(label.clone()),
+            should_archive: cluster.should_archive,
+            action: DecisionAction::Custom(label),
+            existing_filter_id: None,
+            needs_filter_update: false,
+            exclusion_reason: None,
+            custom_filter: Some(rule),
+            additional_labels: vec![],
+            merged_senders: vec![],
+            list_id: cluster.list_id.clone(),
+        };
+
+        self.decisions.insert(key, decision);
+        self.advance();
+        Ok(())
+    }
+
+    /// Ask for a Gmail query and label, show a preview and confirm.
+    ///
+    /// Returns `Ok(None)` if the user cancelled at any prompt.
+    fn prompt_query_filter(
+        cluster: &EmailCluster,
+        clusters: &[EmailCluster],
+    ) -> Result<Option<FilterRule>> {
+        let Ok(query) = inquire::Text::new("Gmail query:")
+            .with_help_message("e.g. from:(*@github.com) -from:noreply@github.com subject:\"weekly digest\" has:attachment")
+            .prompt()
+        else {
+            return Ok(None);
+        };
+        if query.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let Ok(label) = inquire::Text::new("Label:")
+            .with_default(&cluster.suggested_label)
+            .prompt()
+        else {
+            return Ok(None);
+        };
+        let mut rule = FilterRule::from_gmail_query(&query, label)?;
+
+        let matching: Vec<&EmailCluster> = clusters
+            .iter()
+            .filter(|c| cluster_matches_rule(c, &rule))
+            .collect();
+        rule.estimated_matches = matching.iter().map(|c| c.email_count()).sum();
+
+        println!();
+        println!("Filter query:  {}", FilterManager::build_gmail_query_static(&rule));
+        println!("Label:         {}", rule.target_label);
+        println!(
+            "Archive:       {}",
+            if cluster.should_archive { "yes" } else { "no" }
+        );
+        println!(
+            "In this scan:  {} emails across {} clusters (sender and subject terms only)",
+            rule.estimated_matches,
+            matching.len()
+        );
+        for c in matching.iter().take(5) {
+            println!("  - {} ({} emails)", Self::cluster_key(c), c.email_count());
+        }
+        if matching.len() > 5 {
+            println!("  ... and {} more", matching.len() - 5);
+        }
+        println!();
+
+        let confirmed = inquire::Confirm::new("Create this filter?")
+            .with_default(true)
+            .prompt()
+            .unwrap_or(false);
+        Ok(confirmed.then_some(rule))
+    }
+
     fn skip_current(&mut self) {
         if self.current_index < self.clusters.len() {
             self.deferred_indices.push(self.current_index);
@@ -873,11 +1837,16 @@ impl ReviewSession {
                     excluded_senders: cluster.excluded_senders.clone(),
                     subject_pattern: cluster.subject_pattern.clone(),
                     message_ids: cluster.message_ids.clone(),
-                    label: cluster.suggested_label.clone(),
+                    label: LabelName::new(cluster.suggested_label.clone()),
                     should_archive: cluster.should_archive,
                     action: DecisionAction::Skip,
                     existing_filter_id: cluster.existing_filter_id.clone(),
                     needs_filter_update: false, // Skipping means no changes
+                    exclusion_reason: None,
+                    custom_filter: None,
+                    additional_labels: vec![],
+                    merged_senders: vec![],
+                    list_id: cluster.list_id.clone(),
                 };
 
                 self.decisions.insert(key, decision);
@@ -885,25 +1854,189 @@ impl ReviewSession {
         }
     }
 
+    /// Start counting the emails in Gmail that match the current cluster's filter
+    fn start_preview(&mut self) {
+        let Some((client, runtime)) = &self.preview_client else {
+            self.status_message = Some("Match count preview needs a Gmail connection".to_string());
+            return;
+        };
+
+        let query = cluster_query(&self.clusters[self.current_index]);
+        let result = Arc::new(Mutex::new(None));
+        let client = Arc::clone(client);
+        let task_result = Arc::clone(&result);
+        let task = runtime.spawn(async move {
+            let count = client
+                .list_message_ids_limited(&query, PREVIEW_LIMIT)
+                .await
+                .map(|(ids, _)| ids.len())
+                .map_err(|e| e.to_string());
+            *task_result.lock().unwrap() = Some(count);
+        });
+
+        // Replaces (and so cancels) any earlier preview
+        self.preview_task = Some(PreviewTask {
+            index: self.current_index,
+            task,
+            result,
+        });
+        self.cluster_preview_pending = true;
+    }
+
+    /// Collect a finished preview, or cancel it if its cluster is no longer shown.
+    /// Returns whether the display needs to be redrawn.
+    fn poll_preview(&mut self) -> bool {
+        let Some(preview) = &self.preview_task else {
+            return false;
+        };
+
+        if preview.index != self.current_index {
+            self.preview_task = None;
+            self.cluster_preview_pending = false;
+            return true;
+        }
+
+        let Some(result) = preview.result.lock().unwrap().take() else {
+            return false;
+        };
+        match result {
+            Ok(count) => self.clusters[preview.index].preview_count = Some(count),
+            Err(e) => self.status_message = Some(format!("Match count failed: {}", e)),
+        }
+        self.preview_task = None;
+        self.cluster_preview_pending = false;
+        true
+    }
+
+    /// Lines of the `[I]` statistics overlay, the same height as the sample subjects section
+    fn stats_lines(&self, elapsed: Duration) -> Vec<String> {
+        let total = self.clusters.len();
+        let reviewed = self.decisions.len();
+        let remaining = total.saturating_sub(reviewed);
+
+        let (mut accept, mut reject, mut delete, mut exclude, mut merge, mut skip) =
+            (0, 0, 0, 0, 0, 0);
+        for decision in self.decisions.values() {
+            match decision.action {
+                DecisionAction::Accept | DecisionAction::Custom(_) => accept += 1,
+                DecisionAction::Reject => reject += 1,
+                DecisionAction::Delete => delete += 1,
+                DecisionAction::Exclude => exclude += 1,
+                DecisionAction::Merge { .. } => merge += 1,
+                DecisionAction::Skip => skip += 1,
+            }
+        }
+
+        let estimate = if reviewed == 0 {
+            "-".to_string()
+        } else {
+            format_duration(elapsed.mul_f64(remaining as f64 / reviewed as f64))
+        };
+
+        vec![
+            "Session statistics:  (press any key to close)".to_string(),
+            format!(
+                "  Total: {}  Reviewed: {}  Remaining: {}",
+                total, reviewed, remaining
+            ),
+            format!(
+                "  Accept: {}, Reject: {}, Delete: {}, Exclude: {}, Merge: {}, Skip: {}",
+                accept, reject, delete, exclude, merge, skip
+            ),
+            format!("  Elapsed: {}", format_duration(elapsed)),
+            format!("  Estimated time to finish: {}", estimate),
+        ]
+    }
+
     fn advance(&mut self) {
         self.current_index += 1;
 
-        // Skip clusters we've already decided on
-        while self.current_index < self.clusters.len() {
-            let cluster = &self.clusters[self.current_index];
-            let key = Self::cluster_key(cluster);
-            if !self.decisions.contains_key(&key)
-                || matches!(
-                    self.decisions.get(&key).map(|d| &d.action),
-                    Some(DecisionAction::Skip)
-                )
-            {
-                break;
-            }
+        // Skip clusters we've already decided on or that are hidden by the category filter
+        while self.current_index < self.clusters.len() && !self.is_pending(self.current_index) {
             self.current_index += 1;
         }
     }
 
+    /// Whether the cluster at `index` is shown and still needs a decision
+    fn is_pending(&self, index: usize) -> bool {
+        let cluster = &self.clusters[index];
+        if self
+            .category_filter
+            .as_ref()
+            .is_some_and(|category| &cluster.suggested_category != category)
+        {
+            return false;
+        }
+        if self
+            .search_query
+            .as_deref()
+            .is_some_and(|query| !cluster_matches_search(cluster, query))
+        {
+            return false;
+        }
+
+        let key = Self::cluster_key(cluster);
+        !self.decisions.contains_key(&key)
+            || matches!(
+                self.decisions.get(&key).map(|d| &d.action),
+                Some(DecisionAction::Skip)
+            )
+    }
+
+    /// Show only clusters of `category` (or all clusters for `None`), moving to the
+    /// first one that still needs a decision
+    fn set_category_filter(&mut self, category: Option<EmailCategory>) {
+        self.category_filter = category;
+        self.jump_to_first_pending();
+    }
+
+    /// Show only clusters matching `query` (or all clusters for `None`), moving to the
+    /// first one that still needs a decision
+    fn set_search(&mut self, query: Option<String>) {
+        self.search_query = query;
+        self.jump_to_first_pending();
+    }
+
+    fn jump_to_first_pending(&mut self) {
+        self.current_index = (0..self.clusters.len())
+            .find(|&i| self.is_pending(i))
+            .unwrap_or(self.clusters.len());
+    }
+
+    /// Ask which category to focus on; picking the active category again clears the filter
+    fn prompt_category_filter(&mut self) {
+        const SHOW_ALL: &str = "None (show all)";
+
+        // Temporarily disable raw mode for inquire
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Show);
+
+        let mut options = vec![SHOW_ALL.to_string()];
+        options.extend(
+            EmailCategory::all_variants()
+                .iter()
+                .map(|category| format!("{:?}", category)),
+        );
+        let selected = inquire::Select::new("Filter by category:", options).prompt();
+
+        let _ = terminal::enable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Hide);
+
+        let Ok(selected) = selected else {
+            return;
+        };
+        let category = EmailCategory::all_variants()
+            .iter()
+            .find(|category| format!("{:?}", category) == selected)
+            .cloned();
+
+        if category.is_some() && category == self.category_filter {
+            self.set_category_filter(None);
+        } else {
+            self.set_category_filter(category);
+        }
+    }
+
     fn undo(&mut self) {
         if let Some(entry) = self.history.pop() {
             // Restore cluster state
@@ -938,22 +2071,26 @@ impl ReviewSession {
         );
 
         let w = get_display_width();
+        let b = &self.theme.help_chars;
+        let rule = b.horizontal.to_string();
         let line = |content: &str| {
             let len = content.chars().count();
             if len >= w {
-                println!("║ {} ║", content.chars().take(w).collect::<String>());
+                let truncated: String = content.chars().take(w).collect();
+                println!("{} {} {}", b.vertical, truncated, b.vertical);
             } else {
-                println!("║ {}{} ║", content, " ".repeat(w - len));
+                let padding = " ".repeat(w - len);
+                println!("{v} {content}{padding} {v}", v = b.vertical);
             }
         };
-        let sep = || println!("╠{}╣", "═".repeat(w + 2));
+        let sep = || println!("{}{}{}", b.mid_left, rule.repeat(w + 2), b.mid_right);
 
         // Center the title
         let title = "KEYBOARD SHORTCUTS";
         let title_padding = (w.saturating_sub(title.len())) / 2;
         let centered_title = format!("{}{}", " ".repeat(title_padding), title);
 
-        println!("╔{}╗", "═".repeat(w + 2));
+        println!("{}{}{}", b.top_left, rule.repeat(w + 2), b.top_right);
         line(&centered_title);
         sep();
         line("DECISIONS (new clusters):");
@@ -970,18 +2107,27 @@ impl ReviewSession {
         sep();
         line("EDIT BEFORE ACCEPTING:");
         line("  A          Toggle auto-archive ON/OFF");
-        line("  L          Change the target label");
+        line("  L          Change the target label (and add extra labels)");
+        line("  Shift+Q    Type a Gmail query (from:, -from:, to:, subject:, has:)");
+        line("             to use as the filter instead of the cluster's sender");
+        line("  M          MERGE into another cluster (no filter of its own)");
         sep();
-        line("PERMANENT EXCLUSION:");
-        line("  E          EXCLUDE permanently - never show this cluster again");
+        line("EXCLUSION:");
+        line("  E          EXCLUDE - hide this cluster forever or for 7/30/90 days");
         line("             (use --ignore-exclusions to see all clusters afresh)");
         sep();
         line("NAVIGATION:");
         line("  U          Undo last decision");
+        line("  F          Filter by category (pick it again or None to clear)");
+        line("  /          Search domains, senders and subjects (Esc to clear)");
+        line("  I          Session statistics (any key to close)");
+        line("  P          Count emails in Gmail matching the proposed filter");
         line("  ?          Show this help");
-        line("  Q          Quit without saving any changes");
+        line("  q          Quit without saving any changes");
         line("  W          Write all changes (shown at end of review)");
         line("  Ctrl+C     Force quit immediately");
+        line("  Mouse      Scroll down to move on, scroll up to undo,");
+        line("             click [Y] / [N] / [S] (disable with --no-mouse)");
         sep();
         line("WHAT HAPPENS:");
         line("  Y creates: Gmail filter matching from:(*@domain) or from:(specific@email)");
@@ -990,7 +2136,7 @@ impl ReviewSession {
         line("  N ignores: No filter or label created for this sender/domain");
         line("  D deletes: Removes the existing Gmail filter entirely");
         line("  E excludes: Saves to .gmail-automation/exclusions.json, hidden in future runs");
-        println!("╚{}╝", "═".repeat(w + 2));
+        println!("{}{}{}", b.bottom_left, rule.repeat(w + 2), b.bottom_right);
         println!();
         println!("Press any key to continue...");
 
@@ -1016,6 +2162,167 @@ enum SessionAction {
     Finish,
 }
 
+/// Screen area of a clickable `[Y]`, `[N]` or `[S]` button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ButtonRegion {
+    row: u16,
+    start_column: u16,
+    end_column: u16,
+    key: char,
+}
+
+/// Find the clickable buttons in one rendered line
+fn find_buttons(text: &str, row: u16) -> Vec<ButtonRegion> {
+    let chars: Vec<char> = strip_ansi(text).chars().collect();
+    chars
+        .windows(3)
+        .enumerate()
+        .filter_map(|(column, window)| match window {
+            ['[', label @ ('Y' | 'N' | 'S'), ']'] => Some(ButtonRegion {
+                row,
+                start_column: column as u16,
+                end_column: column as u16 + 2,
+                key: label.to_ascii_lowercase(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Key of the button under a mouse click, if any
+fn button_at(buttons: &[ButtonRegion], column: u16, row: u16) -> Option<char> {
+    buttons
+        .iter()
+        .find(|b| b.row == row && (b.start_column..=b.end_column).contains(&column))
+        .map(|b| b.key)
+}
+
+/// Whether `query` appears (case-insensitively) in the cluster's domain, sender or sample subjects
+fn cluster_matches_search(cluster: &EmailCluster, query: &str) -> bool {
+    let query = query.to_lowercase();
+    [
+        cluster.sender_domain.to_lowercase(),
+        cluster.sender_email.to_lowercase(),
+        cluster.sample_subjects.join(" ").to_lowercase(),
+    ]
+    .iter()
+    .any(|field| field.contains(&query))
+}
+
+/// Whether a scanned cluster would be caught by `rule`, judged on sender and subject only.
+///
+/// Other query terms (`to:`, `has:`, free text) can't be checked without the
+/// full message, so they are ignored here; Gmail evaluates them when the
+/// filter runs.
+fn cluster_matches_rule(cluster: &EmailCluster, rule: &FilterRule) -> bool {
+    if rule.from_pattern.is_none() && rule.subject_keywords.is_empty() {
+        return false;
+    }
+
+    if let Some(pattern) = &rule.from_pattern {
+        if rule.is_specific_sender {
+            if !cluster.sender_email.eq_ignore_ascii_case(pattern) {
+                return false;
+            }
+        } else {
+            let domain = pattern.trim_start_matches("*@").to_lowercase();
+            let cluster_domain = cluster.sender_domain.to_lowercase();
+            if cluster_domain != domain && !cluster_domain.ends_with(&format!(".{}", domain)) {
+                return false;
+            }
+            if rule
+                .excluded_senders
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(&cluster.sender_email))
+            {
+                return false;
+            }
+        }
+    }
+
+    rule.subject_keywords.iter().all(|keyword| {
+        let keyword = keyword.trim_matches('"').to_lowercase();
+        cluster
+            .sample_subjects
+            .iter()
+            .any(|s| s.to_lowercase().contains(&keyword))
+    })
+}
+
+/// Secondary labels offered when adding labels to a cluster filed under `primary`
+///
+/// The parents of `primary` come first (`AutoManaged` and `AutoManaged/Newsletters`
+/// for `AutoManaged/Newsletters/github-com`), then the other labels in `available`,
+/// without duplicates and without `primary` itself.
+fn additional_label_options(primary: &str, available: &[String]) -> Vec<String> {
+    let parents = primary
+        .match_indices('/')
+        .map(|(index, _)| primary[..index].to_string());
+    let mut options: Vec<String> = Vec::new();
+    for label in parents.chain(available.iter().cloned()) {
+        if label != primary && !options.contains(&label) {
+            options.push(label);
+        }
+    }
+    options
+}
+
+/// Gmail query matching the emails in a cluster
+fn cluster_query(cluster: &EmailCluster) -> String {
+    let Some(list_id) = &cluster.list_id else {
+        return sender_query(cluster);
+    };
+    if cluster.is_specific_sender {
+        return format!("{} list:{}", sender_query(cluster), list_id);
+    }
+    // A whole list is matched by its List-ID, whichever domain the posts come from
+    std::iter::once(format!("list:{}", list_id))
+        .chain(
+            cluster
+                .excluded_senders
+                .iter()
+                .map(|s| format!("-from:({})", s)),
+        )
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Gmail query matching the senders (and subject) of a cluster
+fn sender_query(cluster: &EmailCluster) -> String {
+    if let Some(subject) = &cluster.subject_pattern {
+        // Subject-based cluster
+        if cluster.is_specific_sender {
+            format!("from:({}) subject:({})", cluster.sender_email, subject)
+        } else {
+            format!("from:(*@{}) subject:({})", cluster.sender_domain, subject)
+        }
+    } else if cluster.is_specific_sender {
+        format!("from:({})", cluster.sender_email)
+    } else if cluster.excluded_senders.is_empty() {
+        format!("from:(*@{})", cluster.sender_domain)
+    } else {
+        let exclusions = cluster
+            .excluded_senders
+            .iter()
+            .map(|s| format!("-from:({})", s))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!("from:(*@{}) {}", cluster.sender_domain, exclusions)
+    }
+}
+
+/// Format a duration as e.g. "1h 05m", "4m 12s" or "9s"
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
 /// Get the display width for the UI box, based on terminal size
 /// Returns inner content width (excluding borders)
 fn get_display_width() -> usize {
@@ -1045,29 +2352,278 @@ fn truncate_str(s: &str, max_len: usize) -> String {
 }
 
 /// ANSI color codes for field comparison display
-mod colors {
-    pub const GREY: &str = "\x1b[90m";
-    pub const RED: &str = "\x1b[31m";
-    pub const BLUE: &str = "\x1b[34m";
-    pub const RESET: &str = "\x1b[0m";
+/// Resolved colors and box characters for drawing the review session
+#[derive(Debug, Clone)]
+pub struct Theme {
+    existing_filter: &'static str,
+    changed_field: &'static str,
+    unchanged_field: &'static str,
+    accepted_badge: &'static str,
+    rejected_badge: &'static str,
+    reset: &'static str,
+    chars: BoxChars,
+    help_chars: BoxChars,
 }
 
-/// Format two field values with color based on whether they differ
-/// Returns (current_colored, proposed_colored)
-fn format_field_pair(current: &str, proposed: &str, differ_color: &str) -> (String, String) {
-    if current == proposed {
-        // Same - both grey
-        (
-            format!("{}{}{}", colors::GREY, current, colors::RESET),
-            format!("{}{}{}", colors::GREY, proposed, colors::RESET),
-        )
-    } else {
-        // Different - both colored
-        (
-            format!("{}{}{}", differ_color, current, colors::RESET),
-            format!("{}{}{}", differ_color, proposed, colors::RESET),
-        )
+impl Theme {
+    /// Build a theme from `ui`; with `color` false every color code is empty
+    pub fn new(ui: &UiConfig, color: bool) -> Self {
+        let code = |c: crate::config::AnsiColor| if color { c.escape_code() } else { "" };
+        let (chars, help_chars) = match ui.box_style {
+            BoxStyle::Unicode => (BoxChars::SINGLE, BoxChars::DOUBLE),
+            BoxStyle::Ascii => (BoxChars::ASCII, BoxChars::ASCII),
+        };
+
+        Self {
+            existing_filter: code(ui.existing_filter_color),
+            changed_field: code(ui.changed_field_color),
+            unchanged_field: code(crate::config::AnsiColor::BrightBlack),
+            accepted_badge: code(ui.accepted_badge_color),
+            rejected_badge: code(ui.rejected_badge_color),
+            reset: if color { "\x1b[0m" } else { "" },
+            chars,
+            help_chars,
+        }
+    }
+
+    fn paint(&self, text: &str, color: &str) -> String {
+        if color.is_empty() {
+            text.to_string()
+        } else {
+            format!("{}{}{}", color, text, self.reset)
+        }
+    }
+
+    /// Color a current/proposed pair: dimmed when equal, highlighted when they differ
+    fn field_pair(&self, current: &str, proposed: &str) -> (String, String) {
+        let color = if current == proposed {
+            self.unchanged_field
+        } else {
+            self.changed_field
+        };
+        (self.paint(current, color), self.paint(proposed, color))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(&UiConfig::default(), true)
+    }
+}
+
+/// Characters for one box drawing style
+#[derive(Debug, Clone, Copy)]
+struct BoxChars {
+    top_left: char,
+    top_right: char,
+    mid_left: char,
+    mid_right: char,
+    bottom_left: char,
+    bottom_right: char,
+    horizontal: char,
+    vertical: char,
+    bar_filled: char,
+    bar_empty: char,
+    bullet: char,
+    warning: char,
+    two_way: &'static str,
+}
+
+impl BoxChars {
+    const SINGLE: Self = Self {
+        top_left: '┌',
+        top_right: '┐',
+        mid_left: '├',
+        mid_right: '┤',
+        bottom_left: '└',
+        bottom_right: '┘',
+        horizontal: '─',
+        vertical: '│',
+        bar_filled: '█',
+        bar_empty: '░',
+        bullet: '•',
+        warning: '⚠',
+        two_way: "↔",
+    };
+
+    const DOUBLE: Self = Self {
+        top_left: '╔',
+        top_right: '╗',
+        mid_left: '╠',
+        mid_right: '╣',
+        bottom_left: '╚',
+        bottom_right: '╝',
+        horizontal: '═',
+        vertical: '║',
+        ..Self::SINGLE
+    };
+
+    const ASCII: Self = Self {
+        top_left: '+',
+        top_right: '+',
+        mid_left: '+',
+        mid_right: '+',
+        bottom_left: '+',
+        bottom_right: '+',
+        horizontal: '-',
+        vertical: '|',
+        bar_filled: '#',
+        bar_empty: '.',
+        bullet: '*',
+        warning: '!',
+        two_way: "<->",
+    };
+}
+
+/// Remove ANSI color sequences (`ESC [ ... m`) from `text`
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip up to and including the final byte of the sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// Number of characters `text` occupies on screen
+fn visible_len(text: &str) -> usize {
+    strip_ansi(text).chars().count()
+}
+
+/// A `Merge` decision that [`resolve_merges`] could not carry out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedMerge {
+    /// Key of the merged cluster, e.g. `orders@shop.com` or `*@shop.com`
+    pub cluster: String,
+    /// Key of the cluster it was to be merged into
+    pub into_domain: String,
+    pub reason: &'static str,
+}
+
+/// Fold `Merge` decisions into the decisions for their target clusters
+///
+/// The merged cluster's messages are attributed to the target, and the merged
+/// cluster gets no filter of its own. A sender of the target's own domain is
+/// already matched by a `*@domain` target, so it only leaves the target's
+/// `excluded_senders`. Any other sender is added to the target's `merged_senders`,
+/// widening its filter to e.g. `from:(*@a.com OR *@b.com)` so future mail is
+/// caught too. Merges that can't be carried out (into a cluster that wasn't
+/// accepted or was entered as a Gmail query, or of a subject cluster outside its
+/// domain) are returned so they can be shown to the user.
+pub fn resolve_merges(
+    decisions: Vec<ClusterDecision>,
+) -> (Vec<ClusterDecision>, Vec<DroppedMerge>) {
+    let (merges, mut decisions): (Vec<_>, Vec<_>) = decisions
+        .into_iter()
+        .partition(|d| matches!(d.action, DecisionAction::Merge { .. }));
+
+    let mut dropped = Vec::new();
+    for merged in merges {
+        let DecisionAction::Merge { into_domain } = &merged.action else {
+            continue;
+        };
+        // Decisions saved before any cluster could be a target name a domain
+        let into_key = if into_domain.contains('@') {
+            into_domain.clone()
+        } else {
+            format!("*@{}", into_domain)
+        };
+        let cluster = merged.cluster_key();
+        let mut drop_merge = |reason| {
+            tracing::warn!("Not merging {} into {}: {}", cluster, into_key, reason);
+            dropped.push(DroppedMerge {
+                cluster: cluster.clone(),
+                into_domain: into_key.clone(),
+                reason,
+            });
+        };
+
+        let target = decisions.iter_mut().find(|d| {
+            d.cluster_key().eq_ignore_ascii_case(&into_key)
+                && matches!(d.action, DecisionAction::Accept | DecisionAction::Custom(_))
+        });
+        let Some(target) = target else {
+            drop_merge("its cluster was not accepted");
+            continue;
+        };
+        if target.custom_filter.is_some() {
+            drop_merge("its cluster's filter was entered as a Gmail query");
+            continue;
+        }
+        // A list's filter matches `list:<id>`, which no sender alternative can widen
+        let same_list = match (&target.list_id, &merged.list_id) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            (None, None) => true,
+            _ => false,
+        };
+        if !same_list {
+            drop_merge("mailing list clusters can only be merged within their list");
+            continue;
+        }
+
+        let same_domain = !target.is_specific_sender
+            && target.subject_pattern.is_none()
+            && target
+                .sender_domain
+                .eq_ignore_ascii_case(&merged.sender_domain);
+        if same_domain {
+            if merged.is_specific_sender {
+                // `excluded_senders` carves senders with a filter of their own out of
+                // the domain filter (`-from:(...)`). The merged sender gives up its own
+                // filter, so it has to leave that list for the domain filter to cover it
+                let excluded = target.excluded_senders.len();
+                target
+                    .excluded_senders
+                    .retain(|s| !s.eq_ignore_ascii_case(&merged.sender_email));
+                if target.excluded_senders.len() != excluded && target.existing_filter_id.is_some()
+                {
+                    target.needs_filter_update = true;
+                }
+            }
+        } else {
+            // A sender alternative can't carry the merged cluster's subject condition
+            if merged.subject_pattern.is_some() {
+                drop_merge("a subject cluster can only be merged into its domain's cluster");
+                continue;
+            }
+            let sender = if merged.is_specific_sender {
+                merged.sender_email.clone()
+            } else {
+                format!("*@{}", merged.sender_domain)
+            };
+            let already_matched = target
+                .from_pattern()
+                .split(" OR ")
+                .any(|s| s.eq_ignore_ascii_case(&sender));
+            if !already_matched {
+                target.merged_senders.push(sender);
+                // Senders of the merged domain with filters of their own stay carved out
+                for excluded in &merged.excluded_senders {
+                    if !target.excluded_senders.contains(excluded) {
+                        target.excluded_senders.push(excluded.clone());
+                    }
+                }
+                if target.existing_filter_id.is_some() {
+                    target.needs_filter_update = true;
+                }
+            }
+        }
+        for id in merged.message_ids {
+            if !target.message_ids.contains(&id) {
+                target.message_ids.push(id);
+            }
+        }
     }
+    (decisions, dropped)
 }
 
 /// Create email clusters from messages and classifications
@@ -1078,24 +2634,34 @@ fn format_field_pair(current: &str, proposed: &str, differ_color: &str) -> (Stri
 /// 3. Finally, create domain-wide clusters for remaining emails
 ///
 ///    This ensures automated emails with consistent subjects get their own granular filters.
+///
+/// Messages in `hidden_categories` are left out, e.g. automatic replies, where a
+/// filter on a colleague's out-of-office reply would also catch their real mail
+/// (see `ClassificationConfig::hidden_cluster_categories`).
 pub fn create_clusters(
     _messages: &[MessageMetadata],
     classifications: &[(MessageMetadata, Classification)],
     min_emails: usize,
+    hidden_categories: &[EmailCategory],
 ) -> Vec<EmailCluster> {
     let mut clusters: Vec<EmailCluster> = Vec::new();
 
-    // Step 1: Group messages by domain
-    let mut domain_map: HashMap<String, Vec<(&MessageMetadata, &Classification)>> = HashMap::new();
+    // Step 1: Group messages by domain, and mailing list messages by list
+    type Group<'a> = Vec<(&'a MessageMetadata, &'a Classification)>;
+    let mut domain_map: HashMap<(String, Option<String>), Group> = HashMap::new();
     for (msg, class) in classifications {
+        if hidden_categories.contains(&class.category) {
+            continue;
+        }
         domain_map
-            .entry(msg.sender_domain.clone())
+            .entry(cluster_group(msg))
             .or_default()
             .push((msg, class));
     }
 
     // Step 2: For each domain, do hierarchical clustering
-    for (domain, domain_msgs) in domain_map {
+    for ((domain, list_id), domain_msgs) in domain_map {
+        let first_of_group = clusters.len();
         // Group by specific sender email within this domain
         let mut sender_map: HashMap<String, Vec<(&MessageMetadata, &Classification)>> =
             HashMap::new();
@@ -1169,6 +2735,9 @@ pub fn create_clusters(
             );
             clusters.push(cluster);
         }
+        for cluster in &mut clusters[first_of_group..] {
+            cluster.list_id = list_id.clone();
+        }
     }
 
     // Sort by specificity first (narrow clusters before broad), then by email count
@@ -1191,6 +2760,89 @@ pub fn create_clusters(
     clusters
 }
 
+/// Domain a message is clustered under, and its mailing list if any
+///
+/// Mailing list messages are grouped by their `List-ID` under the list's main
+/// domain, so posts from many senders to the same list end up together.
+fn cluster_group(msg: &MessageMetadata) -> (String, Option<String>) {
+    match msg.list_id.as_deref().filter(|id| id.contains('.')) {
+        Some(list_id) => (
+            crate::classifier::extract_main_domain(list_id),
+            Some(list_id.to_string()),
+        ),
+        None => (msg.sender_domain.clone(), None),
+    }
+}
+
+/// Merge sender clusters holding less than `threshold` of their domain's messages
+/// into the domain cluster, so small senders don't each get their own filter
+///
+/// Merged senders are dropped from the domain cluster's `excluded_senders`. If the
+/// domain has no cluster yet, the first merged sender becomes one.
+pub fn auto_merge_domain_clusters(clusters: &mut Vec<EmailCluster>, threshold: f32) {
+    if threshold <= 0.0 {
+        return;
+    }
+
+    // Mailing lists count separately from the rest of their domain's mail
+    let group = |c: &EmailCluster| (c.sender_domain.clone(), c.list_id.clone());
+    let mut domain_totals: HashMap<(String, Option<String>), usize> = HashMap::new();
+    for cluster in clusters.iter() {
+        *domain_totals.entry(group(cluster)).or_insert(0) += cluster.email_count();
+    }
+
+    // Subject clusters are narrower than their sender and are never merged
+    let is_sender_cluster = |c: &EmailCluster| c.is_specific_sender && c.subject_pattern.is_none();
+    let (small, kept): (Vec<EmailCluster>, Vec<EmailCluster>) =
+        std::mem::take(clusters).into_iter().partition(|c| {
+            is_sender_cluster(c)
+                && (c.email_count() as f32) < threshold * domain_totals[&group(c)] as f32
+        });
+    *clusters = kept;
+
+    for sender in small {
+        let domain_cluster = clusters.iter_mut().find(|c| {
+            group(c) == group(&sender) && !c.is_specific_sender && c.subject_pattern.is_none()
+        });
+
+        match domain_cluster {
+            Some(domain) => {
+                domain
+                    .excluded_senders
+                    .retain(|s| s != &sender.sender_email);
+
+                // Weight confidence by message count
+                let (domain_count, sender_count) =
+                    (domain.email_count() as f32, sender.email_count() as f32);
+                domain.confidence = (domain.confidence * domain_count
+                    + sender.confidence * sender_count)
+                    / (domain_count + sender_count);
+
+                domain.message_ids.extend(sender.message_ids);
+                domain.thread_ids.extend(sender.thread_ids);
+                let room = 5usize.saturating_sub(domain.sample_subjects.len());
+                domain
+                    .sample_subjects
+                    .extend(sender.sample_subjects.into_iter().take(room));
+            }
+            None => {
+                // Senders that keep their own clusters stay excluded from the domain filter
+                let excluded_senders = clusters
+                    .iter()
+                    .filter(|c| group(c) == group(&sender) && is_sender_cluster(c))
+                    .map(|c| c.sender_email.clone())
+                    .collect();
+                clusters.push(EmailCluster {
+                    sender_email: String::new(),
+                    is_specific_sender: false,
+                    excluded_senders,
+                    ..sender
+                });
+            }
+        }
+    }
+}
+
 /// Detect repeated subject patterns within a set of messages
 /// Returns a map from subject pattern to matching messages
 fn detect_subject_patterns<'a>(
@@ -1275,6 +2927,7 @@ fn build_cluster_with_subject(
 ) -> EmailCluster {
     let first = msgs.first().unwrap();
     let message_ids: Vec<String> = msgs.iter().map(|(m, _)| m.id.clone()).collect();
+    let thread_ids: HashSet<String> = msgs.iter().map(|(m, _)| m.thread_id.clone()).collect();
     let sample_subjects: Vec<String> = msgs
         .iter()
         .take(5)
@@ -1296,18 +2949,30 @@ fn build_cluster_with_subject(
         .map(|(cat, _)| cat)
         .unwrap_or_else(|| first.1.category.clone());
 
-    // Use most common label
+    // Use most common label; attachment sublabels get filters of their own
     let mut label_counts: HashMap<String, usize> = HashMap::new();
     for (_, c) in msgs {
         if !c.suggested_label.is_empty() {
-            *label_counts.entry(c.suggested_label.clone()).or_insert(0) += 1;
+            *label_counts.entry(c.base_label().to_string()).or_insert(0) += 1;
         }
     }
     let suggested_label = label_counts
         .into_iter()
         .max_by_key(|(_, count)| *count)
         .map(|(label, _)| label)
-        .unwrap_or_else(|| first.1.suggested_label.clone());
+        .unwrap_or_else(|| first.1.base_label().to_string());
+
+    // Use most common top reason
+    let mut reason_counts: HashMap<&ClassificationReason, usize> = HashMap::new();
+    for (_, c) in msgs {
+        if let Some(reason) = c.top_reason() {
+            *reason_counts.entry(reason).or_insert(0) += 1;
+        }
+    }
+    let top_reason = reason_counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(reason, _)| reason.clone());
 
     // Check if majority suggest archiving
     let archive_count = msgs.iter().filter(|(_, c)| c.should_archive).count();
@@ -1320,17 +2985,22 @@ fn build_cluster_with_subject(
         excluded_senders,
         subject_pattern,
         message_ids,
+        thread_ids,
         suggested_category,
         suggested_label,
         confidence: avg_confidence,
+        top_reason,
         sample_subjects,
         should_archive,
         existing_filter_id: None, // Will be set by caller after matching against existing filters
         existing_filter_label_id: None, // Will be set by caller after matching against existing filters
         existing_filter_label: None, // Will be set by caller after matching against existing filters
         existing_filter_archive: None, // Will be set by caller after matching against existing filters
+        existing_filter_query: None, // Will be set by caller after matching against existing filters
         source: ClusterSource::EmailScan,
         default_action: None,
+        preview_count: None,
+        list_id: None,
     }
 }
 
@@ -1353,6 +3023,15 @@ mod tests {
             labels: vec![],
             has_unsubscribe: false,
             is_automated: false,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 0,
         }
     }
 
@@ -1364,6 +3043,7 @@ mod tests {
             suggested_label: "auto/newsletters".to_string(),
             should_archive: true,
             reasoning: None,
+            reasons: Vec::new(),
         }
     }
 
@@ -1381,7 +3061,7 @@ mod tests {
             .map(|m| (m.clone(), create_test_classification(m)))
             .collect();
 
-        let clusters = create_clusters(&messages, &classifications, 2);
+        let clusters = create_clusters(&messages, &classifications, 2, &[]);
 
         assert_eq!(clusters.len(), 1); // Only example.com has >= 2 emails
         assert_eq!(clusters[0].sender_domain, "example.com");
@@ -1389,6 +3069,110 @@ mod tests {
         assert!(clusters[0].subject_pattern.is_none()); // No repeated subjects
     }
 
+    #[test]
+    fn test_create_clusters_counts_threads() {
+        let mut messages: Vec<MessageMetadata> = (1..=7)
+            .map(|i| create_test_message(&i.to_string(), "list@example.com", "Update"))
+            .collect();
+        for msg in &mut messages {
+            msg.thread_id = "thread-1".to_string();
+        }
+        messages[6].thread_id = "thread-2".to_string();
+
+        let classifications: Vec<(MessageMetadata, Classification)> = messages
+            .iter()
+            .map(|m| (m.clone(), create_test_classification(m)))
+            .collect();
+
+        let mut cluster = create_clusters(&messages, &classifications, 2, &[]).remove(0);
+        assert_eq!(cluster.thread_count(), 2);
+        assert_eq!(cluster.volume_summary(), "7 emails (2 threads)");
+        assert_eq!(cluster.thread_pattern(), Some(ThreadPattern::MailingList));
+
+        cluster.thread_ids.insert("thread-3".to_string());
+        assert_eq!(cluster.thread_pattern(), Some(ThreadPattern::Conversation));
+        cluster.thread_ids.insert("thread-4".to_string());
+        assert_eq!(cluster.thread_pattern(), None);
+    }
+
+    #[test]
+    fn test_create_clusters_groups_mailing_lists() {
+        let mut messages = vec![
+            create_test_message("1", "alice@gmail.com", "Re: release plan"),
+            create_test_message("2", "bob@example.org", "Re: CI failures"),
+            create_test_message("3", "carol@example.net", "Proposal"),
+            create_test_message("4", "dave@example.org", "Unrelated"),
+        ];
+        for msg in &mut messages[..3] {
+            msg.list_id = Some("dev.rust-lang.org".to_string());
+        }
+
+        let classifications: Vec<(MessageMetadata, Classification)> = messages
+            .iter()
+            .map(|m| (m.clone(), create_test_classification(m)))
+            .collect();
+
+        let clusters = create_clusters(&messages, &classifications, 2, &[]);
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].sender_domain, "rust-lang.org");
+        assert_eq!(clusters[0].email_count(), 3);
+        assert_eq!(clusters[0].list_id.as_deref(), Some("dev.rust-lang.org"));
+        assert_eq!(clusters[0].key(), "*@rust-lang.org|list:dev.rust-lang.org");
+    }
+
+    #[test]
+    fn test_create_clusters_hidden_categories() {
+        let messages = vec![
+            create_test_message("1", "bob@example.com", "Automatic reply: Q3 plan"),
+            create_test_message("2", "bob@example.com", "Automatic reply: Budget"),
+        ];
+        let classifications: Vec<(MessageMetadata, Classification)> = messages
+            .iter()
+            .map(|m| {
+                let mut classification = create_test_classification(m);
+                classification.category = EmailCategory::AutoReply;
+                (m.clone(), classification)
+            })
+            .collect();
+
+        let hidden = [EmailCategory::AutoReply, EmailCategory::Bounce];
+        assert!(create_clusters(&messages, &classifications, 2, &hidden).is_empty());
+        assert_eq!(
+            create_clusters(&messages, &classifications, 2, &[])[0].email_count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_cluster_matches_rule() {
+        let messages = vec![
+            create_test_message("1", "news@example.com", "Weekly Digest #1"),
+            create_test_message("2", "news@example.com", "Weekly Digest #2"),
+            create_test_message("3", "other@different.com", "Invoice"),
+            create_test_message("4", "other@different.com", "Invoice"),
+        ];
+        let classifications: Vec<(MessageMetadata, Classification)> = messages
+            .iter()
+            .map(|m| (m.clone(), create_test_classification(m)))
+            .collect();
+        let clusters = create_clusters(&messages, &classifications, 2, &[]);
+        let example = clusters
+            .iter()
+            .find(|c| c.sender_domain == "example.com")
+            .unwrap();
+
+        let rule = |q: &str| FilterRule::from_gmail_query(q, "Label".to_string()).unwrap();
+        assert!(cluster_matches_rule(example, &rule("from:example.com")));
+        assert!(cluster_matches_rule(
+            example,
+            &rule("from:*@example.com subject:\"weekly digest\"")
+        ));
+        assert!(!cluster_matches_rule(example, &rule("from:example.com subject:invoice")));
+        assert!(!cluster_matches_rule(example, &rule("from:different.com")));
+        assert!(!cluster_matches_rule(example, &rule("has:attachment")));
+    }
+
     #[test]
     fn test_create_clusters_with_subject_patterns() {
         // Test hierarchical clustering: subject patterns should create narrow clusters first
@@ -1411,7 +3195,7 @@ mod tests {
             .map(|m| (m.clone(), create_test_classification(m)))
             .collect();
 
-        let clusters = create_clusters(&messages, &classifications, 3);
+        let clusters = create_clusters(&messages, &classifications, 3, &[]);
 
         // Should create 2 narrow subject-based clusters (for automated emails)
         // The remaining 2 regular emails don't meet threshold, so no sender cluster
@@ -1461,7 +3245,7 @@ mod tests {
             .map(|m| (m.clone(), create_test_classification(m)))
             .collect();
 
-        let clusters = create_clusters(&messages, &classifications, 3);
+        let clusters = create_clusters(&messages, &classifications, 3, &[]);
 
         // Should create 2 clusters: one subject-based, one sender-based
         assert_eq!(clusters.len(), 2);
@@ -1480,6 +3264,65 @@ mod tests {
         assert_eq!(clusters[1].email_count(), 3);
     }
 
+    #[test]
+    fn test_auto_merge_domain_clusters() {
+        // 20 emails from one GitHub sender, 3 from two others each, 2 unclustered
+        let mut messages = Vec::new();
+        for i in 0..20 {
+            messages.push(create_test_message(
+                &format!("n{}", i),
+                "notifications@github.com",
+                &format!("Issue #{} opened", i),
+            ));
+        }
+        for sender in ["alerts", "billing"] {
+            for i in 0..3 {
+                messages.push(create_test_message(
+                    &format!("{}{}", sender, i),
+                    &format!("{}@github.com", sender),
+                    &format!("Message {}", i),
+                ));
+            }
+        }
+        messages.push(create_test_message("x1", "x@github.com", "One"));
+        messages.push(create_test_message("y1", "y@github.com", "Two"));
+
+        let classifications = messages
+            .iter()
+            .map(|m| (m.clone(), create_test_classification(m)))
+            .collect::<Vec<_>>();
+        let mut clusters = create_clusters(&messages, &classifications, 2, &[]);
+        assert_eq!(clusters.len(), 4);
+
+        // alerts and billing hold 3/28 of the domain each, below 20%
+        auto_merge_domain_clusters(&mut clusters, 0.2);
+        assert_eq!(clusters.len(), 2);
+        let domain = clusters.iter().find(|c| !c.is_specific_sender).unwrap();
+        assert_eq!(domain.email_count(), 8);
+        assert_eq!(
+            domain.excluded_senders,
+            vec!["notifications@github.com".to_string()]
+        );
+
+        // A zero threshold leaves clusters alone
+        let mut clusters = create_clusters(&messages, &classifications, 2, &[]);
+        auto_merge_domain_clusters(&mut clusters, 0.0);
+        assert_eq!(clusters.len(), 4);
+
+        // Without leftover messages the merged senders form a new domain cluster
+        let mut clusters = create_clusters(&messages, &classifications, 3, &[]);
+        assert_eq!(clusters.len(), 3);
+        auto_merge_domain_clusters(&mut clusters, 0.2);
+        assert_eq!(clusters.len(), 2);
+        let domain = clusters.iter().find(|c| !c.is_specific_sender).unwrap();
+        assert_eq!(domain.email_count(), 6);
+        assert!(domain.sender_email.is_empty());
+        assert_eq!(
+            domain.excluded_senders,
+            vec!["notifications@github.com".to_string()]
+        );
+    }
+
     #[test]
     fn test_normalize_subject() {
         // Basic cases
@@ -1511,6 +3354,332 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_review_progress_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("review-progress.json");
+
+        let messages = vec![
+            create_test_message("1", "a@example.com", "Hello"),
+            create_test_message("2", "b@other.com", "Hello"),
+        ];
+        let classifications = messages
+            .iter()
+            .map(|m| (m.clone(), create_test_classification(m)))
+            .collect::<Vec<_>>();
+        let clusters = create_clusters(&messages, &classifications, 1, &[]);
+        assert_eq!(clusters.len(), 2);
+
+        let mut session = ReviewSession::new(clusters.clone());
+        session.accept_current();
+        session.advance();
+        session.save_progress(&path).unwrap();
+
+        let restored = ReviewSession::load_progress(&path, clusters.clone()).unwrap();
+        assert_eq!(restored.current_index, 1);
+        assert_eq!(restored.decisions.len(), 1);
+
+        // Decisions for clusters missing from the new scan are dropped
+        let restored = ReviewSession::load_progress(&path, clusters[1..].to_vec()).unwrap();
+        assert!(restored.decisions.is_empty());
+        assert_eq!(restored.current_index, 1);
+
+        assert!(ReviewSession::load_progress(&dir.path().join("missing.json"), clusters).is_err());
+    }
+
+    #[test]
+    fn test_theme() {
+        let theme = Theme::default();
+        let (current, proposed) = theme.field_pair("News", "Promo");
+        assert!(current.starts_with("\x1b[31m"));
+        assert_eq!(strip_ansi(&proposed), "Promo");
+        assert_eq!(visible_len(&current), 4);
+
+        let ui = UiConfig {
+            box_style: BoxStyle::Ascii,
+            ..UiConfig::default()
+        };
+        let plain = Theme::new(&ui, false);
+        assert_eq!(
+            plain.field_pair("News", "Promo"),
+            ("News".into(), "Promo".into())
+        );
+        assert_eq!(plain.paint("ACCEPTED", plain.accepted_badge), "ACCEPTED");
+        assert_eq!(plain.chars.vertical, '|');
+        assert_eq!(plain.help_chars.top_left, '+');
+    }
+
+    #[test]
+    fn test_category_filter() {
+        let mut messages = vec![
+            create_test_message("1", "a@news.com", "Hello"),
+            create_test_message("2", "b@shop.com", "Hello"),
+            create_test_message("3", "c@news2.com", "Hello"),
+        ];
+        messages.sort_by(|a, b| a.id.cmp(&b.id));
+        let classifications = messages
+            .iter()
+            .map(|m| {
+                let mut c = create_test_classification(m);
+                if m.sender_domain == "shop.com" {
+                    c.category = EmailCategory::Marketing;
+                }
+                (m.clone(), c)
+            })
+            .collect::<Vec<_>>();
+        let clusters = create_clusters(&messages, &classifications, 1, &[]);
+        let mut session = ReviewSession::new(clusters);
+
+        session.set_category_filter(Some(EmailCategory::Newsletter));
+        let mut visited = Vec::new();
+        while session.current_index < session.clusters.len() {
+            visited.push(
+                session.clusters[session.current_index]
+                    .suggested_category
+                    .clone(),
+            );
+            session.accept_current();
+            session.advance();
+        }
+        assert_eq!(visited, vec![EmailCategory::Newsletter; 2]);
+
+        // Clearing the filter returns to the remaining Marketing cluster
+        session.set_category_filter(None);
+        assert_eq!(
+            session.clusters[session.current_index].suggested_category,
+            EmailCategory::Marketing
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let messages = vec![
+            create_test_message("1", "a@github.com", "Pull request merged"),
+            create_test_message("2", "b@shop.com", "Your GitHub sponsors receipt"),
+            create_test_message("3", "c@news.com", "Weekly digest"),
+        ];
+        let classifications = messages
+            .iter()
+            .map(|m| (m.clone(), create_test_classification(m)))
+            .collect::<Vec<_>>();
+        let clusters = create_clusters(&messages, &classifications, 1, &[]);
+        let mut session = ReviewSession::new(clusters);
+
+        let press = |session: &mut ReviewSession, code: KeyCode| {
+            session
+                .handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap();
+        };
+
+        press(&mut session, KeyCode::Char('/'));
+        for c in "GitHubx".chars() {
+            press(&mut session, KeyCode::Char(c));
+        }
+        press(&mut session, KeyCode::Backspace);
+        // Typing doesn't act on clusters
+        assert!(session.decisions.is_empty());
+        press(&mut session, KeyCode::Enter);
+        assert_eq!(session.search_query.as_deref(), Some("GitHub"));
+
+        let mut visited = Vec::new();
+        while session.current_index < session.clusters.len() {
+            visited.push(
+                session.clusters[session.current_index]
+                    .sender_domain
+                    .clone(),
+            );
+            press(&mut session, KeyCode::Char('y'));
+        }
+        visited.sort();
+        assert_eq!(visited, vec!["github.com", "shop.com"]);
+
+        // Escape restores the full list
+        press(&mut session, KeyCode::Esc);
+        assert!(session.search_query.is_none());
+        assert_eq!(
+            session.clusters[session.current_index].sender_domain,
+            "news.com"
+        );
+    }
+
+    #[test]
+    fn test_stats_overlay() {
+        let messages = vec![
+            create_test_message("1", "a@one.com", "Hello"),
+            create_test_message("2", "b@two.com", "Hello"),
+            create_test_message("3", "c@three.com", "Hello"),
+            create_test_message("4", "d@four.com", "Hello"),
+        ];
+        let classifications = messages
+            .iter()
+            .map(|m| (m.clone(), create_test_classification(m)))
+            .collect::<Vec<_>>();
+        let clusters = create_clusters(&messages, &classifications, 1, &[]);
+        let mut session = ReviewSession::new(clusters);
+
+        let press = |session: &mut ReviewSession, code: KeyCode| {
+            session
+                .handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap();
+        };
+
+        let lines = session.stats_lines(Duration::from_secs(5));
+        assert!(lines[4].ends_with(": -"));
+
+        press(&mut session, KeyCode::Char('y'));
+        press(&mut session, KeyCode::Char('s'));
+        press(&mut session, KeyCode::Char('i'));
+        assert!(session.show_stats);
+
+        let lines = session.stats_lines(Duration::from_secs(130));
+        assert_eq!(lines[1], "  Total: 4  Reviewed: 2  Remaining: 2");
+        assert_eq!(
+            lines[2],
+            "  Accept: 1, Reject: 0, Delete: 0, Exclude: 0, Merge: 0, Skip: 1"
+        );
+        assert_eq!(lines[3], "  Elapsed: 2m 10s");
+        assert_eq!(lines[4], "  Estimated time to finish: 2m 10s");
+
+        // Any key only dismisses the overlay
+        press(&mut session, KeyCode::Char('n'));
+        assert!(!session.show_stats);
+        assert_eq!(session.decisions.len(), 2);
+    }
+
+    #[test]
+    fn test_domain_group_hint() {
+        let messages = vec![
+            create_test_message("1", "a@amazon-email.com", "Hello"),
+            create_test_message("2", "b@other.com", "Hello"),
+        ];
+        let classifications = messages
+            .iter()
+            .map(|m| (m.clone(), create_test_classification(m)))
+            .collect::<Vec<_>>();
+        let clusters = create_clusters(&messages, &classifications, 1, &[]);
+        let session = ReviewSession::new(clusters).with_domain_groups(vec![DomainGroup {
+            canonical_domain: "amazon.com".to_string(),
+            related_domains: vec!["amazon-email.com".to_string(), "amazonses.com".to_string()],
+            evidence: Vec::new(),
+        }]);
+
+        let hint = |domain: &str| {
+            let cluster = session
+                .clusters
+                .iter()
+                .find(|c| c.sender_domain == domain)
+                .unwrap();
+            session.domain_group_hint(cluster)
+        };
+        assert_eq!(
+            hint("amazon-email.com").as_deref(),
+            Some("3 senders appear to be from the same company (also amazon.com, amazonses.com)")
+        );
+        assert_eq!(hint("other.com"), None);
+    }
+
+    #[tokio::test]
+    async fn test_preview_count() {
+        let messages = vec![
+            create_test_message("1", "a@one.com", "Hello"),
+            create_test_message("2", "b@two.com", "Hello"),
+        ];
+        let classifications = messages
+            .iter()
+            .map(|m| (m.clone(), create_test_classification(m)))
+            .collect::<Vec<_>>();
+        let clusters = create_clusters(&messages, &classifications, 1, &[]);
+        let mut session = ReviewSession::new(clusters);
+
+        // Without a client the key only explains why nothing happens
+        session.start_preview();
+        assert!(!session.cluster_preview_pending);
+        assert!(session.status_message.is_some());
+
+        let pending_preview = |index: usize| {
+            let result = Arc::new(Mutex::new(None));
+            let preview = PreviewTask {
+                index,
+                task: tokio::spawn(std::future::pending()),
+                result: Arc::clone(&result),
+            };
+            (preview, result)
+        };
+
+        let (preview, result) = pending_preview(0);
+        session.preview_task = Some(preview);
+        session.cluster_preview_pending = true;
+        assert!(!session.poll_preview());
+
+        *result.lock().unwrap() = Some(Ok(1500));
+        assert!(session.poll_preview());
+        assert_eq!(session.clusters[0].preview_count, Some(1500));
+        assert!(!session.cluster_preview_pending);
+        assert!(session.preview_task.is_none());
+
+        // Moving to another cluster cancels the query
+        let (preview, result) = pending_preview(0);
+        session.preview_task = Some(preview);
+        session.cluster_preview_pending = true;
+        session.advance();
+        assert!(session.poll_preview());
+        assert!(!session.cluster_preview_pending);
+        *result.lock().unwrap() = Some(Ok(3));
+        assert_eq!(session.clusters[1].preview_count, None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(9)), "9s");
+        assert_eq!(format_duration(Duration::from_secs(252)), "4m 12s");
+        assert_eq!(format_duration(Duration::from_secs(3900)), "1h 05m");
+    }
+
+    #[test]
+    fn test_additional_label_options() {
+        let available = vec![
+            "AutoManaged/Newsletters".to_string(),
+            "AutoManaged/Newsletters/github-com".to_string(),
+            "AutoManaged/Receipts/amazon-com".to_string(),
+        ];
+        assert_eq!(
+            additional_label_options("AutoManaged/Newsletters/github-com", &available),
+            vec![
+                "AutoManaged",
+                "AutoManaged/Newsletters",
+                "AutoManaged/Receipts/amazon-com"
+            ]
+        );
+        assert!(additional_label_options("Inbox", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_find_buttons() {
+        let buttons = find_buttons("│ [Y] Create filter  [N] No filter  [S] Skip for now │", 7);
+        assert_eq!(buttons.len(), 3);
+        assert_eq!(
+            buttons[0],
+            ButtonRegion {
+                row: 7,
+                start_column: 2,
+                end_column: 4,
+                key: 'y'
+            }
+        );
+
+        assert_eq!(button_at(&buttons, 3, 7), Some('y'));
+        assert_eq!(button_at(&buttons, 22, 7), Some('n'));
+        assert_eq!(button_at(&buttons, 3, 8), None);
+        assert_eq!(button_at(&buttons, 10, 7), None);
+
+        // Shift+S is keyboard-only
+        assert!(find_buttons("│ [Shift+S] Skip all existing │", 0).is_empty());
+
+        // Color codes don't shift button columns
+        let colored = find_buttons("│ \x1b[33m! [S] keeps current\x1b[0m │", 0);
+        assert_eq!(button_at(&colored, 5, 0), Some('s'));
+    }
+
     #[test]
     fn test_truncate_str() {
         assert_eq!(truncate_str("short", 10), "short");
@@ -1526,20 +3695,27 @@ mod tests {
             excluded_senders: vec![],
             subject_pattern: None,
             message_ids: vec!["1".to_string(), "2".to_string()],
+            thread_ids: HashSet::from(["t1".to_string()]),
             suggested_category: EmailCategory::Newsletter,
             suggested_label: "auto/newsletters".to_string(),
             confidence: 0.9,
+            top_reason: None,
             sample_subjects: vec!["Subject 1".to_string()],
             should_archive: true,
             existing_filter_id: None,
             existing_filter_label_id: None,
             existing_filter_label: None,
             existing_filter_archive: None,
+            existing_filter_query: None,
             source: ClusterSource::EmailScan,
             default_action: None,
+            preview_count: None,
+            list_id: None,
         };
 
         assert_eq!(cluster.email_count(), 2);
+        assert_eq!(cluster.volume_summary(), "2 emails (1 thread)");
+        assert_eq!(cluster.thread_pattern(), None);
     }
 
     #[test]
@@ -1550,12 +3726,17 @@ mod tests {
             is_specific_sender: true,
             subject_pattern: Some("Newsletter".to_string()),
             message_ids: vec!["msg1".to_string(), "msg2".to_string()],
-            label: "AutoManaged/newsletters".to_string(),
+            label: LabelName::new("AutoManaged/newsletters"),
             action: DecisionAction::Accept,
             should_archive: true,
             existing_filter_id: None,
             needs_filter_update: false,
             excluded_senders: vec![],
+            exclusion_reason: None,
+            custom_filter: None,
+            additional_labels: vec![],
+            merged_senders: vec![],
+            list_id: None,
         };
 
         // Serialize to JSON
@@ -1570,6 +3751,36 @@ mod tests {
         assert!(matches!(restored.action, DecisionAction::Accept));
     }
 
+    #[test]
+    fn test_cluster_decision_exclusion_reason() {
+        let decision = ClusterDecision {
+            sender_email: String::new(),
+            sender_domain: "noisy.com".to_string(),
+            is_specific_sender: false,
+            subject_pattern: None,
+            message_ids: vec![],
+            label: LabelName::default(),
+            action: DecisionAction::Exclude,
+            should_archive: false,
+            existing_filter_id: None,
+            needs_filter_update: false,
+            excluded_senders: vec![],
+            exclusion_reason: Some("too noisy".to_string()),
+            custom_filter: None,
+            additional_labels: vec![],
+            merged_senders: vec![],
+            list_id: None,
+        };
+
+        let json = serde_json::to_string(&decision).unwrap();
+        assert!(json.contains("\"exclusion_reason\":\"too noisy\""));
+
+        // Decisions files written before the field existed still load
+        let legacy = json.replace(",\"exclusion_reason\":\"too noisy\"", "");
+        let restored: ClusterDecision = serde_json::from_str(&legacy).unwrap();
+        assert!(restored.exclusion_reason.is_none());
+    }
+
     #[test]
     fn test_decision_action_serialization() {
         let actions = vec![
@@ -1577,6 +3788,9 @@ mod tests {
             DecisionAction::Reject,
             DecisionAction::Skip,
             DecisionAction::Custom("MyLabel".to_string()),
+            DecisionAction::Merge {
+                into_domain: "example.com".to_string(),
+            },
         ];
 
         for action in actions {
@@ -1586,4 +3800,83 @@ mod tests {
             assert_eq!(format!("{:?}", action), format!("{:?}", restored));
         }
     }
+
+    #[test]
+    fn test_resolve_merges() {
+        let decision = |sender: &str, ids: &[&str], action: DecisionAction| ClusterDecision {
+            sender_email: sender.to_string(),
+            sender_domain: sender.rsplit('@').next().unwrap().to_string(),
+            is_specific_sender: !sender.starts_with('*'),
+            subject_pattern: None,
+            message_ids: ids.iter().map(|id| id.to_string()).collect(),
+            label: LabelName::new("AutoManaged/shop"),
+            action,
+            should_archive: false,
+            existing_filter_id: None,
+            needs_filter_update: false,
+            excluded_senders: vec![],
+            exclusion_reason: None,
+            custom_filter: None,
+            additional_labels: vec![],
+            merged_senders: vec![],
+            list_id: None,
+        };
+        let merge = |domain: &str| DecisionAction::Merge {
+            into_domain: domain.to_string(),
+        };
+
+        let mut shop = decision("*@shop.com", &["m1", "m2"], DecisionAction::Accept);
+        shop.excluded_senders = vec!["orders@shop.com".to_string()];
+        let mut mail = decision("*@shop-mail.com", &["m4", "m1"], merge("*@SHOP.com"));
+        mail.excluded_senders = vec!["alerts@shop-mail.com".to_string()];
+        let mut deals = decision("deals@deals.com", &["m8"], merge("*@shop.com"));
+        deals.subject_pattern = Some("Weekly deals".to_string());
+        let decisions = vec![
+            shop,
+            decision("orders@shop.com", &["m3"], merge("*@shop.com")),
+            mail,
+            // Decisions saved before any cluster could be a target name the domain
+            decision("news@SHOP.com", &["m7", "m1"], merge("shop.com")),
+            decision("info@other.com", &["m9"], merge("*@shop.com")),
+            deals,
+            decision("*@rejected.com", &["m5"], DecisionAction::Reject),
+            decision("rewards@rejected.com", &["m6"], merge("*@rejected.com")),
+        ];
+
+        let (resolved, dropped) = resolve_merges(decisions);
+        // Merged clusters get no decision of their own
+        assert_eq!(resolved.len(), 2);
+        let shop = &resolved[0];
+        assert_eq!(shop.message_ids, ["m1", "m2", "m3", "m4", "m7", "m9"]);
+        // The merged sender is no longer carved out of the domain filter, while the
+        // merged domain's own carve-outs are kept
+        assert_eq!(shop.excluded_senders, ["alerts@shop-mail.com"]);
+        // Clusters of other domains widen the filter instead
+        assert_eq!(
+            shop.from_pattern(),
+            "*@shop.com OR *@shop-mail.com OR info@other.com"
+        );
+        // A merge into a cluster that wasn't accepted attributes nothing
+        assert_eq!(resolved[1].message_ids, ["m5"]);
+
+        let dropped: Vec<_> = dropped
+            .iter()
+            .map(|d| (d.cluster.as_str(), d.into_domain.as_str(), d.reason))
+            .collect();
+        assert_eq!(
+            dropped,
+            [
+                (
+                    "deals@deals.com|subject:Weekly deals",
+                    "*@shop.com",
+                    "a subject cluster can only be merged into its domain's cluster"
+                ),
+                (
+                    "rewards@rejected.com",
+                    "*@rejected.com",
+                    "its cluster was not accepted"
+                ),
+            ]
+        );
+    }
 }
@@ -0,0 +1,73 @@
+//! Append-only JSON Lines logs
+//!
+//! The feedback, A/B test and filter change logs store one JSON object per line,
+//! so a run only ever appends to them and older entries are never rewritten.
+
+use crate::error::{GmailError, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+
+/// Append `records` to the log at `path`, creating the file and its directory if needed
+///
+/// `what` names the records in error messages, e.g. "feedback".
+pub async fn append<T: Serialize>(records: &[T], path: &Path, what: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut lines = String::new();
+    for record in records {
+        let line = serde_json::to_string(record)
+            .map_err(|e| GmailError::Unknown(format!("Failed to serialize {}: {}", what, e)))?;
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(lines.as_bytes()).await?;
+    Ok(())
+}
+
+/// Load every record from the log at `path`, oldest first
+///
+/// A missing log holds no records. Blank lines are skipped.
+pub async fn load<T: DeserializeOwned>(path: &Path, what: &str) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = tokio::fs::read_to_string(path).await?;
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .map_err(|e| GmailError::Unknown(format!("Failed to parse {} log: {}", what, e)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_append_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("log.jsonl");
+
+        assert!(load::<u32>(&path, "number").await.unwrap().is_empty());
+        append(&[1u32, 2], &path, "number").await.unwrap();
+        append(&[3u32], &path, "number").await.unwrap();
+        assert_eq!(load::<u32>(&path, "number").await.unwrap(), vec![1, 2, 3]);
+
+        tokio::fs::write(&path, "1\nnot json\n").await.unwrap();
+        let err = load::<u32>(&path, "number").await.unwrap_err();
+        assert!(err.to_string().contains("Failed to parse number log"));
+    }
+}
@@ -1,16 +1,62 @@
 //! Label management and creation with hierarchy support and consolidation logic
-use crate::client::GmailClient;
+use crate::client::{ExistingFilterInfo, GmailClient, LabelInfo};
+use crate::config::LabelVisibility;
 use crate::error::{GmailError, Result};
+use crate::models::{EmailCategory, LabelId, LabelName, MessageMetadata};
+use chrono::Datelike;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
+/// A set of labels for the same sender domain that could be merged into one
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsolidationSuggestion {
+    /// Full names of the labels to merge
+    pub labels_to_merge: Vec<String>,
+    /// Name of the merged label (e.g. "AutoManaged/amazon-com")
+    pub suggested_name: String,
+    pub reason: String,
+    /// Messages that would be moved (0 until counted with a dry-run `execute_consolidation`)
+    pub message_count: usize,
+}
+
+/// Outcome of `LabelManager::merge_labels`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeResult {
+    pub messages_moved: usize,
+    pub sources_deleted: usize,
+}
+
+/// Progress of an in-flight label merge, saved so a crashed merge can resume
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MergeCheckpoint {
+    source_label_ids: Vec<String>,
+    target_label_id: String,
+    /// Sources whose messages have all been moved
+    completed_sources: Vec<String>,
+    messages_moved: usize,
+}
+
+/// Messages moved between checkpoint saves during a merge
+const MERGE_CHECKPOINT_INTERVAL: usize = 5000;
+
+/// Most message IDs Gmail accepts in one `batchModify` call
+const BATCH_MODIFY_CHUNK_SIZE: usize = 1000;
+
+/// Called with the number of messages modified by each completed batch
+pub type LabelProgressCallback = Arc<dyn Fn(usize) + Send + Sync>;
+
 /// Manages Gmail labels including creation, hierarchy management, and consolidation
 pub struct LabelManager {
     client: Box<dyn GmailClient>,
     label_prefix: String,
-    label_cache: HashMap<String, String>, // name -> id mapping
-    created_labels: Vec<String>,
+    label_cache: HashMap<String, LabelId>, // lowercase name -> id mapping
+    created_labels: Vec<LabelId>,
+    /// Where `merge_labels` saves progress (no checkpointing if unset)
+    merge_checkpoint: Option<PathBuf>,
 }
 
 impl LabelManager {
@@ -21,9 +67,16 @@ impl LabelManager {
             label_prefix: prefix,
             label_cache: HashMap::new(),
             created_labels: Vec::new(),
+            merge_checkpoint: None,
         }
     }
 
+    /// Save `merge_labels` progress to `path` so an interrupted merge can resume
+    pub fn with_merge_checkpoint(mut self, path: PathBuf) -> Self {
+        self.merge_checkpoint = Some(path);
+        self
+    }
+
     /// Loads all existing labels from Gmail into the cache
     /// Call this before creating labels to avoid conflicts
     /// Note: Cache keys are stored lowercase for case-insensitive lookups
@@ -31,9 +84,17 @@ impl LabelManager {
         let labels = self.client.list_labels().await?;
         let count = labels.len();
 
+        for orphan in Self::detect_orphaned_hierarchy(&labels) {
+            warn!(
+                "Label '{}' has no parent label in Gmail (parent was probably deleted)",
+                orphan
+            );
+        }
+
         for label in labels {
             // Store with lowercase key for case-insensitive lookup
-            self.label_cache.insert(label.name.to_lowercase(), label.id);
+            self.label_cache
+                .insert(label.name.to_lowercase(), LabelId::new(label.id));
         }
 
         info!("Loaded {} existing labels into cache", count);
@@ -41,7 +102,7 @@ impl LabelManager {
     }
 
     /// Case-insensitive cache lookup helper
-    fn cache_get(&self, name: &str) -> Option<&String> {
+    fn cache_get(&self, name: &str) -> Option<&LabelId> {
         self.label_cache.get(&name.to_lowercase())
     }
 
@@ -51,7 +112,7 @@ impl LabelManager {
     }
 
     /// Insert into cache with lowercase key
-    fn cache_insert(&mut self, name: String, id: String) {
+    fn cache_insert(&mut self, name: String, id: LabelId) {
         self.label_cache.insert(name.to_lowercase(), id);
     }
 
@@ -84,10 +145,39 @@ impl LabelManager {
     }
 
     /// Gets the label cache (for reporting purposes)
-    pub fn get_label_cache(&self) -> &HashMap<String, String> {
+    #[allow(clippy::deprecated_semver)]
+    #[deprecated(since = "next", note = "Use typed accessors instead")]
+    pub fn get_label_cache(&self) -> &HashMap<String, LabelId> {
         &self.label_cache
     }
 
+    /// Names of all known labels, lowercased for case-insensitive lookup
+    pub fn all_label_names(&self) -> Vec<LabelName> {
+        self.label_cache.keys().map(LabelName::new).collect()
+    }
+
+    /// Name and ID of every known label, names lowercased like [`Self::all_label_names`]
+    pub fn labels(&self) -> impl Iterator<Item = (LabelName, LabelId)> + '_ {
+        self.label_cache
+            .iter()
+            .map(|(name, id)| (LabelName::new(name), id.clone()))
+    }
+
+    /// IDs of all known labels
+    pub fn all_label_ids(&self) -> Vec<LabelId> {
+        self.label_cache.values().cloned().collect()
+    }
+
+    /// Number of known labels
+    pub fn label_count(&self) -> usize {
+        self.label_cache.len()
+    }
+
+    /// Whether a label with this name is known (case-insensitive)
+    pub fn contains_label(&self, name: &str) -> bool {
+        self.cache_contains(name)
+    }
+
     /// Creates a label in Gmail if it doesn't already exist
     ///
     /// This function implements the pattern from lines 636-651 of the implementation spec:
@@ -107,7 +197,7 @@ impl LabelManager {
     /// ```ignore
     /// let label_id = manager.create_label("Newsletters/Tech").await?;
     /// ```
-    pub async fn create_label(&mut self, name: &str) -> Result<String> {
+    pub async fn create_label(&mut self, name: &str) -> Result<LabelId> {
         let full_name = format!("{}/{}", self.label_prefix, name);
         let sanitized_name = self.sanitize_label_name(&full_name)?;
 
@@ -148,7 +238,7 @@ impl LabelManager {
     }
 
     /// Gets label ID by name, creating it if necessary
-    pub async fn get_or_create_label(&mut self, name: &str) -> Result<String> {
+    pub async fn get_or_create_label(&mut self, name: &str) -> Result<LabelId> {
         let full_name = format!("{}/{}", self.label_prefix, name);
         let sanitized_name = self.sanitize_label_name(&full_name)?;
 
@@ -161,7 +251,14 @@ impl LabelManager {
 
     /// Creates a label with the exact name provided (no prefix added)
     /// Use this when the label name already has the full path
-    pub async fn create_label_direct(&mut self, full_name: &str) -> Result<String> {
+    ///
+    /// `visibility` applies to the label itself; any parent labels created along
+    /// the way use Gmail's default (shown) visibility.
+    pub async fn create_label_direct(
+        &mut self,
+        full_name: &str,
+        visibility: &LabelVisibility,
+    ) -> Result<LabelId> {
         // Check if label already exists in cache (case-insensitive)
         if let Some(id) = self.cache_get(full_name) {
             debug!("Label '{}' already exists in cache", full_name);
@@ -176,9 +273,13 @@ impl LabelManager {
         info!("Creating label: {}", full_name);
 
         // Create the label via Gmail API
-        let label_id = self.client.create_label(full_name).await.map_err(|e| {
-            GmailError::ApiError(format!("Failed to create label '{}': {}", full_name, e))
-        })?;
+        let label_id = self
+            .client
+            .create_label_with_visibility(full_name, visibility)
+            .await
+            .map_err(|e| {
+                GmailError::ApiError(format!("Failed to create label '{}': {}", full_name, e))
+            })?;
 
         // Track the created label (cache uses lowercase key)
         self.cache_insert(full_name.to_string(), label_id.clone());
@@ -191,6 +292,23 @@ impl LabelManager {
         Ok(label_id)
     }
 
+    /// Changes whether a label is shown in the message list and the label sidebar
+    pub async fn update_label_visibility(
+        &self,
+        label_id: &str,
+        vis: &LabelVisibility,
+    ) -> Result<()> {
+        self.client
+            .update_label_visibility(label_id, vis)
+            .await
+            .map_err(|e| {
+                GmailError::LabelError(format!(
+                    "Failed to update visibility of label '{}': {}",
+                    label_id, e
+                ))
+            })
+    }
+
     /// Ensures all parent labels exist in the hierarchy
     ///
     /// For example, if creating "AutoManaged/Newsletters/Tech",
@@ -199,30 +317,121 @@ impl LabelManager {
     /// # Arguments
     /// * `label_name` - The full hierarchical label name
     async fn ensure_parent_labels(&mut self, label_name: &str) -> Result<()> {
-        let parts: Vec<&str> = label_name.split('/').collect();
-
-        // Create each level of the hierarchy (skip the last part, which is the label itself)
-        for i in 1..parts.len() {
-            let parent_path = parts[..i].join("/");
-
-            // Check if parent already exists (case-insensitive)
-            if !self.cache_contains(&parent_path) {
-                debug!("Creating parent label: {}", parent_path);
-
-                // Create parent directly (without prefix, as it's already full path)
-                let label_id = self.client.create_label(&parent_path).await.map_err(|e| {
-                    GmailError::ApiError(format!(
-                        "Failed to create parent label '{}': {}",
-                        parent_path, e
-                    ))
-                })?;
-
-                self.cache_insert(parent_path.clone(), label_id.clone());
-                self.created_labels.push(label_id);
+        self.create_missing_parents(&[label_name.to_string()])
+            .await
+            .map(|_| ())
+    }
+
+    /// Creates every missing parent of the given labels, shallowest first
+    ///
+    /// Parents are created one at a time so that labels created afterwards
+    /// (possibly concurrently) never race to create the same parent.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of parent labels created
+    pub async fn create_missing_parents(&mut self, label_names: &[String]) -> Result<usize> {
+        let mut created = 0;
+        for parent_path in Self::compute_required_parents(label_names) {
+            if self.cache_contains(&parent_path) {
+                continue;
             }
+
+            debug!("Creating parent label: {}", parent_path);
+            let label_id = self.client.create_label(&parent_path).await.map_err(|e| {
+                GmailError::ApiError(format!(
+                    "Failed to create parent label '{}': {}",
+                    parent_path, e
+                ))
+            })?;
+
+            self.cache_insert(parent_path, label_id.clone());
+            self.created_labels.push(label_id);
+            created += 1;
         }
 
-        Ok(())
+        Ok(created)
+    }
+
+    /// Returns all unique parent paths of the given labels, sorted by depth (shallowest first)
+    ///
+    /// Paths are compared case-insensitively, like Gmail does; the first spelling seen is kept.
+    /// For example `["a/b/c", "a/d"]` gives `["a", "a/b"]`.
+    pub fn compute_required_parents(label_names: &[String]) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut parents = Vec::new();
+
+        for name in label_names {
+            let parts: Vec<&str> = name.split('/').collect();
+            for i in 1..parts.len() {
+                let parent_path = parts[..i].join("/");
+                if seen.insert(parent_path.to_lowercase()) {
+                    parents.push(parent_path);
+                }
+            }
+        }
+
+        parents.sort_by(|a, b| {
+            a.matches('/')
+                .count()
+                .cmp(&b.matches('/').count())
+                .then_with(|| a.to_lowercase().cmp(&b.to_lowercase()))
+        });
+        parents
+    }
+
+    /// Finds nested labels whose parent label no longer exists (e.g. was deleted in Gmail)
+    pub fn detect_orphaned_hierarchy(existing: &[LabelInfo]) -> Vec<String> {
+        let names: HashSet<String> = existing.iter().map(|l| l.name.to_lowercase()).collect();
+
+        let mut orphaned: Vec<String> = existing
+            .iter()
+            .filter(|label| {
+                label
+                    .name
+                    .rsplit_once('/')
+                    .is_some_and(|(parent, _)| !names.contains(&parent.to_lowercase()))
+            })
+            .map(|label| label.name.clone())
+            .collect();
+        orphaned.sort();
+        orphaned
+    }
+
+    /// Expands a label template for a message
+    ///
+    /// Placeholders:
+    /// - `{domain}` - sender's main domain, sanitized (e.g. "mail.github.com" -> "github-com")
+    /// - `{category}` - lowercase category segment (e.g. "newsletters")
+    /// - `{sender}` - part of the sender address before '@'
+    /// - `{year}` - year the message was received
+    ///
+    /// Empty path segments (e.g. a sender without a domain) are dropped.
+    pub fn resolve_label_name(
+        msg: &MessageMetadata,
+        category: &EmailCategory,
+        template: &str,
+    ) -> String {
+        let main_domain = crate::classifier::extract_main_domain(&msg.sender_domain);
+        let domain = crate::classifier::sanitize_label_name(&main_domain);
+        let sender = msg
+            .sender_email
+            .split('@')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+            .replace('/', "-");
+
+        let expanded = template
+            .replace("{domain}", &domain)
+            .replace("{category}", category.label_segment())
+            .replace("{sender}", &sender)
+            .replace("{year}", &msg.date_received.year().to_string());
+
+        expanded
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join("/")
     }
 
     /// Sanitizes a label name to comply with Gmail's requirements
@@ -388,11 +597,11 @@ impl LabelManager {
         );
 
         // Invert the map: label_id -> Vec<message_ids> for batch operations
-        let mut label_to_messages: HashMap<String, Vec<String>> = HashMap::new();
+        let mut label_to_messages: HashMap<LabelId, Vec<String>> = HashMap::new();
         for (message_id, label_ids) in &message_label_map {
             for label_id in label_ids {
                 label_to_messages
-                    .entry(label_id.clone())
+                    .entry(LabelId::new(label_id.as_str()))
                     .or_default()
                     .push(message_id.clone());
             }
@@ -403,7 +612,7 @@ impl LabelManager {
         // Batch apply each label to its messages
         for (label_id, message_ids) in label_to_messages {
             let remove_labels = if remove_inbox {
-                vec!["INBOX".to_string()]
+                vec![LabelId::new("INBOX")]
             } else {
                 vec![]
             };
@@ -417,9 +626,19 @@ impl LabelManager {
                 )
                 .await
             {
-                Ok(count) => {
-                    debug!("Batch applied label {} to {} messages", label_id, count);
-                    success_count += count;
+                Ok(result) => {
+                    debug!(
+                        "Batch applied label {} to {} messages",
+                        label_id, result.succeeded
+                    );
+                    if !result.failed.is_empty() {
+                        warn!(
+                            "Failed to apply label {} to {} messages",
+                            label_id,
+                            result.failed.len()
+                        );
+                    }
+                    success_count += result.succeeded;
                 }
                 Err(e) => {
                     warn!("Failed to batch apply label {}: {}", label_id, e);
@@ -438,12 +657,12 @@ impl LabelManager {
     }
 
     /// Gets the list of labels created by this manager
-    pub fn get_created_labels(&self) -> &[String] {
+    pub fn get_created_labels(&self) -> &[LabelId] {
         &self.created_labels
     }
 
     /// Gets label ID from cache by name (case-insensitive)
-    pub fn get_label_id(&self, label_name: &str) -> Option<String> {
+    pub fn get_label_id(&self, label_name: &str) -> Option<LabelId> {
         self.cache_get(label_name).cloned()
     }
 
@@ -565,7 +784,7 @@ impl LabelManager {
     pub async fn create_labels_for_categories(
         &mut self,
         categories: HashMap<String, String>,
-    ) -> Result<HashMap<String, String>> {
+    ) -> Result<HashMap<String, LabelId>> {
         let mut label_map = HashMap::new();
         let total = categories.len();
         let mut created = 0;
@@ -658,16 +877,16 @@ impl LabelManager {
             .collect();
 
         // Build reverse lookup from label_id to label_name
-        let id_to_name: HashMap<&String, &String> = self.label_cache
+        let id_to_name: HashMap<&str, &String> = self.label_cache
             .iter()
-            .map(|(name, id)| (id, name))
+            .map(|(name, id)| (id.as_str(), name))
             .collect();
 
         // Collect all required label names (directly used labels + their parent paths)
         let mut required_label_names: HashSet<String> = HashSet::new();
 
         for used_id in used_label_ids.iter() {
-            if let Some(label_name) = id_to_name.get(used_id) {
+            if let Some(label_name) = id_to_name.get(used_id.as_str()) {
                 // Add the label itself
                 required_label_names.insert(label_name.to_lowercase());
 
@@ -689,7 +908,7 @@ impl LabelManager {
                 name_lower.starts_with(&prefix_lower)
                     && !required_label_names.contains(&name_lower)
             })
-            .map(|(name, id)| (id.clone(), name.clone()))
+            .map(|(name, id)| (id.to_string(), name.clone()))
             .collect()
     }
 
@@ -704,10 +923,10 @@ impl LabelManager {
         let prefix_lower = prefix.to_lowercase();
 
         // Build reverse lookup from label_id to label_name
-        let id_to_name: HashMap<&String, &String> = self
+        let id_to_name: HashMap<&str, &String> = self
             .label_cache
             .iter()
-            .map(|(name, id)| (id, name))
+            .map(|(name, id)| (id.as_str(), name))
             .collect();
 
         // Collect all label IDs used by filters
@@ -721,7 +940,7 @@ impl LabelManager {
         let mut required_parents: HashSet<String> = HashSet::new();
 
         for used_id in used_label_ids {
-            if let Some(label_name) = id_to_name.get(used_id) {
+            if let Some(label_name) = id_to_name.get(used_id.as_str()) {
                 let label_name_lower = label_name.to_lowercase();
 
                 // Only process labels that start with the prefix
@@ -779,7 +998,7 @@ impl LabelManager {
                 match self.get_or_create_label(&parent_without_prefix).await {
                     Ok(label_id) => {
                         // get_or_create_label already updates the cache
-                        created_labels.push((label_id, parent_path.clone()));
+                        created_labels.push((label_id.to_string(), parent_path.clone()));
                         info!("Created parent label: {}", parent_path);
                     }
                     Err(e) => {
@@ -801,7 +1020,7 @@ impl LabelManager {
 
         // Sort by name length descending to delete children before parents
         let mut sorted: Vec<_> = orphaned.to_vec();
-        sorted.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+        sorted.sort_by_key(|l| std::cmp::Reverse(l.1.len()));
 
         for (label_id, label_name) in sorted {
             match self.client.delete_label(&label_id).await {
@@ -822,9 +1041,293 @@ impl LabelManager {
 
     /// Remove a label from all messages that have it
     /// Returns the number of messages modified
+    /// Suggests merging auto-managed labels when one domain appears under several categories
+    ///
+    /// For example `AutoManaged/receipts/amazon-com` and `AutoManaged/shipping/amazon-com`
+    /// give a suggestion to merge both into `AutoManaged/amazon-com`.
+    pub fn suggest_consolidations(
+        &self,
+        labels: &[LabelInfo],
+        filters: &[ExistingFilterInfo],
+    ) -> Vec<ConsolidationSuggestion> {
+        let prefix = format!("{}/", self.label_prefix.to_lowercase());
+
+        // domain segment -> labels of the form prefix/<category...>/domain
+        let mut by_domain: HashMap<String, Vec<&LabelInfo>> = HashMap::new();
+        for label in labels {
+            if !label.name.to_lowercase().starts_with(&prefix) {
+                continue;
+            }
+            let parts: Vec<&str> = label.name.split('/').collect();
+            if parts.len() < 3 {
+                continue;
+            }
+            by_domain
+                .entry(parts[parts.len() - 1].to_lowercase())
+                .or_default()
+                .push(label);
+        }
+
+        let mut suggestions: Vec<ConsolidationSuggestion> = by_domain
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .map(|mut group| {
+                group.sort_by(|a, b| a.name.cmp(&b.name));
+                let first: Vec<&str> = group[0].name.split('/').collect();
+                let domain = first[first.len() - 1];
+                let categories: Vec<String> = group
+                    .iter()
+                    .map(|l| {
+                        let parts: Vec<&str> = l.name.split('/').collect();
+                        parts[1..parts.len() - 1].join("/")
+                    })
+                    .collect();
+                let filter_count = filters
+                    .iter()
+                    .filter(|f| {
+                        f.add_label_ids
+                            .iter()
+                            .any(|id| group.iter().any(|l| &l.id == id))
+                    })
+                    .count();
+
+                ConsolidationSuggestion {
+                    labels_to_merge: group.iter().map(|l| l.name.clone()).collect(),
+                    suggested_name: format!("{}/{}", first[0], domain),
+                    reason: format!(
+                        "'{}' appears under {} categories ({}); {} filters use these labels",
+                        domain,
+                        group.len(),
+                        categories.join(", "),
+                        filter_count
+                    ),
+                    message_count: 0,
+                }
+            })
+            .collect();
+
+        suggestions.sort_by(|a, b| a.suggested_name.cmp(&b.suggested_name));
+        suggestions
+    }
+
+    /// Moves every message from the labels in `suggestion` to the merged label
+    ///
+    /// The merged label is created if needed. Source labels are left in place (empty)
+    /// so filters pointing at them keep working until they are updated. With `dry_run`,
+    /// nothing is modified and the number of messages that would move is returned.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of distinct messages moved (or that would be moved)
+    pub async fn execute_consolidation(
+        suggestion: &ConsolidationSuggestion,
+        client: &dyn GmailClient,
+        dry_run: bool,
+    ) -> Result<usize> {
+        let labels = client.list_labels().await?;
+        let find_id = |name: &str| {
+            labels
+                .iter()
+                .find(|l| l.name.eq_ignore_ascii_case(name))
+                .map(|l| l.id.clone())
+        };
+
+        // (source label ID, messages currently carrying it)
+        let mut sources = Vec::new();
+        let mut all_messages = HashSet::new();
+        for name in &suggestion.labels_to_merge {
+            let Some(label_id) = find_id(name) else {
+                warn!("Label '{}' no longer exists, skipping", name);
+                continue;
+            };
+            let message_ids = client
+                .list_message_ids(&format!("label:{}", label_id))
+                .await?;
+            all_messages.extend(message_ids.iter().cloned());
+            sources.push((label_id, message_ids));
+        }
+
+        if dry_run {
+            return Ok(all_messages.len());
+        }
+
+        let target_id = match find_id(&suggestion.suggested_name) {
+            Some(id) => LabelId::new(id),
+            None => client.create_label(&suggestion.suggested_name).await?,
+        };
+
+        let add = vec![target_id];
+        for (source_id, message_ids) in &sources {
+            let remove = vec![LabelId::new(source_id.as_str())];
+            for chunk in message_ids.chunks(1000) {
+                client
+                    .batch_modify_labels(chunk, &add, &remove)
+                    .await?
+                    .into_result()?;
+            }
+        }
+
+        info!(
+            "Moved {} messages into '{}'",
+            all_messages.len(),
+            suggestion.suggested_name
+        );
+        Ok(all_messages.len())
+    }
+
+    /// Moves all messages from the source labels to the target label
+    ///
+    /// For each source label, messages are re-labeled in batches of 1000 (adding
+    /// `target_label_id`, removing the source). Progress is checkpointed every
+    /// 5000 messages when a checkpoint path is set; re-running the same merge
+    /// after a crash skips sources that were already finished. Because moved
+    /// messages lose the source label, a partially moved source simply
+    /// continues with the messages that remain.
+    ///
+    /// If `delete_sources` is set, each source label is deleted once emptied.
+    pub async fn merge_labels(
+        &mut self,
+        source_label_ids: &[String],
+        target_label_id: &str,
+        delete_sources: bool,
+    ) -> Result<MergeResult> {
+        if source_label_ids.iter().any(|id| id == target_label_id) {
+            return Err(GmailError::LabelError(
+                "Target label cannot also be a source label".to_string(),
+            ));
+        }
+
+        let mut checkpoint = self
+            .load_merge_checkpoint(source_label_ids, target_label_id)
+            .await?;
+        if checkpoint.messages_moved > 0 || !checkpoint.completed_sources.is_empty() {
+            info!(
+                "Resuming merge: {} sources done, {} messages already moved",
+                checkpoint.completed_sources.len(),
+                checkpoint.messages_moved
+            );
+        }
+
+        let add = vec![LabelId::new(target_label_id)];
+        let mut since_checkpoint = 0;
+        for source_id in source_label_ids {
+            if checkpoint.completed_sources.contains(source_id) {
+                continue;
+            }
+
+            let message_ids = self
+                .client
+                .list_message_ids(&format!("label:{}", source_id))
+                .await?;
+            info!(
+                "Moving {} messages from label {} to {}",
+                message_ids.len(),
+                source_id,
+                target_label_id
+            );
+
+            let remove = vec![LabelId::new(source_id.as_str())];
+            for chunk in message_ids.chunks(1000) {
+                self.client
+                    .batch_modify_labels(chunk, &add, &remove)
+                    .await?
+                    .into_result()?;
+                checkpoint.messages_moved += chunk.len();
+                since_checkpoint += chunk.len();
+
+                if since_checkpoint >= MERGE_CHECKPOINT_INTERVAL {
+                    self.save_merge_checkpoint(&checkpoint).await?;
+                    since_checkpoint = 0;
+                }
+            }
+
+            checkpoint.completed_sources.push(source_id.clone());
+            self.save_merge_checkpoint(&checkpoint).await?;
+        }
+
+        let mut sources_deleted = 0;
+        if delete_sources {
+            for source_id in source_label_ids {
+                self.client.delete_label(source_id).await?;
+                self.label_cache.retain(|_, id| id != source_id);
+                sources_deleted += 1;
+            }
+        }
+
+        if let Some(path) = &self.merge_checkpoint {
+            if path.exists() {
+                tokio::fs::remove_file(path).await?;
+            }
+        }
+
+        Ok(MergeResult {
+            messages_moved: checkpoint.messages_moved,
+            sources_deleted,
+        })
+    }
+
+    /// Loads the checkpoint for this exact merge, or starts a fresh one
+    async fn load_merge_checkpoint(
+        &self,
+        source_label_ids: &[String],
+        target_label_id: &str,
+    ) -> Result<MergeCheckpoint> {
+        let fresh = MergeCheckpoint {
+            source_label_ids: source_label_ids.to_vec(),
+            target_label_id: target_label_id.to_string(),
+            ..Default::default()
+        };
+
+        let Some(path) = &self.merge_checkpoint else {
+            return Ok(fresh);
+        };
+        if !path.exists() {
+            return Ok(fresh);
+        }
+
+        let json = tokio::fs::read_to_string(path).await?;
+        match serde_json::from_str::<MergeCheckpoint>(&json) {
+            Ok(saved)
+                if saved.source_label_ids == source_label_ids
+                    && saved.target_label_id == target_label_id =>
+            {
+                Ok(saved)
+            }
+            Ok(_) => {
+                warn!(
+                    "Ignoring merge checkpoint {:?}: it belongs to a different merge",
+                    path
+                );
+                Ok(fresh)
+            }
+            Err(e) => {
+                warn!("Ignoring unreadable merge checkpoint {:?}: {}", path, e);
+                Ok(fresh)
+            }
+        }
+    }
+
+    async fn save_merge_checkpoint(&self, checkpoint: &MergeCheckpoint) -> Result<()> {
+        let Some(path) = &self.merge_checkpoint else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let json = serde_json::to_string_pretty(checkpoint).map_err(|e| {
+            GmailError::Unknown(format!("Failed to serialize merge checkpoint: {}", e))
+        })?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
+
+    /// Remove `label_id` from every message carrying it, in batches of 1000
+    ///
+    /// `on_progress` is called after each batch with the number of messages
+    /// it modified. Returns the total number of messages modified.
     pub async fn remove_label_from_all_messages(
         &self,
         label_id: &str,
+        on_progress: Option<LabelProgressCallback>,
     ) -> crate::error::Result<usize> {
         // Search for messages with this label
         let query = format!("label:{}", label_id);
@@ -836,23 +1339,30 @@ impl LabelManager {
 
         info!("Removing label from {} messages", message_ids.len());
 
-        // Batch remove the label (Gmail allows up to 1000 per call)
-        let labels_to_remove = vec![label_id.to_string()];
-        let labels_to_add: Vec<String> = vec![];
+        let labels_to_remove = vec![LabelId::new(label_id)];
+        let labels_to_add: Vec<LabelId> = vec![];
 
-        for chunk in message_ids.chunks(1000) {
-            self.client
+        let mut modified = 0;
+        for chunk in message_ids.chunks(BATCH_MODIFY_CHUNK_SIZE) {
+            let count = self
+                .client
                 .batch_modify_labels(chunk, &labels_to_add, &labels_to_remove)
-                .await?;
+                .await?
+                .into_result()?;
+            modified += count;
+            if let Some(on_progress) = &on_progress {
+                on_progress(count);
+            }
         }
 
-        Ok(message_ids.len())
+        Ok(modified)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client::BatchModifyResult;
 
     #[test]
     fn test_sanitize_label_name() {
@@ -865,18 +1375,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -915,18 +1429,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -953,18 +1471,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -999,18 +1521,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1053,18 +1579,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1084,31 +1614,31 @@ mod tests {
             .expect_create_label()
             .with(eq("Automanaged"))
             .times(1)
-            .returning(|_| Ok("label-parent".to_string()));
+            .returning(|_| Ok(LabelId::new("label-parent")));
 
         mock_client
             .expect_create_label()
             .with(eq("Automanaged/Newsletters"))
             .times(1)
-            .returning(|_| Ok("label-newsletters".to_string()));
+            .returning(|_| Ok(LabelId::new("label-newsletters")));
 
         mock_client
             .expect_create_label()
             .with(eq("Automanaged/Newsletters/Github"))
             .times(1)
-            .returning(|_| Ok("label-id-1".to_string()));
+            .returning(|_| Ok(LabelId::new("label-id-1")));
 
         mock_client
             .expect_create_label()
             .with(eq("Automanaged/Receipts"))
             .times(1)
-            .returning(|_| Ok("label-receipts".to_string()));
+            .returning(|_| Ok(LabelId::new("label-receipts")));
 
         mock_client
             .expect_create_label()
             .with(eq("Automanaged/Receipts/Amazon"))
             .times(1)
-            .returning(|_| Ok("label-id-2".to_string()));
+            .returning(|_| Ok(LabelId::new("label-id-2")));
 
         let mut manager = LabelManager::new(Box::new(mock_client), "AutoManaged".to_string());
 
@@ -1126,11 +1656,11 @@ mod tests {
         assert_eq!(label_map.len(), 2);
         assert_eq!(
             label_map.get("newsletters_github"),
-            Some(&"label-id-1".to_string())
+            Some(&LabelId::new("label-id-1"))
         );
         assert_eq!(
             label_map.get("receipts_amazon"),
-            Some(&"label-id-2".to_string())
+            Some(&LabelId::new("label-id-2"))
         );
     }
 
@@ -1146,18 +1676,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1171,11 +1705,16 @@ mod tests {
             .expect_batch_modify_labels()
             .withf(|msg_ids, add_labels, remove_labels| {
                 msg_ids.len() == 2
-                    && add_labels == &["label-123".to_string()]
+                    && add_labels == ["label-123".to_string()]
                     && remove_labels.is_empty()
             })
             .times(1)
-            .returning(|msg_ids, _, _| Ok(msg_ids.len()));
+            .returning(|msg_ids, _, _| {
+                Ok(BatchModifyResult {
+                    succeeded: msg_ids.len(),
+                    ..Default::default()
+                })
+            });
 
         let manager = LabelManager::new(Box::new(mock_client), "AutoManaged".to_string());
 
@@ -1197,18 +1736,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1219,9 +1762,15 @@ mod tests {
         let mut manager = LabelManager::new(Box::new(mock_client), "automanaged".to_string());
 
         // Setup: Create a label cache with a hierarchy
-        manager.cache_insert("automanaged".to_string(), "label-id-root".to_string());
-        manager.cache_insert("automanaged/receipts".to_string(), "label-id-receipts".to_string());
-        manager.cache_insert("automanaged/receipts/amazon".to_string(), "label-id-amazon".to_string());
+        manager.cache_insert("automanaged".to_string(), LabelId::new("label-id-root"));
+        manager.cache_insert(
+            "automanaged/receipts".to_string(),
+            LabelId::new("label-id-receipts"),
+        );
+        manager.cache_insert(
+            "automanaged/receipts/amazon".to_string(),
+            LabelId::new("label-id-amazon"),
+        );
 
         // Create an ExistingFilterInfo that uses only the deepest label
         let filter = crate::client::ExistingFilterInfo {
@@ -1262,18 +1811,22 @@ mod tests {
             impl crate::client::GmailClient for TestGmailClient {
                 async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
                 async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
                 async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
-                async fn create_label(&self, name: &str) -> Result<String>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
                 async fn delete_label(&self, label_id: &str) -> Result<()>;
                 async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
                 async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
                 async fn delete_filter(&self, filter_id: &str) -> Result<()>;
                 async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
-                async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-                async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[String], remove_label_ids: &[String]) -> Result<usize>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
                 async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
                 async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
@@ -1284,9 +1837,15 @@ mod tests {
         let mut manager = LabelManager::new(Box::new(mock_client), "automanaged".to_string());
 
         // Setup: Create a label cache with both used and unused labels
-        manager.cache_insert("automanaged".to_string(), "label-id-root".to_string());
-        manager.cache_insert("automanaged/used".to_string(), "label-id-used".to_string());
-        manager.cache_insert("automanaged/unused".to_string(), "label-id-unused".to_string());
+        manager.cache_insert("automanaged".to_string(), LabelId::new("label-id-root"));
+        manager.cache_insert(
+            "automanaged/used".to_string(),
+            LabelId::new("label-id-used"),
+        );
+        manager.cache_insert(
+            "automanaged/unused".to_string(),
+            LabelId::new("label-id-unused"),
+        );
 
         // Create an ExistingFilterInfo that uses only automanaged/used
         let filter = crate::client::ExistingFilterInfo {
@@ -1318,4 +1877,492 @@ mod tests {
         assert!(!orphaned_names.contains(&"automanaged/used".to_string()),
             "Used label should not be orphaned");
     }
+
+    #[tokio::test]
+    async fn test_create_label_direct_with_visibility() {
+        use crate::config::VisibilityMode;
+        use async_trait::async_trait;
+        use mockall::predicate::*;
+
+        mockall::mock! {
+            pub TestGmailClient {}
+
+            #[async_trait]
+            impl crate::client::GmailClient for TestGmailClient {
+                async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
+                async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
+                async fn delete_label(&self, label_id: &str) -> Result<()>;
+                async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
+                async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
+                async fn delete_filter(&self, filter_id: &str) -> Result<()>;
+                async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
+                async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
+                async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
+                async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
+            }
+        }
+
+        let hidden = LabelVisibility {
+            message_list: VisibilityMode::Show,
+            label_list: VisibilityMode::Hide,
+        };
+
+        let mut mock_client = MockTestGmailClient::new();
+        // Parent is created with default visibility, the label itself with the requested one
+        mock_client
+            .expect_create_label()
+            .with(eq("AutoManaged"))
+            .times(1)
+            .returning(|_| Ok(LabelId::new("label-root")));
+        mock_client
+            .expect_create_label_with_visibility()
+            .with(eq("AutoManaged/Newsletters"), eq(hidden))
+            .times(1)
+            .returning(|_, _| Ok(LabelId::new("label-newsletters")));
+        mock_client
+            .expect_update_label_visibility()
+            .with(eq("label-newsletters"), eq(LabelVisibility::default()))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut manager = LabelManager::new(Box::new(mock_client), "AutoManaged".to_string());
+        let id = manager
+            .create_label_direct("AutoManaged/Newsletters", &hidden)
+            .await
+            .unwrap();
+        assert_eq!(id, "label-newsletters");
+
+        manager
+            .update_label_visibility(id.as_str(), &LabelVisibility::default())
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_resolve_label_name() {
+        use chrono::TimeZone;
+
+        let msg = MessageMetadata {
+            id: "1".to_string(),
+            thread_id: "t1".to_string(),
+            sender_email: "Digest@mail.github.com".to_string(),
+            sender_domain: "mail.github.com".to_string(),
+            sender_name: "GitHub".to_string(),
+            subject: "Weekly digest".to_string(),
+            recipients: vec![],
+            date_received: chrono::Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+            labels: vec![],
+            has_unsubscribe: true,
+            is_automated: true,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 0,
+        };
+        let resolve = LabelManager::resolve_label_name;
+
+        assert_eq!(
+            resolve(&msg, &EmailCategory::Newsletter, "auto/{category}/{domain}"),
+            "auto/newsletters/github-com"
+        );
+        assert_eq!(
+            resolve(&msg, &EmailCategory::Other, "auto/{year}/{domain}/{sender}"),
+            "auto/2024/github-com/digest"
+        );
+
+        let mut no_domain = msg.clone();
+        no_domain.sender_domain = String::new();
+        assert_eq!(
+            resolve(
+                &no_domain,
+                &EmailCategory::Receipt,
+                "auto/{category}/{domain}"
+            ),
+            "auto/receipts"
+        );
+    }
+
+    #[test]
+    fn test_compute_required_parents() {
+        let labels = vec![
+            "auto/tech/github".to_string(),
+            "auto/tech/gitlab".to_string(),
+            "Auto/Receipts/amazon".to_string(),
+            "standalone".to_string(),
+        ];
+
+        assert_eq!(
+            LabelManager::compute_required_parents(&labels),
+            vec!["auto", "Auto/Receipts", "auto/tech"]
+        );
+        assert!(LabelManager::compute_required_parents(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_detect_orphaned_hierarchy() {
+        let label = |id: &str, name: &str| LabelInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            color: None,
+        };
+        let existing = vec![
+            label("1", "INBOX"),
+            label("2", "auto"),
+            label("3", "auto/tech"),
+            label("4", "AUTO/tech/github"),
+            label("5", "auto/receipts/amazon"),
+            label("6", "gone/child"),
+        ];
+
+        assert_eq!(
+            LabelManager::detect_orphaned_hierarchy(&existing),
+            vec!["auto/receipts/amazon", "gone/child"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_consolidation() {
+        use async_trait::async_trait;
+        use mockall::predicate::*;
+
+        mockall::mock! {
+            pub TestGmailClient {}
+
+            #[async_trait]
+            impl crate::client::GmailClient for TestGmailClient {
+                async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
+                async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
+                async fn delete_label(&self, label_id: &str) -> Result<()>;
+                async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
+                async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
+                async fn delete_filter(&self, filter_id: &str) -> Result<()>;
+                async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
+                async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
+                async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
+                async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
+            }
+        }
+
+        let labels = vec![
+            LabelInfo {
+                id: "L1".to_string(),
+                name: "AutoManaged/receipts/amazon-com".to_string(),
+                color: None,
+            },
+            LabelInfo {
+                id: "L2".to_string(),
+                name: "AutoManaged/shipping/amazon-com".to_string(),
+                color: None,
+            },
+            LabelInfo {
+                id: "L3".to_string(),
+                name: "AutoManaged/newsletters/github-com".to_string(),
+                color: None,
+            },
+            LabelInfo {
+                id: "L4".to_string(),
+                name: "Personal/receipts/amazon-com".to_string(),
+                color: None,
+            },
+        ];
+        let filters = vec![ExistingFilterInfo {
+            id: "f1".to_string(),
+            query: Some("from:(*@amazon.com)".to_string()),
+            from: None,
+            to: None,
+            subject: None,
+            add_label_ids: vec!["L2".to_string()],
+            remove_label_ids: vec![],
+        }];
+
+        let manager = LabelManager::new(
+            Box::new(MockTestGmailClient::new()),
+            "AutoManaged".to_string(),
+        );
+        let suggestions = manager.suggest_consolidations(&labels, &filters);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].suggested_name, "AutoManaged/amazon-com");
+        assert_eq!(
+            suggestions[0].labels_to_merge,
+            vec![
+                "AutoManaged/receipts/amazon-com",
+                "AutoManaged/shipping/amazon-com"
+            ]
+        );
+        assert!(suggestions[0].reason.contains("receipts, shipping"));
+        assert!(suggestions[0].reason.contains("1 filters"));
+
+        let mut client = MockTestGmailClient::new();
+        let listed = labels.clone();
+        client
+            .expect_list_labels()
+            .returning(move || Ok(listed.clone()));
+        client
+            .expect_list_message_ids()
+            .with(eq("label:L1"))
+            .returning(|_| Ok(vec!["m1".to_string(), "m2".to_string()]));
+        client
+            .expect_list_message_ids()
+            .with(eq("label:L2"))
+            .returning(|_| Ok(vec!["m2".to_string(), "m3".to_string()]));
+        client
+            .expect_create_label()
+            .with(eq("AutoManaged/amazon-com"))
+            .times(1)
+            .returning(|_| Ok(LabelId::new("L5")));
+        client
+            .expect_batch_modify_labels()
+            .times(2)
+            .returning(|ids, add, _| {
+                assert_eq!(add, ["L5".to_string()]);
+                Ok(BatchModifyResult {
+                    succeeded: ids.len(),
+                    ..Default::default()
+                })
+            });
+
+        let would_move = LabelManager::execute_consolidation(&suggestions[0], &client, true)
+            .await
+            .unwrap();
+        assert_eq!(would_move, 3);
+
+        let moved = LabelManager::execute_consolidation(&suggestions[0], &client, false)
+            .await
+            .unwrap();
+        assert_eq!(moved, 3);
+    }
+
+    #[tokio::test]
+    async fn test_merge_labels_resumes_from_checkpoint() {
+        use async_trait::async_trait;
+        use mockall::predicate::*;
+
+        mockall::mock! {
+            pub TestGmailClient {}
+
+            #[async_trait]
+            impl crate::client::GmailClient for TestGmailClient {
+                async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
+                async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
+                async fn delete_label(&self, label_id: &str) -> Result<()>;
+                async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
+                async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
+                async fn delete_filter(&self, filter_id: &str) -> Result<()>;
+                async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
+                async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
+                async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
+                async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
+            }
+        }
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let checkpoint_path = temp_dir.path().join("merge-checkpoint.json");
+        let sources = vec!["L1".to_string(), "L2".to_string()];
+
+        // A previous run finished L1 before crashing
+        let saved = MergeCheckpoint {
+            source_label_ids: sources.clone(),
+            target_label_id: "T".to_string(),
+            completed_sources: vec!["L1".to_string()],
+            messages_moved: 10,
+        };
+        std::fs::write(&checkpoint_path, serde_json::to_string(&saved).unwrap()).unwrap();
+
+        let mut client = MockTestGmailClient::new();
+        client
+            .expect_list_message_ids()
+            .with(eq("label:L2"))
+            .times(1)
+            .returning(|_| Ok((0..1500).map(|i| format!("m{}", i)).collect()));
+        client
+            .expect_batch_modify_labels()
+            .times(2)
+            .returning(|ids, add, remove| {
+                assert_eq!(add, ["T".to_string()]);
+                assert_eq!(remove, ["L2".to_string()]);
+                Ok(BatchModifyResult {
+                    succeeded: ids.len(),
+                    ..Default::default()
+                })
+            });
+        client.expect_delete_label().times(2).returning(|_| Ok(()));
+
+        let mut manager = LabelManager::new(Box::new(client), "AutoManaged".to_string())
+            .with_merge_checkpoint(checkpoint_path.clone());
+        let result = manager.merge_labels(&sources, "T", true).await.unwrap();
+
+        assert_eq!(
+            result,
+            MergeResult {
+                messages_moved: 1510,
+                sources_deleted: 2,
+            }
+        );
+        assert!(!checkpoint_path.exists());
+
+        let err = manager.merge_labels(&sources, "L1", false).await;
+        assert!(err.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remove_label_from_all_messages_reports_progress() {
+        use async_trait::async_trait;
+        use mockall::predicate::*;
+        use std::sync::Mutex;
+
+        mockall::mock! {
+            pub TestGmailClient {}
+
+            #[async_trait]
+            impl crate::client::GmailClient for TestGmailClient {
+                async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+                async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+                async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
+                async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
+                async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+                async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+                async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
+                async fn delete_label(&self, label_id: &str) -> Result<()>;
+                async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
+                async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
+                async fn delete_filter(&self, filter_id: &str) -> Result<()>;
+                async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
+                async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+                async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+                async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
+                async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
+                async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
+                async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
+            }
+        }
+
+        let mut client = MockTestGmailClient::new();
+        client
+            .expect_list_message_ids()
+            .with(eq("label:L1"))
+            .times(1)
+            .returning(|_| Ok((0..2500).map(|i| format!("m{}", i)).collect()));
+        client
+            .expect_batch_modify_labels()
+            .times(3)
+            .returning(|ids, add, remove| {
+                assert!(ids.len() <= 1000);
+                assert!(add.is_empty());
+                assert_eq!(remove, ["L1".to_string()]);
+                Ok(BatchModifyResult {
+                    succeeded: ids.len(),
+                    ..Default::default()
+                })
+            });
+
+        let manager = LabelManager::new(Box::new(client), "AutoManaged".to_string());
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&progress);
+        let on_progress: LabelProgressCallback =
+            Arc::new(move |count| recorded.lock().unwrap().push(count));
+
+        let removed = manager
+            .remove_label_from_all_messages("L1", Some(on_progress))
+            .await
+            .unwrap();
+
+        assert_eq!(removed, 2500);
+        assert_eq!(*progress.lock().unwrap(), vec![1000, 1000, 500]);
+    }
+
+    #[test]
+    fn test_label_accessors() {
+        let client = crate::client::MockGmailClient::new(crate::client::GmailFixture::default());
+        let mut manager = LabelManager::new(Box::new(client), "automanaged".to_string());
+        manager.cache_insert(
+            "AutoManaged/Receipts".to_string(),
+            LabelId::new("label-id-receipts"),
+        );
+        manager.cache_insert(
+            "automanaged/news".to_string(),
+            LabelId::new("label-id-news"),
+        );
+
+        assert_eq!(manager.label_count(), 2);
+        assert!(manager.contains_label("automanaged/receipts"));
+        assert!(manager.contains_label("AUTOMANAGED/NEWS"));
+        assert!(!manager.contains_label("automanaged/other"));
+
+        let mut names = manager.all_label_names();
+        names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        assert_eq!(
+            names,
+            vec![
+                LabelName::new("automanaged/news"),
+                LabelName::new("automanaged/receipts")
+            ]
+        );
+
+        let mut ids = manager.all_label_ids();
+        ids.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        assert_eq!(
+            ids,
+            vec![
+                LabelId::new("label-id-news"),
+                LabelId::new("label-id-receipts")
+            ]
+        );
+
+        let mut labels: Vec<_> = manager.labels().collect();
+        labels.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        assert_eq!(
+            labels,
+            vec![
+                (
+                    LabelName::new("automanaged/news"),
+                    LabelId::new("label-id-news")
+                ),
+                (
+                    LabelName::new("automanaged/receipts"),
+                    LabelId::new("label-id-receipts")
+                ),
+            ]
+        );
+    }
 }
@@ -47,20 +47,31 @@
 //!
 //! # Module Organization
 //!
+//! - [`anomaly`] - Detection of sudden bursts of mail from a sender
 //! - [`auth`] - OAuth2 authentication and Gmail API initialization
+//! - [`backup`] - Timestamped backups of filters, labels and state
+//! - [`cleanup`] - Removal of old reports, state snapshots and decision files
 //! - [`client`] - Rate-limited Gmail API client with retry logic
 //! - [`classifier`] - Email classification (rule-based and ML)
 //! - [`cli`] - Command-line interface and pipeline orchestration
 //! - [`config`] - Configuration management
 //! - [`error`] - Error types and result aliases
 //! - [`filter_manager`] - Gmail filter rule generation and management
+//! - [`filter_stats`] - Filter match-count tracking and stale filter detection
+//! - [`jsonl`] - Append-only JSON Lines logs
 //! - [`label_manager`] - Gmail label creation and hierarchy management
+//! - [`live_stats`] - Cached account-wide Gmail counts for `status --live`
 //! - [`models`] - Core data structures
+//! - [`network`] - Grouping related sender domains by MX record
 //! - [`scanner`] - Email scanning with concurrent fetching
 //! - [`state`] - Processing state management with checkpointing
+//! - [`unsubscribe`] - One-click unsubscribing via `List-Unsubscribe` links
 
+pub mod anomaly;
 pub mod auth;
+pub mod backup;
 pub mod circuit_breaker;
+pub mod cleanup;
 pub mod classifier;
 pub mod cli;
 pub mod client;
@@ -68,21 +79,28 @@ pub mod config;
 pub mod error;
 pub mod exclusions;
 pub mod filter_manager;
+pub mod filter_stats;
 pub mod interactive;
+pub mod jsonl;
 pub mod label_manager;
+pub mod live_stats;
 pub mod models;
+pub mod network;
 pub mod rate_limiter;
 pub mod scanner;
 pub mod state;
+pub mod unsubscribe;
 
 // Re-export commonly used types for convenience
 pub use error::{GmailError, Result};
 
 // Core data models
-pub use models::{Classification, EmailCategory, FilterRule, MessageMetadata};
+pub use models::{
+    Classification, EmailCategory, FilterRule, MessageMetadata, UnsubscribeLink, UnsubscribeMethod,
+};
 
 // Classifier types
-pub use classifier::{DomainStats, EmailClassifier};
+pub use classifier::{ClassifierPlugin, DomainStats, EmailClassifier, HeuristicPlugin, MlPlugin};
 
 // Scanner types
 pub use scanner::{MessageFormat, ScanCheckpoint};
@@ -94,25 +112,28 @@ pub use config::{
 };
 
 // Client traits
-pub use client::{GmailClient, ProductionGmailClient, RateLimitedGmailClient};
+pub use client::{
+    BatchModifyResult, FailedMessage, GmailClient, ProductionGmailClient, RateLimitedGmailClient,
+};
 
 // Circuit breaker
-pub use circuit_breaker::{CircuitBreaker, CircuitBreakerStats, CircuitState};
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerStats, CircuitState, CircuitStatus};
 
 // Rate limiting
-pub use rate_limiter::{QuotaCost, QuotaRateLimiter, QuotaStats};
+pub use rate_limiter::{QuotaBreakdown, QuotaCost, QuotaRateLimiter, QuotaStats};
 
 // Manager types
 pub use filter_manager::FilterManager;
 pub use label_manager::LabelManager;
 
 // State management
-pub use state::{ProcessingPhase, ProcessingState};
+pub use state::{ProcessingPhase, ProcessingState, SerializedClassification, StateDiff};
 
 // CLI types (for binary usage)
 pub use cli::{Cli, Commands, ProgressReporter, Report};
 
 // Interactive review types
 pub use interactive::{
-    create_clusters, ClusterDecision, DecisionAction, EmailCluster, ReviewSession,
+    auto_merge_domain_clusters, create_clusters, ClusterDecision, DecisionAction, EmailCluster,
+    ReviewSession,
 };
@@ -0,0 +1,175 @@
+//! Account-wide Gmail counts for `status --live`
+//!
+//! Counting the inbox pages through every message ID, so the counts are cached
+//! in `.gmail-automation/live-cache.json` for `LIVE_CACHE_TTL_SECS` and repeated
+//! `status --live` calls don't hit the API each time.
+
+use crate::client::GmailClient;
+use crate::error::{GmailError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// How long fetched counts are reused before Gmail is asked again
+pub const LIVE_CACHE_TTL_SECS: i64 = 60;
+
+/// Counts fetched from the Gmail account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveStats {
+    pub fetched_at: DateTime<Utc>,
+    /// Label prefix `managed_labels` was counted for
+    pub label_prefix: String,
+    pub inbox_messages: usize,
+    pub filters: usize,
+    /// Labels whose name starts with `label_prefix` (case-insensitive)
+    pub managed_labels: usize,
+}
+
+impl LiveStats {
+    /// Count inbox messages, filters and labels under `label_prefix`
+    pub async fn fetch(client: &dyn GmailClient, label_prefix: &str) -> Result<Self> {
+        let (inbox, filters, labels) = tokio::join!(
+            client.list_message_ids("in:inbox"),
+            client.list_filters(),
+            client.list_labels()
+        );
+        let prefix = label_prefix.to_lowercase();
+
+        Ok(Self {
+            fetched_at: Utc::now(),
+            label_prefix: label_prefix.to_string(),
+            inbox_messages: inbox?.len(),
+            filters: filters?.len(),
+            managed_labels: labels?
+                .iter()
+                .filter(|l| l.name.to_lowercase().starts_with(&prefix))
+                .count(),
+        })
+    }
+
+    /// Whether these counts are recent enough to show again at `now`
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        now - self.fetched_at < chrono::Duration::seconds(LIVE_CACHE_TTL_SECS)
+    }
+
+    /// Cached counts for `label_prefix` that are still fresh at `now`
+    ///
+    /// A missing or unreadable cache counts as no cache.
+    pub async fn load_cached(path: &Path, label_prefix: &str, now: DateTime<Utc>) -> Option<Self> {
+        let json = tokio::fs::read_to_string(path).await.ok()?;
+        let stats: Self = serde_json::from_str(&json).ok()?;
+        (stats.label_prefix == label_prefix && stats.is_fresh(now)).then_some(stats)
+    }
+
+    /// Save the counts to a JSON cache file
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| GmailError::Unknown(format!("Failed to serialize live stats: {}", e)))?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ExistingFilterInfo, LabelInfo};
+    use crate::models::FilterRule;
+    use async_trait::async_trait;
+
+    mockall::mock! {
+        pub TestGmailClient {}
+
+        #[async_trait]
+        impl GmailClient for TestGmailClient {
+            async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
+            async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+            async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+            async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
+            async fn list_labels(&self) -> Result<Vec<LabelInfo>>;
+            async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+            async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+            async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
+            async fn delete_label(&self, label_id: &str) -> Result<()>;
+            async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
+            async fn list_filters(&self) -> Result<Vec<ExistingFilterInfo>>;
+            async fn delete_filter(&self, filter_id: &str) -> Result<()>;
+            async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
+            async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+            async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+            async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+            async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+            async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
+            async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
+            async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
+            async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
+        }
+    }
+
+    fn label(name: &str) -> LabelInfo {
+        LabelInfo {
+            id: format!("Label_{}", name),
+            name: name.to_string(),
+            color: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_live_stats_fetch() {
+        let mut client = MockTestGmailClient::new();
+        client
+            .expect_list_message_ids()
+            .withf(|query| query == "in:inbox")
+            .returning(|_| Ok(vec!["m1".to_string(), "m2".to_string()]));
+        client.expect_list_filters().returning(|| Ok(Vec::new()));
+        client.expect_list_labels().returning(|| {
+            Ok(vec![
+                label("AutoManaged/Shopping"),
+                label("automanaged/News"),
+                label("Personal"),
+            ])
+        });
+
+        let stats = LiveStats::fetch(&client, "AutoManaged").await.unwrap();
+        assert_eq!(stats.inbox_messages, 2);
+        assert_eq!(stats.filters, 0);
+        assert_eq!(stats.managed_labels, 2);
+    }
+
+    #[tokio::test]
+    async fn test_live_stats_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("live-cache.json");
+        let now = Utc::now();
+        assert!(LiveStats::load_cached(&path, "AutoManaged", now)
+            .await
+            .is_none());
+
+        let stats = LiveStats {
+            fetched_at: now,
+            label_prefix: "AutoManaged".to_string(),
+            inbox_messages: 120,
+            filters: 8,
+            managed_labels: 5,
+        };
+        stats.save(&path).await.unwrap();
+
+        let later = now + chrono::Duration::seconds(LIVE_CACHE_TTL_SECS - 1);
+        assert_eq!(
+            LiveStats::load_cached(&path, "AutoManaged", later).await,
+            Some(stats)
+        );
+        // Expired, or counted for another prefix
+        let expired = now + chrono::Duration::seconds(LIVE_CACHE_TTL_SECS);
+        assert!(LiveStats::load_cached(&path, "AutoManaged", expired)
+            .await
+            .is_none());
+        assert!(LiveStats::load_cached(&path, "Other", later)
+            .await
+            .is_none());
+    }
+}
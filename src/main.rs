@@ -1,9 +1,15 @@
 use anyhow::Result;
 use clap::Parser;
-use gmail_automation::cli::{self, Cli, Commands};
+use gmail_automation::cli::{
+    self, Cli, Commands, ExclusionAction, ExportFormat, ImportSource, OutputFormat, ScanFormat,
+};
 use gmail_automation::client::GmailClient;
 use gmail_automation::config::Config;
 use gmail_automation::error::GmailError;
+use gmail_automation::exclusions::ExclusionManager;
+use gmail_automation::live_stats::LiveStats;
+use gmail_automation::models::{describe_gmail_query, LabelName};
+use gmail_automation::unsubscribe::UnsubscribePlan;
 use indicatif::MultiProgress;
 use std::io::Write;
 use std::process;
@@ -75,17 +81,56 @@ impl<'a> MakeWriter<'a> for MultiProgressMakeWriter {
     }
 }
 
-#[tokio::main]
-async fn main() {
+fn main() {
+    // Parse CLI arguments
+    let cli = Cli::parse();
+
+    // Size the runtime before it starts; an unreadable config falls back to the
+    // default here and is reported by the command that loads it
+    let worker_threads = Config::load_blocking(&cli.config)
+        .unwrap_or_default()
+        .execution
+        .effective_worker_threads();
+
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(worker_threads)
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Error: Failed to start async runtime: {}", e);
+            process::exit(1);
+        }
+    };
+
     // Exit with proper code on error
-    if let Err(e) = run().await {
-        eprintln!("Error: {}", e);
+    if let Err(e) = runtime.block_on(run(cli)) {
+        display_error(&e);
         eprintln!("\nFor help, run: gmail-filters --help");
         process::exit(1);
     }
 }
 
-async fn run() -> Result<()> {
+/// Run the command; a cancelled operation (e.g. quitting the review) is not an error
+async fn run(cli: Cli) -> Result<()> {
+    match run_command(cli).await {
+        Err(e) if is_cancellation(&e) => {
+            eprintln!("Cancelled.");
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Whether the error is the user cancelling, beneath any added context
+fn is_cancellation(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<GmailError>()
+        .is_some_and(|e| matches!(e.root_cause(), GmailError::OperationCancelled(_)))
+}
+
+async fn run_command(cli: Cli) -> Result<()> {
     // Install default crypto provider for rustls
     // This is necessary because multiple dependencies use different crypto providers
     // On non-Windows platforms, use aws-lc-rs (better performance, FIPS support)
@@ -100,9 +145,6 @@ async fn run() -> Result<()> {
         .install_default()
         .map_err(|_| anyhow::anyhow!("Failed to install default crypto provider"))?;
 
-    // Parse CLI arguments
-    let cli = Cli::parse();
-
     // Initialize tracing with level based on verbose flag
     let filter = if cli.verbose {
         EnvFilter::try_from_default_env()
@@ -178,6 +220,12 @@ async fn run() -> Result<()> {
             resume,
             ignore_exclusions,
             ref apply_decisions,
+            ref tags,
+            auto_backup,
+            resume_review,
+            ref domains,
+            ref export_domain_stats,
+            ref test_mode,
         } => {
             tracing::info!("Starting full pipeline run");
             if dry_run {
@@ -195,6 +243,19 @@ async fn run() -> Result<()> {
             if let Some(ref path) = apply_decisions {
                 println!("Applying decisions from: {:?}", path);
             }
+            if !domains.is_empty() {
+                println!("Scanning only these domains: {}", domains.join(", "));
+            }
+            if let Some(ref path) = test_mode {
+                println!(
+                    "Running in TEST MODE against fixture {:?} - Gmail is not contacted",
+                    path
+                );
+                println!(
+                    "State and reports are written to {:?}",
+                    cli::test_mode_state_dir()
+                );
+            }
 
             // Run the complete pipeline (clone the inner MultiProgress, not the Arc)
             // Review mode is enabled by default; pass !no_review
@@ -207,6 +268,13 @@ async fn run() -> Result<()> {
                 resume,
                 ignore_exclusions,
                 apply_decisions.clone(),
+                tags.clone(),
+                auto_backup,
+                resume_review,
+                domains.clone(),
+                export_domain_stats.clone(),
+                test_mode.clone(),
+                None,
                 (*multi_progress).clone(),
             )
             .await?;
@@ -223,6 +291,33 @@ async fn run() -> Result<()> {
             println!("Filters created: {}", report.filters_created);
             println!("Messages modified: {}", report.messages_modified);
             println!("Messages archived: {}", report.messages_archived);
+            if report.retry_attempts > 0 {
+                println!("Fetch retries: {}", report.retry_attempts);
+            }
+            if report.permanently_failed > 0 {
+                println!(
+                    "Messages that could not be fetched: {}",
+                    report.permanently_failed
+                );
+            }
+            if report.bounces_detected > 0 {
+                println!("Bounces detected: {}", report.bounces_detected);
+            }
+            if !report.anomalies.is_empty() {
+                println!("Volume spikes detected: {}", report.anomalies.len());
+            }
+            if report.failed_count > 0 {
+                println!(
+                    "Messages with label application errors: {}",
+                    report.failed_count
+                );
+                for id in report.failed_message_ids.iter().take(10) {
+                    println!("  - {}", id);
+                }
+                if report.failed_count > 10 {
+                    println!("  ... and {} more (see `status`)", report.failed_count - 10);
+                }
+            }
             // Cleanup stats
             if report.hierarchy_labels_created > 0 || report.orphaned_labels_deleted > 0
                 || report.filters_deleted > 0 || report.messages_cleaned > 0
@@ -247,6 +342,78 @@ async fn run() -> Result<()> {
             Ok(())
         }
 
+        Commands::Watch {
+            interval_mins,
+            dry_run,
+        } => {
+            use futures::StreamExt;
+
+            let mut config = Config::load(&cli.config).await?;
+            // Ends only if the watcher can't be set up; runs then use the last config
+            let mut changes = Some(Box::pin(gmail_automation::config::watch_config(
+                &cli.config,
+            )));
+            let interval = std::time::Duration::from_secs(interval_mins.saturating_mul(60));
+            println!(
+                "Running every {} minutes and reloading {:?} when it changes (Ctrl-C to stop)",
+                interval_mins, cli.config
+            );
+
+            loop {
+                let run = cli::run_pipeline(
+                    &cli,
+                    dry_run,
+                    false,
+                    false,
+                    false,
+                    false,
+                    false,
+                    None,
+                    Vec::new(),
+                    false,
+                    false,
+                    Vec::new(),
+                    None,
+                    None,
+                    Some(config.clone()),
+                    (*multi_progress).clone(),
+                );
+                let next_run = tokio::time::sleep(interval);
+                match run.await {
+                    Ok(report) => println!(
+                        "Run {} finished: {} emails scanned, {} filters created",
+                        report.run_id, report.emails_scanned, report.filters_created
+                    ),
+                    // A failed run doesn't stop the daemon; the next one may succeed
+                    Err(e) => tracing::error!("Run failed: {}", e.chain_message()),
+                }
+
+                tokio::pin!(next_run);
+                loop {
+                    let Some(stream) = changes.as_mut() else {
+                        (&mut next_run).await;
+                        break;
+                    };
+                    // Changes made during a long run are applied before the next one
+                    tokio::select! {
+                        biased;
+                        change = stream.next() => match change {
+                            Some(Ok(reloaded)) => {
+                                tracing::info!("Reloaded config from {:?}", cli.config);
+                                config = reloaded;
+                            }
+                            Some(Err(e)) => tracing::warn!(
+                                "Keeping the previous config, reload failed: {}",
+                                e
+                            ),
+                            None => changes = None,
+                        },
+                        _ = &mut next_run => break,
+                    }
+                }
+            }
+        }
+
         Commands::Rollback {
             run_id: _,
             labels_only: _,
@@ -266,27 +433,104 @@ async fn run() -> Result<()> {
             Ok(())
         }
 
-        Commands::Status { detailed } => {
+        Commands::Status {
+            detailed,
+            diff_with,
+            live,
+        } => {
             tracing::info!("Checking status...");
 
+            // Counts from Gmail itself, reused from the cache for a minute
+            let live_stats = if live {
+                let config = Config::load(&cli.config).await?;
+                let cache_path = cli.state_file.with_file_name("live-cache.json");
+                let prefix = &config.labels.prefix;
+                match LiveStats::load_cached(&cache_path, prefix, chrono::Utc::now()).await {
+                    Some(stats) => Some(stats),
+                    None => {
+                        let hub = gmail_automation::auth::initialize_gmail_hub(
+                            &cli.credentials,
+                            &cli.token_cache,
+                        )
+                        .await?;
+                        let client =
+                            gmail_automation::client::ProductionGmailClient::with_full_config(
+                                hub,
+                                config.scan.max_concurrent_requests,
+                                250.0, // quota units per second
+                                500.0, // quota burst capacity
+                                config.circuit_breaker.clone(),
+                            )
+                            .with_retry_config(config.client.retry.clone());
+                        let stats = LiveStats::fetch(&client, prefix).await?;
+                        if let Err(e) = stats.save(&cache_path).await {
+                            tracing::warn!("Failed to cache live stats: {}", e);
+                        }
+                        Some(stats)
+                    }
+                }
+            } else {
+                None
+            };
+            let live_lines: Vec<String> = live_stats
+                .iter()
+                .flat_map(|stats| {
+                    [
+                        format!(
+                            "Inbox messages: {}",
+                            cli::format_number(stats.inbox_messages as u64)
+                        ),
+                        format!("Filters: {}", stats.filters),
+                        format!(
+                            "Labels under {}: {}",
+                            stats.label_prefix, stats.managed_labels
+                        ),
+                        format!("Fetched: {}", stats.fetched_at.format("%Y-%m-%d %H:%M:%S")),
+                    ]
+                })
+                .collect();
+
             // Load current state if exists
             if cli.state_file.exists() {
-                let state = gmail_automation::state::ProcessingState::load(&cli.state_file).await?;
+                let config = Config::load(&cli.config).await?;
+                let state_key = config.state.encryption_key()?;
+                let state = gmail_automation::state::ProcessingState::load_with_key(
+                    &cli.state_file,
+                    state_key.as_ref(),
+                )
+                .await?;
+
+                let mut local_lines = vec![format!("Run ID: {}", state.run_id)];
+                if !state.tags.is_empty() {
+                    local_lines.push(format!("Tags: {}", state.tags.join(", ")));
+                }
+                local_lines.extend([
+                    format!("Started: {}", state.started_at.format("%Y-%m-%d %H:%M:%S")),
+                    format!("Updated: {}", state.updated_at.format("%Y-%m-%d %H:%M:%S")),
+                    format!("Phase: {:?}", state.phase),
+                    format!("Completed: {}", state.completed),
+                    format!("Messages scanned: {}", state.messages_scanned),
+                    format!("Messages classified: {}", state.messages_classified),
+                    format!("Labels created: {}", state.labels_created.len()),
+                    format!("Filters created: {}", state.filters_created.len()),
+                    format!("Messages modified: {}", state.messages_modified),
+                    format!("Checkpoints: {}", state.checkpoint_count),
+                ]);
+                if let Some(circuit) = &state.circuit {
+                    local_lines.push(format!("Circuit: {}", circuit));
+                }
 
                 println!("\n========================================");
-                println!("Processing State");
+                if live {
+                    print_columns(
+                        &["Processing State".to_string()],
+                        &["Gmail (live)".to_string()],
+                    );
+                } else {
+                    println!("Processing State");
+                }
                 println!("========================================");
-                println!("Run ID: {}", state.run_id);
-                println!("Started: {}", state.started_at.format("%Y-%m-%d %H:%M:%S"));
-                println!("Updated: {}", state.updated_at.format("%Y-%m-%d %H:%M:%S"));
-                println!("Phase: {:?}", state.phase);
-                println!("Completed: {}", state.completed);
-                println!("Messages scanned: {}", state.messages_scanned);
-                println!("Messages classified: {}", state.messages_classified);
-                println!("Labels created: {}", state.labels_created.len());
-                println!("Filters created: {}", state.filters_created.len());
-                println!("Messages modified: {}", state.messages_modified);
-                println!("Checkpoints: {}", state.checkpoint_count);
+                print_columns(&local_lines, &live_lines);
 
                 if detailed {
                     println!("\n--- Detailed Information ---");
@@ -301,9 +545,1023 @@ async fn run() -> Result<()> {
                     }
                 }
                 println!("========================================");
+
+                if let Some(previous_run_id) = diff_with {
+                    let previous_path = gmail_automation::state::ProcessingState::run_state_path(
+                        &cli.state_file,
+                        &previous_run_id,
+                    );
+                    if !previous_path.exists() {
+                        return Err(GmailError::StateError(format!(
+                            "No saved state found for run {} (expected {:?})",
+                            previous_run_id, previous_path
+                        ))
+                        .into());
+                    }
+                    let previous = gmail_automation::state::ProcessingState::load_with_key(
+                        &previous_path,
+                        state_key.as_ref(),
+                    )
+                    .await?;
+
+                    println!();
+                    println!("{}", state.diff(&previous).to_markdown());
+                }
             } else {
                 println!("No active or previous runs found.");
                 println!("State file: {:?}", cli.state_file);
+                if live {
+                    println!("\n========================================");
+                    println!("Gmail (live)");
+                    println!("========================================");
+                    print_columns(&live_lines, &[]);
+                }
+            }
+
+            Ok(())
+        }
+
+        Commands::History { limit, tag } => {
+            let config = Config::load(&cli.config).await?;
+            let state_key = config.state.encryption_key()?;
+            let state_dir = cli
+                .state_file
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or_else(|| std::path::Path::new("."));
+
+            let mut runs = gmail_automation::state::ProcessingState::load_all_with_key(
+                state_dir,
+                state_key.as_ref(),
+            )
+            .await?;
+            if let Some(tag) = &tag {
+                runs.retain(|run| run.tags.contains(tag));
+            }
+
+            if runs.is_empty() {
+                println!("No previous runs found in {:?}", state_dir);
+                return Ok(());
+            }
+
+            println!(
+                "\n{:<36}  {:<19}  {:>8}  {:>7}  {:>6}  {:>9}",
+                "Run ID", "Started", "Scanned", "Filters", "Labels", "Duration"
+            );
+            println!("{}", "-".repeat(96));
+            for run in runs.iter().take(limit) {
+                println!(
+                    "{:<36}  {:<19}  {:>8}  {:>7}  {:>6}  {:>8}s",
+                    run.run_id,
+                    run.started_at.format("%Y-%m-%d %H:%M:%S"),
+                    cli::format_number(run.messages_scanned as u64),
+                    run.filters_created.len(),
+                    run.labels_created.len(),
+                    run.duration_secs()
+                );
+            }
+            if runs.len() > limit {
+                println!(
+                    "... and {} older runs (use --limit to show more)",
+                    runs.len() - limit
+                );
+            }
+
+            let total_emails: usize = runs.iter().map(|r| r.messages_scanned).sum();
+            let total_filters: usize = runs.iter().map(|r| r.filters_created.len()).sum();
+            let avg_duration =
+                runs.iter().map(|r| r.duration_secs()).sum::<i64>() / runs.len() as i64;
+
+            println!("\nTotals across {} runs:", runs.len());
+            println!(
+                "  Emails processed:  {}",
+                cli::format_number(total_emails as u64)
+            );
+            println!("  Filters created:   {}", total_filters);
+            println!("  Average duration:  {}s", avg_duration);
+
+            Ok(())
+        }
+
+        Commands::Backup { output_dir } => {
+            let config = Config::load(&cli.config).await?;
+            let hub =
+                gmail_automation::auth::initialize_gmail_hub(&cli.credentials, &cli.token_cache)
+                    .await?;
+            let client = gmail_automation::client::ProductionGmailClient::with_full_config(
+                hub,
+                config.scan.max_concurrent_requests,
+                250.0, // quota units per second
+                500.0, // quota burst capacity
+                config.circuit_breaker.clone(),
+            )
+            .with_retry_config(config.client.retry.clone())
+            .with_auth_headers(config.scan.parse_auth_headers)
+            .with_unsubscribe_links(config.scan.extract_unsubscribe_links);
+
+            let manifest = gmail_automation::backup::create_backup(
+                &client,
+                &config,
+                &cli.state_file,
+                &output_dir,
+            )
+            .await?;
+
+            println!("Backup written to {:?}", output_dir);
+            println!("  Filters: {}", manifest.filter_count);
+            println!("  Auto-managed labels: {}", manifest.label_count);
+            if let Some(run_id) = &manifest.state_run_id {
+                println!("  State from run: {}", run_id);
+            }
+
+            Ok(())
+        }
+
+        Commands::Stats {
+            refresh,
+            detailed,
+            days,
+        } => {
+            let config = Config::load(&cli.config).await?;
+            let stats_path = cli.state_file.with_file_name("filter-stats.json");
+            let mut stats =
+                gmail_automation::filter_stats::FilterStatsStore::load(&stats_path).await?;
+
+            if stats.filters().is_empty() && !detailed {
+                println!("No filters tracked yet in {:?}", stats_path);
+                return Ok(());
+            }
+
+            let client = if refresh || detailed {
+                let hub = gmail_automation::auth::initialize_gmail_hub(
+                    &cli.credentials,
+                    &cli.token_cache,
+                )
+                .await?;
+                Some(std::sync::Arc::new(
+                    gmail_automation::client::ProductionGmailClient::with_full_config(
+                        hub,
+                        config.scan.max_concurrent_requests,
+                        250.0, // quota units per second
+                        500.0, // quota burst capacity
+                        config.circuit_breaker.clone(),
+                    )
+                    .with_retry_config(config.client.retry.clone())
+                    .with_auth_headers(config.scan.parse_auth_headers)
+                    .with_unsubscribe_links(config.scan.extract_unsubscribe_links),
+                ))
+            } else {
+                None
+            };
+
+            if refresh && !stats.filters().is_empty() {
+                if let Some(client) = &client {
+                    let refreshed = stats.refresh(client.as_ref()).await;
+                    stats.save(&stats_path).await?;
+                    println!("Refreshed match counts for {} filters", refreshed);
+                }
+            }
+
+            let threshold = config.stats.stale_threshold_days;
+            if stats.filters().is_empty() {
+                println!("No filters tracked yet in {:?}", stats_path);
+            } else {
+                println!(
+                    "\n{:<40}  {:<10}  {:>8}  {:>9}  Query",
+                    "Label", "Created", "Matches", "Snapshots"
+                );
+                println!("{}", "-".repeat(100));
+                for filter in stats.filters() {
+                    println!(
+                        "{:<40}  {:<10}  {:>8}  {:>9}  {}{}",
+                        filter.label,
+                        filter.created_at.format("%Y-%m-%d"),
+                        filter
+                            .latest_matches()
+                            .map(|m| m.to_string())
+                            .unwrap_or_else(|| "-".to_string()),
+                        filter.snapshots.len(),
+                        filter.query,
+                        if filter.is_stale(threshold) {
+                            "  [STALE]"
+                        } else {
+                            ""
+                        }
+                    );
+                    if detailed {
+                        for criterion in describe_gmail_query(&filter.query).lines() {
+                            println!("    {}", criterion);
+                        }
+                    }
+                }
+
+                let stale_count = stats.stale(threshold).len();
+                if stale_count > 0 {
+                    println!(
+                        "\n{} filters matched nothing in the latest snapshot and are older than {} days",
+                        stale_count, threshold
+                    );
+                }
+            }
+
+            if detailed && cli.state_file.exists() {
+                use gmail_automation::state::ProcessingState;
+
+                let state_key = config.state.encryption_key()?;
+                let state =
+                    ProcessingState::load_with_key(&cli.state_file, state_key.as_ref()).await?;
+                let messages: Vec<_> = state
+                    .classifications
+                    .iter()
+                    .map(|c| c.restore().0)
+                    .collect();
+                if !messages.is_empty() {
+                    println!("\nEmail Volume (last {} days of the latest scan)", days);
+                    println!("{}", "-".repeat(100));
+                    println!("{}", cli::render_email_timeline(&messages, days, 50));
+                }
+            }
+
+            if let (true, Some(client)) = (detailed, &client) {
+                use gmail_automation::client::GmailClient;
+                use gmail_automation::label_manager::LabelManager;
+
+                let labels = client.list_labels().await?;
+                let filters = client.list_filters().await?;
+                let label_manager =
+                    LabelManager::new(Box::new(client.clone()), config.labels.prefix.clone());
+                let mut suggestions = label_manager.suggest_consolidations(&labels, &filters);
+
+                println!("\nConsolidation Opportunities");
+                println!("{}", "-".repeat(100));
+                if suggestions.is_empty() {
+                    println!("None - every domain is filed under a single category");
+                }
+                for suggestion in &mut suggestions {
+                    suggestion.message_count =
+                        LabelManager::execute_consolidation(suggestion, client.as_ref(), true)
+                            .await?;
+                    println!(
+                        "{} ({} messages)",
+                        suggestion.suggested_name, suggestion.message_count
+                    );
+                    for label in &suggestion.labels_to_merge {
+                        println!("  <- {}", label);
+                    }
+                    println!("  {}", suggestion.reason);
+                }
+            }
+
+            Ok(())
+        }
+
+        Commands::FilterHistory { filter_id, since } => {
+            use gmail_automation::filter_manager::FilterChange;
+            use gmail_automation::filter_stats::{self, FilterStatsStore};
+
+            let since = since
+                .map(|date| {
+                    chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                        .map(|day| day.and_time(chrono::NaiveTime::MIN).and_utc())
+                        .map_err(|_| {
+                            anyhow::anyhow!("Invalid --since date '{}', expected YYYY-MM-DD", date)
+                        })
+                })
+                .transpose()?;
+            let stats_path = cli.state_file.with_file_name("filter-stats.json");
+            let changes_path = cli.state_file.with_file_name("filter-changes.jsonl");
+            let stats = FilterStatsStore::load(&stats_path).await?;
+            let changes = FilterChange::load_all(&changes_path).await?;
+
+            match filter_id {
+                None => {
+                    if stats.filters().is_empty() {
+                        println!("No filters tracked yet in {:?}", stats_path);
+                        return Ok(());
+                    }
+
+                    let mut filters: Vec<_> = stats
+                        .filters()
+                        .iter()
+                        .map(|filter| (filter.trend(since), filter))
+                        .collect();
+                    filters.sort_by(|(a_trend, a), (b_trend, b)| {
+                        a_trend.cmp(b_trend).then_with(|| a.label.cmp(&b.label))
+                    });
+
+                    println!(
+                        "\n{:<10}  {:<40}  {:>7}  {:>7}  {:>7}  Filter ID",
+                        "Trend", "Label", "First", "Latest", "Updates"
+                    );
+                    println!("{}", "-".repeat(100));
+                    for (trend, filter) in filters {
+                        let snapshots = filter.snapshots_since(since);
+                        let count = |snapshot: Option<&(_, usize)>| {
+                            snapshot
+                                .map(|(_, count)| count.to_string())
+                                .unwrap_or_else(|| "-".to_string())
+                        };
+                        let updates = filter_stats::changes_for(&filter.filter_id, &changes)
+                            .iter()
+                            .filter(|c| since.map_or(true, |since| c.changed_at >= since))
+                            .count();
+                        println!(
+                            "{:<10}  {:<40}  {:>7}  {:>7}  {:>7}  {}",
+                            trend,
+                            filter.label,
+                            count(snapshots.first()),
+                            count(snapshots.last()),
+                            updates,
+                            filter.filter_id
+                        );
+                    }
+                }
+                Some(filter_id) => {
+                    let current_id = filter_stats::current_filter_id(&filter_id, &changes);
+                    let Some(filter) = stats.filters().iter().find(|f| f.filter_id == current_id)
+                    else {
+                        println!("Filter {} is not tracked in {:?}", filter_id, stats_path);
+                        return Ok(());
+                    };
+                    if current_id != filter_id {
+                        println!("Filter {} was replaced by {}", filter_id, current_id);
+                    }
+
+                    println!("\nFilter {} -> {}", filter.filter_id, filter.label);
+                    println!("Query: {}", filter.query);
+                    for criterion in describe_gmail_query(&filter.query).lines() {
+                        println!("  {}", criterion);
+                    }
+                    println!("Trend: {}", filter.trend(since));
+                    println!("{}", "-".repeat(100));
+                    let chain = filter_stats::changes_for(current_id, &changes);
+                    for line in filter_stats::render_timeline(filter, &chain, since) {
+                        println!("{}", line);
+                    }
+                }
+            }
+
+            Ok(())
+        }
+
+        Commands::MergeLabels {
+            source,
+            target,
+            dry_run,
+            delete_sources,
+        } => {
+            use gmail_automation::client::GmailClient;
+            use gmail_automation::label_manager::LabelManager;
+
+            let config = Config::load(&cli.config).await?;
+            let hub =
+                gmail_automation::auth::initialize_gmail_hub(&cli.credentials, &cli.token_cache)
+                    .await?;
+            let client = std::sync::Arc::new(
+                gmail_automation::client::ProductionGmailClient::with_full_config(
+                    hub,
+                    config.scan.max_concurrent_requests,
+                    250.0, // quota units per second
+                    500.0, // quota burst capacity
+                    config.circuit_breaker.clone(),
+                )
+                .with_retry_config(config.client.retry.clone())
+                .with_auth_headers(config.scan.parse_auth_headers)
+                .with_unsubscribe_links(config.scan.extract_unsubscribe_links),
+            );
+
+            let labels = client.list_labels().await?;
+            let find_id = |name: &str| {
+                labels
+                    .iter()
+                    .find(|l| l.name.eq_ignore_ascii_case(name))
+                    .map(|l| l.id.clone())
+            };
+            let source_ids = source
+                .iter()
+                .map(|name| {
+                    find_id(name).ok_or_else(|| {
+                        GmailError::LabelError(format!("Label not found: {}", name))
+                    })
+                })
+                .collect::<gmail_automation::error::Result<Vec<String>>>()?;
+
+            if dry_run {
+                let mut total = 0;
+                for (name, id) in source.iter().zip(&source_ids) {
+                    let count = client
+                        .list_message_ids(&format!("label:{}", id))
+                        .await?
+                        .len();
+                    println!("{:<50} {:>8} messages", name, count);
+                    total += count;
+                }
+                println!(
+                    "\nDry run: would move {} messages into '{}'{}",
+                    total,
+                    target,
+                    if delete_sources {
+                        " and delete the source labels"
+                    } else {
+                        ""
+                    }
+                );
+                return Ok(());
+            }
+
+            let mut label_manager =
+                LabelManager::new(Box::new(client.clone()), config.labels.prefix.clone())
+                    .with_merge_checkpoint(cli.state_file.with_file_name("merge-checkpoint.json"));
+            label_manager.load_existing_labels().await?;
+            let target_id = match find_id(&target) {
+                Some(id) => id,
+                None => {
+                    let sanitized = label_manager.sanitize_label_name(&target)?;
+                    label_manager
+                        .create_label_direct(&sanitized, &Default::default())
+                        .await?
+                        .into_inner()
+                }
+            };
+
+            let result = label_manager
+                .merge_labels(&source_ids, &target_id, delete_sources)
+                .await?;
+            println!("Moved {} messages into '{}'", result.messages_moved, target);
+            if result.sources_deleted > 0 {
+                println!("Deleted {} source labels", result.sources_deleted);
+            }
+
+            Ok(())
+        }
+
+        Commands::Import {
+            source,
+            path,
+            output,
+        } => {
+            use gmail_automation::filter_manager::{self, FilterManager};
+            use gmail_automation::interactive::ClusterDecision;
+
+            let config = Config::load(&cli.config).await?;
+            let mut rules = match source {
+                ImportSource::Thunderbird => filter_manager::import_thunderbird(&path)?,
+            };
+            if rules.is_empty() {
+                println!("No filters in {:?} could be imported", path);
+                return Ok(());
+            }
+
+            // Imported labels go under the managed prefix like classifier labels
+            let prefix = format!("{}/", config.labels.prefix);
+            for rule in &mut rules {
+                let label = rule.target_label.as_str();
+                if !label.to_lowercase().starts_with(&prefix.to_lowercase()) {
+                    rule.target_label = LabelName::new(format!("{}{}", prefix, label));
+                }
+            }
+
+            println!("Imported {} filters from {:?}:", rules.len(), path);
+            for rule in &rules {
+                println!(
+                    "  {}: {} → {}{}",
+                    rule.name,
+                    FilterManager::build_gmail_query_static(rule),
+                    rule.target_label,
+                    if rule.should_archive {
+                        " (archive)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+
+            let decisions: Vec<ClusterDecision> = rules
+                .into_iter()
+                .map(ClusterDecision::from_filter_rule)
+                .collect();
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let json = serde_json::to_string_pretty(&decisions).map_err(|e| {
+                GmailError::Unknown(format!("Failed to serialize decisions: {}", e))
+            })?;
+            std::fs::write(&output, json)?;
+
+            println!("\nSaved decisions to {:?}", output);
+            println!(
+                "Apply with: gmail-automation run --apply-decisions {}",
+                output.display()
+            );
+            Ok(())
+        }
+
+        Commands::Export { format, output } => {
+            if !cli.state_file.exists() {
+                return Err(GmailError::StateError(format!(
+                    "No state file found at {:?}. Run the pipeline first.",
+                    cli.state_file
+                ))
+                .into());
+            }
+
+            let config = Config::load(&cli.config).await?;
+            let state_key = config.state.encryption_key()?;
+            let state = gmail_automation::state::ProcessingState::load_with_key(
+                &cli.state_file,
+                state_key.as_ref(),
+            )
+            .await?;
+
+            match format {
+                ExportFormat::Csv => {
+                    let rows = state.export_classifications_csv(&output)?;
+                    println!("Exported {} classifications to {:?}", rows, output);
+                }
+                ExportFormat::Json => {
+                    state.export_json(&output)?;
+                    println!("Exported state for run {} to {:?}", state.run_id, output);
+                }
+            }
+
+            Ok(())
+        }
+
+        Commands::Cleanup {
+            keep_last,
+            dry_run,
+            force,
+        } => {
+            use gmail_automation::cleanup;
+
+            let dir = cleanup::state_dir(&cli.state_file);
+            let stale = cleanup::find_stale_files(&cli.state_file, keep_last)?;
+            if stale.is_empty() {
+                println!("Nothing to clean up in {:?}", dir);
+                return Ok(());
+            }
+
+            println!(
+                "Files beyond the {} most recent of each kind ({}):",
+                keep_last,
+                stale.len()
+            );
+            for path in &stale {
+                println!("  - {}", path.display());
+            }
+
+            if dry_run {
+                println!("\nDRY RUN - no files deleted");
+                return Ok(());
+            }
+            if !force {
+                print!("\nDelete these files? [y/N]: ");
+                std::io::stdout().flush()?;
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+                if input.trim().to_lowercase() != "y" {
+                    println!("Aborted.");
+                    return Ok(());
+                }
+            }
+
+            let removed = cleanup::remove_files(&stale)?;
+            println!("Deleted {} files", removed);
+            Ok(())
+        }
+
+        Commands::MergeStates {
+            state_a,
+            state_b,
+            output,
+        } => {
+            use gmail_automation::state::ProcessingState;
+
+            let config = Config::load(&cli.config).await?;
+            let state_key = config.state.encryption_key()?;
+            let a = ProcessingState::load_with_key(&state_a, state_key.as_ref()).await?;
+            let b = ProcessingState::load_with_key(&state_b, state_key.as_ref()).await?;
+
+            let conflicts = a.label_conflicts(&b);
+            if !conflicts.is_empty() {
+                eprintln!("Labels created under different IDs in the two runs:");
+                for conflict in &conflicts {
+                    eprintln!(
+                        "  {}: {} ({:?}) vs {} ({:?})",
+                        conflict.name, conflict.id_a, state_a, conflict.id_b, state_b
+                    );
+                }
+                return Err(GmailError::StateError(format!(
+                    "Cannot merge states with {} conflicting labels",
+                    conflicts.len()
+                ))
+                .into());
+            }
+
+            let merged = a
+                .merge(b)
+                .with_compression(config.state.compress)
+                .with_format(config.state.format)
+                .with_encryption_key(state_key);
+            merged.save(&output).await?;
+
+            println!("Merged state written to {:?}", output);
+            println!("  Run ID: {}", merged.run_id);
+            println!("  Messages scanned: {}", merged.messages_scanned);
+            println!("  Labels created: {}", merged.labels_created.len());
+            println!("  Filters created: {}", merged.filters_created.len());
+
+            Ok(())
+        }
+
+        Commands::GenerateRules { min_feedback } => {
+            use gmail_automation::classifier::FeedbackEntry;
+
+            let config = Config::load(&cli.config).await?;
+            let feedback_path = cli.state_file.with_file_name("feedback.jsonl");
+            let feedback = FeedbackEntry::load_all(&feedback_path).await?;
+            if feedback.is_empty() {
+                println!("No review feedback recorded yet in {:?}", feedback_path);
+                return Ok(());
+            }
+
+            // Domains that already have a rule keep it
+            let rules: Vec<_> = FeedbackEntry::suggest_rules(&feedback, min_feedback)
+                .into_iter()
+                .filter(|rule| {
+                    !config
+                        .classification
+                        .rules
+                        .iter()
+                        .any(|existing| existing.domain.eq_ignore_ascii_case(&rule.domain))
+                })
+                .collect();
+            if rules.is_empty() {
+                println!(
+                    "No new rules: no domain has {} or more corrections, most of them to the same label",
+                    min_feedback
+                );
+                return Ok(());
+            }
+
+            let stanzas: String = rules
+                .iter()
+                .map(|rule| {
+                    format!(
+                        "\n[[classification.rules]]\ndomain = {}\nlabel = {}\n",
+                        toml::Value::String(rule.domain.clone()),
+                        toml::Value::String(rule.label.clone())
+                    )
+                })
+                .collect();
+            println!(
+                "Suggested rules from {} corrections in {:?}:",
+                feedback.len(),
+                feedback_path
+            );
+            println!("{}", stanzas);
+
+            print!("Append these rules to {:?}? [y/N]: ", cli.config);
+            std::io::stdout().flush()?;
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+            if input.trim().to_lowercase() != "y" {
+                println!("Aborted.");
+                return Ok(());
+            }
+
+            let mut contents = tokio::fs::read_to_string(&cli.config).await?;
+            if !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(&stanzas);
+            tokio::fs::write(&cli.config, contents).await?;
+            println!("Added {} rules to {:?}", rules.len(), cli.config);
+            Ok(())
+        }
+
+        Commands::Scan {
+            period_days,
+            ref output,
+            format,
+        } => {
+            let scan = cli::run_scan(&cli, period_days, (*multi_progress).clone()).await?;
+            match format {
+                ScanFormat::Json => scan.save_json(output).await?,
+                ScanFormat::Csv => scan.save_csv(output)?,
+            }
+            println!(
+                "Saved {} messages (query: {}) to {:?}",
+                scan.message_count, scan.query, output
+            );
+            Ok(())
+        }
+
+        Commands::Classify { input, json } => {
+            use gmail_automation::scanner::ScanOutput;
+            use std::collections::BTreeMap;
+
+            let config = Config::load(&cli.config).await?;
+            let scan = ScanOutput::load(&input).await?;
+            let classified = cli::classify_scan(&config, &scan)?;
+
+            if json {
+                let classifications: Vec<_> = classified.iter().map(|(_, c)| c).collect();
+                println!("{}", serde_json::to_string_pretty(&classifications)?);
+                return Ok(());
+            }
+
+            let mut categories: BTreeMap<String, usize> = BTreeMap::new();
+            let mut labels: BTreeMap<&str, usize> = BTreeMap::new();
+            for (_, classification) in &classified {
+                *categories
+                    .entry(format!("{:?}", classification.category))
+                    .or_default() += 1;
+                *labels
+                    .entry(classification.suggested_label.as_str())
+                    .or_default() += 1;
+            }
+
+            println!(
+                "\nClassified {} messages scanned at {} (query: {})",
+                classified.len(),
+                scan.scanned_at.format("%Y-%m-%d %H:%M UTC"),
+                scan.query
+            );
+            println!("{}", "=".repeat(60));
+            println!("{:<40} {:>8}", "Category", "Messages");
+            for (category, count) in &categories {
+                println!("{:<40} {:>8}", category, count);
+            }
+            println!("\n{:<40} {:>8}", "Suggested label", "Messages");
+            for (label, count) in &labels {
+                println!("{:<40} {:>8}", label, count);
+            }
+            Ok(())
+        }
+
+        Commands::Compare { run_a, run_b } => {
+            use gmail_automation::cli::Report;
+            use gmail_automation::state::ProcessingState;
+
+            let config = Config::load(&cli.config).await?;
+            let state_key = config.state.encryption_key()?;
+
+            let mut reports = Vec::with_capacity(2);
+            for run_id in [&run_a, &run_b] {
+                let path = ProcessingState::run_state_path(&cli.state_file, run_id);
+                if !path.exists() {
+                    return Err(GmailError::StateError(format!(
+                        "No saved state found for run {} (expected {:?})",
+                        run_id, path
+                    ))
+                    .into());
+                }
+                let state = ProcessingState::load_with_key(&path, state_key.as_ref()).await?;
+                reports.push(Report::from_state(&state));
+            }
+
+            println!("{}", reports[0].compare(&reports[1]).to_markdown());
+            Ok(())
+        }
+
+        Commands::AbTestReport { log } => {
+            use gmail_automation::classifier::{AbTestReport, AgreementResult};
+
+            if !log.exists() {
+                println!("No A/B test log found at {:?}", log);
+                println!("Configure [classification.ab_test] and run the pipeline first.");
+                return Ok(());
+            }
+            let results = AgreementResult::load_all(&log).await?;
+            let report = AbTestReport::from_results(&results);
+            if report.total == 0 {
+                println!("The A/B test log {:?} is empty", log);
+                return Ok(());
+            }
+
+            println!("\nA/B Test Report ({:?})", log);
+            println!("{}", "=".repeat(60));
+            println!("Messages compared: {}", report.total);
+            println!(
+                "Agreement rate:    {:.1}% ({} of {})",
+                report.agreement_rate() * 100.0,
+                report.agreements,
+                report.total
+            );
+
+            println!("\nConfusion matrix (control -> experiment):");
+            for ((control, experiment), count) in &report.confusion {
+                let marker = if control == experiment { " " } else { "*" };
+                println!(
+                    "  {} {:<16} -> {:<16} {:>7}",
+                    marker,
+                    format!("{:?}", control),
+                    format!("{:?}", experiment),
+                    count
+                );
+            }
+
+            if !report.divergences.is_empty() {
+                const MAX_SHOWN: usize = 20;
+                println!("\nDivergent messages ({}):", report.divergences.len());
+                for result in report.divergences.iter().take(MAX_SHOWN) {
+                    println!(
+                        "  {}: {:?} -> {:?}",
+                        result.message_id, result.control_category, result.experiment_category
+                    );
+                }
+                if report.divergences.len() > MAX_SHOWN {
+                    println!("  ... and {} more", report.divergences.len() - MAX_SHOWN);
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Unsubscribe {
+            domain,
+            dry_run,
+            force,
+        } => {
+            if !cli.state_file.exists() {
+                return Err(GmailError::StateError(format!(
+                    "No state file found at {:?}. Run the pipeline first.",
+                    cli.state_file
+                ))
+                .into());
+            }
+
+            let config = Config::load(&cli.config).await?;
+            let state_key = config.state.encryption_key()?;
+            let state = gmail_automation::state::ProcessingState::load_with_key(
+                &cli.state_file,
+                state_key.as_ref(),
+            )
+            .await?;
+
+            let plan = UnsubscribePlan::for_domains(&state.unsubscribe_links, &domain);
+            if plan.is_empty() {
+                println!(
+                    "No unsubscribe links found for {}. Links are collected while scanning \
+                     (scan.extract_unsubscribe_links).",
+                    domain.join(", ")
+                );
+                return Ok(());
+            }
+
+            if !plan.http.is_empty() {
+                println!("Links to request ({}):", plan.http.len());
+                for (sender, link) in &plan.http {
+                    println!("  - {} ({})", link.url, sender);
+                }
+            }
+
+            if dry_run {
+                println!("\nDRY RUN - no requests sent");
+            } else if !plan.http.is_empty() {
+                if !force {
+                    print!("\nUnsubscribe using these links? [y/N]: ");
+                    std::io::stdout().flush()?;
+                    let mut input = String::new();
+                    std::io::stdin().read_line(&mut input)?;
+                    if input.trim().to_lowercase() != "y" {
+                        println!("Aborted.");
+                        return Ok(());
+                    }
+                }
+
+                let mut succeeded = 0;
+                for (sender, link) in &plan.http {
+                    match gmail_automation::unsubscribe::unsubscribe_http(&link.url).await {
+                        Ok(status) => {
+                            succeeded += 1;
+                            tracing::debug!("Unsubscribed from {} (HTTP {})", sender, status);
+                        }
+                        Err(e) => {
+                            println!("  Failed for {}: {}", sender, e);
+                        }
+                    }
+                }
+                println!("Unsubscribed via {} of {} links", succeeded, plan.http.len());
+            }
+
+            if !plan.mailto.is_empty() {
+                println!(
+                    "\nSenders that only accept email ({}) - send a message to unsubscribe:",
+                    plan.mailto.len()
+                );
+                for (sender, link) in &plan.mailto {
+                    println!("  - {} ({})", link.url, sender);
+                }
+            }
+
+            Ok(())
+        }
+
+        Commands::Exclusions { action } => {
+            let exclusions_path = cli.state_file.with_file_name("exclusions.json");
+            let mut manager = match action {
+                ExclusionAction::List {
+                    show_expired: true, ..
+                }
+                | ExclusionAction::Prune => {
+                    ExclusionManager::load_with_expired(&exclusions_path).await?
+                }
+                _ => ExclusionManager::load(&exclusions_path).await?,
+            };
+
+            match action {
+                ExclusionAction::List { format, .. } => match format {
+                    OutputFormat::Json => {
+                        let json = serde_json::to_string_pretty(manager.exclusions())?;
+                        println!("{}", json);
+                    }
+                    OutputFormat::Table => {
+                        if manager.is_empty() {
+                            println!("No exclusions saved in {:?}", exclusions_path);
+                            return Ok(());
+                        }
+                        println!(
+                            "\n{:<40}  {:<7}  {:<10}  {:<16}  {:<7}  Reason",
+                            "Key", "Type", "Added", "Expires", "Status"
+                        );
+                        println!("{}", "-".repeat(100));
+                        for entry in manager.exclusions() {
+                            println!(
+                                "{:<40}  {:<7}  {:<10}  {:<16}  {:<7}  {}",
+                                entry.key,
+                                if entry.is_pattern { "pattern" } else { "exact" },
+                                entry.added_at.format("%Y-%m-%d"),
+                                entry
+                                    .expires_at
+                                    .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+                                    .unwrap_or_else(|| "never".to_string()),
+                                if entry.is_expired() {
+                                    "expired"
+                                } else {
+                                    "active"
+                                },
+                                entry.reason.as_deref().unwrap_or("-")
+                            );
+                        }
+                        println!("\n{} exclusions", manager.len());
+                    }
+                },
+                ExclusionAction::Export { output } => {
+                    let count = manager.export_json(&output)?;
+                    println!("Exported {} exclusions to {:?}", count, output);
+                }
+                ExclusionAction::Import { input, merge } => {
+                    let count = manager.import_json(&input, merge)?;
+                    manager.save(&exclusions_path).await?;
+                    println!(
+                        "{} {} exclusions from {:?} ({} total)",
+                        if merge { "Merged" } else { "Imported" },
+                        count,
+                        input,
+                        manager.len()
+                    );
+                }
+                ExclusionAction::Add {
+                    key,
+                    reason,
+                    expires_days,
+                    pattern,
+                } => {
+                    let expires_at = expires_days
+                        .map(|days| chrono::Utc::now() + chrono::Duration::days(days as i64));
+                    if pattern {
+                        manager.add_pattern_with_expiry(&key, reason, expires_at)?;
+                    } else {
+                        manager.add_with_expiry(key.clone(), reason, expires_at);
+                    }
+                    manager.save(&exclusions_path).await?;
+                    println!("Excluded {}", key);
+                }
+                ExclusionAction::Remove { key } => {
+                    if !manager.remove(&key) {
+                        return Err(
+                            GmailError::Unknown(format!("No exclusion found for {}", key)).into(),
+                        );
+                    }
+                    manager.save(&exclusions_path).await?;
+                    println!("Removed exclusion {}", key);
+                }
+                ExclusionAction::Clear => {
+                    let count = manager.len();
+                    manager.clear();
+                    manager.save(&exclusions_path).await?;
+                    println!("Removed {} exclusions", count);
+                }
+                ExclusionAction::Prune => {
+                    let count = manager.prune_expired();
+                    manager.save(&exclusions_path).await?;
+                    println!(
+                        "Removed {} expired exclusions ({} remaining)",
+                        count,
+                        manager.len()
+                    );
+                }
             }
 
             Ok(())
@@ -331,6 +1589,8 @@ async fn run() -> Result<()> {
             println!("  - classification.mode: 'rules', 'ml', or 'hybrid'");
             println!("  - labels.prefix: Prefix for all created labels");
             println!("  - labels.auto_archive_categories: Categories to auto-archive");
+            println!("\nTip: tag runs for an audit trail, e.g. `run --tag pre-cleanup`,");
+            println!("then find them again with `history --tag pre-cleanup`.");
 
             Ok(())
         }
@@ -339,6 +1599,7 @@ async fn run() -> Result<()> {
             dry_run,
             delete_labels,
             force,
+            count_messages,
         } => {
             tracing::info!("Starting unmanage operation");
 
@@ -360,6 +1621,8 @@ async fn run() -> Result<()> {
                 &config_spinner,
                 &format!("Configuration loaded (prefix: {})", label_prefix),
             );
+            let reporter =
+                cli::ProgressReporter::with_ui_config(reporter.multi_progress(), &config.ui);
 
             // Initialize Gmail API
             let auth_spinner = reporter.add_spinner("Authenticating with Gmail API...");
@@ -368,12 +1631,18 @@ async fn run() -> Result<()> {
                     .await?;
             reporter.finish_spinner(&auth_spinner, "Gmail API authenticated");
 
-            let client = gmail_automation::client::ProductionGmailClient::with_full_config(
-                hub,
-                config.scan.max_concurrent_requests,
-                250.0, // quota units per second
-                500.0, // quota burst capacity
-                config.circuit_breaker.clone(),
+            // Shared with the filter deletion tasks
+            let client = Arc::new(
+                gmail_automation::client::ProductionGmailClient::with_full_config(
+                    hub,
+                    config.scan.max_concurrent_requests,
+                    250.0, // quota units per second
+                    500.0, // quota burst capacity
+                    config.circuit_breaker.clone(),
+                )
+                .with_retry_config(config.client.retry.clone())
+                .with_auth_headers(config.scan.parse_auth_headers)
+                .with_unsubscribe_links(config.scan.extract_unsubscribe_links),
             );
 
             // Fetch filters and labels concurrently (independent API calls)
@@ -460,10 +1729,38 @@ async fn run() -> Result<()> {
                     let _ = reporter
                         .multi_progress()
                         .println(format!("\nLabels to delete ({}):", labels_to_delete.len()));
+                    let mut total_messages = 0u64;
+                    let mut any_truncated = false;
                     for label in &labels_to_delete {
-                        let _ = reporter
-                            .multi_progress()
-                            .println(format!("  - {} (ID: {})", label.name, label.id));
+                        if !count_messages {
+                            let _ = reporter
+                                .multi_progress()
+                                .println(format!("  - {} (ID: {})", label.name, label.id));
+                            continue;
+                        }
+
+                        // Counting is capped so huge labels don't page through the whole mailbox
+                        let query = format!("label:{}", label.id);
+                        let (ids, truncated) = client
+                            .list_message_ids_limited(&query, cli::UNMANAGE_COUNT_LIMIT)
+                            .await?;
+                        total_messages += ids.len() as u64;
+                        any_truncated |= truncated;
+                        let _ = reporter.multi_progress().println(format!(
+                            "  - {} (ID: {}) — ~{}{} messages would lose label",
+                            label.name,
+                            label.id,
+                            cli::format_number(ids.len() as u64),
+                            if truncated { "+" } else { "" }
+                        ));
+                    }
+                    if count_messages {
+                        let _ = reporter.multi_progress().println(format!(
+                            "~{}{} messages total would lose their labels across {} labels",
+                            cli::format_number(total_messages),
+                            if any_truncated { "+" } else { "" },
+                            labels_to_delete.len()
+                        ));
                     }
                 }
             }
@@ -507,27 +1804,64 @@ async fn run() -> Result<()> {
                     let pb = reporter
                         .add_progress_bar(filters_to_delete.len() as u64, "Deleting filters...");
 
-                    let mut deleted = 0;
-                    let mut failed = 0;
-                    for (filter_id, query, _) in &filters_to_delete {
-                        match client.delete_filter(filter_id).await {
-                            Ok(_) => {
-                                deleted += 1;
-                                let query_display =
-                                    query.as_ref().map(|q| q.as_str()).unwrap_or("<no query>");
-                                tracing::debug!("Deleted filter: {}", query_display);
-                            }
+                    // Delete up to `max_concurrent_requests` filters at a time
+                    let concurrency = config.scan.max_concurrent_requests.max(1);
+                    let mut pending = filters_to_delete.iter().cloned();
+                    let mut tasks = tokio::task::JoinSet::new();
+                    let mut results = Vec::with_capacity(filters_to_delete.len());
+                    loop {
+                        while tasks.len() < concurrency {
+                            let Some((filter_id, query, _)) = pending.next() else {
+                                break;
+                            };
+                            let client = Arc::clone(&client);
+                            let pb = pb.clone();
+                            tasks.spawn(async move {
+                                let result = client.delete_filter(&filter_id).await;
+                                pb.inc(1);
+                                (filter_id, query, result)
+                            });
+                        }
+
+                        let Some(joined) = tasks.join_next().await else {
+                            break;
+                        };
+                        results.push(joined.map_err(|e| {
+                            GmailError::Unknown(format!("Filter deletion task failed: {}", e))
+                        })?);
+                    }
+
+                    let mut failed = Vec::new();
+                    for (filter_id, query, result) in &results {
+                        let query_display =
+                            query.as_ref().map(|q| q.as_str()).unwrap_or("<no query>");
+                        match result {
+                            Ok(()) => tracing::debug!("Deleted filter: {}", query_display),
                             Err(e) => {
-                                failed += 1;
                                 tracing::warn!("Failed to delete filter {}: {}", filter_id, e);
+                                failed.push((filter_id, query_display, e));
                             }
                         }
-                        pb.inc(1);
                     }
+                    let deleted = results.len() - failed.len();
                     pb.finish_with_message(format!(
                         "Deleted {} filters ({} failed)",
-                        deleted, failed
+                        deleted,
+                        failed.len()
+                    ));
+
+                    let _ = reporter.multi_progress().println(format!(
+                        "\nFilter deletion summary:\n  {:<8} {}\n  {:<8} {}",
+                        "Deleted",
+                        deleted,
+                        "Failed",
+                        failed.len()
                     ));
+                    for (filter_id, query_display, e) in &failed {
+                        let _ = reporter
+                            .multi_progress()
+                            .println(format!("  ✗ {} (ID: {}): {}", query_display, filter_id, e));
+                    }
                 }
             }
 
@@ -587,8 +1921,20 @@ async fn run() -> Result<()> {
     }
 }
 
+/// Print two columns of lines next to each other; `right` may be empty
+fn print_columns(left: &[String], right: &[String]) {
+    const LEFT_WIDTH: usize = 48;
+
+    for i in 0..left.len().max(right.len()) {
+        let left = left.get(i).map(String::as_str).unwrap_or("");
+        match right.get(i) {
+            Some(right) => println!("{:<width$}{}", left, right, width = LEFT_WIDTH),
+            None => println!("{}", left),
+        }
+    }
+}
+
 /// Display error with context
-#[allow(dead_code)]
 fn display_error(error: &anyhow::Error) {
     eprintln!("Error: {}", error);
 
@@ -599,9 +1945,9 @@ fn display_error(error: &anyhow::Error) {
         cause = e.source();
     }
 
-    // Display helpful hints based on error type
+    // Display helpful hints based on the underlying error, below any added context
     if let Some(gmail_err) = error.downcast_ref::<GmailError>() {
-        match gmail_err {
+        match gmail_err.root_cause() {
             GmailError::AuthError(_) => {
                 eprintln!("\nHint: Make sure your credentials.json file is valid.");
                 eprintln!("      You can download it from Google Cloud Console.");
@@ -1,5 +1,7 @@
+use crate::error::{GmailError, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageMetadata {
@@ -14,6 +16,283 @@ pub struct MessageMetadata {
     pub labels: Vec<String>,
     pub has_unsubscribe: bool,
     pub is_automated: bool,
+    /// SPF result from `Authentication-Results` (`None` when not reported)
+    #[serde(default)]
+    pub spf_pass: Option<bool>,
+    /// DKIM result from `Authentication-Results` (`None` when not reported)
+    #[serde(default)]
+    pub dkim_pass: Option<bool>,
+    /// Mailing list identifier from `List-ID`, e.g. `dev.rust-lang.org`
+    #[serde(default)]
+    pub list_id: Option<String>,
+    /// Unsubscribe mechanisms from `List-Unsubscribe`, in header order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unsubscribe_links: Vec<UnsubscribeLink>,
+    /// Whether the message has an `In-Reply-To` header
+    #[serde(default)]
+    pub is_reply: bool,
+    /// Value of the `Auto-Submitted` header (RFC 3834), e.g. `auto-replied`
+    #[serde(default)]
+    pub auto_submitted: Option<String>,
+    /// Delivery status notification from a mail server (bounce)
+    #[serde(default)]
+    pub is_bounce: bool,
+    /// MIME types of the message and its parts (e.g. `text/calendar`), lowercase
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachment_types: Vec<String>,
+    /// Gmail's estimate of the message size (`sizeEstimate`), in bytes
+    #[serde(default)]
+    pub size_bytes: u64,
+}
+
+/// How an unsubscribe link is acted on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnsubscribeMethod {
+    /// `http(s)://` URL that can be requested directly
+    Http,
+    /// `mailto:` address the user has to write to
+    Mailto,
+}
+
+/// One mechanism from a `List-Unsubscribe` header (RFC 2369)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsubscribeLink {
+    pub url: String,
+    pub method: UnsubscribeMethod,
+}
+
+/// Fluent builder for [`MessageMetadata`], mainly for tests and fixtures
+///
+/// Only the ID and sender are required; everything else has a neutral default.
+#[derive(Debug, Clone, Default)]
+pub struct MessageMetadataBuilder {
+    id: String,
+    thread_id: Option<String>,
+    sender_email: Option<String>,
+    sender_name: Option<String>,
+    subject: String,
+    recipients: Vec<String>,
+    date_received: Option<DateTime<Utc>>,
+    labels: Vec<String>,
+    has_unsubscribe: bool,
+    is_automated: bool,
+    spf_pass: Option<bool>,
+    dkim_pass: Option<bool>,
+    list_id: Option<String>,
+    unsubscribe_links: Vec<UnsubscribeLink>,
+    is_reply: bool,
+    auto_submitted: Option<String>,
+    is_bounce: bool,
+    attachment_types: Vec<String>,
+    size_bytes: u64,
+}
+
+impl MessageMetadataBuilder {
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Thread ID (defaults to `thread_<id>`)
+    pub fn thread_id(mut self, thread_id: &str) -> Self {
+        self.thread_id = Some(thread_id.to_string());
+        self
+    }
+
+    /// Sender address; the sender domain is derived from it
+    pub fn sender(mut self, email: &str) -> Self {
+        self.sender_email = Some(email.to_string());
+        self
+    }
+
+    /// Display name (defaults to the sender address)
+    pub fn sender_name(mut self, name: &str) -> Self {
+        self.sender_name = Some(name.to_string());
+        self
+    }
+
+    pub fn subject(mut self, subject: &str) -> Self {
+        self.subject = subject.to_string();
+        self
+    }
+
+    pub fn recipients(mut self, recipients: &[&str]) -> Self {
+        self.recipients = recipients.iter().map(|r| r.to_string()).collect();
+        self
+    }
+
+    /// Date received (defaults to now)
+    pub fn date(mut self, date: DateTime<Utc>) -> Self {
+        self.date_received = Some(date);
+        self
+    }
+
+    pub fn labels(mut self, labels: &[&str]) -> Self {
+        self.labels = labels.iter().map(|l| l.to_string()).collect();
+        self
+    }
+
+    pub fn unsubscribe(mut self, has_unsubscribe: bool) -> Self {
+        self.has_unsubscribe = has_unsubscribe;
+        self
+    }
+
+    pub fn automated(mut self, is_automated: bool) -> Self {
+        self.is_automated = is_automated;
+        self
+    }
+
+    /// SPF and DKIM results (default: not reported)
+    pub fn auth(mut self, spf_pass: bool, dkim_pass: bool) -> Self {
+        self.spf_pass = Some(spf_pass);
+        self.dkim_pass = Some(dkim_pass);
+        self
+    }
+
+    /// Mailing list identifier (default: none)
+    pub fn list_id(mut self, list_id: &str) -> Self {
+        self.list_id = Some(list_id.to_string());
+        self
+    }
+
+    /// Unsubscribe links (default: none); also marks the message as having
+    /// an unsubscribe header
+    pub fn unsubscribe_links(mut self, links: Vec<UnsubscribeLink>) -> Self {
+        self.has_unsubscribe = true;
+        self.unsubscribe_links = links;
+        self
+    }
+
+    /// Mark the message as a reply (`In-Reply-To` present)
+    pub fn reply(mut self, is_reply: bool) -> Self {
+        self.is_reply = is_reply;
+        self
+    }
+
+    /// `Auto-Submitted` header value (default: none)
+    pub fn auto_submitted(mut self, value: &str) -> Self {
+        self.auto_submitted = Some(value.to_string());
+        self
+    }
+
+    /// Mark the message as a bounce
+    pub fn bounce(mut self, is_bounce: bool) -> Self {
+        self.is_bounce = is_bounce;
+        self
+    }
+
+    /// MIME types of the message parts (default: none)
+    pub fn attachment_types(mut self, types: &[&str]) -> Self {
+        self.attachment_types = types.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    /// Estimated message size in bytes (default: 0)
+    pub fn size_bytes(mut self, size_bytes: u64) -> Self {
+        self.size_bytes = size_bytes;
+        self
+    }
+
+    /// Build the message
+    ///
+    /// # Panics
+    /// Panics if the ID is empty or no sender was set.
+    pub fn build(self) -> MessageMetadata {
+        assert!(
+            !self.id.is_empty(),
+            "MessageMetadataBuilder requires a non-empty message ID"
+        );
+        let sender_email = self.sender_email.unwrap_or_else(|| {
+            panic!(
+                "MessageMetadataBuilder for message '{}' requires a sender; call .sender(email)",
+                self.id
+            )
+        });
+        let sender_domain = sender_email
+            .split('@')
+            .nth(1)
+            .map(|d| d.to_lowercase())
+            .unwrap_or_default();
+
+        MessageMetadata {
+            thread_id: self
+                .thread_id
+                .unwrap_or_else(|| format!("thread_{}", self.id)),
+            id: self.id,
+            sender_name: self.sender_name.unwrap_or_else(|| sender_email.clone()),
+            sender_email,
+            sender_domain,
+            subject: self.subject,
+            recipients: self.recipients,
+            date_received: self.date_received.unwrap_or_else(Utc::now),
+            labels: self.labels,
+            has_unsubscribe: self.has_unsubscribe,
+            is_automated: self.is_automated,
+            spf_pass: self.spf_pass,
+            dkim_pass: self.dkim_pass,
+            list_id: self.list_id,
+            unsubscribe_links: self.unsubscribe_links,
+            is_reply: self.is_reply,
+            auto_submitted: self.auto_submitted,
+            is_bounce: self.is_bounce,
+            attachment_types: self.attachment_types,
+            size_bytes: self.size_bytes,
+        }
+    }
+}
+
+/// A Gmail conversation and the messages it contains, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailThread {
+    pub thread_id: String,
+    /// Subject of the first message in the thread
+    pub subject: String,
+    pub messages: Vec<MessageMetadata>,
+    /// Unique sender addresses in order of first appearance
+    pub participants: Vec<String>,
+    pub first_date: DateTime<Utc>,
+    pub last_date: DateTime<Utc>,
+    /// Union of the labels on every message in the thread
+    pub label_ids: Vec<LabelId>,
+}
+
+impl EmailThread {
+    /// Build a thread from its messages, deriving subject, participants, dates and labels
+    pub fn from_messages(thread_id: String, mut messages: Vec<MessageMetadata>) -> Result<Self> {
+        if messages.is_empty() {
+            return Err(GmailError::InvalidMessageFormat(format!(
+                "Thread {} has no messages",
+                thread_id
+            )));
+        }
+        messages.sort_by_key(|m| m.date_received);
+
+        let mut participants: Vec<String> = Vec::new();
+        let mut label_ids: Vec<LabelId> = Vec::new();
+        for msg in &messages {
+            if !msg.sender_email.is_empty() && !participants.contains(&msg.sender_email) {
+                participants.push(msg.sender_email.clone());
+            }
+            for label in &msg.labels {
+                if !label_ids.iter().any(|id| id == label) {
+                    label_ids.push(LabelId::new(label.as_str()));
+                }
+            }
+        }
+
+        Ok(Self {
+            thread_id,
+            subject: messages[0].subject.clone(),
+            first_date: messages[0].date_received,
+            last_date: messages[messages.len() - 1].date_received,
+            participants,
+            label_ids,
+            messages,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +303,74 @@ pub struct Classification {
     pub suggested_label: String,
     pub should_archive: bool,
     pub reasoning: Option<String>,
+    /// Heuristics behind the classification, most specific first
+    #[serde(default)]
+    pub reasons: Vec<ClassificationReason>,
+}
+
+impl Classification {
+    /// The reason shown in review
+    pub fn top_reason(&self) -> Option<&ClassificationReason> {
+        self.reasons.first()
+    }
+
+    /// MIME type and sublabel of the attachment that appended a sublabel to
+    /// `suggested_label` (see `labels.attachment_sublabels`)
+    pub fn attachment_sublabel(&self) -> Option<(&str, &str)> {
+        self.reasons.iter().find_map(|reason| match reason {
+            ClassificationReason::AttachmentType {
+                mime_type,
+                sublabel,
+            } => Some((mime_type.as_str(), sublabel.as_str())),
+            _ => None,
+        })
+    }
+
+    /// `suggested_label` without the sublabel an attachment appended to it
+    pub fn base_label(&self) -> &str {
+        self.attachment_sublabel()
+            .and_then(|(_, sublabel)| {
+                self.suggested_label
+                    .strip_suffix(sublabel)?
+                    .strip_suffix('/')
+            })
+            .unwrap_or(&self.suggested_label)
+    }
+}
+
+/// Why a message got its category or label
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ClassificationReason {
+    /// The message has a List-Unsubscribe header
+    HasUnsubscribeHeader,
+    /// The sender address, subject or sending service looks automated
+    IsAutomatedSender,
+    /// The subject contains a category keyword
+    MatchesKeyword(String),
+    /// A `classification.rules` entry set the label
+    MatchesCustomRule { rule_domain: String },
+    /// The message came from a mailing list with this List-ID
+    ListIdPresent(String),
+    /// SPF and DKIM both passed, raising bulk mail confidence
+    SpfDkimPass,
+    /// An attachment of this MIME type appended `sublabel` to the label
+    AttachmentType { mime_type: String, sublabel: String },
+}
+
+impl std::fmt::Display for ClassificationReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::HasUnsubscribeHeader => f.write_str("Has List-Unsubscribe header"),
+            Self::IsAutomatedSender => f.write_str("Automated sender"),
+            Self::MatchesKeyword(keyword) => write!(f, "Subject contains \"{}\"", keyword),
+            Self::MatchesCustomRule { rule_domain } => {
+                write!(f, "Classification rule for {}", rule_domain)
+            }
+            Self::ListIdPresent(_) => f.write_str("Has List-ID header"),
+            Self::SpfDkimPass => f.write_str("SPF and DKIM pass"),
+            Self::AttachmentType { mime_type, .. } => write!(f, "Has {} attachment", mime_type),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -35,9 +382,135 @@ pub enum EmailCategory {
     Shipping,
     Financial,
     Personal,
+    /// Out-of-office and other automatic replies
+    AutoReply,
+    /// Delivery failure notices from mail servers
+    Bounce,
+    /// Meeting invitations and responses
+    CalendarInvite,
     Other,
 }
 
+impl EmailCategory {
+    /// Every category, in declaration order
+    pub fn all_variants() -> &'static [EmailCategory] {
+        &[
+            EmailCategory::Newsletter,
+            EmailCategory::Receipt,
+            EmailCategory::Notification,
+            EmailCategory::Marketing,
+            EmailCategory::Shipping,
+            EmailCategory::Financial,
+            EmailCategory::Personal,
+            EmailCategory::AutoReply,
+            EmailCategory::Bounce,
+            EmailCategory::CalendarInvite,
+            EmailCategory::Other,
+        ]
+    }
+
+    /// Lowercase path segment used for this category in generated label names
+    pub fn label_segment(&self) -> &'static str {
+        match self {
+            EmailCategory::Newsletter => "newsletters",
+            EmailCategory::Receipt => "receipts",
+            EmailCategory::Notification => "notifications",
+            EmailCategory::Marketing => "marketing",
+            EmailCategory::Shipping => "shipping",
+            EmailCategory::Financial => "financial",
+            EmailCategory::Personal => "personal",
+            EmailCategory::AutoReply => "auto-replies",
+            EmailCategory::Bounce => "bounces",
+            EmailCategory::CalendarInvite => "calendar",
+            EmailCategory::Other => "other",
+        }
+    }
+}
+
+/// Gmail label ID as returned by the API (e.g. "Label_12345" or "INBOX")
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LabelId(String);
+
+/// Human-readable label name, including its hierarchy (e.g. "AutoManaged/Newsletters")
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LabelName(String);
+
+macro_rules! string_newtype {
+    ($name:ident) => {
+        impl $name {
+            pub fn new(value: impl Into<String>) -> Self {
+                Self(value.into())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.0.is_empty()
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl std::borrow::Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+    };
+}
+
+string_newtype!(LabelId);
+string_newtype!(LabelName);
+
+/// Priority given to filters that don't set one explicitly
+pub const DEFAULT_FILTER_PRIORITY: u32 = 100;
+
+fn default_filter_priority() -> u32 {
+    DEFAULT_FILTER_PRIORITY
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FilterRule {
     pub id: Option<String>,
@@ -48,9 +521,336 @@ pub struct FilterRule {
     /// For domain filters, list of specific senders to exclude (they have their own filters)
     pub excluded_senders: Vec<String>,
     pub subject_keywords: Vec<String>,
-    pub target_label_id: String,
+    /// Label the filter applies
+    pub target_label: LabelName,
+    /// Gmail ID of `target_label`, set by [`FilterRule::resolve_labels`] before creation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_label_id: Option<LabelId>,
     pub should_archive: bool,
     pub estimated_matches: usize,
+    /// Creation order for overlapping filters (lower number = created first)
+    #[serde(default = "default_filter_priority")]
+    pub priority: u32,
+    /// Additional Gmail search terms appended verbatim to the filter query
+    /// (e.g. `to:(me@example.com)` or `has:attachment`)
+    #[serde(default)]
+    pub extra_terms: Vec<String>,
+    /// Secondary labels applied together with `target_label` (e.g. its parent label)
+    #[serde(default)]
+    pub additional_labels: Vec<LabelName>,
+    /// Gmail IDs of `additional_labels`, set by [`FilterRule::resolve_labels`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_label_ids: Vec<LabelId>,
+}
+
+impl FilterRule {
+    /// Name derived from the filter criteria and label, e.g.
+    /// `*@github.com-weekly digest → AutoManaged/GitHub`
+    ///
+    /// Filters with different senders, subject keywords, extra terms or labels
+    /// get different names.
+    pub fn auto_name(&self) -> String {
+        let criteria = std::iter::once(self.from_pattern.as_deref().unwrap_or("any sender"))
+            .chain(self.subject_keywords.iter().map(String::as_str))
+            .chain(self.extra_terms.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("-");
+        format!("{} → {}", criteria, self.target_label)
+    }
+
+    /// Look up the Gmail IDs of `target_label` and `additional_labels` in
+    /// `ids_by_name`, which is keyed by lowercase label name
+    pub fn resolve_labels(&mut self, ids_by_name: &HashMap<String, LabelId>) -> Result<()> {
+        let lookup = |name: &LabelName| {
+            ids_by_name
+                .get(&name.as_str().to_lowercase())
+                .cloned()
+                .ok_or_else(|| {
+                    GmailError::LabelError(format!("Label ID not found for label: {}", name))
+                })
+        };
+        self.target_label_id = Some(lookup(&self.target_label)?);
+        self.additional_label_ids = self
+            .additional_labels
+            .iter()
+            .map(lookup)
+            .collect::<Result<_>>()?;
+        Ok(())
+    }
+
+    /// Gmail IDs of every label the filter adds, target label first
+    ///
+    /// Fails if [`FilterRule::resolve_labels`] hasn't been called.
+    pub fn label_ids(&self) -> Result<Vec<LabelId>> {
+        let target = self.target_label_id.clone().ok_or_else(|| {
+            GmailError::LabelError(format!(
+                "Label '{}' of filter '{}' was not resolved to an ID",
+                self.target_label, self.name
+            ))
+        })?;
+        Ok(std::iter::once(target)
+            .chain(self.additional_label_ids.iter().cloned())
+            .collect())
+    }
+
+    /// Parse a Gmail search query into a filter rule that applies `label`
+    ///
+    /// Supported terms are `from:`, `-from:`, `to:`, `subject:`, `has:`, and bare
+    /// words or quoted phrases. Values may be wrapped in quotes or parentheses,
+    /// e.g. `from:(*@github.com) subject:"weekly digest" has:attachment`.
+    /// Any other operator is rejected so the user isn't surprised by a filter
+    /// that silently ignores part of their query.
+    pub fn from_gmail_query(query: &str, label: String) -> Result<FilterRule> {
+        let mut from_pattern: Option<String> = None;
+        let mut is_specific_sender = false;
+        let mut excluded_senders = Vec::new();
+        let mut subject_keywords = Vec::new();
+        let mut extra_terms = Vec::new();
+
+        for token in tokenize_gmail_query(query)? {
+            let (negated, term) = match token.strip_prefix('-') {
+                Some(rest) => (true, rest),
+                None => (false, token.as_str()),
+            };
+
+            let Some((operator, raw_value)) = split_operator(term) else {
+                // Bare word or quoted phrase: full-text search term
+                extra_terms.push(token.clone());
+                continue;
+            };
+            let value = unwrap_query_value(raw_value);
+            if value.is_empty() {
+                return Err(GmailError::ConfigError(format!(
+                    "Missing value for '{}:' in query",
+                    operator
+                )));
+            }
+
+            match (operator.as_str(), negated) {
+                ("from", false) => {
+                    if from_pattern.is_some() {
+                        return Err(GmailError::ConfigError(
+                            "Only one from: term is supported per filter".to_string(),
+                        ));
+                    }
+                    if value.contains('@') && !value.starts_with('@') && !value.starts_with("*@") {
+                        is_specific_sender = true;
+                        from_pattern = Some(value.to_string());
+                    } else {
+                        let domain = value.trim_start_matches('*').trim_start_matches('@');
+                        from_pattern = Some(format!("*@{}", domain));
+                    }
+                }
+                ("from", true) => excluded_senders.push(value.to_string()),
+                ("to", false) => extra_terms.push(format!("to:({})", value)),
+                ("subject", false) => {
+                    // Keep quotes around multi-word subjects so Gmail matches the phrase
+                    if value.contains(' ') {
+                        subject_keywords.push(format!("\"{}\"", value));
+                    } else {
+                        subject_keywords.push(value.to_string());
+                    }
+                }
+                ("has", false) => {
+                    const HAS_VALUES: &[&str] = &[
+                        "attachment",
+                        "drive",
+                        "document",
+                        "spreadsheet",
+                        "presentation",
+                        "youtube",
+                        "userlabels",
+                        "nouserlabels",
+                    ];
+                    if !HAS_VALUES.contains(&value) {
+                        return Err(GmailError::ConfigError(format!(
+                            "Unsupported value for has: '{}'",
+                            value
+                        )));
+                    }
+                    extra_terms.push(format!("has:{}", value));
+                }
+                (op, true) => {
+                    return Err(GmailError::ConfigError(format!(
+                        "Negation is only supported for from:, not '-{}:'",
+                        op
+                    )));
+                }
+                (op, false) => {
+                    return Err(GmailError::ConfigError(format!(
+                        "Unsupported Gmail query operator: '{}:'",
+                        op
+                    )));
+                }
+            }
+        }
+
+        if from_pattern.is_none() && subject_keywords.is_empty() && extra_terms.is_empty() {
+            return Err(GmailError::ConfigError(
+                "Query must contain at least one positive search term".to_string(),
+            ));
+        }
+        if !excluded_senders.is_empty() && (from_pattern.is_none() || is_specific_sender) {
+            return Err(GmailError::ConfigError(
+                "-from: can only be combined with a domain from: term (e.g. from:*@example.com)"
+                    .to_string(),
+            ));
+        }
+
+        Ok(FilterRule {
+            id: None,
+            name: format!("{} → {}", query.trim(), label),
+            from_pattern,
+            is_specific_sender,
+            excluded_senders,
+            subject_keywords,
+            target_label: LabelName::new(label),
+            target_label_id: None,
+            should_archive: false,
+            estimated_matches: 0,
+            priority: DEFAULT_FILTER_PRIORITY,
+            extra_terms,
+            additional_labels: vec![],
+            additional_label_ids: vec![],
+        })
+    }
+}
+
+/// A Gmail search query as one line per criterion, e.g.
+/// `from:(*@linkedin.com) subject:(Job Alerts)` becomes
+/// `"From: All @linkedin.com\nSubject: Job Alerts"`.
+/// Queries that can't be parsed are returned unchanged.
+pub fn describe_gmail_query(query: &str) -> String {
+    let Ok(tokens) = tokenize_gmail_query(query) else {
+        return query.to_string();
+    };
+
+    let mut from = Vec::new();
+    let mut except = Vec::new();
+    let mut to = Vec::new();
+    let mut subject = Vec::new();
+    let mut has = Vec::new();
+    let mut other = Vec::new();
+    for token in &tokens {
+        let (negated, term) = match token.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, token.as_str()),
+        };
+        let Some((operator, raw_value)) = split_operator(term) else {
+            other.push(token.clone());
+            continue;
+        };
+        let value = unwrap_query_value(raw_value).to_string();
+        match (operator.as_str(), negated) {
+            ("from", false) => from.push(describe_sender(&value)),
+            ("from", true) => except.push(value),
+            ("to", false) => to.push(value),
+            ("subject", false) => subject.push(value),
+            ("has", false) => has.push(value),
+            _ => other.push(token.clone()),
+        }
+    }
+
+    [
+        ("From", from),
+        ("Except", except),
+        ("To", to),
+        ("Subject", subject),
+        ("Has", has),
+        ("Also matching", other),
+    ]
+    .into_iter()
+    .filter(|(_, values)| !values.is_empty())
+    .map(|(name, values)| format!("{}: {}", name, values.join(", ")))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// `*@example.com` as "All @example.com"; specific senders are kept as they are
+pub fn describe_sender(pattern: &str) -> String {
+    match pattern
+        .strip_prefix("*@")
+        .or_else(|| pattern.strip_prefix('@'))
+    {
+        Some(domain) => format!("All @{}", domain),
+        None => pattern.to_string(),
+    }
+}
+
+/// Split a query on whitespace, keeping quoted phrases and parenthesised groups together
+fn tokenize_gmail_query(query: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut paren_depth = 0usize;
+
+    for c in query.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            '(' if !in_quotes => {
+                paren_depth += 1;
+                current.push(c);
+            }
+            ')' if !in_quotes => {
+                if paren_depth == 0 {
+                    return Err(GmailError::ConfigError(
+                        "Unbalanced ')' in query".to_string(),
+                    ));
+                }
+                paren_depth -= 1;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes && paren_depth == 0 => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(GmailError::ConfigError(
+            "Unterminated quote in query".to_string(),
+        ));
+    }
+    if paren_depth > 0 {
+        return Err(GmailError::ConfigError(
+            "Unbalanced '(' in query".to_string(),
+        ));
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+/// Split `operator:value` into a lowercase operator and its raw value.
+/// Returns `None` for bare words and quoted phrases.
+fn split_operator(term: &str) -> Option<(String, &str)> {
+    let (operator, value) = term.split_once(':')?;
+    if operator.is_empty() || !operator.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some((operator.to_lowercase(), value))
+}
+
+/// Strip one layer of surrounding parentheses or quotes from an operator value
+fn unwrap_query_value(value: &str) -> &str {
+    let value = value.trim();
+    let value = value
+        .strip_prefix('(')
+        .and_then(|v| v.strip_suffix(')'))
+        .unwrap_or(value)
+        .trim();
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+        .trim()
 }
 
 /// Custom deserializers for Gmail API types
@@ -115,6 +915,15 @@ mod tests {
             labels: vec!["INBOX".to_string()],
             has_unsubscribe: false,
             is_automated: false,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 0,
         };
 
         let json = serde_json::to_string(&metadata).unwrap();
@@ -129,4 +938,182 @@ mod tests {
         assert_eq!(EmailCategory::Newsletter, EmailCategory::Newsletter);
         assert_ne!(EmailCategory::Newsletter, EmailCategory::Receipt);
     }
+
+    #[test]
+    fn test_message_metadata_builder() {
+        let msg = MessageMetadataBuilder::new("m1")
+            .sender("News@Example.COM")
+            .subject("Weekly digest")
+            .labels(&["INBOX", "UNREAD"])
+            .unsubscribe(true)
+            .automated(true)
+            .build();
+
+        assert_eq!(msg.thread_id, "thread_m1");
+        assert_eq!(msg.sender_domain, "example.com");
+        assert_eq!(msg.sender_name, "News@Example.COM");
+        assert_eq!(msg.labels, vec!["INBOX", "UNREAD"]);
+        assert!(msg.has_unsubscribe && msg.is_automated);
+    }
+
+    #[test]
+    #[should_panic(expected = "requires a sender")]
+    fn test_message_metadata_builder_requires_sender() {
+        MessageMetadataBuilder::new("m1")
+            .subject("No sender")
+            .build();
+    }
+
+    #[test]
+    fn test_email_thread_from_messages() {
+        let message = |id: &str, sender: &str, days_ago: i64, labels: &[&str]| MessageMetadata {
+            id: id.to_string(),
+            thread_id: "t1".to_string(),
+            sender_email: sender.to_string(),
+            sender_domain: "example.com".to_string(),
+            sender_name: sender.to_string(),
+            subject: format!("Subject {}", id),
+            recipients: vec![],
+            date_received: Utc::now() - chrono::Duration::days(days_ago),
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            has_unsubscribe: false,
+            is_automated: false,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 0,
+        };
+
+        let thread = EmailThread::from_messages(
+            "t1".to_string(),
+            vec![
+                message("m2", "bob@example.com", 1, &["INBOX", "Label_1"]),
+                message("m1", "alice@example.com", 3, &["INBOX"]),
+                message("m3", "alice@example.com", 0, &["UNREAD"]),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(thread.subject, "Subject m1");
+        assert_eq!(thread.messages[0].id, "m1");
+        assert_eq!(
+            thread.participants,
+            vec!["alice@example.com", "bob@example.com"]
+        );
+        assert_eq!(thread.label_ids, vec!["INBOX", "Label_1", "UNREAD"]);
+        assert!(thread.first_date < thread.last_date);
+
+        assert!(EmailThread::from_messages("empty".to_string(), vec![]).is_err());
+    }
+
+    #[test]
+    fn test_label_newtypes_serialize_transparently() {
+        let id = LabelId::new("Label_12345");
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"Label_12345\"");
+        assert_eq!(
+            serde_json::from_str::<LabelId>("\"Label_12345\"").unwrap(),
+            id
+        );
+
+        let name = LabelName::new("auto/newsletters");
+        assert_eq!(name.to_string(), "auto/newsletters");
+        assert!(name.as_str().starts_with("auto/"));
+        assert_eq!(String::from(name), "auto/newsletters");
+    }
+
+    #[test]
+    fn test_filter_rule_resolve_labels() {
+        let mut rule =
+            FilterRule::from_gmail_query("from:*@github.com", "AutoManaged/GitHub".to_string())
+                .unwrap();
+        assert!(rule.label_ids().is_err());
+
+        let mut ids_by_name = HashMap::new();
+        ids_by_name.insert("automanaged/github".to_string(), LabelId::new("Label_7"));
+        ids_by_name.insert("automanaged".to_string(), LabelId::new("Label_1"));
+        rule.additional_labels = vec![LabelName::new("AutoManaged")];
+        rule.resolve_labels(&ids_by_name).unwrap();
+        assert_eq!(rule.target_label, "AutoManaged/GitHub");
+        assert_eq!(
+            rule.label_ids().unwrap(),
+            vec![LabelId::new("Label_7"), LabelId::new("Label_1")]
+        );
+
+        rule.target_label = LabelName::new("AutoManaged/Missing");
+        assert!(rule.resolve_labels(&ids_by_name).is_err());
+    }
+
+    #[test]
+    fn test_filter_rule_from_gmail_query() {
+        let rule = FilterRule::from_gmail_query(
+            "from:(*@github.com) -from:security@github.com subject:\"weekly digest\" to:me@example.com has:attachment",
+            "AutoManaged/GitHub".to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(rule.from_pattern.as_deref(), Some("*@github.com"));
+        assert!(!rule.is_specific_sender);
+        assert_eq!(rule.excluded_senders, vec!["security@github.com"]);
+        assert_eq!(rule.subject_keywords, vec!["\"weekly digest\""]);
+        assert_eq!(
+            rule.extra_terms,
+            vec!["to:(me@example.com)", "has:attachment"]
+        );
+        assert_eq!(rule.target_label, "AutoManaged/GitHub");
+        assert_eq!(
+            rule.auto_name(),
+            "*@github.com-\"weekly digest\"-to:(me@example.com)-has:attachment → AutoManaged/GitHub"
+        );
+
+        let rule =
+            FilterRule::from_gmail_query("from:alerts@bank.com", "Bank".to_string()).unwrap();
+        assert!(rule.is_specific_sender);
+        assert_eq!(rule.from_pattern.as_deref(), Some("alerts@bank.com"));
+        assert_eq!(rule.auto_name(), "alerts@bank.com → Bank");
+
+        let rule = FilterRule::from_gmail_query("from:example.com", "Ex".to_string()).unwrap();
+        assert_eq!(rule.from_pattern.as_deref(), Some("*@example.com"));
+    }
+
+    #[test]
+    fn test_filter_rule_from_gmail_query_errors() {
+        for query in [
+            "label:inbox",
+            "-subject:foo",
+            "has:unicorns",
+            "subject:\"open",
+            "from:(a.com",
+            "from:a.com from:b.com",
+            "-from:x@a.com",
+            "   ",
+        ] {
+            let result = FilterRule::from_gmail_query(query, "Label".to_string());
+            assert!(
+                matches!(result, Err(GmailError::ConfigError(_))),
+                "expected ConfigError for {:?}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn test_describe_gmail_query() {
+        assert_eq!(
+            describe_gmail_query(
+                "from:(*@linkedin.com) -from:(messaging-digest-noreply@linkedin.com) subject:(Job Alerts)"
+            ),
+            "From: All @linkedin.com\nExcept: messaging-digest-noreply@linkedin.com\nSubject: Job Alerts"
+        );
+        assert_eq!(
+            describe_gmail_query("from:a@x.com from:b@x.com label:inbox"),
+            "From: a@x.com, b@x.com\nAlso matching: label:inbox"
+        );
+        // Unbalanced quotes can't be tokenized, so the query is shown as is
+        assert_eq!(describe_gmail_query("subject:\"open"), "subject:\"open");
+    }
 }
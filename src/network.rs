@@ -0,0 +1,187 @@
+//! Grouping sender domains that belong to the same company
+//!
+//! Companies often send from several domains (`amazon.com`, `amazon-email.com`, ...)
+//! that would otherwise be reviewed as unrelated clusters. Domains whose MX records
+//! point at the same mail server suffix are grouped together as a hint for review.
+
+use crate::classifier::extract_main_domain;
+use crate::error::{GmailError, Result};
+use futures::stream::{self, StreamExt};
+use std::collections::BTreeMap;
+use tracing::debug;
+use trust_dns_resolver::TokioAsyncResolver;
+
+/// How many MX lookups run at once
+const MAX_CONCURRENT_LOOKUPS: usize = 16;
+
+/// Mail hosting providers shared by unrelated companies; a common MX suffix
+/// among their customers says nothing about who sent the mail
+const SHARED_MX_PROVIDERS: &[&str] = &[
+    "google.com",
+    "googlemail.com",
+    "outlook.com",
+    "office365.us",
+    "pphosted.com",
+    "ppe-hosted.com",
+    "mimecast.com",
+    "barracudanetworks.com",
+    "messagelabs.com",
+    "secureserver.net",
+    "zoho.com",
+    "icloud.com",
+    "yahoodns.net",
+    "messagingengine.com",
+    "protonmail.ch",
+];
+
+/// Sender domains that appear to belong to the same company
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainGroup {
+    /// Domain the group is named after
+    pub canonical_domain: String,
+    /// The other domains in the group
+    pub related_domains: Vec<String>,
+    /// Why the domains were grouped, e.g. "amazon-email.com MX inbound.amazonses.com"
+    pub evidence: Vec<String>,
+}
+
+impl DomainGroup {
+    /// Whether `domain` (or a parent domain of it) is part of the group
+    pub fn contains(&self, domain: &str) -> bool {
+        std::iter::once(&self.canonical_domain)
+            .chain(&self.related_domains)
+            .any(|member| domain == member || domain.ends_with(&format!(".{}", member)))
+    }
+
+    /// Number of domains in the group, including the canonical one
+    pub fn domain_count(&self) -> usize {
+        self.related_domains.len() + 1
+    }
+}
+
+/// Resolve the MX records of `domains` and group those sharing a mail server suffix
+///
+/// Domains without MX records or whose lookup fails are left out.
+pub async fn build_domain_groups(domains: &[String]) -> Result<Vec<DomainGroup>> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()
+        .map_err(|e| GmailError::NetworkError(format!("Failed to create DNS resolver: {}", e)))?;
+
+    let mx_hosts: BTreeMap<String, Vec<String>> = stream::iter(domains)
+        .map(|domain| {
+            let resolver = &resolver;
+            async move {
+                match resolver.mx_lookup(format!("{}.", domain)).await {
+                    Ok(lookup) => {
+                        let hosts = lookup
+                            .iter()
+                            .map(|mx| mx.exchange().to_utf8().trim_end_matches('.').to_lowercase())
+                            .collect();
+                        Some((domain.to_lowercase(), hosts))
+                    }
+                    Err(e) => {
+                        debug!("MX lookup for {} failed: {}", domain, e);
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_LOOKUPS)
+        .filter_map(|result| async move { result })
+        .collect()
+        .await;
+
+    Ok(group_by_mx(&mx_hosts))
+}
+
+/// Group domains whose MX hosts share a registrable domain (e.g. `amazonses.com`)
+///
+/// `mx_hosts` maps each sender domain to its MX hostnames. Only groups of two or
+/// more domains are returned; shared mail hosting providers never form a group.
+pub fn group_by_mx(mx_hosts: &BTreeMap<String, Vec<String>>) -> Vec<DomainGroup> {
+    // MX suffix -> (domain, host) pairs using it
+    let mut by_suffix: BTreeMap<String, Vec<(&String, &String)>> = BTreeMap::new();
+    for (domain, hosts) in mx_hosts {
+        // The alphabetically first suffix keeps each domain in a single group
+        let suffix = hosts
+            .iter()
+            .map(|host| (extract_main_domain(host), host))
+            .filter(|(suffix, _)| !SHARED_MX_PROVIDERS.contains(&suffix.as_str()))
+            .min();
+        if let Some((suffix, host)) = suffix {
+            by_suffix.entry(suffix).or_default().push((domain, host));
+        }
+    }
+
+    by_suffix
+        .into_iter()
+        .filter(|(_, members)| members.len() >= 2)
+        .map(|(suffix, members)| {
+            // Prefer the domain the mail servers are named after, then the shortest
+            let canonical = members
+                .iter()
+                .map(|(domain, _)| *domain)
+                .min_by_key(|domain| (extract_main_domain(domain) != suffix, domain.len(), *domain))
+                .expect("group has members")
+                .clone();
+            DomainGroup {
+                related_domains: members
+                    .iter()
+                    .map(|(domain, _)| (*domain).clone())
+                    .filter(|domain| *domain != canonical)
+                    .collect(),
+                evidence: members
+                    .iter()
+                    .map(|(domain, host)| format!("{} MX {}", domain, host))
+                    .collect(),
+                canonical_domain: canonical,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hosts(entries: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(domain, hosts)| {
+                (
+                    domain.to_string(),
+                    hosts.iter().map(|h| h.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_group_by_mx() {
+        let mx = hosts(&[
+            ("amazon-email.com", &["inbound.amazonses.com"]),
+            ("amazonses.com", &["feedback-smtp.amazonses.com"]),
+            ("amazon.co.uk", &["mx2.amazonses.com", "mx1.amazonses.com"]),
+            // Both use Google Workspace, which says nothing about the sender
+            ("startup.io", &["aspmx.l.google.com"]),
+            ("bakery.example", &["alt1.aspmx.l.google.com"]),
+            ("solo.example", &["mail.solo.example"]),
+        ]);
+
+        let groups = group_by_mx(&mx);
+        assert_eq!(groups.len(), 1);
+        let group = &groups[0];
+        assert_eq!(group.canonical_domain, "amazonses.com");
+        assert_eq!(
+            group.related_domains,
+            vec!["amazon-email.com", "amazon.co.uk"]
+        );
+        assert_eq!(group.domain_count(), 3);
+        assert!(group
+            .evidence
+            .contains(&"amazon.co.uk MX mx1.amazonses.com".to_string()));
+
+        assert!(group.contains("amazon-email.com"));
+        assert!(group.contains("marketing.amazon-email.com"));
+        assert!(!group.contains("startup.io"));
+    }
+}
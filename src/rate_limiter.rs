@@ -12,6 +12,8 @@
 //! - Allows bursting when quota is available
 //! - Blocks when quota is exhausted
 
+use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
@@ -71,6 +73,29 @@ struct RateLimiterState {
     total_consumed: u64,
     /// Total operations performed (for stats)
     total_operations: u64,
+    /// Operations and units consumed per Gmail API method (for stats)
+    by_operation: HashMap<String, QuotaBreakdown>,
+}
+
+impl RateLimiterState {
+    /// Refill the bucket based on elapsed time
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        let refill_amount = elapsed * self.refill_rate;
+        self.available_units = (self.available_units + refill_amount).min(self.max_units);
+        self.last_refill = now;
+    }
+
+    /// Take `units` from the bucket and record them against `operation`
+    fn consume(&mut self, operation: &str, units: f64) {
+        self.available_units -= units;
+        self.total_consumed += units as u64;
+        self.total_operations += 1;
+        let breakdown = self.by_operation.entry(operation.to_string()).or_default();
+        breakdown.count += 1;
+        breakdown.quota_consumed += units as u64;
+    }
 }
 
 impl QuotaRateLimiter {
@@ -97,6 +122,7 @@ impl QuotaRateLimiter {
                 last_refill: Instant::now(),
                 total_consumed: 0,
                 total_operations: 0,
+                by_operation: HashMap::new(),
             })),
         }
     }
@@ -114,19 +140,18 @@ impl QuotaRateLimiter {
     /// # Returns
     /// A guard that can be used to track the operation (currently just returns ())
     pub async fn acquire(&self, cost: QuotaCost) -> QuotaPermit {
+        self.acquire_for("other", cost).await
+    }
+
+    /// Acquire quota units like [`acquire`](Self::acquire), recording them
+    /// against `operation` (e.g. "messages.get") in the per-operation stats
+    pub async fn acquire_for(&self, operation: &str, cost: QuotaCost) -> QuotaPermit {
         let units_needed = cost.units() as f64;
 
         loop {
             let wait_time = {
                 let mut state = self.inner.lock().await;
-
-                // Refill bucket based on elapsed time
-                let now = Instant::now();
-                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
-                let refill_amount = elapsed * state.refill_rate;
-                state.available_units =
-                    (state.available_units + refill_amount).min(state.max_units);
-                state.last_refill = now;
+                state.refill();
 
                 trace!(
                     "Quota state: {:.1}/{:.1} units available, requesting {:.0}",
@@ -137,9 +162,7 @@ impl QuotaRateLimiter {
 
                 if state.available_units >= units_needed {
                     // We have enough quota, consume it
-                    state.available_units -= units_needed;
-                    state.total_consumed += units_needed as u64;
-                    state.total_operations += 1;
+                    state.consume(operation, units_needed);
 
                     debug!(
                         "Acquired {} quota units, {:.1} remaining",
@@ -171,18 +194,10 @@ impl QuotaRateLimiter {
     pub async fn try_acquire(&self, cost: QuotaCost) -> Option<QuotaPermit> {
         let units_needed = cost.units() as f64;
         let mut state = self.inner.lock().await;
-
-        // Refill bucket
-        let now = Instant::now();
-        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
-        let refill_amount = elapsed * state.refill_rate;
-        state.available_units = (state.available_units + refill_amount).min(state.max_units);
-        state.last_refill = now;
+        state.refill();
 
         if state.available_units >= units_needed {
-            state.available_units -= units_needed;
-            state.total_consumed += units_needed as u64;
-            state.total_operations += 1;
+            state.consume("other", units_needed);
             Some(QuotaPermit { _private: () })
         } else {
             None
@@ -198,20 +213,14 @@ impl QuotaRateLimiter {
             refill_rate: state.refill_rate as u32,
             total_consumed: state.total_consumed,
             total_operations: state.total_operations,
+            by_operation: state.by_operation.clone(),
         }
     }
 
     /// Check current available quota without consuming any
     pub async fn available(&self) -> f64 {
         let mut state = self.inner.lock().await;
-
-        // Refill bucket based on elapsed time
-        let now = Instant::now();
-        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
-        let refill_amount = elapsed * state.refill_rate;
-        state.available_units = (state.available_units + refill_amount).min(state.max_units);
-        state.last_refill = now;
-
+        state.refill();
         state.available_units
     }
 }
@@ -252,6 +261,34 @@ pub struct QuotaStats {
     pub total_consumed: u64,
     /// Total operations performed
     pub total_operations: u64,
+    /// Usage per Gmail API method, e.g. "messages.get"
+    pub by_operation: HashMap<String, QuotaBreakdown>,
+}
+
+impl QuotaStats {
+    /// Per-operation usage, highest quota consumption first
+    pub fn breakdown_by_cost(&self) -> Vec<(&str, &QuotaBreakdown)> {
+        let mut breakdown: Vec<_> = self
+            .by_operation
+            .iter()
+            .map(|(operation, usage)| (operation.as_str(), usage))
+            .collect();
+        breakdown.sort_by(|a, b| {
+            b.1.quota_consumed
+                .cmp(&a.1.quota_consumed)
+                .then_with(|| a.0.cmp(b.0))
+        });
+        breakdown
+    }
+}
+
+/// Quota usage of a single Gmail API method
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QuotaBreakdown {
+    /// Number of calls
+    pub count: u64,
+    /// Quota units consumed by those calls
+    pub quota_consumed: u64,
 }
 
 #[cfg(test)]
@@ -334,7 +371,7 @@ mod tests {
         // Should have refilled ~50 units
         let available = limiter.available().await;
         assert!(
-            available >= 40.0 && available <= 60.0,
+            (40.0..=60.0).contains(&available),
             "Should have refilled ~50 units, got {}",
             available
         );
@@ -354,6 +391,35 @@ mod tests {
         assert_eq!(stats.max_units, 100);
     }
 
+    #[tokio::test]
+    async fn test_stats_by_operation() {
+        let limiter = QuotaRateLimiter::with_config(1000.0, 1000.0);
+
+        let _ = limiter.acquire_for("messages.get", QuotaCost::Read).await;
+        let _ = limiter.acquire_for("messages.get", QuotaCost::Read).await;
+        let _ = limiter
+            .acquire_for("messages.batchModify", QuotaCost::Batch)
+            .await;
+        let _ = limiter.acquire(QuotaCost::Custom(1)).await;
+
+        let stats = limiter.stats().await;
+        assert_eq!(
+            stats.by_operation["messages.get"],
+            QuotaBreakdown {
+                count: 2,
+                quota_consumed: 10
+            }
+        );
+        assert_eq!(stats.by_operation["other"].count, 1);
+
+        let order: Vec<&str> = stats
+            .breakdown_by_cost()
+            .into_iter()
+            .map(|(operation, _)| operation)
+            .collect();
+        assert_eq!(order, ["messages.batchModify", "messages.get", "other"]);
+    }
+
     #[tokio::test]
     async fn test_clone_shares_state() {
         let limiter1 = QuotaRateLimiter::with_config(100.0, 100.0);
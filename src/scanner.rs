@@ -1,12 +1,22 @@
 //! Email scanner for retrieving historical messages with concurrent fetching and checkpointing
 
+use crate::client::{GmailClient, MAX_PAGE_SIZE};
 use crate::error::{GmailError, Result};
-use crate::models::MessageMetadata;
+use crate::models::{MessageMetadata, UnsubscribeLink, UnsubscribeMethod};
 use chrono::{DateTime, Duration, Utc};
-use google_gmail1::api::Message;
+use futures::stream::{self, StreamExt};
+use google_gmail1::api::{Message, MessagePart, MessagePartHeader};
+use lru::LruCache;
+use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
 
 /// Message format options for Gmail API
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -154,6 +164,528 @@ impl ScanConfig {
     }
 }
 
+/// Default number of messages kept in a [`MetadataCache`]
+pub const DEFAULT_METADATA_CACHE_SIZE: usize = 50_000;
+
+/// Metadata of messages fetched by earlier runs, so rescans don't fetch them again
+///
+/// Saved to `.gmail-automation/metadata-cache.json`. When full, the least recently
+/// used message is evicted. Cached label IDs are those at the time of the fetch.
+#[derive(Debug)]
+pub struct MetadataCache {
+    entries: LruCache<String, MessageMetadata>,
+    path: PathBuf,
+}
+
+impl MetadataCache {
+    /// An empty cache of at most `max_size` messages that will be saved to `path`
+    pub fn new(path: PathBuf, max_size: usize) -> Self {
+        Self {
+            entries: LruCache::new(cache_capacity(max_size)),
+            path,
+        }
+    }
+
+    /// Load the cache saved at `path`, or start an empty one if there is none,
+    /// keeping the `max_size` most recently used messages
+    pub async fn load(path: &Path, max_size: usize) -> Result<Self> {
+        let mut cache = Self::new(path.to_path_buf(), max_size);
+        if !path.exists() {
+            return Ok(cache);
+        }
+
+        let json = tokio::fs::read_to_string(path).await?;
+        // Saved least recently used first, so inserting restores the order
+        let messages: Vec<MessageMetadata> = serde_json::from_str(&json)
+            .map_err(|e| GmailError::Unknown(format!("Failed to parse metadata cache: {}", e)))?;
+        for message in messages {
+            cache.insert(message);
+        }
+        Ok(cache)
+    }
+
+    /// Cached metadata of message `id`, marking it as recently used
+    pub fn get(&mut self, id: &str) -> Option<&MessageMetadata> {
+        self.entries.get(id)
+    }
+
+    /// Add or refresh a message, evicting the least recently used one when full
+    pub fn insert(&mut self, msg: MessageMetadata) {
+        self.entries.put(msg.id.clone(), msg);
+    }
+
+    /// Split `ids` into cached messages and the IDs that still need fetching
+    pub fn split(&mut self, ids: Vec<String>) -> (Vec<MessageMetadata>, Vec<String>) {
+        let mut cached = Vec::new();
+        let mut missing = Vec::new();
+        for id in ids {
+            match self.get(&id) {
+                Some(msg) => cached.push(msg.clone()),
+                None => missing.push(id),
+            }
+        }
+        (cached, missing)
+    }
+
+    /// Write the cache to its file, least recently used first
+    pub async fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let messages: Vec<&MessageMetadata> = self.entries.iter().rev().map(|(_, m)| m).collect();
+        let json = serde_json::to_string(&messages).map_err(|e| {
+            GmailError::Unknown(format!("Failed to serialize metadata cache: {}", e))
+        })?;
+        tokio::fs::write(&self.path, json).await?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Cache capacity for `max_size`, holding at least one message
+fn cache_capacity(max_size: usize) -> NonZeroUsize {
+    NonZeroUsize::new(max_size).unwrap_or(NonZeroUsize::MIN)
+}
+
+/// Messages saved by the `scan` command, to classify later without the Gmail API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanOutput {
+    pub scanned_at: DateTime<Utc>,
+    /// Gmail search query the messages were listed with
+    pub query: String,
+    pub message_count: usize,
+    pub messages: Vec<MessageMetadata>,
+}
+
+/// One CSV row of a [`ScanOutput`]; list fields are joined with `;`
+#[derive(Serialize)]
+struct ScanCsvRow<'a> {
+    id: &'a str,
+    thread_id: &'a str,
+    sender_email: &'a str,
+    sender_domain: &'a str,
+    sender_name: &'a str,
+    subject: &'a str,
+    date_received: DateTime<Utc>,
+    recipients: String,
+    labels: String,
+    has_unsubscribe: bool,
+    is_automated: bool,
+    list_id: Option<&'a str>,
+    size_bytes: u64,
+}
+
+impl ScanOutput {
+    pub fn new(query: String, messages: Vec<MessageMetadata>) -> Self {
+        Self {
+            scanned_at: Utc::now(),
+            query,
+            message_count: messages.len(),
+            messages,
+        }
+    }
+
+    /// Load a scan saved with [`ScanOutput::save_json`]
+    pub async fn load(path: &Path) -> Result<Self> {
+        let json = tokio::fs::read_to_string(path).await?;
+        serde_json::from_str(&json).map_err(|e| {
+            GmailError::Unknown(format!("Failed to parse scan output {:?}: {}", path, e))
+        })
+    }
+
+    /// Write the scan as pretty-printed JSON
+    pub async fn save_json(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| GmailError::Unknown(format!("Failed to serialize scan output: {}", e)))?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
+
+    /// Write one row per message, for spreadsheets
+    ///
+    /// CSV can't hold the scan header, so only JSON output can be read back by `classify`.
+    pub fn save_csv(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut writer = csv::Writer::from_path(path).map_err(std::io::Error::from)?;
+        for msg in &self.messages {
+            writer
+                .serialize(ScanCsvRow {
+                    id: &msg.id,
+                    thread_id: &msg.thread_id,
+                    sender_email: &msg.sender_email,
+                    sender_domain: &msg.sender_domain,
+                    sender_name: &msg.sender_name,
+                    subject: &msg.subject,
+                    date_received: msg.date_received,
+                    recipients: msg.recipients.join(";"),
+                    labels: msg.labels.join(";"),
+                    has_unsubscribe: msg.has_unsubscribe,
+                    is_automated: msg.is_automated,
+                    list_id: msg.list_id.as_deref(),
+                    size_bytes: msg.size_bytes,
+                })
+                .map_err(std::io::Error::from)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Drop messages whose ID was already seen, keeping the first occurrence
+///
+/// A message can match several scan queries (e.g. one per included label).
+/// Returns the remaining messages and how many were removed.
+pub fn deduplicate(messages: Vec<MessageMetadata>) -> (Vec<MessageMetadata>, usize) {
+    let total = messages.len();
+    let mut seen = HashSet::with_capacity(total);
+    let unique: Vec<MessageMetadata> = messages
+        .into_iter()
+        .filter(|message| seen.insert(message.id.clone()))
+        .collect();
+    let removed = total - unique.len();
+    if removed > 0 {
+        tracing::debug!("Removed {} duplicate messages", removed);
+    }
+    (unique, removed)
+}
+
+/// Whether `sender` matches one of `exclusions` (`scan.exclude_senders`)
+///
+/// Entries are exact addresses or globs where `*` matches any run of characters,
+/// such as `*@domain.com`; matching ignores case.
+pub fn is_sender_excluded(sender: &str, exclusions: &[String]) -> bool {
+    let sender = sender.to_lowercase();
+    exclusions
+        .iter()
+        .any(|pattern| glob_matches(&pattern.trim().to_lowercase(), &sender))
+}
+
+/// Drop messages from senders listed in `scan.exclude_senders`
+///
+/// Unlike cluster exclusions these messages are never classified or counted,
+/// which keeps e.g. self-sent reminders out of the results entirely.
+pub fn apply_sender_exclusions(
+    messages: Vec<MessageMetadata>,
+    exclusions: &[String],
+) -> Vec<MessageMetadata> {
+    if exclusions.is_empty() {
+        return messages;
+    }
+    let total = messages.len();
+    let kept: Vec<MessageMetadata> = messages
+        .into_iter()
+        .filter(|message| !is_sender_excluded(&message.sender_email, exclusions))
+        .collect();
+    let excluded = total - kept.len();
+    if excluded > 0 {
+        tracing::info!(
+            "Excluded {} messages from senders in scan.exclude_senders",
+            excluded
+        );
+    }
+    kept
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(prefix) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let suffix = parts.pop().unwrap_or_default();
+    for part in parts {
+        match remaining.find(part) {
+            Some(index) => remaining = &remaining[index + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= suffix.len() && remaining.ends_with(suffix)
+}
+
+/// Scan each of `domains` separately, `scan.max_concurrent_requests` domains at a time
+///
+/// Every domain gets its own `from:(*@domain)` query on top of [`crate::config::ScanConfig::search_query`],
+/// so the period and label filters match the normal scan. Its messages are fetched as
+/// soon as its IDs are listed, so counts for small domains are ready long before a
+/// global scan would finish. Domains without messages map to an empty list.
+///
+/// Domains share the `scan.max_messages` budget; once it is used up the remaining
+/// domains are skipped and the returned flag is set.
+pub async fn fetch_by_domains(
+    domains: &[String],
+    scan: &crate::config::ScanConfig,
+    client: &Arc<dyn GmailClient>,
+) -> Result<(HashMap<String, Vec<MessageMetadata>>, bool)> {
+    let search_query = scan.search_query();
+    // Messages still allowed by `scan.max_messages`, shared by the running domains
+    let budget = Arc::new(AtomicUsize::new(scan.max_messages.unwrap_or(usize::MAX)));
+    let truncated = Arc::new(AtomicBool::new(false));
+    let mut pending = domains.iter().collect::<HashSet<_>>().into_iter();
+    let mut tasks = JoinSet::new();
+    let mut by_domain = HashMap::new();
+
+    loop {
+        while tasks.len() < scan.max_concurrent_requests.max(1) {
+            let Some(domain) = pending.next() else {
+                break;
+            };
+            if budget.load(Ordering::SeqCst) == 0 {
+                truncated.store(true, Ordering::SeqCst);
+                break;
+            }
+            let client = client.clone();
+            let domain = domain.clone();
+            let query = format!("from:(*@{}) {}", domain, search_query);
+            let budget = budget.clone();
+            let truncated = truncated.clone();
+            tasks.spawn(async move {
+                let mut ids = client.list_message_ids(&query).await?;
+                let allowed = budget
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                        Some(left - left.min(ids.len()))
+                    })
+                    .unwrap_or_default()
+                    .min(ids.len());
+                if allowed < ids.len() {
+                    ids.truncate(allowed);
+                    truncated.store(true, Ordering::SeqCst);
+                }
+                let messages = if ids.is_empty() {
+                    Vec::new()
+                } else {
+                    client.fetch_messages_batch(ids).await?
+                };
+                Ok::<_, GmailError>((domain, messages))
+            });
+        }
+
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        let (domain, messages) = joined
+            .map_err(|e| GmailError::Unknown(format!("Domain scan task failed: {}", e)))??;
+        tracing::info!("{}: {} messages", domain, messages.len());
+        by_domain.insert(domain, messages);
+    }
+
+    Ok((by_domain, truncated.load(Ordering::SeqCst)))
+}
+
+/// Message IDs listed page by page, with the token each page was listed with
+///
+/// As messages are classified, [`ListedPages::resume_token`] moves on to the
+/// first page that still has unclassified messages, so an interrupted scan can
+/// list again from there instead of from the start.
+#[derive(Debug, Default)]
+pub struct ListedPages {
+    /// Token of each page and how many of its messages aren't classified yet
+    pages: Vec<(Option<String>, usize)>,
+    page_of: HashMap<String, usize>,
+    /// Token to list from once every page is classified
+    next_page_token: Option<String>,
+    first_open: usize,
+    truncated: bool,
+}
+
+impl ListedPages {
+    /// Count a delivered message as done
+    pub fn complete(&mut self, id: &str) {
+        if let Some(index) = self.page_of.remove(id) {
+            self.pages[index].1 -= 1;
+        }
+    }
+
+    /// Token of the first page with unclassified messages (`None` is the first page)
+    pub fn resume_token(&mut self) -> Option<String> {
+        while self
+            .pages
+            .get(self.first_open)
+            .is_some_and(|(_, left)| *left == 0)
+        {
+            self.first_open += 1;
+        }
+        match self.pages.get(self.first_open) {
+            Some((token, _)) => token.clone(),
+            None => self.next_page_token.clone(),
+        }
+    }
+
+    /// Whether listing stopped at `max` IDs with more messages left
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+/// List message IDs matching `query` from `page_token`, skipping `already_classified`
+/// and stopping after `max` IDs
+pub async fn list_message_pages(
+    client: &dyn GmailClient,
+    query: &str,
+    page_token: Option<String>,
+    already_classified: &HashSet<String>,
+    max: Option<usize>,
+) -> Result<(Vec<String>, ListedPages)> {
+    let mut ids = Vec::new();
+    let mut listed = ListedPages::default();
+    let mut token = page_token;
+    loop {
+        let (page, next) = client
+            .list_message_page(query, MAX_PAGE_SIZE, token.clone())
+            .await?;
+        let index = listed.pages.len();
+        let mut left = 0;
+        for id in page {
+            if already_classified.contains(&id) {
+                continue;
+            }
+            if max.is_some_and(|max| ids.len() >= max) {
+                // The rest of this page is listed again on resume
+                listed.truncated = true;
+                listed.next_page_token = token.clone();
+                break;
+            }
+            listed.page_of.insert(id.clone(), index);
+            ids.push(id);
+            left += 1;
+        }
+        listed.pages.push((token, left));
+        match next {
+            Some(next) if !listed.truncated => token = Some(next),
+            _ => break,
+        }
+    }
+    Ok((ids, listed))
+}
+
+/// Fetch a single message, or every message of a thread in `thread_mode`
+pub async fn fetch_messages_for(
+    client: &dyn GmailClient,
+    id: &str,
+    thread_mode: bool,
+) -> Result<Vec<MessageMetadata>> {
+    if thread_mode {
+        client.get_thread(id).await.map(|thread| thread.messages)
+    } else {
+        client.get_message(id).await.map(|message| vec![message])
+    }
+}
+
+/// Fetch messages and send each one to `tx` as soon as it arrives.
+///
+/// At most `max_concurrent` requests are in flight, and no new request starts while
+/// `tx` is full, so a slow consumer bounds how many messages are held in memory.
+/// In `thread_mode`, `ids` are thread IDs and every message of each thread is sent.
+/// Fetching stops without error once the receiver is dropped.
+///
+/// IDs that fail with a transient error (see [`GmailError::is_transient`]) are
+/// returned with their error so they can be retried later; any other error
+/// stops fetching.
+pub async fn send_messages(
+    client: &dyn GmailClient,
+    ids: Vec<String>,
+    thread_mode: bool,
+    max_concurrent: usize,
+    tx: mpsc::Sender<MessageMetadata>,
+) -> Result<Vec<(String, GmailError)>> {
+    let mut seen = HashSet::new();
+    let unique_ids = ids.into_iter().filter(move |id| seen.insert(id.clone()));
+
+    let mut fetched = std::pin::pin!(stream::iter(unique_ids)
+        .map(|id| async move {
+            let result = fetch_messages_for(client, &id, thread_mode).await;
+            (id, result)
+        })
+        .buffer_unordered(max_concurrent.max(1)));
+
+    let mut failed = Vec::new();
+    while let Some((id, result)) = fetched.next().await {
+        let messages = match result {
+            Ok(messages) => messages,
+            Err(e) if e.is_transient() => {
+                failed.push((id, e));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        for message in messages {
+            if tx.send(message).await.is_err() {
+                return Ok(failed);
+            }
+        }
+    }
+
+    Ok(failed)
+}
+
+/// Forward messages from [`GmailClient::stream_messages_bounded`] to `tx`
+///
+/// Messages that fail with a transient error even after the client's own retries
+/// are skipped and counted; a later `--resume` run lists and fetches them again.
+/// Any other error stops forwarding. Stops without error once `tx`'s receiver is
+/// dropped.
+pub async fn forward_streamed_messages(
+    mut messages: mpsc::Receiver<Result<MessageMetadata>>,
+    tx: mpsc::Sender<MessageMetadata>,
+) -> Result<usize> {
+    let mut skipped = 0;
+    while let Some(result) = messages.recv().await {
+        match result {
+            Ok(message) => {
+                if tx.send(message).await.is_err() {
+                    break;
+                }
+            }
+            Err(e) if e.is_transient() => {
+                tracing::warn!("Skipping message that could not be fetched: {}", e);
+                skipped += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(skipped)
+}
+
+/// Subjects used by mail servers for delivery failure notices
+static BOUNCE_SUBJECT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(delivery status notification|^\s*undeliverable:|mail delivery failed to)")
+        .unwrap()
+});
+
+/// Whether a message is a bounce (delivery status notification)
+///
+/// Checks for a `multipart/report; report-type=delivery-status` body, the empty
+/// `Return-Path: <>` used by mail servers (RFC 5321), and typical subjects.
+pub fn is_bounce(content_type: Option<&str>, return_path: Option<&str>, subject: &str) -> bool {
+    let delivery_report = content_type.is_some_and(|value| {
+        let value = value.to_ascii_lowercase();
+        value.contains("multipart/report") && value.contains("delivery-status")
+    });
+    let null_sender = return_path.is_some_and(|value| value.trim() == "<>");
+
+    delivery_report || null_sender || BOUNCE_SUBJECT.is_match(subject)
+}
+
+/// Header name to value map borrowing from the parsed API response
+pub type HeaderMap<'a> = HashMap<&'a str, &'a str>;
+
 /// Parse Gmail API Message to MessageMetadata
 pub fn parse_message_metadata(message: &Message) -> Result<MessageMetadata> {
     let id = message
@@ -166,21 +698,63 @@ pub fn parse_message_metadata(message: &Message) -> Result<MessageMetadata> {
         .clone()
         .ok_or_else(|| GmailError::InvalidMessageFormat("Missing thread ID".to_string()))?;
 
-    let headers = get_headers_map(message);
+    let labels = message.label_ids.clone().unwrap_or_default();
 
-    let sender_email = headers
-        .get("From")
-        .and_then(|from| extract_sender_email(from))
-        .unwrap_or_default();
+    let headers = borrow_headers(message);
+
+    let mut metadata = extract_message_metadata(id, thread_id, labels, &headers);
+    if let Some(payload) = &message.payload {
+        metadata.attachment_types = collect_mime_types(payload);
+    }
+    metadata.size_bytes = message.size_estimate.unwrap_or(0).max(0) as u64;
+    Ok(metadata)
+}
+
+/// MIME types of a message part and all nested parts, lowercase and without duplicates
+///
+/// With the `metadata` format only the top-level type is known; `full` messages
+/// also list their parts, e.g. the `text/calendar` part of an invitation.
+pub fn collect_mime_types(part: &MessagePart) -> Vec<String> {
+    fn visit(part: &MessagePart, types: &mut Vec<String>) {
+        if let Some(mime_type) = &part.mime_type {
+            let mime_type = mime_type.to_lowercase();
+            if !types.contains(&mime_type) {
+                types.push(mime_type);
+            }
+        }
+        for child in part.parts.iter().flatten() {
+            visit(child, types);
+        }
+    }
+
+    let mut types = Vec::new();
+    visit(part, &mut types);
+    types
+}
+
+/// Build `MessageMetadata` from a borrowed header map.
+///
+/// Only the fields kept on `MessageMetadata` are copied out of `headers`.
+pub fn extract_message_metadata(
+    id: String,
+    thread_id: String,
+    labels: Vec<String>,
+    headers: &HeaderMap<'_>,
+) -> MessageMetadata {
+    let from = headers.get("From").copied();
+
+    let sender_email = from.and_then(extract_sender_email).unwrap_or_default();
 
     let sender_domain = extract_domain(&sender_email).unwrap_or_default();
 
-    let sender_name = headers
-        .get("From")
-        .and_then(|from| extract_sender_name(from))
+    let sender_name = from
+        .and_then(extract_sender_name)
         .unwrap_or_else(|| sender_email.clone());
 
-    let subject = headers.get("Subject").cloned().unwrap_or_default();
+    let subject = headers
+        .get("Subject")
+        .map(|s| s.to_string())
+        .unwrap_or_default();
 
     let recipients = headers
         .get("To")
@@ -192,12 +766,36 @@ pub fn parse_message_metadata(message: &Message) -> Result<MessageMetadata> {
         .and_then(|date_str| parse_email_date(date_str))
         .unwrap_or_else(Utc::now);
 
-    let labels = message.label_ids.clone().unwrap_or_default();
-
     let has_unsubscribe =
         headers.contains_key("List-Unsubscribe") || headers.contains_key("List-Unsubscribe-Post");
 
-    Ok(MessageMetadata {
+    let (spf_pass, dkim_pass) = headers
+        .get("Authentication-Results")
+        .map(|value| parse_authentication_results(value))
+        .unwrap_or_default();
+
+    let list_id = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("List-ID"))
+        .and_then(|(_, value)| parse_list_id(value));
+
+    let unsubscribe_links = headers
+        .get("List-Unsubscribe")
+        .map(|value| parse_unsubscribe_links(value))
+        .unwrap_or_default();
+
+    let is_reply = headers.contains_key("In-Reply-To");
+    let auto_submitted = headers
+        .get("Auto-Submitted")
+        .map(|v| v.trim().to_lowercase());
+
+    let is_bounce = is_bounce(
+        headers.get("Content-Type").copied(),
+        headers.get("Return-Path").copied(),
+        &subject,
+    );
+
+    MessageMetadata {
         id,
         thread_id,
         sender_email,
@@ -209,7 +807,50 @@ pub fn parse_message_metadata(message: &Message) -> Result<MessageMetadata> {
         labels,
         has_unsubscribe,
         is_automated: false, // Will be determined by classifier
-    })
+        spf_pass,
+        dkim_pass,
+        list_id,
+        unsubscribe_links,
+        is_reply,
+        auto_submitted,
+        is_bounce,
+        attachment_types: Vec::new(),
+        size_bytes: 0,
+    }
+}
+
+/// Parse raw `payload.headers` JSON entries (`{"name": ..., "value": ...}`) without
+/// copying header names or values.
+///
+/// Entries missing either field are skipped; a repeated header keeps its last value.
+pub fn parse_headers_zero_copy(raw_headers: &[serde_json::Value]) -> HeaderMap<'_> {
+    let mut headers = HashMap::with_capacity(raw_headers.len());
+
+    for header in raw_headers {
+        let name = header.get("name").and_then(|n| n.as_str());
+        let value = header.get("value").and_then(|v| v.as_str());
+        if let (Some(name), Some(value)) = (name, value) {
+            headers.insert(name, value);
+        }
+    }
+
+    headers
+}
+
+/// Get all headers as a map borrowing from `message`
+pub fn borrow_headers(message: &Message) -> HeaderMap<'_> {
+    let mut headers = HashMap::new();
+
+    if let Some(header_list) = message.payload.as_ref().and_then(|p| p.headers.as_ref()) {
+        headers.reserve(header_list.len());
+        for header in header_list {
+            if let (Some(name), Some(value)) = (&header.name, &header.value) {
+                headers.insert(name.as_str(), value.as_str());
+            }
+        }
+    }
+
+    headers
 }
 
 /// Extract header value from message
@@ -275,6 +916,102 @@ pub fn extract_recipients(to_header: &str) -> Vec<String> {
         .collect()
 }
 
+/// Extract SPF and DKIM results from an `Authentication-Results` header
+///
+/// Returns `(spf_pass, dkim_pass)`; each is `None` when the method isn't
+/// reported. Matching is case-insensitive, parenthesized comments are ignored,
+/// and DKIM counts as passed when any of several signatures passed.
+pub fn parse_authentication_results(value: &str) -> (Option<bool>, Option<bool>) {
+    let mut stripped = String::with_capacity(value.len());
+    let mut depth = 0usize;
+    for c in value.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+    }
+
+    let mut spf = None;
+    let mut dkim = None;
+    for token in stripped.split(|c: char| c == ';' || c.is_whitespace()) {
+        let Some((method, result)) = token.split_once('=') else {
+            continue;
+        };
+        let passed = result.eq_ignore_ascii_case("pass");
+        if method.eq_ignore_ascii_case("spf") {
+            spf = Some(spf.unwrap_or(false) || passed);
+        } else if method.eq_ignore_ascii_case("dkim") {
+            dkim = Some(dkim.unwrap_or(false) || passed);
+        }
+    }
+    (spf, dkim)
+}
+
+/// Extract the mailing list identifier from a `List-ID` header (RFC 2919)
+///
+/// `List-ID: Rust Dev <dev.rust-lang.org>` yields `"dev.rust-lang.org"`.
+pub fn extract_list_id(headers: &[MessagePartHeader]) -> Option<String> {
+    headers
+        .iter()
+        .find(|h| {
+            h.name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case("List-ID"))
+        })
+        .and_then(|h| h.value.as_deref())
+        .and_then(parse_list_id)
+}
+
+/// List identifier from a `List-ID` value, taken from the angle brackets if present
+fn parse_list_id(value: &str) -> Option<String> {
+    let id = match (value.rfind('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &value[start + 1..end],
+        _ => value,
+    };
+    let id = id.trim();
+    (!id.is_empty()).then(|| id.to_lowercase())
+}
+
+/// Extract unsubscribe links from a `List-Unsubscribe` header (RFC 2369)
+///
+/// The header holds comma-separated, angle-bracketed URLs such as
+/// `<https://example.com/u?id=1>, <mailto:unsubscribe@example.com>`.
+/// Schemes other than http(s) and mailto are skipped.
+pub fn extract_unsubscribe_links(headers: &[MessagePartHeader]) -> Vec<UnsubscribeLink> {
+    headers
+        .iter()
+        .find(|h| {
+            h.name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case("List-Unsubscribe"))
+        })
+        .and_then(|h| h.value.as_deref())
+        .map(parse_unsubscribe_links)
+        .unwrap_or_default()
+}
+
+/// Unsubscribe links from a `List-Unsubscribe` value
+fn parse_unsubscribe_links(value: &str) -> Vec<UnsubscribeLink> {
+    value
+        .split(',')
+        .filter_map(|part| {
+            let url = part.trim().strip_prefix('<')?.strip_suffix('>')?.trim();
+            let scheme = url.split_once(':')?.0.to_ascii_lowercase();
+            let method = match scheme.as_str() {
+                "http" | "https" => UnsubscribeMethod::Http,
+                "mailto" => UnsubscribeMethod::Mailto,
+                _ => return None,
+            };
+            Some(UnsubscribeLink {
+                url: url.to_string(),
+                method,
+            })
+        })
+        .collect()
+}
+
 /// Parse email date header
 pub fn parse_email_date(date_str: &str) -> Option<DateTime<Utc>> {
     // Try RFC 2822 format first
@@ -293,6 +1030,62 @@ pub fn parse_email_date(date_str: &str) -> Option<DateTime<Utc>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::TryStreamExt;
+
+    mockall::mock! {
+        pub TestGmailClient {}
+
+        #[async_trait::async_trait]
+        impl crate::client::GmailClient for TestGmailClient {
+            async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
+            async fn list_message_page(&self, query: &str, page_size: usize, page_token: Option<String>) -> Result<(Vec<String>, Option<String>)>;
+            async fn get_message(&self, id: &str) -> Result<crate::models::MessageMetadata>;
+            async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+            async fn get_thread(&self, thread_id: &str) -> Result<crate::models::EmailThread>;
+            async fn list_labels(&self) -> Result<Vec<crate::client::LabelInfo>>;
+            async fn create_label(&self, name: &str) -> Result<crate::models::LabelId>;
+            async fn create_label_with_visibility(&self, name: &str, visibility: &crate::config::LabelVisibility) -> Result<crate::models::LabelId>;
+            async fn update_label_visibility(&self, label_id: &str, visibility: &crate::config::LabelVisibility) -> Result<()>;
+            async fn delete_label(&self, label_id: &str) -> Result<()>;
+            async fn create_filter(&self, filter: &crate::models::FilterRule) -> Result<String>;
+            async fn list_filters(&self) -> Result<Vec<crate::client::ExistingFilterInfo>>;
+            async fn delete_filter(&self, filter_id: &str) -> Result<()>;
+            async fn update_filter(&self, filter_id: &str, filter: &crate::models::FilterRule) -> Result<String>;
+            async fn apply_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+            async fn remove_label(&self, message_id: &str, label_id: &crate::models::LabelId) -> Result<()>;
+            async fn batch_remove_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+            async fn batch_add_label(&self, message_ids: &[String], label_id: &crate::models::LabelId) -> Result<usize>;
+            async fn batch_modify_labels(&self, message_ids: &[String], add_label_ids: &[crate::models::LabelId], remove_label_ids: &[crate::models::LabelId]) -> Result<crate::client::BatchModifyResult>;
+            async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<crate::models::MessageMetadata>>;
+            async fn fetch_messages_with_progress(&self, message_ids: Vec<String>, on_progress: crate::client::ProgressCallback) -> Result<Vec<crate::models::MessageMetadata>>;
+            async fn quota_stats(&self) -> crate::rate_limiter::QuotaStats;
+        }
+    }
+
+    fn thread_message(id: &str, thread_id: &str, sender: &str) -> MessageMetadata {
+        MessageMetadata {
+            id: id.to_string(),
+            thread_id: thread_id.to_string(),
+            sender_email: sender.to_string(),
+            sender_domain: extract_domain(sender).unwrap_or_default(),
+            sender_name: sender.to_string(),
+            subject: "Re: plans".to_string(),
+            recipients: vec![],
+            date_received: Utc::now(),
+            labels: vec!["INBOX".to_string()],
+            has_unsubscribe: false,
+            is_automated: false,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 0,
+        }
+    }
 
     #[test]
     fn test_message_format() {
@@ -351,6 +1144,536 @@ mod tests {
         assert!(recipients.contains(&"user2@test.org".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_list_message_pages_resumes_from_saved_token() {
+        let mut mock = MockTestGmailClient::new();
+        mock.expect_list_message_page()
+            .withf(|_, _, token| token.is_none())
+            .times(1)
+            .returning(|_, _, _| Ok((vec!["m1".into(), "m2".into()], Some("page2".into()))));
+        mock.expect_list_message_page()
+            .withf(|_, _, token| token.as_deref() == Some("page2"))
+            .times(2)
+            .returning(|_, _, _| Ok((vec!["m3".into(), "m4".into()], None)));
+
+        let (ids, mut pages) = list_message_pages(&mock, "in:inbox", None, &HashSet::new(), None)
+            .await
+            .unwrap();
+        assert_eq!(ids, vec!["m1", "m2", "m3", "m4"]);
+        assert_eq!(pages.resume_token(), None);
+
+        // The scan stops after classifying the first page and part of the second
+        for id in ["m1", "m3", "m2"] {
+            pages.complete(id);
+        }
+        let saved = pages.resume_token();
+        assert_eq!(saved.as_deref(), Some("page2"));
+
+        // A resumed scan lists from the saved page and skips what's already classified
+        let classified: HashSet<String> = ["m1", "m2", "m3"].map(String::from).into();
+        let (ids, _) = list_message_pages(&mock, "in:inbox", saved, &classified, None)
+            .await
+            .unwrap();
+        assert_eq!(ids, vec!["m4"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_message_pages_truncated_resumes_mid_page() {
+        let mut mock = MockTestGmailClient::new();
+        mock.expect_list_message_page()
+            .returning(|_, _, token| match token {
+                None => Ok((vec!["m1".into(), "m2".into()], Some("page2".into()))),
+                Some(_) => Ok((vec!["m3".into(), "m4".into()], None)),
+            });
+
+        let (ids, mut pages) =
+            list_message_pages(&mock, "in:inbox", None, &HashSet::new(), Some(3))
+                .await
+                .unwrap();
+        assert_eq!(ids, vec!["m1", "m2", "m3"]);
+        assert!(pages.truncated());
+        for id in &ids {
+            pages.complete(id);
+        }
+        // m4 was never listed, so its page is listed again
+        assert_eq!(pages.resume_token().as_deref(), Some("page2"));
+    }
+
+    #[tokio::test]
+    async fn test_send_messages_pauses_when_channel_is_full() {
+        let fetched = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = fetched.clone();
+        let mut mock = MockTestGmailClient::new();
+        mock.expect_get_message().returning(move |id| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(thread_message(id, "t1", "a@example.com"))
+        });
+
+        let ids: Vec<String> = (0..10).map(|i| format!("m{}", i)).collect();
+        let (tx, mut rx) = mpsc::channel(2);
+
+        let fetch = send_messages(&mock, ids, false, 1, tx);
+        let consume = async {
+            // Let the fetcher run until the channel fills up
+            tokio::task::yield_now().await;
+            // Two messages buffered plus one fetched and waiting to be sent
+            assert_eq!(fetched.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+            let mut received = Vec::new();
+            while let Some(msg) = rx.recv().await {
+                received.push(msg.id);
+            }
+            received
+        };
+
+        let (result, received) = tokio::join!(fetch, consume);
+        result.unwrap();
+        assert_eq!(received.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_send_messages_stops_when_receiver_dropped() {
+        let mut mock = MockTestGmailClient::new();
+        mock.expect_get_message()
+            .returning(|id| Ok(thread_message(id, "t1", "a@example.com")));
+
+        let ids: Vec<String> = (0..10).map(|i| format!("m{}", i)).collect();
+        let (tx, rx) = mpsc::channel(1);
+        drop(rx);
+
+        assert!(send_messages(&mock, ids, false, 1, tx).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_messages_returns_transient_failures() {
+        let mut mock = MockTestGmailClient::new();
+        mock.expect_get_message().returning(|id| match id {
+            "m1" => Err(GmailError::ServerError {
+                status: 503,
+                message: "Service unavailable".to_string(),
+            }),
+            _ => Ok(thread_message(id, "t1", "a@example.com")),
+        });
+
+        let ids: Vec<String> = (0..3).map(|i| format!("m{}", i)).collect();
+        let (tx, mut rx) = mpsc::channel(10);
+        let failed = send_messages(&mock, ids, false, 1, tx).await.unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, "m1");
+
+        let mut received = Vec::new();
+        while let Some(msg) = rx.recv().await {
+            received.push(msg.id);
+        }
+        assert_eq!(received, ["m0", "m2"]);
+
+        let mut mock = MockTestGmailClient::new();
+        mock.expect_get_message()
+            .returning(|_| Err(GmailError::AuthError("invalid token".to_string())));
+        let (tx, _rx) = mpsc::channel(10);
+        assert!(send_messages(&mock, vec!["m0".to_string()], false, 1, tx)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stream_messages_bounded() {
+        let mut mock = MockTestGmailClient::new();
+        mock.expect_list_message_ids()
+            .returning(|_| Ok((0..5).map(|i| format!("m{}", i)).collect()));
+        mock.expect_get_message().returning(|id| match id {
+            "m3" => Err(GmailError::ServerError {
+                status: 503,
+                message: "Service unavailable".to_string(),
+            }),
+            _ => Ok(thread_message(id, "t1", "a@example.com")),
+        });
+
+        let (messages, handle) =
+            std::sync::Arc::new(mock).stream_messages_bounded("in:inbox", 100, 2);
+        let (tx, mut rx) = mpsc::channel(10);
+        let skipped = forward_streamed_messages(messages, tx).await.unwrap();
+        handle.await.unwrap();
+        assert_eq!(skipped, 1);
+
+        let mut received = Vec::new();
+        while let Some(msg) = rx.recv().await {
+            received.push(msg.id);
+        }
+        received.sort();
+        assert_eq!(received, ["m0", "m1", "m2", "m4"]);
+    }
+
+    #[test]
+    fn test_deduplicate() {
+        let messages: Vec<MessageMetadata> = (0..100)
+            .map(|i| thread_message(&format!("m{}", i % 80), "t1", "a@example.com"))
+            .collect();
+
+        let (unique, removed) = deduplicate(messages);
+        assert_eq!(unique.len(), 80);
+        assert_eq!(removed, 20);
+        assert_eq!(unique[0].id, "m0");
+        assert_eq!(unique[79].id, "m79");
+    }
+
+    #[test]
+    fn test_apply_sender_exclusions() {
+        let messages = vec![
+            thread_message("m1", "t1", "me@example.com"),
+            thread_message("m2", "t2", "Deals@Shop.example"),
+            thread_message("m3", "t3", "news@shop.example.org"),
+            thread_message("m4", "t4", "friend@example.com"),
+        ];
+        let exclusions = vec!["ME@example.com".to_string(), "*@shop.example".to_string()];
+
+        let kept = apply_sender_exclusions(messages, &exclusions);
+        let ids: Vec<&str> = kept.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["m3", "m4"]);
+
+        assert!(is_sender_excluded(
+            "alerts@bank.example",
+            &["alerts@*".to_string()]
+        ));
+        assert!(is_sender_excluded(
+            "a@mail.b.example",
+            &["*@*.b.example".to_string()]
+        ));
+        assert!(!is_sender_excluded(
+            "a@b.example",
+            &["*@*.b.example".to_string()]
+        ));
+        assert!(!is_sender_excluded("a@b.example", &[]));
+    }
+
+    #[tokio::test]
+    async fn test_scan_output_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_path = dir.path().join("scan.json");
+        let csv_path = dir.path().join("scan.csv");
+
+        let output = ScanOutput::new(
+            "after:2024/01/01".to_string(),
+            vec![
+                thread_message("m1", "t1", "a@example.com"),
+                thread_message("m2", "t2", "b@example.com"),
+            ],
+        );
+        output.save_json(&json_path).await.unwrap();
+        output.save_csv(&csv_path).unwrap();
+
+        let loaded = ScanOutput::load(&json_path).await.unwrap();
+        assert_eq!(loaded.query, "after:2024/01/01");
+        assert_eq!(loaded.message_count, 2);
+        assert_eq!(loaded.messages[1].sender_email, "b@example.com");
+
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(csv.starts_with("id,thread_id,sender_email,"));
+        assert_eq!(csv.lines().count(), 3);
+        assert!(ScanOutput::load(&csv_path).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_metadata_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metadata-cache.json");
+
+        let mut cache = MetadataCache::load(&path, 2).await.unwrap();
+        assert!(cache.is_empty());
+        cache.insert(thread_message("m1", "t1", "a@example.com"));
+        cache.insert(thread_message("m2", "t2", "b@example.com"));
+        // Using m1 makes m2 the least recently used, so it is evicted
+        assert!(cache.get("m1").is_some());
+        cache.insert(thread_message("m3", "t3", "c@example.com"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("m2").is_none());
+        cache.save().await.unwrap();
+
+        let mut cache = MetadataCache::load(&path, DEFAULT_METADATA_CACHE_SIZE)
+            .await
+            .unwrap();
+        let (cached, missing) =
+            cache.split(vec!["m1".to_string(), "m2".to_string(), "m3".to_string()]);
+        let cached: Vec<_> = cached.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(cached, ["m1", "m3"]);
+        assert_eq!(missing, ["m2"]);
+
+        // Loading into a smaller cache keeps the most recently used messages
+        cache.save().await.unwrap();
+        let mut cache = MetadataCache::load(&path, 1).await.unwrap();
+        assert!(cache.get("m3").is_some());
+        assert!(cache.get("m1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_by_domains() {
+        let mut mock = MockTestGmailClient::new();
+        mock.expect_list_message_ids().returning(|query| {
+            assert!(query.contains(" after:"));
+            Ok(if query.starts_with("from:(*@a.example)") {
+                vec!["a1".to_string(), "a2".to_string()]
+            } else {
+                Vec::new()
+            })
+        });
+        mock.expect_fetch_messages_batch()
+            .times(1)
+            .returning(|ids| {
+                Ok(ids
+                    .iter()
+                    .map(|id| thread_message(id, "t1", "news@a.example"))
+                    .collect())
+            });
+
+        let client: Arc<dyn GmailClient> = Arc::new(mock);
+        let domains = vec![
+            "a.example".to_string(),
+            "b.example".to_string(),
+            "a.example".to_string(),
+        ];
+        let config = crate::config::ScanConfig {
+            max_concurrent_requests: 2,
+            ..Default::default()
+        };
+        let (by_domain, truncated) = fetch_by_domains(&domains, &config, &client).await.unwrap();
+        assert!(!truncated);
+        assert_eq!(by_domain.len(), 2);
+        assert_eq!(by_domain["a.example"].len(), 2);
+        assert!(by_domain["b.example"].is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_by_domains_respects_scan_filters() {
+        let mut mock = MockTestGmailClient::new();
+        mock.expect_list_message_ids().returning(|query| {
+            assert!(query.contains(" (in:inbox)"));
+            assert!(query.contains(" -in:spam"));
+            Ok(vec!["m1".to_string(), "m2".to_string(), "m3".to_string()])
+        });
+        mock.expect_fetch_messages_batch().returning(|ids| {
+            Ok(ids
+                .iter()
+                .map(|id| thread_message(id, "t1", "news@a.example"))
+                .collect())
+        });
+
+        let client: Arc<dyn GmailClient> = Arc::new(mock);
+        let domains = vec!["a.example".to_string(), "b.example".to_string()];
+        let config = crate::config::ScanConfig {
+            max_concurrent_requests: 1,
+            max_messages: Some(2),
+            include_labels: vec!["INBOX".to_string()],
+            exclude_labels: vec!["SPAM".to_string()],
+            ..Default::default()
+        };
+        let (by_domain, truncated) = fetch_by_domains(&domains, &config, &client).await.unwrap();
+        assert!(truncated);
+        let total: usize = by_domain.values().map(Vec::len).sum();
+        assert_eq!(total, 2);
+    }
+
+    #[tokio::test]
+    async fn test_message_id_stream() {
+        let mut mock = MockTestGmailClient::new();
+        mock.expect_list_message_ids()
+            .returning(|_| Ok(vec!["m1".to_string(), "m2".to_string()]));
+
+        let ids: Vec<String> = mock
+            .message_id_stream("after:2024/01/01", 100)
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(ids, ["m1", "m2"]);
+
+        assert!(mock.message_id_stream("in:inbox", 0).is_err());
+        assert!(mock.message_id_stream("in:inbox", 501).is_err());
+    }
+
+    #[test]
+    fn test_parse_headers_zero_copy() {
+        let raw: Vec<serde_json::Value> = serde_json::from_str(
+            r#"[
+                {"name": "From", "value": "Jane Smith <jane@example.com>"},
+                {"name": "Subject", "value": "Weekly digest"},
+                {"name": "To", "value": "me@example.com, you@test.org"},
+                {"name": "Date", "value": "Tue, 1 Jul 2025 10:00:00 +0000"},
+                {"name": "List-Unsubscribe", "value": "<mailto:u@example.com>"},
+                {"name": "X-Broken"}
+            ]"#,
+        )
+        .unwrap();
+
+        let headers = parse_headers_zero_copy(&raw);
+        assert_eq!(headers.len(), 5);
+        assert!(!headers.contains_key("X-Broken"));
+
+        // Values point into the JSON buffer rather than copies
+        let subject = headers["Subject"];
+        assert!(std::ptr::eq(subject, raw[1]["value"].as_str().unwrap()));
+
+        let msg = extract_message_metadata(
+            "m1".to_string(),
+            "t1".to_string(),
+            vec!["INBOX".to_string()],
+            &headers,
+        );
+        assert_eq!(msg.sender_email, "jane@example.com");
+        assert_eq!(msg.sender_domain, "example.com");
+        assert_eq!(msg.sender_name, "Jane Smith");
+        assert_eq!(msg.subject, "Weekly digest");
+        assert_eq!(msg.recipients.len(), 2);
+        assert_eq!(msg.date_received.to_rfc3339(), "2025-07-01T10:00:00+00:00");
+        assert!(msg.has_unsubscribe);
+    }
+
+    #[test]
+    fn test_parse_authentication_results() {
+        let header = "mx.google.com; dkim=pass header.i=@news.example.com header.s=s1; \
+                      SPF=Pass (google.com: domain of bounce@example.com designates 1.2.3.4 \
+                      as permitted sender) smtp.mailfrom=bounce@example.com; dmarc=pass";
+        assert_eq!(
+            parse_authentication_results(header),
+            (Some(true), Some(true))
+        );
+
+        let failed = "mx.google.com; spf=softfail smtp.mailfrom=x@spam.test; dkim=fail";
+        assert_eq!(
+            parse_authentication_results(failed),
+            (Some(false), Some(false))
+        );
+
+        // Comments don't count, and one passing signature is enough
+        let mixed = "mx.google.com; dkim=fail (spf=pass); dkim=pass header.i=@example.com";
+        assert_eq!(parse_authentication_results(mixed), (None, Some(true)));
+
+        assert_eq!(
+            parse_authentication_results("mx.google.com; none"),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn test_extract_list_id() {
+        let header = |name: &str, value: &str| MessagePartHeader {
+            name: Some(name.to_string()),
+            value: Some(value.to_string()),
+        };
+
+        let headers = vec![
+            header("From", "Rust Dev <dev@rust-lang.org>"),
+            header("List-Id", "Rust Development <Dev.Rust-Lang.org>"),
+        ];
+        assert_eq!(
+            extract_list_id(&headers),
+            Some("dev.rust-lang.org".to_string())
+        );
+
+        let bare = vec![header("List-ID", " announce.example.com ")];
+        assert_eq!(
+            extract_list_id(&bare),
+            Some("announce.example.com".to_string())
+        );
+
+        assert_eq!(extract_list_id(&[header("List-ID", "<>")]), None);
+        assert_eq!(extract_list_id(&[header("From", "a@b.com")]), None);
+    }
+
+    #[test]
+    fn test_extract_unsubscribe_links() {
+        let headers = vec![MessagePartHeader {
+            name: Some("List-Unsubscribe".to_string()),
+            value: Some(
+                "<mailto:leave@lists.example.com?subject=unsubscribe>, \
+                 <https://example.com/u?id=42>, <ftp://example.com/x>, broken"
+                    .to_string(),
+            ),
+        }];
+
+        assert_eq!(
+            extract_unsubscribe_links(&headers),
+            vec![
+                UnsubscribeLink {
+                    url: "mailto:leave@lists.example.com?subject=unsubscribe".to_string(),
+                    method: UnsubscribeMethod::Mailto,
+                },
+                UnsubscribeLink {
+                    url: "https://example.com/u?id=42".to_string(),
+                    method: UnsubscribeMethod::Http,
+                },
+            ]
+        );
+        assert!(extract_unsubscribe_links(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_collect_mime_types() {
+        let part = |mime_type: &str, parts: Option<Vec<MessagePart>>| MessagePart {
+            mime_type: Some(mime_type.to_string()),
+            parts,
+            ..Default::default()
+        };
+        let invite = part(
+            "multipart/mixed",
+            Some(vec![
+                part(
+                    "multipart/alternative",
+                    Some(vec![
+                        part("text/plain", None),
+                        part("text/html", None),
+                        part("text/calendar", None),
+                    ]),
+                ),
+                part("application/ICS", None),
+                part("text/plain", None),
+            ]),
+        );
+
+        assert_eq!(
+            collect_mime_types(&invite),
+            vec![
+                "multipart/mixed",
+                "multipart/alternative",
+                "text/plain",
+                "text/html",
+                "text/calendar",
+                "application/ics",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_bounce() {
+        assert!(is_bounce(
+            Some("multipart/report; report-type=delivery-status; boundary=\"x\""),
+            None,
+            "Returned mail"
+        ));
+        assert!(is_bounce(None, Some(" <> "), "Returned mail"));
+        assert!(is_bounce(
+            None,
+            None,
+            "Delivery Status Notification (Failure)"
+        ));
+        assert!(is_bounce(None, None, "Undeliverable: Quarterly report"));
+        assert!(is_bounce(
+            None,
+            None,
+            "Mail delivery failed to bob@example.com"
+        ));
+
+        assert!(!is_bounce(
+            Some("multipart/alternative"),
+            Some("<bounce@mail.example.com>"),
+            "Your order has shipped"
+        ));
+        assert!(!is_bounce(
+            None,
+            None,
+            "Re: Undeliverable: quarterly report"
+        ));
+    }
+
     #[test]
     fn test_checkpoint_update() {
         let mut checkpoint = ScanCheckpoint::new();
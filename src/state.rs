@@ -1,11 +1,141 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedRwLockWriteGuard, RwLock};
+
+use crate::circuit_breaker::CircuitStatus;
+use crate::config::StateFormat;
+use crate::error::{GmailError, Result};
+use crate::models::{
+    Classification, ClassificationReason, EmailCategory, MessageMetadata, UnsubscribeLink,
+};
+use crate::scanner::ScanCheckpoint;
+
+/// Current schema version of the serialized `ProcessingState`.
+///
+/// BREAKING_CHANGE: increment this and append a migration to `STATE_MIGRATIONS`
+/// whenever the state schema changes (fields added, renamed or retyped).
+pub const STATE_VERSION: u32 = 11;
+
+/// Forward migrations for raw state JSON, indexed by the version they upgrade from
+/// (`STATE_MIGRATIONS[0]` upgrades v0 to v1, and so on)
+type StateMigration = fn(&mut serde_json::Map<String, serde_json::Value>);
+
+static STATE_MIGRATIONS: &[StateMigration] = &[
+    // v0 -> v1: checkpoint tracking was added
+    |state| {
+        state
+            .entry("checkpoint_count")
+            .or_insert_with(|| serde_json::Value::from(0));
+    },
+    // v1 -> v2: per-message classification results are kept for export
+    |state| {
+        state
+            .entry("classifications")
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+    },
+    // v2 -> v3: runs can be tagged
+    |state| {
+        state
+            .entry("tags")
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+    },
+    // v3 -> v4: scans checkpoint their progress
+    |state| {
+        state
+            .entry("scan_checkpoint")
+            .or_insert(serde_json::Value::Null);
+    },
+    // v4 -> v5: failed fetches are queued for retry
+    |state| {
+        state
+            .entry("retry_queue")
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+        state
+            .entry("permanently_failed_ids")
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+    },
+    // v5 -> v6: circuit breaker state is kept for `status`
+    |state| {
+        state.entry("circuit").or_insert(serde_json::Value::Null);
+    },
+    // v6 -> v7: unsubscribe links are kept per sender for `unsubscribe`
+    |state| {
+        state
+            .entry("unsubscribe_links")
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+    },
+    // v7 -> v8: created labels are kept by name to detect conflicts when merging states
+    |state| {
+        state
+            .entry("label_ids_by_name")
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+    },
+    // v8 -> v9: scans can stop early at `scan.max_messages`
+    |state| {
+        state
+            .entry("scan_truncated")
+            .or_insert(serde_json::Value::Bool(false));
+    },
+    // v9 -> v10: time spent in each pipeline phase is recorded for the report
+    |state| {
+        state
+            .entry("phase_timings")
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+    },
+    // v10 -> v11: every label the run applied is kept for the report
+    |state| {
+        state
+            .entry("labels_applied")
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+    },
+];
+
+/// Phases timed by `run_pipeline`, in the order they run
+pub const PIPELINE_PHASES: [&str; 6] = ["Scan", "Classify", "Review", "Labels", "Filters", "Apply"];
 
-use crate::error::Result;
+/// Retries a failed fetch gets before its message is given up on
+pub const MAX_FETCH_RETRIES: u32 = 3;
+
+/// Delay before the first retry of a failed fetch; doubled after every failed retry
+const FETCH_RETRY_BASE_DELAY_SECS: i64 = 5;
+
+/// Magic bytes at the start of every zstd frame, used to auto-detect compressed state files
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Whether `bytes` starts with a MessagePack map marker (fixmap, map16 or map32).
+/// JSON state files always start with `{` or whitespace instead.
+fn is_msgpack_map(bytes: &[u8]) -> bool {
+    matches!(bytes.first(), Some(0x80..=0x8f | 0xde | 0xdf))
+}
+
+/// Marker at the start of encrypted state files, followed by the 96-bit nonce and ciphertext
+const ENCRYPTED_MARKER: &[u8] = b"ENCRYPTED\x00";
+
+/// Size of the AES-GCM nonce in bytes
+const NONCE_LEN: usize = 12;
+
+/// AES-256 key for state encryption, kept out of `Debug` output
+#[derive(Clone)]
+struct EncryptionKey([u8; 32]);
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EncryptionKey(<redacted>)")
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessingState {
+    /// Schema version, missing in files written before versioning was introduced
+    #[serde(default)]
+    pub version: u32,
     pub run_id: String,
     pub started_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -13,12 +143,240 @@ pub struct ProcessingState {
     pub messages_scanned: usize,
     pub messages_classified: usize,
     pub labels_created: Vec<String>,
+    /// IDs of the labels in `labels_created`, by label name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub label_ids_by_name: BTreeMap<String, String>,
+    /// Names of every label the run's filters apply, whether created by the
+    /// run or already present in the mailbox
+    #[serde(default)]
+    pub labels_applied: Vec<String>,
     pub filters_created: Vec<String>,
     pub messages_modified: usize,
     pub last_processed_message_id: Option<String>,
     pub failed_message_ids: Vec<String>,
     pub completed: bool,
     pub checkpoint_count: usize,
+    /// Free-form tags used to group and filter runs
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Classification results from the classify phase, kept for export
+    #[serde(default)]
+    pub classifications: Vec<SerializedClassification>,
+    /// Progress of an unfinished scan; `classifications` holds the messages
+    /// classified so far. Cleared once the scan completes.
+    #[serde(default)]
+    pub scan_checkpoint: Option<ScanCheckpoint>,
+    /// The scan stopped at `scan.max_messages`; `scan_checkpoint` is kept so
+    /// `run --resume` can continue it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub scan_truncated: bool,
+    /// Messages whose fetch failed with a transient error, waiting to be retried
+    #[serde(default)]
+    pub retry_queue: Vec<FailedFetch>,
+    /// Messages that still failed after `MAX_FETCH_RETRIES` retries
+    #[serde(default)]
+    pub permanently_failed_ids: Vec<String>,
+    /// Circuit breaker state when the state was last saved by a run
+    #[serde(default)]
+    pub circuit: Option<CircuitStatus>,
+    /// Unsubscribe links seen while scanning, by sender address
+    #[serde(default)]
+    pub unsubscribe_links: BTreeMap<String, Vec<UnsubscribeLink>>,
+    /// Seconds spent in each pipeline phase, by name (see [`PIPELINE_PHASES`])
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub phase_timings: HashMap<String, u64>,
+    /// Write this state zstd-compressed on every save (see `StateConfig::compress`)
+    #[serde(skip)]
+    pub compress: bool,
+    /// Serialization format used on every save (see `StateConfig::format`)
+    #[serde(skip)]
+    pub format: StateFormat,
+    /// Encrypt this state on every save (see `StateConfig::encryption_key_env`)
+    #[serde(skip)]
+    encryption_key: Option<EncryptionKey>,
+}
+
+/// A message fetch waiting in the retry queue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedFetch {
+    pub message_id: String,
+    /// Retries made so far
+    pub attempts: u32,
+    pub last_error: String,
+    pub next_retry_at: DateTime<Utc>,
+}
+
+impl FailedFetch {
+    pub fn new(message_id: String, error: &GmailError) -> Self {
+        Self {
+            message_id,
+            attempts: 0,
+            last_error: error.to_string(),
+            next_retry_at: Utc::now() + chrono::Duration::seconds(FETCH_RETRY_BASE_DELAY_SECS),
+        }
+    }
+
+    /// Record a failed retry and push the next one back, doubling the delay
+    pub fn record_failure(&mut self, error: &GmailError) {
+        self.attempts += 1;
+        self.last_error = error.to_string();
+        let delay = FETCH_RETRY_BASE_DELAY_SECS << self.attempts.min(16);
+        self.next_retry_at = Utc::now() + chrono::Duration::seconds(delay);
+    }
+
+    /// Whether every retry has been used up
+    pub fn exhausted(&self) -> bool {
+        self.attempts >= MAX_FETCH_RETRIES
+    }
+}
+
+/// Flattened classification of a single message, as stored in the state and exported to CSV
+///
+/// The CSV export only has the columns up to `confidence`; the remaining fields
+/// let [`SerializedClassification::restore`] rebuild the message exactly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializedClassification {
+    pub message_id: String,
+    pub thread_id: String,
+    pub sender_email: String,
+    pub sender_domain: String,
+    pub subject: String,
+    pub date_received: DateTime<Utc>,
+    pub category: EmailCategory,
+    pub suggested_label: String,
+    pub should_archive: bool,
+    pub confidence: f32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sender_name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipients: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub has_unsubscribe: bool,
+    #[serde(default)]
+    pub is_automated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spf_pass: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dkim_pass: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unsubscribe_links: Vec<UnsubscribeLink>,
+    #[serde(default)]
+    pub is_reply: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_submitted: Option<String>,
+    #[serde(default)]
+    pub is_bounce: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachment_types: Vec<String>,
+    #[serde(default)]
+    pub size_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<ClassificationReason>,
+}
+
+/// The CSV columns of a [`SerializedClassification`]
+#[derive(Serialize)]
+struct ClassificationRow<'a> {
+    message_id: &'a str,
+    thread_id: &'a str,
+    sender_email: &'a str,
+    sender_domain: &'a str,
+    subject: &'a str,
+    date_received: DateTime<Utc>,
+    category: &'a EmailCategory,
+    suggested_label: &'a str,
+    should_archive: bool,
+    confidence: f32,
+}
+
+impl SerializedClassification {
+    pub fn new(message: &MessageMetadata, classification: &Classification) -> Self {
+        Self {
+            message_id: message.id.clone(),
+            thread_id: message.thread_id.clone(),
+            sender_email: message.sender_email.clone(),
+            sender_domain: message.sender_domain.clone(),
+            subject: message.subject.clone(),
+            date_received: message.date_received,
+            category: classification.category.clone(),
+            suggested_label: classification.suggested_label.clone(),
+            should_archive: classification.should_archive,
+            confidence: classification.confidence,
+            sender_name: message.sender_name.clone(),
+            recipients: message.recipients.clone(),
+            labels: message.labels.clone(),
+            has_unsubscribe: message.has_unsubscribe,
+            is_automated: message.is_automated,
+            spf_pass: message.spf_pass,
+            dkim_pass: message.dkim_pass,
+            list_id: message.list_id.clone(),
+            unsubscribe_links: message.unsubscribe_links.clone(),
+            is_reply: message.is_reply,
+            auto_submitted: message.auto_submitted.clone(),
+            is_bounce: message.is_bounce,
+            attachment_types: message.attachment_types.clone(),
+            size_bytes: message.size_bytes,
+            reasoning: classification.reasoning.clone(),
+            reasons: classification.reasons.clone(),
+        }
+    }
+
+    /// Rebuild the message and classification, e.g. to resume an interrupted scan
+    pub fn restore(&self) -> (MessageMetadata, Classification) {
+        let message = MessageMetadata {
+            id: self.message_id.clone(),
+            thread_id: self.thread_id.clone(),
+            sender_email: self.sender_email.clone(),
+            sender_domain: self.sender_domain.clone(),
+            sender_name: self.sender_name.clone(),
+            subject: self.subject.clone(),
+            recipients: self.recipients.clone(),
+            date_received: self.date_received,
+            labels: self.labels.clone(),
+            has_unsubscribe: self.has_unsubscribe,
+            is_automated: self.is_automated,
+            spf_pass: self.spf_pass,
+            dkim_pass: self.dkim_pass,
+            list_id: self.list_id.clone(),
+            unsubscribe_links: self.unsubscribe_links.clone(),
+            is_reply: self.is_reply,
+            auto_submitted: self.auto_submitted.clone(),
+            is_bounce: self.is_bounce,
+            attachment_types: self.attachment_types.clone(),
+            size_bytes: self.size_bytes,
+        };
+        let classification = Classification {
+            message_id: self.message_id.clone(),
+            category: self.category.clone(),
+            confidence: self.confidence,
+            suggested_label: self.suggested_label.clone(),
+            should_archive: self.should_archive,
+            reasoning: self.reasoning.clone(),
+            reasons: self.reasons.clone(),
+        };
+        (message, classification)
+    }
+
+    fn csv_row(&self) -> ClassificationRow<'_> {
+        ClassificationRow {
+            message_id: &self.message_id,
+            thread_id: &self.thread_id,
+            sender_email: &self.sender_email,
+            sender_domain: &self.sender_domain,
+            subject: &self.subject,
+            date_received: self.date_received,
+            category: &self.category,
+            suggested_label: &self.suggested_label,
+            should_archive: self.should_archive,
+            confidence: self.confidence,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +392,7 @@ pub enum ProcessingPhase {
 impl ProcessingState {
     pub fn new() -> Self {
         Self {
+            version: STATE_VERSION,
             run_id: uuid::Uuid::new_v4().to_string(),
             started_at: Utc::now(),
             updated_at: Utc::now(),
@@ -41,37 +400,309 @@ impl ProcessingState {
             messages_scanned: 0,
             messages_classified: 0,
             labels_created: Vec::new(),
+            label_ids_by_name: BTreeMap::new(),
+            labels_applied: Vec::new(),
             filters_created: Vec::new(),
             messages_modified: 0,
             last_processed_message_id: None,
             failed_message_ids: Vec::new(),
             completed: false,
             checkpoint_count: 0,
+            tags: Vec::new(),
+            classifications: Vec::new(),
+            scan_checkpoint: None,
+            scan_truncated: false,
+            retry_queue: Vec::new(),
+            permanently_failed_ids: Vec::new(),
+            circuit: None,
+            unsubscribe_links: BTreeMap::new(),
+            phase_timings: HashMap::new(),
+            compress: false,
+            format: StateFormat::Json,
+            encryption_key: None,
         }
     }
 
+    /// Remember the unsubscribe links of a scanned message under its sender
+    pub fn record_unsubscribe_links(&mut self, message: &MessageMetadata) {
+        if message.unsubscribe_links.is_empty() {
+            return;
+        }
+        let links = self
+            .unsubscribe_links
+            .entry(message.sender_email.clone())
+            .or_default();
+        for link in &message.unsubscribe_links {
+            if !links.iter().any(|known| known.url == link.url) {
+                links.push(link.clone());
+            }
+        }
+    }
+
+    /// Add `elapsed` to the time spent in `phase`; resumed runs add to the
+    /// time recorded before the interruption
+    pub fn record_phase_timing(&mut self, phase: &str, elapsed: Duration) {
+        *self.phase_timings.entry(phase.to_string()).or_default() += elapsed.as_secs();
+    }
+
+    /// Phase timings in pipeline order, followed by any unknown phases by name
+    pub fn phase_breakdown(&self) -> Vec<(String, u64)> {
+        let mut breakdown: Vec<(String, u64)> = PIPELINE_PHASES
+            .iter()
+            .filter_map(|phase| {
+                self.phase_timings
+                    .get(*phase)
+                    .map(|secs| (phase.to_string(), *secs))
+            })
+            .collect();
+        let mut others: Vec<(String, u64)> = self
+            .phase_timings
+            .iter()
+            .filter(|(phase, _)| !PIPELINE_PHASES.contains(&phase.as_str()))
+            .map(|(phase, secs)| (phase.clone(), *secs))
+            .collect();
+        others.sort();
+        breakdown.extend(others);
+        breakdown
+    }
+
+    /// Replace the run's tags, dropping duplicates while keeping their order
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        let mut seen = HashSet::new();
+        self.tags = tags
+            .into_iter()
+            .filter(|tag| seen.insert(tag.clone()))
+            .collect();
+    }
+
+    /// Enable or disable zstd compression for subsequent saves
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Serialize subsequent saves in the given format
+    pub fn with_format(mut self, format: StateFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Encrypt subsequent saves with the given AES-256 key (or stop encrypting with `None`)
+    pub fn with_encryption_key(mut self, key: Option<[u8; 32]>) -> Self {
+        self.encryption_key = key.map(EncryptionKey);
+        self
+    }
+
     /// Save state to disk
     pub async fn save(&self, path: &Path) -> Result<()> {
-        // Create parent directory if it doesn't exist
-        if let Some(parent) = path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+        write_state_file(path, &self.encode()?).await?;
+        tracing::debug!("Saved processing state to {:?}", path);
+        Ok(())
+    }
+
+    /// Encode the state as `save` writes it, honouring encryption, compression and format
+    fn encode(&self) -> Result<Vec<u8>> {
+        if let Some(key) = &self.encryption_key {
+            return self.encode_encrypted(&key.0);
         }
+        if self.compress {
+            return self.encode_compressed();
+        }
+        match self.format {
+            StateFormat::Json => Ok(serde_json::to_vec_pretty(self)?),
+            StateFormat::MessagePack => self.encode_msgpack(),
+        }
+    }
 
-        let json = serde_json::to_string_pretty(self)?;
-        tokio::fs::write(path, json).await?;
-        tracing::debug!("Saved processing state to {:?}", path);
+    /// Save state to disk as uncompressed MessagePack
+    pub async fn save_msgpack(&self, path: &Path) -> Result<()> {
+        write_state_file(path, &self.encode_msgpack()?).await?;
+        tracing::debug!("Saved MessagePack processing state to {:?}", path);
+        Ok(())
+    }
+
+    /// Load a MessagePack state file written by `save_msgpack`
+    pub async fn load_msgpack(path: &Path) -> Result<Self> {
+        let bytes = tokio::fs::read(path).await?;
+        Self::decode_msgpack(&bytes)
+    }
+
+    /// Encode as MessagePack with field names, so migrations and `#[serde(default)]` keep working
+    fn encode_msgpack(&self) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(self).map_err(|e| {
+            GmailError::StateError(format!("Failed to encode state as MessagePack: {}", e))
+        })
+    }
+
+    fn decode_msgpack(bytes: &[u8]) -> Result<Self> {
+        let raw: serde_json::Value = rmp_serde::from_slice(bytes)
+            .map_err(|e| GmailError::StateError(format!("Invalid MessagePack state: {}", e)))?;
+        let mut state = Self::migrate(raw)?;
+        state.format = StateFormat::MessagePack;
+        Ok(state)
+    }
+
+    /// Save state to disk zstd-compressed (compact JSON or MessagePack, per `format`)
+    pub async fn save_compressed(&self, path: &Path) -> Result<()> {
+        write_state_file(path, &self.encode_compressed()?).await?;
+        tracing::debug!("Saved compressed processing state to {:?}", path);
+        Ok(())
+    }
+
+    fn encode_compressed(&self) -> Result<Vec<u8>> {
+        let mut encoder = zstd::stream::Encoder::new(Vec::new(), 0)?;
+        match self.format {
+            StateFormat::Json => serde_json::to_writer(&mut encoder, self)?,
+            StateFormat::MessagePack => {
+                std::io::Write::write_all(&mut encoder, &self.encode_msgpack()?)?
+            }
+        }
+        Ok(encoder.finish()?)
+    }
+
+    /// Save state to disk encrypted with AES-256-GCM.
+    ///
+    /// The file layout is `ENCRYPTED\0 || nonce (12 bytes) || ciphertext`. The
+    /// plaintext is zstd-compressed first when compression is enabled.
+    pub async fn save_encrypted(&self, path: &Path, key: &[u8; 32]) -> Result<()> {
+        write_state_file(path, &self.encode_encrypted(key)?).await?;
+        tracing::debug!("Saved encrypted processing state to {:?}", path);
         Ok(())
     }
 
-    /// Load state from disk
+    fn encode_encrypted(&self, key: &[u8; 32]) -> Result<Vec<u8>> {
+        let plaintext = if self.compress {
+            self.encode_compressed()?
+        } else {
+            match self.format {
+                StateFormat::Json => serde_json::to_vec(self)?,
+                StateFormat::MessagePack => self.encode_msgpack()?,
+            }
+        };
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|_| GmailError::StateError("Failed to encrypt state".to_string()))?;
+
+        let mut bytes = Vec::with_capacity(ENCRYPTED_MARKER.len() + NONCE_LEN + ciphertext.len());
+        bytes.extend_from_slice(ENCRYPTED_MARKER);
+        bytes.extend_from_slice(&nonce);
+        bytes.extend_from_slice(&ciphertext);
+        Ok(bytes)
+    }
+
+    /// Load an encrypted state file written by `save_encrypted`
+    pub async fn load_encrypted(path: &Path, key: &[u8; 32]) -> Result<Self> {
+        let bytes = tokio::fs::read(path).await?;
+        Self::decode_encrypted(&bytes, key)
+    }
+
+    fn decode_encrypted(bytes: &[u8], key: &[u8; 32]) -> Result<Self> {
+        let payload = bytes
+            .strip_prefix(ENCRYPTED_MARKER)
+            .filter(|p| p.len() > NONCE_LEN)
+            .ok_or_else(|| GmailError::StateError("State file is not encrypted".to_string()))?;
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                GmailError::StateError(
+                    "Decryption failed: wrong key or corrupted state".to_string(),
+                )
+            })?;
+
+        let mut state = Self::decode(&plaintext)?;
+        state.encryption_key = Some(EncryptionKey(*key));
+        Ok(state)
+    }
+
+    /// Decode an unencrypted state payload: JSON or MessagePack, optionally zstd-compressed
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.starts_with(&ZSTD_MAGIC) {
+            Self::decode_compressed(bytes)
+        } else if is_msgpack_map(bytes) {
+            Self::decode_msgpack(bytes)
+        } else {
+            Self::migrate(serde_json::from_slice(bytes)?)
+        }
+    }
+
+    /// Load a zstd-compressed state file
+    pub async fn load_compressed(path: &Path) -> Result<Self> {
+        let bytes = tokio::fs::read(path).await?;
+        Self::decode_compressed(&bytes)
+    }
+
+    fn decode_compressed(bytes: &[u8]) -> Result<Self> {
+        let decompressed = zstd::stream::decode_all(bytes)?;
+        let mut state = if is_msgpack_map(&decompressed) {
+            Self::decode_msgpack(&decompressed)?
+        } else {
+            Self::migrate(serde_json::from_slice(&decompressed)?)?
+        };
+        state.compress = true;
+        Ok(state)
+    }
+
+    /// Upgrade raw state JSON from any older schema version and deserialize it
+    pub fn migrate(raw: serde_json::Value) -> Result<Self> {
+        let serde_json::Value::Object(mut fields) = raw else {
+            return Err(GmailError::StateError(
+                "State file is not a JSON object".to_string(),
+            ));
+        };
+
+        let version = fields.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+
+        if version > STATE_VERSION {
+            return Err(GmailError::StateError(format!(
+                "State file version {} is newer than supported version {}",
+                version, STATE_VERSION
+            )));
+        }
+
+        for (from, migration) in STATE_MIGRATIONS.iter().enumerate().skip(version as usize) {
+            migration(&mut fields);
+            tracing::info!("Migrated state file from v{} to v{}", from, from + 1);
+        }
+
+        fields.insert(
+            "version".to_string(),
+            serde_json::Value::from(STATE_VERSION),
+        );
+
+        Ok(serde_json::from_value(serde_json::Value::Object(fields))?)
+    }
+
+    /// Load state from disk, auto-detecting JSON, MessagePack or zstd-compressed files
     pub async fn load(path: &Path) -> Result<Self> {
+        Self::load_with_key(path, None).await
+    }
+
+    /// Load state from disk, auto-detecting plain, compressed or encrypted files.
+    ///
+    /// Encrypted files require `key`.
+    pub async fn load_with_key(path: &Path, key: Option<&[u8; 32]>) -> Result<Self> {
         if !path.exists() {
             tracing::info!("No existing state file found, starting fresh");
             return Ok(Self::new());
         }
 
-        let json = tokio::fs::read_to_string(path).await?;
-        let state: Self = serde_json::from_str(&json)?;
+        let bytes = tokio::fs::read(path).await?;
+        let state = if bytes.starts_with(ENCRYPTED_MARKER) {
+            let key = key.ok_or_else(|| {
+                GmailError::StateError(
+                    "State file is encrypted but no encryption key is configured".to_string(),
+                )
+            })?;
+            Self::decode_encrypted(&bytes, key)?
+        } else {
+            Self::decode(&bytes)?
+        };
 
         tracing::info!(
             "Loaded processing state: run_id={}, phase={:?}, messages_scanned={}",
@@ -85,9 +716,14 @@ impl ProcessingState {
 
     /// Save state as a checkpoint (every 100 messages)
     pub async fn checkpoint(&mut self, path: &Path) -> Result<()> {
+        self.record_checkpoint();
+        self.save(path).await
+    }
+
+    /// Count a checkpoint without saving, for states written by a [`StateManager`]
+    pub fn record_checkpoint(&mut self) {
         self.updated_at = Utc::now();
         self.checkpoint_count += 1;
-        self.save(path).await?;
         tracing::info!(
             "Checkpoint #{}: phase={:?}, scanned={}, classified={}",
             self.checkpoint_count,
@@ -95,7 +731,6 @@ impl ProcessingState {
             self.messages_scanned,
             self.messages_classified
         );
-        Ok(())
     }
 
     /// Check if we should create a checkpoint (every 100 messages)
@@ -135,6 +770,10 @@ impl ProcessingState {
 
     /// Check if the run can be resumed
     pub fn can_resume(&self) -> bool {
+        // A truncated scan can be continued even after the rest of the run finished
+        if self.scan_truncated {
+            return true;
+        }
         !self.completed
             && matches!(
                 self.phase,
@@ -163,6 +802,269 @@ impl ProcessingState {
             _ => 0.0,
         }
     }
+
+    /// Load every per-run snapshot (`state-*.json`) in `dir`, newest first
+    pub async fn load_all(dir: &Path) -> Result<Vec<Self>> {
+        Self::load_all_with_key(dir, None).await
+    }
+
+    /// Load every per-run snapshot in `dir`, decrypting with `key` where needed.
+    ///
+    /// Files that fail to load are skipped with a warning.
+    pub async fn load_all_with_key(dir: &Path, key: Option<&[u8; 32]>) -> Result<Vec<Self>> {
+        let mut states = Vec::new();
+        if !dir.exists() {
+            return Ok(states);
+        }
+
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let is_run_state = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with("state-") && n.ends_with(".json"))
+                .unwrap_or(false);
+            if !is_run_state {
+                continue;
+            }
+
+            match Self::load_with_key(&path, key).await {
+                Ok(state) => states.push(state),
+                Err(e) => tracing::warn!("Skipping unreadable state file {:?}: {}", path, e),
+            }
+        }
+
+        states.sort_by_key(|s| std::cmp::Reverse(s.started_at));
+        Ok(states)
+    }
+
+    /// Export the stored classifications as CSV, returning the number of rows written
+    pub fn export_classifications_csv(&self, path: &Path) -> Result<usize> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut writer = csv::Writer::from_path(path).map_err(std::io::Error::from)?;
+        for classification in &self.classifications {
+            writer
+                .serialize(classification.csv_row())
+                .map_err(std::io::Error::from)?;
+        }
+        writer.flush()?;
+
+        Ok(self.classifications.len())
+    }
+
+    /// Export the full state as pretty-printed JSON
+    pub fn export_json(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// Path of the per-run snapshot kept next to the main state file
+    /// (e.g. `.gmail-automation/state-<run_id>.json`)
+    pub fn run_state_path(state_file: &Path, run_id: &str) -> PathBuf {
+        state_file.with_file_name(format!("state-{}.json", run_id))
+    }
+
+    /// Wall-clock duration of the run in seconds
+    pub fn duration_secs(&self) -> i64 {
+        (self.updated_at - self.started_at).num_seconds()
+    }
+
+    /// Compare this state against an earlier run (`other`)
+    pub fn diff(&self, other: &ProcessingState) -> StateDiff {
+        fn difference(a: &[String], b: &[String]) -> Vec<String> {
+            let b: HashSet<&String> = b.iter().collect();
+            let mut items: Vec<String> = a.iter().filter(|x| !b.contains(x)).cloned().collect();
+            items.sort();
+            items.dedup();
+            items
+        }
+
+        StateDiff {
+            new_filters: difference(&self.filters_created, &other.filters_created),
+            removed_filters: difference(&other.filters_created, &self.filters_created),
+            new_labels: difference(&self.labels_created, &other.labels_created),
+            removed_labels: difference(&other.labels_created, &self.labels_created),
+            messages_delta: self.messages_scanned as i64 - other.messages_scanned as i64,
+            duration_delta_secs: self.duration_secs() - other.duration_secs(),
+        }
+    }
+
+    /// Label names both states created, but under different label IDs
+    pub fn label_conflicts(&self, other: &ProcessingState) -> Vec<LabelConflict> {
+        self.label_ids_by_name
+            .iter()
+            .filter_map(|(name, id_a)| {
+                let id_b = other.label_ids_by_name.get(name)?;
+                (id_a != id_b).then(|| LabelConflict {
+                    name: name.clone(),
+                    id_a: id_a.clone(),
+                    id_b: id_b.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Combine two partial runs, e.g. over disjoint scan periods, into one state
+    ///
+    /// Created labels and filters, failures and tags are unioned; message counts
+    /// and phase timings are summed. Progress fields (phase, last processed message, circuit) come from the
+    /// more recently updated state. Check [`ProcessingState::label_conflicts`] first:
+    /// for a conflicting label name the ID from `self` is kept.
+    pub fn merge(mut self, other: ProcessingState) -> ProcessingState {
+        fn union(into: &mut Vec<String>, from: Vec<String>) {
+            for item in from {
+                if !into.contains(&item) {
+                    into.push(item);
+                }
+            }
+        }
+
+        let other_is_newer = other.updated_at > self.updated_at;
+        self.run_id = format!("{}+{}", self.run_id, other.run_id);
+        self.version = STATE_VERSION;
+        self.started_at = self.started_at.min(other.started_at);
+        self.updated_at = self.updated_at.max(other.updated_at);
+        self.messages_scanned += other.messages_scanned;
+        self.messages_classified += other.messages_classified;
+        self.messages_modified += other.messages_modified;
+        self.checkpoint_count += other.checkpoint_count;
+        self.completed = self.completed && other.completed;
+
+        union(&mut self.labels_created, other.labels_created);
+        for (name, id) in other.label_ids_by_name {
+            self.label_ids_by_name.entry(name).or_insert(id);
+        }
+        union(&mut self.labels_applied, other.labels_applied);
+        union(&mut self.filters_created, other.filters_created);
+        union(&mut self.failed_message_ids, other.failed_message_ids);
+        union(
+            &mut self.permanently_failed_ids,
+            other.permanently_failed_ids,
+        );
+        union(&mut self.tags, other.tags);
+        for (phase, secs) in other.phase_timings {
+            *self.phase_timings.entry(phase).or_default() += secs;
+        }
+
+        let classified: HashSet<String> = self
+            .classifications
+            .iter()
+            .map(|c| c.message_id.clone())
+            .collect();
+        self.classifications.extend(
+            other
+                .classifications
+                .into_iter()
+                .filter(|c| !classified.contains(&c.message_id)),
+        );
+        for failed in other.retry_queue {
+            if !self
+                .retry_queue
+                .iter()
+                .any(|f| f.message_id == failed.message_id)
+            {
+                self.retry_queue.push(failed);
+            }
+        }
+        for (sender, links) in other.unsubscribe_links {
+            let known = self.unsubscribe_links.entry(sender).or_default();
+            for link in links {
+                if !known.iter().any(|k| k.url == link.url) {
+                    known.push(link);
+                }
+            }
+        }
+
+        if other_is_newer {
+            self.phase = other.phase;
+            self.last_processed_message_id = other.last_processed_message_id;
+            self.scan_checkpoint = other.scan_checkpoint;
+            self.scan_truncated = other.scan_truncated;
+            self.circuit = other.circuit;
+        }
+        self
+    }
+}
+
+/// A label name that two states recorded with different label IDs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelConflict {
+    pub name: String,
+    pub id_a: String,
+    pub id_b: String,
+}
+
+/// Differences between two pipeline runs, from the older run to the newer one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateDiff {
+    pub new_filters: Vec<String>,
+    pub removed_filters: Vec<String>,
+    pub new_labels: Vec<String>,
+    pub removed_labels: Vec<String>,
+    pub messages_delta: i64,
+    pub duration_delta_secs: i64,
+}
+
+impl StateDiff {
+    /// Check whether any filters or labels changed between the runs
+    pub fn is_empty(&self) -> bool {
+        self.new_filters.is_empty()
+            && self.removed_filters.is_empty()
+            && self.new_labels.is_empty()
+            && self.removed_labels.is_empty()
+    }
+
+    /// Render the diff as a markdown section with `+`/`-` lines
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        md.push_str("## Changes from Previous Run\n\n");
+        md.push_str(&format!(
+            "- **Messages scanned:** {:+}\n",
+            self.messages_delta
+        ));
+        md.push_str(&format!(
+            "- **Duration:** {:+} seconds\n\n",
+            self.duration_delta_secs
+        ));
+
+        if self.is_empty() {
+            md.push_str("_No filter or label changes._\n\n");
+            return md;
+        }
+
+        if !self.new_filters.is_empty() || !self.removed_filters.is_empty() {
+            md.push_str("### Filters\n\n```diff\n");
+            for id in &self.new_filters {
+                md.push_str(&format!("+ {}\n", id));
+            }
+            for id in &self.removed_filters {
+                md.push_str(&format!("- {}\n", id));
+            }
+            md.push_str("```\n\n");
+        }
+
+        if !self.new_labels.is_empty() || !self.removed_labels.is_empty() {
+            md.push_str("### Labels\n\n```diff\n");
+            for id in &self.new_labels {
+                md.push_str(&format!("+ {}\n", id));
+            }
+            for id in &self.removed_labels {
+                md.push_str(&format!("- {}\n", id));
+            }
+            md.push_str("```\n\n");
+        }
+
+        md
+    }
 }
 
 impl Default for ProcessingState {
@@ -171,6 +1073,201 @@ impl Default for ProcessingState {
     }
 }
 
+/// Create the parent directory of `path` and write a state file there
+async fn write_state_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, bytes).await?;
+    Ok(())
+}
+
+/// Owner of the processing state, which a background task writes to disk
+///
+/// The pipeline holds the state through a [`StateGuard`] and calls
+/// [`StateGuard::publish`] at each checkpoint; the writer encodes the state
+/// while the guard is released and saves it every interval when it changed, so
+/// checkpoints never wait for disk I/O. `flush` saves it right away, and a failed
+/// background save is returned by the next `update` or `publish`.
+#[derive(Clone)]
+pub struct StateManager {
+    shared: Arc<SharedState>,
+}
+
+struct SharedState {
+    state: Arc<RwLock<ProcessingState>>,
+    path: PathBuf,
+    /// Whether `state` changed since it was last encoded for writing
+    dirty: AtomicBool,
+    /// Generation of the last encoded state
+    encoded: AtomicU64,
+    /// Generation of the last state written, so an older snapshot never
+    /// overwrites a newer one; held from encoding a snapshot until it is written
+    written: tokio::sync::Mutex<u64>,
+    /// Failure of the background writer not yet returned to the pipeline
+    write_error: std::sync::Mutex<Option<GmailError>>,
+}
+
+impl StateManager {
+    /// Start writing `state` to `path` every `interval` while it has unsaved
+    /// changes. The writer stops once every clone of the manager is dropped.
+    pub fn spawn(state: ProcessingState, path: PathBuf, interval: Duration) -> Self {
+        let manager = Self {
+            shared: Arc::new(SharedState {
+                state: Arc::new(RwLock::new(state)),
+                path,
+                dirty: AtomicBool::new(false),
+                encoded: AtomicU64::new(0),
+                written: tokio::sync::Mutex::new(0),
+                write_error: std::sync::Mutex::new(None),
+            }),
+        };
+
+        let shared = Arc::downgrade(&manager.shared);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticks.tick().await;
+                let Some(shared) = shared.upgrade() else {
+                    break;
+                };
+                if let Err(e) = (StateManager {
+                    shared: Arc::clone(&shared),
+                })
+                .flush()
+                .await
+                {
+                    tracing::warn!("Failed to save processing state: {}", e);
+                    *shared.write_error.lock().unwrap() = Some(e);
+                }
+            }
+        });
+
+        manager
+    }
+
+    /// Take the state for the pipeline. The writer and `flush` wait until it is
+    /// published or the guard is dropped.
+    pub async fn lock(&self) -> StateGuard {
+        StateGuard {
+            state: Some(Arc::clone(&self.shared.state).write_owned().await),
+            manager: self.clone(),
+        }
+    }
+
+    /// Change the state in memory; it is written by the next background save
+    ///
+    /// Fails if the last background save failed.
+    pub async fn update(&self, f: impl FnOnce(&mut ProcessingState)) -> Result<()> {
+        f(&mut *self.shared.state.write().await);
+        self.shared.dirty.store(true, Ordering::Release);
+        self.take_write_error()
+    }
+
+    /// Return the background writer's failure since this was last called
+    fn take_write_error(&self) -> Result<()> {
+        match self.shared.write_error.lock().unwrap().take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Write any unsaved changes now
+    pub async fn flush(&self) -> Result<()> {
+        let state = self.shared.state.read().await;
+        // Taken before encoding, so this waits for a save already in progress
+        let written = self.shared.written.lock().await;
+        let snapshot = self.encode(&state)?;
+        drop(state);
+        self.write(written, snapshot).await
+    }
+
+    /// Encode `state` if it changed since it was last encoded; the caller holds its lock
+    fn encode(&self, state: &ProcessingState) -> Result<Option<(u64, Vec<u8>)>> {
+        if !self.shared.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(None);
+        }
+        match state.encode() {
+            Ok(bytes) => {
+                let generation = self.shared.encoded.fetch_add(1, Ordering::AcqRel) + 1;
+                Ok(Some((generation, bytes)))
+            }
+            Err(e) => {
+                self.shared.dirty.store(true, Ordering::Release);
+                Err(e)
+            }
+        }
+    }
+
+    /// Write `snapshot`, holding `written` from before it was encoded
+    async fn write(
+        &self,
+        mut written: tokio::sync::MutexGuard<'_, u64>,
+        snapshot: Option<(u64, Vec<u8>)>,
+    ) -> Result<()> {
+        let Some((generation, bytes)) = snapshot else {
+            return Ok(());
+        };
+        if *written >= generation {
+            return Ok(());
+        }
+        if let Err(e) = write_state_file(&self.shared.path, &bytes).await {
+            self.shared.dirty.store(true, Ordering::Release);
+            return Err(e);
+        }
+        *written = generation;
+        tracing::debug!("Saved processing state to {:?}", self.shared.path);
+        Ok(())
+    }
+}
+
+/// Exclusive access to the state owned by a [`StateManager`]
+pub struct StateGuard {
+    state: Option<OwnedRwLockWriteGuard<ProcessingState>>,
+    manager: StateManager,
+}
+
+impl StateGuard {
+    /// Mark the state changed and briefly release it, so the background writer
+    /// (or a shutdown `flush`) can encode it
+    ///
+    /// Fails if the last background save failed.
+    pub async fn publish(&mut self) -> Result<()> {
+        self.manager.shared.dirty.store(true, Ordering::Release);
+        self.state = None;
+        self.state = Some(Arc::clone(&self.manager.shared.state).write_owned().await);
+        self.manager.take_write_error()
+    }
+
+    /// Write the state now, without releasing it
+    pub async fn flush(&mut self) -> Result<()> {
+        self.manager.shared.dirty.store(true, Ordering::Release);
+        let written = self.manager.shared.written.lock().await;
+        let state: &ProcessingState = self;
+        let snapshot = self.manager.encode(state)?;
+        self.manager.write(written, snapshot).await
+    }
+}
+
+impl std::ops::Deref for StateGuard {
+    type Target = ProcessingState;
+
+    fn deref(&self) -> &ProcessingState {
+        self.state
+            .as_ref()
+            .expect("state is held between publishes")
+    }
+}
+
+impl std::ops::DerefMut for StateGuard {
+    fn deref_mut(&mut self) -> &mut ProcessingState {
+        self.state
+            .as_mut()
+            .expect("state is held between publishes")
+    }
+}
+
 /// Rollback log for tracking changes that can be undone
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RollbackLog {
@@ -271,6 +1368,7 @@ mod tests {
     #[tokio::test]
     async fn test_processing_state_new() {
         let state = ProcessingState::new();
+        assert_eq!(state.version, STATE_VERSION);
         assert!(!state.run_id.is_empty());
         assert_eq!(state.messages_scanned, 0);
         assert_eq!(state.messages_classified, 0);
@@ -290,6 +1388,11 @@ mod tests {
         state.labels_created.push("label1".to_string());
         state.filters_created.push("filter1".to_string());
         state.last_processed_message_id = Some("msg123".to_string());
+        state.set_tags(vec![
+            "pre-cleanup".to_string(),
+            "dry-test".to_string(),
+            "pre-cleanup".to_string(),
+        ]);
 
         // Save
         state.save(&state_path).await.unwrap();
@@ -306,6 +1409,7 @@ mod tests {
             state.last_processed_message_id,
             loaded_state.last_processed_message_id
         );
+        assert_eq!(loaded_state.tags, vec!["pre-cleanup", "dry-test"]);
     }
 
     #[tokio::test]
@@ -318,6 +1422,566 @@ mod tests {
         assert!(!loaded_state.completed);
     }
 
+    #[test]
+    fn test_processing_state_migrate_v0() {
+        // Pre-versioning state file without checkpoint_count
+        let raw = serde_json::json!({
+            "run_id": "old-run",
+            "started_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "phase": "Classifying",
+            "messages_scanned": 10,
+            "messages_classified": 5,
+            "labels_created": [],
+            "filters_created": [],
+            "messages_modified": 0,
+            "last_processed_message_id": null,
+            "failed_message_ids": [],
+            "completed": false
+        });
+
+        let state = ProcessingState::migrate(raw).unwrap();
+        assert_eq!(state.version, STATE_VERSION);
+        assert_eq!(state.run_id, "old-run");
+        assert_eq!(state.checkpoint_count, 0);
+        assert!(state.unsubscribe_links.is_empty());
+    }
+
+    #[test]
+    fn test_processing_state_migrate_rejects_newer_version() {
+        let mut raw = serde_json::to_value(ProcessingState::new()).unwrap();
+        raw["version"] = serde_json::Value::from(STATE_VERSION + 1);
+
+        let result = ProcessingState::migrate(raw);
+        assert!(matches!(result, Err(GmailError::StateError(_))));
+    }
+
+    #[test]
+    fn test_processing_state_merge() {
+        let mut a = ProcessingState::new();
+        a.messages_scanned = 100;
+        a.messages_modified = 10;
+        a.labels_created = vec!["Label_1".to_string(), "Label_2".to_string()];
+        a.label_ids_by_name
+            .insert("AutoManaged/Shop".to_string(), "Label_1".to_string());
+        a.filters_created = vec!["f1".to_string()];
+        a.failed_message_ids = vec!["m1".to_string()];
+        a.completed = true;
+
+        let mut b = ProcessingState::new();
+        b.updated_at = a.updated_at + chrono::Duration::hours(1);
+        b.phase = ProcessingPhase::Complete;
+        b.messages_scanned = 50;
+        b.messages_modified = 5;
+        b.labels_created = vec!["Label_2".to_string(), "Label_3".to_string()];
+        b.label_ids_by_name
+            .insert("AutoManaged/Shop".to_string(), "Label_3".to_string());
+        b.filters_created = vec!["f1".to_string(), "f2".to_string()];
+        b.failed_message_ids = vec!["m1".to_string(), "m2".to_string()];
+        b.completed = true;
+
+        assert_eq!(
+            a.label_conflicts(&b),
+            vec![LabelConflict {
+                name: "AutoManaged/Shop".to_string(),
+                id_a: "Label_1".to_string(),
+                id_b: "Label_3".to_string(),
+            }]
+        );
+        assert!(a.label_conflicts(&a.clone()).is_empty());
+
+        let expected_id = format!("{}+{}", a.run_id, b.run_id);
+        let updated_at = b.updated_at;
+        let merged = a.merge(b);
+        assert_eq!(merged.run_id, expected_id);
+        assert_eq!(merged.messages_scanned, 150);
+        assert_eq!(merged.messages_modified, 15);
+        assert_eq!(merged.labels_created, vec!["Label_1", "Label_2", "Label_3"]);
+        assert_eq!(merged.filters_created, vec!["f1", "f2"]);
+        assert_eq!(merged.failed_message_ids, vec!["m1", "m2"]);
+        assert_eq!(merged.label_ids_by_name["AutoManaged/Shop"], "Label_1");
+        assert_eq!(merged.updated_at, updated_at);
+        assert!(matches!(merged.phase, ProcessingPhase::Complete));
+        assert!(merged.completed);
+    }
+
+    #[test]
+    fn test_processing_state_phase_timings() {
+        let mut a = ProcessingState::new();
+        a.record_phase_timing("Apply", Duration::from_secs(67));
+        a.record_phase_timing("Scan", Duration::from_secs(40));
+        a.record_phase_timing("Scan", Duration::from_millis(5_900));
+        a.record_phase_timing("Backup", Duration::from_secs(2));
+        assert_eq!(
+            a.phase_breakdown(),
+            vec![
+                ("Scan".to_string(), 45),
+                ("Apply".to_string(), 67),
+                ("Backup".to_string(), 2),
+            ]
+        );
+
+        let mut b = ProcessingState::new();
+        b.record_phase_timing("Scan", Duration::from_secs(5));
+        let merged = a.merge(b);
+        assert_eq!(merged.phase_timings["Scan"], 50);
+    }
+
+    #[test]
+    fn test_processing_state_export_classifications_csv() {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("classifications.csv");
+
+        let message = MessageMetadata {
+            id: "msg1".to_string(),
+            thread_id: "thread1".to_string(),
+            sender_email: "news@example.com".to_string(),
+            sender_domain: "example.com".to_string(),
+            sender_name: "News".to_string(),
+            subject: "Weekly digest, issue 42".to_string(),
+            recipients: vec![],
+            date_received: Utc::now(),
+            labels: vec![],
+            has_unsubscribe: true,
+            is_automated: true,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 0,
+        };
+        let classification = Classification {
+            message_id: "msg1".to_string(),
+            category: EmailCategory::Newsletter,
+            confidence: 0.9,
+            suggested_label: "auto/newsletters".to_string(),
+            should_archive: true,
+            reasoning: None,
+            reasons: Vec::new(),
+        };
+
+        let mut state = ProcessingState::new();
+        state
+            .classifications
+            .push(SerializedClassification::new(&message, &classification));
+
+        let rows = state.export_classifications_csv(&csv_path).unwrap();
+        assert_eq!(rows, 1);
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let mut lines = content.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "message_id,thread_id,sender_email,sender_domain,subject,date_received,category,suggested_label,should_archive,confidence"
+        );
+        let row = lines.next().unwrap();
+        assert!(row
+            .starts_with("msg1,thread1,news@example.com,example.com,\"Weekly digest, issue 42\""));
+        assert!(row.contains(",Newsletter,auto/newsletters,true,0.9"));
+    }
+
+    #[test]
+    fn test_failed_fetch_backoff() {
+        let error = GmailError::ServerError {
+            status: 503,
+            message: "Service unavailable".to_string(),
+        };
+        let mut entry = FailedFetch::new("msg1".to_string(), &error);
+        assert_eq!(entry.attempts, 0);
+        assert!(entry.next_retry_at > Utc::now());
+
+        let mut previous_delay = entry.next_retry_at - Utc::now();
+        for _ in 0..MAX_FETCH_RETRIES {
+            assert!(!entry.exhausted());
+            entry.record_failure(&GmailError::NetworkError("connection reset".to_string()));
+            let delay = entry.next_retry_at - Utc::now();
+            assert!(delay > previous_delay);
+            previous_delay = delay;
+        }
+        assert!(entry.exhausted());
+        assert_eq!(entry.attempts, MAX_FETCH_RETRIES);
+        assert!(entry.last_error.contains("connection reset"));
+    }
+
+    #[tokio::test]
+    async fn test_processing_state_scan_checkpoint_resume() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.json");
+
+        let message = MessageMetadata {
+            id: "msg1".to_string(),
+            thread_id: "thread1".to_string(),
+            sender_email: "news@example.com".to_string(),
+            sender_domain: "example.com".to_string(),
+            sender_name: "News".to_string(),
+            subject: "Weekly digest".to_string(),
+            recipients: vec![],
+            date_received: Utc::now(),
+            labels: vec!["INBOX".to_string()],
+            has_unsubscribe: true,
+            is_automated: true,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 4096,
+        };
+        let classification = Classification {
+            message_id: "msg1".to_string(),
+            category: EmailCategory::Newsletter,
+            confidence: 0.9,
+            suggested_label: "auto/newsletters".to_string(),
+            should_archive: true,
+            reasoning: Some("List-Unsubscribe header".to_string()),
+            reasons: Vec::new(),
+        };
+
+        let mut state = ProcessingState::new();
+        let mut checkpoint = ScanCheckpoint::new();
+        checkpoint.update(None, Some("msg1".to_string()));
+        state.scan_checkpoint = Some(checkpoint);
+        state
+            .classifications
+            .push(SerializedClassification::new(&message, &classification));
+        state.checkpoint(&state_path).await.unwrap();
+
+        let loaded = ProcessingState::load(&state_path).await.unwrap();
+        let checkpoint = loaded.scan_checkpoint.unwrap();
+        assert_eq!(checkpoint.messages_processed, 1);
+        assert_eq!(checkpoint.last_message_id.as_deref(), Some("msg1"));
+
+        let (restored_message, restored_classification) = loaded.classifications[0].restore();
+        assert_eq!(restored_message.id, message.id);
+        assert_eq!(restored_message.sender_email, message.sender_email);
+        assert_eq!(restored_message.subject, message.subject);
+        assert_eq!(restored_message.sender_name, message.sender_name);
+        assert_eq!(restored_message.labels, message.labels);
+        assert!(restored_message.has_unsubscribe);
+        assert!(restored_message.is_automated);
+        assert_eq!(restored_message.size_bytes, message.size_bytes);
+        assert_eq!(restored_classification.category, EmailCategory::Newsletter);
+        assert!(restored_classification.should_archive);
+        assert_eq!(restored_classification.reasoning, classification.reasoning);
+    }
+
+    #[tokio::test]
+    async fn test_processing_state_load_all() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_file = temp_dir.path().join("state.json");
+
+        let mut older = ProcessingState::new();
+        older.started_at = Utc::now() - chrono::Duration::days(1);
+        older
+            .save(&ProcessingState::run_state_path(&state_file, &older.run_id))
+            .await
+            .unwrap();
+
+        let newer = ProcessingState::new();
+        newer
+            .save(&ProcessingState::run_state_path(&state_file, &newer.run_id))
+            .await
+            .unwrap();
+
+        // The main state file and unrelated files are ignored
+        newer.save(&state_file).await.unwrap();
+        std::fs::write(temp_dir.path().join("exclusions.json"), "[]").unwrap();
+
+        let states = ProcessingState::load_all(temp_dir.path()).await.unwrap();
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].run_id, newer.run_id);
+        assert_eq!(states[1].run_id, older.run_id);
+    }
+
+    #[test]
+    fn test_processing_state_diff() {
+        let mut previous = ProcessingState::new();
+        previous.messages_scanned = 100;
+        previous.filters_created = vec!["f1".to_string(), "f2".to_string()];
+        previous.labels_created = vec!["l1".to_string()];
+        previous.updated_at = previous.started_at + chrono::Duration::seconds(60);
+
+        let mut current = ProcessingState::new();
+        current.messages_scanned = 150;
+        current.filters_created = vec!["f2".to_string(), "f3".to_string()];
+        current.labels_created = vec!["l1".to_string(), "l2".to_string()];
+        current.updated_at = current.started_at + chrono::Duration::seconds(45);
+
+        let diff = current.diff(&previous);
+        assert_eq!(diff.new_filters, vec!["f3".to_string()]);
+        assert_eq!(diff.removed_filters, vec!["f1".to_string()]);
+        assert_eq!(diff.new_labels, vec!["l2".to_string()]);
+        assert!(diff.removed_labels.is_empty());
+        assert_eq!(diff.messages_delta, 50);
+        assert_eq!(diff.duration_delta_secs, -15);
+
+        let md = diff.to_markdown();
+        assert!(md.contains("Changes from Previous Run"));
+        assert!(md.contains("+ f3"));
+        assert!(md.contains("- f1"));
+        assert!(md.contains("+50"));
+
+        assert!(current.diff(&current).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_processing_state_compressed_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.json");
+
+        let mut state = ProcessingState::new();
+        state.messages_scanned = 100;
+        state.failed_message_ids = (0..500).map(|i| format!("msg_{:06}", i)).collect();
+
+        state.save_compressed(&state_path).await.unwrap();
+
+        // File starts with the zstd frame magic
+        let bytes = std::fs::read(&state_path).unwrap();
+        assert!(bytes.starts_with(&ZSTD_MAGIC));
+
+        let loaded = ProcessingState::load_compressed(&state_path).await.unwrap();
+        assert_eq!(loaded.run_id, state.run_id);
+        assert_eq!(loaded.failed_message_ids, state.failed_message_ids);
+    }
+
+    #[tokio::test]
+    async fn test_processing_state_encrypted_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.json");
+        let key = [7u8; 32];
+
+        let mut state = ProcessingState::new().with_encryption_key(Some(key));
+        state.failed_message_ids.push("secret-msg-id".to_string());
+        state.save(&state_path).await.unwrap();
+
+        // Nothing readable on disk
+        let bytes = std::fs::read(&state_path).unwrap();
+        assert!(bytes.starts_with(ENCRYPTED_MARKER));
+        assert!(!String::from_utf8_lossy(&bytes).contains("secret-msg-id"));
+
+        let loaded = ProcessingState::load_encrypted(&state_path, &key)
+            .await
+            .unwrap();
+        assert_eq!(loaded.run_id, state.run_id);
+        assert_eq!(loaded.failed_message_ids, state.failed_message_ids);
+
+        // Auto-detected by load_with_key, also when compressed underneath
+        let compressed = state.with_compression(true);
+        compressed.save(&state_path).await.unwrap();
+        let loaded = ProcessingState::load_with_key(&state_path, Some(&key))
+            .await
+            .unwrap();
+        assert_eq!(loaded.run_id, compressed.run_id);
+        assert!(loaded.compress);
+    }
+
+    #[tokio::test]
+    async fn test_state_manager_writes_in_background() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.json");
+
+        let manager = StateManager::spawn(
+            ProcessingState::new(),
+            state_path.clone(),
+            Duration::from_millis(10),
+        );
+        // Nothing changed yet, so nothing is written
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!state_path.exists());
+
+        manager.update(|s| s.messages_scanned = 42).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let loaded = ProcessingState::load(&state_path).await.unwrap();
+        assert_eq!(loaded.messages_scanned, 42);
+    }
+
+    #[tokio::test]
+    async fn test_state_manager_writes_published_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.json");
+
+        let manager = StateManager::spawn(
+            ProcessingState::new(),
+            state_path.clone(),
+            Duration::from_millis(10),
+        );
+        let mut state = manager.lock().await;
+        state.messages_scanned = 7;
+        // Held between checkpoints, so the writer can't save it yet
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!state_path.exists());
+
+        state.publish().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let loaded = ProcessingState::load(&state_path).await.unwrap();
+        assert_eq!(loaded.messages_scanned, 7);
+    }
+
+    #[tokio::test]
+    async fn test_state_manager_reports_write_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        // A file where the state's directory should be, so every save fails
+        let blocker = temp_dir.path().join("blocker");
+        std::fs::write(&blocker, "").unwrap();
+
+        let manager = StateManager::spawn(
+            ProcessingState::new(),
+            blocker.join("state.json"),
+            Duration::from_millis(10),
+        );
+        manager.update(|s| s.messages_scanned = 1).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(manager.update(|s| s.messages_scanned = 2).await.is_err());
+
+        // Saves keep failing, and checkpoints report it too
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let mut state = manager.lock().await;
+        state.messages_scanned = 3;
+        assert!(state.publish().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_state_manager_flush() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.json");
+
+        let manager = StateManager::spawn(
+            ProcessingState::new(),
+            state_path.clone(),
+            Duration::from_secs(3600),
+        );
+        let mut state = manager.lock().await;
+        state.phase = ProcessingPhase::CreatingFilters;
+        state.record_checkpoint();
+        let run_id = state.run_id.clone();
+        state.flush().await.unwrap();
+
+        let loaded = ProcessingState::load(&state_path).await.unwrap();
+        assert_eq!(loaded.run_id, run_id);
+        assert_eq!(loaded.checkpoint_count, 1);
+        assert!(matches!(loaded.phase, ProcessingPhase::CreatingFilters));
+
+        // Changes made before the guard is dropped are saved by the manager
+        state.messages_scanned = 3;
+        state.publish().await.unwrap();
+        drop(state);
+        manager.flush().await.unwrap();
+        let loaded = ProcessingState::load(&state_path).await.unwrap();
+        assert_eq!(loaded.messages_scanned, 3);
+    }
+
+    #[tokio::test]
+    async fn test_processing_state_encrypted_wrong_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.json");
+
+        let state = ProcessingState::new();
+        state.save_encrypted(&state_path, &[1u8; 32]).await.unwrap();
+
+        let err = ProcessingState::load_encrypted(&state_path, &[2u8; 32])
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Decryption failed: wrong key or corrupted state"));
+
+        // Plain load cannot read an encrypted file
+        assert!(ProcessingState::load(&state_path).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_processing_state_load_auto_detects_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let plain_path = temp_dir.path().join("plain.json");
+        let compressed_path = temp_dir.path().join("compressed.json");
+
+        let mut state = ProcessingState::new();
+        state.messages_scanned = 42;
+        state.save(&plain_path).await.unwrap();
+
+        let compressed_state = state.clone().with_compression(true);
+        compressed_state.save(&compressed_path).await.unwrap();
+
+        let plain = ProcessingState::load(&plain_path).await.unwrap();
+        assert_eq!(plain.messages_scanned, 42);
+        assert!(!plain.compress);
+
+        // Compressed files stay compressed on subsequent saves
+        let compressed = ProcessingState::load(&compressed_path).await.unwrap();
+        assert_eq!(compressed.messages_scanned, 42);
+        assert!(compressed.compress);
+    }
+
+    #[tokio::test]
+    async fn test_processing_state_msgpack_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.msgpack");
+
+        let mut state = ProcessingState::new().with_format(StateFormat::MessagePack);
+        state.phase = ProcessingPhase::ApplyingLabels;
+        state.tags = vec!["weekly".to_string()];
+        state.classifications.push(SerializedClassification {
+            message_id: "m1".to_string(),
+            thread_id: "t1".to_string(),
+            sender_email: "news@example.com".to_string(),
+            sender_domain: "example.com".to_string(),
+            subject: "Digest".to_string(),
+            date_received: Utc::now(),
+            category: EmailCategory::Newsletter,
+            suggested_label: "AutoManaged/Newsletters".to_string(),
+            should_archive: true,
+            confidence: 0.75,
+            sender_name: "Example News".to_string(),
+            recipients: vec!["me@example.com".to_string()],
+            labels: vec!["INBOX".to_string()],
+            has_unsubscribe: true,
+            is_automated: true,
+            spf_pass: Some(true),
+            dkim_pass: None,
+            list_id: Some("news.example.com".to_string()),
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 2048,
+            reasoning: None,
+            reasons: Vec::new(),
+        });
+        state.save(&state_path).await.unwrap();
+
+        let bytes = std::fs::read(&state_path).unwrap();
+        assert!(is_msgpack_map(&bytes));
+
+        let loaded = ProcessingState::load_msgpack(&state_path).await.unwrap();
+        assert_eq!(loaded.run_id, state.run_id);
+        assert_eq!(loaded.tags, state.tags);
+        assert_eq!(loaded.classifications, state.classifications);
+        assert!(matches!(loaded.phase, ProcessingPhase::ApplyingLabels));
+
+        // Auto-detected by load, and the format sticks for later saves
+        let loaded = ProcessingState::load(&state_path).await.unwrap();
+        assert_eq!(loaded.format, StateFormat::MessagePack);
+
+        // Also readable when compressed or encrypted underneath
+        let key = [3u8; 32];
+        let wrapped = state.with_compression(true).with_encryption_key(Some(key));
+        wrapped.save(&state_path).await.unwrap();
+        let loaded = ProcessingState::load_with_key(&state_path, Some(&key))
+            .await
+            .unwrap();
+        assert_eq!(loaded.run_id, wrapped.run_id);
+        assert_eq!(loaded.format, StateFormat::MessagePack);
+        assert!(loaded.compress);
+    }
+
     #[tokio::test]
     async fn test_processing_state_checkpoint() {
         let temp_dir = TempDir::new().unwrap();
@@ -447,6 +2111,11 @@ mod tests {
         state.phase = ProcessingPhase::Scanning;
         state.completed = true;
         assert!(!state.can_resume());
+
+        // ... unless the scan stopped at scan.max_messages
+        state.phase = ProcessingPhase::Complete;
+        state.scan_truncated = true;
+        assert!(state.can_resume());
     }
 
     #[tokio::test]
@@ -0,0 +1,173 @@
+//! Unsubscribing from senders using the `List-Unsubscribe` links found while scanning
+//!
+//! HTTP links are requested with an RFC 8058 one-click POST; `mailto:` links
+//! can't be acted on automatically and are only shown to the user.
+
+use crate::error::{GmailError, Result};
+use crate::models::{UnsubscribeLink, UnsubscribeMethod};
+use hyper::{header, Method, Request};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+/// How long to wait for an unsubscribe endpoint to respond
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Body of an RFC 8058 one-click unsubscribe request
+const ONE_CLICK_BODY: &str = "List-Unsubscribe=One-Click";
+
+/// Links to act on for a set of senders, each URL appearing once
+#[derive(Debug, Default, PartialEq)]
+pub struct UnsubscribePlan {
+    /// `(sender, link)` pairs to request automatically
+    pub http: Vec<(String, UnsubscribeLink)>,
+    /// `(sender, link)` pairs for senders without an HTTP link, to handle by hand
+    pub mailto: Vec<(String, UnsubscribeLink)>,
+}
+
+impl UnsubscribePlan {
+    /// Select the links of senders in `domains` (or their subdomains)
+    ///
+    /// `links` maps sender addresses to their links, as recorded in the state file.
+    pub fn for_domains(links: &BTreeMap<String, Vec<UnsubscribeLink>>, domains: &[String]) -> Self {
+        let domains: Vec<String> = domains.iter().map(|d| d.to_lowercase()).collect();
+        let mut seen = HashSet::new();
+        let mut plan = Self::default();
+
+        for (sender, sender_links) in links {
+            let sender_domain = sender
+                .rsplit_once('@')
+                .map(|(_, domain)| domain.to_lowercase())
+                .unwrap_or_default();
+            let matches = domains.iter().any(|domain| {
+                sender_domain == *domain || sender_domain.ends_with(&format!(".{}", domain))
+            });
+            if !matches {
+                continue;
+            }
+
+            let has_http = sender_links
+                .iter()
+                .any(|link| link.method == UnsubscribeMethod::Http);
+            for link in sender_links {
+                if !seen.insert(link.url.clone()) {
+                    continue;
+                }
+                match link.method {
+                    UnsubscribeMethod::Http => plan.http.push((sender.clone(), link.clone())),
+                    UnsubscribeMethod::Mailto if !has_http => {
+                        plan.mailto.push((sender.clone(), link.clone()))
+                    }
+                    UnsubscribeMethod::Mailto => {}
+                }
+            }
+        }
+
+        plan
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.http.is_empty() && self.mailto.is_empty()
+    }
+}
+
+/// Send a one-click unsubscribe request to `url`, returning the HTTP status
+///
+/// Redirects count as success since many endpoints answer with a confirmation page.
+pub async fn unsubscribe_http(url: &str) -> Result<u16> {
+    let connector = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_native_roots()
+        .map_err(|e| GmailError::NetworkError(format!("Failed to load TLS roots: {}", e)))?
+        .https_or_http()
+        .enable_http1()
+        .build();
+    let client = hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
+        .build(connector);
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(url)
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(ONE_CLICK_BODY.to_string())
+        .map_err(|e| GmailError::BadRequest(format!("Invalid unsubscribe URL {}: {}", url, e)))?;
+
+    let response = tokio::time::timeout(REQUEST_TIMEOUT, client.request(request))
+        .await
+        .map_err(|_| GmailError::NetworkError(format!("Timed out requesting {}", url)))?
+        .map_err(|e| GmailError::NetworkError(e.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() || status.is_redirection() {
+        Ok(status.as_u16())
+    } else {
+        Err(GmailError::ServerError {
+            status: status.as_u16(),
+            message: format!("Unsubscribe request to {} was rejected", url),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(url: &str) -> UnsubscribeLink {
+        let method = if url.starts_with("mailto:") {
+            UnsubscribeMethod::Mailto
+        } else {
+            UnsubscribeMethod::Http
+        };
+        UnsubscribeLink {
+            url: url.to_string(),
+            method,
+        }
+    }
+
+    #[test]
+    fn test_plan_for_domains() {
+        let links = BTreeMap::from([
+            (
+                "news@shop.example".to_string(),
+                vec![
+                    link("https://shop.example/u?id=1"),
+                    link("mailto:leave@shop.example"),
+                ],
+            ),
+            (
+                "deals@mail.shop.example".to_string(),
+                // Same endpoint as above, only requested once
+                vec![link("https://shop.example/u?id=1")],
+            ),
+            (
+                "list@lists.example.org".to_string(),
+                vec![link("mailto:leave@lists.example.org")],
+            ),
+            (
+                "friend@other.example".to_string(),
+                vec![link("https://other.example/u")],
+            ),
+        ]);
+
+        let plan = UnsubscribePlan::for_domains(
+            &links,
+            &["Shop.example".to_string(), "example.org".to_string()],
+        );
+
+        assert_eq!(
+            plan.http,
+            vec![(
+                "deals@mail.shop.example".to_string(),
+                link("https://shop.example/u?id=1")
+            )]
+        );
+        // The shop's mailto link is redundant next to its HTTP link
+        assert_eq!(
+            plan.mailto,
+            vec![(
+                "list@lists.example.org".to_string(),
+                link("mailto:leave@lists.example.org")
+            )]
+        );
+
+        assert!(UnsubscribePlan::for_domains(&links, &["nowhere.test".to_string()]).is_empty());
+    }
+}
@@ -1,42 +1,36 @@
 //! Common test utilities and fixtures
 
-use chrono::Utc;
 use gmail_automation::client::GmailClient;
 use gmail_automation::error::Result;
-use gmail_automation::models::{EmailCategory, FilterRule, MessageMetadata};
+use gmail_automation::models::{
+    EmailCategory, EmailThread, FilterRule, MessageMetadata, MessageMetadataBuilder,
+};
 use mockall::mock;
 use mockall::predicate::*;
 use serde_json::json;
 
 /// Create a test message with default values
 pub fn create_test_message(id: &str, sender: &str, subject: &str) -> MessageMetadata {
-    let domain = sender
-        .split('@')
-        .nth(1)
-        .unwrap_or("example.com")
-        .to_string();
-
-    MessageMetadata {
-        id: id.to_string(),
-        thread_id: format!("thread_{}", id),
-        sender_email: sender.to_string(),
-        sender_domain: domain,
-        sender_name: "Test Sender".to_string(),
-        subject: subject.to_string(),
-        recipients: vec!["me@example.com".to_string()],
-        date_received: Utc::now(),
-        labels: vec!["INBOX".to_string()],
-        has_unsubscribe: false,
-        is_automated: false,
-    }
+    MessageMetadataBuilder::new(id)
+        .sender(sender)
+        .sender_name("Test Sender")
+        .subject(subject)
+        .recipients(&["me@example.com"])
+        .labels(&["INBOX"])
+        .build()
 }
 
 /// Create a test message with automated sender
 pub fn create_automated_message(id: &str, sender: &str, subject: &str) -> MessageMetadata {
-    let mut message = create_test_message(id, sender, subject);
-    message.is_automated = true;
-    message.has_unsubscribe = true;
-    message
+    MessageMetadataBuilder::new(id)
+        .sender(sender)
+        .sender_name("Test Sender")
+        .subject(subject)
+        .recipients(&["me@example.com"])
+        .labels(&["INBOX"])
+        .unsubscribe(true)
+        .automated(true)
+        .build()
 }
 
 /// Create a newsletter message
@@ -59,10 +53,21 @@ pub fn category_to_label(category: &EmailCategory) -> String {
         EmailCategory::Shipping => "Shipping".to_string(),
         EmailCategory::Financial => "Financial".to_string(),
         EmailCategory::Personal => "Personal".to_string(),
+        EmailCategory::AutoReply => "AutoReplies".to_string(),
+        EmailCategory::Bounce => "Bounces".to_string(),
+        EmailCategory::CalendarInvite => "Calendar".to_string(),
         EmailCategory::Other => "Other".to_string(),
     }
 }
 
+/// Create a test EmailThread from messages, rewriting their thread IDs to match
+pub fn create_test_thread(thread_id: &str, mut messages: Vec<MessageMetadata>) -> EmailThread {
+    for msg in &mut messages {
+        msg.thread_id = thread_id.to_string();
+    }
+    EmailThread::from_messages(thread_id.to_string(), messages).expect("thread has messages")
+}
+
 /// Create mock Gmail API message response (JSON)
 pub fn mock_gmail_message_response(
     id: &str,
@@ -121,6 +126,7 @@ pub fn create_test_label_info(id: &str, name: &str) -> gmail_automation::client:
     gmail_automation::client::LabelInfo {
         id: id.to_string(),
         name: name.to_string(),
+        color: None,
     }
 }
 
@@ -149,23 +155,27 @@ mock! {
     impl GmailClient for GmailClient {
         async fn list_message_ids(&self, query: &str) -> Result<Vec<String>>;
         async fn get_message(&self, id: &str) -> Result<MessageMetadata>;
+        async fn list_thread_ids(&self, query: &str) -> Result<Vec<String>>;
+        async fn get_thread(&self, thread_id: &str) -> Result<EmailThread>;
         async fn list_labels(&self) -> Result<Vec<gmail_automation::client::LabelInfo>>;
-        async fn create_label(&self, name: &str) -> Result<String>;
+        async fn create_label(&self, name: &str) -> Result<gmail_automation::models::LabelId>;
+        async fn create_label_with_visibility(&self, name: &str, visibility: &gmail_automation::config::LabelVisibility) -> Result<gmail_automation::models::LabelId>;
+        async fn update_label_visibility(&self, label_id: &str, visibility: &gmail_automation::config::LabelVisibility) -> Result<()>;
         async fn delete_label(&self, label_id: &str) -> Result<()>;
         async fn create_filter(&self, filter: &FilterRule) -> Result<String>;
         async fn list_filters(&self) -> Result<Vec<gmail_automation::client::ExistingFilterInfo>>;
         async fn delete_filter(&self, filter_id: &str) -> Result<()>;
         async fn update_filter(&self, filter_id: &str, filter: &FilterRule) -> Result<String>;
-        async fn apply_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-        async fn remove_label(&self, message_id: &str, label_id: &str) -> Result<()>;
-        async fn batch_remove_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
-        async fn batch_add_label(&self, message_ids: &[String], label_id: &str) -> Result<usize>;
+        async fn apply_label(&self, message_id: &str, label_id: &gmail_automation::models::LabelId) -> Result<()>;
+        async fn remove_label(&self, message_id: &str, label_id: &gmail_automation::models::LabelId) -> Result<()>;
+        async fn batch_remove_label(&self, message_ids: &[String], label_id: &gmail_automation::models::LabelId) -> Result<usize>;
+        async fn batch_add_label(&self, message_ids: &[String], label_id: &gmail_automation::models::LabelId) -> Result<usize>;
         async fn batch_modify_labels(
             &self,
             message_ids: &[String],
-            add_label_ids: &[String],
-            remove_label_ids: &[String],
-        ) -> Result<usize>;
+            add_label_ids: &[gmail_automation::models::LabelId],
+            remove_label_ids: &[gmail_automation::models::LabelId],
+        ) -> Result<gmail_automation::client::BatchModifyResult>;
         async fn fetch_messages_batch(&self, message_ids: Vec<String>) -> Result<Vec<MessageMetadata>>;
         async fn fetch_messages_with_progress(
             &self,
@@ -203,6 +213,21 @@ mod tests {
         assert!(msg.subject.contains("Receipt"));
     }
 
+    #[test]
+    fn test_create_test_thread() {
+        let thread = create_test_thread(
+            "thread1",
+            vec![
+                create_test_message("msg1", "alice@example.com", "Hello"),
+                create_test_message("msg2", "bob@example.com", "Re: Hello"),
+            ],
+        );
+        assert_eq!(thread.thread_id, "thread1");
+        assert_eq!(thread.messages.len(), 2);
+        assert!(thread.messages.iter().all(|m| m.thread_id == "thread1"));
+        assert_eq!(thread.participants.len(), 2);
+    }
+
     #[test]
     fn test_mock_gmail_message_response() {
         let response =
@@ -0,0 +1,110 @@
+//! Property-based tests for Gmail query generation
+//!
+//! Generates arbitrary filter rules and checks invariants of
+//! `FilterManager::build_gmail_query_static` that targeted unit tests can miss,
+//! such as empty-looking patterns, special characters and Unicode subjects.
+
+use gmail_automation::filter_manager::FilterManager;
+use gmail_automation::models::{FilterRule, LabelName, DEFAULT_FILTER_PRIORITY};
+use proptest::prelude::*;
+
+/// Sender patterns and excluded addresses
+fn pattern() -> impl Strategy<Value = String> {
+    proptest::string::string_regex("[a-zA-Z0-9._@*-]{1,64}").unwrap()
+}
+
+/// Subject keywords, including non-ASCII letters, spaces, parentheses and quotes
+fn subject_keyword() -> impl Strategy<Value = String> {
+    proptest::string::string_regex("[\\p{L}0-9 ._@*()\"-]{1,32}").unwrap()
+}
+
+/// Filter rules with a sender pattern or at least one subject keyword
+fn filter_rule() -> impl Strategy<Value = FilterRule> {
+    (
+        proptest::option::of(pattern()),
+        any::<bool>(),
+        proptest::collection::vec(pattern(), 0..4),
+        proptest::collection::vec(subject_keyword(), 0..4),
+        any::<bool>(),
+    )
+        .prop_filter(
+            "needs a sender pattern or subject keywords",
+            |(from_pattern, _, _, subject_keywords, _)| {
+                from_pattern.is_some() || !subject_keywords.is_empty()
+            },
+        )
+        .prop_map(
+            |(from_pattern, is_specific_sender, excluded_senders, subject_keywords, archive)| {
+                FilterRule {
+                    id: None,
+                    name: "proptest".to_string(),
+                    from_pattern,
+                    is_specific_sender,
+                    excluded_senders,
+                    subject_keywords,
+                    target_label: LabelName::new("AutoManaged/Proptest"),
+                    target_label_id: None,
+                    should_archive: archive,
+                    estimated_matches: 0,
+                    priority: DEFAULT_FILTER_PRIORITY,
+                    extra_terms: vec![],
+                    additional_labels: vec![],
+                    additional_label_ids: vec![],
+                }
+            },
+        )
+}
+
+/// Parentheses outside quoted phrases are balanced and every quote is closed
+fn parentheses_balanced(query: &str) -> bool {
+    let mut depth = 0i32;
+    let mut in_quotes = false;
+    for c in query.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '(' if !in_quotes => depth += 1,
+            ')' if !in_quotes => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            return false;
+        }
+    }
+    depth == 0 && !in_quotes
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+
+    #[test]
+    fn query_parentheses_are_balanced(rule in filter_rule()) {
+        let query = FilterManager::build_gmail_query_static(&rule);
+        prop_assert!(parentheses_balanced(&query), "unbalanced query: {}", query);
+    }
+
+    #[test]
+    fn query_excludes_senders_only_when_listed(rule in filter_rule()) {
+        let query = FilterManager::build_gmail_query_static(&rule);
+        if rule.excluded_senders.is_empty() {
+            prop_assert!(!query.contains("-from:("), "unexpected exclusion: {}", query);
+        }
+    }
+
+    #[test]
+    fn query_is_not_empty(rule in filter_rule()) {
+        let query = FilterManager::build_gmail_query_static(&rule);
+        prop_assert!(!query.trim().is_empty());
+    }
+
+    #[test]
+    fn query_contains_excluded_senders_verbatim(rule in filter_rule()) {
+        let query = FilterManager::build_gmail_query_static(&rule);
+        // Exclusions only narrow domain-wide filters; a specific sender has nothing to exclude
+        if rule.from_pattern.is_some() && !rule.is_specific_sender {
+            for excluded in &rule.excluded_senders {
+                let term = format!("-from:({})", excluded);
+                prop_assert!(query.contains(&term), "{} missing from {}", term, query);
+            }
+        }
+    }
+}
@@ -3,6 +3,8 @@
 //! This file demonstrates how to use the mock email generator utilities
 //! for performance testing.
 
+// Shared helpers are only partially used by this test binary
+#[allow(dead_code)]
 mod performance;
 
 use performance::mock_generator::*;
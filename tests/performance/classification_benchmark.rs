@@ -316,3 +316,61 @@ fn test_classification_consistency() {
 
     println!("  Status: PASS - Classifier is deterministic");
 }
+
+#[test]
+#[serial]
+fn test_subject_keyword_matching_vs_naive_contains_50k() {
+    use gmail_automation::classifier::DEFAULT_SUBJECT_KEYWORDS;
+
+    println!("\n=== Subject Keyword Matching: Aho-Corasick vs naive contains ===");
+
+    let messages = generate_mock_emails(50_000);
+    let classifier = EmailClassifier::new("auto".to_string());
+
+    // Naive baseline: lowercase each subject and test every keyword separately
+    let start = Instant::now();
+    let naive: Vec<_> = messages
+        .iter()
+        .map(|msg| {
+            let subject = msg.subject.to_lowercase();
+            DEFAULT_SUBJECT_KEYWORDS
+                .iter()
+                .filter(|(_, keywords)| keywords.iter().any(|k| subject.contains(k)))
+                .map(|(category, _)| category.clone())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let naive_duration = start.elapsed();
+
+    let start = Instant::now();
+    let automaton: Vec<_> = messages
+        .iter()
+        .map(|msg| classifier.subject_categories(&msg.subject))
+        .collect();
+    let automaton_duration = start.elapsed();
+
+    let speedup = naive_duration.as_secs_f64() / automaton_duration.as_secs_f64();
+    println!(
+        "  Naive contains: {}",
+        format_benchmark_result(messages.len(), naive_duration)
+    );
+    println!(
+        "  Aho-Corasick:   {}",
+        format_benchmark_result(messages.len(), automaton_duration)
+    );
+    println!("  Speedup: {:.1}x", speedup);
+
+    assert_eq!(
+        naive, automaton,
+        "Both matchers must find the same categories"
+    );
+
+    // Unoptimized builds compile the automaton without optimizations, so only
+    // hold release builds to the speedup target
+    #[cfg(not(debug_assertions))]
+    assert!(
+        speedup >= 10.0,
+        "Aho-Corasick matching should be at least 10x faster, got {:.1}x",
+        speedup
+    );
+}
@@ -0,0 +1,75 @@
+//! Header parsing allocation benchmarks
+//!
+//! Compares building an owned `HashMap<String, String>` per message against the
+//! borrowed map from `scanner::parse_headers_zero_copy`.
+
+use gmail_automation::scanner::{extract_message_metadata, parse_headers_zero_copy};
+use serde_json::{json, Value};
+use serial_test::serial;
+use std::collections::HashMap;
+use std::hint::black_box;
+
+use super::memory_tracking_allocator::allocation_count;
+
+/// Build `payload.headers` arrays shaped like Gmail metadata responses
+fn generate_raw_headers(count: usize) -> Vec<Vec<Value>> {
+    (0..count)
+        .map(|i| {
+            vec![
+                json!({"name": "From", "value": format!("Sender {} <news{}@example.com>", i, i % 50)}),
+                json!({"name": "To", "value": "me@example.com"}),
+                json!({"name": "Subject", "value": format!("Weekly digest #{}", i)}),
+                json!({"name": "Date", "value": "Tue, 1 Jul 2025 10:00:00 +0000"}),
+                json!({"name": "List-Unsubscribe", "value": "<mailto:unsubscribe@example.com>"}),
+                json!({"name": "Message-ID", "value": format!("<{}@example.com>", i)}),
+                json!({"name": "Content-Type", "value": "text/html; charset=UTF-8"}),
+            ]
+        })
+        .collect()
+}
+
+#[test]
+#[serial]
+fn test_header_map_allocations_50k() {
+    println!("\n=== Header Parsing: owned map vs zero-copy map ===");
+
+    let raw = generate_raw_headers(50_000);
+
+    let before = allocation_count();
+    for headers in &raw {
+        let owned: HashMap<String, String> = headers
+            .iter()
+            .filter_map(|h| {
+                Some((
+                    h["name"].as_str()?.to_string(),
+                    h["value"].as_str()?.to_string(),
+                ))
+            })
+            .collect();
+        black_box(owned);
+    }
+    let owned_allocations = allocation_count() - before;
+
+    let before = allocation_count();
+    for headers in &raw {
+        black_box(parse_headers_zero_copy(headers));
+    }
+    let zero_copy_allocations = allocation_count() - before;
+
+    println!("  Owned map:     {} allocations", owned_allocations);
+    println!("  Zero-copy map: {} allocations", zero_copy_allocations);
+
+    // One allocation for the table itself versus one per header name and value
+    assert!(
+        zero_copy_allocations * 5 < owned_allocations,
+        "Zero-copy parsing should allocate far less: {} vs {}",
+        zero_copy_allocations,
+        owned_allocations
+    );
+
+    // The borrowed map still feeds metadata extraction
+    let headers = parse_headers_zero_copy(&raw[0]);
+    let msg = extract_message_metadata("m0".into(), "t0".into(), vec![], &headers);
+    assert_eq!(msg.sender_domain, "example.com");
+    assert!(msg.has_unsubscribe);
+}
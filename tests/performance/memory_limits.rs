@@ -455,11 +455,11 @@ mod tests {
         // - DateTime (12 bytes)
         // - 2 bools (2 bytes)
         // 512 bytes should be a safe upper bound
-        assert!(
+        const _: () = assert!(
             ESTIMATED_BYTES_PER_MESSAGE >= 256,
             "Estimate should account for heap allocations"
         );
-        assert!(
+        const _: () = assert!(
             ESTIMATED_BYTES_PER_MESSAGE <= 1024,
             "Estimate should not be unreasonably high"
         );
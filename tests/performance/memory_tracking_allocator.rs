@@ -15,12 +15,16 @@ static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
 /// Peak allocated memory in bytes
 static PEAK: AtomicUsize = AtomicUsize::new(0);
 
+/// Number of allocation calls since startup
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
 /// Memory tracking allocator
 pub struct TrackingAllocator;
 
 unsafe impl GlobalAlloc for TrackingAllocator {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
         let size = layout.size();
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
         let old = ALLOCATED.fetch_add(size, Ordering::SeqCst);
         let new = old + size;
 
@@ -146,6 +150,11 @@ pub fn peak_allocated() -> usize {
     PEAK.load(Ordering::SeqCst)
 }
 
+/// Get the number of allocation calls since startup
+pub fn allocation_count() -> usize {
+    ALLOCATIONS.load(Ordering::SeqCst)
+}
+
 /// Print current memory stats
 pub fn print_memory_stats(label: &str) {
     let current = current_allocated();
@@ -355,7 +355,7 @@ mod tests {
             );
 
             // Check if within claimed typical range
-            if total_mb >= 50.0 && total_mb <= 100.0 {
+            if (50.0..=100.0).contains(&total_mb) {
                 println!(
                     "\nSUCCESS: Memory usage is within README claimed typical range (50-100 MB)"
                 );
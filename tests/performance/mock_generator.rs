@@ -172,6 +172,15 @@ impl MockGenerator {
             labels,
             has_unsubscribe,
             is_automated,
+            spf_pass: None,
+            dkim_pass: None,
+            list_id: None,
+            unsubscribe_links: Vec::new(),
+            is_reply: false,
+            auto_submitted: None,
+            is_bounce: false,
+            attachment_types: Vec::new(),
+            size_bytes: 0,
         }
     }
 
@@ -297,6 +306,9 @@ impl MockGenerator {
                 "emma.taylor",
                 "frank.white",
             ],
+            EmailCategory::AutoReply => vec!["jane.doe", "john.smith", "alice.brown"],
+            EmailCategory::Bounce => vec!["mailer-daemon", "postmaster"],
+            EmailCategory::CalendarInvite => vec!["calendar-notification", "calendar"],
             EmailCategory::Other => vec!["info", "contact", "support", "help", "service"],
         };
 
@@ -391,6 +403,21 @@ impl MockGenerator {
                 "Can we chat?",
                 "Question for you",
             ],
+            EmailCategory::AutoReply => vec![
+                "Automatic reply: Meeting tomorrow",
+                "Out of office: Re: Project update",
+                "Auto: Quick question",
+            ],
+            EmailCategory::Bounce => vec![
+                "Delivery Status Notification (Failure)",
+                "Undeliverable: Meeting tomorrow",
+                "Mail delivery failed to jane@example.com",
+            ],
+            EmailCategory::CalendarInvite => vec![
+                "Invitation: Sprint planning @ Mon 10am",
+                "Updated invitation: Design review",
+                "Accepted: Quarterly sync",
+            ],
             EmailCategory::Other => vec![
                 "Information Request",
                 "General Inquiry",
@@ -457,6 +484,8 @@ impl MockGenerator {
     fn generate_automated_flags(&mut self, category: &EmailCategory) -> (bool, bool) {
         match category {
             EmailCategory::Personal => (false, false),
+            EmailCategory::AutoReply | EmailCategory::Bounce => (false, true),
+            EmailCategory::CalendarInvite => (false, true),
             EmailCategory::Newsletter => (true, true),
             EmailCategory::Marketing => (true, true),
             EmailCategory::Receipt => (false, true),
@@ -23,6 +23,9 @@ static ALLOCATOR: memory_tracking_allocator::TrackingAllocator =
 #[cfg(test)]
 mod classification_benchmark;
 
+#[cfg(test)]
+mod header_parsing_benchmark;
+
 #[cfg(test)]
 mod memory_usage_test;
 
@@ -394,6 +397,15 @@ fn generate_message(
         labels: vec!["INBOX".to_string()],
         has_unsubscribe: category.should_have_unsubscribe() && rng.gen_bool(0.8),
         is_automated: category.is_automated(),
+        spf_pass: None,
+        dkim_pass: None,
+        list_id: None,
+        unsubscribe_links: Vec::new(),
+        is_reply: false,
+        auto_submitted: None,
+        is_bounce: false,
+        attachment_types: Vec::new(),
+        size_bytes: 0,
     }
 }
 
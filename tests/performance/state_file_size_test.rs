@@ -341,6 +341,162 @@ async fn test_state_file_compression_info() {
     println!("      However, readability is prioritized for debugging purposes.");
 }
 
+/// Benchmark save/load times for a ~10 MB state in compressed vs uncompressed mode
+#[tokio::test]
+#[serial]
+async fn test_state_file_compression_benchmark() {
+    use std::time::Instant;
+
+    let temp_dir = TempDir::new().unwrap();
+    let plain_path = temp_dir.path().join("state_plain.json");
+    let compressed_path = temp_dir.path().join("state_compressed.json");
+
+    // ~350k failed IDs produce a pretty-printed state of roughly 10 MB
+    let mut state = ProcessingState::new();
+    populate_state_with_custom_data(&mut state, 100_000, 500, 250, 350_000);
+
+    let start = Instant::now();
+    state.save(&plain_path).await.unwrap();
+    let plain_save = start.elapsed();
+
+    let start = Instant::now();
+    state.save_compressed(&compressed_path).await.unwrap();
+    let compressed_save = start.elapsed();
+
+    let start = Instant::now();
+    let plain_loaded = ProcessingState::load(&plain_path).await.unwrap();
+    let plain_load = start.elapsed();
+
+    let start = Instant::now();
+    let compressed_loaded = ProcessingState::load(&compressed_path).await.unwrap();
+    let compressed_load = start.elapsed();
+
+    let plain_size = std::fs::metadata(&plain_path).unwrap().len();
+    let compressed_size = std::fs::metadata(&compressed_path).unwrap().len();
+
+    println!("\n=== State File Compression Benchmark ===");
+    println!(
+        "{:<14} | {:<15} | {:<12} | {:<12}",
+        "Mode", "File Size (KB)", "Save (ms)", "Load (ms)"
+    );
+    println!("{}", "-".repeat(60));
+    println!(
+        "{:<14} | {:<15.2} | {:<12} | {:<12}",
+        "uncompressed",
+        plain_size as f64 / 1024.0,
+        plain_save.as_millis(),
+        plain_load.as_millis()
+    );
+    println!(
+        "{:<14} | {:<15.2} | {:<12} | {:<12}",
+        "zstd",
+        compressed_size as f64 / 1024.0,
+        compressed_save.as_millis(),
+        compressed_load.as_millis()
+    );
+    println!(
+        "\nCompression ratio: {:.1}x",
+        plain_size as f64 / compressed_size as f64
+    );
+
+    assert_eq!(
+        plain_loaded.failed_message_ids.len(),
+        compressed_loaded.failed_message_ids.len()
+    );
+    assert!(
+        compressed_size < plain_size / 4,
+        "Compressed state should be at least 4x smaller ({} vs {} bytes)",
+        compressed_size,
+        plain_size
+    );
+}
+
+/// Benchmark JSON vs MessagePack save/load for a realistic 50K-message state
+#[tokio::test]
+#[serial]
+async fn test_state_format_json_vs_msgpack_benchmark() {
+    use gmail_automation::config::StateFormat;
+    use gmail_automation::models::{Classification, EmailCategory};
+    use gmail_automation::state::SerializedClassification;
+    use std::time::Instant;
+
+    let temp_dir = TempDir::new().unwrap();
+    let json_path = temp_dir.path().join("state.json");
+    let msgpack_path = temp_dir.path().join("state.msgpack");
+
+    let messages = super::mock_generator::generate_mock_emails_with_seed(50_000, 42);
+    let mut state = ProcessingState::new();
+    populate_state_for_messages(&mut state, messages.len());
+    state.classifications = messages
+        .iter()
+        .map(|msg| {
+            let classification = Classification {
+                message_id: msg.id.clone(),
+                category: EmailCategory::Newsletter,
+                confidence: 0.9,
+                suggested_label: format!("AutoManaged/Newsletters/{}", msg.sender_domain),
+                should_archive: true,
+                reasoning: None,
+                reasons: Vec::new(),
+            };
+            SerializedClassification::new(msg, &classification)
+        })
+        .collect();
+
+    let start = Instant::now();
+    state.save(&json_path).await.unwrap();
+    let json_save = start.elapsed();
+
+    let msgpack_state = state.clone().with_format(StateFormat::MessagePack);
+    let start = Instant::now();
+    msgpack_state.save(&msgpack_path).await.unwrap();
+    let msgpack_save = start.elapsed();
+
+    let start = Instant::now();
+    let json_loaded = ProcessingState::load(&json_path).await.unwrap();
+    let json_load = start.elapsed();
+
+    let start = Instant::now();
+    let msgpack_loaded = ProcessingState::load(&msgpack_path).await.unwrap();
+    let msgpack_load = start.elapsed();
+
+    let json_size = std::fs::metadata(&json_path).unwrap().len();
+    let msgpack_size = std::fs::metadata(&msgpack_path).unwrap().len();
+
+    println!(
+        "\n=== State Format Benchmark: {} Messages ===",
+        messages.len()
+    );
+    println!(
+        "{:<14} | {:<15} | {:<12} | {:<12}",
+        "Format", "File Size (KB)", "Save (ms)", "Load (ms)"
+    );
+    println!("{}", "-".repeat(60));
+    println!(
+        "{:<14} | {:<15.2} | {:<12} | {:<12}",
+        "json",
+        json_size as f64 / 1024.0,
+        json_save.as_millis(),
+        json_load.as_millis()
+    );
+    println!(
+        "{:<14} | {:<15.2} | {:<12} | {:<12}",
+        "messagepack",
+        msgpack_size as f64 / 1024.0,
+        msgpack_save.as_millis(),
+        msgpack_load.as_millis()
+    );
+
+    assert_eq!(msgpack_loaded.format, StateFormat::MessagePack);
+    assert_eq!(json_loaded.classifications, msgpack_loaded.classifications);
+    assert!(
+        msgpack_size < json_size,
+        "MessagePack state should be smaller than pretty JSON ({} vs {} bytes)",
+        msgpack_size,
+        json_size
+    );
+}
+
 /// Populate state with data simulating processing of N messages
 fn populate_state_for_messages(state: &mut ProcessingState, message_count: usize) {
     use gmail_automation::state::ProcessingPhase;
@@ -2,6 +2,8 @@
 //!
 //! This file validates the README claim: "State file: ~1 KB per 1,000 messages"
 
+// Shared helpers are only partially used by this test binary
+#[allow(dead_code)]
 mod performance;
 
 // Re-export the tests from the performance module